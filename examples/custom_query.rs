@@ -0,0 +1,152 @@
+// # Custom query example
+//
+// This example shows how you can implement your own
+// query. As an example, we will write a query that
+// decays the score of the documents of another query
+// with their age, read from a fast field.
+//
+// A query is made of three layers:
+// - the `Query` describes the documents to match and how to score them.
+// - the `Weight` is the query tied to a `Searcher`. It typically holds
+// the statistics of the whole index, and creates a `Scorer` for each segment.
+// - the `Scorer` iterates over the matching documents of a segment,
+// and computes their score.
+
+// ---
+// Importing tantivy...
+#[macro_use]
+extern crate tantivy;
+use std::fmt;
+use tantivy::collector::TopDocs;
+use tantivy::fastfield::FastFieldReader;
+use tantivy::query::{Query, QueryParser, Scorer, Weight};
+use tantivy::schema::{Field, Schema, FAST, TEXT};
+use tantivy::{DocId, DocSet, Index, Score, Searcher, SegmentReader, SkipResult};
+
+/// Multiplies the score of the documents of `query` by
+/// `1 / (1 + age / half_life)`.
+struct AgeDecayQuery {
+    query: Box<Query>,
+    age_field: Field,
+    half_life: f32,
+}
+
+// Queries are cloned via `box_clone`.
+impl Clone for AgeDecayQuery {
+    fn clone(&self) -> Self {
+        AgeDecayQuery {
+            query: self.query.box_clone(),
+            age_field: self.age_field,
+            half_life: self.half_life,
+        }
+    }
+}
+
+impl fmt::Debug for AgeDecayQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "AgeDecay(query={:?}, half_life={})",
+            self.query, self.half_life
+        )
+    }
+}
+
+impl Query for AgeDecayQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> tantivy::Result<Box<Weight>> {
+        // the weight of the wrapped query does the heavy lifting.
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(AgeDecayWeight {
+            weight,
+            age_field: self.age_field,
+            half_life: self.half_life,
+        }))
+    }
+}
+
+struct AgeDecayWeight {
+    weight: Box<Weight>,
+    age_field: Field,
+    half_life: f32,
+}
+
+impl Weight for AgeDecayWeight {
+    fn scorer(&self, reader: &SegmentReader) -> tantivy::Result<Box<Scorer>> {
+        Ok(Box::new(AgeDecayScorer {
+            scorer: self.weight.scorer(reader)?,
+            age_reader: reader.fast_field_reader(self.age_field)?,
+            half_life: self.half_life,
+        }))
+    }
+}
+
+struct AgeDecayScorer {
+    scorer: Box<Scorer>,
+    age_reader: FastFieldReader<u64>,
+    half_life: f32,
+}
+
+// A `Scorer` is a `DocSet`, that is a cursor over the matching documents...
+impl DocSet for AgeDecayScorer {
+    fn advance(&mut self) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+// ... that can compute the score of the current document.
+impl Scorer for AgeDecayScorer {
+    fn score(&mut self) -> Score {
+        let age = self.age_reader.get(self.doc()) as f32;
+        self.scorer.score() / (1f32 + age / self.half_life)
+    }
+}
+
+fn main() -> tantivy::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let age_days = schema_builder.add_u64_field("age_days", FAST);
+    let schema = schema_builder.build();
+
+    let index = Index::create_in_ram(schema);
+    let mut index_writer = index.writer(50_000_000)?;
+    index_writer.add_document(doc!(
+        title => "The old man and the sea",
+        age_days => 3_000u64
+    ))?;
+    index_writer.add_document(doc!(
+        title => "The sea, the sea",
+        age_days => 10u64
+    ))?;
+    index_writer.add_document(doc!(
+        title => "Twenty thousand leagues under the sea",
+        age_days => 100u64
+    ))?;
+    index_writer.commit()?;
+    index.load_searchers()?;
+
+    let searcher = index.searcher();
+    let query_parser = QueryParser::for_index(&index, vec![title]);
+    let query = AgeDecayQuery {
+        query: query_parser.parse_query("sea")?,
+        age_field: age_days,
+        half_life: 365f32,
+    };
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+    for (score, doc_address) in top_docs {
+        let retrieved_doc = searcher.doc(doc_address)?;
+        println!("{} {:?}", score, retrieved_doc);
+    }
+    Ok(())
+}
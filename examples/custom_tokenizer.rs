@@ -71,7 +71,7 @@ fn main() -> tantivy::Result<()> {
     title => "The Old Man and the Sea",
     body => "He was an old man who fished alone in a skiff in the Gulf Stream and \
      he had gone eighty-four days now without taking a fish."
-    ));
+    ))?;
     index_writer.add_document(doc!(
     title => "Of Mice and Men",
        body => r#"A few miles south of Soledad, the Salinas River drops in close to the hillside
@@ -82,14 +82,14 @@ fn main() -> tantivy::Result<()> {
                 fresh and green with every spring, carrying in their lower leaf junctures the
                 debris of the winter’s flooding; and sycamores with mottled, white, recumbent
                 limbs and branches that arch over the pool"#
-    ));
+    ))?;
     index_writer.add_document(doc!(
     title => "Frankenstein",
         body => r#"You will rejoice to hear that no disaster has accompanied the commencement of an
                 enterprise which you have regarded with such evil forebodings.  I arrived here
                 yesterday, and my first task is to assure my dear sister of my welfare and
                 increasing confidence in the success of my undertaking."#
-    ));
+    ))?;
     index_writer.commit()?;
     index.load_searchers()?;
 
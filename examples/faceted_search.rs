@@ -46,12 +46,12 @@ fn main() -> tantivy::Result<()> {
     index_writer.add_document(doc!(
         name => "the ditch",
         tags => Facet::from("/pools/north")
-    ));
+    ))?;
 
     index_writer.add_document(doc!(
         name => "little stacey",
         tags => Facet::from("/pools/south")
-    ));
+    ))?;
 
     index_writer.commit()?;
 
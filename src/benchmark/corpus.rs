@@ -0,0 +1,109 @@
+use core::Index;
+use directory::Directory;
+use schema::{Document, Schema, STORED, STRING, TEXT};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use Result;
+
+/// A set of documents to benchmark tantivy with.
+///
+/// The documents are kept in memory, so that reading them
+/// does not interfere with the measures.
+pub struct Corpus {
+    schema: Schema,
+    documents: Vec<Document>,
+}
+
+impl Corpus {
+    /// Schema of the Wikipedia abstracts corpus, with
+    /// a `url`, a `title` and a `body` field.
+    pub fn wikipedia_schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("url", STRING | STORED);
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.build()
+    }
+
+    /// Creates a corpus from documents.
+    pub fn new(schema: Schema, documents: Vec<Document>) -> Corpus {
+        Corpus { schema, documents }
+    }
+
+    /// Reads a corpus of JSON documents, one per line.
+    ///
+    /// Empty lines are ignored.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if a line is not
+    /// a valid document for `schema`.
+    pub fn from_reader<R: BufRead>(schema: Schema, reader: R) -> Result<Corpus> {
+        let mut documents = Vec::new();
+        for line_res in reader.lines() {
+            let line = line_res?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            documents.push(schema.parse_document(&line)?);
+        }
+        Ok(Corpus::new(schema, documents))
+    }
+
+    /// Reads a corpus from a file of JSON documents, one per line.
+    ///
+    /// See [`Corpus::from_reader`](#method.from_reader).
+    pub fn open<P: AsRef<Path>>(schema: Schema, path: P) -> Result<Corpus> {
+        let file = File::open(path)?;
+        Corpus::from_reader(schema, BufReader::new(file))
+    }
+
+    /// Returns the schema of the documents.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the documents of the corpus.
+    pub fn documents(&self) -> &[Document] {
+        &self.documents
+    }
+
+    /// Returns the number of documents of the corpus.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Returns true iff the corpus has no documents.
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Indexes the corpus in a new index, created in `directory`.
+    ///
+    /// The documents are committed once, and the searchers of
+    /// the index are loaded.
+    pub fn build_index<Dir: Directory>(&self, directory: Dir, heap_size: usize) -> Result<Index> {
+        let index = Index::create(directory, self.schema.clone())?;
+        self.index_documents(&index, heap_size)?;
+        Ok(index)
+    }
+
+    /// Indexes the corpus in a new index, in RAM.
+    ///
+    /// See [`Corpus::build_index`](#method.build_index).
+    pub fn build_index_in_ram(&self, heap_size: usize) -> Result<Index> {
+        let index = Index::create_in_ram(self.schema.clone());
+        self.index_documents(&index, heap_size)?;
+        Ok(index)
+    }
+
+    fn index_documents(&self, index: &Index, heap_size: usize) -> Result<()> {
+        let mut index_writer = index.writer(heap_size)?;
+        for document in &self.documents {
+            index_writer.add_document(document.clone())?;
+        }
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+        index.load_searchers()
+    }
+}
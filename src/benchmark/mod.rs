@@ -0,0 +1,37 @@
+//! Benchmark harness, measuring the indexing and search performance
+//! of tantivy on a corpus of documents.
+//!
+//! This module requires the `benchmark` feature.
+//!
+//! A benchmark
+//! - loads a [`Corpus`](./struct.Corpus.html) of JSON documents, one per line,
+//! such as the Wikipedia abstracts used by `tantivy-cli`,
+//! - indexes it,
+//! - runs a fixed [`QuerySuite`](./struct.QuerySuite.html) several times,
+//! and reports the throughput and the latency percentiles of the queries
+//! in a [`BenchmarkReport`](./struct.BenchmarkReport.html).
+//!
+//! Running the same corpus and query suite against two releases
+//! makes performance regressions measurable.
+//!
+//! ```rust,no_run
+//! # extern crate tantivy;
+//! use tantivy::benchmark::{Corpus, QuerySuite};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let corpus = Corpus::open(Corpus::wikipedia_schema(), "wiki-articles.json")?;
+//! let index = corpus.build_index_in_ram(100_000_000)?;
+//! let fields = vec![
+//!     index.schema().get_field("title").unwrap(),
+//!     index.schema().get_field("body").unwrap(),
+//! ];
+//! let report = QuerySuite::wikipedia().run(&index, fields, 10)?;
+//! println!("{}", report);
+//! # Ok(())
+//! # }
+//! ```
+mod corpus;
+mod query_suite;
+
+pub use self::corpus::Corpus;
+pub use self::query_suite::{BenchmarkReport, QueryReport, QuerySuite};
@@ -0,0 +1,339 @@
+use collector::{Count, TopDocs};
+use core::Index;
+use query::QueryParser;
+use schema::Field;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use Result;
+
+const TOP_K: usize = 10;
+
+/// Fixed set of queries, run against the Wikipedia abstracts corpus.
+///
+/// It mixes single terms, conjunctions, disjunctions and phrases,
+/// with frequent and rare terms.
+const WIKIPEDIA_QUERIES: &[&str] = &[
+    "the",
+    "city",
+    "album",
+    "rust",
+    "the city",
+    "+united +states",
+    "+football +club +england",
+    "music OR film",
+    "war OR peace OR history",
+    "\"united states\"",
+    "\"the new york times\"",
+    "+river -city",
+];
+
+fn to_seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
+
+/// Returns the `p`-th percentile of sorted durations,
+/// with the nearest-rank method.
+fn percentile(sorted_durations: &[Duration], p: f64) -> Duration {
+    assert!(
+        p >= 0f64 && p <= 100f64,
+        "The percentile must be within [0, 100]."
+    );
+    if sorted_durations.is_empty() {
+        return Duration::default();
+    }
+    let rank = (p / 100f64 * sorted_durations.len() as f64).ceil() as usize;
+    sorted_durations[rank.max(1).min(sorted_durations.len()) - 1]
+}
+
+/// A fixed list of queries, in the query parser syntax.
+#[derive(Clone, Debug)]
+pub struct QuerySuite {
+    queries: Vec<String>,
+}
+
+impl QuerySuite {
+    /// Creates a query suite from a list of queries.
+    pub fn new(queries: Vec<String>) -> QuerySuite {
+        QuerySuite { queries }
+    }
+
+    /// Query suite for the Wikipedia abstracts corpus.
+    ///
+    /// See [`Corpus::wikipedia_schema`](./struct.Corpus.html#method.wikipedia_schema).
+    pub fn wikipedia() -> QuerySuite {
+        QuerySuite::new(
+            WIKIPEDIA_QUERIES
+                .iter()
+                .map(|query| query.to_string())
+                .collect(),
+        )
+    }
+
+    /// Reads a query suite, one query per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<QuerySuite> {
+        let mut queries = Vec::new();
+        for line_res in reader.lines() {
+            let line = line_res?;
+            let query = line.trim();
+            if query.is_empty() || query.starts_with('#') {
+                continue;
+            }
+            queries.push(query.to_string());
+        }
+        Ok(QuerySuite::new(queries))
+    }
+
+    /// Reads a query suite from a file, one query per line.
+    ///
+    /// See [`QuerySuite::from_reader`](#method.from_reader).
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<QuerySuite> {
+        let file = File::open(path)?;
+        QuerySuite::from_reader(BufReader::new(file))
+    }
+
+    /// Returns the queries of the suite.
+    pub fn queries(&self) -> &[String] {
+        &self.queries
+    }
+
+    /// Runs the query suite `num_iterations` times against the last
+    /// searcher loaded by `index`, and reports the latencies of the queries.
+    ///
+    /// The queries are parsed with `default_fields` as default fields.
+    /// Each search counts the matching documents and collects the top 10,
+    /// and the suite is run once beforehand to warm up the caches.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if one of the queries is invalid.
+    pub fn run(
+        &self,
+        index: &Index,
+        default_fields: Vec<Field>,
+        num_iterations: usize,
+    ) -> Result<BenchmarkReport> {
+        let query_parser = QueryParser::for_index(index, default_fields);
+        let queries = self
+            .queries
+            .iter()
+            .map(|query| query_parser.parse_query(query))
+            .collect::<::std::result::Result<Vec<_>, _>>()?;
+        let searcher = index.searcher();
+        let collector = (Count, TopDocs::with_limit(TOP_K));
+        let mut query_reports = Vec::with_capacity(queries.len());
+        for (query_str, query) in self.queries.iter().zip(&queries) {
+            let (num_hits, _) = searcher.search(query.as_ref(), &collector)?;
+            query_reports.push(QueryReport {
+                query: query_str.clone(),
+                num_hits,
+                latencies: Vec::with_capacity(num_iterations),
+            });
+        }
+        let start = Instant::now();
+        for _ in 0..num_iterations {
+            for (query_report, query) in query_reports.iter_mut().zip(&queries) {
+                let search_start = Instant::now();
+                searcher.search(query.as_ref(), &collector)?;
+                query_report.latencies.push(search_start.elapsed());
+            }
+        }
+        let total_duration = start.elapsed();
+        for query_report in &mut query_reports {
+            query_report.latencies.sort();
+        }
+        Ok(BenchmarkReport {
+            queries: query_reports,
+            total_duration,
+        })
+    }
+}
+
+/// Latencies of a query of a `QuerySuite`.
+#[derive(Clone, Debug)]
+pub struct QueryReport {
+    query: String,
+    num_hits: usize,
+    latencies: Vec<Duration>,
+}
+
+impl QueryReport {
+    /// Returns the query, in the query parser syntax.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the number of documents matching the query.
+    pub fn num_hits(&self) -> usize {
+        self.num_hits
+    }
+
+    /// Returns the latencies of the runs of the query, sorted.
+    pub fn latencies(&self) -> &[Duration] {
+        &self.latencies
+    }
+
+    /// Returns the mean latency of the query.
+    pub fn mean(&self) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::default();
+        }
+        let total: Duration = self
+            .latencies
+            .iter()
+            .fold(Duration::default(), |total, &latency| total + latency);
+        total / self.latencies.len() as u32
+    }
+
+    /// Returns the `p`-th percentile of the latencies of the query.
+    ///
+    /// # Panics
+    /// Panics if `p` is not within `[0, 100]`.
+    pub fn percentile(&self, p: f64) -> Duration {
+        percentile(&self.latencies, p)
+    }
+}
+
+/// Result of a run of a `QuerySuite`.
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    queries: Vec<QueryReport>,
+    total_duration: Duration,
+}
+
+impl BenchmarkReport {
+    /// Returns the reports of the queries, in the order of the suite.
+    pub fn queries(&self) -> &[QueryReport] {
+        &self.queries
+    }
+
+    /// Returns the number of searches run.
+    pub fn num_searches(&self) -> usize {
+        self.queries.iter().map(|query| query.latencies.len()).sum()
+    }
+
+    /// Returns the time spent running the suite.
+    pub fn total_duration(&self) -> Duration {
+        self.total_duration
+    }
+
+    /// Returns the number of searches run per second.
+    pub fn qps(&self) -> f64 {
+        let seconds = to_seconds(self.total_duration);
+        if seconds == 0f64 {
+            return 0f64;
+        }
+        self.num_searches() as f64 / seconds
+    }
+
+    /// Returns the `p`-th percentile of the latencies of all of the searches.
+    ///
+    /// # Panics
+    /// Panics if `p` is not within `[0, 100]`.
+    pub fn percentile(&self, p: f64) -> Duration {
+        let mut latencies: Vec<Duration> = self
+            .queries
+            .iter()
+            .flat_map(|query| query.latencies.iter().cloned())
+            .collect();
+        latencies.sort();
+        percentile(&latencies, p)
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} searches in {:.3}s, {:.1} QPS",
+            self.num_searches(),
+            to_seconds(self.total_duration),
+            self.qps()
+        )?;
+        writeln!(
+            f,
+            "{:>10} {:>10} {:>10} {:>10}  query",
+            "hits", "p50 (ms)", "p90 (ms)", "p99 (ms)"
+        )?;
+        for query in &self.queries {
+            writeln!(
+                f,
+                "{:>10} {:>10.3} {:>10.3} {:>10.3}  {}",
+                query.num_hits,
+                to_seconds(query.percentile(50f64)) * 1_000f64,
+                to_seconds(query.percentile(90f64)) * 1_000f64,
+                to_seconds(query.percentile(99f64)) * 1_000f64,
+                query.query
+            )?;
+        }
+        writeln!(
+            f,
+            "{:>10} {:>10.3} {:>10.3} {:>10.3}  (all)",
+            "",
+            to_seconds(self.percentile(50f64)) * 1_000f64,
+            to_seconds(self.percentile(90f64)) * 1_000f64,
+            to_seconds(self.percentile(99f64)) * 1_000f64
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{percentile, QuerySuite};
+    use benchmark::Corpus;
+    use std::time::Duration;
+
+    #[test]
+    fn test_percentile() {
+        let durations: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&durations, 0f64), Duration::from_millis(1));
+        assert_eq!(percentile(&durations, 50f64), Duration::from_millis(5));
+        assert_eq!(percentile(&durations, 90f64), Duration::from_millis(9));
+        assert_eq!(percentile(&durations, 99f64), Duration::from_millis(10));
+        assert_eq!(percentile(&durations, 100f64), Duration::from_millis(10));
+        assert_eq!(percentile(&[], 50f64), Duration::default());
+    }
+
+    #[test]
+    fn test_run_query_suite() {
+        let corpus_json = r#"
+{"url": "https://en.wikipedia.org/wiki/Rust", "title": "Rust", "body": "Rust is an iron oxide"}
+
+{"url": "https://en.wikipedia.org/wiki/Paris", "title": "Paris", "body": "Paris is the capital city of France"}
+{"url": "https://en.wikipedia.org/wiki/Lyon", "title": "Lyon", "body": "Lyon is a city of France"}
+"#;
+        let corpus =
+            Corpus::from_reader(Corpus::wikipedia_schema(), corpus_json.as_bytes()).unwrap();
+        assert_eq!(corpus.len(), 3);
+        let index = corpus.build_index_in_ram(3_000_000).unwrap();
+        assert_eq!(index.searcher().num_docs(), 3);
+        let schema = index.schema();
+        let fields = vec![
+            schema.get_field("title").unwrap(),
+            schema.get_field("body").unwrap(),
+        ];
+        let query_suite =
+            QuerySuite::from_reader("# comment\ncity\n\n+city +paris\n".as_bytes()).unwrap();
+        assert_eq!(
+            query_suite.queries(),
+            &["city".to_string(), "+city +paris".to_string()]
+        );
+        let report = query_suite.run(&index, fields.clone(), 3).unwrap();
+        assert_eq!(report.num_searches(), 6);
+        assert_eq!(report.queries()[0].num_hits(), 2);
+        assert_eq!(report.queries()[1].num_hits(), 1);
+        for query_report in report.queries() {
+            assert_eq!(query_report.latencies().len(), 3);
+            assert!(query_report.percentile(50f64) <= query_report.percentile(99f64));
+        }
+        assert!(report.percentile(50f64) <= report.percentile(100f64));
+        assert!(format!("{}", report).contains("+city +paris"));
+        assert!(QuerySuite::wikipedia().run(&index, fields, 1).is_ok());
+        let invalid_suite = QuerySuite::new(vec!["title:(".to_string()]);
+        assert!(invalid_suite.run(&index, vec![], 1).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use collector::Collector;
+use collector::SegmentCollector;
+use fastfield::FastFieldReader;
+use schema::{Field, FieldType};
+use std::cmp;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+use TantivyError;
+
+/// Number of bits of the hash used to pick a register.
+const PRECISION: u32 = 14;
+const NUM_REGISTERS: usize = 1 << PRECISION;
+
+/// Mixes the bits of a `u64`, so that close values get unrelated hashes.
+///
+/// This is the finalizer of `SplitMix64`.
+fn hash_u64(val: u64) -> u64 {
+    let mut hash = val.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
+
+/// Sketch estimating the number of distinct values inserted in it.
+///
+/// This is a HyperLogLog with 2^14 registers. As in HyperLogLog++,
+/// the values are hashed on 64 bits, so that large cardinalities do
+/// not suffer from hash collisions. Small cardinalities are estimated
+/// by linear counting.
+///
+/// The standard error of the estimate is about 1%, and the sketch
+/// takes 16KB of memory whatever the number of values.
+///
+/// Two sketches can be merged, without losing accuracy.
+#[derive(Clone, Debug)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> HyperLogLog {
+        HyperLogLog {
+            registers: vec![0u8; NUM_REGISTERS],
+        }
+    }
+}
+
+impl HyperLogLog {
+    /// Inserts a value in the sketch.
+    pub fn insert(&mut self, val: u64) {
+        let hash = hash_u64(val);
+        let register = (hash >> (64 - PRECISION)) as usize;
+        // position of the first 1 bit in the remaining bits.
+        let rank = cmp::min((hash << PRECISION).leading_zeros(), 64 - PRECISION) + 1;
+        self.registers[register] = cmp::max(self.registers[register], rank as u8);
+    }
+
+    /// Merges another sketch in this one.
+    ///
+    /// The sketch then estimates the number of distinct values
+    /// inserted in either of them.
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (register, &other_register) in self.registers.iter_mut().zip(&other.registers) {
+            *register = cmp::max(*register, other_register);
+        }
+    }
+
+    /// Returns the estimated number of distinct values.
+    pub fn estimate(&self) -> u64 {
+        let num_registers = NUM_REGISTERS as f64;
+        let alpha = 0.7213f64 / (1f64 + 1.079f64 / num_registers);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-i32::from(register)))
+            .sum();
+        let raw_estimate = alpha * num_registers * num_registers / sum;
+        let num_zero_registers = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0u8)
+            .count();
+        if raw_estimate <= 2.5f64 * num_registers && num_zero_registers > 0 {
+            // the raw estimate is biased for small cardinalities,
+            // where linear counting is more accurate.
+            let linear_count = num_registers * (num_registers / num_zero_registers as f64).ln();
+            return linear_count.round() as u64;
+        }
+        raw_estimate.round() as u64
+    }
+}
+
+/// Collector estimating the number of distinct values of a
+/// fast field over the matching documents.
+///
+/// The field must be a single value `u64` or `i64` fast field.
+/// The values are inserted in a `HyperLogLog` sketch, so the memory
+/// used does not depend on the number of distinct values.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{Index, Result};
+/// use tantivy::collector::CardinalityCollector;
+/// use tantivy::query::QueryParser;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let user_id = schema_builder.add_u64_field("user_id", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         index_writer.add_document(doc!(title => "red camera", user_id => 1u64))?;
+///         index_writer.add_document(doc!(title => "blue camera", user_id => 2u64))?;
+///         index_writer.add_document(doc!(title => "camera lens", user_id => 1u64))?;
+///         index_writer.add_document(doc!(title => "red case", user_id => 3u64))?;
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let query = QueryParser::for_index(&index, vec![title]).parse_query("camera")?;
+///     let num_users = searcher.search(&query, &CardinalityCollector::for_field(user_id))?;
+///     assert_eq!(num_users.estimate(), 2);
+///     Ok(())
+/// }
+/// ```
+pub struct CardinalityCollector {
+    field: Field,
+}
+
+impl CardinalityCollector {
+    /// Creates a cardinality collector over the fast field `field`.
+    pub fn for_field(field: Field) -> CardinalityCollector {
+        CardinalityCollector { field }
+    }
+}
+
+impl Collector for CardinalityCollector {
+    type Fruit = HyperLogLog;
+
+    type Child = CardinalitySegmentCollector;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<CardinalitySegmentCollector> {
+        let field_entry = reader.schema().get_field_entry(self.field);
+        let ff_reader = match *field_entry.field_type() {
+            FieldType::U64(_) => FastValueReader::U64(reader.fast_field_reader(self.field)?),
+            FieldType::I64(_) => FastValueReader::I64(reader.fast_field_reader(self.field)?),
+            FieldType::F64(_) => FastValueReader::F64(reader.fast_field_reader(self.field)?),
+            _ => {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The field {:?} is not a u64, i64 or f64 fast field.",
+                    field_entry.name()
+                )));
+            }
+        };
+        Ok(CardinalitySegmentCollector {
+            ff_reader,
+            hyperloglog: HyperLogLog::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_hyperloglogs: Vec<HyperLogLog>) -> Result<HyperLogLog> {
+        let mut hyperloglog = HyperLogLog::default();
+        for segment_hyperloglog in &segment_hyperloglogs {
+            hyperloglog.merge(segment_hyperloglog);
+        }
+        Ok(hyperloglog)
+    }
+}
+
+enum FastValueReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+}
+
+/// Segment Collector associated to `CardinalityCollector`.
+pub struct CardinalitySegmentCollector {
+    ff_reader: FastValueReader,
+    hyperloglog: HyperLogLog,
+}
+
+impl SegmentCollector for CardinalitySegmentCollector {
+    type Fruit = HyperLogLog;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = match self.ff_reader {
+            FastValueReader::U64(ref ff_reader) => ff_reader.get(doc),
+            FastValueReader::I64(ref ff_reader) => ff_reader.get(doc) as u64,
+            FastValueReader::F64(ref ff_reader) => ff_reader.get(doc).to_bits(),
+        };
+        self.hyperloglog.insert(val);
+    }
+
+    fn harvest(self) -> HyperLogLog {
+        self.hyperloglog
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.hyperloglog.registers.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CardinalityCollector, HyperLogLog};
+    use query::AllQuery;
+    use schema::{Schema, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_hyperloglog() {
+        for &num_vals in &[0u64, 1u64, 100u64, 10_000u64, 1_000_000u64] {
+            let mut hyperloglog = HyperLogLog::default();
+            for val in 0..num_vals {
+                hyperloglog.insert(val);
+                hyperloglog.insert(val);
+            }
+            let estimate = hyperloglog.estimate() as f64;
+            let error = (estimate - num_vals as f64).abs();
+            assert!(
+                error <= 1f64 + num_vals as f64 * 0.03f64,
+                "estimated {} distinct values, instead of {}",
+                estimate,
+                num_vals
+            );
+        }
+    }
+
+    #[test]
+    fn test_hyperloglog_merge() {
+        let mut left = HyperLogLog::default();
+        let mut right = HyperLogLog::default();
+        for val in 0u64..600u64 {
+            left.insert(val);
+        }
+        for val in 400u64..1_000u64 {
+            right.insert(val);
+        }
+        left.merge(&right);
+        let estimate = left.estimate();
+        assert!(estimate >= 980 && estimate <= 1_020, "{}", estimate);
+    }
+
+    #[test]
+    fn test_cardinality_collector() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let user_id = schema_builder.add_i64_field("user_id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+            for val in -50i64..50i64 {
+                index_writer.add_document(doc!(user_id => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            for val in 0i64..100i64 {
+                index_writer.add_document(doc!(user_id => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let num_users = searcher
+            .search(&AllQuery, &CardinalityCollector::for_field(user_id))
+            .unwrap()
+            .estimate();
+        assert!(num_users >= 148 && num_users <= 152, "{}", num_users);
+        assert!(searcher
+            .search(&AllQuery, &CardinalityCollector::for_field(title))
+            .is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use super::Collector;
 use collector::SegmentCollector;
+use query::Weight;
 use DocId;
 use Result;
 use Score;
@@ -27,16 +28,16 @@ use SegmentReader;
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -72,6 +73,18 @@ impl Collector for Count {
     fn merge_fruits(&self, segment_counts: Vec<usize>) -> Result<usize> {
         Ok(segment_counts.into_iter().sum())
     }
+
+    fn collect_segment_count(
+        &self,
+        weight: &Weight,
+        segment: &SegmentReader,
+    ) -> Result<Option<usize>> {
+        // `Weight::count` does not exclude the deleted documents.
+        if segment.num_deleted_docs() > 0 {
+            return Ok(None);
+        }
+        Ok(Some(weight.count(segment)? as usize))
+    }
 }
 
 #[derive(Default)]
@@ -96,6 +109,42 @@ mod tests {
     use super::{Count, SegmentCountCollector};
     use collector::Collector;
     use collector::SegmentCollector;
+    use query::{EmptyScorer, Query, Scorer, TermQuery, Weight};
+    use schema::{IndexRecordOption, Schema, STRING};
+    use Index;
+    use Result;
+    use Searcher;
+    use SegmentReader;
+    use Term;
+
+    /// Query matching no documents, whose weight nonetheless
+    /// counts `num_docs` documents in each segment.
+    #[derive(Clone, Debug)]
+    struct CountOnlyQuery {
+        num_docs: u32,
+    }
+
+    struct CountOnlyWeight {
+        num_docs: u32,
+    }
+
+    impl Query for CountOnlyQuery {
+        fn weight(&self, _searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<Weight>> {
+            Ok(Box::new(CountOnlyWeight {
+                num_docs: self.num_docs,
+            }))
+        }
+    }
+
+    impl Weight for CountOnlyWeight {
+        fn scorer(&self, _reader: &SegmentReader) -> Result<Box<Scorer>> {
+            Ok(Box::new(EmptyScorer))
+        }
+
+        fn count(&self, _reader: &SegmentReader) -> Result<u32> {
+            Ok(self.num_docs)
+        }
+    }
 
     #[test]
     fn test_count_collect_does_not_requires_scoring() {
@@ -126,4 +175,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_count_uses_weight_count() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "b"),
+            IndexRecordOption::Basic,
+        );
+        let searcher = index.searcher();
+        assert_eq!(
+            searcher
+                .search(&CountOnlyQuery { num_docs: 42 }, &Count)
+                .unwrap(),
+            42
+        );
+        assert_eq!(searcher.search(&term_query, &Count).unwrap(), 2);
+        // the documents of the first segment, which has deletes, are collected
+        // rather than counted by the weight.
+        index_writer
+            .delete_term(Term::from_field_text(text_field, "b"))
+            .unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(
+            searcher
+                .search(&CountOnlyQuery { num_docs: 42 }, &Count)
+                .unwrap(),
+            42
+        );
+        assert_eq!(searcher.search(&term_query, &Count).unwrap(), 1);
+    }
 }
@@ -11,6 +11,7 @@ use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
 use std::collections::Bound;
 use std::iter::Peekable;
+use std::mem;
 use std::{u64, usize};
 use DocId;
 use Result;
@@ -105,23 +106,23 @@ fn facet_depth(facet_bytes: &[u8]) -> usize {
 ///             title => "The Name of the Wind",
 ///             facet => Facet::from("/lang/en"),
 ///             facet => Facet::from("/category/fiction/fantasy")
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "Dune",
 ///             facet => Facet::from("/lang/en"),
 ///             facet => Facet::from("/category/fiction/sci-fi")
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "La Vénus d'Ille",
 ///             facet => Facet::from("/lang/fr"),
 ///             facet => Facet::from("/category/fiction/fantasy"),
 ///             facet => Facet::from("/category/fiction/horror")
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
 ///             facet => Facet::from("/lang/en"),
 ///             facet => Facet::from("/category/biography")
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -373,6 +374,14 @@ impl SegmentCollector for FacetSegmentCollector {
         }
         FacetCounts { facet_counts }
     }
+
+    fn memory_usage(&self) -> usize {
+        (self.facet_ords_buf.capacity()
+            + self.counts.capacity()
+            + self.collapse_facet_ords.capacity())
+            * mem::size_of::<u64>()
+            + self.collapse_mapping.capacity() * mem::size_of::<usize>()
+    }
 }
 
 /// Intermediary result of the `FacetCollector` that stores
@@ -479,7 +488,7 @@ mod tests {
         for i in 0..num_facets * 10 {
             let mut doc = Document::new();
             doc.add_facet(facet_field, facets[i % num_facets].clone());
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
@@ -524,12 +533,14 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-        index_writer.add_document(doc!(
-            facet_field => Facet::from_text(&"/subjects/A/a"),
-            facet_field => Facet::from_text(&"/subjects/B/a"),
-            facet_field => Facet::from_text(&"/subjects/A/b"),
-            facet_field => Facet::from_text(&"/subjects/B/b"),
-        ));
+        index_writer
+            .add_document(doc!(
+                facet_field => Facet::from_text(&"/subjects/A/a"),
+                facet_field => Facet::from_text(&"/subjects/B/a"),
+                facet_field => Facet::from_text(&"/subjects/A/b"),
+                facet_field => Facet::from_text(&"/subjects/B/b"),
+            ))
+            .unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -575,7 +586,7 @@ mod tests {
 
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         for doc in docs {
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
@@ -631,7 +642,7 @@ mod bench {
 
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         for doc in docs {
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
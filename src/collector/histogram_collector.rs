@@ -0,0 +1,229 @@
+use collector::Collector;
+use collector::SegmentCollector;
+use fastfield::FastFieldReader;
+use fastfield::FastValue;
+use schema::Field;
+use std::marker::PhantomData;
+use std::mem;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Buckets of a `HistogramCollector`.
+///
+/// The `u64` representation of `f64` values only preserves their order,
+/// so the buckets are computed on the values converted to `f64`.
+#[derive(Clone, Copy)]
+struct Buckets {
+    min_value: f64,
+    bucket_width: f64,
+    num_buckets: usize,
+}
+
+impl Buckets {
+    fn bucket(&self, val: f64) -> Option<usize> {
+        let bucket = ((val - self.min_value) / self.bucket_width).floor();
+        // NaN values are not within any bucket.
+        if bucket >= 0f64 && bucket < self.num_buckets as f64 {
+            Some(bucket as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Histogram collector over a single value fast field.
+///
+/// The collector counts the matching documents in `num_buckets`
+/// buckets of width `bucket_width`, starting at `min_value`:
+/// the bucket `i` counts the documents whose value is within
+/// `[min_value + i * bucket_width, min_value + (i + 1) * bucket_width)`.
+///
+/// The documents whose value is outside of the buckets are ignored.
+///
+/// The buckets are computed on the values converted to `f64`, which
+/// is only exact for the integers up to 2^53 in absolute value.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{Index, Result};
+/// use tantivy::collector::HistogramCollector;
+/// use tantivy::query::AllQuery;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let price = schema_builder.add_u64_field("price", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         for &val in &[3u64, 12u64, 17u64, 25u64, 120u64] {
+///             index_writer.add_document(doc!(price => val))?;
+///         }
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     // 3 buckets: [0, 10), [10, 20) and [20, 30).
+///     let histogram = HistogramCollector::new(price, 0u64, 10f64, 3);
+///     let counts = searcher.search(&AllQuery, &histogram)?;
+///     assert_eq!(counts, vec![1, 2, 1]);
+///     Ok(())
+/// }
+/// ```
+pub struct HistogramCollector<T> {
+    field: Field,
+    buckets: Buckets,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FastValue> HistogramCollector<T> {
+    /// Creates a histogram collector over the fast field `field`.
+    ///
+    /// # Panics
+    /// The method panics if `bucket_width` is not strictly positive.
+    pub fn new(
+        field: Field,
+        min_value: T,
+        bucket_width: f64,
+        num_buckets: usize,
+    ) -> HistogramCollector<T> {
+        assert!(
+            bucket_width > 0f64,
+            "The bucket width must be strictly greater than 0."
+        );
+        HistogramCollector {
+            field,
+            buckets: Buckets {
+                min_value: min_value.to_f64(),
+                bucket_width,
+                num_buckets,
+            },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: FastValue + Send + Sync + 'static> Collector for HistogramCollector<T> {
+    type Fruit = Vec<u64>;
+
+    type Child = HistogramSegmentCollector<T>;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<HistogramSegmentCollector<T>> {
+        Ok(HistogramSegmentCollector {
+            ff_reader: reader.fast_field_reader(self.field)?,
+            buckets: self.buckets,
+            counts: vec![0u64; self.buckets.num_buckets],
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_counts: Vec<Vec<u64>>) -> Result<Vec<u64>> {
+        let mut counts = vec![0u64; self.buckets.num_buckets];
+        for segment_counts in segment_counts {
+            for (count, segment_count) in counts.iter_mut().zip(segment_counts) {
+                *count += segment_count;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/// Segment Collector associated to `HistogramCollector`.
+pub struct HistogramSegmentCollector<T: FastValue> {
+    ff_reader: FastFieldReader<T>,
+    buckets: Buckets,
+    counts: Vec<u64>,
+}
+
+impl<T: FastValue + Send + Sync + 'static> SegmentCollector for HistogramSegmentCollector<T> {
+    type Fruit = Vec<u64>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self.ff_reader.get(doc).to_f64();
+        if let Some(bucket) = self.buckets.bucket(val) {
+            self.counts[bucket] += 1;
+        }
+    }
+
+    fn harvest(self) -> Vec<u64> {
+        self.counts
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.counts.capacity() * mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HistogramCollector;
+    use query::AllQuery;
+    use schema::{Schema, FAST};
+    use Index;
+
+    #[test]
+    fn test_histogram_collector_i64() {
+        let mut schema_builder = Schema::builder();
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for &val in &[-25i64, -12i64, -1i64, 0i64, 4i64, 9i64] {
+                index_writer.add_document(doc!(temperature => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            for &val in &[-10i64, 10i64, 14i64, 100i64] {
+                index_writer.add_document(doc!(temperature => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let histogram = HistogramCollector::new(temperature, -20i64, 10f64, 4);
+        let counts = searcher.search(&AllQuery, &histogram).unwrap();
+        assert_eq!(counts, vec![1, 2, 3, 2]);
+    }
+
+    #[test]
+    fn test_histogram_collector_f64() {
+        let mut schema_builder = Schema::builder();
+        let ratio = schema_builder.add_f64_field("ratio", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for &val in &[
+                -0.75f64, -0.5f64, -0.1f64, 0f64, 0.2f64, 0.45f64, 0.5f64, 1.2f64,
+            ] {
+                index_writer.add_document(doc!(ratio => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let histogram = HistogramCollector::new(ratio, -0.5f64, 0.5f64, 3);
+        let counts = searcher.search(&AllQuery, &histogram).unwrap();
+        assert_eq!(counts, vec![2, 3, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_histogram_collector_zero_width() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("val", FAST);
+        HistogramCollector::new(field, 0u64, 0f64, 4);
+    }
+}
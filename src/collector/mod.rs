@@ -49,10 +49,10 @@ use tantivy::collector::{Count, TopDocs};
 #     let mut index_writer = index.writer(3_000_000)?;
 #       index_writer.add_document(doc!(
 #       title => "The Name of the Wind",
-#      ));
+#      ))?;
 #     index_writer.add_document(doc!(
 #        title => "The Diary of Muadib",
-#     ));
+#     ))?;
 #     index_writer.commit().unwrap();
 #     index.load_searchers()?;
 #     let searcher = index.searcher();
@@ -86,6 +86,7 @@ See the `custom_collector` example.
 */
 
 use downcast;
+use query::Weight;
 use DocId;
 use Result;
 use Score;
@@ -104,11 +105,26 @@ mod top_score_collector;
 pub use self::top_score_collector::TopDocs;
 
 mod top_field_collector;
-pub use self::top_field_collector::TopDocsByField;
+pub use self::top_field_collector::{Order, TopDocsByField};
+
+mod top_group_collector;
+pub use self::top_group_collector::TopDocsByGroup;
 
 mod facet_collector;
 pub use self::facet_collector::FacetCollector;
 
+mod histogram_collector;
+pub use self::histogram_collector::HistogramCollector;
+
+mod range_collector;
+pub use self::range_collector::RangeCollector;
+
+mod stats_collector;
+pub use self::stats_collector::{Stats, StatsCollector};
+
+mod cardinality_collector;
+pub use self::cardinality_collector::{CardinalityCollector, HyperLogLog};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast::Any {}
@@ -153,6 +169,20 @@ pub trait Collector: Sync {
     /// Combines the fruit associated to the collection of each segments
     /// into one fruit.
     fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit>;
+
+    /// Computes the fruit of a segment from `Weight::count`, without
+    /// collecting its documents, if the collector only requires the
+    /// number of documents matching the query.
+    ///
+    /// Returns `None` if the documents of the segment must be collected,
+    /// which is the default.
+    fn collect_segment_count(
+        &self,
+        _weight: &Weight,
+        _segment: &SegmentReader,
+    ) -> Result<Option<Self::Fruit>> {
+        Ok(None)
+    }
 }
 
 /// The `SegmentCollector` is the trait in charge of defining the
@@ -170,6 +200,15 @@ pub trait SegmentCollector: 'static {
 
     /// Extract the fruit of the collection from the `SegmentCollector`.
     fn harvest(self) -> Self::Fruit;
+
+    /// Returns an estimate of the number of bytes held in memory
+    /// by the `SegmentCollector`, like its heap of top documents
+    /// or its counters.
+    ///
+    /// The estimate is used to enforce the memory limit of queries.
+    fn memory_usage(&self) -> usize {
+        0
+    }
 }
 
 // -----------------------------------------------
@@ -225,6 +264,10 @@ where
     fn harvest(self) -> <Self as SegmentCollector>::Fruit {
         (self.0.harvest(), self.1.harvest())
     }
+
+    fn memory_usage(&self) -> usize {
+        self.0.memory_usage() + self.1.memory_usage()
+    }
 }
 
 // 3-Tuple
@@ -283,6 +326,10 @@ where
     fn harvest(self) -> <Self as SegmentCollector>::Fruit {
         (self.0.harvest(), self.1.harvest(), self.2.harvest())
     }
+
+    fn memory_usage(&self) -> usize {
+        self.0.memory_usage() + self.1.memory_usage() + self.2.memory_usage()
+    }
 }
 
 // 4-Tuple
@@ -356,6 +403,13 @@ where
             self.3.harvest(),
         )
     }
+
+    fn memory_usage(&self) -> usize {
+        self.0.memory_usage()
+            + self.1.memory_usage()
+            + self.2.memory_usage()
+            + self.3.memory_usage()
+    }
 }
 
 #[allow(missing_docs)]
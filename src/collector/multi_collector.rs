@@ -60,11 +60,16 @@ impl SegmentCollector for Box<BoxableSegmentCollector> {
     fn harvest(self) -> Box<Fruit> {
         BoxableSegmentCollector::harvest_from_box(self)
     }
+
+    fn memory_usage(&self) -> usize {
+        self.as_ref().memory_usage()
+    }
 }
 
 pub trait BoxableSegmentCollector {
     fn collect(&mut self, doc: u32, score: f32);
     fn harvest_from_box(self: Box<Self>) -> Box<Fruit>;
+    fn memory_usage(&self) -> usize;
 }
 
 pub struct SegmentCollectorWrapper<TSegmentCollector: SegmentCollector>(TSegmentCollector);
@@ -79,6 +84,10 @@ impl<TSegmentCollector: SegmentCollector> BoxableSegmentCollector
     fn harvest_from_box(self: Box<Self>) -> Box<Fruit> {
         Box::new(self.0.harvest())
     }
+
+    fn memory_usage(&self) -> usize {
+        self.0.memory_usage()
+    }
 }
 
 pub struct FruitHandle<TFruit: Fruit> {
@@ -116,16 +125,16 @@ impl<TFruit: Fruit> FruitHandle<TFruit> {
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -242,6 +251,10 @@ impl SegmentCollector for MultiCollectorChild {
                 .collect(),
         }
     }
+
+    fn memory_usage(&self) -> usize {
+        self.children.iter().map(|child| child.memory_usage()).sum()
+    }
 }
 
 #[cfg(test)]
@@ -264,13 +277,17 @@ mod tests {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(text=>"abc"));
-            index_writer.add_document(doc!(text=>"abc abc abc"));
-            index_writer.add_document(doc!(text=>"abc abc"));
+            index_writer.add_document(doc!(text=>"abc")).unwrap();
+            index_writer
+                .add_document(doc!(text=>"abc abc abc"))
+                .unwrap();
+            index_writer.add_document(doc!(text=>"abc abc")).unwrap();
             index_writer.commit().unwrap();
-            index_writer.add_document(doc!(text=>""));
-            index_writer.add_document(doc!(text=>"abc abc abc abc"));
-            index_writer.add_document(doc!(text=>"abc"));
+            index_writer.add_document(doc!(text=>"")).unwrap();
+            index_writer
+                .add_document(doc!(text=>"abc abc abc abc"))
+                .unwrap();
+            index_writer.add_document(doc!(text=>"abc")).unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -0,0 +1,224 @@
+use collector::Collector;
+use collector::SegmentCollector;
+use fastfield::FastFieldReader;
+use fastfield::FastValue;
+use schema::Field;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Maps a bound on a fast value to a bound on its `u64` representation,
+/// which preserves the order of the values.
+fn to_u64_bound<T: FastValue>(bound: Bound<&T>) -> Bound<u64> {
+    match bound {
+        Bound::Included(val) => Bound::Included(val.to_u64()),
+        Bound::Excluded(val) => Bound::Excluded(val.to_u64()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// A range of `u64` representations of fast values.
+#[derive(Clone)]
+struct U64Range {
+    lower: Bound<u64>,
+    upper: Bound<u64>,
+}
+
+impl U64Range {
+    fn contains(&self, val: u64) -> bool {
+        let is_above_lower = match self.lower {
+            Bound::Included(lower) => val >= lower,
+            Bound::Excluded(lower) => val > lower,
+            Bound::Unbounded => true,
+        };
+        let is_below_upper = match self.upper {
+            Bound::Included(upper) => val <= upper,
+            Bound::Excluded(upper) => val < upper,
+            Bound::Unbounded => true,
+        };
+        is_above_lower && is_below_upper
+    }
+}
+
+/// Range aggregation over a single value fast field.
+///
+/// The collector counts the matching documents within each of the
+/// ranges registered with `.add_range(...)`, and returns the counts
+/// in the order the ranges were added.
+/// The ranges may overlap, or leave gaps between them: a document is
+/// counted once in every range containing its value.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{Index, Result};
+/// use tantivy::collector::{Count, RangeCollector};
+/// use tantivy::query::AllQuery;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let price = schema_builder.add_u64_field("price", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         for &val in &[3u64, 10u64, 12u64, 49u64, 50u64, 120u64] {
+///             index_writer.add_document(doc!(price => val))?;
+///         }
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let mut price_ranges = RangeCollector::for_field(price);
+///     price_ranges.add_range(0u64..10u64);
+///     price_ranges.add_range(10u64..50u64);
+///     price_ranges.add_range(50u64..);
+///     let (count, counts) = searcher.search(&AllQuery, &(Count, price_ranges))?;
+///     assert_eq!(count, 6);
+///     assert_eq!(counts, vec![1, 3, 2]);
+///     Ok(())
+/// }
+/// ```
+pub struct RangeCollector<T> {
+    field: Field,
+    ranges: Vec<U64Range>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FastValue> RangeCollector<T> {
+    /// Creates a range collector over the fast field `field`, without any range.
+    pub fn for_field(field: Field) -> RangeCollector<T> {
+        RangeCollector {
+            field,
+            ranges: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds a range to count the documents of, e.g. `10..50` or `50..`.
+    pub fn add_range<R: RangeBounds<T>>(&mut self, range: R) {
+        self.ranges.push(U64Range {
+            lower: to_u64_bound(range.start_bound()),
+            upper: to_u64_bound(range.end_bound()),
+        });
+    }
+}
+
+impl<T: FastValue + Send + Sync + 'static> Collector for RangeCollector<T> {
+    type Fruit = Vec<u64>;
+
+    type Child = RangeSegmentCollector<T>;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<RangeSegmentCollector<T>> {
+        Ok(RangeSegmentCollector {
+            ff_reader: reader.fast_field_reader(self.field)?,
+            ranges: self.ranges.clone(),
+            counts: vec![0u64; self.ranges.len()],
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_counts: Vec<Vec<u64>>) -> Result<Vec<u64>> {
+        let mut counts = vec![0u64; self.ranges.len()];
+        for segment_counts in segment_counts {
+            for (count, segment_count) in counts.iter_mut().zip(segment_counts) {
+                *count += segment_count;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/// Segment Collector associated to `RangeCollector`.
+pub struct RangeSegmentCollector<T: FastValue> {
+    ff_reader: FastFieldReader<T>,
+    ranges: Vec<U64Range>,
+    counts: Vec<u64>,
+}
+
+impl<T: FastValue + Send + Sync + 'static> SegmentCollector for RangeSegmentCollector<T> {
+    type Fruit = Vec<u64>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self.ff_reader.get(doc).to_u64();
+        for (range, count) in self.ranges.iter().zip(self.counts.iter_mut()) {
+            if range.contains(val) {
+                *count += 1;
+            }
+        }
+    }
+
+    fn harvest(self) -> Vec<u64> {
+        self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeCollector;
+    use collector::{MultiCollector, TopDocs};
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, Schema, FAST, STRING};
+    use Index;
+    use Term;
+
+    #[test]
+    fn test_range_collector() {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING);
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(city => "oslo", temperature => -12i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(city => "rome", temperature => 21i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(city => "oslo", temperature => 0i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(city => "rome", temperature => 30i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+
+        let mut temperature_ranges = RangeCollector::for_field(temperature);
+        temperature_ranges.add_range(..0i64);
+        temperature_ranges.add_range(0i64..=21i64);
+        temperature_ranges.add_range(21i64..);
+        temperature_ranges.add_range(..);
+        let counts = searcher.search(&AllQuery, &temperature_ranges).unwrap();
+        assert_eq!(counts, vec![1, 2, 2, 4]);
+
+        let mut collectors = MultiCollector::new();
+        let ranges_handle = collectors.add_collector(temperature_ranges);
+        let top_docs_handle = collectors.add_collector(TopDocs::with_limit(1));
+        let oslo = TermQuery::new(
+            Term::from_field_text(city, "oslo"),
+            IndexRecordOption::Basic,
+        );
+        let mut multi_fruit = searcher.search(&oslo, &collectors).unwrap();
+        assert_eq!(ranges_handle.extract(&mut multi_fruit), vec![1, 1, 0, 2]);
+        assert_eq!(top_docs_handle.extract(&mut multi_fruit).len(), 1);
+    }
+}
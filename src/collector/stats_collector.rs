@@ -0,0 +1,262 @@
+use collector::Collector;
+use collector::SegmentCollector;
+use fastfield::FastFieldReader;
+use fastfield::FastValue;
+use schema::Field;
+use std::marker::PhantomData;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Statistics over the values of a fast field,
+/// computed by the `StatsCollector`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats<T> {
+    count: u64,
+    min: Option<T>,
+    max: Option<T>,
+    sum: f64,
+}
+
+impl<T: FastValue + PartialOrd> Stats<T> {
+    fn empty() -> Stats<T> {
+        Stats {
+            count: 0u64,
+            min: None,
+            max: None,
+            sum: 0f64,
+        }
+    }
+
+    fn add(&mut self, val: T) {
+        self.count += 1;
+        self.sum += val.to_f64();
+        if self.min.map(|min| val < min).unwrap_or(true) {
+            self.min = Some(val);
+        }
+        if self.max.map(|max| val > max).unwrap_or(true) {
+            self.max = Some(val);
+        }
+    }
+
+    fn merge(&mut self, other: &Stats<T>) {
+        self.count += other.count;
+        self.sum += other.sum;
+        if let Some(other_min) = other.min {
+            if self.min.map(|min| other_min < min).unwrap_or(true) {
+                self.min = Some(other_min);
+            }
+        }
+        if let Some(other_max) = other.max {
+            if self.max.map(|max| other_max > max).unwrap_or(true) {
+                self.max = Some(other_max);
+            }
+        }
+    }
+
+    /// Returns the number of documents.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the minimum value, or `None` if there are no documents.
+    pub fn min(&self) -> Option<T> {
+        self.min
+    }
+
+    /// Returns the maximum value, or `None` if there are no documents.
+    pub fn max(&self) -> Option<T> {
+        self.max
+    }
+
+    /// Returns the sum of the values.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the mean of the values, or `None` if there are no documents.
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.sum / self.count as f64)
+        }
+    }
+}
+
+/// Collector computing the count, minimum, maximum, sum and mean
+/// of a single value fast field over the matching documents.
+///
+/// The values are read from the fast field, in a single pass:
+/// the stored fields are never loaded.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{Index, Result};
+/// use tantivy::collector::StatsCollector;
+/// use tantivy::query::QueryParser;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let price = schema_builder.add_u64_field("price", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         index_writer.add_document(doc!(title => "red camera", price => 300u64))?;
+///         index_writer.add_document(doc!(title => "blue camera", price => 100u64))?;
+///         index_writer.add_document(doc!(title => "camera lens", price => 200u64))?;
+///         index_writer.add_document(doc!(title => "red case", price => 20u64))?;
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let query = QueryParser::for_index(&index, vec![title]).parse_query("camera")?;
+///     let stats = searcher.search(&query, &StatsCollector::for_field(price))?;
+///     assert_eq!(stats.count(), 3);
+///     assert_eq!(stats.min(), Some(100u64));
+///     assert_eq!(stats.max(), Some(300u64));
+///     assert_eq!(stats.mean(), Some(200f64));
+///     Ok(())
+/// }
+/// ```
+pub struct StatsCollector<T> {
+    field: Field,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FastValue> StatsCollector<T> {
+    /// Creates a stats collector over the fast field `field`.
+    pub fn for_field(field: Field) -> StatsCollector<T> {
+        StatsCollector {
+            field,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: FastValue + PartialOrd + Send + Sync + 'static> Collector for StatsCollector<T> {
+    type Fruit = Stats<T>;
+
+    type Child = StatsSegmentCollector<T>;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<StatsSegmentCollector<T>> {
+        Ok(StatsSegmentCollector {
+            ff_reader: reader.fast_field_reader(self.field)?,
+            stats: Stats::empty(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_stats: Vec<Stats<T>>) -> Result<Stats<T>> {
+        let mut stats = Stats::empty();
+        for segment_stats in &segment_stats {
+            stats.merge(segment_stats);
+        }
+        Ok(stats)
+    }
+}
+
+/// Segment Collector associated to `StatsCollector`.
+pub struct StatsSegmentCollector<T: FastValue> {
+    ff_reader: FastFieldReader<T>,
+    stats: Stats<T>,
+}
+
+impl<T: FastValue + PartialOrd + Send + Sync + 'static> SegmentCollector
+    for StatsSegmentCollector<T>
+{
+    type Fruit = Stats<T>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self.ff_reader.get(doc);
+        self.stats.add(val);
+    }
+
+    fn harvest(self) -> Stats<T> {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatsCollector;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, Schema, FAST, STRING};
+    use Index;
+    use Term;
+
+    #[test]
+    fn test_stats_collector() {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING);
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(city => "oslo", temperature => -12i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(city => "rome", temperature => 21i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(city => "oslo", temperature => 3i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(city => "rome", temperature => 30i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+
+        let stats = searcher
+            .search(&AllQuery, &StatsCollector::for_field(temperature))
+            .unwrap();
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.min(), Some(-12i64));
+        assert_eq!(stats.max(), Some(30i64));
+        assert_eq!(stats.sum(), 42f64);
+        assert_eq!(stats.mean(), Some(10.5f64));
+
+        let oslo = TermQuery::new(
+            Term::from_field_text(city, "oslo"),
+            IndexRecordOption::Basic,
+        );
+        let stats = searcher
+            .search(&oslo, &StatsCollector::for_field(temperature))
+            .unwrap();
+        assert_eq!(stats.count(), 2);
+        assert_eq!(stats.min(), Some(-12i64));
+        assert_eq!(stats.max(), Some(3i64));
+        assert_eq!(stats.mean(), Some(-4.5f64));
+
+        let paris = TermQuery::new(
+            Term::from_field_text(city, "paris"),
+            IndexRecordOption::Basic,
+        );
+        let stats = searcher
+            .search(&paris, &StatsCollector::<i64>::for_field(temperature))
+            .unwrap();
+        assert_eq!(stats.count(), 0);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.max(), None);
+        assert_eq!(stats.mean(), None);
+    }
+}
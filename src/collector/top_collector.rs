@@ -1,4 +1,5 @@
-use serde::export::PhantomData;
+use std::marker::PhantomData;
+use std::mem;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use DocAddress;
@@ -120,7 +121,7 @@ pub(crate) struct TopSegmentCollector<T> {
 }
 
 impl<T: PartialOrd> TopSegmentCollector<T> {
-    fn new(segment_id: SegmentLocalId, limit: usize) -> TopSegmentCollector<T> {
+    pub(crate) fn new(segment_id: SegmentLocalId, limit: usize) -> TopSegmentCollector<T> {
         TopSegmentCollector {
             limit,
             heap: BinaryHeap::with_capacity(limit),
@@ -144,6 +145,12 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
             .collect()
     }
 
+    /// Returns the number of bytes allocated by the heap
+    /// of the collector.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.heap.capacity() * mem::size_of::<ComparableDoc<T, DocId>>()
+    }
+
     /// Return true iff at least K documents have gone through
     /// the collector.
     #[inline(always)]
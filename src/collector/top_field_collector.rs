@@ -5,6 +5,7 @@ use collector::SegmentCollector;
 use fastfield::FastFieldReader;
 use fastfield::FastValue;
 use schema::Field;
+use std::marker::PhantomData;
 use DocAddress;
 use Result;
 use SegmentLocalId;
@@ -35,10 +36,10 @@ use SegmentReader;
 /// #   index_writer.add_document(doc!(
 /// #       title => "The Name of the Wind",
 /// #       rating => 92u64,
-/// #   ));
-/// #   index_writer.add_document(doc!(title => "The Diary of Muadib", rating => 97u64));
-/// #   index_writer.add_document(doc!(title => "A Dairy Cow", rating => 63u64));
-/// #   index_writer.add_document(doc!(title => "The Diary of a Young Girl", rating => 80u64));
+/// #   )).unwrap();
+/// #   index_writer.add_document(doc!(title => "The Diary of Muadib", rating => 97u64)).unwrap();
+/// #   index_writer.add_document(doc!(title => "A Dairy Cow", rating => 63u64)).unwrap();
+/// #   index_writer.add_document(doc!(title => "The Diary of a Young Girl", rating => 80u64)).unwrap();
 /// #   index_writer.commit().unwrap();
 /// #   index.load_searchers().unwrap();
 ///	#   let query = QueryParser::for_index(&index, vec![title]).parse_query("diary").unwrap();
@@ -65,9 +66,43 @@ use SegmentReader;
 ///          .search(query, &top_docs_by_rating)
 /// }
 /// ```
+///
+/// The documents are sorted by decreasing value by default.
+/// See [`TopDocsByField::order`](#method.order) to sort them by increasing value.
 pub struct TopDocsByField<T> {
-    collector: TopCollector<T>,
+    collector: TopCollector<u64>,
     field: Field,
+    order: Order,
+    _marker: PhantomData<T>,
+}
+
+/// Order in which `TopDocsByField` sorts the documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// The documents with the lowest values come first.
+    Asc,
+    /// The documents with the highest values come first.
+    Desc,
+}
+
+impl Order {
+    /// Maps the `u64` representation of a fast value to the key
+    /// the documents are collected by.
+    ///
+    /// The best documents have the highest keys, and mapping
+    /// a key again returns the original `u64`.
+    fn sort_key(self, val: u64) -> u64 {
+        match self {
+            Order::Asc => u64::max_value() - val,
+            Order::Desc => val,
+        }
+    }
+
+    fn to_fast_values<T: FastValue>(self, keys: Vec<(u64, DocAddress)>) -> Vec<(T, DocAddress)> {
+        keys.into_iter()
+            .map(|(key, doc_address)| (T::from_u64(self.sort_key(key)), doc_address))
+            .collect()
+    }
 }
 
 impl<T: FastValue + PartialOrd + Clone> TopDocsByField<T> {
@@ -82,8 +117,19 @@ impl<T: FastValue + PartialOrd + Clone> TopDocsByField<T> {
         TopDocsByField {
             collector: TopCollector::with_limit(limit),
             field,
+            order: Order::Desc,
+            _marker: PhantomData,
         }
     }
+
+    /// Sets the order in which the documents are sorted.
+    ///
+    /// With `Order::Asc`, the collector keeps the K documents with
+    /// the lowest values, e.g. the cheapest products.
+    pub fn order(mut self, order: Order) -> TopDocsByField<T> {
+        self.order = order;
+        self
+    }
 }
 
 impl<T: FastValue + PartialOrd + Send + Sync + 'static> Collector for TopDocsByField<T> {
@@ -98,7 +144,11 @@ impl<T: FastValue + PartialOrd + Send + Sync + 'static> Collector for TopDocsByF
     ) -> Result<TopFieldSegmentCollector<T>> {
         let collector = self.collector.for_segment(segment_local_id, reader)?;
         let reader = reader.fast_field_reader(self.field)?;
-        Ok(TopFieldSegmentCollector { collector, reader })
+        Ok(TopFieldSegmentCollector {
+            collector,
+            reader,
+            order: self.order,
+        })
     }
 
     fn requires_scoring(&self) -> bool {
@@ -109,13 +159,24 @@ impl<T: FastValue + PartialOrd + Send + Sync + 'static> Collector for TopDocsByF
         &self,
         segment_fruits: Vec<Vec<(T, DocAddress)>>,
     ) -> Result<Vec<(T, DocAddress)>> {
-        self.collector.merge_fruits(segment_fruits)
+        let segment_keys: Vec<Vec<(u64, DocAddress)>> = segment_fruits
+            .into_iter()
+            .map(|segment_fruit| {
+                segment_fruit
+                    .into_iter()
+                    .map(|(val, doc_address)| (self.order.sort_key(val.to_u64()), doc_address))
+                    .collect()
+            })
+            .collect();
+        let keys = self.collector.merge_fruits(segment_keys)?;
+        Ok(self.order.to_fast_values(keys))
     }
 }
 
 pub struct TopFieldSegmentCollector<T: FastValue + PartialOrd> {
-    collector: TopSegmentCollector<T>,
+    collector: TopSegmentCollector<u64>,
     reader: FastFieldReader<T>,
+    order: Order,
 }
 
 impl<T: FastValue + PartialOrd + Send + Sync + 'static> SegmentCollector
@@ -125,17 +186,22 @@ impl<T: FastValue + PartialOrd + Send + Sync + 'static> SegmentCollector
 
     fn collect(&mut self, doc: u32, _score: f32) {
         let field_value = self.reader.get(doc);
-        self.collector.collect(doc, field_value);
+        self.collector
+            .collect(doc, self.order.sort_key(field_value.to_u64()));
     }
 
     fn harvest(self) -> Vec<(T, DocAddress)> {
-        self.collector.harvest()
+        self.order.to_fast_values(self.collector.harvest())
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.collector.memory_usage()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::TopDocsByField;
+    use super::{Order, TopDocsByField};
     use collector::Collector;
     use collector::TopDocs;
     use query::Query;
@@ -158,18 +224,24 @@ mod tests {
         let size = schema_builder.add_u64_field(SIZE, FAST);
         let schema = schema_builder.build();
         let (index, query) = index("beer", title, schema, |index_writer| {
-            index_writer.add_document(doc!(
-                title => "bottle of beer",
-                size => 12u64,
-            ));
-            index_writer.add_document(doc!(
-                title => "growler of beer",
-                size => 64u64,
-            ));
-            index_writer.add_document(doc!(
-                title => "pint of beer",
-                size => 16u64,
-            ));
+            index_writer
+                .add_document(doc!(
+                    title => "bottle of beer",
+                    size => 12u64,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "growler of beer",
+                    size => 64u64,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "pint of beer",
+                    size => 16u64,
+                ))
+                .unwrap();
         });
         let searcher = index.searcher();
 
@@ -185,6 +257,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_top_field_collector_asc() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_i64_field(SIZE, FAST);
+        let schema = schema_builder.build();
+        let (index, query) = index("beer", title, schema, |index_writer| {
+            index_writer
+                .add_document(doc!(
+                    title => "bottle of beer",
+                    size => 12i64,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "growler of beer",
+                    size => 64i64,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "pint of beer",
+                    size => -16i64,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "can of beer",
+                    size => 33i64,
+                ))
+                .unwrap();
+        });
+        let searcher = index.searcher();
+
+        let top_collector = TopDocs::with_limit(3)
+            .order_by_field(size)
+            .order(Order::Asc);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![
+                (-16, DocAddress(0, 2)),
+                (12, DocAddress(0, 0)),
+                (33, DocAddress(0, 3))
+            ]
+        );
+
+        let top_collector = TopDocs::with_limit(3)
+            .order_by_field(size)
+            .order(Order::Desc);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![
+                (64, DocAddress(0, 1)),
+                (33, DocAddress(0, 3)),
+                (12, DocAddress(0, 0))
+            ]
+        );
+    }
+
     #[test]
     #[should_panic]
     fn test_field_does_not_exist() {
@@ -193,10 +326,12 @@ mod tests {
         let size = schema_builder.add_u64_field(SIZE, FAST);
         let schema = schema_builder.build();
         let (index, _) = index("beer", title, schema, |index_writer| {
-            index_writer.add_document(doc!(
-                title => "bottle of beer",
-                size => 12u64,
-            ));
+            index_writer
+                .add_document(doc!(
+                    title => "bottle of beer",
+                    size => 12u64,
+                ))
+                .unwrap();
         });
         let searcher = index.searcher();
         let top_collector: TopDocsByField<u64> = TopDocs::with_limit(4).order_by_field(Field(2));
@@ -213,10 +348,12 @@ mod tests {
         let size = schema_builder.add_u64_field(SIZE, IntOptions::default());
         let schema = schema_builder.build();
         let (index, _) = index("beer", title, schema, |index_writer| {
-            index_writer.add_document(doc!(
-                title => "bottle of beer",
-                size => 12u64,
-            ));
+            index_writer
+                .add_document(doc!(
+                    title => "bottle of beer",
+                    size => 12u64,
+                ))
+                .unwrap();
         });
         let searcher = index.searcher();
         let segment = searcher.segment_reader(0);
@@ -0,0 +1,266 @@
+use super::Collector;
+use collector::top_collector::TopCollector;
+use collector::top_collector::TopSegmentCollector;
+use collector::SegmentCollector;
+use fastfield::FastFieldReader;
+use fastfield::FastValue;
+use schema::Field;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::mem;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// The Top Group Collector groups the documents by the value of
+/// a fast field, and keeps track of the K best documents of each group.
+///
+/// The groups are returned ordered by the score of their best document,
+/// and only the best `num_groups` groups are returned.
+/// Within a group, the documents are sorted by decreasing score.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{Index, Result};
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::QueryParser;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let brand = schema_builder.add_u64_field("brand", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         index_writer.add_document(doc!(title => "red camera", brand => 1u64))?;
+///         index_writer.add_document(doc!(title => "red camera red case", brand => 1u64))?;
+///         index_writer.add_document(doc!(title => "blue camera", brand => 2u64))?;
+///         index_writer.add_document(doc!(title => "camera lens", brand => 3u64))?;
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let query_parser = QueryParser::for_index(&index, vec![title]);
+///     let query = query_parser.parse_query("red camera")?;
+///     // the 2 best documents of each of the 2 best brands.
+///     let top_docs_by_brand = TopDocs::with_limit(2).group_by_field(brand, 2);
+///     let groups: Vec<(u64, Vec<_>)> = searcher.search(&query, &top_docs_by_brand)?;
+///
+///     assert_eq!(groups.len(), 2);
+///     assert_eq!(groups[0].0, 1u64);
+///     assert_eq!(groups[0].1.len(), 2);
+///     Ok(())
+/// }
+/// ```
+pub struct TopDocsByGroup<T> {
+    collector: TopCollector<Score>,
+    field: Field,
+    num_groups: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FastValue + Eq + Hash + PartialOrd> TopDocsByGroup<T> {
+    /// Creates a top group collector, keeping `limit` documents for
+    /// each of the `num_groups` best groups.
+    ///
+    /// The given field must be a single value fast field, otherwise the
+    /// collector returns an error while collecting results.
+    ///
+    /// # Panics
+    /// The method panics if `limit` or `num_groups` is 0
+    pub(crate) fn new(field: Field, num_groups: usize, limit: usize) -> TopDocsByGroup<T> {
+        if num_groups < 1 {
+            panic!("The number of groups must be strictly greater than 0.");
+        }
+        TopDocsByGroup {
+            collector: TopCollector::with_limit(limit),
+            field,
+            num_groups,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Collector for TopDocsByGroup<T>
+where
+    T: FastValue + Eq + Hash + PartialOrd + Send + Sync + 'static,
+{
+    type Fruit = Vec<(T, Vec<(Score, DocAddress)>)>;
+
+    type Child = TopGroupSegmentCollector<T>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<TopGroupSegmentCollector<T>> {
+        Ok(TopGroupSegmentCollector {
+            limit: self.collector.limit(),
+            segment_local_id,
+            ff_reader: reader.fast_field_reader(self.field)?,
+            groups: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit> {
+        // Every segment keeps all of its groups, as the best documents of
+        // a group may come from a segment where the group is not among
+        // the best ones.
+        let mut group_fruits: HashMap<T, Vec<Vec<(Score, DocAddress)>>> = HashMap::new();
+        for segment_fruit in segment_fruits {
+            for (group, top_docs) in segment_fruit {
+                group_fruits
+                    .entry(group)
+                    .or_insert_with(Vec::new)
+                    .push(top_docs);
+            }
+        }
+        let mut groups = Vec::with_capacity(group_fruits.len());
+        for (group, top_docs) in group_fruits {
+            groups.push((group, self.collector.merge_fruits(top_docs)?));
+        }
+        groups.sort_by(|left, right| {
+            let left_score = left.1.first().map(|&(score, _)| score);
+            let right_score = right.1.first().map(|&(score, _)| score);
+            right_score
+                .partial_cmp(&left_score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.0.partial_cmp(&right.0).unwrap_or(Ordering::Equal))
+        });
+        groups.truncate(self.num_groups);
+        Ok(groups)
+    }
+}
+
+/// Segment Collector associated to `TopDocsByGroup`.
+pub struct TopGroupSegmentCollector<T: FastValue> {
+    limit: usize,
+    segment_local_id: SegmentLocalId,
+    ff_reader: FastFieldReader<T>,
+    groups: HashMap<T, TopSegmentCollector<Score>>,
+}
+
+impl<T> SegmentCollector for TopGroupSegmentCollector<T>
+where
+    T: FastValue + Eq + Hash + PartialOrd + Send + Sync + 'static,
+{
+    type Fruit = Vec<(T, Vec<(Score, DocAddress)>)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let group = self.ff_reader.get(doc);
+        let (segment_local_id, limit) = (self.segment_local_id, self.limit);
+        self.groups
+            .entry(group)
+            .or_insert_with(|| TopSegmentCollector::new(segment_local_id, limit))
+            .collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.groups
+            .into_iter()
+            .map(|(group, collector)| (group, collector.harvest()))
+            .collect()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.groups
+            .values()
+            .map(|collector| mem::size_of::<T>() + collector.memory_usage())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collector::TopDocs;
+    use query::QueryParser;
+    use schema::{Schema, FAST, TEXT};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_top_docs_by_group() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let brand = schema_builder.add_i64_field("brand", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(title => "beer", brand => 1i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "beer beer beer", brand => -2i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "beer beer", brand => 1i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "wine", brand => 4i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(title => "beer beer", brand => 3i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "beer", brand => -2i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        // the segments are not sorted by creation.
+        let (first, second) = if searcher.segment_reader(0).max_doc() == 4 {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        let query = QueryParser::for_index(&index, vec![title])
+            .parse_query("beer")
+            .unwrap();
+
+        let groups: Vec<(i64, Vec<_>)> = searcher
+            .search(&query, &TopDocs::with_limit(2).group_by_field(brand, 10))
+            .unwrap();
+        let group_docs: Vec<(i64, Vec<DocAddress>)> = groups
+            .iter()
+            .map(|&(group, ref top_docs)| {
+                let docs = top_docs.iter().map(|&(_, doc)| doc).collect();
+                (group, docs)
+            })
+            .collect();
+        assert_eq!(
+            group_docs,
+            vec![
+                (-2, vec![DocAddress(first, 1), DocAddress(second, 1)]),
+                (1, vec![DocAddress(first, 2), DocAddress(first, 0)]),
+                (3, vec![DocAddress(second, 0)]),
+            ]
+        );
+        for &(_, ref top_docs) in &groups {
+            assert!(top_docs.windows(2).all(|docs| docs[0].0 >= docs[1].0));
+        }
+
+        let groups: Vec<(i64, Vec<_>)> = searcher
+            .search(&query, &TopDocs::with_limit(1).group_by_field(brand, 2))
+            .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, -2);
+        assert_eq!(groups[0].1.len(), 1);
+        assert_eq!(groups[1].0, 1);
+    }
+}
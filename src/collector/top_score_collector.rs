@@ -3,8 +3,10 @@ use collector::top_collector::TopCollector;
 use collector::top_collector::TopSegmentCollector;
 use collector::SegmentCollector;
 use collector::TopDocsByField;
+use collector::TopDocsByGroup;
 use fastfield::FastValue;
 use schema::Field;
+use std::hash::Hash;
 use DocAddress;
 use DocId;
 use Result;
@@ -38,16 +40,16 @@ use SegmentReader;
 ///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -85,6 +87,18 @@ impl TopDocs {
     ) -> TopDocsByField<T> {
         TopDocsByField::new(field, self.0.limit())
     }
+
+    /// Groups the documents by the value of a fast field, and keeps
+    /// the top-K documents of each of the `num_groups` best groups.
+    ///
+    /// See [`TopDocsByGroup`](./struct.TopDocsByGroup.html).
+    pub fn group_by_field<T: FastValue + Eq + Hash + PartialOrd>(
+        self,
+        field: Field,
+        num_groups: usize,
+    ) -> TopDocsByGroup<T> {
+        TopDocsByGroup::new(field, num_groups, self.0.limit())
+    }
 }
 
 impl Collector for TopDocs {
@@ -123,6 +137,10 @@ impl SegmentCollector for TopScoreSegmentCollector {
     fn harvest(self) -> Vec<(Score, DocAddress)> {
         self.0.harvest()
     }
+
+    fn memory_usage(&self) -> usize {
+        self.0.memory_usage()
+    }
 }
 
 #[cfg(test)]
@@ -143,9 +161,15 @@ mod tests {
         {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"Hello happy tax payer."));
-            index_writer.add_document(doc!(text_field=>"Droopy says hello happy tax payer"));
-            index_writer.add_document(doc!(text_field=>"I like Droopy"));
+            index_writer
+                .add_document(doc!(text_field=>"Hello happy tax payer."))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"Droopy says hello happy tax payer"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"I like Droopy"))
+                .unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
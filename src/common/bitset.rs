@@ -1,4 +1,5 @@
 use std::fmt;
+use std::mem;
 use std::u64;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -119,6 +120,11 @@ impl TinySet {
     }
 }
 
+/// A set of `u32` elements within `[0, max_value[`,
+/// stored as a bitmap.
+///
+/// It can be turned into a `DocSet` via
+/// [`BitSetDocSet`](../query/struct.BitSetDocSet.html).
 #[derive(Clone)]
 pub struct BitSet {
     tinysets: Box<[TinySet]>,
@@ -187,6 +193,7 @@ impl BitSet {
             .map(|delta_bucket| bucket + delta_bucket as u32)
     }
 
+    /// Returns the exclusive upper bound of the elements of the `BitSet`.
     pub fn max_value(&self) -> u32 {
         self.max_value
     }
@@ -197,6 +204,11 @@ impl BitSet {
     pub(crate) fn tinyset(&self, bucket: u32) -> TinySet {
         self.tinysets[bucket as usize]
     }
+
+    /// Returns the number of bytes allocated by the bitset.
+    pub(crate) fn num_bytes(&self) -> usize {
+        self.tinysets.len() * mem::size_of::<TinySet>()
+    }
 }
 
 #[cfg(test)]
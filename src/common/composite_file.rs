@@ -2,7 +2,7 @@ use common::BinarySerializable;
 use common::CountingWriter;
 use common::VInt;
 use directory::ReadOnlySource;
-use directory::WritePtr;
+use directory::{SeekableWrite, WritePtr};
 use schema::Field;
 use space_usage::FieldUsage;
 use space_usage::PerFieldSpaceUsage;
@@ -42,7 +42,7 @@ pub struct CompositeWrite<W = WritePtr> {
     offsets: HashMap<FileAddr, usize>,
 }
 
-impl<W: Write> CompositeWrite<W> {
+impl<W: SeekableWrite> CompositeWrite<W> {
     /// Crate a new API writer that writes a composite file
     /// in a given write.
     pub fn wrap(w: W) -> CompositeWrite<W> {
@@ -69,7 +69,8 @@ impl<W: Write> CompositeWrite<W> {
     /// Close the composite file
     ///
     /// An index of the different field offsets
-    /// will be written as a footer.
+    /// will be written as a footer, and the underlying
+    /// write is terminated.
     pub fn close(mut self) -> io::Result<()> {
         let footer_offset = self.write.written_bytes();
         VInt(self.offsets.len() as u64).serialize(&mut self.write)?;
@@ -91,8 +92,8 @@ impl<W: Write> CompositeWrite<W> {
 
         let footer_len = (self.write.written_bytes() - footer_offset) as u32;
         footer_len.serialize(&mut self.write)?;
-        self.write.flush()?;
-        Ok(())
+        let (mut write, _) = self.write.finish()?;
+        write.terminate()
     }
 }
 
@@ -169,6 +170,25 @@ impl CompositeFile {
             .map(|&(from, to)| self.data.slice(from, to))
     }
 
+    /// Loads the files of all of the fields in the page cache.
+    ///
+    /// See `ReadOnlySource::warm`.
+    pub fn warm(&self) {
+        self.data.warm();
+    }
+
+    /// Returns the `(field, idx)` addresses of the files
+    /// stored in the composite file, sorted.
+    pub fn file_addrs(&self) -> Vec<(Field, usize)> {
+        let mut file_addrs: Vec<(Field, usize)> = self
+            .offsets_index
+            .keys()
+            .map(|file_addr| (file_addr.field, file_addr.idx))
+            .collect();
+        file_addrs.sort();
+        file_addrs
+    }
+
     pub fn space_usage(&self) -> PerFieldSpaceUsage {
         let mut fields = HashMap::new();
         for (&field_addr, &(start, end)) in self.offsets_index.iter() {
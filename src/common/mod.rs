@@ -13,7 +13,10 @@ pub use self::serialize::{BinarySerializable, FixedSize};
 pub use self::vint::VInt;
 pub use byteorder::LittleEndian as Endianness;
 
+use chrono::{TimeZone, Utc};
 use std::io;
+use std::net::{IpAddr, Ipv6Addr};
+use DateTime;
 
 /// Computes the number of bits that will be used for bitpacking.
 ///
@@ -101,11 +104,117 @@ pub fn u64_to_i64(val: u64) -> i64 {
     (val ^ HIGHEST_BIT) as i64
 }
 
+/// Maps a `f64` to `u64`
+///
+/// For simplicity, tantivy internally handles `f64` as `u64`.
+/// The mapping is defined by this function.
+///
+/// Maps `f64` to `u64` so that the order is preserved:
+/// the sign bit of the positive values is flipped, and all of
+/// the bits of the negative values are flipped.
+///
+/// `-0.0` is mapped right before `0.0`, and the `NaN` values
+/// end up at both ends of the `u64` range, depending on their sign.
+///
+/// # See also
+/// The [reverse mapping is `u64_to_f64`](./fn.u64_to_f64.html).
+#[inline(always)]
+pub fn f64_to_u64(val: f64) -> u64 {
+    let bits = val.to_bits();
+    if bits & HIGHEST_BIT == 0 {
+        bits ^ HIGHEST_BIT
+    } else {
+        !bits
+    }
+}
+
+/// Reverse the mapping given by [`f64_to_u64`](./fn.f64_to_u64.html).
+#[inline(always)]
+pub fn u64_to_f64(val: u64) -> f64 {
+    f64::from_bits(if val & HIGHEST_BIT != 0 {
+        val ^ HIGHEST_BIT
+    } else {
+        !val
+    })
+}
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Maps a `DateTime` to the number of nanoseconds since
+/// the UNIX epoch, as tantivy internally handles the dates.
+///
+/// The dates out of the range of this representation, before 1677 or
+/// after 2262, are saturated to `i64::min_value()` or `i64::max_value()`.
+///
+/// # See also
+/// The [reverse mapping is `i64_to_date`](./fn.i64_to_date.html).
+pub fn date_to_i64(date: &DateTime) -> i64 {
+    let secs = date.timestamp();
+    secs.checked_mul(NANOS_PER_SEC)
+        .and_then(|nanos| nanos.checked_add(i64::from(date.timestamp_subsec_nanos())))
+        .unwrap_or_else(|| {
+            if secs < 0 {
+                i64::min_value()
+            } else {
+                i64::max_value()
+            }
+        })
+}
+
+/// Reverse the mapping given by [`date_to_i64`](./fn.date_to_i64.html).
+pub fn i64_to_date(val: i64) -> DateTime {
+    let mut secs = val / NANOS_PER_SEC;
+    let mut nanos = val % NANOS_PER_SEC;
+    if nanos < 0 {
+        secs -= 1;
+        nanos += NANOS_PER_SEC;
+    }
+    Utc.timestamp(secs, nanos as u32)
+}
+
+/// Maps an IP address to the IPv6 address tantivy internally handles.
+///
+/// IPv4 addresses are mapped into IPv6 (`::ffff:a.b.c.d`), so that
+/// both kinds of addresses can be held by the same field.
+pub fn ip_addr_to_ipv6(ip_addr: IpAddr) -> Ipv6Addr {
+    match ip_addr {
+        IpAddr::V4(ipv4_addr) => ipv4_addr.to_ipv6_mapped(),
+        IpAddr::V6(ipv6_addr) => ipv6_addr,
+    }
+}
+
+/// Returns the first and the last addresses of the CIDR block
+/// made of the addresses sharing the `prefix_len` first bits of `ip_addr`,
+/// mapped into IPv6 as by `ip_addr_to_ipv6`.
+///
+/// The prefix of an IPv4 block is relative to the IPv4 address,
+/// e.g. `10.0.0.0/8`. Returns `None` if the prefix is longer than the address.
+pub(crate) fn ip_cidr_range(ip_addr: IpAddr, prefix_len: u8) -> Option<(Ipv6Addr, Ipv6Addr)> {
+    let addr_len: u32 = if ip_addr.is_ipv4() { 32 } else { 128 };
+    if u32::from(prefix_len) > addr_len {
+        return None;
+    }
+    let ipv6_prefix_len = u32::from(prefix_len) + 128 - addr_len;
+    let host_mask = u128::max_value()
+        .checked_shr(ipv6_prefix_len)
+        .unwrap_or(0u128);
+    let val = u128::from(ip_addr_to_ipv6(ip_addr));
+    Some((
+        Ipv6Addr::from(val & !host_mask),
+        Ipv6Addr::from(val | host_mask),
+    ))
+}
+
 #[cfg(test)]
 pub(crate) mod test {
 
     pub use super::serialize::test::fixed_size_test;
-    use super::{compute_num_bits, i64_to_u64, u64_to_i64};
+    use super::{compute_num_bits, f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64};
+    use super::{date_to_i64, i64_to_date};
+    use super::{ip_addr_to_ipv6, ip_cidr_range};
+    use chrono::{TimeZone, Utc};
+    use std::f64;
+    use std::net::{IpAddr, Ipv6Addr};
 
     fn test_i64_converter_helper(val: i64) {
         assert_eq!(u64_to_i64(i64_to_u64(val)), val);
@@ -123,6 +232,106 @@ pub(crate) mod test {
         }
     }
 
+    fn test_f64_converter_helper(val: f64) {
+        assert_eq!(u64_to_f64(f64_to_u64(val)).to_bits(), val.to_bits());
+    }
+
+    #[test]
+    fn test_f64_converter() {
+        test_f64_converter_helper(f64::INFINITY);
+        test_f64_converter_helper(f64::NEG_INFINITY);
+        test_f64_converter_helper(0.0);
+        test_f64_converter_helper(-0.0);
+        test_f64_converter_helper(f64::MIN_POSITIVE);
+        let values = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1000.5,
+            -1.0,
+            -f64::MIN_POSITIVE,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            1.0,
+            1000.5,
+            f64::MAX,
+            f64::INFINITY,
+        ];
+        for window in values.windows(2) {
+            test_f64_converter_helper(window[0]);
+            assert!(f64_to_u64(window[0]) < f64_to_u64(window[1]));
+        }
+        assert!(u64_to_f64(f64_to_u64(f64::NAN)).is_nan());
+    }
+
+    #[test]
+    fn test_date_converter() {
+        let dates = [
+            Utc.ymd(1677, 9, 22).and_hms(0, 12, 44),
+            Utc.ymd(1969, 12, 31).and_hms_nano(23, 59, 59, 999_999_999),
+            Utc.timestamp(0, 0),
+            Utc.ymd(2019, 3, 14).and_hms_milli(15, 9, 26, 535),
+            Utc.ymd(2262, 4, 11).and_hms(23, 47, 16),
+        ];
+        for window in dates.windows(2) {
+            assert_eq!(i64_to_date(date_to_i64(&window[0])), window[0]);
+            assert!(date_to_i64(&window[0]) < date_to_i64(&window[1]));
+        }
+        assert_eq!(date_to_i64(&Utc.timestamp(0, 0)), 0);
+        assert_eq!(date_to_i64(&Utc.timestamp(-1, 1)), -999_999_999);
+        assert_eq!(
+            date_to_i64(&Utc.ymd(1000, 1, 1).and_hms(0, 0, 0)),
+            i64::min_value()
+        );
+        assert_eq!(
+            date_to_i64(&Utc.ymd(3000, 1, 1).and_hms(0, 0, 0)),
+            i64::max_value()
+        );
+    }
+
+    #[test]
+    fn test_ip_addr_converter() {
+        let ipv4_addr: IpAddr = "192.168.0.1".parse().unwrap();
+        let ipv6_addr: Ipv6Addr = "::ffff:192.168.0.1".parse().unwrap();
+        assert_eq!(ip_addr_to_ipv6(ipv4_addr), ipv6_addr);
+        assert_eq!(ip_addr_to_ipv6(IpAddr::V6(ipv6_addr)), ipv6_addr);
+    }
+
+    #[test]
+    fn test_ip_cidr_range() {
+        let range = |ip: &str, prefix_len: u8| {
+            ip_cidr_range(ip.parse().unwrap(), prefix_len)
+                .map(|(first, last)| (first.to_string(), last.to_string()))
+        };
+        let expected = |first: &str, last: &str| Some((first.to_string(), last.to_string()));
+        assert_eq!(
+            range("10.1.2.3", 8),
+            expected("::ffff:10.0.0.0", "::ffff:10.255.255.255")
+        );
+        assert_eq!(
+            range("10.1.2.3", 32),
+            expected("::ffff:10.1.2.3", "::ffff:10.1.2.3")
+        );
+        assert_eq!(
+            range("10.1.2.3", 0),
+            expected("::ffff:0.0.0.0", "::ffff:255.255.255.255")
+        );
+        assert_eq!(range("10.1.2.3", 33), None);
+        assert_eq!(
+            range("2001:db8::1", 32),
+            expected("2001:db8::", "2001:db8:ffff:ffff:ffff:ffff:ffff:ffff")
+        );
+        assert_eq!(
+            range("2001:db8::1", 0),
+            expected("::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")
+        );
+        assert_eq!(
+            range("2001:db8::1", 128),
+            expected("2001:db8::1", "2001:db8::1")
+        );
+        assert_eq!(range("2001:db8::1", 129), None);
+    }
+
     #[test]
     fn test_compute_num_bits() {
         assert_eq!(compute_num_bits(1), 1u8);
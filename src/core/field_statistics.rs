@@ -0,0 +1,34 @@
+use std::ops::Add;
+
+/// Statistics of an indexed field, over a segment or over
+/// all of the segments of a searcher.
+///
+/// Like the document frequencies of the terms, these statistics
+/// include the deleted documents.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FieldStatistics {
+    /// Number of documents, including those that do not have
+    /// any value for the field.
+    pub num_docs: u64,
+    /// Number of tokens indexed for the field, over all of the documents.
+    pub total_num_tokens: u64,
+}
+
+impl FieldStatistics {
+    /// Returns the average number of tokens per document,
+    /// as used by BM25 for the field norms.
+    pub fn average_fieldnorm(&self) -> f32 {
+        self.total_num_tokens as f32 / self.num_docs as f32
+    }
+}
+
+impl Add for FieldStatistics {
+    type Output = FieldStatistics;
+
+    fn add(self, other: FieldStatistics) -> FieldStatistics {
+        FieldStatistics {
+            num_docs: self.num_docs + other.num_docs,
+            total_num_tokens: self.total_num_tokens + other.total_num_tokens,
+        }
+    }
+}
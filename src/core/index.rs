@@ -1,3 +1,4 @@
+use super::index_meta::check_format_version;
 use super::pool::LeasedItem;
 use super::pool::Pool;
 use super::segment::create_segment;
@@ -5,10 +6,16 @@ use super::segment::Segment;
 use core::searcher::Searcher;
 use core::Executor;
 use core::IndexMeta;
+use core::IndexReader;
+use core::ReloadPolicy;
+use core::SegmentComponent;
 use core::SegmentId;
 use core::SegmentMeta;
 use core::SegmentReader;
+use core::ShardDescriptor;
+use core::INDEX_FORMAT_VERSION;
 use core::META_FILEPATH;
+use directory::Footer;
 use directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
 use directory::MmapDirectory;
@@ -16,18 +23,26 @@ use directory::{Directory, RAMDirectory};
 use error::TantivyError;
 use indexer::index_writer::open_index_writer;
 use indexer::index_writer::HEAP_SIZE_MIN;
-use indexer::segment_updater::save_new_metas;
+use indexer::segment_updater::{save_metas, save_new_metas};
+use indexer::split_index;
+use indexer::DirectoryLock;
 use indexer::LockType;
 use num_cpus;
+use postings::PostingsCodec;
 use schema::Field;
 use schema::FieldType;
 use schema::Schema;
+use schema::SchemaBuilder;
 use serde_json;
 use std::borrow::BorrowMut;
+use std::collections::HashSet;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use store::StoreCompression;
+use termdict::TermDictionaryCodec;
 use tokenizer::BoxedTokenizer;
 use tokenizer::TokenizerManager;
 use IndexWriter;
@@ -36,6 +51,7 @@ use Result;
 fn load_metas(directory: &Directory) -> Result<IndexMeta> {
     let meta_data = directory.atomic_read(&META_FILEPATH)?;
     let meta_string = String::from_utf8_lossy(&meta_data);
+    check_format_version(&meta_string)?;
     serde_json::from_str(&meta_string)
         .map_err(|_| TantivyError::CorruptedFile(META_FILEPATH.clone()))
 }
@@ -48,6 +64,9 @@ pub struct Index {
     searcher_pool: Arc<Pool<Searcher>>,
     executor: Arc<Executor>,
     tokenizers: TokenizerManager,
+    postings_codec: PostingsCodec,
+    store_compression: StoreCompression,
+    term_dictionary_codec: TermDictionaryCodec,
 }
 
 impl Index {
@@ -159,11 +178,63 @@ impl Index {
             searcher_pool: Arc::new(Pool::new()),
             tokenizers: TokenizerManager::default(),
             executor: Arc::new(Executor::single_thread()),
+            postings_codec: PostingsCodec::default(),
+            store_compression: StoreCompression::default(),
+            term_dictionary_codec: TermDictionaryCodec::default(),
         };
         index.load_searchers()?;
         Ok(index)
     }
 
+    /// Returns the codec used to encode the postings of new segments.
+    pub fn postings_codec(&self) -> PostingsCodec {
+        self.postings_codec
+    }
+
+    /// Sets the codec used to encode the postings of the segments
+    /// created from now on, by indexing or by merging.
+    ///
+    /// Existing segments are not affected: the codec is recorded in
+    /// each segment's meta, and segments using different codecs can be
+    /// searched together.
+    ///
+    /// This only affects the `IndexWriter`s created after the call.
+    pub fn set_postings_codec(&mut self, postings_codec: PostingsCodec) {
+        self.postings_codec = postings_codec;
+    }
+
+    /// Returns the compression used for the doc store of new segments.
+    pub fn store_compression(&self) -> StoreCompression {
+        self.store_compression
+    }
+
+    /// Sets the compression used for the doc store of the segments
+    /// created from now on, by indexing or by merging.
+    ///
+    /// As for the postings codec, the compression is recorded
+    /// in each segment's meta.
+    ///
+    /// This only affects the `IndexWriter`s created after the call.
+    pub fn set_store_compression(&mut self, store_compression: StoreCompression) {
+        self.store_compression = store_compression;
+    }
+
+    /// Returns the codec used for the term dictionaries of new segments.
+    pub fn term_dictionary_codec(&self) -> TermDictionaryCodec {
+        self.term_dictionary_codec
+    }
+
+    /// Sets the codec used for the term dictionaries of the segments
+    /// created from now on, by indexing or by merging.
+    ///
+    /// As for the postings codec, the codec is recorded
+    /// in each segment's meta.
+    ///
+    /// This only affects the `IndexWriter`s created after the call.
+    pub fn set_term_dictionary_codec(&mut self, term_dictionary_codec: TermDictionaryCodec) {
+        self.term_dictionary_codec = term_dictionary_codec;
+    }
+
     /// Accessor for the tokenizer manager.
     pub fn tokenizers(&self) -> &TokenizerManager {
         &self.tokenizers
@@ -209,6 +280,129 @@ impl Index {
         load_metas(self.directory())
     }
 
+    /// Appends new fields to the schema of the index.
+    ///
+    /// `add_fields` is given a `SchemaBuilder` containing the
+    /// current fields of the schema, and adds the new fields to it.
+    /// Its result, typically the new `Field` handles, is returned.
+    ///
+    /// The existing segments are not rewritten: the new fields are
+    /// missing from them, as if none of their documents had a value for
+    /// these fields. Each segment records the number of fields of the schema
+    /// it was written with, and the schema version recorded in the
+    /// `meta.json` file is incremented.
+    ///
+    /// The index writer lock is acquired during the update, so that
+    /// this fails if an `IndexWriter` is opened.
+    /// The clones of the `Index` created before the call keep the
+    /// previous schema, and cannot open an `IndexWriter` anymore.
+    ///
+    /// # Errors
+    /// Returns a `SchemaError` if one of the new fields has the
+    /// name of another field.
+    pub fn add_fields<F, R>(&mut self, add_fields: F) -> Result<R>
+    where
+        F: FnOnce(&mut SchemaBuilder) -> R,
+    {
+        let _directory_lock = LockType::IndexWriterLock.acquire_lock(&self.directory)?;
+        let metas = self.load_metas()?;
+        let num_fields = metas.schema.fields().len();
+        let mut schema_builder = metas.schema.to_builder();
+        let result = add_fields(&mut schema_builder);
+        let schema = schema_builder.build();
+        for (field_id, field_entry) in schema.fields().iter().enumerate() {
+            if schema.get_field(field_entry.name()) != Some(Field(field_id as u32)) {
+                return Err(TantivyError::SchemaError(format!(
+                    "A field named {:?} already exists.",
+                    field_entry.name()
+                )));
+            }
+        }
+        let segments = metas
+            .segments
+            .into_iter()
+            .map(|segment_meta| match segment_meta.num_fields() {
+                Some(_) => segment_meta,
+                None => segment_meta.with_num_fields(num_fields as u32),
+            })
+            .collect();
+        let new_metas = IndexMeta {
+            segments,
+            schema: schema.clone(),
+            opstamp: metas.opstamp,
+            payload: metas.payload,
+            generation: metas.generation + 1,
+            schema_version: metas.schema_version + 1,
+            shard: metas.shard,
+            index_format_version: INDEX_FORMAT_VERSION,
+        };
+        save_metas(&new_metas, &mut self.directory)?;
+        self.schema = schema;
+        self.load_searchers()?;
+        Ok(result)
+    }
+
+    /// Returns the shard descriptor recorded in the `meta.json` file,
+    /// if any.
+    pub fn shard_descriptor(&self) -> Result<Option<ShardDescriptor>> {
+        Ok(self.load_metas()?.shard)
+    }
+
+    /// Records the shard descriptor of the index in the `meta.json` file.
+    /// `None` removes the descriptor.
+    ///
+    /// The index writer lock is acquired during the update, so that
+    /// this fails if an `IndexWriter` is opened.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if the descriptor
+    /// is an empty hash range.
+    pub fn set_shard_descriptor(&mut self, shard: Option<ShardDescriptor>) -> Result<()> {
+        if let Some(ShardDescriptor::HashRange { start, end }) = shard {
+            if start >= end {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The hash range [{}, {}) is empty.",
+                    start, end
+                )));
+            }
+        }
+        let _directory_lock = LockType::IndexWriterLock.acquire_lock(&self.directory)?;
+        let metas = self.load_metas()?;
+        let new_metas = IndexMeta {
+            generation: metas.generation + 1,
+            shard,
+            index_format_version: INDEX_FORMAT_VERSION,
+            ..metas
+        };
+        save_metas(&new_metas, &mut self.directory)
+    }
+
+    /// Splits the index in as many new indexes as there are
+    /// `target_directories`, by hash of the routing key `key_field`.
+    ///
+    /// The documents of the last commit are streamed segment by segment
+    /// through a merge filtering out the documents of the other indexes,
+    /// so that each new index is made of a single segment.
+    /// The key of a document is the first value of `key_field`,
+    /// and the new indexes record the shard descriptor of the
+    /// range of hashes of their keys, see
+    /// [`ShardDescriptor::hash_key`](./struct.ShardDescriptor.html#method.hash_key).
+    ///
+    /// The new indexes have the schema, the postings codec
+    /// and the store compression of the index.
+    ///
+    /// # Errors
+    /// Returns a `SchemaError` if `key_field` is neither stored nor
+    /// a single value fast field, and `IndexAlreadyExists` if one of the
+    /// target directories already contains an index.
+    pub fn split<Dir: Directory>(
+        &self,
+        key_field: Field,
+        target_directories: Vec<Dir>,
+    ) -> Result<Vec<Index>> {
+        split_index(self, key_field, target_directories)
+    }
+
     /// Open a new index writer. Attempts to acquire a lockfile.
     ///
     /// The lockfile should be deleted on drop, but it is possible
@@ -225,7 +419,7 @@ impl Index {
     /// Each thread will receive a budget of  `overall_heap_size_in_bytes / num_threads`.
     ///
     /// # Errors
-    /// If the lockfile already exists, returns `Error::FileAlreadyExists`.
+    /// If the lockfile already exists, returns `TantivyError::LockFailure`.
     /// # Panics
     /// If the heap size per thread is too small, panics.
     pub fn writer_with_num_threads(
@@ -234,6 +428,44 @@ impl Index {
         overall_heap_size_in_bytes: usize,
     ) -> Result<IndexWriter> {
         let directory_lock = LockType::IndexWriterLock.acquire_lock(&self.directory)?;
+        self.open_writer(num_threads, overall_heap_size_in_bytes, directory_lock)
+    }
+
+    /// Same as `writer_with_num_threads`, but if another `IndexWriter`
+    /// holds the lockfile, waits for at most `lock_timeout` for it
+    /// to be released.
+    ///
+    /// This is useful when the writer of another process is
+    /// about to be dropped, e.g. during a rolling restart.
+    ///
+    /// # Errors
+    /// If the lockfile still exists after `lock_timeout`,
+    /// returns `TantivyError::LockFailure`.
+    /// # Panics
+    /// If the heap size per thread is too small, panics.
+    pub fn writer_with_lock_timeout(
+        &self,
+        num_threads: usize,
+        overall_heap_size_in_bytes: usize,
+        lock_timeout: Duration,
+    ) -> Result<IndexWriter> {
+        let directory_lock =
+            LockType::IndexWriterLock.acquire_lock_with_timeout(&self.directory, lock_timeout)?;
+        self.open_writer(num_threads, overall_heap_size_in_bytes, directory_lock)
+    }
+
+    fn open_writer(
+        &self,
+        num_threads: usize,
+        overall_heap_size_in_bytes: usize,
+        directory_lock: DirectoryLock,
+    ) -> Result<IndexWriter> {
+        if self.load_metas()?.schema != self.schema {
+            return Err(TantivyError::SchemaError(
+                "The schema of the index has changed. The index should be reopened."
+                    .to_string(),
+            ));
+        }
         let heap_size_in_bytes_per_thread = overall_heap_size_in_bytes / num_threads;
         open_index_writer(
             self,
@@ -250,7 +482,7 @@ impl Index {
     /// between a given number of threads.
     ///
     /// # Errors
-    /// If the lockfile already exists, returns `Error::FileAlreadyExists`.
+    /// If the lockfile already exists, returns `TantivyError::LockFailure`.
     /// # Panics
     /// If the heap size per thread is too small, panics.
     pub fn writer(&self, overall_heap_size_in_bytes: usize) -> Result<IndexWriter> {
@@ -285,7 +517,11 @@ impl Index {
 
     /// Creates a new segment.
     pub fn new_segment(&self) -> Segment {
-        let segment_meta = SegmentMeta::new(SegmentId::generate_random(), 0);
+        let segment_meta = SegmentMeta::new(SegmentId::generate_random(), 0)
+            .with_postings_codec(self.postings_codec)
+            .with_store_compression(self.store_compression)
+            .with_term_dictionary_codec(self.term_dictionary_codec)
+            .with_num_fields(self.schema.fields().len() as u32);
         self.segment(segment_meta)
     }
 
@@ -314,6 +550,53 @@ impl Index {
             .collect())
     }
 
+    /// Verifies the checksums of the files of the searchable segments,
+    /// and returns the paths of the damaged files.
+    ///
+    /// The files of the segments written in a versioned format must exist
+    /// and end with a footer, and are reported as damaged otherwise.
+    /// The segments written by the versions of tantivy prior to the
+    /// introduction of the footers only have their footers verified
+    /// where they exist, as their files cannot be told apart from
+    /// truncated ones.
+    pub fn validate_checksum(&self) -> Result<HashSet<PathBuf>> {
+        let mut damaged_files = HashSet::new();
+        for segment_meta in self.searchable_segment_metas()? {
+            let footer_required = segment_meta.format_version() >= 1;
+            for &component in SegmentComponent::iterator() {
+                let is_written = match component {
+                    SegmentComponent::DELETE => segment_meta.delete_opstamp().is_some(),
+                    SegmentComponent::FASTFIELDUPDATES => segment_meta.has_fast_field_updates(),
+                    _ => true,
+                };
+                if !is_written {
+                    continue;
+                }
+                let path = segment_meta.relative_path(component);
+                if !self.directory.exists(&path) {
+                    if footer_required {
+                        damaged_files.insert(path);
+                    }
+                    continue;
+                }
+                let source = self.directory.open_read(&path)?;
+                match Footer::extract(&source) {
+                    (content, Some(footer)) => {
+                        if !footer.is_valid_checksum(content.as_slice()) {
+                            damaged_files.insert(path);
+                        }
+                    }
+                    (_, None) => {
+                        if footer_required {
+                            damaged_files.insert(path);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(damaged_files)
+    }
+
     /// Sets the number of searchers to use
     ///
     /// Only works after the next call to `load_searchers`
@@ -330,21 +613,58 @@ impl Index {
     ///
     /// If indexing and searching happen in different processes, the way to
     /// get the freshest `index` at all time, is to watch `meta.json` and
-    /// call `load_searchers` whenever a changes happen.
+    /// call `load_searchers` whenever a changes happen. An `IndexReader`
+    /// with the `OnCommit` reload policy does this automatically.
     pub fn load_searchers(&self) -> Result<()> {
+        let num_searchers: usize = self.num_searchers.load(Ordering::Acquire);
+        let searchers = self.open_searchers(num_searchers)?;
+        self.searcher_pool.publish_new_generation(searchers);
+        Ok(())
+    }
+
+    /// Opens `num_searchers` searchers over the state of the last
+    /// `.commit()`, without publishing them in the searcher pool.
+    ///
+    /// The searchers share the same segment readers.
+    pub(crate) fn open_searchers(&self, num_searchers: usize) -> Result<Vec<Searcher>> {
         let _meta_lock = LockType::MetaLock.acquire_lock(self.directory())?;
-        let searchable_segments = self.searchable_segments()?;
-        let segment_readers: Vec<SegmentReader> = searchable_segments
+        let index_meta = self.load_metas()?;
+        let segment_readers: Vec<SegmentReader> = index_meta
+            .segments
             .iter()
-            .map(SegmentReader::open)
+            .map(|segment_meta| SegmentReader::open(&self.segment(segment_meta.clone())))
             .collect::<Result<_>>()?;
         let schema = self.schema();
-        let num_searchers: usize = self.num_searchers.load(Ordering::Acquire);
         let searchers = (0..num_searchers)
-            .map(|_| Searcher::new(schema.clone(), self.clone(), segment_readers.clone()))
+            .map(|_| {
+                Searcher::new(
+                    schema.clone(),
+                    self.clone(),
+                    index_meta.generation,
+                    index_meta.opstamp,
+                    index_meta.segments.clone(),
+                    segment_readers.clone(),
+                )
+            })
             .collect();
-        self.searcher_pool.publish_new_generation(searchers);
-        Ok(())
+        Ok(searchers)
+    }
+
+    /// Opens a searcher over the state of the last `.commit()`,
+    /// without publishing it in the searcher pool.
+    pub(crate) fn open_searcher(&self) -> Result<Searcher> {
+        let mut searchers = self.open_searchers(1)?;
+        Ok(searchers.remove(0))
+    }
+
+    /// Creates an `IndexReader`, with its own pool of `num_searchers`
+    /// searchers over the state of the last `.commit()`.
+    ///
+    /// The searchers of the reader are reloaded as defined
+    /// by `reload_policy`.
+    pub fn reader(&self, reload_policy: ReloadPolicy) -> Result<IndexReader> {
+        let num_searchers: usize = self.num_searchers.load(Ordering::Acquire);
+        IndexReader::new(self.clone(), num_searchers, reload_policy)
     }
 
     /// Returns a searcher
@@ -377,15 +697,27 @@ impl Clone for Index {
             searcher_pool: Arc::clone(&self.searcher_pool),
             tokenizers: self.tokenizers.clone(),
             executor: self.executor.clone(),
+            postings_codec: self.postings_codec,
+            store_compression: self.store_compression,
+            term_dictionary_codec: self.term_dictionary_codec,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use directory::RAMDirectory;
-    use schema::{Schema, INT_INDEXED, TEXT};
+    use collector::Count;
+    use core::SegmentComponent;
+    use core::ShardDescriptor;
+    use directory::Footer;
+    use directory::{Directory, RAMDirectory};
+    use futures::Future;
+    use indexer::NoMergePolicy;
+    use query::TermQuery;
+    use schema::{IndexRecordOption, Schema, FAST, INT_INDEXED, STORED, TEXT};
+    use std::path::PathBuf;
     use Index;
+    use Term;
 
     #[test]
     fn test_indexer_for_field() {
@@ -446,9 +778,238 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_searcher_generation_pinning() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let pinned_searcher = index.searcher().clone();
+        let pinned_generation = pinned_searcher.generation();
+        assert_eq!(pinned_generation, index.load_metas().unwrap().generation);
+
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids[..])
+            .unwrap()
+            .wait()
+            .unwrap();
+        index_writer.garbage_collect_files().unwrap();
+        index.load_searchers().unwrap();
+        assert!(index.searcher().generation() > pinned_generation);
+        assert_eq!(index.searcher().num_docs(), 2);
+
+        let pinned_path = PathBuf::from(format!(
+            "{}.idx",
+            pinned_searcher.segment_reader(0).segment_id().uuid_string()
+        ));
+        assert!(index.directory().exists(&pinned_path));
+        assert_eq!(pinned_searcher.generation(), pinned_generation);
+        assert_eq!(pinned_searcher.num_docs(), 1);
+        drop(pinned_searcher);
+        index_writer.garbage_collect_files().unwrap();
+        assert!(!index.directory().exists(&pinned_path));
+    }
+
+    #[test]
+    fn test_searcher_opstamp() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().opstamp(), 0);
+        let add_opstamp = index_writer.add_document(doc!(text_field => "a")).unwrap();
+        let commit_opstamp = index_writer.commit().unwrap();
+        assert!(add_opstamp < commit_opstamp);
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().opstamp(), commit_opstamp);
+        let delete_opstamp = index_writer
+            .delete_term(Term::from_field_text(text_field, "a"))
+            .unwrap();
+        assert!(delete_opstamp >= index.searcher().opstamp());
+        let second_commit_opstamp = index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        assert!(delete_opstamp < index.searcher().opstamp());
+        assert_eq!(index.searcher().opstamp(), second_commit_opstamp);
+        assert_eq!(index.searcher().num_docs(), 0);
+    }
+
+    #[test]
+    fn test_add_fields() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let directory = RAMDirectory::create();
+        let mut index = Index::create(directory.clone(), schema_builder.build()).unwrap();
+        let stale_index = index.clone();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(body => "a")).unwrap();
+            index_writer.add_document(doc!(body => "a b")).unwrap();
+            index_writer.commit().unwrap();
+            assert!(index.add_fields(|_| ()).is_err());
+        }
+        let (title, price) = index
+            .add_fields(|schema_builder| {
+                (
+                    schema_builder.add_text_field("title", TEXT),
+                    schema_builder.add_u64_field("price", INT_INDEXED | FAST),
+                )
+            })
+            .unwrap();
+        assert_eq!(index.schema().fields().len(), 3);
+        assert_eq!(index.load_metas().unwrap().schema_version, 1);
+        assert!(index
+            .add_fields(|schema_builder| schema_builder.add_text_field("body", TEXT))
+            .is_err());
+        assert!(stale_index.writer_with_num_threads(1, 3_000_000).is_err());
+
+        let count = |index: &Index, term: Term| {
+            index.load_searchers().unwrap();
+            let query = TermQuery::new(term, IndexRecordOption::WithFreqs);
+            index.searcher().search(&query, &Count).unwrap()
+        };
+        {
+            let searcher = index.searcher();
+            let segment_reader = searcher.segment_reader(0);
+            assert!(segment_reader.has_field(body));
+            assert!(!segment_reader.has_field(price));
+            assert!(segment_reader.fast_field_reader::<u64>(price).is_err());
+        }
+        assert_eq!(count(&index, Term::from_field_text(title, "a")), 0);
+
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer
+            .add_document(doc!(body => "a", title => "a", price => 42u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        assert_eq!(count(&index, Term::from_field_text(body, "a")), 3);
+        assert_eq!(count(&index, Term::from_field_text(title, "a")), 1);
+        assert_eq!(count(&index, Term::from_field_u64(price, 42u64)), 1);
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids[..])
+            .unwrap()
+            .wait()
+            .unwrap();
+        index_writer.wait_merging_threads().unwrap();
+
+        let reopened_index = Index::open(directory).unwrap();
+        assert!(reopened_index.schema() == index.schema());
+        assert_eq!(count(&reopened_index, Term::from_field_text(title, "a")), 1);
+        let searcher = reopened_index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert!(segment_reader.has_field(price));
+        let price_reader = segment_reader.fast_field_reader::<u64>(price).unwrap();
+        let mut prices: Vec<u64> = (0..segment_reader.max_doc())
+            .map(|doc| price_reader.get(doc))
+            .collect();
+        prices.sort();
+        assert_eq!(prices, vec![0, 0, 42]);
+    }
+
     fn throw_away_schema() -> Schema {
         let mut schema_builder = Schema::builder();
         let _ = schema_builder.add_u64_field("num_likes", INT_INDEXED);
         schema_builder.build()
     }
+
+    #[test]
+    fn test_shard_descriptor() {
+        let directory = RAMDirectory::create();
+        let mut index = Index::create(directory.clone(), throw_away_schema()).unwrap();
+        assert_eq!(index.shard_descriptor().unwrap(), None);
+        let shard = ShardDescriptor::HashRange {
+            start: 0u64,
+            end: 1u64 << 32,
+        };
+        index.set_shard_descriptor(Some(shard.clone())).unwrap();
+        assert!(index
+            .set_shard_descriptor(Some(ShardDescriptor::HashRange { start: 3, end: 3 }))
+            .is_err());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            assert!(index.set_shard_descriptor(None).is_err());
+            index_writer.add_document(doc!()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        // the descriptor is kept by commits.
+        let reopened_index = Index::open(directory).unwrap();
+        assert_eq!(reopened_index.shard_descriptor().unwrap(), Some(shard));
+        index.set_shard_descriptor(None).unwrap();
+        assert_eq!(reopened_index.shard_descriptor().unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_checksum() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "hello happy tax payer"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        assert!(index.validate_checksum().unwrap().is_empty());
+        let segment_meta = index.searchable_segment_metas().unwrap()[0].clone();
+        let store_path = segment_meta.relative_path(SegmentComponent::STORE);
+        let mut data = index
+            .directory()
+            .open_read(&store_path)
+            .unwrap()
+            .as_slice()
+            .to_owned();
+        data[0] ^= 1u8;
+        index
+            .directory_mut()
+            .atomic_write(&store_path, &data[..])
+            .unwrap();
+        let damaged_files = index.validate_checksum().unwrap();
+        assert_eq!(damaged_files.len(), 1);
+        assert!(damaged_files.contains(&store_path));
+    }
+
+    #[test]
+    fn test_validate_checksum_missing_footer() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "hello happy tax payer"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        let segment_meta = index.searchable_segment_metas().unwrap()[0].clone();
+        assert!(segment_meta.format_version() >= 1);
+        let store_path = segment_meta.relative_path(SegmentComponent::STORE);
+        let content = {
+            let source = index.directory().open_read(&store_path).unwrap();
+            let (content, footer_opt) = Footer::extract(&source);
+            assert!(footer_opt.is_some());
+            content.as_slice().to_owned()
+        };
+        index
+            .directory_mut()
+            .atomic_write(&store_path, &content[..])
+            .unwrap();
+        let fieldnorms_path = segment_meta.relative_path(SegmentComponent::FIELDNORMS);
+        index.directory().delete(&fieldnorms_path).unwrap();
+        let damaged_files = index.validate_checksum().unwrap();
+        assert_eq!(damaged_files.len(), 2);
+        assert!(damaged_files.contains(&store_path));
+        assert!(damaged_files.contains(&fieldnorms_path));
+    }
 }
@@ -1,7 +1,11 @@
 use core::SegmentMeta;
+use core::ShardDescriptor;
+use core::INDEX_FORMAT_VERSION;
+use error::TantivyError;
 use schema::Schema;
 use serde_json;
 use std::fmt;
+use Result;
 
 /// Meta information about the `Index`.
 ///
@@ -10,6 +14,10 @@ use std::fmt;
 /// * the searchable segments,
 /// * the index `docstamp`
 /// * the schema
+/// * the generation, incremented every time the `meta.json` file is written.
+/// * the schema version, incremented every time fields are added to the schema.
+/// * the shard descriptor, if the index is a shard of a larger dataset.
+/// * the version of the index format of the tantivy that wrote the file.
 ///
 #[derive(Clone, Serialize, Deserialize)]
 pub struct IndexMeta {
@@ -18,6 +26,14 @@ pub struct IndexMeta {
     pub opstamp: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
+    #[serde(default)]
+    pub generation: u64,
+    #[serde(default)]
+    pub schema_version: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardDescriptor>,
+    #[serde(default)]
+    pub index_format_version: u32,
 }
 
 impl IndexMeta {
@@ -27,10 +43,59 @@ impl IndexMeta {
             schema,
             opstamp: 0u64,
             payload: None,
+            generation: 0u64,
+            schema_version: 0u64,
+            shard: None,
+            index_format_version: INDEX_FORMAT_VERSION,
         }
     }
 }
 
+/// Versions of the index format recorded in the `meta.json` file.
+///
+/// They are deserialized before the rest of the file, so that an index
+/// written in a more recent format is reported as such, rather than
+/// as a corrupted file.
+#[derive(Deserialize)]
+struct FormatVersions {
+    #[serde(default)]
+    index_format_version: u32,
+    #[serde(default)]
+    segments: Vec<SegmentFormatVersion>,
+}
+
+#[derive(Deserialize)]
+struct SegmentFormatVersion {
+    #[serde(default)]
+    format_version: u32,
+}
+
+/// Returns an `IncompatibleIndex` error if the index, or one of its
+/// segments, was written in a more recent version of the index format
+/// than `INDEX_FORMAT_VERSION`.
+pub(crate) fn check_format_version(meta_json: &str) -> Result<()> {
+    let format_versions: FormatVersions = match serde_json::from_str(meta_json) {
+        Ok(format_versions) => format_versions,
+        // the corruption is reported when deserializing the `IndexMeta`.
+        Err(_) => return Ok(()),
+    };
+    let max_format_version = format_versions
+        .segments
+        .iter()
+        .map(|segment| segment.format_version)
+        .chain(Some(format_versions.index_format_version))
+        .max()
+        .unwrap_or(0u32);
+    if max_format_version > INDEX_FORMAT_VERSION {
+        return Err(TantivyError::IncompatibleIndex(format!(
+            "The index was written in the version {} of the index format, while this \
+             version of tantivy only supports the versions up to {}. Please upgrade tantivy.",
+            max_format_version, INDEX_FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
 impl fmt::Debug for IndexMeta {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -45,7 +110,9 @@ impl fmt::Debug for IndexMeta {
 #[cfg(test)]
 mod tests {
 
-    use super::IndexMeta;
+    use super::{check_format_version, IndexMeta};
+    use core::INDEX_FORMAT_VERSION;
+    use error::TantivyError;
     use schema::{Schema, TEXT};
     use serde_json;
 
@@ -61,8 +128,30 @@ mod tests {
             schema,
             opstamp: 0u64,
             payload: None,
+            generation: 0u64,
+            schema_version: 0u64,
+            shard: None,
+            index_format_version: INDEX_FORMAT_VERSION,
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
-        assert_eq!(json, r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0}"#);
+        assert_eq!(
+            json,
+            r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0,"generation":0,"schema_version":0,"index_format_version":1}"#
+        );
+    }
+
+    #[test]
+    fn test_check_format_version() {
+        assert!(check_format_version(r#"{"segments":[]}"#).is_ok());
+        assert!(check_format_version(r#"{"segments":[],"index_format_version":1}"#).is_ok());
+        assert!(check_format_version("corrupted").is_ok());
+        match check_format_version(r#"{"segments":[{"format_version":2}]}"#) {
+            Err(TantivyError::IncompatibleIndex(_)) => {}
+            _ => panic!("Expected an incompatible index error"),
+        }
+        match check_format_version(r#"{"segments":[],"index_format_version":2}"#) {
+            Err(TantivyError::IncompatibleIndex(_)) => {}
+            _ => panic!("Expected an incompatible index error"),
+        }
     }
 }
@@ -0,0 +1,279 @@
+use super::pool::LeasedItem;
+use super::pool::Pool;
+use core::Index;
+use core::Searcher;
+use directory::{Directory, WatchHandle};
+use std::sync::{Arc, Mutex, RwLock};
+use Result;
+
+/// Defines when an `IndexReader` reloads its searchers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReloadPolicy {
+    /// The searchers are only reloaded by calls to `IndexReader::reload`.
+    Manual,
+    /// The searchers are reloaded after every commit or merge.
+    ///
+    /// The reader relies on `Directory::watch` to be notified of the
+    /// changes of the meta file of the index, so that commits happening
+    /// in another process are picked up as well.
+    OnCommit,
+}
+
+/// Hook called by an `IndexReader` on its new searchers,
+/// before they are used for search.
+///
+/// Warmers make it possible to load data in the page cache, see
+/// [`Searcher::warm`](./struct.Searcher.html#method.warm), or to
+/// precompute caches before the first query on a new segment.
+/// As the searchers of a new generation share most of their segments
+/// with the previous generation, a warmer typically keys its caches
+/// by `SegmentId`, and only computes those of the new segments.
+pub trait Warmer: Send + Sync {
+    /// Warms the searcher.
+    ///
+    /// An error aborts the reload of the searchers.
+    fn warm(&self, searcher: &Searcher) -> Result<()>;
+}
+
+struct InnerIndexReader {
+    index: Index,
+    num_searchers: usize,
+    searcher_pool: Pool<Searcher>,
+    warmers: RwLock<Vec<Arc<Warmer>>>,
+    // Generation of the searchers of the pool.
+    // The lock is held while reloading.
+    generation: Mutex<u64>,
+}
+
+impl InnerIndexReader {
+    fn reload(&self) -> Result<()> {
+        let mut generation = self.generation.lock().unwrap();
+        self.load_searchers(&mut generation)
+    }
+
+    fn reload_if_changed(&self) -> Result<()> {
+        let mut generation = self.generation.lock().unwrap();
+        if self.index.load_metas()?.generation != *generation {
+            self.load_searchers(&mut generation)?;
+        }
+        Ok(())
+    }
+
+    fn load_searchers(&self, generation: &mut u64) -> Result<()> {
+        let searchers = self.index.open_searchers(self.num_searchers)?;
+        if let Some(searcher) = searchers.first() {
+            // the searchers share their segment readers,
+            // so warming one of them is enough.
+            for warmer in self.warmers.read().unwrap().iter() {
+                warmer.warm(searcher)?;
+            }
+            *generation = searcher.generation();
+        }
+        self.searcher_pool.publish_new_generation(searchers);
+        Ok(())
+    }
+}
+
+/// Handle owning a pool of searchers over an index.
+///
+/// Contrary to the searchers of the `Index`, which have to be
+/// reloaded by calling `Index::load_searchers`, the searchers
+/// of an `IndexReader` can be reloaded automatically, as defined
+/// by its `ReloadPolicy`.
+///
+/// An `IndexReader` is created by `Index::reader`, and can be cloned
+/// cheaply: the clones share the same searchers.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{Index, ReloadPolicy, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     let reader = index.reader(ReloadPolicy::Manual)?;
+///     let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///     index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+///     index_writer.commit()?;
+///     assert_eq!(reader.searcher().num_docs(), 0);
+///     reader.reload()?;
+///     assert_eq!(reader.searcher().num_docs(), 1);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct IndexReader {
+    inner: Arc<InnerIndexReader>,
+    reload_policy: ReloadPolicy,
+    // the searchers are reloaded on commit as long as the handle is alive.
+    _watch_handle: Option<WatchHandle>,
+}
+
+impl IndexReader {
+    pub(crate) fn new(
+        index: Index,
+        num_searchers: usize,
+        reload_policy: ReloadPolicy,
+    ) -> Result<IndexReader> {
+        let inner = Arc::new(InnerIndexReader {
+            index,
+            num_searchers,
+            searcher_pool: Pool::new(),
+            warmers: RwLock::new(Vec::new()),
+            generation: Mutex::new(0u64),
+        });
+        inner.reload()?;
+        let watch_handle = if reload_policy == ReloadPolicy::OnCommit {
+            let inner_weak = Arc::downgrade(&inner);
+            let watch_handle = inner.index.directory().watch(Box::new(move || {
+                if let Some(inner) = inner_weak.upgrade() {
+                    if let Err(e) = inner.reload_if_changed() {
+                        error!("Failed to reload the index reader: {:?}", e);
+                    }
+                }
+            }))?;
+            Some(watch_handle)
+        } else {
+            None
+        };
+        Ok(IndexReader {
+            inner,
+            reload_policy,
+            _watch_handle: watch_handle,
+        })
+    }
+
+    /// Returns the reload policy of the reader.
+    pub fn reload_policy(&self) -> ReloadPolicy {
+        self.reload_policy
+    }
+
+    /// Reloads the searchers, so that they reflect the state
+    /// of the last `.commit()`.
+    ///
+    /// The searchers currently in use are not affected, and
+    /// the ones acquired after the call see the new state.
+    pub fn reload(&self) -> Result<()> {
+        self.inner.reload()
+    }
+
+    /// Registers a warmer, called on the searchers of every
+    /// reload from now on.
+    ///
+    /// The searchers are reloaded right away, so that
+    /// they are warmed by `warmer` as well.
+    pub fn add_warmer(&self, warmer: Arc<Warmer>) -> Result<()> {
+        self.inner.warmers.write().unwrap().push(warmer);
+        self.reload()
+    }
+
+    /// Returns a searcher
+    ///
+    /// This method should be called every single time a search
+    /// query is performed.
+    /// The searchers are taken from a pool of `num_searchers` searchers.
+    /// If no searcher is available
+    /// this may block.
+    pub fn searcher(&self) -> LeasedItem<Searcher> {
+        self.inner.searcher_pool.acquire()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReloadPolicy, Warmer};
+    use indexer::NoMergePolicy;
+    use schema::{Schema, TEXT};
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use Index;
+    use Result;
+    use Searcher;
+    use SegmentId;
+
+    #[test]
+    fn test_index_reader_manual_reload() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader(ReloadPolicy::Manual).unwrap();
+        assert_eq!(reader.reload_policy(), ReloadPolicy::Manual);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 0);
+        drop(searcher);
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        // the searchers of the index are independent.
+        assert_eq!(index.searcher().num_docs(), 0);
+    }
+
+    #[test]
+    fn test_index_reader_reload_on_commit() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader(ReloadPolicy::OnCommit).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        let mut num_docs = 0;
+        for _ in 0..100 {
+            num_docs = reader.searcher().num_docs();
+            if num_docs == 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(num_docs, 1);
+    }
+
+    #[derive(Default)]
+    struct SegmentWarmer {
+        warmed_segments: Mutex<HashSet<SegmentId>>,
+        num_warmed_segments: Mutex<usize>,
+    }
+
+    impl Warmer for SegmentWarmer {
+        fn warm(&self, searcher: &Searcher) -> Result<()> {
+            searcher.warm();
+            let mut warmed_segments = self.warmed_segments.lock().unwrap();
+            for segment_reader in searcher.segment_readers() {
+                if warmed_segments.insert(segment_reader.segment_id()) {
+                    *self.num_warmed_segments.lock().unwrap() += 1;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_index_reader_warmer() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        let reader = index.reader(ReloadPolicy::Manual).unwrap();
+        let warmer = Arc::new(SegmentWarmer::default());
+        reader.add_warmer(warmer.clone()).unwrap();
+        assert_eq!(*warmer.num_warmed_segments.lock().unwrap(), 1);
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        // only the new segment is warmed.
+        assert_eq!(*warmer.num_warmed_segments.lock().unwrap(), 2);
+        assert_eq!(reader.searcher().num_docs(), 2);
+    }
+}
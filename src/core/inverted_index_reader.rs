@@ -2,6 +2,7 @@ use common::BinarySerializable;
 use directory::ReadOnlySource;
 use owned_read::OwnedRead;
 use positions::PositionReader;
+use postings::PostingsCodec;
 use postings::TermInfo;
 use postings::{BlockSegmentPostings, SegmentPostings};
 use schema::FieldType;
@@ -28,6 +29,7 @@ pub struct InvertedIndexReader {
     positions_source: ReadOnlySource,
     positions_idx_source: ReadOnlySource,
     record_option: IndexRecordOption,
+    postings_codec: PostingsCodec,
     total_num_tokens: u64,
 }
 
@@ -39,6 +41,7 @@ impl InvertedIndexReader {
         positions_source: ReadOnlySource,
         positions_idx_source: ReadOnlySource,
         record_option: IndexRecordOption,
+        postings_codec: PostingsCodec,
     ) -> InvertedIndexReader {
         let total_num_tokens_data = postings_source.slice(0, 8);
         let mut total_num_tokens_cursor = total_num_tokens_data.as_slice();
@@ -49,6 +52,7 @@ impl InvertedIndexReader {
             positions_source,
             positions_idx_source,
             record_option,
+            postings_codec,
             total_num_tokens,
         }
     }
@@ -65,6 +69,7 @@ impl InvertedIndexReader {
             positions_source: ReadOnlySource::empty(),
             positions_idx_source: ReadOnlySource::empty(),
             record_option,
+            postings_codec: PostingsCodec::default(),
             total_num_tokens: 0u64,
         }
     }
@@ -130,6 +135,7 @@ impl InvertedIndexReader {
             OwnedRead::new(postings_data),
             self.record_option,
             requested_option,
+            self.postings_codec,
         )
     }
 
@@ -193,4 +199,30 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32)
     }
+
+    /// Returns the overall number of occurrences of the term,
+    /// in all of the documents (including deleted documents).
+    ///
+    /// If the term frequencies were not recorded for the field,
+    /// every document containing the term counts as one occurrence.
+    pub fn total_term_freq(&self, term: &Term) -> u64 {
+        let term_info = match self.get_term_info(term) {
+            Some(term_info) => term_info,
+            None => return 0u64,
+        };
+        if !self.record_option.has_freq() {
+            return u64::from(term_info.doc_freq);
+        }
+        let mut block_postings =
+            self.read_block_postings_from_terminfo(&term_info, IndexRecordOption::WithFreqs);
+        let mut total_term_freq = 0u64;
+        while block_postings.advance() {
+            total_term_freq += block_postings
+                .freqs()
+                .iter()
+                .map(|&term_freq| u64::from(term_freq))
+                .sum::<u64>();
+        }
+        total_term_freq
+    }
 }
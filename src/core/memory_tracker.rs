@@ -0,0 +1,123 @@
+use error::TantivyError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use Result;
+
+/// Number of documents collected between two updates
+/// of the memory used by a segment collector.
+pub(crate) const MEMORY_CHECK_PERIOD: u32 = 1_024;
+
+/// Keeps track of the memory held on behalf of a query,
+/// over all of its segments.
+pub(crate) struct MemoryTracker {
+    used: AtomicUsize,
+    limit: usize,
+}
+
+impl MemoryTracker {
+    pub fn new(limit: usize) -> MemoryTracker {
+        MemoryTracker {
+            used: AtomicUsize::new(0),
+            limit,
+        }
+    }
+
+    /// Accounts for `num_bytes` more bytes.
+    ///
+    /// # Errors
+    /// Returns a `MemoryLimitExceeded` error if the memory used
+    /// exceeds the limit.
+    pub fn allocate(&self, num_bytes: usize) -> Result<()> {
+        let required = self.used.fetch_add(num_bytes, Ordering::SeqCst) + num_bytes;
+        if required > self.limit {
+            return Err(TantivyError::MemoryLimitExceeded {
+                required,
+                limit: self.limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// Accounts for `num_bytes` bytes being released.
+    pub fn release(&self, num_bytes: usize) {
+        self.used.fetch_sub(num_bytes, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::MemoryTracker;
+    use collector::{Count, FacetCollector, TopDocs};
+    use core::Index;
+    use error::TantivyError;
+    use query::{AllQuery, RegexQuery};
+    use schema::{Facet, Schema, STRING};
+
+    #[test]
+    fn test_memory_tracker() {
+        let memory_tracker = MemoryTracker::new(100);
+        assert!(memory_tracker.allocate(60).is_ok());
+        assert!(memory_tracker.allocate(60).is_err());
+        memory_tracker.release(60);
+        memory_tracker.release(60);
+        assert!(memory_tracker.allocate(100).is_ok());
+    }
+
+    #[test]
+    fn test_search_with_memory_limit() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let facet = schema_builder.add_facet_field("facet");
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for i in 0..3_000u32 {
+                let path = format!("/category/{}", i);
+                index_writer
+                    .add_document(doc!(
+                        text => format!("term{}", i),
+                        facet => Facet::from(path.as_str())
+                    ))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(
+            searcher
+                .search_with_memory_limit(&AllQuery, &Count, 1_000)
+                .unwrap(),
+            3_000
+        );
+        // the heap of the collector exceeds the limit.
+        match searcher.search_with_memory_limit(&AllQuery, &TopDocs::with_limit(1_000), 1_000) {
+            Err(TantivyError::MemoryLimitExceeded { required, limit }) => {
+                assert!(required > 1_000);
+                assert_eq!(limit, 1_000);
+            }
+            _ => panic!("The memory limit should have been exceeded."),
+        }
+        // the bitset of the matching terms exceeds the limit.
+        let regex_query = RegexQuery::new("term.*".to_string(), text);
+        assert!(searcher
+            .search_with_memory_limit(&regex_query, &Count, 100)
+            .is_err());
+        assert_eq!(
+            searcher
+                .search_with_memory_limit(&regex_query, &Count, 100_000)
+                .unwrap(),
+            3_000
+        );
+        // the counters of the facets exceed the limit.
+        let mut facet_collector = FacetCollector::for_field(facet);
+        facet_collector.add_facet("/category");
+        assert!(searcher
+            .search_with_memory_limit(&AllQuery, &facet_collector, 10_000)
+            .is_err());
+        let facet_counts = searcher
+            .search_with_memory_limit(&AllQuery, &facet_collector, 1_000_000)
+            .unwrap();
+        assert_eq!(facet_counts.get("/category").count(), 3_000);
+    }
+}
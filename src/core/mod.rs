@@ -1,29 +1,50 @@
 mod executor;
+mod field_statistics;
 pub mod index;
 mod index_meta;
+mod index_reader;
 mod inverted_index_reader;
+mod memory_tracker;
 mod pool;
+mod query_profile;
+mod search_iterator;
 pub mod searcher;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_meta;
 mod segment_reader;
+mod shard_descriptor;
+mod tenant_index;
 
 pub use self::executor::Executor;
+pub use self::field_statistics::FieldStatistics;
 pub use self::index::Index;
 pub use self::index_meta::IndexMeta;
+pub use self::index_reader::{IndexReader, ReloadPolicy, Warmer};
 pub use self::inverted_index_reader::InvertedIndexReader;
-pub use self::searcher::Searcher;
+pub use self::query_profile::{QueryProfile, SegmentProfile};
+pub use self::search_iterator::SearchIterator;
+pub use self::searcher::{SearchFuture, Searcher};
 pub use self::segment::Segment;
 pub use self::segment::SerializableSegment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_meta::SegmentMeta;
 pub use self::segment_reader::SegmentReader;
+pub use self::shard_descriptor::ShardDescriptor;
+pub use self::tenant_index::TenantIndex;
 
 use std::path::PathBuf;
 
+/// Version of the index format written by this version of tantivy.
+///
+/// It is recorded in the `meta.json` file, in the meta of every segment,
+/// and in the footer of the segment files. Indexes written in a more
+/// recent format cannot be opened, and the segments written in a previous
+/// format can be rewritten with `IndexWriter::upgrade`.
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
 lazy_static! {
     /// The meta file contains all the information about the list of segments and the schema
     /// of the index.
@@ -0,0 +1,174 @@
+use core::SegmentId;
+use std::time::Duration;
+
+/// Time spent by a query on a segment, stage by stage.
+///
+/// See [`QueryProfile`](./struct.QueryProfile.html).
+#[derive(Clone, Debug)]
+pub struct SegmentProfile {
+    pub(crate) segment_id: SegmentId,
+    pub(crate) num_hits: u32,
+    pub(crate) term_lookup: Duration,
+    pub(crate) postings: Duration,
+    pub(crate) scoring: Duration,
+    pub(crate) collection: Duration,
+}
+
+impl SegmentProfile {
+    pub(crate) fn new(segment_id: SegmentId) -> SegmentProfile {
+        SegmentProfile {
+            segment_id,
+            num_hits: 0u32,
+            term_lookup: Duration::default(),
+            postings: Duration::default(),
+            scoring: Duration::default(),
+            collection: Duration::default(),
+        }
+    }
+
+    /// Returns the id of the segment.
+    pub fn segment_id(&self) -> SegmentId {
+        self.segment_id
+    }
+
+    /// Returns the number of documents of the segment
+    /// pushed to the collector.
+    pub fn num_hits(&self) -> u32 {
+        self.num_hits
+    }
+
+    /// Returns the time spent creating the scorer of the segment,
+    /// that is looking up the terms of the query in the term dictionary
+    /// and opening their postings.
+    pub fn term_lookup_duration(&self) -> Duration {
+        self.term_lookup
+    }
+
+    /// Returns the time spent advancing the scorer from a matching
+    /// document to the next one, that is decoding the postings and
+    /// combining them.
+    pub fn postings_duration(&self) -> Duration {
+        self.postings
+    }
+
+    /// Returns the time spent computing the score of the
+    /// matching documents.
+    pub fn scoring_duration(&self) -> Duration {
+        self.scoring
+    }
+
+    /// Returns the time spent by the segment collector, collecting
+    /// the matching documents and harvesting its result.
+    pub fn collection_duration(&self) -> Duration {
+        self.collection
+    }
+
+    /// Returns the overall time spent on the segment.
+    pub fn total_duration(&self) -> Duration {
+        self.term_lookup + self.postings + self.scoring + self.collection
+    }
+}
+
+/// Time spent by a query, stage by stage.
+///
+/// Profiling a query is the equivalent of an `EXPLAIN ANALYZE`:
+/// the query is run, and the profile is returned alongside its results.
+/// See [`Searcher::search_with_profile`](./struct.Searcher.html#method.search_with_profile).
+///
+/// Timing every document adds a significant overhead:
+/// the durations should be compared with one another rather than
+/// with the duration of a regular search.
+#[derive(Clone, Debug)]
+pub struct QueryProfile {
+    pub(crate) weight: Duration,
+    pub(crate) segments: Vec<SegmentProfile>,
+    pub(crate) merge: Duration,
+}
+
+impl QueryProfile {
+    /// Returns the time spent creating the weight of the query,
+    /// that is mostly computing the statistics of its terms
+    /// over the whole index.
+    pub fn weight_duration(&self) -> Duration {
+        self.weight
+    }
+
+    /// Returns the profiles of the segments, in the order
+    /// of the segment readers of the searcher.
+    pub fn segments(&self) -> &[SegmentProfile] {
+        &self.segments
+    }
+
+    /// Returns the time spent merging the results of the segments.
+    pub fn merge_duration(&self) -> Duration {
+        self.merge
+    }
+
+    /// Returns the number of documents pushed to the collector.
+    pub fn num_hits(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| u64::from(segment.num_hits))
+            .sum()
+    }
+
+    /// Returns the overall time spent by the query.
+    pub fn total_duration(&self) -> Duration {
+        self.segments
+            .iter()
+            .map(SegmentProfile::total_duration)
+            .fold(self.weight + self.merge, |total, duration| total + duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::{Count, TopDocs};
+    use core::Index;
+    use query::QueryParser;
+    use schema::{Schema, Term, TEXT};
+
+    #[test]
+    fn test_search_with_profile() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text => "a b")).unwrap();
+            index_writer.add_document(doc!(text => "a c")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text => "a")).unwrap();
+            index_writer.add_document(doc!(text => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text, "c"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text])
+            .parse_query("a")
+            .unwrap();
+        let (count, profile) = searcher.search_with_profile(&query, &Count).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(profile.num_hits(), 2);
+        assert_eq!(profile.segments().len(), searcher.segment_readers().len());
+        for (segment_profile, segment_reader) in
+            profile.segments().iter().zip(searcher.segment_readers())
+        {
+            assert_eq!(segment_profile.segment_id(), segment_reader.segment_id());
+            assert_eq!(segment_profile.num_hits(), 1);
+            assert!(segment_profile.total_duration() <= profile.total_duration());
+        }
+        let (top_docs, _) = searcher
+            .search_with_profile(&query, &TopDocs::with_limit(10))
+            .unwrap();
+        assert_eq!(
+            top_docs,
+            searcher.search(&query, &TopDocs::with_limit(10)).unwrap()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use core::Searcher;
+use docset::DocSet;
+use query::{Scorer, Weight};
+use DocAddress;
+use Result;
+use Score;
+
+/// Lazy iterator over the documents matching a query,
+/// with their score.
+///
+/// The documents are returned segment by segment, in the order
+/// of their `DocAddress`, and the deleted documents are skipped.
+/// The scorer of a segment is only created once the documents of
+/// the previous segments have been consumed, so that a huge set of hits
+/// can be streamed without being collected in memory.
+///
+/// See [`Searcher::search_iter`](./struct.Searcher.html#method.search_iter).
+pub struct SearchIterator<'a> {
+    searcher: &'a Searcher,
+    weight: Box<Weight>,
+    segment_ord: usize,
+    scorer_opt: Option<Box<Scorer>>,
+}
+
+impl<'a> SearchIterator<'a> {
+    pub(crate) fn new(searcher: &'a Searcher, weight: Box<Weight>) -> SearchIterator<'a> {
+        SearchIterator {
+            searcher,
+            weight,
+            segment_ord: 0,
+            scorer_opt: None,
+        }
+    }
+}
+
+impl<'a> Iterator for SearchIterator<'a> {
+    /// An error is returned if the scorer of a segment
+    /// cannot be created. The iteration then moves on to
+    /// the next segment.
+    type Item = Result<(Score, DocAddress)>;
+
+    fn next(&mut self) -> Option<Result<(Score, DocAddress)>> {
+        let segment_readers = self.searcher.segment_readers();
+        while self.segment_ord < segment_readers.len() {
+            let segment_reader = &segment_readers[self.segment_ord];
+            if self.scorer_opt.is_none() {
+                match self.weight.scorer(segment_reader) {
+                    Ok(scorer) => {
+                        self.scorer_opt = Some(scorer);
+                    }
+                    Err(err) => {
+                        self.segment_ord += 1;
+                        return Some(Err(err));
+                    }
+                }
+            }
+            if let Some(ref mut scorer) = self.scorer_opt {
+                while scorer.advance() {
+                    let doc = scorer.doc();
+                    if !segment_reader.is_deleted(doc) {
+                        let doc_address = DocAddress(self.segment_ord as u32, doc);
+                        return Some(Ok((scorer.score(), doc_address)));
+                    }
+                }
+            }
+            self.scorer_opt = None;
+            self.segment_ord += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::Index;
+    use query::{AllQuery, QueryParser};
+    use schema::{Schema, Term, TEXT};
+    use DocAddress;
+
+    #[test]
+    fn test_search_iter() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text => "a b")).unwrap();
+            index_writer.add_document(doc!(text => "c")).unwrap();
+            index_writer.add_document(doc!(text => "a c")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text => "a")).unwrap();
+            index_writer.add_document(doc!(text => "a d")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text, "d"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text])
+            .parse_query("a")
+            .unwrap();
+        let hits = searcher
+            .search_iter(&query)
+            .unwrap()
+            .collect::<::Result<Vec<_>>>()
+            .unwrap();
+        let doc_addresses: Vec<DocAddress> =
+            hits.iter().map(|&(_, doc_address)| doc_address).collect();
+        let large_first = searcher.segment_reader(0).max_doc() == 3;
+        let (large, small) = if large_first { (0, 1) } else { (1, 0) };
+        let mut expected = vec![
+            DocAddress(large, 0),
+            DocAddress(large, 2),
+            DocAddress(small, 0),
+        ];
+        expected.sort();
+        assert_eq!(doc_addresses, expected);
+        assert!(hits.iter().all(|&(score, _)| score > 0f32));
+        assert_eq!(searcher.search_iter(&AllQuery).unwrap().count(), 4);
+    }
+}
@@ -1,8 +1,17 @@
 use collector::Collector;
 use collector::SegmentCollector;
+use core::memory_tracker::{MemoryTracker, MEMORY_CHECK_PERIOD};
 use core::Executor;
+use core::FieldStatistics;
 use core::InvertedIndexReader;
+use core::SearchIterator;
+use core::SegmentMeta;
 use core::SegmentReader;
+use core::{QueryProfile, SegmentProfile};
+use error::TantivyError;
+use futures::future;
+use futures::sync::oneshot;
+use futures::Future;
 use query::Query;
 use query::Scorer;
 use query::Weight;
@@ -12,6 +21,7 @@ use schema::{Field, Term};
 use space_usage::SearcherSpaceUsage;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Instant;
 use store::StoreReader;
 use termdict::TermMerger;
 use DocAddress;
@@ -24,6 +34,9 @@ fn collect_segment<C: Collector>(
     segment_ord: u32,
     segment_reader: &SegmentReader,
 ) -> Result<C::Fruit> {
+    if let Some(fruit) = collector.collect_segment_count(weight, segment_reader)? {
+        return Ok(fruit);
+    }
     let mut scorer = weight.scorer(segment_reader)?;
     let mut segment_collector = collector.for_segment(segment_ord as u32, segment_reader)?;
     if let Some(delete_bitset) = segment_reader.delete_bitset() {
@@ -38,14 +51,147 @@ fn collect_segment<C: Collector>(
     Ok(segment_collector.harvest())
 }
 
+/// Same as `collect_segment`, but accounting for the memory
+/// held by the scorer and the segment collector in `memory_tracker`.
+///
+/// The memory of the scorer is released once the segment has been
+/// collected, while the memory of the segment collector is kept
+/// as an estimate of the memory held by its fruit.
+fn collect_segment_with_memory_limit<C: Collector>(
+    collector: &C,
+    weight: &Weight,
+    segment_ord: u32,
+    segment_reader: &SegmentReader,
+    memory_tracker: &MemoryTracker,
+) -> Result<C::Fruit> {
+    let mut scorer = weight.scorer(segment_reader)?;
+    let scorer_memory_usage = scorer.memory_usage();
+    memory_tracker.allocate(scorer_memory_usage)?;
+    let mut segment_collector = collector.for_segment(segment_ord as u32, segment_reader)?;
+    let mut collector_memory_usage = segment_collector.memory_usage();
+    memory_tracker.allocate(collector_memory_usage)?;
+    let delete_bitset_opt = segment_reader.delete_bitset();
+    let mut num_collected = 0u32;
+    while scorer.advance() {
+        let doc = scorer.doc();
+        if delete_bitset_opt
+            .map(|delete_bitset| delete_bitset.is_deleted(doc))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        segment_collector.collect(doc, scorer.score());
+        num_collected += 1;
+        if num_collected % MEMORY_CHECK_PERIOD == 0 {
+            let memory_usage = segment_collector.memory_usage();
+            if memory_usage > collector_memory_usage {
+                memory_tracker.allocate(memory_usage - collector_memory_usage)?;
+                collector_memory_usage = memory_usage;
+            }
+        }
+    }
+    let memory_usage = segment_collector.memory_usage();
+    if memory_usage > collector_memory_usage {
+        memory_tracker.allocate(memory_usage - collector_memory_usage)?;
+    }
+    memory_tracker.release(scorer_memory_usage);
+    Ok(segment_collector.harvest())
+}
+
+/// Same as `collect_segment`, but timing each of the stages
+/// of the collection.
+fn collect_segment_with_profile<C: Collector>(
+    collector: &C,
+    weight: &Weight,
+    segment_ord: u32,
+    segment_reader: &SegmentReader,
+) -> Result<(C::Fruit, SegmentProfile)> {
+    let mut profile = SegmentProfile::new(segment_reader.segment_id());
+    let start = Instant::now();
+    let mut scorer = weight.scorer(segment_reader)?;
+    profile.term_lookup = start.elapsed();
+    let start = Instant::now();
+    let mut segment_collector = collector.for_segment(segment_ord as u32, segment_reader)?;
+    profile.collection = start.elapsed();
+    let delete_bitset_opt = segment_reader.delete_bitset();
+    loop {
+        let start = Instant::now();
+        let has_doc = scorer.advance();
+        profile.postings += start.elapsed();
+        if !has_doc {
+            break;
+        }
+        let doc = scorer.doc();
+        if delete_bitset_opt
+            .map(|delete_bitset| delete_bitset.is_deleted(doc))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let start = Instant::now();
+        let score = scorer.score();
+        profile.scoring += start.elapsed();
+        let start = Instant::now();
+        segment_collector.collect(doc, score);
+        profile.collection += start.elapsed();
+        profile.num_hits += 1;
+    }
+    let start = Instant::now();
+    let fruit = segment_collector.harvest();
+    profile.collection += start.elapsed();
+    Ok((fruit, profile))
+}
+
+/// Future returned by the asynchronous methods of the `Searcher`.
+pub type SearchFuture<T> = Box<Future<Item = T, Error = TantivyError> + Send>;
+
+/// Runs `f` in a task spawned on `executor`, and returns a future
+/// of its result.
+fn spawn_blocking<T, F, E>(executor: &E, f: F) -> SearchFuture<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+    E: future::Executor<Box<Future<Item = (), Error = ()> + Send>>,
+{
+    let (sender, receiver) = oneshot::channel();
+    let task = future::lazy(move || {
+        // the receiver may have been dropped, if the
+        // result is not awaited anymore.
+        let _ = sender.send(f());
+        Ok::<(), ()>(())
+    });
+    if executor.execute(Box::new(task)).is_err() {
+        return Box::new(future::err(TantivyError::ErrorInThread(
+            "The executor rejected the search task.".to_string(),
+        )));
+    }
+    Box::new(receiver.then(|result| match result {
+        Ok(result) => result,
+        Err(_) => Err(TantivyError::ErrorInThread(
+            "The search task was dropped before completing.".to_string(),
+        )),
+    }))
+}
+
 /// Holds a list of `SegmentReader`s ready for search.
 ///
 /// It guarantees that the `Segment` will not be removed before
 /// the destruction of the `Searcher`.
 ///
+/// A `Searcher` is a point-in-time view of the index,
+/// identified by its [`generation`](#method.generation).
+/// Cloning it is cheap, and pins this view: the files of its segments
+/// are not garbage collected as long as a clone is alive.
+/// This makes it possible to serve several requests, like the pages
+/// of a result set, from the same generation.
+#[derive(Clone)]
 pub struct Searcher {
     schema: Schema,
     index: Index,
+    generation: u64,
+    opstamp: u64,
+    // Living `SegmentMeta`s protect their files from garbage collection.
+    _segment_metas: Vec<SegmentMeta>,
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
 }
@@ -55,6 +201,9 @@ impl Searcher {
     pub(crate) fn new(
         schema: Schema,
         index: Index,
+        generation: u64,
+        opstamp: u64,
+        segment_metas: Vec<SegmentMeta>,
         segment_readers: Vec<SegmentReader>,
     ) -> Searcher {
         let store_readers = segment_readers
@@ -64,11 +213,33 @@ impl Searcher {
         Searcher {
             schema,
             index,
+            generation,
+            opstamp,
+            _segment_metas: segment_metas,
             segment_readers,
             store_readers,
         }
     }
 
+    /// Returns the generation of the index this searcher was
+    /// loaded from.
+    ///
+    /// The generation is incremented every time the index meta
+    /// file is written, that is on every commit and at the end of each merge.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the opstamp of the commit this searcher was loaded from.
+    ///
+    /// All of the operations with an opstamp strictly lower than this one
+    /// are visible to the searcher. Comparing it with the opstamps returned
+    /// by `IndexWriter::add_document` and `IndexWriter::delete_term`
+    /// makes it possible to wait for a searcher that includes a given write.
+    pub fn opstamp(&self) -> u64 {
+        self.opstamp
+    }
+
     /// Returns the `Index` associated to the `Searcher`
     pub fn index(&self) -> &Index {
         &self.index
@@ -84,6 +255,17 @@ impl Searcher {
         store_reader.get(doc_id)
     }
 
+    /// Asynchronous version of [`doc(...)`](#method.doc).
+    ///
+    /// The document is fetched in a task spawned on `executor`.
+    pub fn doc_async<E>(&self, doc_address: DocAddress, executor: &E) -> SearchFuture<Document>
+    where
+        E: future::Executor<Box<Future<Item = (), Error = ()> + Send>>,
+    {
+        let searcher = self.clone();
+        spawn_blocking(executor, move || searcher.doc(doc_address))
+    }
+
     /// Access the schema associated to the index of this searcher.
     pub fn schema(&self) -> &Schema {
         &self.schema
@@ -108,6 +290,36 @@ impl Searcher {
             .sum::<u64>()
     }
 
+    /// Return the overall number of occurrences of the given term,
+    /// in all of the documents.
+    pub fn total_term_freq(&self, term: &Term) -> u64 {
+        self.segment_readers
+            .iter()
+            .map(|segment_reader| {
+                segment_reader
+                    .inverted_index(term.field())
+                    .total_term_freq(term)
+            })
+            .sum::<u64>()
+    }
+
+    /// Returns the statistics of `field`, aggregated over all of the segments.
+    pub fn field_statistics(&self, field: Field) -> FieldStatistics {
+        self.segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.field_statistics(field))
+            .fold(FieldStatistics::default(), |acc, stats| acc + stats)
+    }
+
+    /// Warms all of the segment readers of the searcher.
+    ///
+    /// See [`SegmentReader::warm`](./struct.SegmentReader.html#method.warm).
+    pub fn warm(&self) {
+        for segment_reader in &self.segment_readers {
+            segment_reader.warm();
+        }
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.segment_readers
@@ -157,14 +369,94 @@ impl Searcher {
     ) -> Result<C::Fruit> {
         let scoring_enabled = collector.requires_scoring();
         let weight = query.weight(self, scoring_enabled)?;
+        self.collect_with_executor(weight.as_ref(), collector, executor)
+    }
+
+    fn collect_with_executor<C: Collector>(
+        &self,
+        weight: &Weight,
+        collector: &C,
+        executor: &Executor,
+    ) -> Result<C::Fruit> {
         let segment_readers = self.segment_readers();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
-                collect_segment(
+                collect_segment(collector, weight, segment_ord as u32, segment_reader)
+            },
+            segment_readers.iter().enumerate(),
+        )?;
+        collector.merge_fruits(fruits)
+    }
+
+    /// Asynchronous version of [`search(...)`](#method.search).
+    ///
+    /// The weight of the query is created in the calling thread, while
+    /// the segments are searched, and the fruits merged, in a task
+    /// spawned on `executor`. This makes it possible for an event loop
+    /// to await the search, without blocking on it.
+    ///
+    /// Any futures `Executor` can be used, for instance a `CpuPool`.
+    /// Within the task, the segments are searched with the search
+    /// executor of the index.
+    pub fn search_async<C, E>(
+        &self,
+        query: &Query,
+        collector: C,
+        executor: &E,
+    ) -> SearchFuture<C::Fruit>
+    where
+        C: Collector + Send + 'static,
+        E: future::Executor<Box<Future<Item = (), Error = ()> + Send>>,
+    {
+        let weight = match query.weight(self, collector.requires_scoring()) {
+            Ok(weight) => weight,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        let searcher = self.clone();
+        spawn_blocking(executor, move || {
+            let executor = searcher.index.search_executor();
+            searcher.collect_with_executor(weight.as_ref(), &collector, executor)
+        })
+    }
+
+    /// Returns a lazy iterator over the documents matching `query`,
+    /// with their score, in the order of their `DocAddress`.
+    ///
+    /// Contrary to collectors, the iterator makes it possible to stream
+    /// all of the hits of a query, for instance to export them.
+    ///
+    /// See [`SearchIterator`](./struct.SearchIterator.html).
+    pub fn search_iter(&self, query: &Query) -> Result<SearchIterator> {
+        let weight = query.weight(self, true)?;
+        Ok(SearchIterator::new(self, weight))
+    }
+
+    /// Same as [`search(...)`](#method.search), but aborts the search
+    /// with a `MemoryLimitExceeded` error if the memory held on behalf of
+    /// the query exceeds `memory_limit` bytes.
+    ///
+    /// The memory accounted for is an estimate, reported by the scorers
+    /// (decoded blocks of postings, bitsets, ...) and the segment collectors
+    /// (heaps of top documents, counters, ...). The memory held by
+    /// segment collectors is checked every 1024 collected documents.
+    pub fn search_with_memory_limit<C: Collector>(
+        &self,
+        query: &Query,
+        collector: &C,
+        memory_limit: usize,
+    ) -> Result<C::Fruit> {
+        let memory_tracker = MemoryTracker::new(memory_limit);
+        let scoring_enabled = collector.requires_scoring();
+        let weight = query.weight(self, scoring_enabled)?;
+        let segment_readers = self.segment_readers();
+        let fruits = self.index.search_executor().map(
+            |(segment_ord, segment_reader)| {
+                collect_segment_with_memory_limit(
                     collector,
                     weight.as_ref(),
                     segment_ord as u32,
                     segment_reader,
+                    &memory_tracker,
                 )
             },
             segment_readers.iter().enumerate(),
@@ -172,6 +464,44 @@ impl Searcher {
         collector.merge_fruits(fruits)
     }
 
+    /// Same as [`search(...)`](#method.search), but also returns the
+    /// time spent in each stage of the search, segment by segment.
+    ///
+    /// The segments are searched sequentially, whatever the
+    /// search executor of the index.
+    ///
+    /// See [`QueryProfile`](./struct.QueryProfile.html).
+    pub fn search_with_profile<C: Collector>(
+        &self,
+        query: &Query,
+        collector: &C,
+    ) -> Result<(C::Fruit, QueryProfile)> {
+        let scoring_enabled = collector.requires_scoring();
+        let start = Instant::now();
+        let weight = query.weight(self, scoring_enabled)?;
+        let weight_duration = start.elapsed();
+        let mut fruits = Vec::with_capacity(self.segment_readers.len());
+        let mut segment_profiles = Vec::with_capacity(self.segment_readers.len());
+        for (segment_ord, segment_reader) in self.segment_readers.iter().enumerate() {
+            let (fruit, segment_profile) = collect_segment_with_profile(
+                collector,
+                weight.as_ref(),
+                segment_ord as u32,
+                segment_reader,
+            )?;
+            fruits.push(fruit);
+            segment_profiles.push(segment_profile);
+        }
+        let start = Instant::now();
+        let fruit = collector.merge_fruits(fruits)?;
+        let profile = QueryProfile {
+            weight: weight_duration,
+            segments: segment_profiles,
+            merge: start.elapsed(),
+        };
+        Ok((fruit, profile))
+    }
+
     /// Return the field searcher associated to a `Field`.
     pub fn field(&self, field: Field) -> FieldSearcher {
         let inv_index_readers = self
@@ -223,3 +553,75 @@ impl fmt::Debug for Searcher {
         write!(f, "Searcher({:?})", segment_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use collector::{Count, TopDocs};
+    use futures::Future;
+    use futures_cpupool::CpuPool;
+    use query::TermQuery;
+    use schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use Index;
+    use Term;
+
+    #[test]
+    fn test_search_async() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let pool = CpuPool::new(2);
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let count = searcher.search_async(&query, Count, &pool);
+        let top_docs = searcher.search_async(&query, TopDocs::with_limit(1), &pool);
+        assert_eq!(count.wait().unwrap(), 2);
+        let top_docs = top_docs.wait().unwrap();
+        assert_eq!(top_docs.len(), 1);
+        let doc = searcher.doc_async(top_docs[0].1, &pool).wait().unwrap();
+        assert_eq!(doc, searcher.doc(top_docs[0].1).unwrap());
+    }
+
+    #[test]
+    fn test_term_statistics() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b a"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c"))
+                .unwrap();
+            index_writer.add_document(doc!()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_a = Term::from_field_text(text_field, "a");
+        assert_eq!(searcher.doc_freq(&term_a), 2);
+        assert_eq!(searcher.total_term_freq(&term_a), 3);
+        let term_d = Term::from_field_text(text_field, "d");
+        assert_eq!(searcher.total_term_freq(&term_d), 0);
+        let field_statistics = searcher.field_statistics(text_field);
+        assert_eq!(field_statistics.num_docs, 4);
+        assert_eq!(field_statistics.total_num_tokens, 6);
+        assert_eq!(field_statistics.average_fieldnorm(), 1.5);
+    }
+}
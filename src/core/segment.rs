@@ -2,14 +2,20 @@ use super::SegmentComponent;
 use core::Index;
 use core::SegmentId;
 use core::SegmentMeta;
-use directory::error::{OpenReadError, OpenWriteError};
+use directory::error::{IOError, OpenReadError, OpenWriteError};
 use directory::Directory;
-use directory::{ReadOnlySource, WritePtr};
+use directory::{Footer, FooterWrite};
+use directory::{ReadOnlySource, SeekableWrite, WritePtr};
+use indexer::merge_cancellation::{CancelableWrite, MergeCancellation};
 use indexer::segment_serializer::SegmentSerializer;
+use indexer::write_throttle::{ThrottledWrite, WriteThrottle};
 use schema::Schema;
 use std::fmt;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 use std::result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use Result;
 
 /// A segment is a piece of the index.
@@ -17,6 +23,10 @@ use Result;
 pub struct Segment {
     index: Index,
     meta: SegmentMeta,
+    write_throttle: Option<Arc<WriteThrottle>>,
+    cancellation: Option<MergeCancellation>,
+    // shared by the clones of the segment.
+    num_bytes_written: Arc<AtomicUsize>,
 }
 
 impl fmt::Debug for Segment {
@@ -30,7 +40,13 @@ impl fmt::Debug for Segment {
 /// The function is here to make it private outside `tantivy`.
 /// #[doc(hidden)]
 pub fn create_segment(index: Index, meta: SegmentMeta) -> Segment {
-    Segment { index, meta }
+    Segment {
+        index,
+        meta,
+        write_throttle: None,
+        cancellation: None,
+        num_bytes_written: Arc::new(AtomicUsize::new(0)),
+    }
 }
 
 impl Segment {
@@ -52,11 +68,45 @@ impl Segment {
     #[doc(hidden)]
     pub fn with_delete_meta(self, num_deleted_docs: u32, opstamp: u64) -> Segment {
         Segment {
-            index: self.index,
             meta: self.meta.with_delete_meta(num_deleted_docs, opstamp),
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn with_fast_field_updates(self, opstamp: u64) -> Segment {
+        Segment {
+            meta: self.meta.with_fast_field_updates(opstamp),
+            ..self
+        }
+    }
+
+    /// Limits the throughput of the writes of the segment's files.
+    pub(crate) fn with_write_throttle(self, write_throttle: Arc<WriteThrottle>) -> Segment {
+        Segment {
+            write_throttle: Some(write_throttle),
+            ..self
+        }
+    }
+
+    /// Makes the writes of the segment's files fail once
+    /// `cancellation` is cancelled.
+    pub(crate) fn with_cancellation(self, cancellation: MergeCancellation) -> Segment {
+        Segment {
+            cancellation: Some(cancellation),
+            ..self
         }
     }
 
+    /// Returns the number of bytes written so far in the files of the
+    /// segment by this `Segment` and its clones, footers included.
+    ///
+    /// This makes it possible to record the size of the segment in its
+    /// `SegmentMeta` without opening its files again.
+    pub(crate) fn num_bytes_written(&self) -> u64 {
+        self.num_bytes_written.load(Ordering::SeqCst) as u64
+    }
+
     /// Returns the segment's id.
     pub fn id(&self) -> SegmentId {
         self.meta.id()
@@ -71,23 +121,84 @@ impl Segment {
     }
 
     /// Open one of the component file for a *regular* read.
+    ///
+    /// The footer of the file is not part of the returned source.
     pub fn open_read(
         &self,
         component: SegmentComponent,
     ) -> result::Result<ReadOnlySource, OpenReadError> {
         let path = self.relative_path(component);
         let source = self.index.directory().open_read(&path)?;
-        Ok(source)
+        let (content, footer_opt) = Footer::extract(&source);
+        if let Some(footer) = footer_opt {
+            footer
+                .check_format_version()
+                .map_err(|err| IOError::with_path(path, err))?;
+        }
+        Ok(content)
     }
 
     /// Open one of the component file for *regular* write.
+    ///
+    /// The footer, holding the checksum of the file, is appended
+    /// when the returned `WritePtr` is terminated.
     pub fn open_write(
         &mut self,
         component: SegmentComponent,
     ) -> result::Result<WritePtr, OpenWriteError> {
         let path = self.relative_path(component);
-        let write = self.index.directory_mut().open_write(&path)?;
-        Ok(write)
+        // The buffer of the directory is replaced by a single one
+        // on top of the throttling, the footer and the cancellation.
+        let mut write: Box<SeekableWrite> = self
+            .index
+            .directory_mut()
+            .open_write(&path)?
+            .into_inner()
+            .map_err(|err| IOError::with_path(path.clone(), err.into()))?;
+        write = Box::new(CountingWrite {
+            underlying: write,
+            num_bytes_written: Arc::clone(&self.num_bytes_written),
+        });
+        if let Some(ref write_throttle) = self.write_throttle {
+            write = Box::new(ThrottledWrite::new(write, Arc::clone(write_throttle)));
+        }
+        write = Box::new(FooterWrite::new(write));
+        if let Some(ref cancellation) = self.cancellation {
+            write = Box::new(CancelableWrite::new(write, cancellation.clone()));
+        }
+        Ok(BufWriter::new(write))
+    }
+}
+
+/// Wraps a `Write` to add the number of bytes written
+/// to the counter of a `Segment`.
+struct CountingWrite<W> {
+    underlying: W,
+    num_bytes_written: Arc<AtomicUsize>,
+}
+
+impl<W: Write> Write for CountingWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        self.num_bytes_written
+            .fetch_add(num_bytes, Ordering::SeqCst);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl<W: Seek> Seek for CountingWrite<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.underlying.seek(pos)
+    }
+}
+
+impl<W: SeekableWrite> SeekableWrite for CountingWrite<W> {
+    fn terminate(&mut self) -> io::Result<()> {
+        self.underlying.terminate()
     }
 }
 
@@ -3,7 +3,8 @@ use std::slice;
 /// Enum describing each component of a tantivy segment.
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
-/// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
+/// except the delete and fast field updates components that take an
+/// `segment_uuid`.`opstamp`.`component_extension`
 #[derive(Copy, Clone)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated to terms
@@ -14,9 +15,11 @@ pub enum SegmentComponent {
     POSITIONSSKIP,
     /// Column-oriented random-access storage of fields.
     FASTFIELDS,
-    /// Stores the sum  of the length (in terms) of each field for each document.
-    /// Field norms are stored as a special u64 fast field.
+    /// Stores the length (in terms) of each field for each document.
+    /// Field norms are stored in one column per field, with one byte per document.
     FIELDNORMS,
+    /// Byte offsets of the tokens of the text fields indexed with offsets.
+    OFFSETS,
     /// Dictionary associating `Term`s to `TermInfo`s which is
     /// simply an address into the `postings` file and the `positions` file.
     TERMS,
@@ -26,20 +29,25 @@ pub enum SegmentComponent {
     STORE,
     /// Bitset describing which document of the segment is deleted.
     DELETE,
+    /// New values of the fast fields of some documents, overriding
+    /// the values stored in the `FASTFIELDS` component.
+    FASTFIELDUPDATES,
 }
 
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 8] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 10] = [
             SegmentComponent::POSTINGS,
             SegmentComponent::POSITIONS,
             SegmentComponent::POSITIONSSKIP,
             SegmentComponent::FASTFIELDS,
             SegmentComponent::FIELDNORMS,
+            SegmentComponent::OFFSETS,
             SegmentComponent::TERMS,
             SegmentComponent::STORE,
             SegmentComponent::DELETE,
+            SegmentComponent::FASTFIELDUPDATES,
         ];
         SEGMENT_COMPONENTS.into_iter()
     }
@@ -1,10 +1,15 @@
 use super::SegmentComponent;
 use census::{Inventory, TrackedObject};
 use core::SegmentId;
+use core::INDEX_FORMAT_VERSION;
+use postings::PostingsCodec;
 use serde;
 use std::collections::HashSet;
 use std::fmt;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use store::StoreCompression;
+use termdict::TermDictionaryCodec;
 
 lazy_static! {
     static ref INVENTORY: Inventory<InnerSegmentMeta> = { Inventory::new() };
@@ -14,6 +19,9 @@ lazy_static! {
 struct DeleteMeta {
     num_deleted_docs: u32,
     opstamp: u64,
+    // Number of bytes of the delete file.
+    #[serde(default)]
+    num_bytes: Option<u64>,
 }
 
 /// `SegmentMeta` contains simple meta information about a segment.
@@ -71,17 +79,71 @@ impl SegmentMeta {
             segment_id,
             max_doc,
             deletes: None,
+            postings_codec: PostingsCodec::default(),
+            store_compression: StoreCompression::default(),
+            term_dictionary_codec: TermDictionaryCodec::default(),
+            creation_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|duration| duration.as_secs()),
+            num_fields: None,
+            fast_field_updates_opstamp: None,
+            format_version: INDEX_FORMAT_VERSION,
+            num_bytes: None,
+            fast_field_updates_num_bytes: None,
         };
         SegmentMeta {
             tracked: INVENTORY.track(inner),
         }
     }
 
+    /// Creates the `SegmentMeta` of a new segment, written with
+    /// the same settings as the segment of `settings_meta`: its
+    /// codecs, its doc store compression and its number of fields.
+    ///
+    /// The settings of a segment are picked by `Index::new_segment`, and
+    /// carried over to the segments derived from it (flushed, merged,
+    /// split, reindexed...), as the files of the segment are encoded
+    /// according to them.
+    #[doc(hidden)]
+    pub fn new_with_settings(
+        segment_id: SegmentId,
+        max_doc: u32,
+        settings_meta: &SegmentMeta,
+    ) -> SegmentMeta {
+        let segment_meta = SegmentMeta::new(segment_id, max_doc)
+            .with_postings_codec(settings_meta.postings_codec())
+            .with_store_compression(settings_meta.store_compression())
+            .with_term_dictionary_codec(settings_meta.term_dictionary_codec());
+        match settings_meta.num_fields() {
+            Some(num_fields) => segment_meta.with_num_fields(num_fields),
+            None => segment_meta,
+        }
+    }
+
     /// Returns the segment id.
     pub fn id(&self) -> SegmentId {
         self.tracked.segment_id
     }
 
+    /// Returns the time at which the segment was created,
+    /// with a precision of one second.
+    ///
+    /// Segments created by older versions of tantivy do not
+    /// have a creation time.
+    pub fn creation_time(&self) -> Option<SystemTime> {
+        self.tracked
+            .creation_time
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Returns the version of the index format the segment was written in.
+    ///
+    /// Segments written before the format was versioned have the version 0.
+    pub fn format_version(&self) -> u32 {
+        self.tracked.format_version
+    }
+
     /// Returns the number of deleted documents.
     pub fn num_deleted_docs(&self) -> u32 {
         self.tracked
@@ -117,7 +179,12 @@ impl SegmentMeta {
             SegmentComponent::STORE => ".store".to_string(),
             SegmentComponent::FASTFIELDS => ".fast".to_string(),
             SegmentComponent::FIELDNORMS => ".fieldnorm".to_string(),
+            SegmentComponent::OFFSETS => ".offsets".to_string(),
             SegmentComponent::DELETE => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
+            SegmentComponent::FASTFIELDUPDATES => format!(
+                ".{}.upd",
+                self.fast_field_updates_opstamp().unwrap_or(0)
+            ),
         });
         PathBuf::from(path)
     }
@@ -156,11 +223,155 @@ impl SegmentMeta {
         let delete_meta = DeleteMeta {
             num_deleted_docs,
             opstamp,
+            num_bytes: None,
         };
         let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
-            segment_id: inner_meta.segment_id,
-            max_doc: inner_meta.max_doc,
             deletes: Some(delete_meta),
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the number of fields of the schema the segment
+    /// was written with.
+    ///
+    /// The fields appended to the schema afterwards, via
+    /// [`Index::add_fields`](./struct.Index.html#method.add_fields),
+    /// are missing from the segment.
+    ///
+    /// Segments written before the first schema change
+    /// do not record their number of fields.
+    pub fn num_fields(&self) -> Option<u32> {
+        self.tracked.num_fields
+    }
+
+    #[doc(hidden)]
+    pub fn with_num_fields(self, num_fields: u32) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            num_fields: Some(num_fields),
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the number of bytes taken by the files of the segment,
+    /// as recorded when they were written.
+    ///
+    /// Segments written by older versions of tantivy
+    /// do not record their size.
+    pub fn num_bytes(&self) -> Option<u64> {
+        let mut num_bytes = self.tracked.num_bytes?;
+        if let Some(ref delete_meta) = self.tracked.deletes {
+            num_bytes += delete_meta.num_bytes?;
+        }
+        if self.has_fast_field_updates() {
+            num_bytes += self.tracked.fast_field_updates_num_bytes?;
+        }
+        Some(num_bytes)
+    }
+
+    /// Records the number of bytes of the files written with the
+    /// segment, i.e. all of its files except its delete and fast
+    /// field updates files.
+    #[doc(hidden)]
+    pub fn with_num_bytes(self, num_bytes: u64) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            num_bytes: Some(num_bytes),
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Records the number of bytes of the delete file.
+    ///
+    /// This has no effect on a segment without deletes.
+    #[doc(hidden)]
+    pub fn with_delete_num_bytes(self, num_bytes: u64) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| {
+            let mut inner_meta = inner_meta.clone();
+            if let Some(ref mut delete_meta) = inner_meta.deletes {
+                delete_meta.num_bytes = Some(num_bytes);
+            }
+            inner_meta
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Records the number of bytes of the fast field updates file.
+    #[doc(hidden)]
+    pub fn with_fast_field_updates_num_bytes(self, num_bytes: u64) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            fast_field_updates_num_bytes: Some(num_bytes),
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the opstamp of the last fast field update
+    /// taken in account in this segment.
+    ///
+    /// Returns `None` if none of the fast field values
+    /// of the segment were updated.
+    pub fn fast_field_updates_opstamp(&self) -> Option<u64> {
+        self.tracked.fast_field_updates_opstamp
+    }
+
+    /// Returns true iff the segment has a fast field updates file.
+    pub fn has_fast_field_updates(&self) -> bool {
+        self.fast_field_updates_opstamp().is_some()
+    }
+
+    #[doc(hidden)]
+    pub fn with_fast_field_updates(self, opstamp: u64) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            fast_field_updates_opstamp: Some(opstamp),
+            fast_field_updates_num_bytes: None,
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the codec used to encode the postings of this segment.
+    pub fn postings_codec(&self) -> PostingsCodec {
+        self.tracked.postings_codec
+    }
+
+    #[doc(hidden)]
+    pub fn with_postings_codec(self, postings_codec: PostingsCodec) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            postings_codec,
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the compression used for the doc store of this segment.
+    pub fn store_compression(&self) -> StoreCompression {
+        self.tracked.store_compression
+    }
+
+    #[doc(hidden)]
+    pub fn with_store_compression(self, store_compression: StoreCompression) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            store_compression,
+            ..inner_meta.clone()
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the codec used for the term dictionaries of this segment.
+    pub fn term_dictionary_codec(&self) -> TermDictionaryCodec {
+        self.tracked.term_dictionary_codec
+    }
+
+    #[doc(hidden)]
+    pub fn with_term_dictionary_codec(
+        self,
+        term_dictionary_codec: TermDictionaryCodec,
+    ) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            term_dictionary_codec,
+            ..inner_meta.clone()
         });
         SegmentMeta { tracked }
     }
@@ -171,4 +382,91 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     deletes: Option<DeleteMeta>,
+    // Segments created before the codec was configurable
+    // are all bitpacked.
+    #[serde(default)]
+    postings_codec: PostingsCodec,
+    #[serde(default)]
+    store_compression: StoreCompression,
+    #[serde(default)]
+    term_dictionary_codec: TermDictionaryCodec,
+    // Number of seconds since the UNIX epoch.
+    #[serde(default)]
+    creation_time: Option<u64>,
+    // Number of fields of the schema the segment was written with.
+    #[serde(default)]
+    num_fields: Option<u32>,
+    // Opstamp of the last fast field update taken in account
+    // in the fast field updates file, if any.
+    #[serde(default)]
+    fast_field_updates_opstamp: Option<u64>,
+    // Version of the index format the segment was written in.
+    #[serde(default)]
+    format_version: u32,
+    // Number of bytes taken by the files of the segment,
+    // except its delete and fast field updates files.
+    #[serde(default)]
+    num_bytes: Option<u64>,
+    // Number of bytes of the fast field updates file.
+    #[serde(default)]
+    fast_field_updates_num_bytes: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SegmentMeta;
+    use core::SegmentId;
+    use postings::PostingsCodec;
+    use store::StoreCompression;
+    use termdict::TermDictionaryCodec;
+
+    #[test]
+    fn test_new_with_settings() {
+        let settings_meta = SegmentMeta::new(SegmentId::generate_random(), 0)
+            .with_postings_codec(PostingsCodec::VInt)
+            .with_store_compression(StoreCompression::SharedDictionary)
+            .with_term_dictionary_codec(TermDictionaryCodec::Block)
+            .with_num_fields(3)
+            .with_delete_meta(1, 2);
+        let segment_id = SegmentId::generate_random();
+        let segment_meta = SegmentMeta::new_with_settings(segment_id, 10, &settings_meta);
+        assert_eq!(segment_meta.id(), segment_id);
+        assert_eq!(segment_meta.max_doc(), 10);
+        assert_eq!(segment_meta.postings_codec(), PostingsCodec::VInt);
+        assert_eq!(
+            segment_meta.store_compression(),
+            StoreCompression::SharedDictionary
+        );
+        assert_eq!(
+            segment_meta.term_dictionary_codec(),
+            TermDictionaryCodec::Block
+        );
+        assert_eq!(segment_meta.num_fields(), Some(3));
+        assert!(!segment_meta.has_deletes());
+        let legacy_meta = SegmentMeta::new(SegmentId::generate_random(), 0);
+        let segment_meta = SegmentMeta::new_with_settings(segment_id, 10, &legacy_meta);
+        assert_eq!(segment_meta.num_fields(), None);
+    }
+
+    #[test]
+    fn test_num_bytes() {
+        let segment_meta = SegmentMeta::new(SegmentId::generate_random(), 10);
+        assert_eq!(segment_meta.num_bytes(), None);
+        let segment_meta = segment_meta.with_num_bytes(100);
+        assert_eq!(segment_meta.num_bytes(), Some(100));
+        let segment_meta = segment_meta.with_delete_meta(1, 2);
+        assert_eq!(segment_meta.num_bytes(), None);
+        let segment_meta = segment_meta.with_delete_num_bytes(10);
+        assert_eq!(segment_meta.num_bytes(), Some(110));
+        let segment_meta = segment_meta.with_fast_field_updates(3);
+        assert_eq!(segment_meta.num_bytes(), None);
+        let segment_meta = segment_meta.with_fast_field_updates_num_bytes(20);
+        assert_eq!(segment_meta.num_bytes(), Some(130));
+        // a new delete file replaces the previous one.
+        let segment_meta = segment_meta
+            .with_delete_meta(2, 4)
+            .with_delete_num_bytes(12);
+        assert_eq!(segment_meta.num_bytes(), Some(132));
+    }
 }
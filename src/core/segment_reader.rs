@@ -1,5 +1,6 @@
 use common::CompositeFile;
 use common::HasLen;
+use core::FieldStatistics;
 use core::InvertedIndexReader;
 use core::Segment;
 use core::SegmentComponent;
@@ -9,9 +10,14 @@ use error::TantivyError;
 use fastfield::DeleteBitSet;
 use fastfield::FacetReader;
 use fastfield::FastFieldReader;
+use fastfield::FastFieldUpdates;
+use fastfield::IpAddrFastFieldReader;
+use fastfield::StrFastFieldReader;
 use fastfield::{self, FastFieldNotAvailableError};
 use fastfield::{BytesFastFieldReader, FastValue, MultiValueIntFastFieldReader};
 use fieldnorm::FieldNormReader;
+use offsets::OffsetsReader;
+use postings::PostingsCodec;
 use schema::Cardinality;
 use schema::Field;
 use schema::FieldType;
@@ -21,8 +27,8 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 use std::sync::RwLock;
-use store::StoreReader;
-use termdict::TermDictionary;
+use store::{StoreCompression, StoreReader};
+use termdict::{TermDictionary, TermDictionaryCodec};
 use DocId;
 use Result;
 
@@ -53,10 +59,16 @@ pub struct SegmentReader {
     positions_idx_composite: CompositeFile,
     fast_fields_composite: CompositeFile,
     fieldnorms_composite: CompositeFile,
+    offsets_composite: CompositeFile,
 
     store_source: ReadOnlySource,
     delete_bitset_opt: Option<DeleteBitSet>,
+    fast_field_updates: Arc<FastFieldUpdates>,
     schema: Schema,
+    num_fields: u32,
+    postings_codec: PostingsCodec,
+    store_compression: StoreCompression,
+    term_dictionary_codec: TermDictionaryCodec,
 }
 
 impl SegmentReader {
@@ -82,6 +94,17 @@ impl SegmentReader {
         &self.schema
     }
 
+    /// Returns true iff the field was part of the schema
+    /// when the segment was written.
+    ///
+    /// Fields appended to the schema afterwards are missing
+    /// from the segment: their inverted index is empty, their
+    /// fast field readers are not available, and their field
+    /// norms are all `0`.
+    pub fn has_field(&self, field: Field) -> bool {
+        field.0 < self.num_fields
+    }
+
     /// Return the number of documents that have been
     /// deleted in the segment.
     pub fn num_deleted_docs(&self) -> DocId {
@@ -112,15 +135,41 @@ impl SegmentReader {
         let field_entry = self.schema.get_field_entry(field);
         if Item::fast_field_cardinality(field_entry.field_type()) == Some(Cardinality::SingleValue)
         {
-            self.fast_fields_composite
-                .open_read(field)
-                .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))
-                .map(FastFieldReader::open)
+            self.open_fast_field_reader(field)
         } else {
             Err(FastFieldNotAvailableError::new(field_entry))
         }
     }
 
+    /// Accessor to the single value fast field of a given `field`,
+    /// whatever the type of its values.
+    ///
+    /// The values are returned in their `u64` internal representation,
+    /// as the merger does not need to decode them.
+    pub(crate) fn raw_fast_field_reader(
+        &self,
+        field: Field,
+    ) -> fastfield::Result<FastFieldReader<u64>> {
+        self.open_fast_field_reader(field)
+    }
+
+    fn open_fast_field_reader<Item: FastValue>(
+        &self,
+        field: Field,
+    ) -> fastfield::Result<FastFieldReader<Item>> {
+        let field_entry = self.schema.get_field_entry(field);
+        self.fast_fields_composite
+            .open_read(field)
+            .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))
+            .map(FastFieldReader::open)
+            .map(
+                |fast_field_reader| match self.fast_field_updates.field_updates(field) {
+                    Some(updates) => fast_field_reader.with_updates(updates),
+                    None => fast_field_reader,
+                },
+            )
+    }
+
     pub(crate) fn fast_field_reader_with_idx<Item: FastValue>(
         &self,
         field: Field,
@@ -143,19 +192,35 @@ impl SegmentReader {
         let field_entry = self.schema.get_field_entry(field);
         if Item::fast_field_cardinality(field_entry.field_type()) == Some(Cardinality::MultiValues)
         {
-            let idx_reader = self.fast_field_reader_with_idx(field, 0)?;
-            let vals_reader = self.fast_field_reader_with_idx(field, 1)?;
-            Ok(MultiValueIntFastFieldReader::open(idx_reader, vals_reader))
+            self.open_multi_fast_field_reader(field)
         } else {
             Err(FastFieldNotAvailableError::new(field_entry))
         }
     }
 
+    /// Accessor to the multivalued fast field of a given `field`,
+    /// whatever the type of its values, as their `u64` internal representation.
+    pub(crate) fn raw_multi_fast_field_reader(
+        &self,
+        field: Field,
+    ) -> fastfield::Result<MultiValueIntFastFieldReader<u64>> {
+        self.open_multi_fast_field_reader(field)
+    }
+
+    fn open_multi_fast_field_reader<Item: FastValue>(
+        &self,
+        field: Field,
+    ) -> fastfield::Result<MultiValueIntFastFieldReader<Item>> {
+        let idx_reader = self.fast_field_reader_with_idx(field, 0)?;
+        let vals_reader = self.fast_field_reader_with_idx(field, 1)?;
+        Ok(MultiValueIntFastFieldReader::open(idx_reader, vals_reader))
+    }
+
     /// Accessor to the `BytesFastFieldReader` associated to a given `Field`.
     pub fn bytes_fast_field_reader(&self, field: Field) -> fastfield::Result<BytesFastFieldReader> {
         let field_entry = self.schema.get_field_entry(field);
         match *field_entry.field_type() {
-            FieldType::Bytes => {}
+            FieldType::Bytes(ref bytes_options) if bytes_options.is_fast() => {}
             _ => return Err(FastFieldNotAvailableError::new(field_entry)),
         }
         let idx_reader = self
@@ -170,6 +235,24 @@ impl SegmentReader {
         Ok(BytesFastFieldReader::open(idx_reader, values))
     }
 
+    /// Accessor to the `IpAddrFastFieldReader` associated to a given `Field`.
+    pub fn ip_addr_fast_field_reader(
+        &self,
+        field: Field,
+    ) -> fastfield::Result<IpAddrFastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        match *field_entry.field_type() {
+            FieldType::IpAddr(ref ip_addr_options) if ip_addr_options.is_fast() => {}
+            _ => return Err(FastFieldNotAvailableError::new(field_entry)),
+        }
+        let high_bits_reader = self.fast_field_reader_with_idx(field, 0)?;
+        let low_bits_reader = self.fast_field_reader_with_idx(field, 1)?;
+        Ok(IpAddrFastFieldReader::open(
+            high_bits_reader,
+            low_bits_reader,
+        ))
+    }
+
     /// Accessor to the `FacetReader` associated to a given `Field`.
     pub fn facet_reader(&self, field: Field) -> Result<FacetReader> {
         let field_entry = self.schema.get_field_entry(field);
@@ -189,21 +272,47 @@ impl SegmentReader {
                 field_entry.name()
             ))
         })?;
-        let termdict = TermDictionary::from_source(&termdict_source);
+        let termdict =
+            TermDictionary::from_source_with_codec(&termdict_source, self.term_dictionary_codec);
         let facet_reader = FacetReader::new(term_ords_reader, termdict);
         Ok(facet_reader)
     }
 
+    /// Accessor to the `StrFastFieldReader` associated to a given `Field`.
+    ///
+    /// The field must be a text field set as a fast field
+    /// (see `TextOptions::set_fast`).
+    pub fn str_fast_field_reader(&self, field: Field) -> Result<StrFastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        let term_ords_reader = self.multi_fast_field_reader(field).map_err(|_| {
+            TantivyError::InvalidArgument(format!(
+                "The field \"{}\" is not a string fast field.",
+                field_entry.name()
+            ))
+        })?;
+        let termdict = match self.termdict_composite.open_read(field) {
+            Some(termdict_source) => {
+                TermDictionary::from_source_with_codec(&termdict_source, self.term_dictionary_codec)
+            }
+            // None of the documents of the segment have a value for the field.
+            None => TermDictionary::empty(field_entry.field_type()),
+        };
+        Ok(StrFastFieldReader::new(term_ords_reader, termdict))
+    }
+
     /// Accessor to the segment's `Field norms`'s reader.
     ///
     /// Field norms are the length (in tokens) of the fields.
     /// It is used in the computation of the [TfIdf](https://fulmicoton.gitbooks.io/tantivy-doc/content/tfidf.html).
     ///
-    /// They are simply stored as a fast field, serialized in
-    /// the `.fieldnorm` file of the segment.
+    /// They are stored over one byte per document, independently
+    /// from the fast fields, in the `.fieldnorm` file of the segment.
+    /// Scoring reads them directly, without accessing the doc store.
     pub fn get_fieldnorms_reader(&self, field: Field) -> FieldNormReader {
         if let Some(fieldnorm_source) = self.fieldnorms_composite.open_read(field) {
             FieldNormReader::open(fieldnorm_source)
+        } else if !self.has_field(field) {
+            FieldNormReader::open(ReadOnlySource::from(vec![0u8; self.max_doc as usize]))
         } else {
             let field_name = self.schema.get_field_name(field);
             let err_msg = format!(
@@ -214,9 +323,22 @@ impl SegmentReader {
         }
     }
 
+    /// Accessor to the offsets of the tokens of `field`.
+    ///
+    /// Returns `None` if the field was not indexed with offsets
+    /// in this segment.
+    pub fn offsets_reader(&self, field: Field) -> Option<OffsetsReader> {
+        let idx_source = self.offsets_composite.open_read_with_idx(field, 0)?;
+        let vals_source = self.offsets_composite.open_read_with_idx(field, 1)?;
+        Some(OffsetsReader::open(idx_source, vals_source))
+    }
+
     /// Accessor to the segment's `StoreReader`.
     pub fn get_store_reader(&self) -> StoreReader {
-        StoreReader::from_source(self.store_source.clone())
+        StoreReader::from_source_with_compression(
+            self.store_source.clone(),
+            self.store_compression,
+        )
     }
 
     /// Open a new segment for reading.
@@ -253,6 +375,15 @@ impl SegmentReader {
         let fieldnorms_data = segment.open_read(SegmentComponent::FIELDNORMS)?;
         let fieldnorms_composite = CompositeFile::open(&fieldnorms_data)?;
 
+        // segments written before offsets were introduced do not have the file.
+        let offsets_composite = {
+            if let Ok(source) = segment.open_read(SegmentComponent::OFFSETS) {
+                CompositeFile::open(&source)?
+            } else {
+                CompositeFile::empty()
+            }
+        };
+
         let delete_bitset_opt = if segment.meta().has_deletes() {
             let delete_data = segment.open_read(SegmentComponent::DELETE)?;
             Some(DeleteBitSet::open(delete_data))
@@ -260,7 +391,18 @@ impl SegmentReader {
             None
         };
 
+        let fast_field_updates = if segment.meta().has_fast_field_updates() {
+            let updates_data = segment.open_read(SegmentComponent::FASTFIELDUPDATES)?;
+            FastFieldUpdates::open(&updates_data)?
+        } else {
+            FastFieldUpdates::default()
+        };
+
         let schema = segment.schema();
+        let num_fields = segment
+            .meta()
+            .num_fields()
+            .unwrap_or(schema.fields().len() as u32);
         Ok(SegmentReader {
             inv_idx_reader_cache: Arc::new(RwLock::new(HashMap::new())),
             max_doc: segment.meta().max_doc(),
@@ -269,15 +411,29 @@ impl SegmentReader {
             postings_composite,
             fast_fields_composite,
             fieldnorms_composite,
+            offsets_composite,
             segment_id: segment.id(),
             store_source,
             delete_bitset_opt,
+            fast_field_updates: Arc::new(fast_field_updates),
             positions_composite,
             positions_idx_composite,
             schema,
+            num_fields,
+            postings_codec: segment.meta().postings_codec(),
+            store_compression: segment.meta().store_compression(),
+            term_dictionary_codec: segment.meta().term_dictionary_codec(),
         })
     }
 
+    /// Returns the statistics of `field` in the segment.
+    pub fn field_statistics(&self, field: Field) -> FieldStatistics {
+        FieldStatistics {
+            num_docs: u64::from(self.max_doc()),
+            total_num_tokens: self.inverted_index(field).total_num_tokens(),
+        }
+    }
+
     /// Returns a field reader associated to the field given in argument.
     /// If the field was not present in the index during indexing time,
     /// the InvertedIndexReader is empty.
@@ -332,11 +488,12 @@ impl SegmentReader {
             .expect("Index corrupted. Failed to open field positions in composite file.");
 
         let inv_idx_reader = Arc::new(InvertedIndexReader::new(
-            TermDictionary::from_source(&termdict_source),
+            TermDictionary::from_source_with_codec(&termdict_source, self.term_dictionary_codec),
             postings_source,
             positions_source,
             positions_idx_source,
             record_option,
+            self.postings_codec,
         ));
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -349,6 +506,32 @@ impl SegmentReader {
         inv_idx_reader
     }
 
+    /// Loads the term dictionaries, the postings, the fast fields and
+    /// the field norms of the segment in the page cache, and opens
+    /// the inverted index readers of the indexed fields.
+    ///
+    /// The positions and the doc store, which are usually the largest
+    /// and the least frequently accessed files, are not loaded.
+    ///
+    /// This avoids paying for page faults on the first queries over
+    /// a new segment.
+    pub fn warm(&self) {
+        for (field, field_entry) in self.schema.fields().iter().enumerate() {
+            if field_entry.is_indexed() {
+                self.inverted_index(Field(field as u32));
+            }
+        }
+        self.termdict_composite.warm();
+        self.postings_composite.warm();
+        self.fast_fields_composite.warm();
+        self.fieldnorms_composite.warm();
+    }
+
+    /// Returns the fast field values updated since the segment was written.
+    pub(crate) fn fast_field_updates(&self) -> &FastFieldUpdates {
+        &self.fast_field_updates
+    }
+
     /// Returns the segment id
     pub fn segment_id(&self) -> SegmentId {
         self.segment_id
@@ -360,6 +543,30 @@ impl SegmentReader {
         self.delete_bitset_opt.as_ref()
     }
 
+    /// Returns a reader of the same segment, in which the documents
+    /// of `delete_bitset` are deleted instead of the documents
+    /// deleted in the segment.
+    pub(crate) fn with_delete_bitset(&self, delete_bitset: DeleteBitSet) -> SegmentReader {
+        SegmentReader {
+            num_docs: self.max_doc - delete_bitset.len() as DocId,
+            delete_bitset_opt: Some(delete_bitset),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a reader of the same segment, in which the fast field
+    /// values are updated by `fast_field_updates` instead of the
+    /// updates of the segment.
+    pub(crate) fn with_fast_field_updates(
+        &self,
+        fast_field_updates: FastFieldUpdates,
+    ) -> SegmentReader {
+        SegmentReader {
+            fast_field_updates: Arc::new(fast_field_updates),
+            ..self.clone()
+        }
+    }
+
     /// Returns true iff the `doc` is marked
     /// as deleted.
     pub fn is_deleted(&self, doc: DocId) -> bool {
@@ -383,11 +590,13 @@ impl SegmentReader {
             self.positions_idx_composite.space_usage(),
             self.fast_fields_composite.space_usage(),
             self.fieldnorms_composite.space_usage(),
+            self.offsets_composite.space_usage(),
             self.get_store_reader().space_usage(),
             self.delete_bitset_opt
                 .as_ref()
                 .map(|x| x.space_usage())
                 .unwrap_or(0),
+            self.fast_field_updates.space_usage(),
         )
     }
 }
@@ -460,10 +669,10 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => "tantivy"));
-            index_writer.add_document(doc!(name => "horse"));
-            index_writer.add_document(doc!(name => "jockey"));
-            index_writer.add_document(doc!(name => "cap"));
+            index_writer.add_document(doc!(name => "tantivy")).unwrap();
+            index_writer.add_document(doc!(name => "horse")).unwrap();
+            index_writer.add_document(doc!(name => "jockey")).unwrap();
+            index_writer.add_document(doc!(name => "cap")).unwrap();
 
             // we should now have one segment with two docs
             index_writer.commit().unwrap();
@@ -471,8 +680,12 @@ mod test {
 
         {
             let mut index_writer2 = index.writer(50_000_000).unwrap();
-            index_writer2.delete_term(Term::from_field_text(name, "horse"));
-            index_writer2.delete_term(Term::from_field_text(name, "cap"));
+            index_writer2
+                .delete_term(Term::from_field_text(name, "horse"))
+                .unwrap();
+            index_writer2
+                .delete_term(Term::from_field_text(name, "cap"))
+                .unwrap();
 
             // ok, now we should have a deleted doc
             index_writer2.commit().unwrap();
@@ -0,0 +1,139 @@
+use postings::murmurhash2;
+
+/// Describes the part of a sharded dataset held by an index.
+///
+/// Tantivy does not route documents by itself: the descriptor is
+/// recorded in the `meta.json` file of the index, so that a sharding
+/// layer built on top of tantivy can check that the documents and the
+/// queries it sends to an index belong to this index.
+///
+/// See [`Index::set_shard_descriptor`](./struct.Index.html#method.set_shard_descriptor).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShardDescriptor {
+    /// The index holds the documents the routing key of which
+    /// hashes within `[start, end)`.
+    HashRange {
+        /// Start of the range of hashes, included.
+        start: u64,
+        /// End of the range of hashes, excluded.
+        end: u64,
+    },
+    /// The index holds the documents of a single tenant.
+    Tenant {
+        /// Identifier of the tenant.
+        tenant_id: String,
+    },
+}
+
+impl ShardDescriptor {
+    /// Returns the hash of a routing key, as used by the hash range shards.
+    ///
+    /// Hashes range within `[0, 2^32)`.
+    pub fn hash_key(key: &[u8]) -> u64 {
+        u64::from(murmurhash2(key))
+    }
+
+    /// Splits the range of the hashes in `num_shards` contiguous
+    /// hash ranges of equal sizes.
+    ///
+    /// # Panics
+    /// Panics if `num_shards` is 0.
+    pub fn split_hash_range(num_shards: usize) -> Vec<ShardDescriptor> {
+        assert!(num_shards > 0, "The number of shards must be positive.");
+        let num_shards = num_shards as u64;
+        (0..num_shards)
+            .map(|shard_ord| ShardDescriptor::HashRange {
+                start: (shard_ord << 32) / num_shards,
+                end: ((shard_ord + 1) << 32) / num_shards,
+            })
+            .collect()
+    }
+
+    /// Returns true iff the documents with the routing key hash `hash`
+    /// belong to the shard.
+    ///
+    /// A tenant shard does not contain any hash.
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        match *self {
+            ShardDescriptor::HashRange { start, end } => start <= hash && hash < end,
+            ShardDescriptor::Tenant { .. } => false,
+        }
+    }
+
+    /// Returns true iff the documents of the tenant `tenant_id`
+    /// belong to the shard.
+    ///
+    /// A hash range shard does not contain any tenant.
+    pub fn contains_tenant(&self, tenant_id: &str) -> bool {
+        match *self {
+            ShardDescriptor::HashRange { .. } => false,
+            ShardDescriptor::Tenant {
+                tenant_id: ref shard_tenant_id,
+            } => shard_tenant_id == tenant_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ShardDescriptor;
+    use serde_json;
+
+    #[test]
+    fn test_shard_descriptor() {
+        let hash_range = ShardDescriptor::HashRange { start: 10, end: 20 };
+        assert!(!hash_range.contains_hash(9));
+        assert!(hash_range.contains_hash(10));
+        assert!(hash_range.contains_hash(19));
+        assert!(!hash_range.contains_hash(20));
+        assert!(!hash_range.contains_tenant("acme"));
+        let tenant = ShardDescriptor::Tenant {
+            tenant_id: "acme".to_string(),
+        };
+        assert!(tenant.contains_tenant("acme"));
+        assert!(!tenant.contains_tenant("other"));
+        assert!(!tenant.contains_hash(10));
+        assert_eq!(
+            ShardDescriptor::hash_key(b"key"),
+            ShardDescriptor::hash_key(b"key")
+        );
+        assert!(ShardDescriptor::hash_key(b"key") < 1u64 << 32);
+        assert_eq!(
+            serde_json::to_string(&hash_range).unwrap(),
+            r#"{"type":"hash_range","start":10,"end":20}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&tenant).unwrap(),
+            r#"{"type":"tenant","tenant_id":"acme"}"#
+        );
+    }
+
+    #[test]
+    fn test_split_hash_range() {
+        let shards = ShardDescriptor::split_hash_range(3);
+        assert_eq!(shards.len(), 3);
+        assert_eq!(
+            shards[0],
+            ShardDescriptor::HashRange {
+                start: 0,
+                end: 1_431_655_765
+            }
+        );
+        assert_eq!(
+            shards[2],
+            ShardDescriptor::HashRange {
+                start: 2_863_311_530,
+                end: 1u64 << 32
+            }
+        );
+        for hash in vec![0u64, 1_431_655_765, 3_000_000_000, (1u64 << 32) - 1] {
+            let num_shards = shards
+                .iter()
+                .filter(|shard| shard.contains_hash(hash))
+                .count();
+            assert_eq!(num_shards, 1);
+        }
+    }
+}
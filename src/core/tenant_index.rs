@@ -0,0 +1,279 @@
+use collector::Collector;
+use core::Index;
+use error::TantivyError;
+use query::{Query, TenantQuery, TermQuery};
+use schema::{Document, Field, FieldType, IndexRecordOption, Term};
+use IndexWriter;
+use Result;
+
+/// View over the documents of a single tenant of an index
+/// shared by several tenants.
+///
+/// The documents of a tenant are identified by their tenant id,
+/// indexed in a dedicated `tenant_field`. The view
+/// - adds the tenant id to the documents it indexes,
+/// - restricts the queries it runs to the documents of the tenant,
+/// wrapping them into a [`TenantQuery`](./query/struct.TenantQuery.html).
+///
+/// Deletes are not restricted to the tenant with `IndexWriter::delete_term`,
+/// which deletes the documents of all of the tenants containing the term.
+/// `TenantIndex::delete_term` and `TenantIndex::update_document` only
+/// delete the documents of the tenant.
+#[derive(Clone)]
+pub struct TenantIndex {
+    index: Index,
+    tenant_field: Field,
+    tenant_id: String,
+}
+
+impl TenantIndex {
+    /// Creates the view of the documents of `tenant_id` in `index`.
+    ///
+    /// # Errors
+    /// Returns a `SchemaError` if `tenant_field` is not a text field
+    /// indexed with the `raw` tokenizer, and an `InvalidArgument` error
+    /// if `tenant_id` is empty.
+    pub fn new(index: Index, tenant_field: Field, tenant_id: &str) -> Result<TenantIndex> {
+        {
+            let schema = index.schema();
+            let field_entry = schema.get_field_entry(tenant_field);
+            let is_raw = match *field_entry.field_type() {
+                FieldType::Str(ref options) => options
+                    .get_indexing_options()
+                    .map(|indexing| indexing.tokenizer() == "raw")
+                    .unwrap_or(false),
+                _ => false,
+            };
+            if !is_raw {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} cannot identify tenants as it is not a text field \
+                     indexed with the raw tokenizer.",
+                    field_entry.name()
+                )));
+            }
+        }
+        if tenant_id.is_empty() {
+            return Err(TantivyError::InvalidArgument(
+                "The tenant id must not be empty.".to_string(),
+            ));
+        }
+        Ok(TenantIndex {
+            index,
+            tenant_field,
+            tenant_id: tenant_id.to_string(),
+        })
+    }
+
+    /// Returns the underlying index, shared by all of the tenants.
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Returns the id of the tenant.
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// Returns the term identifying the documents of the tenant.
+    pub fn tenant_term(&self) -> Term {
+        Term::from_field_text(self.tenant_field, &self.tenant_id)
+    }
+
+    /// Adds a document of the tenant, adding the tenant id
+    /// to the document.
+    ///
+    /// Returns the opstamp of the operation, see
+    /// [`IndexWriter::add_document`](./struct.IndexWriter.html#method.add_document).
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if the document
+    /// already has a value for the tenant field.
+    pub fn add_document(&self, index_writer: &mut IndexWriter, document: Document) -> Result<u64> {
+        let document = self.with_tenant_id(document)?;
+        index_writer.add_document(document)
+    }
+
+    /// Deletes the documents of the tenant containing `term`.
+    ///
+    /// Returns the opstamp of the operation, see
+    /// [`IndexWriter::delete_query`](./struct.IndexWriter.html#method.delete_query).
+    pub fn delete_term(&self, index_writer: &mut IndexWriter, term: Term) -> Result<u64> {
+        index_writer.delete_query(self.term_query(term))
+    }
+
+    /// Replaces the documents of the tenant containing `term`
+    /// with `document`, adding the tenant id to the document.
+    ///
+    /// The documents of the other tenants containing `term` are kept.
+    ///
+    /// Returns the opstamp of the added document, see
+    /// [`IndexWriter::update_document`](./struct.IndexWriter.html#method.update_document).
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if the document already has a
+    /// value for the tenant field, in which case neither the delete nor
+    /// the document are recorded.
+    pub fn update_document(
+        &self,
+        index_writer: &mut IndexWriter,
+        term: Term,
+        document: Document,
+    ) -> Result<u64> {
+        let document = self.with_tenant_id(document)?;
+        index_writer.check_poisoned()?;
+        index_writer.check_disk_space_limit()?;
+        index_writer.delete_query(self.term_query(term))?;
+        index_writer.add_document(document)
+    }
+
+    /// Returns the query matching the documents of the tenant containing `term`.
+    fn term_query(&self, term: Term) -> Box<Query> {
+        let term_query = TermQuery::new(term, IndexRecordOption::Basic);
+        Box::new(self.filter_query(Box::new(term_query)))
+    }
+
+    /// Adds the tenant id to `document`, checking that it does not
+    /// have a value for the tenant field yet.
+    fn with_tenant_id(&self, mut document: Document) -> Result<Document> {
+        if document.get_first(self.tenant_field).is_some() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The document already has a value for the tenant field {:?}.",
+                self.index.schema().get_field_name(self.tenant_field)
+            )));
+        }
+        document.add_text(self.tenant_field, &self.tenant_id);
+        Ok(document)
+    }
+
+    /// Restricts `query` to the documents of the tenant.
+    pub fn filter_query(&self, query: Box<Query>) -> TenantQuery {
+        TenantQuery::new(query, self.tenant_term())
+    }
+
+    /// Runs `query` over the documents of the tenant, in the
+    /// last searcher loaded by the index.
+    pub fn search<C: Collector>(&self, query: &Query, collector: &C) -> Result<C::Fruit> {
+        let tenant_query = self.filter_query(query.box_clone());
+        self.index.searcher().search(&tenant_query, collector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::TenantIndex;
+    use collector::{Count, TopDocs};
+    use core::Index;
+    use query::{AllQuery, QueryParser, TermQuery};
+    use schema::{IndexRecordOption, Schema, Term, STORED, STRING, TEXT};
+
+    #[test]
+    fn test_tenant_index() {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let acme = TenantIndex::new(index.clone(), tenant, "acme").unwrap();
+        let globex = TenantIndex::new(index.clone(), tenant, "globex").unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            acme.add_document(&mut index_writer, doc!(body => "hello happy tax payer"))
+                .unwrap();
+            acme.add_document(&mut index_writer, doc!(body => "hello"))
+                .unwrap();
+            globex
+                .add_document(&mut index_writer, doc!(body => "hello world"))
+                .unwrap();
+            assert!(acme
+                .add_document(&mut index_writer, doc!(tenant => "globex", body => "hello"))
+                .is_err());
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        assert_eq!(acme.search(&AllQuery, &Count).unwrap(), 2);
+        assert_eq!(globex.search(&AllQuery, &Count).unwrap(), 1);
+        let query_parser = QueryParser::for_index(&index, vec![body]);
+        let query = query_parser.parse_query("hello").unwrap();
+        assert_eq!(index.searcher().search(&query, &Count).unwrap(), 3);
+        assert_eq!(acme.search(&query, &Count).unwrap(), 2);
+        let world = TermQuery::new(
+            Term::from_field_text(body, "world"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert_eq!(acme.search(&world, &Count).unwrap(), 0);
+        assert_eq!(globex.search(&world, &Count).unwrap(), 1);
+        // the scores are not altered by the tenant filter.
+        let top_docs = index
+            .searcher()
+            .search(&query, &TopDocs::with_limit(3))
+            .unwrap();
+        let acme_top_docs = acme.search(&query, &TopDocs::with_limit(3)).unwrap();
+        assert_eq!(acme_top_docs.len(), 2);
+        for &(score, doc_address) in &acme_top_docs {
+            assert!(top_docs.contains(&(score, doc_address)));
+        }
+        let other = TenantIndex::new(index.clone(), tenant, "other").unwrap();
+        assert_eq!(other.search(&AllQuery, &Count).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_tenant_index_invalid() {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(TenantIndex::new(index.clone(), body, "acme").is_err());
+        assert!(TenantIndex::new(index.clone(), tenant, "").is_err());
+    }
+
+    #[test]
+    fn test_tenant_index_delete_and_update() {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let key = schema_builder.add_text_field("key", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let acme = TenantIndex::new(index.clone(), tenant, "acme").unwrap();
+        let globex = TenantIndex::new(index.clone(), tenant, "globex").unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for tenant_index in &[&acme, &globex] {
+            tenant_index
+                .add_document(&mut index_writer, doc!(key => "a", body => "hello"))
+                .unwrap();
+            tenant_index
+                .add_document(&mut index_writer, doc!(key => "b", body => "hello"))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        let key_a = Term::from_field_text(key, "a");
+        let key_b = Term::from_field_text(key, "b");
+        acme.delete_term(&mut index_writer, key_a.clone()).unwrap();
+        acme.update_document(
+            &mut index_writer,
+            key_b.clone(),
+            doc!(key => "b", body => "world"),
+        )
+        .unwrap();
+        assert!(acme
+            .update_document(
+                &mut index_writer,
+                key_b.clone(),
+                doc!(tenant => "acme", key => "b", body => "world")
+            )
+            .is_err());
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let query_a = TermQuery::new(key_a, IndexRecordOption::Basic);
+        let query_b = TermQuery::new(key_b, IndexRecordOption::Basic);
+        let world = TermQuery::new(
+            Term::from_field_text(body, "world"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(acme.search(&query_a, &Count).unwrap(), 0);
+        assert_eq!(acme.search(&query_b, &Count).unwrap(), 1);
+        assert_eq!(acme.search(&world, &Count).unwrap(), 1);
+        assert_eq!(globex.search(&query_a, &Count).unwrap(), 1);
+        assert_eq!(globex.search(&query_b, &Count).unwrap(), 1);
+        assert_eq!(globex.search(&world, &Count).unwrap(), 0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+struct CachedFile {
+    num_bytes: usize,
+    last_access: u64,
+}
+
+/// Keeps track of the files of the cache, in order
+/// to evict the least recently used ones once the cache
+/// exceeds its capacity.
+pub(crate) struct CacheState {
+    capacity: Option<usize>,
+    num_bytes: usize,
+    clock: u64,
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+impl CacheState {
+    pub fn new(capacity: Option<usize>) -> CacheState {
+        CacheState {
+            capacity,
+            num_bytes: 0,
+            clock: 0,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Records an access to a cached file, and returns
+    /// the files that should be evicted from the cache.
+    pub fn access(&mut self, path: &Path, num_bytes: usize) -> Vec<PathBuf> {
+        self.clock += 1;
+        let cached_file = CachedFile {
+            num_bytes,
+            last_access: self.clock,
+        };
+        if let Some(previous_file) = self.files.insert(path.to_owned(), cached_file) {
+            self.num_bytes -= previous_file.num_bytes;
+        }
+        self.num_bytes += num_bytes;
+        let mut evicted_paths = Vec::new();
+        if let Some(capacity) = self.capacity {
+            // the file that was just accessed is never evicted.
+            while self.num_bytes > capacity && self.files.len() > 1 {
+                let lru_path = self
+                    .files
+                    .iter()
+                    .min_by_key(|&(_, cached_file)| cached_file.last_access)
+                    .map(|(path, _)| path.clone())
+                    .expect("The cache cannot be empty.");
+                self.remove(&lru_path);
+                evicted_paths.push(lru_path);
+            }
+        }
+        evicted_paths
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        if let Some(cached_file) = self.files.remove(path) {
+            self.num_bytes -= cached_file.num_bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::CacheState;
+    use std::path::Path;
+
+    #[test]
+    fn test_cache_state_evicts_least_recently_used() {
+        let mut cache_state = CacheState::new(Some(10));
+        assert!(cache_state.access(Path::new("a"), 4).is_empty());
+        assert!(cache_state.access(Path::new("b"), 4).is_empty());
+        assert!(cache_state.access(Path::new("a"), 4).is_empty());
+        assert_eq!(
+            cache_state.access(Path::new("c"), 4),
+            vec![Path::new("b").to_owned()]
+        );
+        cache_state.remove(Path::new("a"));
+        assert!(cache_state.access(Path::new("d"), 4).is_empty());
+        // a file larger than the capacity is kept until the next access.
+        assert_eq!(cache_state.access(Path::new("e"), 20).len(), 2);
+        assert_eq!(
+            cache_state.access(Path::new("f"), 1),
+            vec![Path::new("e").to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_cache_state_unbounded() {
+        let mut cache_state = CacheState::new(None);
+        for i in 0..100 {
+            assert!(cache_state
+                .access(Path::new(&i.to_string()), 1_000)
+                .is_empty());
+        }
+    }
+}
@@ -1,21 +1,27 @@
 use directory::error::{DeleteError, OpenReadError, OpenWriteError};
-use directory::{ReadOnlySource, WritePtr};
+use directory::{ReadOnlySource, WatchCallback, WatchHandle, WritePtr};
 use std::fmt;
 use std::io;
 use std::marker::Send;
 use std::marker::Sync;
 use std::path::Path;
 use std::result;
+use Result as TantivyResult;
 
 /// Write-once read many (WORM) abstraction for where
 /// tantivy's data should be stored.
 ///
-/// There are currently two implementations of `Directory`
+/// There are currently four implementations of `Directory`
 ///
 /// - The [`MMapDirectory`](struct.MmapDirectory.html), this
 /// should be your default choice.
 /// - The [`RAMDirectory`](struct.RAMDirectory.html), which
 /// should be used mostly for tests.
+/// - The [`ObjectStoreDirectory`](struct.ObjectStoreDirectory.html),
+/// serving an index stored in an object store from a local cache.
+/// - The [`EncryptedDirectory`](struct.EncryptedDirectory.html),
+/// wrapping another directory to encrypt its files at rest. It
+/// requires the `encryption` feature.
 ///
 pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     /// Opens a virtual file for read.
@@ -34,6 +40,10 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     ///
     /// Removing a nonexistent file, yields a
     /// `DeleteError::DoesNotExist`.
+    ///
+    /// On Windows, files that are still open (e.g. mmapped)
+    /// cannot be removed, and implementations may return
+    /// an `IOError` in that case.
     fn delete(&self, path: &Path) -> result::Result<(), DeleteError>;
 
     /// Returns true iff the file exists
@@ -73,6 +83,14 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     ///
     /// The file may or may not previously exist.
     fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()>;
+
+    /// Registers a callback, called every time the meta file
+    /// of the index (`meta.json`) changes, e.g. after a commit.
+    ///
+    /// The callback may be called from another thread,
+    /// and may be called spuriously.
+    /// It is unregistered once the returned `WatchHandle` is dropped.
+    fn watch(&self, watch_callback: WatchCallback) -> TantivyResult<WatchHandle>;
 }
 
 /// DirectoryClone
@@ -0,0 +1,418 @@
+extern crate aes_gcm;
+extern crate rand;
+
+use self::aes_gcm::aead::generic_array::GenericArray;
+use self::aes_gcm::aead::{Aead, NewAead, Payload};
+use self::aes_gcm::Aes256Gcm;
+use common::BinarySerializable;
+use directory::error::{DeleteError, IOError, OpenReadError, OpenWriteError};
+use directory::{Directory, ReadOnlySource, WatchCallback, WatchHandle};
+use directory::{SeekableWrite, WritePtr};
+use std::fmt;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::result;
+use std::sync::Arc;
+use Result as TantivyResult;
+
+/// Maximum number of bytes of plain text encrypted in a block.
+const BLOCK_SIZE: usize = 1 << 16;
+
+/// Length of the random identifier starting every file,
+/// which is also the length of the nonces.
+const FILE_ID_LEN: usize = 12;
+
+/// Additional authenticated data of the blocks, telling
+/// whether the block is the last one of its file.
+/// A file truncated at a block boundary is therefore detected.
+const INNER_BLOCK_AAD: &[u8] = &[0u8];
+const LAST_BLOCK_AAD: &[u8] = &[1u8];
+
+/// The nonce of a block is the identifier of its file, with the
+/// ordinal of the block xored into its last 4 bytes, so that a nonce
+/// is never used twice with the same key.
+fn block_nonce(file_id: &[u8], block_ord: u32) -> [u8; FILE_ID_LEN] {
+    let mut nonce = [0u8; FILE_ID_LEN];
+    nonce.copy_from_slice(file_id);
+    for (nonce_byte, ord_byte) in nonce[FILE_ID_LEN - 4..]
+        .iter_mut()
+        .zip(block_ord.to_le_bytes().iter())
+    {
+        *nonce_byte ^= *ord_byte;
+    }
+    nonce
+}
+
+/// Returns a random 96 bits file identifier.
+fn new_file_id() -> [u8; FILE_ID_LEN] {
+    rand::random()
+}
+
+fn encrypt_block<W: Write>(
+    cipher: &Aes256Gcm,
+    file_id: &[u8],
+    block_ord: u32,
+    is_last_block: bool,
+    plain_text: &[u8],
+    write: &mut W,
+) -> io::Result<()> {
+    let nonce = block_nonce(file_id, block_ord);
+    let payload = Payload {
+        msg: plain_text,
+        aad: if is_last_block {
+            LAST_BLOCK_AAD
+        } else {
+            INNER_BLOCK_AAD
+        },
+    };
+    let cipher_text = cipher
+        .encrypt(GenericArray::from_slice(&nonce), payload)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "Failed to encrypt a block."))?;
+    (cipher_text.len() as u32).serialize(write)?;
+    write.write_all(&cipher_text[..])
+}
+
+/// Encrypts the content of a whole file.
+fn encrypt(cipher: &Aes256Gcm, plain_text: &[u8]) -> io::Result<Vec<u8>> {
+    let file_id = new_file_id();
+    let mut data = file_id.to_vec();
+    let num_blocks = if plain_text.is_empty() {
+        // an empty file is made of an empty last block.
+        1
+    } else {
+        (plain_text.len() + BLOCK_SIZE - 1) / BLOCK_SIZE
+    };
+    for block_ord in 0..num_blocks {
+        let start = block_ord * BLOCK_SIZE;
+        let stop = plain_text.len().min(start + BLOCK_SIZE);
+        let is_last_block = block_ord + 1 == num_blocks;
+        encrypt_block(
+            cipher,
+            &file_id,
+            block_ord as u32,
+            is_last_block,
+            &plain_text[start..stop],
+            &mut data,
+        )?;
+    }
+    Ok(data)
+}
+
+/// Decrypts the content of a whole file.
+fn decrypt(cipher: &Aes256Gcm, data: &[u8]) -> io::Result<Vec<u8>> {
+    let truncated_error = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "The encrypted file is truncated.",
+        )
+    };
+    if data.len() < FILE_ID_LEN {
+        return Err(truncated_error());
+    }
+    let (file_id, mut blocks) = data.split_at(FILE_ID_LEN);
+    let mut plain_text = Vec::with_capacity(blocks.len());
+    let mut block_ord = 0u32;
+    loop {
+        if blocks.is_empty() {
+            // the last block is missing.
+            return Err(truncated_error());
+        }
+        let cipher_text_len = u32::deserialize(&mut blocks)? as usize;
+        if blocks.len() < cipher_text_len {
+            return Err(truncated_error());
+        }
+        let (cipher_text, remaining_blocks) = blocks.split_at(cipher_text_len);
+        let nonce = block_nonce(file_id, block_ord);
+        let is_last_block = remaining_blocks.is_empty();
+        let payload = Payload {
+            msg: cipher_text,
+            aad: if is_last_block {
+                LAST_BLOCK_AAD
+            } else {
+                INNER_BLOCK_AAD
+            },
+        };
+        let block = cipher
+            .decrypt(GenericArray::from_slice(&nonce), payload)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Failed to decrypt a block. The key is wrong, or the file is corrupted.",
+                )
+            })?;
+        plain_text.extend_from_slice(&block[..]);
+        if is_last_block {
+            return Ok(plain_text);
+        }
+        blocks = remaining_blocks;
+        block_ord += 1;
+    }
+}
+
+/// Writer associated with the `EncryptedDirectory`.
+///
+/// The plain text is encrypted by blocks of `BLOCK_SIZE` bytes.
+/// Flushing encrypts the pending bytes as a shorter block, and
+/// terminating the writer encrypts the last block of the file,
+/// which may be empty.
+struct EncryptedWriter {
+    underlying: WritePtr,
+    cipher: Arc<Aes256Gcm>,
+    file_id: [u8; FILE_ID_LEN],
+    block_ord: u32,
+    buffer: Vec<u8>,
+}
+
+impl EncryptedWriter {
+    fn write_block(&mut self, len: usize, is_last_block: bool) -> io::Result<()> {
+        encrypt_block(
+            &self.cipher,
+            &self.file_id,
+            self.block_ord,
+            is_last_block,
+            &self.buffer[..len],
+            &mut self.underlying,
+        )?;
+        self.buffer.drain(..len);
+        self.block_ord += 1;
+        Ok(())
+    }
+}
+
+impl Write for EncryptedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        // a full block is only written once more data follows it,
+        // as it may turn out to be the last block of the file.
+        while self.buffer.len() > BLOCK_SIZE {
+            self.write_block(BLOCK_SIZE, false)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let len = self.buffer.len();
+            self.write_block(len, false)?;
+        }
+        self.underlying.flush()
+    }
+}
+
+impl Seek for EncryptedWriter {
+    /// Seeking is not supported, as the blocks are encrypted
+    /// as soon as they are complete.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Cannot seek {:?} in an encrypted file.", pos),
+        ))
+    }
+}
+
+impl SeekableWrite for EncryptedWriter {
+    fn terminate(&mut self) -> io::Result<()> {
+        let len = self.buffer.len();
+        self.write_block(len, true)?;
+        self.underlying.terminate()
+    }
+}
+
+/// Wrapper of directories that encrypts the files at rest.
+///
+/// The files are encrypted with AES-256-GCM, by blocks of 64KB,
+/// so that any modification of their content, including its
+/// truncation, is detected when they are read. The files written
+/// with `open_write` can only be read once they are terminated. As tantivy needs the content of the files
+/// in memory, the files are entirely decrypted by `open_read`,
+/// and held in anonymous memory while they are open.
+///
+/// Only the content of the files is encrypted: their names,
+/// their number and their sizes are visible to anyone with
+/// access to the wrapped directory.
+///
+/// ```rust
+/// use tantivy::directory::{EncryptedDirectory, RAMDirectory};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::Index;
+///
+/// let mut schema_builder = Schema::builder();
+/// schema_builder.add_text_field("title", TEXT);
+/// let key = [42u8; 32];
+/// let directory = EncryptedDirectory::wrap(RAMDirectory::create(), &key);
+/// let index = Index::create(directory, schema_builder.build()).unwrap();
+/// ```
+pub struct EncryptedDirectory {
+    underlying: Box<Directory>,
+    cipher: Arc<Aes256Gcm>,
+}
+
+impl EncryptedDirectory {
+    /// Wraps `directory`, encrypting its files with the 256 bits `key`.
+    pub fn wrap<D: Directory>(directory: D, key: &[u8; 32]) -> EncryptedDirectory {
+        EncryptedDirectory {
+            underlying: Box::new(directory),
+            cipher: Arc::new(Aes256Gcm::new(GenericArray::from_slice(key))),
+        }
+    }
+}
+
+impl Clone for EncryptedDirectory {
+    fn clone(&self) -> EncryptedDirectory {
+        EncryptedDirectory {
+            underlying: self.underlying.box_clone(),
+            cipher: Arc::clone(&self.cipher),
+        }
+    }
+}
+
+impl fmt::Debug for EncryptedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EncryptedDirectory({:?})", self.underlying)
+    }
+}
+
+impl Directory for EncryptedDirectory {
+    fn open_read(&self, path: &Path) -> result::Result<ReadOnlySource, OpenReadError> {
+        let source = self.underlying.open_read(path)?;
+        let plain_text = decrypt(&self.cipher, source.as_slice())
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        Ok(ReadOnlySource::from(plain_text))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&mut self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        let mut underlying = self.underlying.open_write(path)?;
+        let file_id = new_file_id();
+        underlying
+            .write_all(&file_id)
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        let encrypted_writer = EncryptedWriter {
+            underlying,
+            cipher: Arc::clone(&self.cipher),
+            file_id,
+            block_ord: 0u32,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+        };
+        Ok(BufWriter::new(Box::new(encrypted_writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        let data = self.underlying.atomic_read(path)?;
+        let plain_text = decrypt(&self.cipher, &data[..])
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        Ok(plain_text)
+    }
+
+    fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let encrypted_data = encrypt(&self.cipher, data)?;
+        self.underlying.atomic_write(path, &encrypted_data[..])
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> TantivyResult<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{EncryptedDirectory, BLOCK_SIZE};
+    use collector::Count;
+    use directory::{Directory, RAMDirectory, SeekableWrite};
+    use query::TermQuery;
+    use schema::{IndexRecordOption, Schema, TEXT};
+    use std::io::Write;
+    use std::path::Path;
+    use Index;
+    use Term;
+
+    #[test]
+    fn test_encrypted_directory() {
+        let ram_directory = RAMDirectory::create();
+        let mut directory = EncryptedDirectory::wrap(ram_directory.clone(), &[1u8; 32]);
+        let path = Path::new("some_path_for_test");
+        let data: Vec<u8> = (0..BLOCK_SIZE * 2 + 10).map(|i| (i % 7) as u8).collect();
+        {
+            let mut write = directory.open_write(path).unwrap();
+            write.write_all(&data[..BLOCK_SIZE + 3]).unwrap();
+            write.flush().unwrap();
+            write.write_all(&data[BLOCK_SIZE + 3..]).unwrap();
+            write.terminate().unwrap();
+        }
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), &data[..]);
+        let encrypted_data = ram_directory.open_read(path).unwrap();
+        assert!(!encrypted_data
+            .as_slice()
+            .windows(100)
+            .any(|window| window == &data[..100]));
+
+        directory.atomic_write(Path::new("meta"), b"abc").unwrap();
+        assert_eq!(directory.atomic_read(Path::new("meta")).unwrap(), b"abc");
+
+        let other_key_directory = EncryptedDirectory::wrap(ram_directory.clone(), &[2u8; 32]);
+        assert!(other_key_directory.open_read(path).is_err());
+        assert!(other_key_directory.atomic_read(Path::new("meta")).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_directory_truncated() {
+        let mut ram_directory = RAMDirectory::create();
+        let mut directory = EncryptedDirectory::wrap(ram_directory.clone(), &[1u8; 32]);
+        let data: Vec<u8> = (0..BLOCK_SIZE * 3).map(|i| (i % 7) as u8).collect();
+        directory
+            .atomic_write(Path::new("meta"), &data[..])
+            .unwrap();
+        let encrypted_data = ram_directory.atomic_read(Path::new("meta")).unwrap();
+        // truncates the file at a block boundary.
+        let block_len = (encrypted_data.len() - 12) / 3;
+        let truncated_data = &encrypted_data[..encrypted_data.len() - block_len];
+        ram_directory
+            .atomic_write(Path::new("truncated"), truncated_data)
+            .unwrap();
+        assert!(directory.atomic_read(Path::new("truncated")).is_err());
+
+        let path = Path::new("not_terminated");
+        {
+            let mut write = directory.open_write(path).unwrap();
+            write.write_all(&data[..]).unwrap();
+            write.flush().unwrap();
+        }
+        assert!(directory.open_read(path).is_err());
+
+        directory.atomic_write(Path::new("empty"), b"").unwrap();
+        assert!(directory
+            .atomic_read(Path::new("empty"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_encrypted_index() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let directory = EncryptedDirectory::wrap(RAMDirectory::create(), &[1u8; 32]);
+        let index = Index::create(directory, schema_builder.build()).unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "b"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(index.searcher().search(&query, &Count).unwrap(), 2);
+    }
+}
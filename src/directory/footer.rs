@@ -0,0 +1,204 @@
+use common::BinarySerializable;
+use core::INDEX_FORMAT_VERSION;
+use crc32fast::Hasher;
+use directory::{ReadOnlySource, SeekableWrite};
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// Magic number ending the footer, making it possible to tell
+/// the files written with a footer from the files written
+/// by the previous versions of tantivy.
+const FOOTER_MAGIC_NUMBER: u32 = 0x7A4E_F007;
+
+/// The footer is made of the CRC32 of the content of the file,
+/// the format version and the magic number, each of them
+/// serialized over 4 bytes.
+pub const FOOTER_LEN: usize = 12;
+
+/// Footer appended to the segment component files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Footer {
+    /// CRC32 of the content of the file, footer excluded.
+    pub crc: u32,
+    /// Version of the index format the file was written in.
+    pub format_version: u32,
+}
+
+impl Footer {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.crc.serialize(writer)?;
+        self.format_version.serialize(writer)?;
+        FOOTER_MAGIC_NUMBER.serialize(writer)
+    }
+
+    /// Splits `source` into its content and its footer.
+    ///
+    /// Returns `None` as the footer if the file was written
+    /// without footer, by a previous version of tantivy.
+    pub fn extract(source: &ReadOnlySource) -> (ReadOnlySource, Option<Footer>) {
+        if source.len() < FOOTER_LEN {
+            return (source.clone(), None);
+        }
+        let content_len = source.len() - FOOTER_LEN;
+        let mut footer_data = &source.as_slice()[content_len..];
+        let crc = u32::deserialize(&mut footer_data).unwrap();
+        let format_version = u32::deserialize(&mut footer_data).unwrap();
+        let magic_number = u32::deserialize(&mut footer_data).unwrap();
+        if magic_number != FOOTER_MAGIC_NUMBER {
+            return (source.clone(), None);
+        }
+        let footer = Footer {
+            crc,
+            format_version,
+        };
+        (source.slice_to(content_len), Some(footer))
+    }
+
+    /// Returns an error if the file was written in a more recent
+    /// version of the index format than `INDEX_FORMAT_VERSION`.
+    pub fn check_format_version(&self) -> io::Result<()> {
+        if self.format_version > INDEX_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The file was written in the version {} of the index format, while this \
+                     version of tantivy only supports the versions up to {}.",
+                    self.format_version, INDEX_FORMAT_VERSION
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true iff the CRC32 of `content` matches
+    /// the one recorded in the footer.
+    pub fn is_valid_checksum(&self, content: &[u8]) -> bool {
+        let mut hasher = Hasher::new();
+        hasher.update(content);
+        hasher.finalize() == self.crc
+    }
+}
+
+/// Wraps a `Write` to compute the CRC32 of the data written,
+/// and append the `Footer` of the file when it is terminated.
+///
+/// A file that is dropped without being terminated, e.g. because
+/// its serialization failed, does not get any footer. Its content
+/// is only flushed.
+pub struct FooterWrite<W: Write> {
+    underlying: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> FooterWrite<W> {
+    pub fn new(underlying: W) -> FooterWrite<W> {
+        FooterWrite {
+            underlying,
+            hasher: Hasher::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for FooterWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        self.hasher.update(&buf[..num_bytes]);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl<W: Write> Seek for FooterWrite<W> {
+    /// Seeking would invalidate the checksum, and is not supported.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Cannot seek {:?} in a file with a footer.", pos),
+        ))
+    }
+}
+
+impl<W: Write> Drop for FooterWrite<W> {
+    fn drop(&mut self) {
+        if let Err(e) = self.underlying.flush() {
+            warn!(
+                "Failed to flush a file dropped before being terminated: {:?}",
+                e
+            );
+        }
+    }
+}
+
+impl<W: SeekableWrite> SeekableWrite for FooterWrite<W> {
+    fn terminate(&mut self) -> io::Result<()> {
+        let footer = Footer {
+            crc: self.hasher.clone().finalize(),
+            format_version: INDEX_FORMAT_VERSION,
+        };
+        footer.serialize(&mut self.underlying)?;
+        self.underlying.terminate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Footer, FooterWrite, FOOTER_LEN};
+    use core::INDEX_FORMAT_VERSION;
+    use directory::{Directory, RAMDirectory, ReadOnlySource, SeekableWrite};
+    use std::io::Write;
+    use std::path::Path;
+
+    #[test]
+    fn test_footer() {
+        let mut directory = RAMDirectory::create();
+        let path = Path::new("with_footer");
+        {
+            let mut write = FooterWrite::new(directory.open_write(path).unwrap());
+            write.write_all(b"hello").unwrap();
+            write.terminate().unwrap();
+        }
+        let source = directory.open_read(path).unwrap();
+        assert_eq!(source.len(), 5 + FOOTER_LEN);
+        let (content, footer_opt) = Footer::extract(&source);
+        assert_eq!(content.as_slice(), b"hello");
+        let footer = footer_opt.unwrap();
+        assert_eq!(footer.format_version, INDEX_FORMAT_VERSION);
+        assert!(footer.check_format_version().is_ok());
+        assert!(footer.is_valid_checksum(content.as_slice()));
+        assert!(!footer.is_valid_checksum(b"hellO"));
+    }
+
+    #[test]
+    fn test_footer_not_terminated() {
+        let mut directory = RAMDirectory::create();
+        let path = Path::new("without_footer");
+        {
+            let mut write = FooterWrite::new(directory.open_write(path).unwrap());
+            write.write_all(b"hello").unwrap();
+            write.flush().unwrap();
+        }
+        let source = directory.open_read(path).unwrap();
+        assert_eq!(source.as_slice(), b"hello");
+        assert!(Footer::extract(&source).1.is_none());
+    }
+
+    #[test]
+    fn test_footer_more_recent_format_version() {
+        let footer = Footer {
+            crc: 0u32,
+            format_version: INDEX_FORMAT_VERSION + 1,
+        };
+        assert!(footer.check_format_version().is_err());
+    }
+
+    #[test]
+    fn test_no_footer() {
+        let source = ReadOnlySource::from(b"legacy file without footer".to_vec());
+        let (content, footer_opt) = Footer::extract(&source);
+        assert!(footer_opt.is_none());
+        assert_eq!(content.as_slice(), source.as_slice());
+    }
+}
@@ -1,6 +1,6 @@
 use core::MANAGED_FILEPATH;
 use directory::error::{DeleteError, IOError, OpenReadError, OpenWriteError};
-use directory::{ReadOnlySource, WritePtr};
+use directory::{ReadOnlySource, WatchCallback, WatchHandle, WritePtr};
 use error::TantivyError;
 use indexer::LockType;
 use serde_json;
@@ -227,6 +227,10 @@ impl Directory for ManagedDirectory {
     fn exists(&self, path: &Path) -> bool {
         self.directory.exists(path)
     }
+
+    fn watch(&self, watch_callback: WatchCallback) -> Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
 }
 
 impl Clone for ManagedDirectory {
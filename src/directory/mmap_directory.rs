@@ -1,11 +1,16 @@
 use atomicwrites;
 use common::make_io_err;
+use core::META_FILEPATH;
+use directory::cache_state::CacheState;
 use directory::error::{DeleteError, IOError, OpenDirectoryError, OpenReadError, OpenWriteError};
 use directory::shared_vec_slice::SharedVecSlice;
 use directory::Directory;
 use directory::ReadOnlySource;
+use directory::SeekableWrite;
 use directory::WritePtr;
+use directory::{WatchCallback, WatchCallbackList, WatchHandle};
 use fst::raw::MmapReadOnly;
+use notify::{self, RawEvent, RecursiveMode, Watcher};
 use std::collections::hash_map::Entry as HashMapEntry;
 use std::collections::HashMap;
 use std::convert::From;
@@ -16,21 +21,56 @@ use std::io::{self, Seek, SeekFrom};
 use std::io::{BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use std::result;
+use std::sync::mpsc::channel;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::RwLock;
+use std::thread;
 use tempdir::TempDir;
+use Result as TantivyResult;
+
+/// Size of the chunks read by a single positional read call.
+const POSITIONAL_READ_CHUNK_LEN: usize = 1 << 20;
+
+/// Default number of bytes of anonymous memory held by the cache
+/// of a directory opened with `ReadMode::PositionalRead`.
+pub const DEFAULT_POSITIONAL_READ_CACHE_CAPACITY: usize = 256 * (1 << 20);
+
+/// Defines how the files of a `MmapDirectory` are made available
+/// for reading.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadMode {
+    /// Files are mmapped. This is the default and should be your
+    /// choice when the index sits on a local disk.
+    Mmap,
+    /// Files are read into anonymous memory using positional reads,
+    /// and the resulting buffers are kept in the directory cache.
+    ///
+    /// The cache holds at most `DEFAULT_POSITIONAL_READ_CACHE_CAPACITY`
+    /// bytes, unless the directory is opened with
+    /// `MmapDirectory::open_with_cache_capacity`. Past this capacity,
+    /// the least recently opened files are dropped from the cache, and
+    /// read again the next time they are opened. A file remains in memory
+    /// as long as some `ReadOnlySource` points to it.
+    ///
+    /// Mmapping files over network or FUSE filesystems (NFS, sshfs, ...)
+    /// may cause `SIGBUS` or unpredictable latency spikes whenever a page
+    /// is faulted in. This mode avoids `mmap` altogether, at the cost of
+    /// holding the files in memory.
+    PositionalRead,
+}
+
+impl Default for ReadMode {
+    fn default() -> ReadMode {
+        ReadMode::Mmap
+    }
+}
 
 /// Returns None iff the file exists, can be read, but is empty (and hence
 /// cannot be mmapped).
 ///
 fn open_mmap(full_path: &Path) -> result::Result<Option<MmapReadOnly>, OpenReadError> {
-    let file = File::open(full_path).map_err(|e| {
-        if e.kind() == io::ErrorKind::NotFound {
-            OpenReadError::FileDoesNotExist(full_path.to_owned())
-        } else {
-            OpenReadError::IOError(IOError::with_path(full_path.to_owned(), e))
-        }
-    })?;
+    let file = open_file(full_path)?;
 
     let meta_data = file
         .metadata()
@@ -48,11 +88,69 @@ fn open_mmap(full_path: &Path) -> result::Result<Option<MmapReadOnly>, OpenReadE
     }
 }
 
+fn open_file(full_path: &Path) -> result::Result<File, OpenReadError> {
+    File::open(full_path).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            OpenReadError::FileDoesNotExist(full_path.to_owned())
+        } else {
+            OpenReadError::IOError(IOError::with_path(full_path.to_owned(), e))
+        }
+    })
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    file.read_at(buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::windows::fs::FileExt;
+    file.seek_read(buf, offset)
+}
+
+/// Fallback for platforms without positional reads.
+///
+/// The file handle is never shared, so seeking it is fine.
+#[cfg(not(any(unix, windows)))]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    let mut file = file;
+    file.seek(SeekFrom::Start(offset))?;
+    file.read(buf)
+}
+
+/// Reads the entire content of a file using positional reads.
+///
+/// Contrary to `open_mmap`, the data is copied in anonymous memory,
+/// so that no page fault on the file itself can happen afterwards.
+fn read_positional(full_path: &Path) -> result::Result<SharedVecSlice, OpenReadError> {
+    let file = open_file(full_path)?;
+    let io_err = |e| IOError::with_path(full_path.to_owned(), e);
+    let len = file.metadata().map_err(io_err)?.len() as usize;
+    let mut data = vec![0u8; len];
+    let mut offset = 0;
+    while offset < len {
+        let chunk_end = (offset + POSITIONAL_READ_CHUNK_LEN).min(len);
+        let num_bytes = read_at(&file, &mut data[offset..chunk_end], offset as u64)
+            .map_err(io_err)?;
+        if num_bytes == 0 {
+            // The file was truncated while we were reading it.
+            let msg = format!("Unexpected end of file while reading {:?}", full_path);
+            return Err(io_err(make_io_err(msg)).into());
+        }
+        offset += num_bytes;
+    }
+    Ok(SharedVecSlice::from(data))
+}
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct CacheCounters {
     // Number of time the cache prevents to call `mmap`
+    // (or to read the file in `ReadMode::PositionalRead`).
     pub hit: usize,
     // Number of time tantivy had to call `mmap`
+    // (or to read the file in `ReadMode::PositionalRead`)
     // as no entry was in the cache.
     pub miss: usize,
 }
@@ -64,25 +162,36 @@ pub struct CacheInfo {
 }
 
 struct MmapCache {
+    read_mode: ReadMode,
     counters: CacheCounters,
-    cache: HashMap<PathBuf, MmapReadOnly>,
+    cache: HashMap<PathBuf, ReadOnlySource>,
+    cache_state: CacheState,
 }
 
-impl Default for MmapCache {
-    fn default() -> MmapCache {
+impl MmapCache {
+    fn new(read_mode: ReadMode, cache_capacity: Option<usize>) -> MmapCache {
         MmapCache {
+            read_mode,
             counters: CacheCounters::default(),
             cache: HashMap::new(),
+            cache_state: CacheState::new(cache_capacity),
         }
     }
-}
 
-impl MmapCache {
-    /// Removes a `MmapReadOnly` entry from the mmap cache.
+    /// Removes a `ReadOnlySource` entry from the mmap cache.
     fn discard_from_cache(&mut self, full_path: &Path) -> bool {
+        self.cache_state.remove(full_path);
         self.cache.remove(full_path).is_some()
     }
 
+    /// Records an access to a cached source, evicting the
+    /// least recently used sources if the cache is full.
+    fn access(&mut self, full_path: &Path, num_bytes: usize) {
+        for evicted_path in self.cache_state.access(full_path, num_bytes) {
+            self.cache.remove(&evicted_path);
+        }
+    }
+
     fn get_info(&mut self) -> CacheInfo {
         let paths: Vec<PathBuf> = self.cache.keys().cloned().collect();
         CacheInfo {
@@ -91,35 +200,93 @@ impl MmapCache {
         }
     }
 
-    fn get_mmap(&mut self, full_path: &Path) -> Result<Option<MmapReadOnly>, OpenReadError> {
-        Ok(match self.cache.entry(full_path.to_owned()) {
+    fn get_source(&mut self, full_path: &Path) -> Result<Option<ReadOnlySource>, OpenReadError> {
+        let read_mode = self.read_mode;
+        let source_opt = match self.cache.entry(full_path.to_owned()) {
             HashMapEntry::Occupied(occupied_entry) => {
-                let mmap = occupied_entry.get();
+                let source = occupied_entry.get();
                 self.counters.hit += 1;
-                Some(mmap.clone())
+                Some(source.clone())
             }
             HashMapEntry::Vacant(vacant_entry) => {
                 self.counters.miss += 1;
-                if let Some(mmap) = open_mmap(full_path)? {
-                    vacant_entry.insert(mmap.clone());
-                    Some(mmap)
+                let source_opt = match read_mode {
+                    ReadMode::Mmap => open_mmap(full_path)?.map(ReadOnlySource::Mmap),
+                    ReadMode::PositionalRead => {
+                        Some(ReadOnlySource::Anonymous(read_positional(full_path)?))
+                    }
+                };
+                if let Some(source) = source_opt {
+                    vacant_entry.insert(source.clone());
+                    Some(source)
                 } else {
                     None
                 }
             }
+        };
+        if let Some(ref source) = source_opt {
+            self.access(full_path, source.len());
+        }
+        Ok(source_opt)
+    }
+}
+
+/// Watches the root directory with the file system notifications
+/// (e.g. inotify on Linux), and calls the registered callbacks
+/// when the meta file is changed.
+struct WatcherWrapper {
+    _watcher: Mutex<notify::RecommendedWatcher>,
+    watcher_router: Arc<WatchCallbackList>,
+}
+
+impl WatcherWrapper {
+    fn new(path: &Path) -> TantivyResult<WatcherWrapper> {
+        let (tx, watcher_recv) = channel::<RawEvent>();
+        let mut watcher = notify::raw_watcher(tx)
+            .map_err(|err| make_io_err(format!("Failed to create the watcher: {:?}", err)))?;
+        watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .map_err(|err| make_io_err(format!("Failed to watch {:?}: {:?}", path, err)))?;
+        let watcher_router: Arc<WatchCallbackList> = Arc::default();
+        let watcher_router_clone = Arc::clone(&watcher_router);
+        thread::Builder::new()
+            .name("thrd-tantivy-meta-watch".to_string())
+            .spawn(move || {
+                // the channel is closed, ending the loop,
+                // once the watcher is dropped.
+                for event in watcher_recv {
+                    let file_name = event.path.as_ref().and_then(|path| path.file_name());
+                    if file_name == Some(META_FILEPATH.as_os_str()) {
+                        watcher_router_clone.broadcast();
+                    }
+                }
+            })?;
+        Ok(WatcherWrapper {
+            _watcher: Mutex::new(watcher),
+            watcher_router,
         })
     }
+
+    fn watch(&self, watch_callback: WatchCallback) -> WatchHandle {
+        self.watcher_router.subscribe(watch_callback)
+    }
 }
 
 /// Directory storing data in files, read via mmap.
 ///
 /// The Mmap object are cached to limit the
 /// system calls.
+///
+/// Alternatively, the directory can be opened with
+/// `ReadMode::PositionalRead` to avoid mmap altogether.
+/// See [`MmapDirectory::open_with_read_mode`](#method.open_with_read_mode).
 #[derive(Clone)]
 pub struct MmapDirectory {
     root_path: PathBuf,
     mmap_cache: Arc<RwLock<MmapCache>>,
     _temp_directory: Arc<Option<TempDir>>,
+    // the watcher is only created when a callback is registered.
+    watcher: Arc<Mutex<Option<WatcherWrapper>>>,
 }
 
 impl fmt::Debug for MmapDirectory {
@@ -138,8 +305,9 @@ impl MmapDirectory {
         let tempdir_path = PathBuf::from(tempdir.path());
         let directory = MmapDirectory {
             root_path: tempdir_path,
-            mmap_cache: Arc::new(RwLock::new(MmapCache::default())),
+            mmap_cache: Arc::new(RwLock::new(MmapCache::new(ReadMode::Mmap, None))),
             _temp_directory: Arc::new(Some(tempdir)),
+            watcher: Arc::default(),
         };
         Ok(directory)
     }
@@ -149,6 +317,50 @@ impl MmapDirectory {
     /// Returns an error if the `directory_path` does not
     /// exist or if it is not a directory.
     pub fn open<P: AsRef<Path>>(directory_path: P) -> Result<MmapDirectory, OpenDirectoryError> {
+        MmapDirectory::open_with_read_mode(directory_path, ReadMode::Mmap)
+    }
+
+    /// Opens a MmapDirectory in a directory, with the given `ReadMode`.
+    ///
+    /// The cache of the directory is unbounded in `ReadMode::Mmap`,
+    /// and holds at most `DEFAULT_POSITIONAL_READ_CACHE_CAPACITY` bytes
+    /// in `ReadMode::PositionalRead`.
+    ///
+    /// Returns an error if the `directory_path` does not
+    /// exist or if it is not a directory.
+    pub fn open_with_read_mode<P: AsRef<Path>>(
+        directory_path: P,
+        read_mode: ReadMode,
+    ) -> Result<MmapDirectory, OpenDirectoryError> {
+        let cache_capacity = match read_mode {
+            ReadMode::Mmap => None,
+            ReadMode::PositionalRead => Some(DEFAULT_POSITIONAL_READ_CACHE_CAPACITY),
+        };
+        MmapDirectory::open_with_cache(directory_path, read_mode, cache_capacity)
+    }
+
+    /// Opens a MmapDirectory in a directory, with the given `ReadMode`,
+    /// whose cache holds at most `cache_capacity` bytes.
+    ///
+    /// Past this capacity, the least recently opened files are
+    /// dropped from the cache. A file that is larger than the capacity
+    /// is still cached, until the next file is opened.
+    ///
+    /// Returns an error if the `directory_path` does not
+    /// exist or if it is not a directory.
+    pub fn open_with_cache_capacity<P: AsRef<Path>>(
+        directory_path: P,
+        read_mode: ReadMode,
+        cache_capacity: usize,
+    ) -> Result<MmapDirectory, OpenDirectoryError> {
+        MmapDirectory::open_with_cache(directory_path, read_mode, Some(cache_capacity))
+    }
+
+    fn open_with_cache<P: AsRef<Path>>(
+        directory_path: P,
+        read_mode: ReadMode,
+        cache_capacity: Option<usize>,
+    ) -> Result<MmapDirectory, OpenDirectoryError> {
         let directory_path: &Path = directory_path.as_ref();
         if !directory_path.exists() {
             Err(OpenDirectoryError::DoesNotExist(PathBuf::from(
@@ -161,8 +373,9 @@ impl MmapDirectory {
         } else {
             Ok(MmapDirectory {
                 root_path: PathBuf::from(directory_path),
-                mmap_cache: Arc::new(RwLock::new(MmapCache::default())),
+                mmap_cache: Arc::new(RwLock::new(MmapCache::new(read_mode, cache_capacity))),
                 _temp_directory: Arc::new(None),
+                watcher: Arc::default(),
             })
         }
     }
@@ -240,6 +453,8 @@ impl Seek for SafeFileWriter {
     }
 }
 
+impl SeekableWrite for SafeFileWriter {}
+
 impl Directory for MmapDirectory {
     fn open_read(&self, path: &Path) -> result::Result<ReadOnlySource, OpenReadError> {
         debug!("Open Read {:?}", path);
@@ -255,8 +470,7 @@ impl Directory for MmapDirectory {
         })?;
 
         Ok(mmap_cache
-            .get_mmap(&full_path)?
-            .map(ReadOnlySource::Mmap)
+            .get_source(&full_path)?
             .unwrap_or_else(|| ReadOnlySource::Anonymous(SharedVecSlice::empty())))
     }
 
@@ -292,6 +506,13 @@ impl Directory for MmapDirectory {
 
     /// Any entry associated to the path in the mmap will be
     /// removed before the file is deleted.
+    ///
+    /// On Windows, a file cannot be deleted as long as it is mmapped.
+    /// If some `ReadOnlySource` still points to the file, the deletion
+    /// fails with an `IOError`, and the `ManagedDirectory` will retry
+    /// on its next garbage collection.
+    /// Files read with `ReadMode::PositionalRead` are not kept open,
+    /// and can be deleted right away.
     fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
         debug!("Deleting file {:?}", path);
         let full_path = self.resolve_path(path);
@@ -354,6 +575,15 @@ impl Directory for MmapDirectory {
         meta_file.write(|f| f.write_all(data))?;
         Ok(())
     }
+
+    fn watch(&self, watch_callback: WatchCallback) -> TantivyResult<WatchHandle> {
+        let mut watcher_lock = self.watcher.lock().expect("Watcher lock poisoned");
+        if watcher_lock.is_none() {
+            *watcher_lock = Some(WatcherWrapper::new(&self.root_path)?);
+        }
+        let watcher = watcher_lock.as_ref().expect("The watcher was just created");
+        Ok(watcher.watch(watch_callback))
+    }
 }
 
 #[cfg(test)]
@@ -425,4 +655,60 @@ mod tests {
         assert_eq!(mmap_directory.get_cache_info().mmapped.len(), 0);
     }
 
+    #[test]
+    fn test_positional_read_cache() {
+        let tempdir = TempDir::new("index").unwrap();
+        let mut directory =
+            MmapDirectory::open_with_read_mode(tempdir.path(), ReadMode::PositionalRead).unwrap();
+        let path = PathBuf::from("test");
+        {
+            let mut w = directory.open_write(&path).unwrap();
+            w.write_all(&[1u8, 2u8, 3u8]).unwrap();
+            w.flush().unwrap();
+        }
+        match directory.open_read(&path).unwrap() {
+            ReadOnlySource::Anonymous(ref data) => assert_eq!(data.as_slice(), &[1u8, 2u8, 3u8]),
+            _ => panic!("Positional read should not mmap files"),
+        }
+        assert_eq!(&*directory.open_read(&path).unwrap(), &[1u8, 2u8, 3u8]);
+        assert_eq!(directory.get_cache_info().counters.hit, 1);
+        assert_eq!(directory.get_cache_info().counters.miss, 1);
+        directory.delete(&path).unwrap();
+        assert_eq!(directory.get_cache_info().mmapped.len(), 0);
+    }
+
+    #[test]
+    fn test_positional_read_cache_capacity() {
+        let tempdir = TempDir::new("index").unwrap();
+        let mut directory = MmapDirectory::open_with_cache_capacity(
+            tempdir.path(),
+            ReadMode::PositionalRead,
+            2_500,
+        )
+        .unwrap();
+        let paths: Vec<PathBuf> = (0..3).map(|i| PathBuf::from(format!("f{}", i))).collect();
+        for (i, path) in paths.iter().enumerate() {
+            let mut w = directory.open_write(path).unwrap();
+            w.write_all(&vec![i as u8; 1_000]).unwrap();
+            w.flush().unwrap();
+        }
+        let f0 = directory.open_read(&paths[0]).unwrap();
+        directory.open_read(&paths[1]).unwrap();
+        directory.open_read(&paths[0]).unwrap();
+        // f1 is the least recently opened file.
+        directory.open_read(&paths[2]).unwrap();
+        let mut cached_paths = directory.get_cache_info().mmapped;
+        cached_paths.sort();
+        assert_eq!(
+            cached_paths,
+            vec![tempdir.path().join("f0"), tempdir.path().join("f2")]
+        );
+        assert_eq!(directory.get_cache_info().counters.miss, 3);
+        // f1 is read again from the file.
+        assert_eq!(&*directory.open_read(&paths[1]).unwrap(), &[1u8; 1_000][..]);
+        assert_eq!(directory.get_cache_info().counters.miss, 4);
+        // the evicted sources remain valid.
+        assert_eq!(&*f0, &[0u8; 1_000][..]);
+    }
+
 }
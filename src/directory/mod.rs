@@ -7,29 +7,62 @@ WORM directory abstraction.
 #[cfg(feature = "mmap")]
 mod mmap_directory;
 
+mod cache_state;
 mod directory;
+#[cfg(feature = "encryption")]
+mod encrypted_directory;
+mod footer;
 mod managed_directory;
+mod object_store_directory;
 mod ram_directory;
 mod read_only_source;
 mod shared_vec_slice;
+mod watch_event_router;
 
 /// Errors specific to the directory module.
 pub mod error;
 
-use std::io::{BufWriter, Seek, Write};
+use std::io::{self, BufWriter, Seek, Write};
 
 pub use self::directory::{Directory, DirectoryClone};
+#[cfg(feature = "encryption")]
+pub use self::encrypted_directory::EncryptedDirectory;
+pub use self::object_store_directory::{ObjectStore, ObjectStoreDirectory};
 pub use self::ram_directory::RAMDirectory;
 pub use self::read_only_source::ReadOnlySource;
+pub use self::watch_event_router::{WatchCallback, WatchHandle};
 
 #[cfg(feature = "mmap")]
-pub use self::mmap_directory::MmapDirectory;
+pub use self::mmap_directory::{MmapDirectory, ReadMode, DEFAULT_POSITIONAL_READ_CACHE_CAPACITY};
 
+pub(crate) use self::footer::{Footer, FooterWrite};
 pub(crate) use self::managed_directory::ManagedDirectory;
+pub(crate) use self::watch_event_router::{watch_by_polling, WatchCallbackList};
+
+/// Seek + Write, with an explicit end of file.
+pub trait SeekableWrite: Seek + Write {
+    /// Flushes the written data and finalizes the file.
+    ///
+    /// Writers appending trailing data to the file (e.g. a footer)
+    /// write it here, so that errors are reported to the caller.
+    /// Nothing should be written after calling `terminate`.
+    fn terminate(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl SeekableWrite for Box<SeekableWrite> {
+    fn terminate(&mut self) -> io::Result<()> {
+        (**self).terminate()
+    }
+}
 
-/// Synonym of Seek + Write
-pub trait SeekableWrite: Seek + Write {}
-impl<T: Seek + Write> SeekableWrite for T {}
+impl<W: SeekableWrite> SeekableWrite for BufWriter<W> {
+    fn terminate(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.get_mut().terminate()
+    }
+}
 
 /// Write object for Directory.
 ///
@@ -43,6 +76,10 @@ mod tests {
     use super::*;
     use std::io::{Seek, SeekFrom, Write};
     use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
 
     lazy_static! {
         static ref TEST_PATH: &'static Path = Path::new("some_path_for_test");
@@ -61,6 +98,15 @@ mod tests {
         test_directory(&mut mmap_directory);
     }
 
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_positional_read_directory() {
+        let tempdir = ::tempdir::TempDir::new("index").unwrap();
+        let mut directory =
+            MmapDirectory::open_with_read_mode(tempdir.path(), ReadMode::PositionalRead).unwrap();
+        test_directory(&mut directory);
+    }
+
     #[test]
     #[should_panic]
     fn ram_directory_panics_if_flush_forgotten() {
@@ -154,12 +200,40 @@ mod tests {
         assert!(directory.delete(*TEST_PATH).is_err());
     }
 
+    fn test_watch(directory: &mut Directory) {
+        let meta_path = Path::new("meta.json");
+        let counter: Arc<AtomicUsize> = Arc::default();
+        let counter_clone = Arc::clone(&counter);
+        let watch_handle = directory
+            .watch(Box::new(move || {
+                counter_clone.fetch_add(1, Ordering::SeqCst);
+            }))
+            .unwrap();
+        directory.atomic_write(meta_path, b"generation 1").unwrap();
+        for _ in 0..100 {
+            if counter.load(Ordering::SeqCst) > 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert!(counter.load(Ordering::SeqCst) > 0);
+        thread::sleep(Duration::from_millis(100));
+        let count = counter.load(Ordering::SeqCst);
+        // the callback is unregistered once its handle is dropped.
+        drop(watch_handle);
+        directory.atomic_write(meta_path, b"generation 2").unwrap();
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(counter.load(Ordering::SeqCst), count);
+        assert!(directory.delete(meta_path).is_ok());
+    }
+
     fn test_directory(directory: &mut Directory) {
         test_simple(directory);
         test_seek(directory);
         test_rewrite_forbidden(directory);
         test_write_create_the_file(directory);
         test_directory_delete(directory);
+        test_watch(directory);
     }
 
 }
@@ -0,0 +1,416 @@
+use core::META_FILEPATH;
+use directory::cache_state::CacheState;
+use directory::error::{DeleteError, IOError, OpenReadError, OpenWriteError};
+use directory::{watch_by_polling, Directory, ReadOnlySource, WatchCallback, WatchHandle};
+use directory::{SeekableWrite, WritePtr};
+use std::fmt;
+use std::io::{self, BufWriter, Cursor, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::result;
+use std::sync::{Arc, Mutex};
+use Result as TantivyResult;
+
+/// Client of an object store, such as Amazon S3 or Google
+/// Cloud Storage, holding the files of an `ObjectStoreDirectory`.
+///
+/// Each file is stored as one object, whose key is derived
+/// from the path of the file, e.g. by prefixing it with the
+/// location of the index in the bucket.
+pub trait ObjectStore: fmt::Debug + Send + Sync + 'static {
+    /// Returns the content of an object, or `None`
+    /// if it does not exist.
+    fn get(&self, path: &Path) -> io::Result<Option<Vec<u8>>>;
+
+    /// Creates or replaces an object.
+    ///
+    /// Readers should never observe a partially written object,
+    /// which is the case of the `PUT` requests of the usual object stores.
+    fn put(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+
+    /// Deletes an object.
+    ///
+    /// Returns false if the object did not exist.
+    fn delete(&self, path: &Path) -> io::Result<bool>;
+
+    /// Returns true iff the object exists.
+    fn exists(&self, path: &Path) -> io::Result<bool>;
+}
+
+/// Writer associated with the `ObjectStoreDirectory`.
+///
+/// The data is buffered in memory, and the whole
+/// file is uploaded once, when the writer is terminated.
+struct ObjectWriter {
+    path: PathBuf,
+    store: Arc<ObjectStore>,
+    data: Cursor<Vec<u8>>,
+}
+
+impl Seek for ObjectWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.data.seek(pos)
+    }
+}
+
+impl Write for ObjectWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SeekableWrite for ObjectWriter {
+    fn terminate(&mut self) -> io::Result<()> {
+        self.store.put(&self.path, self.data.get_ref())
+    }
+}
+
+/// Directory storing its files in an `ObjectStore`, and
+/// caching the files it reads in a local directory.
+///
+/// The files are downloaded lazily, the first time they are
+/// opened, so that an index can be searched without copying it
+/// entirely first. As the files of the segments never change,
+/// the cached files never need to be invalidated.
+/// The files written with `atomic_write` (e.g. `meta.json`)
+/// are never cached, and always read from the object store.
+///
+/// The cache is typically a `MmapDirectory` on a local disk.
+/// Its size can be bounded with `with_cache_capacity`, in which case
+/// the least recently opened files are evicted from the cache.
+/// Its files are not deleted when the index is served from
+/// another cache, and should be removed by the application.
+///
+/// Object stores do not notify their clients of the changes
+/// of their objects: `watch` polls `meta.json` every 500ms.
+///
+/// # Warning
+///
+/// The lock files are objects as well, and object stores do not
+/// make it possible to create them atomically. Only one process
+/// should open an `IndexWriter` on the index.
+pub struct ObjectStoreDirectory {
+    store: Arc<ObjectStore>,
+    cache: Box<Directory>,
+    cache_state: Arc<Mutex<CacheState>>,
+}
+
+impl ObjectStoreDirectory {
+    /// Creates a directory over the objects of `store`,
+    /// cached in `cache`, without any bound on the size of the cache.
+    pub fn new<S: ObjectStore, D: Directory>(store: S, cache: D) -> ObjectStoreDirectory {
+        ObjectStoreDirectory::create(store, cache, None)
+    }
+
+    /// Creates a directory over the objects of `store`, cached
+    /// in `cache`, which holds at most `cache_capacity` bytes.
+    ///
+    /// A file that is larger than the capacity is still cached,
+    /// until the next file is cached.
+    pub fn with_cache_capacity<S: ObjectStore, D: Directory>(
+        store: S,
+        cache: D,
+        cache_capacity: usize,
+    ) -> ObjectStoreDirectory {
+        ObjectStoreDirectory::create(store, cache, Some(cache_capacity))
+    }
+
+    fn create<S: ObjectStore, D: Directory>(
+        store: S,
+        cache: D,
+        cache_capacity: Option<usize>,
+    ) -> ObjectStoreDirectory {
+        ObjectStoreDirectory {
+            store: Arc::new(store),
+            cache: Box::new(cache),
+            cache_state: Arc::new(Mutex::new(CacheState::new(cache_capacity))),
+        }
+    }
+
+    /// Records an access to a cached file, evicting the
+    /// least recently used files if the cache is full.
+    fn access_cached_file(&self, path: &Path, num_bytes: usize) {
+        let evicted_paths = self
+            .cache_state
+            .lock()
+            .expect("The cache state lock is poisoned.")
+            .access(path, num_bytes);
+        for evicted_path in evicted_paths {
+            // the evicted file is downloaded again if it is opened later on.
+            if let Err(err) = self.remove_cached_copy(&evicted_path) {
+                warn!("{}", err);
+            }
+        }
+    }
+
+    /// Removes the cached copy of a file, if any.
+    fn evict(&self, path: &Path) -> io::Result<()> {
+        self.cache_state
+            .lock()
+            .expect("The cache state lock is poisoned.")
+            .remove(path);
+        self.remove_cached_copy(path)
+    }
+
+    fn remove_cached_copy(&self, path: &Path) -> io::Result<()> {
+        match self.cache.delete(path) {
+            Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => Ok(()),
+            Err(DeleteError::IOError(err)) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to remove the cached copy of {:?}: {}", path, err),
+            )),
+        }
+    }
+}
+
+impl Clone for ObjectStoreDirectory {
+    fn clone(&self) -> ObjectStoreDirectory {
+        ObjectStoreDirectory {
+            store: Arc::clone(&self.store),
+            cache: self.cache.box_clone(),
+            cache_state: Arc::clone(&self.cache_state),
+        }
+    }
+}
+
+impl fmt::Debug for ObjectStoreDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ObjectStoreDirectory({:?}, {:?})",
+            self.store, self.cache
+        )
+    }
+}
+
+impl Directory for ObjectStoreDirectory {
+    fn open_read(&self, path: &Path) -> result::Result<ReadOnlySource, OpenReadError> {
+        if self.cache.exists(path) {
+            let source = self.cache.open_read(path)?;
+            self.access_cached_file(path, source.len());
+            return Ok(source);
+        }
+        let data = self
+            .store
+            .get(path)
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_owned()))?;
+        // the cache is shared with the clones of the directory, which
+        // may download the same file concurrently: `atomic_write`
+        // ensures that the cached copy is never observed partially written.
+        let mut cache = self.cache.box_clone();
+        cache
+            .atomic_write(path, &data[..])
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        let source = self.cache.open_read(path)?;
+        self.access_cached_file(path, source.len());
+        Ok(source)
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        let existed = self
+            .store
+            .delete(path)
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        self.evict(path)
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        if existed {
+            Ok(())
+        } else {
+            Err(DeleteError::FileDoesNotExist(path.to_owned()))
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        match self.store.exists(path) {
+            Ok(exists) => exists,
+            Err(err) => {
+                error!("Failed to check whether {:?} exists: {:?}", path, err);
+                false
+            }
+        }
+    }
+
+    fn open_write(&mut self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        let exists = self
+            .store
+            .exists(path)
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        if exists {
+            return Err(OpenWriteError::FileAlreadyExists(path.to_owned()));
+        }
+        // force the creation of the file, as the other directories do.
+        self.store
+            .put(path, &[])
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?;
+        let object_writer = ObjectWriter {
+            path: path.to_owned(),
+            store: Arc::clone(&self.store),
+            data: Cursor::new(Vec::new()),
+        };
+        Ok(BufWriter::new(Box::new(object_writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        self.store
+            .get(path)
+            .map_err(|err| IOError::with_path(path.to_owned(), err))?
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_owned()))
+    }
+
+    fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.store.put(path, data)?;
+        self.evict(path)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> TantivyResult<WatchHandle> {
+        let store = Arc::clone(&self.store);
+        let read_meta = move || match store.get(&META_FILEPATH) {
+            Ok(meta_opt) => meta_opt,
+            Err(err) => {
+                error!("Failed to read the meta file: {:?}", err);
+                None
+            }
+        };
+        watch_by_polling(read_meta, watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{ObjectStore, ObjectStoreDirectory};
+    use collector::Count;
+    use directory::{Directory, RAMDirectory, SeekableWrite};
+    use query::AllQuery;
+    use schema::{Schema, TEXT};
+    use std::collections::HashMap;
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, RwLock};
+    use Index;
+
+    #[derive(Clone, Debug, Default)]
+    struct InMemoryObjectStore {
+        objects: Arc<RwLock<HashMap<PathBuf, Vec<u8>>>>,
+        downloads: Arc<RwLock<Vec<PathBuf>>>,
+    }
+
+    impl ObjectStore for InMemoryObjectStore {
+        fn get(&self, path: &Path) -> io::Result<Option<Vec<u8>>> {
+            let data_opt = self.objects.read().unwrap().get(path).cloned();
+            if data_opt.is_some() {
+                self.downloads.write().unwrap().push(path.to_owned());
+            }
+            Ok(data_opt)
+        }
+
+        fn put(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            self.objects
+                .write()
+                .unwrap()
+                .insert(path.to_owned(), data.to_owned());
+            Ok(())
+        }
+
+        fn delete(&self, path: &Path) -> io::Result<bool> {
+            Ok(self.objects.write().unwrap().remove(path).is_some())
+        }
+
+        fn exists(&self, path: &Path) -> io::Result<bool> {
+            Ok(self.objects.read().unwrap().contains_key(path))
+        }
+    }
+
+    #[test]
+    fn test_object_store_directory() {
+        let store = InMemoryObjectStore::default();
+        let mut directory = ObjectStoreDirectory::new(store.clone(), RAMDirectory::create());
+        let path = Path::new("some_path_for_test");
+        directory.atomic_write(path, b"abc").unwrap();
+        assert!(directory.exists(path));
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), b"abc");
+        // the atomic writes evict the cached copy of the file.
+        directory.atomic_write(path, b"def").unwrap();
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), b"def");
+        assert!(directory.open_write(path).is_err());
+        directory.delete(path).unwrap();
+        assert!(!directory.exists(path));
+        assert!(directory.open_read(path).is_err());
+        assert!(directory.delete(path).is_err());
+    }
+
+    #[test]
+    fn test_object_store_directory_upload_on_terminate() {
+        let store = InMemoryObjectStore::default();
+        let mut directory = ObjectStoreDirectory::new(store.clone(), RAMDirectory::create());
+        let path = Path::new("some_path_for_test");
+        let mut write = directory.open_write(path).unwrap();
+        write.write_all(b"abc").unwrap();
+        write.flush().unwrap();
+        assert_eq!(store.get(path).unwrap(), Some(Vec::new()));
+        write.terminate().unwrap();
+        assert_eq!(store.get(path).unwrap(), Some(b"abc".to_vec()));
+    }
+
+    #[test]
+    fn test_object_store_directory_cache_eviction() {
+        let store = InMemoryObjectStore::default();
+        let cache = RAMDirectory::create();
+        let mut directory =
+            ObjectStoreDirectory::with_cache_capacity(store.clone(), cache.clone(), 10);
+        let (path1, path2, path3) = (Path::new("path1"), Path::new("path2"), Path::new("path3"));
+        for path in &[path1, path2, path3] {
+            let mut write = directory.open_write(path).unwrap();
+            write.write_all(b"abcd").unwrap();
+            write.terminate().unwrap();
+        }
+        assert_eq!(directory.open_read(path1).unwrap().as_slice(), b"abcd");
+        assert_eq!(directory.open_read(path2).unwrap().as_slice(), b"abcd");
+        assert_eq!(directory.open_read(path1).unwrap().as_slice(), b"abcd");
+        // the least recently used file is evicted.
+        assert_eq!(directory.open_read(path3).unwrap().as_slice(), b"abcd");
+        assert!(cache.exists(path1));
+        assert!(!cache.exists(path2));
+        assert!(cache.exists(path3));
+        // and downloaded again when it is opened.
+        store.downloads.write().unwrap().clear();
+        assert_eq!(directory.open_read(path2).unwrap().as_slice(), b"abcd");
+        assert_eq!(
+            *store.downloads.read().unwrap(),
+            vec![PathBuf::from("path2")]
+        );
+    }
+
+    #[test]
+    fn test_object_store_index() {
+        let store = InMemoryObjectStore::default();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        {
+            let directory = ObjectStoreDirectory::new(store.clone(), RAMDirectory::create());
+            let index = Index::create(directory, schema_builder.build()).unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        // serving the index from a new, empty cache.
+        let directory = ObjectStoreDirectory::new(store.clone(), RAMDirectory::create());
+        let index = Index::open(directory).unwrap();
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().search(&AllQuery, &Count).unwrap(), 2);
+        // the segment files are read from the cache from now on,
+        // and only `meta.json` is downloaded again.
+        store.downloads.write().unwrap().clear();
+        index.load_searchers().unwrap();
+        assert_eq!(
+            *store.downloads.read().unwrap(),
+            vec![PathBuf::from("meta.json")]
+        );
+    }
+}
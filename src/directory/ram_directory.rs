@@ -1,14 +1,16 @@
 use super::shared_vec_slice::SharedVecSlice;
 use common::make_io_err;
+use core::META_FILEPATH;
 use directory::error::{DeleteError, IOError, OpenReadError, OpenWriteError};
-use directory::WritePtr;
-use directory::{Directory, ReadOnlySource};
+use directory::{Directory, ReadOnlySource, WatchCallback, WatchCallbackList, WatchHandle};
+use directory::{SeekableWrite, WritePtr};
 use std::collections::HashMap;
 use std::fmt;
 use std::io::{self, BufWriter, Cursor, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::result;
 use std::sync::{Arc, RwLock};
+use Result as TantivyResult;
 
 /// Writer associated with the `RAMDirectory`
 ///
@@ -70,6 +72,8 @@ impl Write for VecWriter {
     }
 }
 
+impl SeekableWrite for VecWriter {}
+
 #[derive(Clone)]
 struct InnerDirectory(Arc<RwLock<HashMap<PathBuf, Arc<Vec<u8>>>>>);
 
@@ -150,6 +154,7 @@ impl fmt::Debug for RAMDirectory {
 #[derive(Clone)]
 pub struct RAMDirectory {
     fs: InnerDirectory,
+    watch_router: Arc<WatchCallbackList>,
 }
 
 impl RAMDirectory {
@@ -157,6 +162,7 @@ impl RAMDirectory {
     pub fn create() -> RAMDirectory {
         RAMDirectory {
             fs: InnerDirectory::new(),
+            watch_router: Arc::default(),
         }
     }
 }
@@ -205,6 +211,13 @@ impl Directory for RAMDirectory {
         self.fs.write(path_buf, &Vec::new())?;
         vec_writer.write_all(data)?;
         vec_writer.flush()?;
+        if path == *META_FILEPATH {
+            self.watch_router.broadcast();
+        }
         Ok(())
     }
+
+    fn watch(&self, watch_callback: WatchCallback) -> TantivyResult<WatchHandle> {
+        Ok(self.watch_router.subscribe(watch_callback))
+    }
 }
@@ -4,6 +4,10 @@ use common::HasLen;
 use fst::raw::MmapReadOnly;
 use stable_deref_trait::{CloneStableDeref, StableDeref};
 use std::ops::Deref;
+use std::ptr;
+
+/// Size of the pages touched by `ReadOnlySource::warm`.
+const PAGE_SIZE: usize = 4_096;
 
 /// Read object that represents files in tantivy.
 ///
@@ -16,6 +20,9 @@ pub enum ReadOnlySource {
     #[cfg(feature = "mmap")]
     Mmap(MmapReadOnly),
     /// Wrapping a `Vec<u8>`
+    ///
+    /// This is also the variant used by `MmapDirectory` when
+    /// opened with `ReadMode::PositionalRead`.
     Anonymous(SharedVecSlice),
 }
 
@@ -45,6 +52,18 @@ impl ReadOnlySource {
         }
     }
 
+    /// Reads one byte of every page of the data, so that
+    /// a memory mapped source is loaded in the page cache.
+    pub fn warm(&self) {
+        let data = self.as_slice();
+        for offset in (0..data.len()).step_by(PAGE_SIZE) {
+            // volatile reads are not optimized away.
+            unsafe {
+                ptr::read_volatile(&data[offset]);
+            }
+        }
+    }
+
     /// Splits into 2 `ReadOnlySource`, at the offset given
     /// as an argument.
     pub fn split(self, addr: usize) -> (ReadOnlySource, ReadOnlySource) {
@@ -0,0 +1,166 @@
+use std::sync::{Arc, RwLock, Weak};
+use std::thread;
+use std::time::Duration;
+use Result;
+
+/// Interval at which the directories that cannot be notified
+/// of the changes of their files poll the meta file.
+const POLL_INTERVAL_MILLIS: u64 = 500;
+
+/// Callback called by a `Directory` when the meta file of the index changes.
+pub type WatchCallback = Box<Fn() + Sync + Send>;
+
+/// Handle of a callback registered with `Directory::watch`.
+///
+/// The callback is unregistered once all of the clones
+/// of its handle are dropped.
+#[derive(Clone)]
+pub struct WatchHandle(Arc<WatchCallback>);
+
+/// List of the callbacks registered on a directory, called
+/// when the meta file changes.
+///
+/// The list only holds weak references to the callbacks, which
+/// are owned by their `WatchHandle`.
+#[derive(Default)]
+pub(crate) struct WatchCallbackList {
+    router: RwLock<Vec<Weak<WatchCallback>>>,
+}
+
+impl WatchCallbackList {
+    /// Registers a callback, which stays registered
+    /// as long as the returned handle is alive.
+    pub fn subscribe(&self, watch_callback: WatchCallback) -> WatchHandle {
+        let watch_callback_arc = Arc::new(watch_callback);
+        let watch_callback_weak = Arc::downgrade(&watch_callback_arc);
+        self.router
+            .write()
+            .expect("Watch callback list lock poisoned")
+            .push(watch_callback_weak);
+        WatchHandle(watch_callback_arc)
+    }
+
+    /// Returns the callbacks still registered, and
+    /// removes the other ones from the list.
+    fn retain(&self) -> Vec<Arc<WatchCallback>> {
+        let mut callbacks = Vec::new();
+        let mut router_wlock = self
+            .router
+            .write()
+            .expect("Watch callback list lock poisoned");
+        router_wlock.retain(|watch_callback_weak| {
+            if let Some(watch_callback) = watch_callback_weak.upgrade() {
+                callbacks.push(watch_callback);
+                true
+            } else {
+                false
+            }
+        });
+        callbacks
+    }
+
+    /// Calls all of the registered callbacks.
+    ///
+    /// The callbacks are called in a new thread, so that
+    /// the caller (e.g. the writer of the meta file) is not
+    /// blocked, and can hold locks the callbacks may need.
+    pub fn broadcast(&self) {
+        let callbacks = self.retain();
+        if callbacks.is_empty() {
+            return;
+        }
+        let spawn_res = thread::Builder::new()
+            .name("thrd-tantivy-watch".to_string())
+            .spawn(move || {
+                for callback in callbacks {
+                    callback();
+                }
+            });
+        if let Err(e) = spawn_res {
+            error!(
+                "Failed to spawn the thread calling the watch callbacks: {:?}",
+                e
+            );
+        }
+    }
+}
+
+/// Calls `watch_callback` every time the content of the meta file,
+/// as returned by `read_meta`, changes.
+///
+/// The meta file is polled from a thread, stopped once
+/// the returned handle is dropped.
+pub(crate) fn watch_by_polling<F>(
+    read_meta: F,
+    watch_callback: WatchCallback,
+) -> Result<WatchHandle>
+where
+    F: Fn() -> Option<Vec<u8>> + Send + 'static,
+{
+    let watch_handle = WatchHandle(Arc::new(watch_callback));
+    let watch_callback_weak = Arc::downgrade(&watch_handle.0);
+    let mut last_meta = read_meta();
+    thread::Builder::new()
+        .name("thrd-tantivy-meta-poll".to_string())
+        .spawn(move || loop {
+            thread::sleep(Duration::from_millis(POLL_INTERVAL_MILLIS));
+            let watch_callback = match watch_callback_weak.upgrade() {
+                Some(watch_callback) => watch_callback,
+                None => return,
+            };
+            let meta = read_meta();
+            if meta != last_meta {
+                last_meta = meta;
+                watch_callback();
+            }
+        })?;
+    Ok(watch_handle)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{WatchCallbackList, WatchHandle};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn subscribe_counter(
+        watch_callback_list: &WatchCallbackList,
+        counter: &Arc<AtomicUsize>,
+    ) -> WatchHandle {
+        let counter = Arc::clone(counter);
+        watch_callback_list.subscribe(Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }))
+    }
+
+    fn wait_for_count(counter: &AtomicUsize, expected: usize) {
+        for _ in 0..100 {
+            if counter.load(Ordering::SeqCst) == expected {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), expected);
+        // lets the broadcasting thread release the callbacks.
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_watch_callback_list() {
+        let watch_callback_list = WatchCallbackList::default();
+        let counter = Arc::new(AtomicUsize::default());
+        let handle_a = subscribe_counter(&watch_callback_list, &counter);
+        let handle_b = subscribe_counter(&watch_callback_list, &counter);
+        watch_callback_list.broadcast();
+        wait_for_count(&counter, 2);
+        drop(handle_a);
+        watch_callback_list.broadcast();
+        wait_for_count(&counter, 3);
+        drop(handle_b);
+        watch_callback_list.broadcast();
+        wait_for_count(&counter, 3);
+    }
+}
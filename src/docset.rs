@@ -2,6 +2,7 @@ use common::BitSet;
 use std::borrow::Borrow;
 use std::borrow::BorrowMut;
 use std::cmp::Ordering;
+use std::mem;
 use DocId;
 
 /// Expresses the outcome of a call to `DocSet`'s `.skip_next(...)`.
@@ -104,6 +105,15 @@ pub trait DocSet {
         }
         count
     }
+
+    /// Returns an estimate of the number of bytes held in memory by
+    /// the `DocSet` on top of its own size, like its decoded blocks
+    /// of postings or its bitsets.
+    ///
+    /// The estimate is used to enforce the memory limit of queries.
+    fn memory_usage(&self) -> usize {
+        0
+    }
 }
 
 impl<TDocSet: DocSet + ?Sized> DocSet for Box<TDocSet> {
@@ -136,4 +146,9 @@ impl<TDocSet: DocSet + ?Sized> DocSet for Box<TDocSet> {
         let unboxed: &mut TDocSet = self.borrow_mut();
         unboxed.append_to_bitset(bitset);
     }
+
+    fn memory_usage(&self) -> usize {
+        let unboxed: &TDocSet = self.borrow();
+        mem::size_of_val(unboxed) + unboxed.memory_usage()
+    }
 }
@@ -35,6 +35,10 @@ pub enum TantivyError {
     /// Data corruption.
     #[fail(display = "File contains corrupted data: '{:?}'", _0)]
     CorruptedFile(PathBuf),
+    /// The index was written in a more recent version of the index
+    /// format than the one supported by this version of tantivy.
+    #[fail(display = "Incompatible index: '{}'", _0)]
+    IncompatibleIndex(String),
     /// A thread holding the locked panicked and poisoned the lock.
     #[fail(display = "A thread holding the locked panicked and poisoned the lock")]
     Poisoned,
@@ -50,6 +54,48 @@ pub enum TantivyError {
     /// Tried to access a fastfield reader for a field not configured accordingly.
     #[fail(display = "Fast field not available: '{:?}'", _0)]
     FastFieldError(#[cause] FastFieldNotAvailableError),
+    /// The operation would make the index exceed the disk space limit
+    /// of the `IndexWriter`.
+    #[fail(
+        display = "Disk space limit exceeded: {} bytes used, {} bytes required, limit is {} bytes",
+        used, required, limit
+    )]
+    DiskSpaceLimitExceeded {
+        /// Number of bytes currently used by the index.
+        used: u64,
+        /// Estimated number of bytes required by the operation.
+        required: u64,
+        /// Disk space limit, in bytes.
+        limit: u64,
+    },
+    /// The memory held on behalf of a query exceeds its memory limit.
+    ///
+    /// See [`Searcher::search_with_memory_limit`](../struct.Searcher.html#method.search_with_memory_limit).
+    #[fail(
+        display = "Query memory limit exceeded: {} bytes required, limit is {} bytes",
+        required, limit
+    )]
+    MemoryLimitExceeded {
+        /// Estimated number of bytes required by the query.
+        required: usize,
+        /// Memory limit of the query, in bytes.
+        limit: usize,
+    },
+    /// A multi-term query expands to more terms than its expansion limit.
+    ///
+    /// See [`ExpansionLimit`](../query/enum.ExpansionLimit.html).
+    #[fail(display = "The query expands to more than {} terms", max_expansions)]
+    ExpansionLimitExceeded {
+        /// Maximum number of terms the query may expand to.
+        max_expansions: usize,
+    },
+    /// An indexing thread of the `IndexWriter` panicked.
+    ///
+    /// The documents it was indexing are lost, so the `IndexWriter`
+    /// refuses to commit. Calling `.rollback()` reopens a clean `IndexWriter`
+    /// from the last commit.
+    #[fail(display = "The index writer is poisoned: '{}'", _0)]
+    WriterPoisoned(String),
     /// System error. (e.g.: We failed spawning a new thread)
     #[fail(display = "System error.'{}'", _0)]
     SystemError(String),
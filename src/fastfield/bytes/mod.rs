@@ -6,21 +6,29 @@ pub use self::writer::BytesFastFieldWriter;
 
 #[cfg(test)]
 mod tests {
-    use schema::Schema;
+    use schema::{BytesOptions, Schema, Value};
+    use DocAddress;
     use Index;
 
     #[test]
     fn test_bytes() {
         let mut schema_builder = Schema::builder();
-        let field = schema_builder.add_bytes_field("bytesfield");
+        let field =
+            schema_builder.add_bytes_field("bytesfield", BytesOptions::default().set_fast());
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-        index_writer.add_document(doc!(field=>vec![0u8, 1, 2, 3]));
-        index_writer.add_document(doc!(field=>vec![]));
-        index_writer.add_document(doc!(field=>vec![255u8]));
-        index_writer.add_document(doc!(field=>vec![1u8, 3, 5, 7, 9]));
-        index_writer.add_document(doc!(field=>vec![0u8; 1000]));
+        index_writer
+            .add_document(doc!(field=>vec![0u8, 1, 2, 3]))
+            .unwrap();
+        index_writer.add_document(doc!(field=>vec![])).unwrap();
+        index_writer.add_document(doc!(field=>vec![255u8])).unwrap();
+        index_writer
+            .add_document(doc!(field=>vec![1u8, 3, 5, 7, 9]))
+            .unwrap();
+        index_writer
+            .add_document(doc!(field=>vec![0u8; 1000]))
+            .unwrap();
         assert!(index_writer.commit().is_ok());
 
         index.load_searchers().unwrap();
@@ -35,4 +43,33 @@ mod tests {
         let long = vec![0u8; 1000];
         assert_eq!(bytes_reader.get_val(4), long.as_slice());
     }
+
+    #[test]
+    fn test_stored_bytes() {
+        let mut schema_builder = Schema::builder();
+        let field =
+            schema_builder.add_bytes_field("bytesfield", BytesOptions::default().set_stored());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer
+            .add_document(doc!(field=>vec![0u8, 1, 2, 3]))
+            .unwrap();
+        assert!(index_writer.commit().is_ok());
+
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let doc = searcher.doc(DocAddress(0u32, 0u32)).unwrap();
+        assert_eq!(
+            doc.get_first(field),
+            Some(&Value::Bytes(vec![0u8, 1, 2, 3]))
+        );
+        // the values are encoded in base64 in JSON, as they are parsed.
+        let json = schema.to_json(&doc);
+        assert_eq!(json, r#"{"bytesfield":["AAECAw=="]}"#);
+        assert_eq!(schema.parse_document(&json).unwrap(), doc);
+        // the field is not a fast field.
+        let reader = searcher.segment_reader(0);
+        assert!(reader.bytes_fast_field_reader(field).is_err());
+    }
 }
@@ -1,7 +1,6 @@
 use bit_set::BitSet;
 use common::HasLen;
 use directory::ReadOnlySource;
-use directory::WritePtr;
 use space_usage::ByteCount;
 use std::io;
 use std::io::Write;
@@ -10,7 +9,7 @@ use DocId;
 /// Write a delete `BitSet`
 ///
 /// where `delete_bitset` is the set of deleted `DocId`.
-pub fn write_delete_bitset(delete_bitset: &BitSet, writer: &mut WritePtr) -> io::Result<()> {
+pub fn write_delete_bitset<W: Write>(delete_bitset: &BitSet, writer: &mut W) -> io::Result<()> {
     let max_doc = delete_bitset.capacity();
     let mut byte = 0u8;
     let mut shift = 0u8;
@@ -71,6 +70,14 @@ impl DeleteBitSet {
     }
 }
 
+impl<'a> From<&'a BitSet> for DeleteBitSet {
+    fn from(delete_bitset: &BitSet) -> DeleteBitSet {
+        let mut data = Vec::new();
+        write_delete_bitset(delete_bitset, &mut data).expect("Writing in a Vec should never fail.");
+        DeleteBitSet::open(ReadOnlySource::from(data))
+    }
+}
+
 impl HasLen for DeleteBitSet {
     fn len(&self) -> usize {
         self.len
@@ -0,0 +1,46 @@
+mod reader;
+mod writer;
+
+pub use self::reader::IpAddrFastFieldReader;
+pub use self::writer::IpAddrFastFieldWriter;
+
+#[cfg(test)]
+mod tests {
+    use schema::{IpAddrOptions, Schema};
+    use std::net::{IpAddr, Ipv6Addr};
+    use Document;
+    use Index;
+
+    #[test]
+    fn test_ip_addr_fast_field() {
+        let ip = |ip_str: &str| ip_str.parse::<IpAddr>().unwrap();
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_ip_addr_field("ip", IpAddrOptions::default().set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer
+            .add_document(doc!(field => ip("192.168.0.1")))
+            .unwrap();
+        index_writer.add_document(Document::default()).unwrap();
+        index_writer
+            .add_document(doc!(field => ip("2001:db8::ff00:42:8329")))
+            .unwrap();
+        assert!(index_writer.commit().is_ok());
+
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let reader = searcher.segment_reader(0);
+        let ip_addr_reader = reader.ip_addr_fast_field_reader(field).unwrap();
+        assert_eq!(
+            ip_addr_reader.get(0),
+            "::ffff:192.168.0.1".parse::<Ipv6Addr>().unwrap()
+        );
+        // documents without value get the unspecified address.
+        assert_eq!(ip_addr_reader.get(1), Ipv6Addr::from(0u128));
+        assert_eq!(
+            IpAddr::V6(ip_addr_reader.get(2)),
+            ip("2001:db8::ff00:42:8329")
+        );
+        assert!(reader.fast_field_reader::<u64>(field).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+use fastfield::FastFieldReader;
+use std::net::Ipv6Addr;
+use DocId;
+
+/// Reader for IP address fast fields
+///
+/// The addresses are held as `u128`, split into two `u64` fast fields:
+/// one for their 64 high bits, one for their 64 low bits.
+/// As the high bits of the IPv4 addresses mapped into IPv6 are all
+/// the same, a field of IPv4 addresses costs as much as a `u64` fast field.
+pub struct IpAddrFastFieldReader {
+    high_bits_reader: FastFieldReader<u64>,
+    low_bits_reader: FastFieldReader<u64>,
+}
+
+impl IpAddrFastFieldReader {
+    pub(crate) fn open(
+        high_bits_reader: FastFieldReader<u64>,
+        low_bits_reader: FastFieldReader<u64>,
+    ) -> IpAddrFastFieldReader {
+        IpAddrFastFieldReader {
+            high_bits_reader,
+            low_bits_reader,
+        }
+    }
+
+    /// Returns the IP address associated to the given `doc`.
+    ///
+    /// IPv4 addresses are mapped into IPv6, and can be recovered
+    /// with `Ipv6Addr::to_ipv4`. The documents without any address
+    /// get the unspecified address `::`.
+    pub fn get(&self, doc: DocId) -> Ipv6Addr {
+        let high_bits = u128::from(self.high_bits_reader.get(doc));
+        let low_bits = u128::from(self.low_bits_reader.get(doc));
+        Ipv6Addr::from(high_bits << 64 | low_bits)
+    }
+}
@@ -0,0 +1,69 @@
+use fastfield::serializer::FastFieldSerializer;
+use schema::{Document, Field, Value};
+use std::io;
+use std::net::Ipv6Addr;
+
+/// Writer for IP address fast fields
+///
+/// The addresses are kept in memory until the segment is
+/// serialized, as `u128`. They are then written as two `u64`
+/// fast fields, holding their high and low bits respectively
+/// (see `IpAddrFastFieldReader`).
+pub struct IpAddrFastFieldWriter {
+    field: Field,
+    vals: Vec<u128>,
+}
+
+impl IpAddrFastFieldWriter {
+    /// Creates a new `IpAddrFastFieldWriter`
+    pub fn new(field: Field) -> IpAddrFastFieldWriter {
+        IpAddrFastFieldWriter {
+            field,
+            vals: Vec::new(),
+        }
+    }
+
+    /// Access the field associated to the `IpAddrFastFieldWriter`
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Records the address of the next document.
+    pub fn add_val(&mut self, val: Ipv6Addr) {
+        self.vals.push(u128::from(val));
+    }
+
+    /// Records the address of a new document.
+    ///
+    /// If the document has more than one address for the field,
+    /// only the first one is taken in account. The documents
+    /// without any address get the unspecified address `::`.
+    pub fn add_document(&mut self, doc: &Document) {
+        let val = match doc.get_first(self.field) {
+            Some(&Value::IpAddr(ip_addr)) => ip_addr,
+            Some(value) => panic!(
+                "IP address field contained non-IpAddr Value!. Field {:?} = {:?}",
+                self.field, value
+            ),
+            None => Ipv6Addr::from(0u128),
+        };
+        self.add_val(val);
+    }
+
+    /// Serializes the fast field values by pushing them to the `FastFieldSerializer`.
+    pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        // the high bits go to the first `u64` fast field, the low bits to the second one.
+        for (idx, shift) in [64u32, 0u32].iter().enumerate() {
+            let words = || self.vals.iter().map(|&val| (val >> shift) as u64);
+            let min_value = words().min().unwrap_or(0u64);
+            let max_value = words().max().unwrap_or(0u64);
+            let mut words_serializer =
+                serializer.new_u64_fast_field_with_idx(self.field, min_value, max_value, idx)?;
+            for word in words() {
+                words_serializer.add_val(word)?;
+            }
+            words_serializer.close_field()?;
+        }
+        Ok(())
+    }
+}
@@ -13,8 +13,12 @@ the `DocSet` : for instance for scoring, grouping, filtering, or faceting.
 
 
 Fields have to be declared as `FAST` in the  schema.
-Currently only 64-bits integers (signed or unsigned) are
-supported.
+Currently 64-bits integers (signed or unsigned),
+64-bits floating point numbers, dates, bytes and
+IP addresses are supported.
+
+Text fields can be fast fields as well: they then hold
+the ordinals of the terms of the documents.
 
 They are stored in a bit-packed fashion so that their
 memory usage is directly linear with the amplitude of the
@@ -28,9 +32,12 @@ pub use self::delete::write_delete_bitset;
 pub use self::delete::DeleteBitSet;
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
+pub use self::ip_addr::{IpAddrFastFieldReader, IpAddrFastFieldWriter};
 pub use self::multivalued::{MultiValueIntFastFieldReader, MultiValueIntFastFieldWriter};
 pub use self::reader::FastFieldReader;
 pub use self::serializer::FastFieldSerializer;
+pub use self::str_reader::StrFastFieldReader;
+pub(crate) use self::updates::FastFieldUpdates;
 pub use self::writer::{FastFieldsWriter, IntFastFieldWriter};
 use common;
 use schema::Cardinality;
@@ -41,12 +48,15 @@ mod bytes;
 mod delete;
 mod error;
 mod facet_reader;
+mod ip_addr;
 mod multivalued;
 mod reader;
 mod serializer;
+mod str_reader;
+mod updates;
 mod writer;
 
-/// Trait for types that are allowed for fast fields: (u64 or i64).
+/// Trait for types that are allowed for fast fields: (u64, i64 or f64).
 pub trait FastValue: Default + Clone + Copy {
     /// Converts a value from u64
     ///
@@ -67,6 +77,9 @@ pub trait FastValue: Default + Clone + Copy {
     /// Cast value to `u64`.
     /// The value is just reinterpreted in memory.
     fn as_u64(&self) -> u64;
+
+    /// Converts a value to `f64`, e.g. to compute a sum or a mean.
+    fn to_f64(&self) -> f64;
 }
 
 impl FastValue for u64 {
@@ -82,10 +95,17 @@ impl FastValue for u64 {
         *self
     }
 
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+
     fn fast_field_cardinality(field_type: &FieldType) -> Option<Cardinality> {
         match *field_type {
             FieldType::U64(ref integer_options) => integer_options.get_fastfield_cardinality(),
             FieldType::HierarchicalFacet => Some(Cardinality::MultiValues),
+            FieldType::Str(ref text_options) if text_options.is_fast() => {
+                Some(Cardinality::MultiValues)
+            }
             _ => None,
         }
     }
@@ -103,6 +123,7 @@ impl FastValue for i64 {
     fn fast_field_cardinality(field_type: &FieldType) -> Option<Cardinality> {
         match *field_type {
             FieldType::I64(ref integer_options) => integer_options.get_fastfield_cardinality(),
+            FieldType::Date(ref date_options) => date_options.get_fastfield_cardinality(),
             _ => None,
         }
     }
@@ -110,13 +131,44 @@ impl FastValue for i64 {
     fn as_u64(&self) -> u64 {
         *self as u64
     }
+
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl FastValue for f64 {
+    fn from_u64(val: u64) -> Self {
+        common::u64_to_f64(val)
+    }
+
+    fn to_u64(&self) -> u64 {
+        common::f64_to_u64(*self)
+    }
+
+    fn fast_field_cardinality(field_type: &FieldType) -> Option<Cardinality> {
+        match *field_type {
+            FieldType::F64(ref integer_options) => integer_options.get_fastfield_cardinality(),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> u64 {
+        self.to_bits()
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self
+    }
 }
 
 fn value_to_u64(value: &Value) -> u64 {
     match *value {
         Value::U64(ref val) => *val,
         Value::I64(ref val) => common::i64_to_u64(*val),
-        _ => panic!("Expected a u64/i64 field, got {:?} ", value),
+        Value::F64(ref val) => common::f64_to_u64(*val),
+        Value::Date(ref date) => common::i64_to_u64(common::date_to_i64(date)),
+        _ => panic!("Expected a u64/i64/f64/date field, got {:?} ", value),
     }
 }
 
@@ -124,18 +176,24 @@ fn value_to_u64(value: &Value) -> u64 {
 mod tests {
 
     use super::*;
+    use chrono::{TimeZone, Utc};
     use common::CompositeFile;
     use directory::{Directory, RAMDirectory, WritePtr};
     use fastfield::FastFieldReader;
+    use i64_to_date;
     use rand::prelude::SliceRandom;
     use rand::rngs::StdRng;
     use rand::SeedableRng;
     use schema::Document;
     use schema::Field;
     use schema::Schema;
+    use schema::Value;
     use schema::FAST;
+    use schema::{DateOptions, DatePrecision, INT_STORED};
     use std::collections::HashMap;
     use std::path::Path;
+    use DocAddress;
+    use Index;
 
     lazy_static! {
         pub static ref SCHEMA: Schema = {
@@ -367,6 +425,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_f64_fastfield() {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let f64_field = schema_builder.add_f64_field("field", FAST);
+        let schema = schema_builder.build();
+        let vals = [-1.5f64, 0.0f64, 2.25f64, -100.0f64, 1e10f64];
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::from_write(write).unwrap();
+            let mut fast_field_writers = FastFieldsWriter::from_schema(&schema);
+            for &val in &vals {
+                let mut doc = Document::default();
+                doc.add_f64(f64_field, val);
+                fast_field_writers.add_document(&doc);
+            }
+            fast_field_writers.add_document(&Document::default());
+            fast_field_writers
+                .serialize(&mut serializer, &HashMap::new())
+                .unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            let fast_fields_composite = CompositeFile::open(&source).unwrap();
+            let data = fast_fields_composite.open_read(f64_field).unwrap();
+            let fast_field_reader = FastFieldReader::<f64>::open(data);
+            assert_eq!(fast_field_reader.min_value(), -100.0f64);
+            assert_eq!(fast_field_reader.max_value(), 1e10f64);
+            for (doc, &val) in vals.iter().enumerate() {
+                assert_eq!(fast_field_reader.get(doc as u32), val);
+            }
+            // documents without value get 0.0
+            assert_eq!(fast_field_reader.get(5u32), 0.0f64);
+        }
+    }
+
+    #[test]
+    fn test_date_fastfield() {
+        let mut schema_builder = Schema::builder();
+        let date_field = schema_builder.add_date_field(
+            "date",
+            DateOptions::from(FAST | INT_STORED).set_precision(DatePrecision::Milliseconds),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let date = Utc.ymd(2019, 3, 14).and_hms_nano(15, 9, 26, 535_897_932);
+        let truncated_date = Utc.ymd(2019, 3, 14).and_hms_milli(15, 9, 26, 535);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_date(date_field, &date);
+            index_writer.add_document(doc).unwrap();
+            index_writer.add_document(Document::default()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let fast_field_reader = segment_reader.fast_field_reader::<i64>(date_field).unwrap();
+        assert_eq!(i64_to_date(fast_field_reader.get(0u32)), truncated_date);
+        // documents without value get the UNIX epoch.
+        assert_eq!(
+            i64_to_date(fast_field_reader.get(1u32)),
+            Utc.timestamp(0, 0)
+        );
+        let stored_doc = searcher.doc(DocAddress(0u32, 0u32)).unwrap();
+        assert_eq!(
+            stored_doc.get_first(date_field),
+            Some(&Value::Date(truncated_date))
+        );
+    }
+
     // Warning: this generates the same permutation at each call
     pub fn generate_permutation() -> Vec<u64> {
         let mut permutation: Vec<u64> = (0u64..100_000u64).collect();
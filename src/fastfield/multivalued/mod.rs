@@ -22,10 +22,14 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-        index_writer.add_document(doc!(field=>1u64, field=>3u64));
-        index_writer.add_document(doc!());
-        index_writer.add_document(doc!(field=>4u64));
-        index_writer.add_document(doc!(field=>5u64, field=>20u64,field=>1u64));
+        index_writer
+            .add_document(doc!(field=>1u64, field=>3u64))
+            .unwrap();
+        index_writer.add_document(doc!()).unwrap();
+        index_writer.add_document(doc!(field=>4u64)).unwrap();
+        index_writer
+            .add_document(doc!(field=>5u64, field=>20u64,field=>1u64))
+            .unwrap();
         assert!(index_writer.commit().is_ok());
 
         index.load_searchers().unwrap();
@@ -57,10 +61,14 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-        index_writer.add_document(doc!(field=> 1i64, field => 3i64));
-        index_writer.add_document(doc!());
-        index_writer.add_document(doc!(field=> -4i64));
-        index_writer.add_document(doc!(field=> -5i64, field => -20i64, field=>1i64));
+        index_writer
+            .add_document(doc!(field=> 1i64, field => 3i64))
+            .unwrap();
+        index_writer.add_document(doc!()).unwrap();
+        index_writer.add_document(doc!(field=> -4i64)).unwrap();
+        index_writer
+            .add_document(doc!(field=> -5i64, field => -20i64, field=>1i64))
+            .unwrap();
         assert!(index_writer.commit().is_ok());
 
         index.load_searchers().unwrap();
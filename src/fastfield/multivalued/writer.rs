@@ -34,17 +34,20 @@ pub struct MultiValueIntFastFieldWriter {
     field: Field,
     vals: Vec<u64>,
     doc_index: Vec<u64>,
-    is_facet: bool,
+    is_term_ords: bool,
 }
 
 impl MultiValueIntFastFieldWriter {
     /// Creates a new `IntFastFieldWriter`
-    pub(crate) fn new(field: Field, is_facet: bool) -> Self {
+    ///
+    /// If `is_term_ords` is true, the values are the term ids of
+    /// the field, pushed by the `SegmentWriter` while indexing.
+    pub(crate) fn new(field: Field, is_term_ords: bool) -> Self {
         MultiValueIntFastFieldWriter {
             field,
             vals: Vec::new(),
             doc_index: Vec::new(),
-            is_facet,
+            is_term_ords,
         }
     }
 
@@ -67,8 +70,9 @@ impl MultiValueIntFastFieldWriter {
     /// all of the matching field values present in the document.
     pub fn add_document(&mut self, doc: &Document) {
         self.next_doc();
-        // facets are indexed in the `SegmentWriter` as we encode their unordered id.
-        if !self.is_facet {
+        // facets and string fast fields are indexed in the `SegmentWriter`
+        // as we encode their unordered id.
+        if !self.is_term_ords {
             for field_value in doc.field_values() {
                 if field_value.field() == self.field {
                     self.add_val(value_to_u64(field_value.value()));
@@ -91,8 +95,9 @@ impl MultiValueIntFastFieldWriter {
     /// Serializes fast field values by pushing them to the `FastFieldSerializer`.
     ///
     /// If a mapping is given, the values are remapped *and sorted* before serialization.
-    /// This is used when serializing `facets`. Specifically their terms are
-    /// first stored in the writer as their position in the `IndexWriter`'s `HashMap`.
+    /// This is used when serializing `facets` and string fast fields.
+    /// Specifically their terms are first stored in the writer as their
+    /// position in the `IndexWriter`'s `HashMap`.
     /// This value is called an `UnorderedTermId`.
     ///
     /// During the serialization of the segment, terms gets sorted and
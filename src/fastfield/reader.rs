@@ -9,9 +9,11 @@ use fastfield::{FastFieldSerializer, FastFieldsWriter};
 use owning_ref::OwningRef;
 use schema::Schema;
 use schema::FAST;
+use std::cmp;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::sync::Arc;
 use DocId;
 
 /// Trait for accessing a fastfield.
@@ -23,6 +25,7 @@ pub struct FastFieldReader<Item: FastValue> {
     bit_unpacker: BitUnpacker<OwningRef<ReadOnlySource, [u8]>>,
     min_value_u64: u64,
     max_value_u64: u64,
+    updates: Option<Arc<HashMap<DocId, u64>>>,
     _phantom: PhantomData<Item>,
 }
 
@@ -46,10 +49,31 @@ impl<Item: FastValue> FastFieldReader<Item> {
             min_value_u64: min_value,
             max_value_u64: max_value,
             bit_unpacker,
+            updates: None,
             _phantom: PhantomData,
         }
     }
 
+    /// Overrides the values of some of the documents.
+    ///
+    /// The min and max values are widened to include the new values.
+    pub(crate) fn with_updates(self, updates: Arc<HashMap<DocId, u64>>) -> Self {
+        let min_value_u64 = updates
+            .values()
+            .cloned()
+            .fold(self.min_value_u64, cmp::min);
+        let max_value_u64 = updates
+            .values()
+            .cloned()
+            .fold(self.max_value_u64, cmp::max);
+        FastFieldReader {
+            min_value_u64,
+            max_value_u64,
+            updates: Some(updates),
+            ..self
+        }
+    }
+
     /// Return the value associated to the given document.
     ///
     /// This accessor should return as fast as possible.
@@ -59,6 +83,11 @@ impl<Item: FastValue> FastFieldReader<Item> {
     /// May panic if `doc` is greater than the segment
     // `maxdoc`.
     pub fn get(&self, doc: DocId) -> Item {
+        if let Some(ref updates) = self.updates {
+            if let Some(&val) = updates.get(&doc) {
+                return Item::from_u64(val);
+            }
+        }
         Item::from_u64(self.min_value_u64 + self.bit_unpacker.get(doc as usize))
     }
 
@@ -85,6 +114,13 @@ impl<Item: FastValue> FastFieldReader<Item> {
         for out in output_u64.iter_mut() {
             *out = Item::from_u64(*out + self.min_value_u64).as_u64();
         }
+        if let Some(ref updates) = self.updates {
+            for (doc, out) in (start..).zip(output_u64.iter_mut()) {
+                if let Some(&val) = updates.get(&doc) {
+                    *out = Item::from_u64(val).as_u64();
+                }
+            }
+        }
     }
 
     /// Returns the minimum value for this fast field.
@@ -0,0 +1,167 @@
+use super::MultiValueIntFastFieldReader;
+use termdict::TermDictionary;
+use termdict::TermOrdinal;
+use DocId;
+
+/// The string fast field reader makes it possible to access the terms
+/// of a string fast field (see `TextOptions::set_fast`) of a document
+/// in a specific segment, without fetching its stored fields.
+///
+/// The terms of a document are exposed in the form of the list of their
+/// ordinals, which are their position in the sorted list of the terms
+/// of the field. Sorting or grouping the documents of a segment by their
+/// term ordinals is therefore the same as doing it by their terms.
+///
+/// This ordinal is segment local and only makes sense for a given segment.
+/// It can be translated into the term via `.ord_to_bytes(...)`.
+pub struct StrFastFieldReader {
+    term_ords: MultiValueIntFastFieldReader<u64>,
+    term_dict: TermDictionary,
+}
+
+impl StrFastFieldReader {
+    /// Creates a new `StrFastFieldReader`.
+    ///
+    /// A string fast field reader just wraps :
+    /// - a `MultiValueIntFastFieldReader` that makes it possible to
+    /// access the list of term ords for a given document.
+    /// - a `TermDictionary` that helps associating a term to
+    /// an ordinal and vice versa.
+    pub fn new(
+        term_ords: MultiValueIntFastFieldReader<u64>,
+        term_dict: TermDictionary,
+    ) -> StrFastFieldReader {
+        StrFastFieldReader {
+            term_ords,
+            term_dict,
+        }
+    }
+
+    /// Returns the number of terms of the field in the segment.
+    /// This does not take in account the documents that may be marked
+    /// as deleted.
+    ///
+    /// Term ordinals range from `0` to `num_terms() - 1`.
+    pub fn num_terms(&self) -> usize {
+        self.term_dict.num_terms()
+    }
+
+    /// Accessor for the term dictionary of the field.
+    pub fn term_dict(&self) -> &TermDictionary {
+        &self.term_dict
+    }
+
+    /// Given a term ordinal, writes the bytes of the term
+    /// associated to it in `output`.
+    ///
+    /// The terms of the text fields are valid UTF-8.
+    pub fn ord_to_bytes(&self, term_ord: TermOrdinal, output: &mut Vec<u8>) {
+        let found_term = self.term_dict.ord_to_term(term_ord, output);
+        assert!(found_term, "Term ordinal {} no found.", term_ord);
+    }
+
+    /// Return the sorted list of the term ordinals associated to a document.
+    ///
+    /// A term appears as many times as the document contains it.
+    pub fn term_ords(&self, doc: DocId, output: &mut Vec<TermOrdinal>) {
+        self.term_ords.get_vals(doc, output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::StrFastFieldReader;
+    use futures::Future;
+    use schema::{Document, Schema, STORED, STRING, TEXT};
+    use std::str;
+    use DocId;
+    use Index;
+
+    fn terms(str_reader: &StrFastFieldReader, doc: DocId) -> Vec<String> {
+        let mut term_ords = Vec::new();
+        str_reader.term_ords(doc, &mut term_ords);
+        let mut term = Vec::new();
+        term_ords
+            .into_iter()
+            .map(|term_ord| {
+                str_reader.ord_to_bytes(term_ord, &mut term);
+                str::from_utf8(&term).unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_str_fast_field_reader() {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING.set_fast());
+        let title = schema_builder.add_text_field("title", TEXT.set_fast() | STORED);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let country = schema_builder.add_text_field("country", STRING.set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(city => "Tokyo", title => "the cat and the dog"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(city => "Osaka", city => "Kyoto"))
+                .unwrap();
+            index_writer.add_document(Document::default()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let city_reader = segment_reader.str_fast_field_reader(city).unwrap();
+        assert_eq!(city_reader.num_terms(), 3);
+        assert_eq!(terms(&city_reader, 0), vec!["Tokyo"]);
+        assert_eq!(terms(&city_reader, 1), vec!["Kyoto", "Osaka"]);
+        assert!(terms(&city_reader, 2).is_empty());
+        let title_reader = segment_reader.str_fast_field_reader(title).unwrap();
+        assert_eq!(
+            terms(&title_reader, 0),
+            vec!["and", "cat", "dog", "the", "the"]
+        );
+        assert!(segment_reader.str_fast_field_reader(body).is_err());
+        let country_reader = segment_reader.str_fast_field_reader(country).unwrap();
+        assert_eq!(country_reader.num_terms(), 0);
+        assert!(terms(&country_reader, 0).is_empty());
+    }
+
+    #[test]
+    fn test_str_fast_field_merge() {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING.set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(city => "Tokyo")).unwrap();
+        index_writer.add_document(doc!(city => "Osaka")).unwrap();
+        index_writer.commit().unwrap();
+        index_writer
+            .add_document(doc!(city => "Kyoto", city => "Tokyo"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let city_reader = searcher
+            .segment_reader(0)
+            .str_fast_field_reader(city)
+            .unwrap();
+        assert_eq!(city_reader.num_terms(), 3);
+        let mut doc_terms: Vec<Vec<String>> = (0..3).map(|doc| terms(&city_reader, doc)).collect();
+        doc_terms.sort();
+        assert_eq!(
+            doc_terms,
+            vec![vec!["Kyoto", "Tokyo"], vec!["Osaka"], vec!["Tokyo"]]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use common::BinarySerializable;
+use directory::ReadOnlySource;
+use schema::Field;
+use space_usage::ByteCount;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::sync::Arc;
+use DocId;
+
+/// New values of the single value fast fields of some of
+/// the documents of a segment.
+///
+/// They override the values serialized with the segment,
+/// and are stored in the `.upd` file of the segment.
+#[derive(Clone, Debug, Default)]
+pub struct FastFieldUpdates {
+    fields: BTreeMap<Field, Arc<HashMap<DocId, u64>>>,
+}
+
+impl FastFieldUpdates {
+    /// Opens the fast field updates given their data source.
+    pub fn open(data: &ReadOnlySource) -> io::Result<FastFieldUpdates> {
+        let mut cursor = data.as_slice();
+        let num_fields = u32::deserialize(&mut cursor)?;
+        let mut fields = BTreeMap::new();
+        for _ in 0..num_fields {
+            let field = Field::deserialize(&mut cursor)?;
+            let num_docs = u32::deserialize(&mut cursor)?;
+            let mut values = HashMap::with_capacity(num_docs as usize);
+            for _ in 0..num_docs {
+                let doc = DocId::deserialize(&mut cursor)?;
+                let val = u64::deserialize(&mut cursor)?;
+                values.insert(doc, val);
+            }
+            fields.insert(field, Arc::new(values));
+        }
+        Ok(FastFieldUpdates { fields })
+    }
+
+    /// Serializes the updates.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (self.fields.len() as u32).serialize(writer)?;
+        for (field, values) in &self.fields {
+            field.serialize(writer)?;
+            (values.len() as u32).serialize(writer)?;
+            let mut sorted_values: Vec<(DocId, u64)> =
+                values.iter().map(|(&doc, &val)| (doc, val)).collect();
+            sorted_values.sort();
+            for (doc, val) in sorted_values {
+                doc.serialize(writer)?;
+                val.serialize(writer)?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Sets the value of `field` for the document `doc`.
+    pub fn set(&mut self, field: Field, doc: DocId, val: u64) {
+        let values = self
+            .fields
+            .entry(field)
+            .or_insert_with(|| Arc::new(HashMap::new()));
+        Arc::make_mut(values).insert(doc, val);
+    }
+
+    /// Returns the new values of `field`, if any.
+    pub fn field_updates(&self, field: Field) -> Option<Arc<HashMap<DocId, u64>>> {
+        self.fields.get(&field).cloned()
+    }
+
+    /// Returns the number of bytes of the serialized updates.
+    pub fn space_usage(&self) -> ByteCount {
+        let num_bytes_per_update = mem::size_of::<DocId>() + mem::size_of::<u64>();
+        self.fields
+            .values()
+            .map(|values| 8 + values.len() * num_bytes_per_update)
+            .sum::<usize>()
+            + if self.is_empty() { 0 } else { 4 }
+    }
+
+    /// Returns true iff no value was updated.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FastFieldUpdates;
+    use directory::ReadOnlySource;
+    use schema::Field;
+
+    #[test]
+    fn test_fast_field_updates_serialization() {
+        let mut updates = FastFieldUpdates::default();
+        assert!(updates.is_empty());
+        updates.set(Field(2), 3, 17u64);
+        updates.set(Field(0), 1, 4u64);
+        updates.set(Field(2), 3, 18u64);
+        updates.set(Field(2), 0, 1u64);
+        assert!(!updates.is_empty());
+        let mut buffer = Vec::new();
+        updates.write(&mut buffer).unwrap();
+        let reopened = FastFieldUpdates::open(&ReadOnlySource::from(buffer)).unwrap();
+        let field_updates = reopened.field_updates(Field(2)).unwrap();
+        assert_eq!(field_updates.len(), 2);
+        assert_eq!(field_updates.get(&3), Some(&18u64));
+        assert_eq!(field_updates.get(&0), Some(&1u64));
+        assert_eq!(reopened.field_updates(Field(0)).unwrap().get(&1), Some(&4u64));
+        assert!(reopened.field_updates(Field(1)).is_none());
+    }
+}
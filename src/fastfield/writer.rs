@@ -2,7 +2,7 @@ use super::multivalued::MultiValueIntFastFieldWriter;
 use common;
 use common::BinarySerializable;
 use common::VInt;
-use fastfield::{BytesFastFieldWriter, FastFieldSerializer};
+use fastfield::{BytesFastFieldWriter, FastFieldSerializer, IpAddrFastFieldWriter};
 use postings::UnorderedTermId;
 use schema::{Cardinality, Document, Field, FieldType, Schema};
 use std::collections::HashMap;
@@ -14,6 +14,7 @@ pub struct FastFieldsWriter {
     single_value_writers: Vec<IntFastFieldWriter>,
     multi_values_writers: Vec<MultiValueIntFastFieldWriter>,
     bytes_value_writers: Vec<BytesFastFieldWriter>,
+    ip_addr_value_writers: Vec<IpAddrFastFieldWriter>,
 }
 
 impl FastFieldsWriter {
@@ -22,36 +23,56 @@ impl FastFieldsWriter {
         let mut single_value_writers = Vec::new();
         let mut multi_values_writers = Vec::new();
         let mut bytes_value_writers = Vec::new();
+        let mut ip_addr_value_writers = Vec::new();
 
         for (field_id, field_entry) in schema.fields().iter().enumerate() {
             let field = Field(field_id as u32);
-            let default_value = if let FieldType::I64(_) = *field_entry.field_type() {
-                common::i64_to_u64(0i64)
-            } else {
-                0u64
+            let default_value = match *field_entry.field_type() {
+                FieldType::I64(_) | FieldType::Date(_) => common::i64_to_u64(0i64),
+                FieldType::F64(_) => common::f64_to_u64(0.0f64),
+                _ => 0u64,
             };
-            match *field_entry.field_type() {
-                FieldType::I64(ref int_options) | FieldType::U64(ref int_options) => {
-                    match int_options.get_fastfield_cardinality() {
-                        Some(Cardinality::SingleValue) => {
-                            let mut fast_field_writer = IntFastFieldWriter::new(field);
-                            fast_field_writer.set_val_if_missing(default_value);
-                            single_value_writers.push(fast_field_writer);
-                        }
-                        Some(Cardinality::MultiValues) => {
-                            let fast_field_writer = MultiValueIntFastFieldWriter::new(field, false);
-                            multi_values_writers.push(fast_field_writer);
-                        }
-                        None => {}
-                    }
+            let int_cardinality_opt = match *field_entry.field_type() {
+                FieldType::I64(ref int_options)
+                | FieldType::U64(ref int_options)
+                | FieldType::F64(ref int_options) => int_options.get_fastfield_cardinality(),
+                FieldType::Date(ref date_options) => date_options.get_fastfield_cardinality(),
+                _ => None,
+            };
+            match int_cardinality_opt {
+                Some(Cardinality::SingleValue) => {
+                    let mut fast_field_writer = IntFastFieldWriter::new(field);
+                    fast_field_writer.set_val_if_missing(default_value);
+                    single_value_writers.push(fast_field_writer);
+                }
+                Some(Cardinality::MultiValues) => {
+                    let fast_field_writer = MultiValueIntFastFieldWriter::new(field, false);
+                    multi_values_writers.push(fast_field_writer);
                 }
+                None => {}
+            }
+            match *field_entry.field_type() {
                 FieldType::HierarchicalFacet => {
                     let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
                     multi_values_writers.push(fast_field_writer);
                 }
-                FieldType::Bytes => {
-                    let fast_field_writer = BytesFastFieldWriter::new(field);
-                    bytes_value_writers.push(fast_field_writer);
+                FieldType::Str(ref text_options) => {
+                    if text_options.is_fast() {
+                        let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
+                        multi_values_writers.push(fast_field_writer);
+                    }
+                }
+                FieldType::Bytes(ref bytes_options) => {
+                    if bytes_options.is_fast() {
+                        let fast_field_writer = BytesFastFieldWriter::new(field);
+                        bytes_value_writers.push(fast_field_writer);
+                    }
+                }
+                FieldType::IpAddr(ref ip_addr_options) => {
+                    if ip_addr_options.is_fast() {
+                        let fast_field_writer = IpAddrFastFieldWriter::new(field);
+                        ip_addr_value_writers.push(fast_field_writer);
+                    }
                 }
                 _ => {}
             }
@@ -60,6 +81,7 @@ impl FastFieldsWriter {
             single_value_writers,
             multi_values_writers,
             bytes_value_writers,
+            ip_addr_value_writers,
         }
     }
 
@@ -107,6 +129,9 @@ impl FastFieldsWriter {
         for field_writer in &mut self.bytes_value_writers {
             field_writer.add_document(doc);
         }
+        for field_writer in &mut self.ip_addr_value_writers {
+            field_writer.add_document(doc);
+        }
     }
 
     /// Serializes all of the `FastFieldWriter`s by pushing them in
@@ -126,6 +151,9 @@ impl FastFieldsWriter {
         for field_writer in &self.bytes_value_writers {
             field_writer.serialize(serializer)?;
         }
+        for field_writer in &self.ip_addr_value_writers {
+            field_writer.serialize(serializer)?;
+        }
         Ok(())
     }
 }
@@ -43,7 +43,7 @@ fn test_indexing() {
         } else {
             if committed_docs.remove(&random_val) || uncommitted_docs.remove(&random_val) {
                 let doc_id_term = Term::from_field_u64(id_field, random_val);
-                index_writer.delete_term(doc_id_term);
+                index_writer.delete_term(doc_id_term).unwrap();
             } else {
                 uncommitted_docs.insert(random_val);
                 let mut doc = Document::new();
@@ -51,7 +51,7 @@ fn test_indexing() {
                 for i in 1u64..10u64 {
                     doc.add_u64(multiples_field, random_val * i);
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
         }
     }
@@ -79,6 +79,17 @@ impl DeleteQueue {
             .push(delete_operation);
     }
 
+    // Appends several delete operations at once.
+    //
+    // Consumers either see all of them, or none of them.
+    pub fn push_all(&self, delete_operations: Vec<DeleteOperation>) {
+        self.inner
+            .write()
+            .expect("Failed to acquire write lock on delete queue writer")
+            .writer
+            .extend(delete_operations);
+    }
+
     // DeleteQueue is a linked list of blocks of
     // delete operations.
     //
@@ -249,6 +260,7 @@ impl DeleteCursor {
 mod tests {
 
     use super::{DeleteOperation, DeleteQueue};
+    use indexer::operation::DeleteTarget;
     use schema::{Field, Term};
 
     #[test]
@@ -259,7 +271,7 @@ mod tests {
             let field = Field(1u32);
             DeleteOperation {
                 opstamp: i as u64,
-                term: Term::from_field_u64(field, i as u64),
+                target: DeleteTarget::Term(Term::from_field_u64(field, i as u64)),
             }
         };
 
@@ -1,5 +1,5 @@
 use directory::error::OpenWriteError;
-use std::io::Write;
+use directory::SeekableWrite;
 use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
@@ -48,6 +48,16 @@ impl RetryPolicy {
         }
     }
 
+    /// Retries every `100ms`, until `timeout` has elapsed.
+    fn with_timeout(timeout: Duration) -> RetryPolicy {
+        let wait_in_ms = 100u64;
+        let timeout_in_ms = timeout.as_secs() * 1_000 + u64::from(timeout.subsec_millis());
+        RetryPolicy {
+            num_retries: ((timeout_in_ms + wait_in_ms - 1) / wait_in_ms) as usize,
+            wait_in_ms,
+        }
+    }
+
     fn wait_and_retry(&mut self) -> bool {
         if self.num_retries == 0 {
             false
@@ -77,7 +87,7 @@ impl LockType {
             OpenWriteError::FileAlreadyExists(_) => TantivyError::LockFailure(self),
             OpenWriteError::IOError(io_error) => TantivyError::IOError(io_error),
         })?;
-        write.flush()?;
+        write.terminate()?;
         Ok(DirectoryLock {
             directory: directory.box_clone(),
             path: path.to_owned(),
@@ -86,8 +96,25 @@ impl LockType {
 
     /// Acquire a lock in the given directory.
     pub fn acquire_lock(self, directory: &Directory) -> Result<DirectoryLock, TantivyError> {
+        self.acquire_lock_with_retry_policy(directory, self.retry_policy())
+    }
+
+    /// Acquire a lock in the given directory, waiting for
+    /// at most `timeout` if it is currently held.
+    pub fn acquire_lock_with_timeout(
+        self,
+        directory: &Directory,
+        timeout: Duration,
+    ) -> Result<DirectoryLock, TantivyError> {
+        self.acquire_lock_with_retry_policy(directory, RetryPolicy::with_timeout(timeout))
+    }
+
+    fn acquire_lock_with_retry_policy(
+        self,
+        directory: &Directory,
+        mut retry_policy: RetryPolicy,
+    ) -> Result<DirectoryLock, TantivyError> {
         let mut box_directory = directory.box_clone();
-        let mut retry_policy = self.retry_policy();
         loop {
             let lock_result = self.try_acquire_lock(&mut *box_directory);
             match lock_result {
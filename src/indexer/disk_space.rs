@@ -0,0 +1,69 @@
+//! Estimation of the space taken by an index on disk.
+//!
+//! These estimations are used by the `IndexWriter` to enforce
+//! its disk space limit, before accepting new documents or
+//! starting to write a new segment.
+use core::Index;
+use core::SegmentComponent;
+use core::SegmentMeta;
+use directory::Directory;
+use std::path::Path;
+use Result;
+
+/// Measures the number of bytes taken on disk by the files of a segment,
+/// and records it in its `SegmentMeta`.
+///
+/// The size of the segments is recorded as their files are written, so
+/// this is only required for the segments written by older versions
+/// of tantivy. As it opens all of the files of the segment, the result
+/// should be kept rather than measured again.
+pub fn with_measured_num_bytes(index: &Index, segment_meta: SegmentMeta) -> Result<SegmentMeta> {
+    let directory = index.directory();
+    let file_num_bytes = |path: &Path| -> Result<u64> {
+        if directory.exists(path) {
+            Ok(directory.open_read(path)?.len() as u64)
+        } else {
+            Ok(0u64)
+        }
+    };
+    let mut num_bytes = 0u64;
+    for &component in SegmentComponent::iterator() {
+        match component {
+            SegmentComponent::DELETE | SegmentComponent::FASTFIELDUPDATES => {}
+            _ => {
+                num_bytes += file_num_bytes(&segment_meta.relative_path(component))?;
+            }
+        }
+    }
+    let mut segment_meta = segment_meta.with_num_bytes(num_bytes);
+    if segment_meta.delete_opstamp().is_some() {
+        let delete_path = segment_meta.relative_path(SegmentComponent::DELETE);
+        segment_meta = segment_meta.with_delete_num_bytes(file_num_bytes(&delete_path)?);
+    }
+    if segment_meta.has_fast_field_updates() {
+        let updates_path = segment_meta.relative_path(SegmentComponent::FASTFIELDUPDATES);
+        segment_meta =
+            segment_meta.with_fast_field_updates_num_bytes(file_num_bytes(&updates_path)?);
+    }
+    Ok(segment_meta)
+}
+
+/// Estimates the number of bytes of the segment resulting from
+/// the merge of the given segments.
+///
+/// Deleted documents are not copied into the merged segment, so the size
+/// of each segment is prorated by its ratio of alive documents.
+///
+/// The segment metas are expected to record their number of bytes.
+pub fn estimate_merge_num_bytes(segment_metas: &[SegmentMeta]) -> u64 {
+    let mut num_bytes = 0f64;
+    for segment_meta in segment_metas {
+        if segment_meta.max_doc() == 0 {
+            continue;
+        }
+        let alive_ratio = f64::from(segment_meta.num_docs()) / f64::from(segment_meta.max_doc());
+        let segment_num_bytes = segment_meta.num_bytes().unwrap_or(0u64);
+        num_bytes += segment_num_bytes as f64 * alive_ratio;
+    }
+    num_bytes.ceil() as u64
+}
@@ -0,0 +1,183 @@
+use core::SegmentReader;
+use DocId;
+
+/// Address of a document in the segments being merged,
+/// as its segment ordinal and its doc id within the segment.
+pub(crate) type OldDocAddr = (usize, DocId);
+
+/// Maps the doc ids of the segments being merged to the doc ids
+/// of the resulting segment.
+///
+/// The mapping is shared by all of the mergers (postings, fieldnorms,
+/// fast fields, offsets and doc store), so that a document keeps the
+/// same doc id in all of the data structures of the new segment.
+///
+/// A document without a new doc id, e.g. a deleted document,
+/// is dropped from the new segment.
+pub(crate) struct DocIdMapping {
+    /// Old address of each of the new doc ids, in the new doc id order.
+    new_to_old: Vec<OldDocAddr>,
+    /// For each segment, the new doc id of each of its doc ids.
+    old_to_new: Vec<Vec<Option<DocId>>>,
+    /// True iff the new doc ids follow the order of the segments,
+    /// and of the doc ids within each segment.
+    is_stacked: bool,
+}
+
+impl DocIdMapping {
+    /// Creates a mapping given the old address of each new doc id.
+    ///
+    /// # Panics
+    /// Panics if an old address is out of bounds, or appears twice.
+    pub fn new(readers: &[SegmentReader], new_to_old: Vec<OldDocAddr>) -> DocIdMapping {
+        let mut old_to_new: Vec<Vec<Option<DocId>>> = readers
+            .iter()
+            .map(|reader| vec![None; reader.max_doc() as usize])
+            .collect();
+        for (new_doc, &(segment_ord, old_doc)) in new_to_old.iter().enumerate() {
+            let new_doc_slot = &mut old_to_new[segment_ord][old_doc as usize];
+            assert!(
+                new_doc_slot.is_none(),
+                "Document {} of segment {} is mapped twice.",
+                old_doc,
+                segment_ord
+            );
+            *new_doc_slot = Some(new_doc as DocId);
+        }
+        let is_stacked = new_to_old
+            .windows(2)
+            .all(|old_doc_addrs| old_doc_addrs[0] < old_doc_addrs[1]);
+        DocIdMapping {
+            new_to_old,
+            old_to_new,
+            is_stacked,
+        }
+    }
+
+    /// Creates the mapping stacking the alive documents of the segments.
+    ///
+    /// The deleted documents are dropped, and the alive documents keep
+    /// their relative order:
+    /// - Segment 0's alive docs become doc ids `[0, seg0.num_docs)`
+    /// - Segment 1's alive docs become doc ids `[seg0.num_docs, seg0.num_docs + seg1.num_docs)`
+    /// ...
+    pub fn compact_deletes(readers: &[SegmentReader]) -> DocIdMapping {
+        let new_to_old = readers
+            .iter()
+            .enumerate()
+            .flat_map(|(segment_ord, reader)| {
+                reader
+                    .doc_ids_alive()
+                    .map(move |doc_id| (segment_ord, doc_id))
+            })
+            .collect();
+        DocIdMapping::new(readers, new_to_old)
+    }
+
+    /// Returns the number of documents in the new segment.
+    pub fn num_docs(&self) -> u32 {
+        self.new_to_old.len() as u32
+    }
+
+    /// Returns the old addresses of the documents, in the new doc id order.
+    pub fn iter_old_doc_addrs<'a>(&'a self) -> impl Iterator<Item = OldDocAddr> + 'a {
+        self.new_to_old.iter().cloned()
+    }
+
+    /// Returns the new doc id of each of the doc ids of a segment.
+    pub fn old_to_new_doc_ids(&self, segment_ord: usize) -> &[Option<DocId>] {
+        &self.old_to_new[segment_ord]
+    }
+
+    /// Returns true iff the new doc ids follow the order of the segments,
+    /// and of the doc ids within each segment.
+    ///
+    /// In that case, the data of the segments can be appended
+    /// one after the other, skipping the dropped documents.
+    pub fn is_stacked(&self) -> bool {
+        self.is_stacked
+    }
+
+    /// Returns true iff all of the documents of a segment are kept.
+    pub fn keeps_all_docs(&self, segment_ord: usize) -> bool {
+        self.old_to_new[segment_ord].iter().all(Option::is_some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::DocIdMapping;
+    use core::{Index, SegmentReader};
+    use schema::{Schema, INT_INDEXED};
+    use Term;
+
+    fn segment_readers(index: &Index) -> Vec<SegmentReader> {
+        index.searcher().segment_readers().to_vec()
+    }
+
+    #[test]
+    fn test_doc_id_mapping() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for id in 0u64..3u64 {
+                index_writer.add_document(doc!(id_field=>id)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            for id in 3u64..5u64 {
+                index_writer.add_document(doc!(id_field=>id)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(id_field, 1u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let mut readers = segment_readers(&index);
+        readers.sort_by_key(|reader| -(reader.max_doc() as i32));
+        assert_eq!(readers.len(), 2);
+
+        let doc_id_mapping = DocIdMapping::compact_deletes(&readers);
+        assert_eq!(doc_id_mapping.num_docs(), 4);
+        assert!(doc_id_mapping.is_stacked());
+        assert_eq!(
+            doc_id_mapping.iter_old_doc_addrs().collect::<Vec<_>>(),
+            vec![(0, 0), (0, 2), (1, 0), (1, 1)]
+        );
+        assert_eq!(
+            doc_id_mapping.old_to_new_doc_ids(0),
+            &[Some(0), None, Some(1)]
+        );
+        assert_eq!(doc_id_mapping.old_to_new_doc_ids(1), &[Some(2), Some(3)]);
+        assert!(!doc_id_mapping.keeps_all_docs(0));
+        assert!(doc_id_mapping.keeps_all_docs(1));
+
+        let doc_id_mapping = DocIdMapping::new(&readers, vec![(1, 1), (0, 2), (0, 0)]);
+        assert_eq!(doc_id_mapping.num_docs(), 3);
+        assert!(!doc_id_mapping.is_stacked());
+        assert_eq!(
+            doc_id_mapping.old_to_new_doc_ids(0),
+            &[Some(2), None, Some(1)]
+        );
+        assert_eq!(doc_id_mapping.old_to_new_doc_ids(1), &[None, Some(0)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_doc_id_mapping_duplicate() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(id_field=>1u64)).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        DocIdMapping::new(&segment_readers(&index), vec![(0, 0), (0, 0)]);
+    }
+}
@@ -0,0 +1,291 @@
+use common::{CompositeFile, CompositeWrite};
+use core::Index;
+use core::Segment;
+use core::SegmentComponent;
+use core::SegmentId;
+use core::SegmentMeta;
+use core::SegmentReader;
+use directory::error::OpenReadError;
+use directory::SeekableWrite;
+use error::TantivyError;
+use indexer::index_writer::advance_deletes;
+use indexer::operation::AddOperation;
+use indexer::SegmentEntry;
+use indexer::SegmentWriter;
+use schema::{Document, Field, FieldValue, Schema};
+use std::io::Write;
+use Result;
+
+// Number of bits of the hash table of the `SegmentWriter`
+// used to reindex a field.
+const REINDEX_TABLE_BITS: usize = 18;
+
+/// Components storing their data per field.
+const PER_FIELD_COMPONENTS: [SegmentComponent; 7] = [
+    SegmentComponent::TERMS,
+    SegmentComponent::POSTINGS,
+    SegmentComponent::POSITIONS,
+    SegmentComponent::POSITIONSSKIP,
+    SegmentComponent::FASTFIELDS,
+    SegmentComponent::FIELDNORMS,
+    SegmentComponent::OFFSETS,
+];
+
+/// Checks that `field` can be rebuilt from the doc store.
+pub fn check_reindexable(schema: &Schema, field: Field) -> Result<()> {
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_stored() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} cannot be reindexed as it is not stored.",
+            field_entry.name()
+        )));
+    }
+    Ok(())
+}
+
+/// Rebuilds the data of `field` in the segment of `segment_entry`,
+/// by indexing the stored values of the field again.
+///
+/// The data of the other fields, as well as the doc store and the delete
+/// bitset, are copied as is in the segment `reindexed_segment_id`.
+pub fn reindex_segment_field(
+    index: &Index,
+    mut segment_entry: SegmentEntry,
+    field: Field,
+    reindexed_segment_id: SegmentId,
+    target_opstamp: u64,
+) -> Result<SegmentEntry> {
+    let schema = index.schema();
+    check_reindexable(&schema, field)?;
+
+    // deletes are applied first, so that the reindexed
+    // segment inherits an up-to-date delete bitset.
+    let segment = index.segment(segment_entry.meta().clone());
+    advance_deletes(segment, &mut segment_entry, target_opstamp)?;
+    let original_meta = segment_entry.meta().clone();
+    let original_segment = index.segment(original_meta.clone());
+
+    // The field is indexed in a temporary segment, the files
+    // of which are removed by the next garbage collection.
+    let field_segment = index.segment(SegmentMeta::new_with_settings(
+        SegmentId::generate_random(),
+        original_meta.max_doc(),
+        &original_meta,
+    ));
+    index_stored_values(&original_segment, field_segment.clone(), field, &schema)?;
+
+    let mut reindexed_meta = SegmentMeta::new_with_settings(
+        reindexed_segment_id,
+        original_meta.max_doc(),
+        &original_meta,
+    );
+    if let Some(delete_opstamp) = original_meta.delete_opstamp() {
+        reindexed_meta =
+            reindexed_meta.with_delete_meta(original_meta.num_deleted_docs(), delete_opstamp);
+    }
+    if let Some(updates_opstamp) = original_meta.fast_field_updates_opstamp() {
+        reindexed_meta = reindexed_meta.with_fast_field_updates(updates_opstamp);
+    }
+    let mut reindexed_segment = index.segment(reindexed_meta.clone());
+    for &component in PER_FIELD_COMPONENTS.iter() {
+        let original_composite = open_composite(&original_segment, component)?;
+        let field_composite = open_composite(&field_segment, component)?;
+        let write = reindexed_segment.open_write(component)?;
+        let mut composite_write = CompositeWrite::wrap(write);
+        let mut file_addrs: Vec<(Field, usize, &CompositeFile)> = original_composite
+            .file_addrs()
+            .into_iter()
+            .filter(|&(file_field, _)| file_field != field)
+            .map(|(file_field, idx)| (file_field, idx, &original_composite))
+            .collect();
+        file_addrs.extend(
+            field_composite
+                .file_addrs()
+                .into_iter()
+                .filter(|&(file_field, _)| file_field == field)
+                .map(|(file_field, idx)| (file_field, idx, &field_composite)),
+        );
+        file_addrs.sort_by_key(|&(file_field, idx, _)| (file_field, idx));
+        for (file_field, idx, composite) in file_addrs {
+            let source = composite
+                .open_read_with_idx(file_field, idx)
+                .expect("The file address was listed by the composite file.");
+            composite_write
+                .for_field_with_idx(file_field, idx)
+                .write_all(source.as_slice())?;
+        }
+        composite_write.close()?;
+    }
+    copy_component(
+        &original_segment,
+        &mut reindexed_segment,
+        SegmentComponent::STORE,
+    )?;
+    let mut reindexed_meta = reindexed_meta.with_num_bytes(reindexed_segment.num_bytes_written());
+    if original_meta.has_deletes() {
+        let delete_num_bytes = copy_component(
+            &original_segment,
+            &mut reindexed_segment,
+            SegmentComponent::DELETE,
+        )?;
+        reindexed_meta = reindexed_meta.with_delete_num_bytes(delete_num_bytes);
+    }
+    if original_meta.has_fast_field_updates() {
+        let updates_num_bytes = copy_component(
+            &original_segment,
+            &mut reindexed_segment,
+            SegmentComponent::FASTFIELDUPDATES,
+        )?;
+        reindexed_meta = reindexed_meta.with_fast_field_updates_num_bytes(updates_num_bytes);
+    }
+
+    let delete_cursor = segment_entry.delete_cursor().clone();
+    Ok(SegmentEntry::new(reindexed_meta, delete_cursor, None))
+}
+
+/// Indexes the values of `field` stored in `segment` in `field_segment`,
+/// preserving the doc ids.
+fn index_stored_values(
+    segment: &Segment,
+    field_segment: Segment,
+    field: Field,
+    schema: &Schema,
+) -> Result<()> {
+    let segment_reader = SegmentReader::open(segment)?;
+    let store_reader = segment_reader.get_store_reader();
+    let mut segment_writer = SegmentWriter::for_segment(REINDEX_TABLE_BITS, field_segment, schema)?;
+    for doc_id in 0..segment_reader.max_doc() {
+        let stored_doc = store_reader.get(doc_id)?;
+        let mut document = Document::default();
+        for value in stored_doc.get_all(field) {
+            document.add(FieldValue::new(field, value.clone()));
+        }
+        segment_writer.add_document(
+            AddOperation {
+                opstamp: 0u64,
+                document,
+            },
+            schema,
+        )?;
+    }
+    segment_writer.finalize()?;
+    Ok(())
+}
+
+fn open_composite(segment: &Segment, component: SegmentComponent) -> Result<CompositeFile> {
+    match segment.open_read(component) {
+        Ok(source) => Ok(CompositeFile::open(&source)?),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(CompositeFile::empty()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Copies a component of `segment` into `target_segment`, and returns
+/// the number of bytes of the copy.
+fn copy_component(
+    segment: &Segment,
+    target_segment: &mut Segment,
+    component: SegmentComponent,
+) -> Result<u64> {
+    let num_bytes_before = target_segment.num_bytes_written();
+    let source = segment.open_read(component)?;
+    let mut write = target_segment.open_write(component)?;
+    write.write_all(source.as_slice())?;
+    write.terminate()?;
+    Ok(target_segment.num_bytes_written() - num_bytes_before)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::Count;
+    use query::TermQuery;
+    use schema::{self, IndexRecordOption, TextFieldIndexing, TextOptions};
+    use tokenizer::{LowerCaser, SimpleTokenizer, Tokenizer};
+    use Index;
+    use Term;
+
+    #[test]
+    fn test_reindex_field() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("custom")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+            .set_stored();
+        let title = schema_builder.add_text_field("title", text_options);
+        let body = schema_builder.add_text_field("body", schema::TEXT);
+        let id = schema_builder.add_u64_field("id", schema::INT_INDEXED | schema::FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register("custom", SimpleTokenizer);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer
+            .add_document(doc!(title => "Hello World", body => "Body", id => 1u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(title => "Another Title", body => "Body", id => 2u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index_writer
+            .add_document(doc!(title => "Hello", body => "Other", id => 3u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(title => "Hello", body => "Other", id => 4u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index_writer
+            .delete_term(Term::from_field_u64(id, 4u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let count = |term: Term| {
+            index.load_searchers().unwrap();
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            index.searcher().search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(Term::from_field_text(title, "hello")), 0);
+        assert_eq!(count(Term::from_field_text(title, "Hello")), 2);
+
+        index
+            .tokenizers()
+            .register("custom", SimpleTokenizer.filter(LowerCaser));
+        index_writer.reindex_field(title).unwrap();
+        assert_eq!(count(Term::from_field_text(title, "hello")), 2);
+        assert_eq!(count(Term::from_field_text(title, "Hello")), 0);
+        assert_eq!(count(Term::from_field_text(title, "title")), 1);
+        // the other fields, and the deletes are untouched.
+        assert_eq!(count(Term::from_field_text(body, "body")), 2);
+        assert_eq!(count(Term::from_field_text(body, "other")), 1);
+        assert_eq!(count(Term::from_field_u64(id, 4u64)), 0);
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        for segment_reader in searcher.segment_readers() {
+            let id_reader = segment_reader.fast_field_reader::<u64>(id).unwrap();
+            let mut ids: Vec<u64> = (0..segment_reader.max_doc())
+                .map(|doc| id_reader.get(doc))
+                .collect();
+            ids.sort();
+            assert!(ids == vec![1, 2] || ids == vec![3, 4]);
+        }
+
+        // new documents are indexed with the new tokenizer.
+        index_writer
+            .add_document(doc!(title => "HELLO", id => 5u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        assert_eq!(count(Term::from_field_text(title, "hello")), 3);
+    }
+
+    #[test]
+    fn test_reindex_field_not_stored() {
+        let mut schema_builder = schema::Schema::builder();
+        let body = schema_builder.add_text_field("body", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(body => "a")).unwrap();
+        index_writer.commit().unwrap();
+        assert!(index_writer.reindex_field(body).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use bit_set::BitSet;
+use core::Index;
+use core::IndexMeta;
+use core::SegmentMeta;
+use core::SegmentReader;
+use core::SerializableSegment;
+use core::ShardDescriptor;
+use core::INDEX_FORMAT_VERSION;
+use directory::Directory;
+use error::TantivyError;
+use fastfield::{DeleteBitSet, FastFieldReader};
+use indexer::merger::IndexMerger;
+use indexer::segment_updater::save_metas;
+use indexer::SegmentSerializer;
+use schema::{Cardinality, Field, FieldType, Schema, Term, Value};
+use serde_json;
+use DocId;
+use Result;
+
+/// Returns the routing key of the value `value` of a field.
+///
+/// The key of a value is the bytes of its term, so that
+/// a sharding layer can easily compute the key of a document.
+fn key_bytes(field: Field, value: &Value) -> Vec<u8> {
+    match *value {
+        Value::Str(ref text) => text.as_bytes().to_vec(),
+        Value::U64(val) => Term::from_field_u64(field, val).value_bytes().to_vec(),
+        Value::I64(val) => Term::from_field_i64(field, val).value_bytes().to_vec(),
+        Value::F64(val) => Term::from_field_f64(field, val).value_bytes().to_vec(),
+        Value::Date(ref date) => Term::from_field_date(field, date).value_bytes().to_vec(),
+        Value::Facet(ref facet) => Term::from_facet(field, facet).value_bytes().to_vec(),
+        Value::Bytes(ref bytes) => bytes.clone(),
+        Value::IpAddr(ip_addr) => ip_addr.octets().to_vec(),
+        Value::JsonObject(ref json_object) => {
+            serde_json::to_vec(json_object).expect("Serializing a JSON object cannot fail.")
+        }
+        Value::PreTokStr(ref tokenized_text) => tokenized_text.text.as_bytes().to_vec(),
+    }
+}
+
+/// Reads the routing key hashes of the documents of a segment.
+enum KeyHasher {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+    Stored,
+}
+
+impl KeyHasher {
+    fn for_reader(reader: &SegmentReader, key_field: Field) -> Result<KeyHasher> {
+        let field_entry = reader.schema().get_field_entry(key_field);
+        let is_single_fast_field = |cardinality_opt: Option<Cardinality>| {
+            cardinality_opt == Some(Cardinality::SingleValue) && reader.has_field(key_field)
+        };
+        match *field_entry.field_type() {
+            FieldType::U64(ref options)
+                if is_single_fast_field(options.get_fastfield_cardinality()) =>
+            {
+                Ok(KeyHasher::U64(reader.fast_field_reader(key_field)?))
+            }
+            FieldType::I64(ref options)
+                if is_single_fast_field(options.get_fastfield_cardinality()) =>
+            {
+                Ok(KeyHasher::I64(reader.fast_field_reader(key_field)?))
+            }
+            FieldType::F64(ref options)
+                if is_single_fast_field(options.get_fastfield_cardinality()) =>
+            {
+                Ok(KeyHasher::F64(reader.fast_field_reader(key_field)?))
+            }
+            _ => Ok(KeyHasher::Stored),
+        }
+    }
+
+    fn hash(&self, reader: &SegmentReader, key_field: Field, doc: DocId) -> Result<u64> {
+        let key = match *self {
+            KeyHasher::U64(ref fast_field_reader) => {
+                key_bytes(key_field, &Value::U64(fast_field_reader.get(doc)))
+            }
+            KeyHasher::I64(ref fast_field_reader) => {
+                key_bytes(key_field, &Value::I64(fast_field_reader.get(doc)))
+            }
+            KeyHasher::F64(ref fast_field_reader) => {
+                key_bytes(key_field, &Value::F64(fast_field_reader.get(doc)))
+            }
+            KeyHasher::Stored => {
+                // the documents without any key are routed as an empty key.
+                let stored_doc = reader.get_store_reader().get(doc)?;
+                stored_doc
+                    .get_first(key_field)
+                    .map(|value| key_bytes(key_field, value))
+                    .unwrap_or_else(Vec::new)
+            }
+        };
+        Ok(ShardDescriptor::hash_key(&key))
+    }
+}
+
+/// Checks that the routing key of the documents
+/// can be read from the field `key_field`.
+fn check_key_field(schema: &Schema, key_field: Field) -> Result<()> {
+    let field_entry = schema.get_field_entry(key_field);
+    let is_single_fast_field = match *field_entry.field_type() {
+        FieldType::U64(ref options) | FieldType::I64(ref options) | FieldType::F64(ref options) => {
+            options.get_fastfield_cardinality() == Some(Cardinality::SingleValue)
+        }
+        _ => false,
+    };
+    if !is_single_fast_field && !field_entry.is_stored() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} cannot be used as a routing key as it is neither stored \
+             nor a single value fast field.",
+            field_entry.name()
+        )));
+    }
+    Ok(())
+}
+
+/// Splits the last commit of `index` in as many new indexes as there
+/// are `target_directories`, by hash of the routing key `key_field`.
+///
+/// See [`Index::split`](../struct.Index.html#method.split).
+pub(crate) fn split_index<D: Directory>(
+    index: &Index,
+    key_field: Field,
+    target_directories: Vec<D>,
+) -> Result<Vec<Index>> {
+    if target_directories.is_empty() {
+        return Err(TantivyError::InvalidArgument(
+            "An index cannot be split in 0 indexes.".to_string(),
+        ));
+    }
+    if target_directories.iter().any(Index::exists) {
+        return Err(TantivyError::IndexAlreadyExists);
+    }
+    let schema = index.schema();
+    check_key_field(&schema, key_field)?;
+    let metas = index.load_metas()?;
+    let readers = metas
+        .segments
+        .iter()
+        .map(|segment_meta| SegmentReader::open(&index.segment(segment_meta.clone())))
+        .collect::<Result<Vec<_>>>()?;
+    let shards = ShardDescriptor::split_hash_range(target_directories.len());
+
+    // the shard of the alive documents of each segment.
+    let mut doc_shards: Vec<Vec<Option<usize>>> = Vec::with_capacity(readers.len());
+    for reader in &readers {
+        let key_hasher = KeyHasher::for_reader(reader, key_field)?;
+        let mut segment_doc_shards = Vec::with_capacity(reader.max_doc() as usize);
+        for doc in 0..reader.max_doc() {
+            if reader.is_deleted(doc) {
+                segment_doc_shards.push(None);
+            } else {
+                let hash = key_hasher.hash(reader, key_field, doc)?;
+                segment_doc_shards.push(shards.iter().position(|shard| shard.contains_hash(hash)));
+            }
+        }
+        doc_shards.push(segment_doc_shards);
+    }
+
+    let mut split_indexes = Vec::with_capacity(shards.len());
+    for (shard_ord, (directory, shard)) in target_directories.into_iter().zip(shards).enumerate() {
+        let mut split_index = Index::create(directory, schema.clone())?;
+        split_index.set_postings_codec(index.postings_codec());
+        split_index.set_store_compression(index.store_compression());
+        split_index.set_term_dictionary_codec(index.term_dictionary_codec());
+        // the documents of the other shards are filtered out
+        // by deleting them before merging the segments.
+        let shard_readers: Vec<SegmentReader> = readers
+            .iter()
+            .zip(&doc_shards)
+            .map(|(reader, segment_doc_shards)| {
+                let mut delete_bitset = BitSet::with_capacity(reader.max_doc() as usize);
+                for (doc, doc_shard) in segment_doc_shards.iter().enumerate() {
+                    if *doc_shard != Some(shard_ord) {
+                        delete_bitset.insert(doc);
+                    }
+                }
+                reader.with_delete_bitset(DeleteBitSet::from(&delete_bitset))
+            })
+            .collect();
+        let mut segments = Vec::new();
+        if shard_readers.iter().any(|reader| reader.num_docs() > 0) {
+            let merger = IndexMerger::for_readers(schema.clone(), shard_readers);
+            let mut segment = split_index.new_segment();
+            let num_docs = merger.write(SegmentSerializer::for_segment(&mut segment)?)?;
+            segments.push(
+                SegmentMeta::new_with_settings(segment.id(), num_docs, segment.meta())
+                    .with_num_bytes(segment.num_bytes_written()),
+            );
+        }
+        let split_metas = IndexMeta {
+            segments,
+            schema: schema.clone(),
+            opstamp: metas.opstamp,
+            payload: None,
+            generation: 1u64,
+            schema_version: metas.schema_version,
+            shard: Some(shard),
+            index_format_version: INDEX_FORMAT_VERSION,
+        };
+        save_metas(&split_metas, split_index.directory_mut())?;
+        split_index.load_searchers()?;
+        split_indexes.push(split_index);
+    }
+    Ok(split_indexes)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::Count;
+    use core::{Index, Searcher, ShardDescriptor};
+    use directory::RAMDirectory;
+    use query::{AllQuery, TermQuery};
+    use schema::{self, IndexRecordOption, Term};
+
+    #[test]
+    fn test_split_index() {
+        let mut schema_builder = schema::Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", schema::STRING | schema::STORED);
+        let body = schema_builder.add_text_field("body", schema::TEXT);
+        let id = schema_builder.add_u64_field("id", schema::INT_INDEXED | schema::FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let tenants = ["acme", "globex", "initech", "umbrella", "hooli", "wonka"];
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for i in 0..60u64 {
+                let tenant_id = tenants[i as usize % tenants.len()];
+                index_writer
+                    .add_document(doc!(tenant => tenant_id, body => "hello", id => i))
+                    .unwrap();
+                if i == 30 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(id, 3u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+
+        let split_indexes = index
+            .split(tenant, vec![RAMDirectory::create(), RAMDirectory::create()])
+            .unwrap();
+        assert_eq!(split_indexes.len(), 2);
+        let count = |searcher: &Searcher, term: Term| {
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        let mut num_docs = 0;
+        for split_index in &split_indexes {
+            let shard = split_index.shard_descriptor().unwrap().unwrap();
+            let searcher = split_index.searcher();
+            assert!(searcher.segment_readers().len() <= 1);
+            num_docs += searcher.search(&AllQuery, &Count).unwrap();
+            assert_eq!(
+                count(&searcher, Term::from_field_text(body, "hello")),
+                searcher.num_docs() as usize
+            );
+            // all of the documents of a tenant are in the same index.
+            for tenant_id in &tenants {
+                let tenant_count = count(&searcher, Term::from_field_text(tenant, tenant_id));
+                let hash = ShardDescriptor::hash_key(tenant_id.as_bytes());
+                if shard.contains_hash(hash) {
+                    let expected = if *tenant_id == "umbrella" { 9 } else { 10 };
+                    assert_eq!(tenant_count, expected);
+                } else {
+                    assert_eq!(tenant_count, 0);
+                }
+            }
+            assert_eq!(count(&searcher, Term::from_field_u64(id, 3u64)), 0);
+        }
+        assert_eq!(num_docs, 59);
+    }
+
+    #[test]
+    fn test_split_index_by_fast_field() {
+        let mut schema_builder = schema::Schema::builder();
+        let id = schema_builder.add_u64_field("id", schema::INT_INDEXED | schema::FAST);
+        let body = schema_builder.add_text_field("body", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for i in 0..20u64 {
+                index_writer.add_document(doc!(id => i)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let directories = vec![
+            RAMDirectory::create(),
+            RAMDirectory::create(),
+            RAMDirectory::create(),
+        ];
+        let split_indexes = index.split(id, directories.clone()).unwrap();
+        for i in 0..20u64 {
+            let key = Term::from_field_u64(id, i);
+            let hash = ShardDescriptor::hash_key(key.value_bytes());
+            for split_index in &split_indexes {
+                let shard = split_index.shard_descriptor().unwrap().unwrap();
+                let query = TermQuery::new(key.clone(), IndexRecordOption::Basic);
+                let expected = if shard.contains_hash(hash) { 1 } else { 0 };
+                assert_eq!(
+                    split_index.searcher().search(&query, &Count).unwrap(),
+                    expected
+                );
+            }
+        }
+        // the target directories must not contain any index.
+        assert!(index.split(id, directories).is_err());
+        // the key field must be stored or a fast field.
+        assert!(index.split(body, vec![RAMDirectory::create()]).is_err());
+    }
+}
@@ -1,34 +1,50 @@
-use super::operation::AddOperation;
+use super::operation::{AddBatch, AddOperation};
 use super::segment_updater::SegmentUpdater;
 use super::PreparedCommit;
 use bit_set::BitSet;
 use core::Index;
+use core::Searcher;
 use core::Segment;
 use core::SegmentComponent;
 use core::SegmentId;
 use core::SegmentMeta;
 use core::SegmentReader;
+use core::INDEX_FORMAT_VERSION;
 use crossbeam::channel;
+use directory::SeekableWrite;
 use docset::DocSet;
 use error::TantivyError;
 use fastfield::write_delete_bitset;
+use fastfield::DeleteBitSet;
+use fastfield::{FastFieldUpdates, FastValue};
 use futures::sync::oneshot::Receiver;
 use indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use indexer::doc_opstamp_mapping::DocToOpstampMapping;
-use indexer::operation::DeleteOperation;
+use indexer::indexing_observer::DOCUMENT_BATCH_SIZE;
+use indexer::operation::{DeleteOperation, DeleteTarget};
 use indexer::stamper::Stamper;
 use indexer::DirectoryLock;
 use indexer::MergePolicy;
 use indexer::SegmentEntry;
+use indexer::SegmentInfo;
 use indexer::SegmentWriter;
+use indexer::{IndexingObserver, IndexingStats};
 use postings::compute_table_size;
+use query::Query;
+use schema::Cardinality;
 use schema::Document;
+use schema::Field;
 use schema::IndexRecordOption;
 use schema::Term;
+use std::collections::HashMap;
 use std::mem;
 use std::mem::swap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use DocId;
 use Result;
 
 // Size of the margin for the heap. A segment is closed when the remaining memory
@@ -43,8 +59,8 @@ pub const HEAP_SIZE_MAX: usize = u32::max_value() as usize - MARGIN_IN_BYTES;
 // reaches `PIPELINE_MAX_SIZE_IN_DOCS`
 const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
 
-type DocumentSender = channel::Sender<AddOperation>;
-type DocumentReceiver = channel::Receiver<AddOperation>;
+type DocumentSender = channel::Sender<AddBatch>;
+type DocumentReceiver = channel::Receiver<AddBatch>;
 
 /// Split the thread memory budget into
 /// - the heap size
@@ -81,6 +97,9 @@ pub struct IndexWriter {
     heap_size_in_bytes_per_thread: usize,
 
     workers_join_handle: Vec<JoinHandle<Result<()>>>,
+    // memory used by the segment currently being built
+    // by each worker.
+    workers_mem_usage: Vec<Arc<AtomicUsize>>,
 
     document_receiver: DocumentReceiver,
     document_sender: DocumentSender,
@@ -97,6 +116,27 @@ pub struct IndexWriter {
 
     stamper: Stamper,
     committed_opstamp: u64,
+
+    // set when an indexing worker panics.
+    poisoned: Arc<AtomicBool>,
+
+    // segment readers opened by `nrt_searcher`, keyed by the id, the
+    // delete opstamp and the fast field updates opstamp of their segment.
+    nrt_segment_readers: HashMap<NrtSegmentKey, SegmentReader>,
+}
+
+type NrtSegmentKey = (SegmentId, Option<u64>, Option<u64>);
+
+/// Poisons the `IndexWriter` if the indexing worker
+/// holding it panics.
+struct PoisonGuard(Arc<AtomicBool>);
+
+impl Drop for PoisonGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.store(true, Ordering::Release);
+        }
+    }
 }
 
 /// Open a new index writer. Attempts to acquire a lockfile.
@@ -154,6 +194,7 @@ pub fn open_index_writer(
         segment_updater,
 
         workers_join_handle: vec![],
+        workers_mem_usage: vec![],
         num_threads,
 
         delete_queue,
@@ -164,6 +205,10 @@ pub fn open_index_writer(
         generation: 0,
 
         worker_id: 0,
+
+        poisoned: Arc::new(AtomicBool::new(false)),
+
+        nrt_segment_readers: HashMap::new(),
     };
     index_writer.start_workers()?;
     Ok(index_writer)
@@ -171,6 +216,7 @@ pub fn open_index_writer(
 
 pub fn compute_deleted_bitset(
     delete_bitset: &mut BitSet,
+    fast_field_updates: &mut FastFieldUpdates,
     segment_reader: &SegmentReader,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &DocToOpstampMapping,
@@ -178,37 +224,191 @@ pub fn compute_deleted_bitset(
 ) -> Result<bool> {
     let mut might_have_changed = false;
 
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::while_let_loop))]
     loop {
-        if let Some(delete_op) = delete_cursor.get() {
-            if delete_op.opstamp > target_opstamp {
-                break;
-            } else {
-                // A delete operation should only affect
-                // document that were inserted after it.
-                //
-                // Limit doc helps identify the first document
-                // that may be affected by the delete operation.
-                let limit_doc = doc_opstamps.compute_doc_limit(delete_op.opstamp);
-                let inverted_index = segment_reader.inverted_index(delete_op.term.field());
+        let (opstamp, target) = match delete_cursor.get() {
+            Some(delete_op) if delete_op.opstamp <= target_opstamp => {
+                (delete_op.opstamp, delete_op.target.clone())
+            }
+            _ => break,
+        };
+        match target {
+            DeleteTarget::Term(_) => {}
+            DeleteTarget::Query(weight) => {
+                delete_cursor.advance();
+                let limit_doc = doc_opstamps.compute_doc_limit(opstamp);
+                let mut scorer = weight.scorer(segment_reader)?;
+                might_have_changed |= delete_docs(&mut scorer, limit_doc, delete_bitset);
+                continue;
+            }
+            DeleteTarget::FastFieldUpdate { term, field, value } => {
+                delete_cursor.advance();
+                let limit_doc = doc_opstamps.compute_doc_limit(opstamp);
+                let inverted_index = segment_reader.inverted_index(term.field());
                 if let Some(mut docset) =
-                    inverted_index.read_postings(&delete_op.term, IndexRecordOption::Basic)
+                    inverted_index.read_postings(&term, IndexRecordOption::Basic)
                 {
                     while docset.advance() {
-                        let deleted_doc = docset.doc();
-                        if deleted_doc < limit_doc {
-                            delete_bitset.insert(deleted_doc as usize);
+                        let doc = docset.doc();
+                        if doc < limit_doc {
+                            fast_field_updates.set(field, doc, value);
                             might_have_changed = true;
                         }
                     }
                 }
+                continue;
+            }
+        }
+        // Consecutive operations sharing the same opstamp were
+        // recorded by a single call to `delete_terms`.
+        let terms = pop_delete_terms(delete_cursor, opstamp);
+
+        // A delete operation should only affect
+        // document that were inserted after it.
+        //
+        // Limit doc helps identify the first document
+        // that may be affected by the delete operation.
+        let limit_doc = doc_opstamps.compute_doc_limit(opstamp);
+        if terms.len() == 1 {
+            let inverted_index = segment_reader.inverted_index(terms[0].field());
+            if let Some(mut docset) =
+                inverted_index.read_postings(&terms[0], IndexRecordOption::Basic)
+            {
+                might_have_changed |= delete_docs(&mut docset, limit_doc, delete_bitset);
             }
         } else {
+            might_have_changed |=
+                delete_sorted_terms(&terms, segment_reader, limit_doc, delete_bitset);
+        }
+    }
+    Ok(might_have_changed)
+}
+
+/// Pops the term delete operations with the given opstamp
+/// at the head of the delete cursor, and returns their terms.
+fn pop_delete_terms(delete_cursor: &mut DeleteCursor, opstamp: u64) -> Vec<Term> {
+    let mut terms = Vec::new();
+    while let Some(delete_op) = delete_cursor.get() {
+        if delete_op.opstamp != opstamp {
             break;
         }
+        match delete_op.target {
+            DeleteTarget::Term(ref term) => terms.push(term.clone()),
+            DeleteTarget::Query(_) | DeleteTarget::FastFieldUpdate { .. } => break,
+        }
         delete_cursor.advance();
     }
-    Ok(might_have_changed)
+    terms
+}
+
+fn delete_docs(docset: &mut DocSet, limit_doc: DocId, delete_bitset: &mut BitSet) -> bool {
+    let mut might_have_changed = false;
+    while docset.advance() {
+        let deleted_doc = docset.doc();
+        if deleted_doc < limit_doc {
+            delete_bitset.insert(deleted_doc as usize);
+            might_have_changed = true;
+        }
+    }
+    might_have_changed
+}
+
+/// Deletes the documents containing any of the `terms`.
+///
+/// `terms` are sorted, so that the term dictionary of each field
+/// is streamed once instead of being searched for every term.
+fn delete_sorted_terms(
+    terms: &[Term],
+    segment_reader: &SegmentReader,
+    limit_doc: DocId,
+    delete_bitset: &mut BitSet,
+) -> bool {
+    let mut might_have_changed = false;
+    let mut start = 0;
+    while start < terms.len() {
+        let field = terms[start].field();
+        let stop = terms[start..]
+            .iter()
+            .position(|term| term.field() != field)
+            .map(|len| start + len)
+            .unwrap_or_else(|| terms.len());
+        let field_terms = &terms[start..stop];
+        start = stop;
+
+        let inverted_index = segment_reader.inverted_index(field);
+        let mut term_stream = inverted_index
+            .terms()
+            .range()
+            .ge(field_terms[0].value_bytes())
+            .le(field_terms[field_terms.len() - 1].value_bytes())
+            .into_stream();
+        let mut field_terms_it = field_terms.iter().peekable();
+        while term_stream.advance() {
+            let key = term_stream.key();
+            while field_terms_it
+                .peek()
+                .map(|term| term.value_bytes() < key)
+                .unwrap_or(false)
+            {
+                field_terms_it.next();
+            }
+            match field_terms_it.peek() {
+                Some(term) if term.value_bytes() == key => {}
+                Some(_) => continue,
+                None => break,
+            }
+            let mut docset = inverted_index
+                .read_postings_from_terminfo(term_stream.value(), IndexRecordOption::Basic);
+            might_have_changed |= delete_docs(&mut docset, limit_doc, delete_bitset);
+        }
+    }
+    might_have_changed
+}
+
+/// Computes the deleted documents and the fast field updates of a
+/// segment, applying the delete operations of the delete cursor
+/// of `segment_entry` up to the target opstamp.
+///
+/// Nothing is written: the delete cursor is advanced, and the
+/// deletes and updates of the segment are returned.
+fn apply_deletes(
+    segment_reader: &SegmentReader,
+    segment_entry: &mut SegmentEntry,
+    target_opstamp: u64,
+) -> Result<(BitSet, FastFieldUpdates)> {
+    let max_doc = segment_reader.max_doc();
+
+    let mut delete_bitset: BitSet = match segment_entry.delete_bitset() {
+        Some(previous_delete_bitset) => (*previous_delete_bitset).clone(),
+        None => BitSet::with_capacity(max_doc as usize),
+    };
+
+    // The updates file contains the updates of the
+    // previous commits, including the ones of `segment_entry`.
+    let mut fast_field_updates = if segment_entry.meta().has_fast_field_updates() {
+        segment_reader.fast_field_updates().clone()
+    } else {
+        segment_entry
+            .fast_field_updates()
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    compute_deleted_bitset(
+        &mut delete_bitset,
+        &mut fast_field_updates,
+        segment_reader,
+        segment_entry.delete_cursor(),
+        &DocToOpstampMapping::None,
+        target_opstamp,
+    )?;
+
+    // TODO optimize
+    for doc in 0u32..max_doc {
+        if segment_reader.is_deleted(doc) {
+            delete_bitset.insert(doc as usize);
+        }
+    }
+    Ok((delete_bitset, fast_field_updates))
 }
 
 /// Advance delete for the given segment up
@@ -219,44 +419,45 @@ pub fn advance_deletes(
     target_opstamp: u64,
 ) -> Result<()> {
     {
-        if segment_entry.meta().delete_opstamp() == Some(target_opstamp) {
+        if segment_entry.meta().delete_opstamp() == Some(target_opstamp)
+            || segment_entry.meta().fast_field_updates_opstamp() == Some(target_opstamp)
+        {
             // We are already up-to-date here.
             return Ok(());
         }
 
         let segment_reader = SegmentReader::open(&segment)?;
-        let max_doc = segment_reader.max_doc();
-
-        let mut delete_bitset: BitSet = match segment_entry.delete_bitset() {
-            Some(previous_delete_bitset) => (*previous_delete_bitset).clone(),
-            None => BitSet::with_capacity(max_doc as usize),
-        };
-
-        let delete_cursor = segment_entry.delete_cursor();
-
-        compute_deleted_bitset(
-            &mut delete_bitset,
-            &segment_reader,
-            delete_cursor,
-            &DocToOpstampMapping::None,
-            target_opstamp,
-        )?;
-
-        // TODO optimize
-        for doc in 0u32..max_doc {
-            if segment_reader.is_deleted(doc) {
-                delete_bitset.insert(doc as usize);
-            }
-        }
+        let (delete_bitset, fast_field_updates) =
+            apply_deletes(&segment_reader, segment_entry, target_opstamp)?;
 
         let num_deleted_docs = delete_bitset.len();
+        let mut delete_num_bytes = None;
         if num_deleted_docs > 0 {
             segment = segment.with_delete_meta(num_deleted_docs as u32, target_opstamp);
+            let num_bytes_before = segment.num_bytes_written();
             let mut delete_file = segment.open_write(SegmentComponent::DELETE)?;
             write_delete_bitset(&delete_bitset, &mut delete_file)?;
+            delete_file.terminate()?;
+            delete_num_bytes = Some(segment.num_bytes_written() - num_bytes_before);
+        }
+        let mut updates_num_bytes = None;
+        if !fast_field_updates.is_empty() {
+            segment = segment.with_fast_field_updates(target_opstamp);
+            let num_bytes_before = segment.num_bytes_written();
+            let mut updates_file = segment.open_write(SegmentComponent::FASTFIELDUPDATES)?;
+            fast_field_updates.write(&mut updates_file)?;
+            updates_file.terminate()?;
+            updates_num_bytes = Some(segment.num_bytes_written() - num_bytes_before);
+        }
+        let mut segment_meta = segment.meta().clone();
+        if let Some(num_bytes) = delete_num_bytes {
+            segment_meta = segment_meta.with_delete_num_bytes(num_bytes);
         }
+        if let Some(num_bytes) = updates_num_bytes {
+            segment_meta = segment_meta.with_fast_field_updates_num_bytes(num_bytes);
+        }
+        segment_entry.set_meta(segment_meta);
     }
-    segment_entry.set_meta((*segment.meta()).clone());
     Ok(())
 }
 
@@ -264,18 +465,32 @@ fn index_documents(
     memory_budget: usize,
     segment: &Segment,
     generation: usize,
-    document_iterator: &mut Iterator<Item = AddOperation>,
+    document_iterator: &mut Iterator<Item = AddBatch>,
     segment_updater: &mut SegmentUpdater,
     mut delete_cursor: DeleteCursor,
+    worker_mem_usage: &AtomicUsize,
 ) -> Result<bool> {
     let schema = segment.schema();
     let segment_id = segment.id();
     let table_size = initial_table_size(memory_budget);
     let mut segment_writer = SegmentWriter::for_segment(table_size, segment.clone(), &schema)?;
-    for doc in document_iterator {
-        segment_writer.add_document(doc, &schema)?;
+    let indexing_monitor = segment_updater.indexing_monitor();
+    let mut num_docs_in_batch = 0u32;
+    for add_batch in document_iterator {
+        // the memory usage is only checked in between two batches,
+        // so that the documents of a batch all end up in the same segment.
+        for doc in add_batch {
+            segment_writer.add_document(doc, &schema)?;
+
+            num_docs_in_batch += 1;
+            if num_docs_in_batch == DOCUMENT_BATCH_SIZE {
+                indexing_monitor.documents_indexed(segment_id, num_docs_in_batch);
+                num_docs_in_batch = 0;
+            }
+        }
 
         let mem_usage = segment_writer.mem_usage();
+        worker_mem_usage.store(mem_usage, Ordering::Relaxed);
 
         if mem_usage >= memory_budget - MARGIN_IN_BYTES {
             info!(
@@ -286,6 +501,10 @@ fn index_documents(
         }
     }
 
+    if num_docs_in_batch > 0 {
+        indexing_monitor.documents_indexed(segment_id, num_docs_in_batch);
+    }
+
     if !segment_updater.is_alive() {
         return Ok(false);
     }
@@ -296,36 +515,54 @@ fn index_documents(
     // the worker thread.
     assert!(num_docs > 0);
 
+    indexing_monitor.flush_started(segment_id, num_docs);
+    fail_point!("IndexWriter::index_documents#flush");
     let doc_opstamps: Vec<u64> = segment_writer.finalize()?;
+    worker_mem_usage.store(0, Ordering::Relaxed);
 
-    let segment_meta = SegmentMeta::new(segment_id, num_docs);
+    let segment_meta = SegmentMeta::new_with_settings(segment_id, num_docs, segment.meta())
+        .with_num_bytes(segment.num_bytes_written());
 
     let last_docstamp: u64 = *(doc_opstamps.last().unwrap());
 
     let segment_entry: SegmentEntry = if delete_cursor.get().is_some() {
         let doc_to_opstamps = DocToOpstampMapping::from(doc_opstamps);
-        let segment_reader = SegmentReader::open(segment)?;
+        // the meta of `segment` does not know about the documents
+        // that were just written, so we open a fresh segment carrying the final meta.
+        let segment_with_max_doc = segment.index().segment(segment_meta.clone());
+        let segment_reader = SegmentReader::open(&segment_with_max_doc)?;
         let mut deleted_bitset = BitSet::with_capacity(num_docs as usize);
+        let mut fast_field_updates = FastFieldUpdates::default();
         let may_have_deletes = compute_deleted_bitset(
             &mut deleted_bitset,
+            &mut fast_field_updates,
             &segment_reader,
             &mut delete_cursor,
             &doc_to_opstamps,
             last_docstamp,
         )?;
-        SegmentEntry::new(segment_meta, delete_cursor, {
+        let mut segment_entry = SegmentEntry::new(segment_meta, delete_cursor, {
             if may_have_deletes {
                 Some(deleted_bitset)
             } else {
                 None
             }
-        })
+        });
+        if !fast_field_updates.is_empty() {
+            segment_entry.set_fast_field_updates(fast_field_updates);
+        }
+        segment_entry
     } else {
         // if there are no delete operation in the queue, no need
         // to even open the segment.
         SegmentEntry::new(segment_meta, delete_cursor, None)
     };
-    Ok(segment_updater.add_segment(generation, segment_entry))
+    let added = segment_updater.add_segment(generation, segment_entry);
+    // the segments of the generations discarded by a rollback are not flushed.
+    if added {
+        indexing_monitor.flush_ended(segment_id, num_docs);
+    }
+    Ok(added)
 }
 
 impl IndexWriter {
@@ -333,18 +570,19 @@ impl IndexWriter {
     pub fn wait_merging_threads(mut self) -> Result<()> {
         // this will stop the indexing thread,
         // dropping the last reference to the segment_updater.
-        drop(self.document_sender);
+        self.recreate_document_channel();
 
         let former_workers_handles = mem::replace(&mut self.workers_join_handle, vec![]);
         for join_handle in former_workers_handles {
             join_handle
                 .join()
-                .expect("Indexing Worker thread panicked")
+                .map_err(|_| {
+                    TantivyError::WriterPoisoned("An indexing worker thread panicked.".into())
+                })?
                 .map_err(|_| {
                     TantivyError::ErrorInThread("Error in indexing worker thread.".into())
                 })?;
         }
-        drop(self.workers_join_handle);
 
         let result = self
             .segment_updater
@@ -387,12 +625,16 @@ impl IndexWriter {
         let mut delete_cursor = self.delete_queue.cursor();
 
         let mem_budget = self.heap_size_in_bytes_per_thread;
+        let worker_mem_usage = Arc::new(AtomicUsize::new(0));
+        let worker_mem_usage_clone = Arc::clone(&worker_mem_usage);
+        let poisoned = Arc::clone(&self.poisoned);
         let join_handle: JoinHandle<Result<()>> = thread::Builder::new()
             .name(format!(
                 "thrd-tantivy-index{}-gen{}",
                 self.worker_id, generation
             ))
             .spawn(move || {
+                let _poison_guard = PoisonGuard(poisoned);
                 loop {
                     let mut document_iterator =
                         document_receiver_clone.clone().into_iter().peekable();
@@ -404,7 +646,9 @@ impl IndexWriter {
                     // this is a valid guarantee as the
                     // peeked document now belongs to
                     // our local iterator.
-                    if let Some(operation) = document_iterator.peek() {
+                    if let Some(operation) =
+                        document_iterator.peek().and_then(|batch| batch.first())
+                    {
                         delete_cursor.skip_to(operation.opstamp);
                     } else {
                         // No more documents.
@@ -420,14 +664,47 @@ impl IndexWriter {
                         &mut document_iterator,
                         &mut segment_updater,
                         delete_cursor.clone(),
+                        &worker_mem_usage_clone,
                     )?;
                 }
             })?;
         self.worker_id += 1;
         self.workers_join_handle.push(join_handle);
+        self.workers_mem_usage.push(worker_mem_usage);
         Ok(())
     }
 
+    /// Rebuilds the inverted index, fast field and field norms
+    /// of `field` in the committed segments, from the stored values of the field.
+    ///
+    /// This is useful when the tokenizer associated to a text field
+    /// changed: the new tokenizer can be registered under the same
+    /// name in the index `TokenizerManager`, and only that field has to be
+    /// reindexed. The other fields are copied as is.
+    ///
+    /// The field needs to be stored. The documents added since the last
+    /// commit are not reindexed, and the segments currently being merged
+    /// cannot be reindexed either, in which case an error is returned.
+    ///
+    /// Each segment is replaced as soon as it has been reindexed,
+    /// and the change is visible after the next call to `load_searchers`.
+    pub fn reindex_field(&mut self, field: Field) -> Result<()> {
+        self.check_poisoned()?;
+        self.segment_updater.reindex_field(field)
+    }
+
+    /// Aborts the merges currently running.
+    ///
+    /// The files partially written by these merges are deleted,
+    /// and their segments are left untouched. They may be merged
+    /// again later, as decided by the merge policy.
+    ///
+    /// Merges are also aborted when the `IndexWriter` is dropped or
+    /// rolled back.
+    pub fn cancel_merges(&self) {
+        self.segment_updater.cancel_merges();
+    }
+
     /// Accessor to the merge policy.
     pub fn get_merge_policy(&self) -> Box<MergePolicy> {
         self.segment_updater.get_merge_policy()
@@ -438,6 +715,176 @@ impl IndexWriter {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Lists the segments handled by the `IndexWriter`.
+    ///
+    /// In addition to the searchable segments, this includes
+    /// the segments that were flushed since the last commit.
+    /// For each segment, the info also tells whether it is
+    /// currently being merged.
+    ///
+    /// Segments that are being written by indexing threads
+    /// or as the result of a merge are not listed.
+    pub fn segment_infos(&self) -> Vec<SegmentInfo> {
+        self.segment_updater.segment_infos()
+    }
+
+    /// Opens a near-real-time searcher, over all of the documents
+    /// added so far, without committing them.
+    ///
+    /// The pending documents are flushed in new segments, and the
+    /// pending deletes and fast field updates are applied in memory.
+    /// Nothing is made durable: the uncommitted segments are still
+    /// discarded on rollback or on a crash.
+    ///
+    /// This is much cheaper than a commit, but every call creates
+    /// small segments, which then need to be merged. The segment
+    /// readers are kept from one call to the next: only the segments
+    /// that are new, or whose deletes or fast field updates were
+    /// written since the previous call, are opened again.
+    ///
+    /// All of the operations with an opstamp strictly lower than
+    /// the opstamp of the searcher are visible to it.
+    pub fn nrt_searcher(&mut self) -> Result<Searcher> {
+        self.flush_workers()?;
+        let opstamp = self.stamper.stamp();
+        let index_meta = self.index.load_metas()?;
+        let mut segment_metas = Vec::new();
+        let mut segment_readers = Vec::new();
+        let mut nrt_segment_readers = HashMap::new();
+        for mut segment_entry in self.segment_updater.segment_entries()? {
+            let key = {
+                let segment_meta = segment_entry.meta();
+                (
+                    segment_meta.id(),
+                    segment_meta.delete_opstamp(),
+                    segment_meta.fast_field_updates_opstamp(),
+                )
+            };
+            let segment_reader = match self.nrt_segment_readers.remove(&key) {
+                Some(segment_reader) => segment_reader,
+                None => {
+                    let segment = self.index.segment(segment_entry.meta().clone());
+                    SegmentReader::open(&segment)?
+                }
+            };
+            nrt_segment_readers.insert(key, segment_reader.clone());
+            // the segment entry is a copy: its delete cursor
+            // is not advanced in the segment updater.
+            let (delete_bitset, fast_field_updates) =
+                apply_deletes(&segment_reader, &mut segment_entry, opstamp)?;
+            let mut segment_reader = segment_reader.with_fast_field_updates(fast_field_updates);
+            if !delete_bitset.is_empty() {
+                segment_reader =
+                    segment_reader.with_delete_bitset(DeleteBitSet::from(&delete_bitset));
+            }
+            segment_metas.push(segment_entry.meta().clone());
+            segment_readers.push(segment_reader);
+        }
+        // the readers of the segments that were merged or
+        // rewritten since the previous call are dropped.
+        self.nrt_segment_readers = nrt_segment_readers;
+        Ok(Searcher::new(
+            self.index.schema(),
+            self.index.clone(),
+            index_meta.generation,
+            opstamp,
+            segment_metas,
+            segment_readers,
+        ))
+    }
+
+    /// Registers an observer notified of the progress of indexing:
+    /// batches of indexed documents, segment flushes and merges.
+    ///
+    /// Passing `None` unregisters the current observer.
+    pub fn set_indexing_observer(&self, observer: Option<Arc<IndexingObserver>>) {
+        self.segment_updater
+            .indexing_monitor()
+            .set_observer(observer);
+    }
+
+    /// Returns the number of documents indexed, segments flushed
+    /// and merges completed since the writer was opened.
+    pub fn indexing_stats(&self) -> IndexingStats {
+        self.segment_updater.indexing_monitor().stats()
+    }
+
+    /// Returns the limit on the write throughput of merges,
+    /// in bytes per second.
+    pub fn get_merge_io_limit(&self) -> Option<u64> {
+        self.segment_updater.get_merge_io_limit()
+    }
+
+    /// Limits the write throughput of merges, in bytes per second.
+    /// `None` means no limit.
+    ///
+    /// The limit is shared by all of the merges running concurrently,
+    /// and also applies to the merges in progress.
+    /// Throttling merges prevents them from starving
+    /// the queries on the same disk of IO.
+    pub fn set_merge_io_limit(&self, bytes_per_sec: Option<u64>) {
+        self.segment_updater.set_merge_io_limit(bytes_per_sec);
+    }
+
+    /// Returns the disk space limit, in bytes.
+    pub fn get_disk_space_limit(&self) -> Option<u64> {
+        self.segment_updater.get_disk_space_limit()
+    }
+
+    /// Sets a limit on the number of bytes the index
+    /// may take on disk. `None` means no limit.
+    ///
+    /// Before accepting a new document or starting a merge, the
+    /// `IndexWriter` estimates the number of bytes that will be written.
+    /// If the index would exceed the limit, the operation fails
+    /// early with a `TantivyError::DiskSpaceLimitExceeded` error,
+    /// rather than filling the disk in the middle of a write.
+    ///
+    /// A rejected document is not added, and can be added again once
+    /// some space is freed. The documents that were already accepted are
+    /// always flushed. A failed merge is simply not started.
+    ///
+    /// The limit is enforced on estimations, so it should
+    /// be set with some margin.
+    pub fn set_disk_space_limit(&self, disk_space_limit: Option<u64>) {
+        self.segment_updater.set_disk_space_limit(disk_space_limit);
+    }
+
+    /// Returns the number of bytes taken on disk by the segments
+    /// of the index, including the uncommitted ones.
+    pub fn num_bytes_on_disk(&self) -> Result<u64> {
+        self.segment_updater.num_bytes_on_disk()
+    }
+
+    /// Estimates the number of bytes that will be written when flushing
+    /// the segments currently being built by the indexing threads.
+    ///
+    /// The estimation is based on the memory used by the indexing threads.
+    pub fn estimate_flush_num_bytes(&self) -> u64 {
+        self.workers_mem_usage
+            .iter()
+            .map(|mem_usage| mem_usage.load(Ordering::Relaxed) as u64)
+            .sum()
+    }
+
+    /// Returns an error if flushing the documents being indexed
+    /// would exceed the disk space limit.
+    pub(crate) fn check_disk_space_limit(&self) -> Result<()> {
+        if self.get_disk_space_limit().is_none() {
+            return Ok(());
+        }
+        // The in-memory buffers are a reasonable upper bound
+        // of what is remaining to be written.
+        self.segment_updater
+            .check_disk_space_limit(self.estimate_flush_num_bytes())
+    }
+
+    /// Estimates the number of bytes of the segment
+    /// resulting from the merge of the given segments.
+    pub fn estimate_merge_num_bytes(&self, segment_ids: &[SegmentId]) -> Result<u64> {
+        self.segment_updater.estimate_merge_num_bytes(segment_ids)
+    }
+
     fn start_workers(&mut self) -> Result<()> {
         for _ in 0..self.num_threads {
             self.add_indexing_worker()?;
@@ -455,9 +902,33 @@ impl IndexWriter {
     ///
     /// `segment_ids` is required to be non-empty.
     pub fn merge(&mut self, segment_ids: &[SegmentId]) -> Result<Receiver<SegmentMeta>> {
+        self.check_poisoned()?;
         self.segment_updater.start_merge(segment_ids)
     }
 
+    /// Rewrites the committed segments written in a previous version
+    /// of the index format, by merging them into a single segment
+    /// in the current format, `INDEX_FORMAT_VERSION`.
+    ///
+    /// As for `.merge(...)`, the new segment replaces the former ones
+    /// in the `meta.json` file once the merge is over.
+    ///
+    /// Returns `None` if all of the segments are in the current format,
+    /// or are already being merged.
+    pub fn upgrade(&mut self) -> Result<Option<Receiver<SegmentMeta>>> {
+        let segment_ids: Vec<SegmentId> = self
+            .segment_infos()
+            .into_iter()
+            .filter(|segment_info| segment_info.is_committed() && !segment_info.is_merging())
+            .filter(|segment_info| segment_info.meta().format_version() < INDEX_FORMAT_VERSION)
+            .map(|segment_info| segment_info.segment_id())
+            .collect();
+        if segment_ids.is_empty() {
+            return Ok(None);
+        }
+        self.merge(&segment_ids[..]).map(Some)
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -474,6 +945,25 @@ impl IndexWriter {
         document_receiver
     }
 
+    /// Returns true if an indexing worker thread panicked.
+    ///
+    /// A poisoned `IndexWriter` refuses to commit, as the
+    /// documents being indexed by the worker are lost.
+    /// Calling [`.rollback()`](#method.rollback) recovers a clean
+    /// `IndexWriter`.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn check_poisoned(&self) -> Result<()> {
+        if self.is_poisoned() {
+            return Err(TantivyError::WriterPoisoned(
+                "An indexing worker thread panicked. Rollback to recover the writer.".into(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Rollback to the last commit
     ///
     /// This cancels all of the update that
@@ -481,6 +971,13 @@ impl IndexWriter {
     /// After calling rollback, the index is in the same
     /// state as it was after the last commit.
     ///
+    /// This is also the way to recover from a poisoned
+    /// `IndexWriter`: the faulty workers are replaced,
+    /// and the writer accepts documents again.
+    ///
+    /// The disk space limit, the merge IO limit and the
+    /// indexing observer of the writer are kept.
+    ///
     /// The opstamp at the last commit is returned.
     pub fn rollback(&mut self) -> Result<()> {
         info!("Rolling back to opstamp {}", self.committed_opstamp);
@@ -503,6 +1000,12 @@ impl IndexWriter {
             self.heap_size_in_bytes_per_thread,
             directory_lock,
         )?;
+        new_index_writer.set_disk_space_limit(self.get_disk_space_limit());
+        new_index_writer.set_merge_io_limit(self.get_merge_io_limit());
+        new_index_writer
+            .segment_updater
+            .indexing_monitor()
+            .set_observer(self.segment_updater.indexing_monitor().observer());
 
         // the current `self` is dropped right away because of this call.
         //
@@ -554,6 +1057,20 @@ impl IndexWriter {
         // committed segments.
         info!("Preparing commit");
 
+        self.flush_workers()?;
+
+        let commit_opstamp = self.stamper.stamp();
+        let prepared_commit = PreparedCommit::new(self, commit_opstamp);
+        info!("Prepared commit {}", commit_opstamp);
+        Ok(prepared_commit)
+    }
+
+    /// Makes the indexing workers flush the pending documents
+    /// in new segments, regardless of the size of their current
+    /// segment, and replaces them with new workers.
+    fn flush_workers(&mut self) -> Result<()> {
+        self.check_poisoned()?;
+
         // this will drop the current document channel
         // and recreate a new one channels.
         self.recreate_document_channel();
@@ -563,21 +1080,21 @@ impl IndexWriter {
             &mut former_workers_join_handle,
             &mut self.workers_join_handle,
         );
+        self.workers_mem_usage.clear();
 
         for worker_handle in former_workers_join_handle {
-            let indexing_worker_result = worker_handle
-                .join()
-                .map_err(|e| TantivyError::ErrorInThread(format!("{:?}", e)))?;
+            let indexing_worker_result = worker_handle.join().map_err(|_| {
+                // The documents of the worker are lost with it.
+                // Committing would silently drop them.
+                self.poisoned.store(true, Ordering::Release);
+                TantivyError::WriterPoisoned("An indexing worker thread panicked.".into())
+            })?;
 
             indexing_worker_result?;
             // add a new worker for the next generation.
             self.add_indexing_worker()?;
         }
-
-        let commit_opstamp = self.stamper.stamp();
-        let prepared_commit = PreparedCommit::new(self, commit_opstamp);
-        info!("Prepared commit {}", commit_opstamp);
-        Ok(prepared_commit)
+        Ok(())
     }
 
     /// Commits all of the pending changes
@@ -598,6 +1115,30 @@ impl IndexWriter {
         self.prepare_commit()?.commit()
     }
 
+    /// Shuts the `IndexWriter` down.
+    ///
+    /// The pending documents are flushed and committed,
+    /// and the merges are given up to `merge_timeout` to
+    /// complete. The merges still running after this delay,
+    /// including the ones triggered by the completion of other
+    /// merges, are aborted.
+    /// The directory lock is released once all of the
+    /// indexing and merging threads have stopped.
+    ///
+    /// Returns the opstamp of the commit.
+    pub fn finalize(mut self, merge_timeout: Duration) -> Result<u64> {
+        let opstamp = self.commit()?;
+        let deadline = Instant::now() + merge_timeout;
+        if !self.segment_updater.wait_merges_until(deadline) {
+            info!("Merge timeout reached, aborting the running merges.");
+            // prevents new merges from starting.
+            self.segment_updater.kill();
+            self.segment_updater.cancel_merges();
+        }
+        self.wait_merging_threads()?;
+        Ok(opstamp)
+    }
+
     pub(crate) fn segment_updater(&self) -> &SegmentUpdater {
         &self.segment_updater
     }
@@ -610,11 +1151,130 @@ impl IndexWriter {
     ///
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
-    pub fn delete_term(&mut self, term: Term) -> u64 {
+    ///
+    /// # Errors
+    /// Returns a `WriterPoisoned` error if an indexing worker
+    /// thread panicked, in which case the delete is not recorded.
+    /// See [`.is_poisoned()`](#method.is_poisoned).
+    pub fn delete_term(&mut self, term: Term) -> Result<u64> {
+        self.check_poisoned()?;
         let opstamp = self.stamper.stamp();
-        let delete_operation = DeleteOperation { opstamp, term };
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::Term(term),
+        };
         self.delete_queue.push(delete_operation);
-        opstamp
+        Ok(opstamp)
+    }
+
+    /// Delete all documents containing any of the given terms.
+    ///
+    /// This is equivalent to calling `delete_term` for each of the
+    /// terms, except that all of the deletes share the same opstamp,
+    /// which is returned. The terms are sorted and deduplicated,
+    /// and each term dictionary is streamed once per segment to
+    /// apply them.
+    ///
+    /// # Errors
+    /// Returns a `WriterPoisoned` error if an indexing worker
+    /// thread panicked, in which case the deletes are not recorded.
+    pub fn delete_terms<I: IntoIterator<Item = Term>>(&mut self, terms: I) -> Result<u64> {
+        self.check_poisoned()?;
+        let mut terms: Vec<Term> = terms.into_iter().collect();
+        terms.sort();
+        terms.dedup();
+        let opstamp = self.stamper.stamp();
+        let delete_operations = terms
+            .into_iter()
+            .map(|term| DeleteOperation {
+                opstamp,
+                target: DeleteTarget::Term(term),
+            })
+            .collect();
+        self.delete_queue.push_all(delete_operations);
+        Ok(opstamp)
+    }
+
+    /// Delete all documents matching a given query.
+    ///
+    /// As for `delete_term`, the delete operation only affects documents
+    /// that were added in previous commits, or previously in the same
+    /// commit, and is visible after calling `commit()`.
+    ///
+    /// The query is executed, without scoring, against each of the
+    /// segments when the deletes are applied. Its weight is built
+    /// right away, on the state of the last commit.
+    ///
+    /// # Errors
+    /// Returns a `WriterPoisoned` error if an indexing worker
+    /// thread panicked, in which case the delete is not recorded.
+    pub fn delete_query(&mut self, query: Box<Query>) -> Result<u64> {
+        self.check_poisoned()?;
+        let searcher = self.index.open_searcher()?;
+        let weight = query.weight(&searcher, false)?;
+        let opstamp = self.stamper.stamp();
+        self.delete_queue.push(DeleteOperation {
+            opstamp,
+            target: DeleteTarget::Query(Arc::from(weight)),
+        });
+        Ok(opstamp)
+    }
+
+    /// Sets the value of the fast field `field` to `value`, for
+    /// all of the documents containing a given term, without
+    /// reindexing them.
+    ///
+    /// As for deletes, the update only affects documents that were added
+    /// in previous commits, or previously in the same commit, and is visible
+    /// after calling `commit()`. It is recorded in a per-segment updates
+    /// file consulted by the fast field readers, until the segment is merged.
+    ///
+    /// Only the fast field is updated: the stored value, if any, is not.
+    ///
+    /// # Errors
+    /// Returns a `SchemaError` if `field` is not a single value fast field
+    /// of type `Item`, if it is indexed, or if the field of `term` is not indexed.
+    ///
+    /// Returns a `WriterPoisoned` error if an indexing worker
+    /// thread panicked, in which case the update is not recorded.
+    pub fn update_fast_field<Item: FastValue>(
+        &mut self,
+        term: Term,
+        field: Field,
+        value: Item,
+    ) -> Result<u64> {
+        self.check_poisoned()?;
+        let schema = self.index.schema();
+        let field_entry = schema.get_field_entry(field);
+        if Item::fast_field_cardinality(field_entry.field_type()) != Some(Cardinality::SingleValue)
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "{:?} is not a single value fast field of the updated type.",
+                field_entry.name()
+            )));
+        }
+        if field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "{:?} is indexed, and cannot be updated.",
+                field_entry.name()
+            )));
+        }
+        if !schema.get_field_entry(term.field()).is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "{:?} is not indexed.",
+                schema.get_field_name(term.field())
+            )));
+        }
+        let opstamp = self.stamper.stamp();
+        self.delete_queue.push(DeleteOperation {
+            opstamp,
+            target: DeleteTarget::FastFieldUpdate {
+                term,
+                field,
+                value: value.to_u64(),
+            },
+        });
+        Ok(opstamp)
     }
 
     /// Returns the opstamp of the last successful commit.
@@ -638,27 +1298,255 @@ impl IndexWriter {
     ///
     /// Currently it represents the number of documents that
     /// have been added since the creation of the index.
-    pub fn add_document(&mut self, document: Document) -> u64 {
+    ///
+    /// # Errors
+    /// Returns a `WriterPoisoned` error if an indexing worker
+    /// thread panicked, in which case the document is not added.
+    /// See [`.is_poisoned()`](#method.is_poisoned).
+    ///
+    /// Returns a `DiskSpaceLimitExceeded` error if the index would
+    /// exceed its disk space limit, in which case the document is not added.
+    /// See [`.set_disk_space_limit(...)`](#method.set_disk_space_limit).
+    pub fn add_document(&mut self, document: Document) -> Result<u64> {
+        self.check_poisoned()?;
+        self.check_disk_space_limit()?;
         let opstamp = self.stamper.stamp();
         let add_operation = AddOperation { opstamp, document };
-        let send_result = self.document_sender.send(add_operation);
+        self.send_add_batch(vec![add_operation]);
+        Ok(opstamp)
+    }
+
+    /// Replaces the documents containing `term` with `document`.
+    ///
+    /// `term` is typically the value of a unique key field of `document`.
+    /// The delete operation gets an opstamp lower than the one of
+    /// the document, so that it only deletes the previous versions
+    /// of the document. As they are part of the same commit, the
+    /// documents are replaced atomically.
+    ///
+    /// Returns the opstamp of the added document.
+    ///
+    /// # Errors
+    /// Returns a `WriterPoisoned` error if an indexing worker
+    /// thread panicked, or a `DiskSpaceLimitExceeded` error if the
+    /// index would exceed its disk space limit, in which case neither
+    /// the delete nor the document are recorded.
+    pub fn update_document(&mut self, term: Term, document: Document) -> Result<u64> {
+        self.check_poisoned()?;
+        self.check_disk_space_limit()?;
+        self.delete_term(term)?;
+        self.add_document(document)
+    }
+
+    /// Adds a block of documents.
+    ///
+    /// The documents of the block are given consecutive opstamps, and
+    /// are indexed contiguously, in the order of the block, in the same segment.
+    /// Merges preserve the order of the documents, so that the block stays
+    /// contiguous as long as none of its documents is deleted.
+    ///
+    /// This is required by [`ToParentQuery`](./query/struct.ToParentQuery.html)
+    /// and [`ToChildQuery`](./query/struct.ToChildQuery.html), for which a block
+    /// is made of child documents followed by their parent document.
+    /// A block should therefore be deleted as a whole, for instance
+    /// by deleting a term shared by all of its documents.
+    ///
+    /// Returns the opstamp of the last document of the block.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if the block is empty, a
+    /// `WriterPoisoned` error if an indexing worker thread panicked,
+    /// or a `DiskSpaceLimitExceeded` error if the index would exceed
+    /// its disk space limit, in which case the block is not added.
+    pub fn add_document_block(&mut self, documents: Vec<Document>) -> Result<u64> {
+        if documents.is_empty() {
+            return Err(TantivyError::InvalidArgument(
+                "A document block cannot be empty.".to_string(),
+            ));
+        }
+        self.check_poisoned()?;
+        self.check_disk_space_limit()?;
+        let add_batch: AddBatch = documents
+            .into_iter()
+            .map(|document| AddOperation {
+                opstamp: self.stamper.stamp(),
+                document,
+            })
+            .collect();
+        let last_opstamp = add_batch.last().unwrap().opstamp;
+        self.send_add_batch(add_batch);
+        Ok(last_opstamp)
+    }
+
+    fn send_add_batch(&self, add_batch: AddBatch) {
+        let send_result = self.document_sender.send(add_batch);
         if let Err(e) = send_result {
             panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
         }
-        opstamp
+    }
+}
+
+impl Drop for IndexWriter {
+    fn drop(&mut self) {
+        // Pending merges would outlive the `IndexWriter`
+        // and keep on writing after the release of the directory lock.
+        self.segment_updater.kill();
+        self.segment_updater.cancel_merges();
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::initial_table_size;
+    use super::{initial_table_size, IndexWriter};
+    use collector::Count;
+    use core::Searcher;
+    use core::{SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+    use core::{INDEX_FORMAT_VERSION, META_FILEPATH};
+    use directory::error::{DeleteError, OpenReadError, OpenWriteError};
+    use directory::{Directory, RAMDirectory, ReadOnlySource, SeekableWrite};
+    use directory::{WatchCallback, WatchHandle, WritePtr};
+    use docset::DocSet;
     use error::*;
+    use futures::Future;
+    use indexer::disk_space::with_measured_num_bytes;
+    use indexer::merge_policy::tests::MergeWheneverPossible;
+    use indexer::IndexingObserver;
     use indexer::NoMergePolicy;
+    use query::{RangeQuery, TermQuery};
     use schema::{self, Document};
+    use serde_json;
+    use std::fmt;
+    use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
     use Index;
     use Term;
 
+    type WriteGate = Arc<Mutex<Option<(Sender<()>, Receiver<()>)>>>;
+
+    /// `RAMDirectory` whose next write can be blocked, so that
+    /// the tests can act while a merge is running.
+    #[derive(Clone)]
+    struct BlockingDirectory {
+        underlying: RAMDirectory,
+        gate: WriteGate,
+    }
+
+    impl BlockingDirectory {
+        fn create() -> BlockingDirectory {
+            BlockingDirectory {
+                underlying: RAMDirectory::create(),
+                gate: WriteGate::default(),
+            }
+        }
+
+        /// Blocks the next write to the files of the directory.
+        ///
+        /// Returns a receiver notified once the write is blocked,
+        /// and a sender to release it.
+        fn block_next_write(&self) -> (Receiver<()>, Sender<()>) {
+            let (blocked_sender, blocked_receiver) = channel();
+            let (release_sender, release_receiver) = channel();
+            *self.gate.lock().unwrap() = Some((blocked_sender, release_receiver));
+            (blocked_receiver, release_sender)
+        }
+    }
+
+    impl fmt::Debug for BlockingDirectory {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "BlockingDirectory({:?})", self.underlying)
+        }
+    }
+
+    impl Directory for BlockingDirectory {
+        fn open_read(&self, path: &Path) -> Result<ReadOnlySource, OpenReadError> {
+            self.underlying.open_read(path)
+        }
+
+        fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+            self.underlying.delete(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.underlying.exists(path)
+        }
+
+        fn open_write(&mut self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+            let underlying = self.underlying.open_write(path)?;
+            Ok(BufWriter::new(Box::new(BlockingWrite {
+                underlying,
+                gate: Arc::clone(&self.gate),
+            })))
+        }
+
+        fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.underlying.atomic_read(path)
+        }
+
+        fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+            self.underlying.atomic_write(path, data)
+        }
+
+        fn watch(&self, watch_callback: WatchCallback) -> ::Result<WatchHandle> {
+            self.underlying.watch(watch_callback)
+        }
+    }
+
+    struct BlockingWrite {
+        underlying: WritePtr,
+        gate: WriteGate,
+    }
+
+    impl Write for BlockingWrite {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let gate = self.gate.lock().unwrap().take();
+            if let Some((blocked_sender, release_receiver)) = gate {
+                blocked_sender.send(()).unwrap();
+                release_receiver.recv().unwrap();
+            }
+            self.underlying.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.underlying.flush()
+        }
+    }
+
+    impl Seek for BlockingWrite {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.underlying.seek(pos)
+        }
+    }
+
+    impl SeekableWrite for BlockingWrite {
+        fn terminate(&mut self) -> io::Result<()> {
+            self.underlying.terminate()
+        }
+    }
+
+    /// Creates an index with two segments, in a `BlockingDirectory`.
+    fn create_index_with_two_segments(
+        directory: &BlockingDirectory,
+    ) -> (Index, IndexWriter, schema::Field) {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create(directory.clone(), schema_builder.build()).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0..2 {
+            for doc_id in 0..100 {
+                let text = format!("term{}_{}", segment_ord, doc_id);
+                index_writer.add_document(doc!(text_field => text)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        (index, index_writer, text_field)
+    }
+
     #[test]
     fn test_lockfile_stops_duplicates() {
         let schema_builder = schema::Schema::builder();
@@ -715,6 +1603,25 @@ mod tests {
         let _index_writer_two = index.writer(40_000_000).unwrap();
     }
 
+    #[test]
+    fn test_lockfile_wait_with_timeout() {
+        let schema_builder = schema::Schema::builder();
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        match index.writer_with_lock_timeout(1, 3_000_000, Duration::from_millis(100)) {
+            Err(TantivyError::LockFailure(_)) => {}
+            _ => panic!("Expected LockFailure error"),
+        }
+        let drop_handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            drop(index_writer);
+        });
+        let _index_writer_two = index
+            .writer_with_lock_timeout(1, 3_000_000, Duration::from_secs(10))
+            .unwrap();
+        drop_handle.join().unwrap();
+    }
+
     #[test]
     fn test_commit_and_rollback() {
         let mut schema_builder = schema::Schema::builder();
@@ -730,14 +1637,14 @@ mod tests {
         {
             // writing the segment
             let mut index_writer = index.writer(3_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a"));
+            index_writer.add_document(doc!(text_field=>"a")).unwrap();
             index_writer.rollback().unwrap();
 
             assert_eq!(index_writer.commit_opstamp(), 0u64);
             assert_eq!(num_docs_containing("a"), 0);
             {
-                index_writer.add_document(doc!(text_field=>"b"));
-                index_writer.add_document(doc!(text_field=>"c"));
+                index_writer.add_document(doc!(text_field=>"b")).unwrap();
+                index_writer.add_document(doc!(text_field=>"c")).unwrap();
             }
             assert_eq!(index_writer.commit().unwrap(), 2u64);
             index.load_searchers().unwrap();
@@ -766,13 +1673,13 @@ mod tests {
             for _doc in 0..100 {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().expect("commit failed");
             for _doc in 0..100 {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             // this should create 8 segments and trigger a merge.
             index_writer.commit().expect("commit failed");
@@ -797,7 +1704,7 @@ mod tests {
             let mut index_writer = index.writer(12_000_000).unwrap();
             // create 8 segments with 100 tiny docs
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "a"));
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
             }
             {
                 let mut prepared_commit = index_writer.prepare_commit().expect("commit failed");
@@ -810,7 +1717,7 @@ mod tests {
                 assert_eq!(metas.payload.unwrap(), "first commit");
             }
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "a"));
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
             }
             index_writer.commit().unwrap();
             {
@@ -831,7 +1738,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(4, 12_000_000).unwrap();
             // create 8 segments with 100 tiny docs
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "a"));
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
             }
             {
                 let mut prepared_commit = index_writer.prepare_commit().expect("commit failed");
@@ -844,7 +1751,7 @@ mod tests {
                 assert!(metas.payload.is_none());
             }
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "b"));
+                index_writer.add_document(doc!(text_field => "b")).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -866,6 +1773,814 @@ mod tests {
         assert_eq!(initial_table_size(1_000_000_000), 19);
     }
 
+    #[test]
+    fn test_segment_infos() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        assert!(index_writer.segment_infos().is_empty());
+        for num_docs in 1..3 {
+            for _ in 0..num_docs {
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index_writer
+            .delete_term(Term::from_field_text(text_field, "a"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        let mut segment_infos = index_writer.segment_infos();
+        segment_infos.sort_by_key(|segment_info| segment_info.max_doc());
+        assert_eq!(segment_infos.len(), 2);
+        for (segment_info, max_doc) in segment_infos.iter().zip(1..3) {
+            assert_eq!(segment_info.max_doc(), max_doc);
+            assert_eq!(segment_info.num_docs(), 0);
+            assert_eq!(segment_info.num_deleted_docs(), max_doc);
+            assert!(segment_info.is_committed());
+            assert!(!segment_info.is_merging());
+            assert!(segment_info.creation_time().unwrap() <= SystemTime::now());
+        }
+        let mut segment_ids: Vec<_> = segment_infos
+            .iter()
+            .map(|segment_info| segment_info.segment_id())
+            .collect();
+        let mut searchable_segment_ids = index.searchable_segment_ids().unwrap();
+        segment_ids.sort();
+        searchable_segment_ids.sort();
+        assert_eq!(segment_ids, searchable_segment_ids);
+    }
+
+    #[test]
+    fn test_disk_space_limit_add_document() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        let num_bytes_on_disk = index_writer.num_bytes_on_disk().unwrap();
+        assert!(num_bytes_on_disk > 0);
+
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.set_disk_space_limit(Some(num_bytes_on_disk));
+        assert_eq!(index_writer.get_disk_space_limit(), Some(num_bytes_on_disk));
+        // the document accepted before the limit was set is flushed anyway.
+        index_writer.commit().unwrap();
+        match index_writer.add_document(doc!(text_field => "c")) {
+            Err(TantivyError::DiskSpaceLimitExceeded { used, limit, .. }) => {
+                assert!(used > num_bytes_on_disk);
+                assert_eq!(limit, num_bytes_on_disk);
+            }
+            _ => panic!("Expected DiskSpaceLimitExceeded error"),
+        }
+        // the limit is kept by rollbacks.
+        index_writer.rollback().unwrap();
+        assert_eq!(index_writer.get_disk_space_limit(), Some(num_bytes_on_disk));
+        assert!(index_writer.add_document(doc!(text_field => "c")).is_err());
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().num_docs(), 2);
+
+        // the sizes of the segments are recorded in their metas.
+        let segment_metas = index.searchable_segment_metas().unwrap();
+        let recorded_num_bytes: u64 = segment_metas
+            .iter()
+            .map(|segment_meta| segment_meta.num_bytes().unwrap())
+            .sum();
+        assert_eq!(
+            recorded_num_bytes,
+            index_writer.num_bytes_on_disk().unwrap()
+        );
+        for segment_meta in &segment_metas {
+            let measured_meta = with_measured_num_bytes(&index, segment_meta.clone()).unwrap();
+            assert_eq!(measured_meta.num_bytes(), segment_meta.num_bytes());
+        }
+    }
+
+    #[test]
+    fn test_merge_io_limit() {
+        let schema_builder = schema::Schema::builder();
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        assert_eq!(index_writer.get_merge_io_limit(), None);
+        index_writer.set_merge_io_limit(Some(1_000_000));
+        assert_eq!(index_writer.get_merge_io_limit(), Some(1_000_000));
+        // the limit is kept by rollbacks.
+        index_writer.rollback().unwrap();
+        assert_eq!(index_writer.get_merge_io_limit(), Some(1_000_000));
+        index_writer.set_merge_io_limit(None);
+        assert_eq!(index_writer.get_merge_io_limit(), None);
+    }
+
+    #[test]
+    fn test_disk_space_limit_merge() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..2 {
+            for _ in 0..100 {
+                index_writer
+                    .add_document(doc!(text_field => "a b c"))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let num_bytes_on_disk = index_writer.num_bytes_on_disk().unwrap();
+        assert!(num_bytes_on_disk > 0);
+        assert_eq!(index_writer.estimate_flush_num_bytes(), 0);
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        assert_eq!(segment_ids.len(), 2);
+        let merge_num_bytes = index_writer
+            .estimate_merge_num_bytes(&segment_ids[..])
+            .unwrap();
+        assert!(merge_num_bytes > 0);
+        assert!(merge_num_bytes <= num_bytes_on_disk);
+
+        index_writer.set_disk_space_limit(Some(num_bytes_on_disk));
+        match index_writer.merge(&segment_ids[..]) {
+            Err(TantivyError::DiskSpaceLimitExceeded { required, .. }) => {
+                assert_eq!(required, merge_num_bytes);
+            }
+            _ => panic!("Expected DiskSpaceLimitExceeded error"),
+        }
+        index_writer.set_disk_space_limit(Some(num_bytes_on_disk + merge_num_bytes));
+        assert!(index_writer.merge(&segment_ids[..]).is_ok());
+    }
+
+    #[test]
+    fn test_num_bytes_recorded() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INT_INDEXED);
+        let likes_field = schema_builder.add_u64_field("likes", schema::FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0u64..10 {
+            index_writer
+                .add_document(doc!(id_field => id, likes_field => id))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        index_writer
+            .delete_term(Term::from_field_u64(id_field, 1))
+            .unwrap();
+        index_writer
+            .update_fast_field(Term::from_field_u64(id_field, 2), likes_field, 100u64)
+            .unwrap();
+        index_writer.commit().unwrap();
+        let segment_metas = index.searchable_segment_metas().unwrap();
+        assert_eq!(segment_metas.len(), 1);
+        let segment_meta = &segment_metas[0];
+        assert!(segment_meta.has_deletes());
+        assert!(segment_meta.has_fast_field_updates());
+        // the sizes counted as the files were written match their actual size.
+        let measured_meta = with_measured_num_bytes(&index, segment_meta.clone()).unwrap();
+        assert!(segment_meta.num_bytes().is_some());
+        assert_eq!(measured_meta.num_bytes(), segment_meta.num_bytes());
+        drop(index_writer);
+
+        make_legacy_metas(&index);
+        assert!(index.searchable_segment_metas().unwrap()[0]
+            .num_bytes()
+            .is_none());
+        let index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        assert_eq!(
+            Some(index_writer.num_bytes_on_disk().unwrap()),
+            segment_meta.num_bytes()
+        );
+        // legacy segments are measured once.
+        assert!(index_writer
+            .segment_infos()
+            .iter()
+            .all(|segment_info| segment_info.meta().num_bytes().is_some()));
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Mutex<Vec<(&'static str, u32)>>,
+    }
+
+    impl IndexingObserver for RecordingObserver {
+        fn documents_indexed(&self, _segment_id: SegmentId, num_docs: u32) {
+            self.events.lock().unwrap().push(("docs", num_docs));
+        }
+
+        fn flush_started(&self, _segment_id: SegmentId, num_docs: u32) {
+            self.events.lock().unwrap().push(("flush_start", num_docs));
+        }
+
+        fn flush_ended(&self, _segment_id: SegmentId, num_docs: u32) {
+            self.events.lock().unwrap().push(("flush_end", num_docs));
+        }
+
+        fn merge_ended(&self, merged_segment_ids: &[SegmentId], merged_segment: &SegmentMeta) {
+            assert_eq!(merged_segment_ids.len(), 2);
+            self.events
+                .lock()
+                .unwrap()
+                .push(("merge", merged_segment.max_doc()));
+        }
+    }
+
+    #[test]
+    fn test_indexing_observer() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let observer = Arc::new(RecordingObserver::default());
+        index_writer.set_indexing_observer(Some(observer.clone()));
+        for _ in 0..1_500 {
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+        }
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        assert_eq!(
+            &observer.events.lock().unwrap()[..],
+            &[
+                ("docs", 1_000),
+                ("docs", 500),
+                ("flush_start", 1_500),
+                ("flush_end", 1_500),
+                ("docs", 1),
+                ("flush_start", 1),
+                ("flush_end", 1),
+            ]
+        );
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids[..])
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert_eq!(
+            observer.events.lock().unwrap().last(),
+            Some(&("merge", 1_501))
+        );
+        let indexing_stats = index_writer.indexing_stats();
+        assert_eq!(indexing_stats.num_indexed_docs(), 1_501);
+        assert_eq!(indexing_stats.num_flushes(), 2);
+        assert_eq!(indexing_stats.num_merges(), 1);
+    }
+
+    #[test]
+    fn test_delete_terms() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let id_field = schema_builder.add_u64_field("id", schema::INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0u64..100 {
+            let text = format!("term{}", id % 10);
+            index_writer
+                .add_document(doc!(text_field => text, id_field => id))
+                .unwrap();
+            if id == 50 {
+                index_writer.commit().unwrap();
+            }
+        }
+        let delete_opstamp = index_writer
+            .delete_terms(vec![
+                Term::from_field_text(text_field, "term3"),
+                Term::from_field_u64(id_field, 0),
+                Term::from_field_text(text_field, "term1"),
+                Term::from_field_text(text_field, "term3"),
+                Term::from_field_u64(id_field, 98),
+                Term::from_field_text(text_field, "missing"),
+            ])
+            .unwrap();
+        // added after the delete, not affected by it.
+        let add_opstamp = index_writer
+            .add_document(doc!(text_field => "term1", id_field => 100u64))
+            .unwrap();
+        assert!(delete_opstamp < add_opstamp);
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let doc_freq = |term: Term| {
+            searcher
+                .segment_readers()
+                .iter()
+                .map(|segment_reader| {
+                    let inverted_index = segment_reader.inverted_index(term.field());
+                    let mut docset = inverted_index
+                        .read_postings(&term, schema::IndexRecordOption::Basic)
+                        .unwrap();
+                    let mut num_alive_docs = 0;
+                    while docset.advance() {
+                        if !segment_reader.is_deleted(docset.doc()) {
+                            num_alive_docs += 1;
+                        }
+                    }
+                    num_alive_docs
+                })
+                .sum::<u32>()
+        };
+        assert_eq!(doc_freq(Term::from_field_text(text_field, "term3")), 0);
+        assert_eq!(doc_freq(Term::from_field_text(text_field, "term1")), 1);
+        assert_eq!(doc_freq(Term::from_field_text(text_field, "term0")), 9);
+        assert_eq!(doc_freq(Term::from_field_text(text_field, "term8")), 9);
+        assert_eq!(searcher.num_docs(), 101 - 10 - 10 - 1 - 1);
+    }
+
+    #[test]
+    fn test_delete_query() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0u64..100 {
+            index_writer.add_document(doc!(id_field => id)).unwrap();
+            if id == 50 {
+                index_writer.commit().unwrap();
+            }
+        }
+        // spans the committed and the uncommitted documents.
+        let delete_opstamp = index_writer
+            .delete_query(Box::new(RangeQuery::new_u64(id_field, 40..60)))
+            .unwrap();
+        // added after the delete, not affected by it.
+        let add_opstamp = index_writer.add_document(doc!(id_field => 45u64)).unwrap();
+        assert!(delete_opstamp < add_opstamp);
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 101 - 20);
+        let deleted_range = RangeQuery::new_u64(id_field, 40..60);
+        assert_eq!(searcher.search(&deleted_range, &Count).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_nrt_searcher() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        let last_opstamp = index_writer.add_document(doc!(text_field => "c")).unwrap();
+        let searcher = index_writer.nrt_searcher().unwrap();
+        assert!(searcher.opstamp() > last_opstamp);
+        assert_eq!(searcher.num_docs(), 4);
+
+        index_writer
+            .delete_term(Term::from_field_text(text_field, "a"))
+            .unwrap();
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        let searcher = index_writer.nrt_searcher().unwrap();
+        assert_eq!(searcher.num_docs(), 3);
+        let count = |searcher: &Searcher, text: &str| {
+            let term = Term::from_field_text(text_field, text);
+            let query = TermQuery::new(term, schema::IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(&searcher, "a"), 1);
+        assert_eq!(count(&searcher, "c"), 1);
+        // nothing was committed.
+        assert_eq!(index.searcher().num_docs(), 2);
+
+        index_writer.rollback().unwrap();
+        let searcher = index_writer.nrt_searcher().unwrap();
+        assert_eq!(searcher.num_docs(), 2);
+        assert_eq!(count(&searcher, "a"), 1);
+    }
+
+    #[test]
+    fn test_nrt_searcher_reuses_segment_readers() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT | schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        assert_eq!(index_writer.nrt_searcher().unwrap().num_docs(), 1);
+        let segment_meta = index.searchable_segment_metas().unwrap()[0].clone();
+        // the segment cannot be opened anymore, but its reader is kept.
+        index
+            .directory()
+            .delete(&segment_meta.relative_path(SegmentComponent::STORE))
+            .unwrap();
+        assert!(SegmentReader::open(&index.segment(segment_meta.clone())).is_err());
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer
+            .delete_term(Term::from_field_text(text_field, "a"))
+            .unwrap();
+        let searcher = index_writer.nrt_searcher().unwrap();
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(index_writer.nrt_segment_readers.len(), 2);
+        assert!(index_writer
+            .nrt_segment_readers
+            .contains_key(&(segment_meta.id(), None, None)));
+    }
+
+    #[test]
+    fn test_update_document() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", schema::STRING);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let id = |val: &str| Term::from_field_text(id_field, val);
+        index_writer
+            .update_document(id("a"), doc!(id_field => "a", text_field => "v1"))
+            .unwrap();
+        index_writer
+            .update_document(id("b"), doc!(id_field => "b", text_field => "v1"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index_writer
+            .update_document(id("a"), doc!(id_field => "a", text_field => "v2"))
+            .unwrap();
+        // replaced twice in the same commit.
+        index_writer
+            .update_document(id("c"), doc!(id_field => "c", text_field => "v1"))
+            .unwrap();
+        index_writer
+            .update_document(id("c"), doc!(id_field => "c", text_field => "v2"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let text = |val: &str| Term::from_field_text(text_field, val);
+        let count = |term: Term| {
+            let query = TermQuery::new(term, schema::IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(text("v1")), 1);
+        assert_eq!(count(text("v2")), 2);
+        assert_eq!(count(id("a")), 1);
+        assert_eq!(count(id("c")), 1);
+    }
+
+    #[test]
+    fn test_update_fast_field() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INT_INDEXED);
+        let likes_field = schema_builder.add_u64_field("likes", schema::FAST);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0u64..10 {
+            index_writer
+                .add_document(doc!(id_field => id, likes_field => id))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        for id in 10u64..20 {
+            index_writer
+                .add_document(doc!(id_field => id, likes_field => id))
+                .unwrap();
+        }
+        let id_term = |id: u64| Term::from_field_u64(id_field, id);
+        index_writer
+            .update_fast_field(id_term(3), likes_field, 100u64)
+            .unwrap();
+        index_writer
+            .update_fast_field(id_term(15), likes_field, 200u64)
+            .unwrap();
+        // added after the update, not affected by it.
+        index_writer
+            .update_fast_field(id_term(30), likes_field, 300u64)
+            .unwrap();
+        index_writer
+            .add_document(doc!(id_field => 30u64, likes_field => 30u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let likes = |id: u64| {
+            index.load_searchers().unwrap();
+            let searcher = index.searcher();
+            for segment_reader in searcher.segment_readers() {
+                let inverted_index = segment_reader.inverted_index(id_field);
+                if let Some(mut docset) =
+                    inverted_index.read_postings(&id_term(id), schema::IndexRecordOption::Basic)
+                {
+                    if docset.advance() {
+                        let likes_reader = segment_reader.fast_field_reader::<u64>(likes_field);
+                        return likes_reader.unwrap().get(docset.doc());
+                    }
+                }
+            }
+            panic!("Document {} not found.", id);
+        };
+        assert_eq!(likes(3), 100);
+        assert_eq!(likes(4), 4);
+        assert_eq!(likes(15), 200);
+        assert_eq!(likes(30), 30);
+
+        index_writer
+            .update_fast_field(id_term(3), likes_field, 101u64)
+            .unwrap();
+        index_writer.commit().unwrap();
+        assert_eq!(likes(3), 101);
+        assert_eq!(likes(15), 200);
+
+        assert!(index_writer
+            .update_fast_field(id_term(3), id_field, 1u64)
+            .is_err());
+        assert!(index_writer
+            .update_fast_field(id_term(3), likes_field, 1i64)
+            .is_err());
+        assert!(index_writer
+            .update_fast_field(Term::from_field_u64(likes_field, 3), likes_field, 1u64)
+            .is_err());
+        assert!(index_writer
+            .update_fast_field(Term::from_field_text(text_field, "a"), text_field, 1u64)
+            .is_err());
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        assert_eq!(likes(3), 101);
+        assert_eq!(likes(15), 200);
+        assert_eq!(likes(19), 19);
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_meta = &index.searchable_segment_metas().unwrap()[0];
+        assert!(!segment_meta.has_fast_field_updates());
+    }
+
+    #[test]
+    fn test_cancel_merges() {
+        let directory = BlockingDirectory::create();
+        let (index, mut index_writer, _) = create_index_with_two_segments(&directory);
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        let (blocked_receiver, release_sender) = directory.block_next_write();
+        let merge_future = index_writer.merge(&segment_ids[..]).unwrap();
+        // the merge is still running as long as its first write is blocked.
+        blocked_receiver.recv().unwrap();
+        assert!(index_writer
+            .segment_infos()
+            .iter()
+            .all(|segment_info| segment_info.is_merging()));
+        index_writer.cancel_merges();
+        release_sender.send(()).unwrap();
+        assert!(merge_future.wait().is_err());
+        index_writer.wait_merging_threads().unwrap();
+
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let mut searchable_segment_ids = index.searchable_segment_ids().unwrap();
+        assert_eq!(searchable_segment_ids.len(), 2);
+        assert!(index_writer
+            .segment_infos()
+            .iter()
+            .all(|segment_info| !segment_info.is_merging()));
+        index_writer
+            .merge(&searchable_segment_ids[..])
+            .unwrap()
+            .wait()
+            .unwrap();
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().num_docs(), 200);
+        searchable_segment_ids = index.searchable_segment_ids().unwrap();
+        assert_eq!(searchable_segment_ids.len(), 1);
+    }
+
+    #[test]
+    fn test_cancel_merges_on_drop() {
+        let directory = BlockingDirectory::create();
+        let (index, mut index_writer, _) = create_index_with_two_segments(&directory);
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        let (blocked_receiver, release_sender) = directory.block_next_write();
+        let merge_future = index_writer.merge(&segment_ids[..]).unwrap();
+        blocked_receiver.recv().unwrap();
+        drop(index_writer);
+        release_sender.send(()).unwrap();
+        assert!(merge_future.wait().is_err());
+        assert_eq!(index.searchable_segment_ids().unwrap().len(), 2);
+        // the lock was released on drop.
+        assert!(index.writer_with_num_threads(1, 3_000_000).is_ok());
+    }
+
+    /// Rewrites the `meta.json` file of the index as if it
+    /// was written before the index format was versioned,
+    /// and before the sizes of the segments were recorded.
+    fn make_legacy_metas(index: &Index) {
+        let mut directory = index.directory().clone();
+        let meta_data = directory.atomic_read(&META_FILEPATH).unwrap();
+        // The json text is edited line by line rather than through a `serde_json::Value`,
+        // as the latter would reorder the keys of the schema.
+        let meta_json = String::from_utf8(meta_data).unwrap();
+        let mut lines: Vec<&str> = Vec::new();
+        for line in meta_json.lines() {
+            let trimmed_line = line.trim_left();
+            if trimmed_line.starts_with("\"index_format_version\":")
+                || trimmed_line.starts_with("\"format_version\":")
+                || trimmed_line.starts_with("\"num_bytes\":")
+                || trimmed_line.starts_with("\"fast_field_updates_num_bytes\":")
+            {
+                if !line.ends_with(',') {
+                    // the removed key was the last one of its object.
+                    if let Some(previous_line) = lines.pop() {
+                        lines.push(previous_line.trim_right_matches(','));
+                    }
+                }
+            } else {
+                lines.push(line);
+            }
+        }
+        let legacy_meta_json = lines.join("\n");
+        assert!(!legacy_meta_json.contains("format_version"));
+        directory
+            .atomic_write(&META_FILEPATH, legacy_meta_json.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        make_legacy_metas(&index);
+        assert!(index
+            .searchable_segment_metas()
+            .unwrap()
+            .iter()
+            .all(|segment_meta| segment_meta.format_version() == 0));
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "c")).unwrap();
+        index_writer.commit().unwrap();
+        // only the two legacy segments are rewritten.
+        let upgraded_segment_meta = index_writer.upgrade().unwrap().unwrap().wait().unwrap();
+        assert_eq!(upgraded_segment_meta.max_doc(), 2);
+        assert!(index_writer.upgrade().unwrap().is_none());
+        index_writer.wait_merging_threads().unwrap();
+        let segment_metas = index.searchable_segment_metas().unwrap();
+        assert_eq!(segment_metas.len(), 2);
+        assert!(segment_metas
+            .iter()
+            .all(|segment_meta| segment_meta.format_version() == INDEX_FORMAT_VERSION));
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().num_docs(), 3);
+    }
+
+    #[test]
+    fn test_incompatible_index_format() {
+        let schema_builder = schema::Schema::builder();
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut directory = index.directory().clone();
+        let meta_data = directory.atomic_read(&META_FILEPATH).unwrap();
+        let mut metas: serde_json::Value = serde_json::from_slice(&meta_data).unwrap();
+        metas["index_format_version"] = (INDEX_FORMAT_VERSION + 1).into();
+        let meta_data = serde_json::to_vec(&metas).unwrap();
+        directory.atomic_write(&META_FILEPATH, &meta_data).unwrap();
+        match index.load_metas() {
+            Err(TantivyError::IncompatibleIndex(_)) => {}
+            _ => panic!("Expected an incompatible index error"),
+        }
+    }
+
+    #[test]
+    fn test_add_empty_document_block() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        match index_writer.add_document_block(Vec::new()) {
+            Err(TantivyError::InvalidArgument(_)) => {}
+            _ => panic!("Expected an invalid argument error"),
+        }
+        let opstamp = index_writer
+            .add_document_block(vec![doc!(text_field => "a")])
+            .unwrap();
+        assert_eq!(index_writer.commit().unwrap(), opstamp + 1);
+    }
+
+    #[test]
+    fn test_finalize() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(MergeWheneverPossible));
+        for _ in 0..2 {
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        let opstamp = index_writer.finalize(Duration::from_secs(60)).unwrap();
+        assert_eq!(opstamp, index.load_metas().unwrap().opstamp);
+        assert_eq!(index.searchable_segment_ids().unwrap().len(), 1);
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().num_docs(), 3);
+        // the lock was released.
+        assert!(index.writer_with_num_threads(1, 3_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_finalize_merge_timeout() {
+        let directory = BlockingDirectory::create();
+        let (index, mut index_writer, _) = create_index_with_two_segments(&directory);
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        let (blocked_receiver, release_sender) = directory.block_next_write();
+        let merge_future = index_writer.merge(&segment_ids[..]).unwrap();
+        blocked_receiver.recv().unwrap();
+        let cancellations = index_writer.segment_updater().merge_cancellations();
+        assert_eq!(cancellations.len(), 1);
+        // the merge stays blocked until `finalize` cancels it.
+        let release_handle = thread::spawn(move || {
+            while !cancellations[0].is_cancelled() {
+                thread::yield_now();
+            }
+            release_sender.send(()).unwrap();
+        });
+        index_writer.finalize(Duration::from_millis(0)).unwrap();
+        release_handle.join().unwrap();
+        assert!(merge_future.wait().is_err());
+        assert_eq!(index.searchable_segment_ids().unwrap().len(), 2);
+        assert!(index.writer_with_num_threads(1, 3_000_000).is_ok());
+    }
+
+    #[cfg(not(feature = "no_fail"))]
+    #[test]
+    fn test_poisoned_writer() {
+        use fail;
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        assert!(!index_writer.is_poisoned());
+
+        fail::cfg("IndexWriter::index_documents#flush", "panic").unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        let commit_result = index_writer.commit();
+        fail::cfg("IndexWriter::index_documents#flush", "off").unwrap();
+        match commit_result {
+            Err(TantivyError::WriterPoisoned(_)) => {}
+            _ => panic!("Expected WriterPoisoned error"),
+        }
+        assert!(index_writer.is_poisoned());
+        match index_writer.add_document(doc!(text_field => "c")) {
+            Err(TantivyError::WriterPoisoned(_)) => {}
+            _ => panic!("Expected WriterPoisoned error"),
+        }
+        let term_a = Term::from_field_text(text_field, "a");
+        let delete_results = vec![
+            index_writer.delete_term(term_a.clone()),
+            index_writer.delete_terms(vec![term_a.clone()]),
+            index_writer.delete_query(Box::new(TermQuery::new(
+                term_a.clone(),
+                schema::IndexRecordOption::Basic,
+            ))),
+            index_writer.update_fast_field(term_a, text_field, 1u64),
+        ];
+        for delete_result in delete_results {
+            match delete_result {
+                Err(TantivyError::WriterPoisoned(_)) => {}
+                _ => panic!("Expected WriterPoisoned error"),
+            }
+        }
+        match index_writer.commit() {
+            Err(TantivyError::WriterPoisoned(_)) => {}
+            _ => panic!("Expected WriterPoisoned error"),
+        }
+
+        index_writer.rollback().unwrap();
+        assert!(!index_writer.is_poisoned());
+        index_writer.add_document(doc!(text_field => "d")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let num_docs_containing = |s: &str| {
+            let searcher = index.searcher();
+            let term = Term::from_field_text(text_field, s);
+            searcher.doc_freq(&term)
+        };
+        assert_eq!(num_docs_containing("a"), 1);
+        assert_eq!(num_docs_containing("b"), 0);
+        assert_eq!(num_docs_containing("c"), 0);
+        assert_eq!(num_docs_containing("d"), 1);
+    }
+
     #[cfg(not(feature = "no_fail"))]
     #[test]
     fn test_write_commit_fails() {
@@ -876,12 +2591,12 @@ mod tests {
 
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         for _ in 0..100 {
-            index_writer.add_document(doc!(text_field => "a"));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
         }
         index_writer.commit().unwrap();
         fail::cfg("RAMDirectory::atomic_write", "return(error_write_failed)").unwrap();
         for _ in 0..100 {
-            index_writer.add_document(doc!(text_field => "b"));
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
         }
         assert!(index_writer.commit().is_err());
         index.load_searchers().unwrap();
@@ -0,0 +1,152 @@
+use core::SegmentId;
+use core::SegmentMeta;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Number of documents after which an indexing worker
+/// notifies the observer of its progress.
+pub(crate) const DOCUMENT_BATCH_SIZE: u32 = 1_000;
+
+/// An `IndexingObserver` is notified of the progress
+/// of an `IndexWriter`.
+///
+/// It is registered via
+/// [`IndexWriter::set_indexing_observer`](./struct.IndexWriter.html#method.set_indexing_observer).
+///
+/// The callbacks are called from the indexing and merging threads,
+/// and should therefore return quickly.
+/// All of them do nothing by default.
+pub trait IndexingObserver: Send + Sync + 'static {
+    /// Called every time an indexing worker has added a batch of
+    /// `num_docs` documents to the segment it is building.
+    fn documents_indexed(&self, _segment_id: SegmentId, _num_docs: u32) {}
+
+    /// Called when an indexing worker starts to flush the segment
+    /// it has built.
+    fn flush_started(&self, _segment_id: SegmentId, _num_docs: u32) {}
+
+    /// Called when a segment has been flushed, unless it was
+    /// discarded by a rollback.
+    fn flush_ended(&self, _segment_id: SegmentId, _num_docs: u32) {}
+
+    /// Called when the segments `merged_segment_ids` have been
+    /// merged into `merged_segment`.
+    fn merge_ended(&self, _merged_segment_ids: &[SegmentId], _merged_segment: &SegmentMeta) {}
+}
+
+/// Counters describing the work done by an `IndexWriter`
+/// since it was opened.
+///
+/// See [`IndexWriter::indexing_stats`](./struct.IndexWriter.html#method.indexing_stats).
+#[derive(Clone, Debug)]
+pub struct IndexingStats {
+    num_indexed_docs: usize,
+    num_flushes: usize,
+    num_merges: usize,
+    elapsed: Duration,
+}
+
+impl IndexingStats {
+    /// Returns the number of documents added to the segments
+    /// being built, since the writer was opened.
+    pub fn num_indexed_docs(&self) -> usize {
+        self.num_indexed_docs
+    }
+
+    /// Returns the number of segments flushed by the indexing workers.
+    pub fn num_flushes(&self) -> usize {
+        self.num_flushes
+    }
+
+    /// Returns the number of merges that completed.
+    pub fn num_merges(&self) -> usize {
+        self.num_merges
+    }
+
+    /// Returns the time elapsed since the writer was opened.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the average number of documents indexed per second.
+    pub fn docs_per_sec(&self) -> f64 {
+        let elapsed_secs =
+            self.elapsed.as_secs() as f64 + f64::from(self.elapsed.subsec_nanos()) * 1e-9;
+        if elapsed_secs > 0f64 {
+            self.num_indexed_docs as f64 / elapsed_secs
+        } else {
+            0f64
+        }
+    }
+}
+
+/// Keeps track of the indexing stats, and forwards the
+/// events to the registered observer if any.
+pub(crate) struct IndexingMonitor {
+    observer: RwLock<Option<Arc<IndexingObserver>>>,
+    num_indexed_docs: AtomicUsize,
+    num_flushes: AtomicUsize,
+    num_merges: AtomicUsize,
+    start: Instant,
+}
+
+impl Default for IndexingMonitor {
+    fn default() -> IndexingMonitor {
+        IndexingMonitor {
+            observer: RwLock::new(None),
+            num_indexed_docs: AtomicUsize::default(),
+            num_flushes: AtomicUsize::default(),
+            num_merges: AtomicUsize::default(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl IndexingMonitor {
+    pub fn set_observer(&self, observer: Option<Arc<IndexingObserver>>) {
+        *self.observer.write().unwrap() = observer;
+    }
+
+    pub fn observer(&self) -> Option<Arc<IndexingObserver>> {
+        self.observer.read().unwrap().clone()
+    }
+
+    pub fn stats(&self) -> IndexingStats {
+        IndexingStats {
+            num_indexed_docs: self.num_indexed_docs.load(Ordering::Relaxed),
+            num_flushes: self.num_flushes.load(Ordering::Relaxed),
+            num_merges: self.num_merges.load(Ordering::Relaxed),
+            elapsed: self.start.elapsed(),
+        }
+    }
+
+    pub fn documents_indexed(&self, segment_id: SegmentId, num_docs: u32) {
+        self.num_indexed_docs
+            .fetch_add(num_docs as usize, Ordering::Relaxed);
+        if let Some(observer) = self.observer() {
+            observer.documents_indexed(segment_id, num_docs);
+        }
+    }
+
+    pub fn flush_started(&self, segment_id: SegmentId, num_docs: u32) {
+        if let Some(observer) = self.observer() {
+            observer.flush_started(segment_id, num_docs);
+        }
+    }
+
+    pub fn flush_ended(&self, segment_id: SegmentId, num_docs: u32) {
+        self.num_flushes.fetch_add(1, Ordering::Relaxed);
+        if let Some(observer) = self.observer() {
+            observer.flush_ended(segment_id, num_docs);
+        }
+    }
+
+    pub fn merge_ended(&self, merged_segment_ids: &[SegmentId], merged_segment: &SegmentMeta) {
+        self.num_merges.fetch_add(1, Ordering::Relaxed);
+        if let Some(observer) = self.observer() {
+            observer.merge_ended(merged_segment_ids, merged_segment);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use postings::MultiFieldPostingsWriter;
+use schema::{Field, Term, Type};
+use serde_json::{Map as JsonObject, Value as JsonValue};
+use tokenizer::{BoxedTokenizer, Token};
+use DocId;
+
+/// Gap between the positions of two values of a JSON object,
+/// so that a phrase query never matches across values.
+const POSITION_GAP: u32 = 2;
+
+/// Indexes the leaves of the JSON objects of a document for a field.
+///
+/// Each leaf is indexed under its path, the keys of the nested objects
+/// being joined with dots: `{"size": {"width": 30}}` is indexed under
+/// `size.width`. The texts are tokenized with `tokenizer`, the numbers
+/// are indexed as `i64`, `u64` or `f64` and the booleans as the
+/// `"true"` and `"false"` texts. The elements of the arrays are indexed
+/// under the path of the array, and the `null` values are ignored.
+///
+/// Returns the number of tokens indexed.
+pub(crate) fn index_json_objects(
+    doc: DocId,
+    field: Field,
+    json_objects: &[&JsonObject<String, JsonValue>],
+    tokenizer: &BoxedTokenizer,
+    multifield_postings: &mut MultiFieldPostingsWriter,
+) -> u32 {
+    let mut json_indexer = JsonIndexer {
+        doc,
+        field,
+        tokenizer,
+        multifield_postings,
+        json_path: String::new(),
+        position: 0u32,
+        num_tokens: 0u32,
+    };
+    for json_object in json_objects {
+        json_indexer.index_object(json_object);
+    }
+    json_indexer.num_tokens
+}
+
+struct JsonIndexer<'a> {
+    doc: DocId,
+    field: Field,
+    tokenizer: &'a BoxedTokenizer,
+    multifield_postings: &'a mut MultiFieldPostingsWriter,
+    // path of the value being indexed.
+    json_path: String,
+    // position of the next token.
+    position: u32,
+    num_tokens: u32,
+}
+
+impl<'a> JsonIndexer<'a> {
+    fn index_object(&mut self, json_object: &JsonObject<String, JsonValue>) {
+        for (key, json_value) in json_object {
+            let json_path_len = self.json_path.len();
+            if json_path_len > 0 {
+                self.json_path.push('.');
+            }
+            self.json_path.push_str(key);
+            self.index_value(json_value);
+            self.json_path.truncate(json_path_len);
+        }
+    }
+
+    fn index_value(&mut self, json_value: &JsonValue) {
+        match *json_value {
+            JsonValue::Null => {}
+            JsonValue::Bool(val) => self.index_text(if val { "true" } else { "false" }),
+            JsonValue::Number(ref number) => {
+                let term = if let Some(val) = number.as_i64() {
+                    Term::from_field_json_i64(self.field, &self.json_path, val)
+                } else if let Some(val) = number.as_u64() {
+                    Term::from_field_json_u64(self.field, &self.json_path, val)
+                } else if let Some(val) = number.as_f64() {
+                    Term::from_field_json_f64(self.field, &self.json_path, val)
+                } else {
+                    return;
+                };
+                self.multifield_postings
+                    .subscribe_at(self.doc, self.position, &term);
+                self.position += 1 + POSITION_GAP;
+                self.num_tokens += 1;
+            }
+            JsonValue::String(ref text) => self.index_text(text),
+            JsonValue::Array(ref json_values) => {
+                for json_value in json_values {
+                    self.index_value(json_value);
+                }
+            }
+            JsonValue::Object(ref json_object) => self.index_object(json_object),
+        }
+    }
+
+    fn index_text(&mut self, text: &str) {
+        let mut term = Term::for_json_path(self.field, &self.json_path, Type::Str);
+        let json_path_term_len = term.as_slice().len();
+        let (doc, position) = (self.doc, self.position);
+        let multifield_postings = &mut *self.multifield_postings;
+        let mut end_position = position;
+        let num_tokens = self
+            .tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| {
+                term.truncate(json_path_term_len);
+                term.append_bytes(token.text.as_bytes());
+                let token_position = position + token.position as u32;
+                multifield_postings.subscribe_at(doc, token_position, &term);
+                end_position = token_position + 1;
+            });
+        self.position = end_position + POSITION_GAP;
+        self.num_tokens += num_tokens;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::Count;
+    use query::{PhraseQuery, TermQuery};
+    use schema::{IndexRecordOption, JsonObjectOptions, Schema, Term, STORED, TEXT};
+    use serde_json::{Map as JsonObject, Value as JsonValue};
+    use Index;
+
+    fn json_object(json_value: JsonValue) -> JsonObject<String, JsonValue> {
+        json_value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_index_json_objects() {
+        let mut schema_builder = Schema::builder();
+        let attrs = schema_builder.add_json_object_field("attrs", JsonObjectOptions::from(TEXT));
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(attrs => json_object(json!({
+                    "color": "Dark red",
+                    "size": {"width": 30, "height": 12.5},
+                    "tags": ["new", "sale"],
+                    "available": true,
+                    "discount": null
+                }))))
+                .unwrap();
+            index_writer
+                .add_document(doc!(attrs => json_object(json!({
+                    "color": ["light", "red"],
+                    "size": {"width": 30},
+                    "width": 12.5
+                }))))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |term: Term| {
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(Term::from_field_json_text(attrs, "color", "red")), 2);
+        assert_eq!(count(Term::from_field_json_text(attrs, "color", "dark")), 1);
+        assert_eq!(count(Term::from_field_json_i64(attrs, "size.width", 30)), 2);
+        assert_eq!(
+            count(Term::from_field_json_f64(attrs, "size.height", 12.5)),
+            1
+        );
+        assert_eq!(count(Term::from_field_json_f64(attrs, "width", 12.5)), 1);
+        assert_eq!(count(Term::from_field_json_text(attrs, "width", "12")), 0);
+        assert_eq!(count(Term::from_field_json_text(attrs, "tags", "sale")), 1);
+        assert_eq!(
+            count(Term::from_field_json_text(attrs, "available", "true")),
+            1
+        );
+        assert_eq!(count(Term::from_field_json_text(attrs, "red", "color")), 0);
+        let phrase_count = |words: &[&str]| {
+            let terms = words
+                .iter()
+                .map(|word| Term::from_field_json_text(attrs, "color", word))
+                .collect();
+            searcher.search(&PhraseQuery::new(terms), &Count).unwrap()
+        };
+        assert_eq!(phrase_count(&["dark", "red"]), 1);
+        // the elements of an array are not contiguous.
+        assert_eq!(phrase_count(&["light", "red"]), 0);
+    }
+
+    #[test]
+    fn test_stored_json_objects() {
+        let mut schema_builder = Schema::builder();
+        let attrs = schema_builder.add_json_object_field("attrs", JsonObjectOptions::from(STORED));
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let doc = schema
+            .parse_document(r#"{"attrs": {"color": "red", "size": {"width": 30}}}"#)
+            .unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let stored_doc = searcher
+            .segment_reader(0)
+            .get_store_reader()
+            .get(0)
+            .unwrap();
+        assert_eq!(
+            stored_doc.get_first(attrs).unwrap().json_object_value(),
+            &json_object(json!({"color": "red", "size": {"width": 30}}))
+        );
+        assert_eq!(
+            schema.to_json(&stored_doc),
+            r#"{"attrs":[{"color":"red","size":{"width":30}}]}"#
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use directory::SeekableWrite;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// `MergeCancellation` is a flag shared by a merge
+/// and the `SegmentUpdater` that started it.
+///
+/// Once it is cancelled, all of the writes of the
+/// merged segment fail, which aborts the merge.
+#[derive(Clone, Default)]
+pub struct MergeCancellation(Arc<AtomicBool>);
+
+impl MergeCancellation {
+    /// Cancels the merge.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Returns true if the merge was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn check(&self) -> io::Result<()> {
+        if self.is_cancelled() {
+            // `ErrorKind::Interrupted` would be retried by `write_all`.
+            Err(io::Error::new(io::ErrorKind::Other, "The merge was cancelled."))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Wraps a `Write` so that it fails as soon as
+/// its `MergeCancellation` is cancelled.
+pub struct CancelableWrite<W> {
+    underlying: W,
+    cancellation: MergeCancellation,
+}
+
+impl<W> CancelableWrite<W> {
+    pub fn new(underlying: W, cancellation: MergeCancellation) -> CancelableWrite<W> {
+        CancelableWrite {
+            underlying,
+            cancellation,
+        }
+    }
+}
+
+impl<W: Write> Write for CancelableWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.cancellation.check()?;
+        self.underlying.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.cancellation.check()?;
+        self.underlying.flush()
+    }
+}
+
+impl<W: Seek> Seek for CancelableWrite<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.underlying.seek(pos)
+    }
+}
+
+impl<W: SeekableWrite> SeekableWrite for CancelableWrite<W> {
+    fn terminate(&mut self) -> io::Result<()> {
+        self.cancellation.check()?;
+        self.underlying.terminate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{CancelableWrite, MergeCancellation};
+    use std::io::{Cursor, ErrorKind, Write};
+
+    #[test]
+    fn test_cancelable_write() {
+        let cancellation = MergeCancellation::default();
+        let mut write = CancelableWrite::new(Cursor::new(Vec::new()), cancellation.clone());
+        write.write_all(b"abc").unwrap();
+        assert!(!cancellation.is_cancelled());
+        cancellation.cancel();
+        assert!(cancellation.is_cancelled());
+        assert_eq!(
+            write.write_all(b"def").unwrap_err().kind(),
+            ErrorKind::Other
+        );
+        assert_eq!(write.underlying.get_ref(), b"abc");
+    }
+}
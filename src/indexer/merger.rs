@@ -1,16 +1,20 @@
+use common;
 use core::Segment;
 use core::SegmentReader;
 use core::SerializableSegment;
 use docset::DocSet;
-use fastfield::DeleteBitSet;
+use fastfield;
 use fastfield::FastFieldReader;
 use fastfield::FastFieldSerializer;
+use fastfield::IpAddrFastFieldWriter;
 use fastfield::MultiValueIntFastFieldReader;
 use fieldnorm::FieldNormReader;
 use fieldnorm::FieldNormsSerializer;
 use fieldnorm::FieldNormsWriter;
+use indexer::doc_id_mapping::DocIdMapping;
 use indexer::SegmentSerializer;
 use itertools::Itertools;
+use offsets::OffsetsWriter;
 use postings::InvertedIndexSerializer;
 use postings::Postings;
 use schema::Cardinality;
@@ -18,6 +22,7 @@ use schema::FieldType;
 use schema::{Field, Schema};
 use std::cmp;
 use std::collections::HashMap;
+use std::net::Ipv6Addr;
 use store::StoreWriter;
 use termdict::TermMerger;
 use termdict::TermOrdinal;
@@ -54,33 +59,36 @@ fn compute_total_num_tokens(readers: &[SegmentReader], field: Field) -> u64 {
 pub struct IndexMerger {
     schema: Schema,
     readers: Vec<SegmentReader>,
-    max_doc: u32,
+    doc_id_mapping: DocIdMapping,
+}
+
+/// Returns the doc ids of a segment that are kept in the merged segment,
+/// in the order of the segment.
+fn kept_doc_ids<'a>(old_to_new_doc_ids: &'a [Option<DocId>]) -> impl Iterator<Item = DocId> + 'a {
+    old_to_new_doc_ids
+        .iter()
+        .enumerate()
+        .filter(|&(_, new_doc_id_opt)| new_doc_id_opt.is_some())
+        .map(|(doc_id, _)| doc_id as DocId)
 }
 
 fn compute_min_max_val(
     u64_reader: &FastFieldReader<u64>,
-    max_doc: DocId,
-    delete_bitset_opt: Option<&DeleteBitSet>,
+    old_to_new_doc_ids: &[Option<DocId>],
 ) -> Option<(u64, u64)> {
-    if max_doc == 0 {
+    if old_to_new_doc_ids.is_empty() {
         None
+    } else if old_to_new_doc_ids.iter().all(Option::is_some) {
+        // all documents are kept,
+        // we can use the previous min_val, max_val.
+        Some((u64_reader.min_value(), u64_reader.max_value()))
     } else {
-        match delete_bitset_opt {
-            Some(delete_bitset) => {
-                // some deleted documents,
-                // we need to recompute the max / min
-                (0..max_doc)
-                    .filter(|doc_id| !delete_bitset.is_deleted(*doc_id))
-                    .map(|doc_id| u64_reader.get(doc_id))
-                    .minmax()
-                    .into_option()
-            }
-            None => {
-                // no deleted documents,
-                // we can use the previous min_val, max_val.
-                Some((u64_reader.min_value(), u64_reader.max_value()))
-            }
-        }
+        // some documents are dropped,
+        // we need to recompute the max / min
+        kept_doc_ids(old_to_new_doc_ids)
+            .map(|doc_id| u64_reader.get(doc_id))
+            .minmax()
+            .into_option()
     }
 }
 
@@ -142,38 +150,132 @@ impl DeltaComputer {
 impl IndexMerger {
     pub fn open(schema: Schema, segments: &[Segment]) -> Result<IndexMerger> {
         let mut readers = vec![];
-        let mut max_doc: u32 = 0u32;
         for segment in segments {
             if segment.meta().num_docs() > 0 {
-                let reader = SegmentReader::open(segment)?;
-                max_doc += reader.num_docs();
-                readers.push(reader);
+                readers.push(SegmentReader::open(segment)?);
             }
         }
-        Ok(IndexMerger {
+        Ok(IndexMerger::for_readers(schema, readers))
+    }
+
+    /// Creates a merger given the readers of the segments to merge.
+    ///
+    /// The documents deleted in the readers are not merged.
+    pub(crate) fn for_readers(schema: Schema, readers: Vec<SegmentReader>) -> IndexMerger {
+        let readers: Vec<SegmentReader> = readers
+            .into_iter()
+            .filter(|reader| reader.num_docs() > 0)
+            .collect();
+        let doc_id_mapping = DocIdMapping::compact_deletes(&readers);
+        IndexMerger {
             schema,
             readers,
-            max_doc,
-        })
+            doc_id_mapping,
+        }
+    }
+
+    /// Opens a reader for `field` on each of the segments.
+    ///
+    /// The segments written before `field` was added to the schema
+    /// have no data for `field`, and get `None`.
+    fn field_readers<T, F>(&self, field: Field, open_reader: F) -> Result<Vec<Option<T>>>
+    where
+        F: Fn(&SegmentReader) -> fastfield::Result<T>,
+    {
+        self.readers
+            .iter()
+            .map(|reader| {
+                if reader.has_field(field) {
+                    Ok(Some(open_reader(reader)?))
+                } else {
+                    Ok(None)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the value of the single value fast field `field`
+    /// for the documents that do not have any.
+    fn default_fast_value(&self, field: Field) -> u64 {
+        match *self.schema.get_field_entry(field).field_type() {
+            FieldType::I64(_) | FieldType::Date(_) => common::i64_to_u64(0i64),
+            FieldType::F64(_) => common::f64_to_u64(0.0f64),
+            _ => 0u64,
+        }
     }
 
     fn write_fieldnorms(&self, fieldnorms_serializer: &mut FieldNormsSerializer) -> Result<()> {
         let fields = FieldNormsWriter::fields_with_fieldnorm(&self.schema);
-        let mut fieldnorms_data = Vec::with_capacity(self.max_doc as usize);
+        let mut fieldnorms_data = Vec::with_capacity(self.doc_id_mapping.num_docs() as usize);
         for field in fields {
             fieldnorms_data.clear();
-            for reader in &self.readers {
-                let fieldnorms_reader = reader.get_fieldnorms_reader(field);
-                for doc_id in reader.doc_ids_alive() {
-                    let fieldnorm_id = fieldnorms_reader.fieldnorm_id(doc_id);
-                    fieldnorms_data.push(fieldnorm_id);
-                }
+            let fieldnorms_readers: Vec<FieldNormReader> = self
+                .readers
+                .iter()
+                .map(|reader| reader.get_fieldnorms_reader(field))
+                .collect();
+            for (segment_ord, doc_id) in self.doc_id_mapping.iter_old_doc_addrs() {
+                let fieldnorm_id = fieldnorms_readers[segment_ord].fieldnorm_id(doc_id);
+                fieldnorms_data.push(fieldnorm_id);
             }
             fieldnorms_serializer.serialize_field(field, &fieldnorms_data[..])?;
         }
         Ok(())
     }
 
+    fn write_offsets(&self, offsets_serializer: &mut FastFieldSerializer) -> Result<()> {
+        let mut vals = Vec::with_capacity(100);
+        for field in OffsetsWriter::fields_with_offsets(&self.schema) {
+            // segments that were indexed without offsets for the field
+            // have no offsets for any of their documents.
+            let offsets_readers: Vec<_> = self
+                .readers
+                .iter()
+                .map(|reader| reader.offsets_reader(field))
+                .collect();
+
+            // first pass, computing the number of values and their maximum,
+            // required to bitpack them.
+            let mut num_vals = 0u64;
+            let mut max_value = 0u64;
+            for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+                if let Some(ref offsets_reader) = offsets_readers[segment_ord] {
+                    offsets_reader.vals(doc, &mut vals);
+                    num_vals += vals.len() as u64;
+                    max_value = vals.iter().cloned().fold(max_value, cmp::max);
+                }
+            }
+
+            {
+                let mut serialize_idx =
+                    offsets_serializer.new_u64_fast_field_with_idx(field, 0, num_vals, 0)?;
+                let mut idx = 0u64;
+                for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+                    serialize_idx.add_val(idx)?;
+                    if let Some(ref offsets_reader) = offsets_readers[segment_ord] {
+                        offsets_reader.vals(doc, &mut vals);
+                        idx += vals.len() as u64;
+                    }
+                }
+                serialize_idx.add_val(idx)?;
+                serialize_idx.close_field()?;
+            }
+
+            let mut serialize_vals =
+                offsets_serializer.new_u64_fast_field_with_idx(field, 0, max_value, 1)?;
+            for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+                if let Some(ref offsets_reader) = offsets_readers[segment_ord] {
+                    offsets_reader.vals(doc, &mut vals);
+                    for &val in &vals {
+                        serialize_vals.add_val(val)?;
+                    }
+                }
+            }
+            serialize_vals.close_field()?;
+        }
+        Ok(())
+    }
+
     fn write_fast_fields(
         &self,
         fast_field_serializer: &mut FastFieldSerializer,
@@ -188,57 +290,98 @@ impl IndexMerger {
                         .remove(&field)
                         .expect("Logic Error in Tantivy (Please report). HierarchicalFact field should have required a\
                         `term_ordinal_mapping`.");
-                    self.write_hierarchical_facet_field(
+                    self.write_term_id_fast_field(
                         field,
                         &term_ordinal_mapping,
                         fast_field_serializer,
                     )?;
                 }
-                FieldType::U64(ref options) | FieldType::I64(ref options) => {
-                    match options.get_fastfield_cardinality() {
-                        Some(Cardinality::SingleValue) => {
-                            self.write_single_fast_field(field, fast_field_serializer)?;
-                        }
-                        Some(Cardinality::MultiValues) => {
-                            self.write_multi_fast_field(field, fast_field_serializer)?;
-                        }
-                        None => {}
+                FieldType::U64(ref options)
+                | FieldType::I64(ref options)
+                | FieldType::F64(ref options) => self.write_int_fast_field(
+                    field,
+                    options.get_fastfield_cardinality(),
+                    fast_field_serializer,
+                )?,
+                FieldType::Date(ref options) => self.write_int_fast_field(
+                    field,
+                    options.get_fastfield_cardinality(),
+                    fast_field_serializer,
+                )?,
+                FieldType::Str(ref options) => {
+                    if options.is_fast() {
+                        let term_ordinal_mapping = term_ord_mappings.remove(&field).expect(
+                            "Logic Error in Tantivy (Please report). String fast fields should \
+                             have required a `term_ordinal_mapping`.",
+                        );
+                        self.write_term_id_fast_field(
+                            field,
+                            &term_ordinal_mapping,
+                            fast_field_serializer,
+                        )?;
+                    }
+                }
+                FieldType::Bytes(ref options) => {
+                    if options.is_fast() {
+                        self.write_bytes_fast_field(field, fast_field_serializer)?;
                     }
                 }
-                FieldType::Str(_) => {
-                    // We don't handle str fast field for the moment
-                    // They can be implemented using what is done
-                    // for facets in the future.
+                FieldType::IpAddr(ref options) => {
+                    if options.is_fast() {
+                        self.write_ip_addr_fast_field(field, fast_field_serializer)?;
+                    }
                 }
-                FieldType::Bytes => {
-                    self.write_bytes_fast_field(field, fast_field_serializer)?;
+                FieldType::JsonObject(_) => {
+                    // JSON object fields have no fast field.
                 }
             }
         }
         Ok(())
     }
 
-    // used both to merge field norms, `u64/i64` single fast fields.
+    fn write_int_fast_field(
+        &self,
+        field: Field,
+        cardinality_opt: Option<Cardinality>,
+        fast_field_serializer: &mut FastFieldSerializer,
+    ) -> Result<()> {
+        match cardinality_opt {
+            Some(Cardinality::SingleValue) => {
+                self.write_single_fast_field(field, fast_field_serializer)
+            }
+            Some(Cardinality::MultiValues) => {
+                self.write_multi_fast_field(field, fast_field_serializer)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // used both to merge field norms, `u64/i64/f64/date` single fast fields.
     fn write_single_fast_field(
         &self,
         field: Field,
         fast_field_serializer: &mut FastFieldSerializer,
     ) -> Result<()> {
-        let mut u64_readers = vec![];
+        let u64_readers: Vec<Option<FastFieldReader<u64>>> =
+            self.field_readers(field, |reader| reader.raw_fast_field_reader(field))?;
+        let default_value = self.default_fast_value(field);
         let mut min_value = u64::max_value();
         let mut max_value = u64::min_value();
 
-        for reader in &self.readers {
-            let u64_reader: FastFieldReader<u64> = reader.fast_field_reader(field)?;
-            if let Some((seg_min_val, seg_max_val)) =
-                compute_min_max_val(&u64_reader, reader.max_doc(), reader.delete_bitset())
-            {
-                // the segment has some non-deleted documents
+        for (segment_ord, u64_reader_opt) in u64_readers.iter().enumerate() {
+            let old_to_new_doc_ids = self.doc_id_mapping.old_to_new_doc_ids(segment_ord);
+            let min_max_val_opt = match *u64_reader_opt {
+                Some(ref u64_reader) => compute_min_max_val(u64_reader, old_to_new_doc_ids),
+                // The field was added to the schema after the segment
+                // was written: its documents get the default value.
+                None if old_to_new_doc_ids.iter().any(Option::is_some) => {
+                    Some((default_value, default_value))
+                }
+                None => None,
+            };
+            if let Some((seg_min_val, seg_max_val)) = min_max_val_opt {
                 min_value = cmp::min(min_value, seg_min_val);
                 max_value = cmp::max(max_value, seg_max_val);
-                u64_readers.push((reader.max_doc(), u64_reader, reader.delete_bitset()));
-            } else {
-                // all documents have been deleted.
             }
         }
 
@@ -250,16 +393,12 @@ impl IndexMerger {
 
         let mut fast_single_field_serializer =
             fast_field_serializer.new_u64_fast_field(field, min_value, max_value)?;
-        for (max_doc, u64_reader, delete_bitset_opt) in u64_readers {
-            for doc_id in 0u32..max_doc {
-                let is_deleted = delete_bitset_opt
-                    .map(|delete_bitset| delete_bitset.is_deleted(doc_id))
-                    .unwrap_or(false);
-                if !is_deleted {
-                    let val = u64_reader.get(doc_id);
-                    fast_single_field_serializer.add_val(val)?;
-                }
-            }
+        for (segment_ord, doc_id) in self.doc_id_mapping.iter_old_doc_addrs() {
+            let val = u64_readers[segment_ord]
+                .as_ref()
+                .map(|u64_reader| u64_reader.get(doc_id))
+                .unwrap_or(default_value);
+            fast_single_field_serializer.add_val(val)?;
         }
 
         fast_single_field_serializer.close_field()?;
@@ -271,24 +410,29 @@ impl IndexMerger {
         field: Field,
         fast_field_serializer: &mut FastFieldSerializer,
     ) -> Result<()> {
-        let mut total_num_vals = 0u64;
+        let idx_readers: Vec<Option<FastFieldReader<u64>>> = self
+            .field_readers(field, |reader| {
+                reader.fast_field_reader_with_idx::<u64>(field, 0)
+            })?;
+        let num_vals = |idx_reader: &FastFieldReader<u64>, doc: DocId| {
+            idx_reader.get(doc + 1) - idx_reader.get(doc)
+        };
 
         // In the first pass, we compute the total number of vals.
         //
         // This is required by the bitpacker, as it needs to know
         // what should be the bit length use for bitpacking.
-        for reader in &self.readers {
-            let idx_reader = reader.fast_field_reader_with_idx::<u64>(field, 0)?;
-            if let Some(delete_bitset) = reader.delete_bitset() {
-                for doc in 0u32..reader.max_doc() {
-                    if !delete_bitset.is_deleted(doc) {
-                        let start = idx_reader.get(doc);
-                        let end = idx_reader.get(doc + 1);
-                        total_num_vals += end - start;
+        let mut total_num_vals = 0u64;
+        for (segment_ord, idx_reader_opt) in idx_readers.iter().enumerate() {
+            if let Some(ref idx_reader) = *idx_reader_opt {
+                if self.doc_id_mapping.keeps_all_docs(segment_ord) {
+                    total_num_vals += idx_reader.max_value();
+                } else {
+                    let old_to_new_doc_ids = self.doc_id_mapping.old_to_new_doc_ids(segment_ord);
+                    for doc in kept_doc_ids(old_to_new_doc_ids) {
+                        total_num_vals += num_vals(idx_reader, doc);
                     }
                 }
-            } else {
-                total_num_vals += idx_reader.max_value();
             }
         }
 
@@ -297,13 +441,11 @@ impl IndexMerger {
         let mut serialize_idx =
             fast_field_serializer.new_u64_fast_field_with_idx(field, 0, total_num_vals, 0)?;
         let mut idx = 0;
-        for reader in &self.readers {
-            let idx_reader = reader.fast_field_reader_with_idx::<u64>(field, 0)?;
-            for doc in reader.doc_ids_alive() {
-                serialize_idx.add_val(idx)?;
-                let start = idx_reader.get(doc);
-                let end = idx_reader.get(doc + 1);
-                idx += end - start;
+        for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+            serialize_idx.add_val(idx)?;
+            // the documents of the segments without the field have no values.
+            if let Some(ref idx_reader) = idx_readers[segment_ord] {
+                idx += num_vals(idx_reader, doc);
             }
         }
         serialize_idx.add_val(idx)?;
@@ -311,7 +453,7 @@ impl IndexMerger {
         Ok(())
     }
 
-    fn write_hierarchical_facet_field(
+    fn write_term_id_fast_field(
         &self,
         field: Field,
         term_ordinal_mappings: &TermOrdinalMapping,
@@ -325,19 +467,19 @@ impl IndexMerger {
         self.write_fast_field_idx(field, fast_field_serializer)?;
 
         // We can now write the actual fast field values.
-        // In the case of hierarchical facets, they are actually term ordinals.
+        // In the case of hierarchical facets and string fast fields,
+        // they are actually term ordinals.
         let max_term_ord = term_ordinal_mappings.max_term_ord();
         {
+            let ff_readers: Vec<Option<MultiValueIntFastFieldReader<u64>>> =
+                self.field_readers(field, |reader| reader.multi_fast_field_reader(field))?;
             let mut serialize_vals =
                 fast_field_serializer.new_u64_fast_field_with_idx(field, 0u64, max_term_ord, 1)?;
             let mut vals = Vec::with_capacity(100);
-            for (segment_ord, segment_reader) in self.readers.iter().enumerate() {
-                let term_ordinal_mapping: &[TermOrdinal] =
-                    term_ordinal_mappings.get_segment(segment_ord);
-                let ff_reader: MultiValueIntFastFieldReader<u64> =
-                    segment_reader.multi_fast_field_reader(field)?;
-                // TODO optimize if no deletes
-                for doc in segment_reader.doc_ids_alive() {
+            for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+                if let Some(ref ff_reader) = ff_readers[segment_ord] {
+                    let term_ordinal_mapping: &[TermOrdinal] =
+                        term_ordinal_mappings.get_segment(segment_ord);
                     ff_reader.get_vals(doc, &mut vals);
                     for &prev_term_ord in &vals {
                         let new_term_ord = term_ordinal_mapping[prev_term_ord as usize];
@@ -362,6 +504,9 @@ impl IndexMerger {
         // First we merge the idx fast field.
         self.write_fast_field_idx(field, fast_field_serializer)?;
 
+        let ff_readers: Vec<Option<MultiValueIntFastFieldReader<u64>>> =
+            self.field_readers(field, |reader| reader.raw_multi_fast_field_reader(field))?;
+
         let mut min_value = u64::max_value();
         let mut max_value = u64::min_value();
 
@@ -373,10 +518,8 @@ impl IndexMerger {
         // Computing those is non-trivial if some documents are deleted.
         // We go through a complete first pass to compute the minimum and the
         // maximum value and initialize our Serializer.
-        for reader in &self.readers {
-            let ff_reader: MultiValueIntFastFieldReader<u64> =
-                reader.multi_fast_field_reader(field)?;
-            for doc in reader.doc_ids_alive() {
+        for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+            if let Some(ref ff_reader) = ff_readers[segment_ord] {
                 ff_reader.get_vals(doc, &mut vals);
                 for &val in &vals {
                     min_value = cmp::min(val, min_value);
@@ -395,11 +538,8 @@ impl IndexMerger {
         {
             let mut serialize_vals = fast_field_serializer
                 .new_u64_fast_field_with_idx(field, min_value, max_value, 1)?;
-            for reader in &self.readers {
-                let ff_reader: MultiValueIntFastFieldReader<u64> =
-                    reader.multi_fast_field_reader(field)?;
-                // TODO optimize if no deletes
-                for doc in reader.doc_ids_alive() {
+            for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+                if let Some(ref ff_reader) = ff_readers[segment_ord] {
                     ff_reader.get_vals(doc, &mut vals);
                     for &val in &vals {
                         serialize_vals.add_val(val)?;
@@ -418,11 +558,11 @@ impl IndexMerger {
     ) -> Result<()> {
         self.write_fast_field_idx(field, fast_field_serializer)?;
 
+        let bytes_readers =
+            self.field_readers(field, |reader| reader.bytes_fast_field_reader(field))?;
         let mut serialize_vals = fast_field_serializer.new_bytes_fast_field_with_idx(field, 1)?;
-        for reader in &self.readers {
-            let bytes_reader = reader.bytes_fast_field_reader(field)?;
-            // TODO: optimize if no deletes
-            for doc in reader.doc_ids_alive() {
+        for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+            if let Some(ref bytes_reader) = bytes_readers[segment_ord] {
                 let val = bytes_reader.get_val(doc);
                 serialize_vals.write_all(val)?;
             }
@@ -432,6 +572,27 @@ impl IndexMerger {
         Ok(())
     }
 
+    fn write_ip_addr_fast_field(
+        &self,
+        field: Field,
+        fast_field_serializer: &mut FastFieldSerializer,
+    ) -> Result<()> {
+        let ip_addr_readers =
+            self.field_readers(field, |reader| reader.ip_addr_fast_field_reader(field))?;
+        // the addresses are buffered, as the number of bits of the
+        // two underlying fast fields depends on all of them.
+        let mut ip_addr_writer = IpAddrFastFieldWriter::new(field);
+        for (segment_ord, doc) in self.doc_id_mapping.iter_old_doc_addrs() {
+            let val = ip_addr_readers[segment_ord]
+                .as_ref()
+                .map(|ip_addr_reader| ip_addr_reader.get(doc))
+                .unwrap_or_else(|| Ipv6Addr::from(0u128));
+            ip_addr_writer.add_val(val);
+        }
+        ip_addr_writer.serialize(fast_field_serializer)?;
+        Ok(())
+    }
+
     fn write_postings_for_field(
         &self,
         indexed_field: Field,
@@ -455,46 +616,29 @@ impl IndexMerger {
             max_term_ords.push(terms.num_terms() as u64);
         }
 
-        let mut term_ord_mapping_opt = if *field_type == FieldType::HierarchicalFacet {
+        let has_term_ords = match *field_type {
+            FieldType::HierarchicalFacet => true,
+            FieldType::Str(ref options) => options.is_fast(),
+            _ => false,
+        };
+        let mut term_ord_mapping_opt = if has_term_ords {
             Some(TermOrdinalMapping::new(max_term_ords))
         } else {
             None
         };
 
         let mut merged_terms = TermMerger::new(field_term_streams);
-        let mut max_doc = 0;
 
-        // map from segment doc ids to the resulting merged segment doc id.
-        let mut merged_doc_id_map: Vec<Vec<Option<DocId>>> = Vec::with_capacity(self.readers.len());
-
-        for reader in &self.readers {
-            let mut segment_local_map = Vec::with_capacity(reader.max_doc() as usize);
-            for doc_id in 0..reader.max_doc() {
-                if reader.is_deleted(doc_id) {
-                    segment_local_map.push(None);
-                } else {
-                    segment_local_map.push(Some(max_doc));
-                    max_doc += 1u32;
-                }
-            }
-            merged_doc_id_map.push(segment_local_map);
-        }
+        // If the doc ids are not stacked, the postings of a term
+        // need to be sorted by their new doc id before being written.
+        let is_stacked = self.doc_id_mapping.is_stacked();
+        let mut remapped_docs: Vec<(DocId, u32, Vec<u32>)> = Vec::new();
 
         // The total number of tokens will only be exact when there has been no deletes.
         //
         // Otherwise, we approximate by removing deleted documents proportionally.
         let total_num_tokens: u64 = compute_total_num_tokens(&self.readers, indexed_field);
 
-        // Create the total list of doc ids
-        // by stacking the doc ids from the different segment.
-        //
-        // In the new segments, the doc id from the different
-        // segment are stacked so that :
-        // - Segment 0's doc ids become doc id [0, seg.max_doc]
-        // - Segment 1's doc ids become  [seg0.max_doc, seg0.max_doc + seg.max_doc]
-        // - Segment 2's doc ids become  [seg0.max_doc + seg1.max_doc,
-        //                                seg0.max_doc + seg1.max_doc + seg2.max_doc]
-        // ...
         let mut field_serializer = serializer.new_field(indexed_field, total_num_tokens)?;
 
         let field_entry = self.schema.get_field_entry(indexed_field);
@@ -516,11 +660,12 @@ impl IndexMerger {
                     let segment_ord = heap_item.segment_ord;
                     let term_info = heap_item.streamer.value();
                     let segment_reader = &self.readers[heap_item.segment_ord];
+                    let old_to_new_doc_ids = self.doc_id_mapping.old_to_new_doc_ids(segment_ord);
                     let inverted_index = segment_reader.inverted_index(indexed_field);
                     let mut segment_postings = inverted_index
                         .read_postings_from_terminfo(term_info, segment_postings_option);
                     while segment_postings.advance() {
-                        if !segment_reader.is_deleted(segment_postings.doc()) {
+                        if old_to_new_doc_ids[segment_postings.doc() as usize].is_some() {
                             return Some((segment_ord, segment_postings));
                         }
                     }
@@ -548,7 +693,7 @@ impl IndexMerger {
                 // We can now serialize this postings, by pushing each document to the
                 // postings serializer.
                 for (segment_ord, mut segment_postings) in segment_postings {
-                    let old_to_new_doc_id = &merged_doc_id_map[segment_ord];
+                    let old_to_new_doc_ids = self.doc_id_mapping.old_to_new_doc_ids(segment_ord);
                     loop {
                         let doc = segment_postings.doc();
 
@@ -560,19 +705,28 @@ impl IndexMerger {
                         //
                         //  For this reason, we cannot use a `while segment_postings.advance()` loop.
 
-                        // deleted doc are skipped as they do not have a `remapped_doc_id`.
-                        if let Some(remapped_doc_id) = old_to_new_doc_id[doc as usize] {
+                        // dropped docs are skipped as they do not have a `remapped_doc_id`.
+                        if let Some(remapped_doc_id) = old_to_new_doc_ids[doc as usize] {
                             // we make sure to only write the term iff
                             // there is at least one document.
                             let term_freq = segment_postings.term_freq();
                             segment_postings.positions(&mut positions_buffer);
 
-                            let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                            field_serializer.write_doc(
-                                remapped_doc_id,
-                                term_freq,
-                                delta_positions,
-                            )?;
+                            if is_stacked {
+                                let delta_positions =
+                                    delta_computer.compute_delta(&positions_buffer);
+                                field_serializer.write_doc(
+                                    remapped_doc_id,
+                                    term_freq,
+                                    delta_positions,
+                                )?;
+                            } else {
+                                remapped_docs.push((
+                                    remapped_doc_id,
+                                    term_freq,
+                                    positions_buffer.clone(),
+                                ));
+                            }
                         }
                         if !segment_postings.advance() {
                             break;
@@ -580,6 +734,12 @@ impl IndexMerger {
                     }
                 }
 
+                remapped_docs.sort_by_key(|&(remapped_doc_id, _, _)| remapped_doc_id);
+                for (remapped_doc_id, term_freq, positions) in remapped_docs.drain(..) {
+                    let delta_positions = delta_computer.compute_delta(&positions);
+                    field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions)?;
+                }
+
                 // closing the term.
                 field_serializer.close_term()?;
             }
@@ -609,15 +769,27 @@ impl IndexMerger {
     }
 
     fn write_storable_fields(&self, store_writer: &mut StoreWriter) -> Result<()> {
-        for reader in &self.readers {
-            let store_reader = reader.get_store_reader();
-            if reader.num_deleted_docs() > 0 {
-                for doc_id in reader.doc_ids_alive() {
+        let store_readers: Vec<_> = self
+            .readers
+            .iter()
+            .map(|reader| reader.get_store_reader())
+            .collect();
+        if !self.doc_id_mapping.is_stacked() {
+            for (segment_ord, doc_id) in self.doc_id_mapping.iter_old_doc_addrs() {
+                let doc = store_readers[segment_ord].get(doc_id)?;
+                store_writer.store(&doc)?;
+            }
+            return Ok(());
+        }
+        for (segment_ord, store_reader) in store_readers.iter().enumerate() {
+            if self.doc_id_mapping.keeps_all_docs(segment_ord) {
+                store_writer.stack(store_reader)?;
+            } else {
+                let old_to_new_doc_ids = self.doc_id_mapping.old_to_new_doc_ids(segment_ord);
+                for doc_id in kept_doc_ids(old_to_new_doc_ids) {
                     let doc = store_reader.get(doc_id)?;
                     store_writer.store(&doc)?;
                 }
-            } else {
-                store_writer.stack(&store_reader)?;
             }
         }
         Ok(())
@@ -628,10 +800,11 @@ impl SerializableSegment for IndexMerger {
     fn write(&self, mut serializer: SegmentSerializer) -> Result<u32> {
         let term_ord_mappings = self.write_postings(serializer.get_postings_serializer())?;
         self.write_fieldnorms(serializer.get_fieldnorms_serializer())?;
+        self.write_offsets(serializer.get_offsets_serializer())?;
         self.write_fast_fields(serializer.get_fast_field_serializer(), term_ord_mappings)?;
         self.write_storable_fields(serializer.get_store_writer())?;
         serializer.close()?;
-        Ok(self.max_doc)
+        Ok(self.doc_id_mapping.num_docs())
     }
 }
 
@@ -672,7 +845,8 @@ mod tests {
         let text_field = schema_builder.add_text_field("text", text_fieldtype);
         let score_fieldtype = schema::IntOptions::default().set_fast(Cardinality::SingleValue);
         let score_field = schema_builder.add_u64_field("score", score_fieldtype);
-        let bytes_score_field = schema_builder.add_bytes_field("score_bytes");
+        let bytes_score_field = schema_builder
+            .add_bytes_field("score_bytes", schema::BytesOptions::default().set_fast());
         let index = Index::create_in_ram(schema_builder.build());
 
         let add_score_bytes = |doc: &mut Document, score: u32| {
@@ -692,21 +866,21 @@ mod tests {
                     doc.add_text(text_field, "af b");
                     doc.add_u64(score_field, 3);
                     add_score_bytes(&mut doc, 3);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c");
                     doc.add_u64(score_field, 5);
                     add_score_bytes(&mut doc, 5);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c d");
                     doc.add_u64(score_field, 7);
                     add_score_bytes(&mut doc, 7);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().expect("committed");
             }
@@ -718,14 +892,14 @@ mod tests {
                     doc.add_text(text_field, "af b");
                     doc.add_u64(score_field, 11);
                     add_score_bytes(&mut doc, 11);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c g");
                     doc.add_u64(score_field, 13);
                     add_score_bytes(&mut doc, 13);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().expect("Commit failed");
             }
@@ -833,7 +1007,8 @@ mod tests {
         let text_field = schema_builder.add_text_field("text", text_fieldtype);
         let score_fieldtype = schema::IntOptions::default().set_fast(Cardinality::SingleValue);
         let score_field = schema_builder.add_u64_field("score", score_fieldtype);
-        let bytes_score_field = schema_builder.add_bytes_field("score_bytes");
+        let bytes_score_field = schema_builder
+            .add_bytes_field("score_bytes", schema::BytesOptions::default().set_fast());
         let index = Index::create_in_ram(schema_builder.build());
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
 
@@ -856,22 +1031,30 @@ mod tests {
 
         {
             // a first commit
-            index_writer.add_document(doc!(
-                text_field => "a b d",
-                score_field => 1u64,
-                bytes_score_field => vec![0u8, 0, 0, 1],
-            ));
-            index_writer.add_document(doc!(
-                text_field => "b c",
-                score_field => 2u64,
-                bytes_score_field => vec![0u8, 0, 0, 2],
-            ));
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
-            index_writer.add_document(doc!(
-                text_field => "c d",
-                score_field => 3u64,
-                bytes_score_field => vec![0u8, 0, 0, 3],
-            ));
+            index_writer
+                .add_document(doc!(
+                    text_field => "a b d",
+                    score_field => 1u64,
+                    bytes_score_field => vec![0u8, 0, 0, 1],
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "b c",
+                    score_field => 2u64,
+                    bytes_score_field => vec![0u8, 0, 0, 2],
+                ))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "c d",
+                    score_field => 3u64,
+                    bytes_score_field => vec![0u8, 0, 0, 3],
+                ))
+                .unwrap();
             index_writer.commit().expect("committed");
             index.load_searchers().unwrap();
             let ref searcher = *index.searcher();
@@ -897,28 +1080,40 @@ mod tests {
         }
         {
             // a second commit
-            index_writer.add_document(doc!(
-                text_field => "a d e",
-                score_field => 4_000u64,
-                bytes_score_field => vec![0u8, 0, 0, 4],
-            ));
-            index_writer.add_document(doc!(
-                text_field => "e f",
-                score_field => 5_000u64,
-                bytes_score_field => vec![0u8, 0, 0, 5],
-            ));
-            index_writer.delete_term(Term::from_field_text(text_field, "a"));
-            index_writer.delete_term(Term::from_field_text(text_field, "f"));
-            index_writer.add_document(doc!(
-                text_field => "f g",
-                score_field => 6_000u64,
-                bytes_score_field => vec![0u8, 0, 23, 112],
-            ));
-            index_writer.add_document(doc!(
-                text_field => "g h",
-                score_field => 7_000u64,
-                bytes_score_field => vec![0u8, 0, 27, 88],
-            ));
+            index_writer
+                .add_document(doc!(
+                    text_field => "a d e",
+                    score_field => 4_000u64,
+                    bytes_score_field => vec![0u8, 0, 0, 4],
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "e f",
+                    score_field => 5_000u64,
+                    bytes_score_field => vec![0u8, 0, 0, 5],
+                ))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "a"))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "f"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "f g",
+                    score_field => 6_000u64,
+                    bytes_score_field => vec![0u8, 0, 23, 112],
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "g h",
+                    score_field => 7_000u64,
+                    bytes_score_field => vec![0u8, 0, 27, 88],
+                ))
+                .unwrap();
             index_writer.commit().expect("committed");
             index.load_searchers().unwrap();
             let searcher = index.searcher();
@@ -1025,7 +1220,9 @@ mod tests {
         }
         {
             // test a commit with only deletes
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
             index_writer.commit().unwrap();
 
             index.load_searchers().unwrap();
@@ -1124,7 +1321,9 @@ mod tests {
 
         {
             // Test removing all docs
-            index_writer.delete_term(Term::from_field_text(text_field, "g"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "g"))
+                .unwrap();
             let segment_ids = index
                 .searchable_segment_ids()
                 .expect("Searchable segments failed.");
@@ -1141,6 +1340,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge_fieldnorms() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let id_field = schema_builder.add_u64_field("id", schema::INT_INDEXED | schema::FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", id_field => 1u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b", id_field => 2u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b c", id_field => 3u64))
+                .unwrap();
+            index_writer.add_document(doc!(id_field => 0u64)).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(id_field, 2u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            let segment_ids = index
+                .searchable_segment_ids()
+                .expect("Searchable segments failed.");
+            index_writer
+                .merge(&segment_ids)
+                .expect("Failed to initiate merge")
+                .wait()
+                .expect("Merging failed");
+            index_writer.wait_merging_threads().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let id_reader = segment_reader.fast_field_reader::<u64>(id_field).unwrap();
+        let fieldnorms_reader = segment_reader.get_fieldnorms_reader(text_field);
+        let mut fieldnorms: Vec<(u64, u32)> = (0..segment_reader.max_doc())
+            .map(|doc| (id_reader.get(doc), fieldnorms_reader.fieldnorm(doc)))
+            .collect();
+        fieldnorms.sort();
+        assert_eq!(fieldnorms, vec![(0, 0), (1, 1), (3, 3)]);
+    }
+
+    #[test]
+    fn test_merge_signed_and_float_fast_fields() {
+        let mut schema_builder = schema::Schema::builder();
+        let i64_field = schema_builder.add_i64_field("i64", schema::FAST);
+        let f64_field = schema_builder.add_f64_field("f64", schema::FAST);
+        let multi_f64_field = schema_builder.add_f64_field(
+            "multi_f64",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(
+                    i64_field => -3i64,
+                    f64_field => -1.5f64,
+                    multi_f64_field => 2.5f64,
+                    multi_f64_field => -0.5f64
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(i64_field => 7i64, f64_field => 0.25f64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            let segment_ids = index
+                .searchable_segment_ids()
+                .expect("Searchable segments failed.");
+            index_writer
+                .merge(&segment_ids)
+                .expect("Failed to initiate merge")
+                .wait()
+                .expect("Merging failed");
+            index_writer.wait_merging_threads().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let i64_reader = segment_reader.fast_field_reader::<i64>(i64_field).unwrap();
+        let f64_reader = segment_reader.fast_field_reader::<f64>(f64_field).unwrap();
+        let mut values: Vec<(i64, f64)> = (0..segment_reader.max_doc())
+            .map(|doc| (i64_reader.get(doc), f64_reader.get(doc)))
+            .collect();
+        values.sort_by_key(|&(i64_val, _)| i64_val);
+        assert_eq!(values, vec![(-3, -1.5), (7, 0.25)]);
+        let multi_f64_reader = segment_reader
+            .multi_fast_field_reader::<f64>(multi_f64_field)
+            .unwrap();
+        let mut vals = Vec::new();
+        let doc = (0..segment_reader.max_doc())
+            .find(|&doc| i64_reader.get(doc) == -3)
+            .unwrap();
+        multi_f64_reader.get_vals(doc, &mut vals);
+        assert_eq!(&vals, &[2.5, -0.5]);
+    }
+
     #[test]
     fn test_merge_facets() {
         let mut schema_builder = schema::Schema::builder();
@@ -1153,7 +1456,7 @@ mod tests {
                 for facet in doc_facets {
                     doc.add_facet(facet_field, Facet::from(facet));
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             };
 
             index_doc(&mut index_writer, &["/top/a/firstdoc", "/top/b"]);
@@ -1238,7 +1541,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             let facet = Facet::from_path(vec!["top", "a", "firstdoc"]);
             let facet_term = Term::from_facet(facet_field, &facet);
-            index_writer.delete_term(facet_term);
+            index_writer.delete_term(facet_term).unwrap();
             index_writer.commit().unwrap();
             index.load_searchers().unwrap();
             test_searcher(
@@ -1268,11 +1571,13 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             let mut doc = Document::default();
             doc.add_u64(int_field, 1);
-            index_writer.add_document(doc.clone());
+            index_writer.add_document(doc.clone()).unwrap();
             index_writer.commit().expect("commit failed");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
             index_writer.commit().expect("commit failed");
-            index_writer.delete_term(Term::from_field_u64(int_field, 1));
+            index_writer
+                .delete_term(Term::from_field_u64(int_field, 1))
+                .unwrap();
             index_writer.commit().expect("commit failed");
         }
         index.load_searchers().unwrap();
@@ -1312,7 +1617,7 @@ mod tests {
                 for &val in int_vals {
                     doc.add_u64(int_field, val);
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             };
 
             index_doc(&mut index_writer, &[1, 2]);
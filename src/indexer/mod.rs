@@ -1,28 +1,40 @@
 pub mod delete_queue;
 mod directory_lock;
+mod disk_space;
+mod doc_id_mapping;
 mod doc_opstamp_mapping;
+mod field_reindexer;
+mod index_splitter;
 pub mod index_writer;
+mod indexing_observer;
+mod json_indexer;
 mod log_merge_policy;
+pub(crate) mod merge_cancellation;
 pub mod merge_policy;
 pub mod merger;
 pub mod operation;
 mod prepared_commit;
 mod segment_entry;
+mod segment_info;
 mod segment_manager;
 mod segment_register;
 pub mod segment_serializer;
 pub mod segment_updater;
 mod segment_writer;
 mod stamper;
+pub(crate) mod write_throttle;
 
 pub(crate) use self::directory_lock::DirectoryLock;
+pub(crate) use self::index_splitter::split_index;
 pub use self::directory_lock::LockType;
 
 pub use self::index_writer::IndexWriter;
+pub use self::indexing_observer::{IndexingObserver, IndexingStats};
 pub use self::log_merge_policy::LogMergePolicy;
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
 pub use self::prepared_commit::PreparedCommit;
 pub use self::segment_entry::{SegmentEntry, SegmentState};
+pub use self::segment_info::SegmentInfo;
 pub use self::segment_manager::SegmentManager;
 pub use self::segment_serializer::SegmentSerializer;
 pub use self::segment_writer::SegmentWriter;
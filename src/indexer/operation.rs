@@ -1,11 +1,51 @@
+use query::Weight;
 use schema::Document;
+use schema::Field;
 use schema::Term;
+use std::fmt;
+use std::sync::Arc;
+
+/// Documents targeted by a delete operation.
+#[derive(Clone)]
+pub enum DeleteTarget {
+    /// The documents containing the term.
+    Term(Term),
+    /// The documents matching the query the weight was built from.
+    Query(Arc<Weight>),
+    /// The documents containing the term are not deleted:
+    /// the value of their fast field is updated instead.
+    FastFieldUpdate {
+        /// Term identifying the updated documents.
+        term: Term,
+        /// Updated single value fast field.
+        field: Field,
+        /// New value of the fast field, as a `u64`.
+        value: u64,
+    },
+}
+
+impl fmt::Debug for DeleteTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeleteTarget::Term(ref term) => write!(f, "Term({:?})", term),
+            DeleteTarget::Query(_) => write!(f, "Query"),
+            DeleteTarget::FastFieldUpdate {
+                ref term,
+                field,
+                value,
+            } => write!(f, "FastFieldUpdate({:?}, {:?}, {})", term, field, value),
+        }
+    }
+}
 
 /// Timestamped Delete operation.
-#[derive(Clone, Eq, PartialEq, Debug)]
+///
+/// Fast field updates go through the delete queue as well,
+/// see `DeleteTarget::FastFieldUpdate`.
+#[derive(Clone, Debug)]
 pub struct DeleteOperation {
     pub opstamp: u64,
-    pub term: Term,
+    pub target: DeleteTarget,
 }
 
 /// Timestamped Add operation.
@@ -14,3 +54,6 @@ pub struct AddOperation {
     pub opstamp: u64,
     pub document: Document,
 }
+
+/// Add operations that are indexed contiguously, in the same segment.
+pub type AddBatch = Vec<AddOperation>;
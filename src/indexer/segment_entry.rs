@@ -1,6 +1,7 @@
 use bit_set::BitSet;
 use core::SegmentId;
 use core::SegmentMeta;
+use fastfield::FastFieldUpdates;
 use indexer::delete_queue::DeleteCursor;
 use std::fmt;
 
@@ -29,6 +30,8 @@ impl SegmentState {
 /// - `delete_bitset` is a bitset describing
 /// documents that were deleted during the commit
 /// itself.
+/// - `fast_field_updates` are the fast field values updated
+/// during the commit itself.
 /// - `delete_cursor` is the position in the delete queue.
 /// Deletes happening before the cursor are reflected either
 /// in the .del file or in the `delete_bitset`.
@@ -37,6 +40,7 @@ pub struct SegmentEntry {
     meta: SegmentMeta,
     state: SegmentState,
     delete_bitset: Option<BitSet>,
+    fast_field_updates: Option<FastFieldUpdates>,
     delete_cursor: DeleteCursor,
 }
 
@@ -51,10 +55,22 @@ impl SegmentEntry {
             meta: segment_meta,
             state: SegmentState::Ready,
             delete_bitset,
+            fast_field_updates: None,
             delete_cursor,
         }
     }
 
+    /// Return a reference to the fast field values updated
+    /// during the commit.
+    pub fn fast_field_updates(&self) -> Option<&FastFieldUpdates> {
+        self.fast_field_updates.as_ref()
+    }
+
+    /// Set the fast field values updated during the commit.
+    pub fn set_fast_field_updates(&mut self, fast_field_updates: FastFieldUpdates) {
+        self.fast_field_updates = Some(fast_field_updates);
+    }
+
     /// Return a reference to the segment entry deleted bitset.
     ///
     /// `DocId` in this bitset are flagged as deleted.
@@ -0,0 +1,69 @@
+use core::SegmentId;
+use core::SegmentMeta;
+use std::time::SystemTime;
+
+/// `SegmentInfo` describes a segment handled by an `IndexWriter`,
+/// and its state at the time the info was collected.
+///
+/// See [`IndexWriter::segment_infos`](./struct.IndexWriter.html#method.segment_infos).
+#[derive(Clone, Debug)]
+pub struct SegmentInfo {
+    meta: SegmentMeta,
+    committed: bool,
+    merging: bool,
+}
+
+impl SegmentInfo {
+    pub(crate) fn new(meta: SegmentMeta, committed: bool, merging: bool) -> SegmentInfo {
+        SegmentInfo {
+            meta,
+            committed,
+            merging,
+        }
+    }
+
+    /// Returns the segment id.
+    pub fn segment_id(&self) -> SegmentId {
+        self.meta.id()
+    }
+
+    /// Returns the number of documents in the segment,
+    /// deleted documents included.
+    pub fn max_doc(&self) -> u32 {
+        self.meta.max_doc()
+    }
+
+    /// Returns the number of alive documents in the segment.
+    pub fn num_docs(&self) -> u32 {
+        self.meta.num_docs()
+    }
+
+    /// Returns the number of deleted documents.
+    ///
+    /// Deletes that were not committed yet are not accounted for.
+    pub fn num_deleted_docs(&self) -> u32 {
+        self.meta.num_deleted_docs()
+    }
+
+    /// Returns the time at which the segment was created, if known.
+    pub fn creation_time(&self) -> Option<SystemTime> {
+        self.meta.creation_time()
+    }
+
+    /// Returns true iff the segment belongs to the last commit.
+    ///
+    /// Uncommitted segments are not searchable yet.
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+    /// Returns true iff the segment is currently being merged.
+    pub fn is_merging(&self) -> bool {
+        self.merging
+    }
+
+    /// Returns the segment meta.
+    pub fn meta(&self) -> &SegmentMeta {
+        &self.meta
+    }
+}
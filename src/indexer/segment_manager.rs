@@ -5,6 +5,7 @@ use core::META_FILEPATH;
 use error::TantivyError;
 use indexer::delete_queue::DeleteCursor;
 use indexer::SegmentEntry;
+use indexer::SegmentInfo;
 use std::collections::hash_set::HashSet;
 use std::fmt::{self, Debug, Formatter};
 use std::path::PathBuf;
@@ -72,6 +73,46 @@ impl SegmentManager {
         segment_entries
     }
 
+    /// Returns the metas of all of the segments, uncommitted ones first.
+    pub fn segment_metas(&self) -> Vec<SegmentMeta> {
+        let registers_lock = self.read();
+        let mut segment_metas = registers_lock.uncommitted.segment_metas();
+        segment_metas.extend(registers_lock.committed.segment_metas());
+        segment_metas
+    }
+
+    /// Replaces the meta of a segment by `segment_meta`, which records
+    /// the number of bytes of its files.
+    ///
+    /// The meta is left untouched if the files of the segment changed
+    /// in the meantime, e.g. because deletes were applied to it.
+    pub fn record_num_bytes(&self, segment_meta: &SegmentMeta) {
+        let mut registers_lock = self.write();
+        registers_lock.uncommitted.record_num_bytes(segment_meta);
+        registers_lock.committed.record_num_bytes(segment_meta);
+    }
+
+    /// Returns the information about all of the segments, committed ones first.
+    pub fn segment_infos(&self) -> Vec<SegmentInfo> {
+        let registers_lock = self.read();
+        let mut segment_infos = Vec::new();
+        for &(register, committed) in &[
+            (&registers_lock.committed, true),
+            (&registers_lock.uncommitted, false),
+        ] {
+            let mut segment_entries = register.segment_entries();
+            segment_entries.sort_by_key(|segment_entry| segment_entry.segment_id());
+            segment_infos.extend(segment_entries.into_iter().map(|segment_entry| {
+                SegmentInfo::new(
+                    segment_entry.meta().clone(),
+                    committed,
+                    !segment_entry.is_ready(),
+                )
+            }));
+        }
+        segment_infos
+    }
+
     /// Returns the overall number of segments in the `SegmentManager`
     pub fn num_segments(&self) -> usize {
         let registers_lock = self.read();
@@ -233,3 +274,37 @@ impl SegmentManager {
         registers_lock.committed.segment_metas()
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::SegmentManager;
+    use core::{SegmentId, SegmentMeta};
+    use indexer::delete_queue::DeleteQueue;
+    use indexer::SegmentEntry;
+
+    #[test]
+    fn test_segment_infos() {
+        let delete_queue = DeleteQueue::new();
+        let committed_meta = SegmentMeta::new(SegmentId::generate_random(), 10);
+        let segment_manager =
+            SegmentManager::from_segments(vec![committed_meta.clone()], &delete_queue.cursor());
+        let uncommitted_meta = SegmentMeta::new(SegmentId::generate_random(), 3);
+        segment_manager.add_segment(SegmentEntry::new(
+            uncommitted_meta.clone(),
+            delete_queue.cursor(),
+            None,
+        ));
+        segment_manager.start_merge(&[committed_meta.id()]).unwrap();
+
+        let segment_infos = segment_manager.segment_infos();
+        assert_eq!(segment_infos.len(), 2);
+        assert_eq!(segment_infos[0].segment_id(), committed_meta.id());
+        assert!(segment_infos[0].is_committed());
+        assert!(segment_infos[0].is_merging());
+        assert_eq!(segment_infos[1].segment_id(), uncommitted_meta.id());
+        assert!(!segment_infos[1].is_committed());
+        assert!(!segment_infos[1].is_merging());
+        assert_eq!(segment_infos[1].num_docs(), 3);
+    }
+}
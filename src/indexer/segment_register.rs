@@ -71,6 +71,14 @@ impl SegmentRegister {
         self.segment_states.insert(segment_id, segment_entry);
     }
 
+    pub fn record_num_bytes(&mut self, segment_meta: &SegmentMeta) {
+        if let Some(segment_entry) = self.segment_states.get_mut(&segment_meta.id()) {
+            if segment_entry.meta().list_files() == segment_meta.list_files() {
+                segment_entry.set_meta(segment_meta.clone());
+            }
+        }
+    }
+
     pub fn remove_segment(&mut self, segment_id: &SegmentId) {
         self.segment_states.remove(segment_id);
     }
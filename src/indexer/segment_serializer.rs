@@ -13,6 +13,7 @@ pub struct SegmentSerializer {
     store_writer: StoreWriter,
     fast_field_serializer: FastFieldSerializer,
     fieldnorms_serializer: FieldNormsSerializer,
+    offsets_serializer: FastFieldSerializer,
     postings_serializer: InvertedIndexSerializer,
 }
 
@@ -27,11 +28,18 @@ impl SegmentSerializer {
         let fieldnorms_write = segment.open_write(SegmentComponent::FIELDNORMS)?;
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
+        let offsets_write = segment.open_write(SegmentComponent::OFFSETS)?;
+        let offsets_serializer = FastFieldSerializer::from_write(offsets_write)?;
+
+        let store_compression = segment.meta().store_compression();
+        let schema = segment.schema();
         let postings_serializer = InvertedIndexSerializer::open(segment)?;
         Ok(SegmentSerializer {
-            store_writer: StoreWriter::new(store_write),
+            store_writer: StoreWriter::with_compression(store_write, store_compression)
+                .with_schema_hints(&schema),
             fast_field_serializer,
             fieldnorms_serializer,
+            offsets_serializer,
             postings_serializer,
         })
     }
@@ -51,6 +59,11 @@ impl SegmentSerializer {
         &mut self.fieldnorms_serializer
     }
 
+    /// Accessor to the serializer of the token offsets.
+    pub fn get_offsets_serializer(&mut self) -> &mut FastFieldSerializer {
+        &mut self.offsets_serializer
+    }
+
     /// Accessor to the `StoreWriter`.
     pub fn get_store_writer(&mut self) -> &mut StoreWriter {
         &mut self.store_writer
@@ -62,6 +75,7 @@ impl SegmentSerializer {
         self.postings_serializer.close()?;
         self.store_writer.close()?;
         self.fieldnorms_serializer.close()?;
+        self.offsets_serializer.close()?;
         Ok(())
     }
 }
@@ -5,6 +5,7 @@ use core::Segment;
 use core::SegmentId;
 use core::SegmentMeta;
 use core::SerializableSegment;
+use core::INDEX_FORMAT_VERSION;
 use core::META_FILEPATH;
 use directory::{Directory, DirectoryClone};
 use error::TantivyError;
@@ -15,14 +16,20 @@ use futures_cpupool::Builder as CpuPoolBuilder;
 use futures_cpupool::CpuFuture;
 use futures_cpupool::CpuPool;
 use indexer::delete_queue::DeleteCursor;
+use indexer::disk_space::{estimate_merge_num_bytes, with_measured_num_bytes};
+use indexer::field_reindexer::{check_reindexable, reindex_segment_field};
 use indexer::index_writer::advance_deletes;
+use indexer::indexing_observer::IndexingMonitor;
+use indexer::merge_cancellation::MergeCancellation;
 use indexer::merger::IndexMerger;
 use indexer::stamper::Stamper;
+use indexer::write_throttle::WriteThrottle;
 use indexer::MergeCandidate;
 use indexer::SegmentEntry;
+use indexer::SegmentInfo;
 use indexer::SegmentSerializer;
 use indexer::{DefaultMergePolicy, MergePolicy};
-use schema::Schema;
+use schema::{Field, Schema};
 use serde_json;
 use std::borrow::BorrowMut;
 use std::collections::HashMap;
@@ -32,8 +39,10 @@ use std::ops::DerefMut;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::sync::{Condvar, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 use Result;
 
 /// Save the index meta file.
@@ -46,7 +55,11 @@ use Result;
 ///
 /// This method is not part of tantivy's public API
 pub fn save_new_metas(schema: Schema, opstamp: u64, directory: &mut Directory) -> Result<()> {
-    save_metas(vec![], schema, opstamp, None, directory)
+    let metas = IndexMeta {
+        opstamp,
+        ..IndexMeta::with_schema(schema)
+    };
+    save_metas(&metas, directory)
 }
 
 /// Save the index meta file.
@@ -58,20 +71,8 @@ pub fn save_new_metas(schema: Schema, opstamp: u64, directory: &mut Directory) -
 /// and flushed.
 ///
 /// This method is not part of tantivy's public API
-pub fn save_metas(
-    segment_metas: Vec<SegmentMeta>,
-    schema: Schema,
-    opstamp: u64,
-    payload: Option<String>,
-    directory: &mut Directory,
-) -> Result<()> {
-    let metas = IndexMeta {
-        segments: segment_metas,
-        schema,
-        opstamp,
-        payload,
-    };
-    let mut buffer = serde_json::to_vec_pretty(&metas)?;
+pub fn save_metas(metas: &IndexMeta, directory: &mut Directory) -> Result<()> {
+    let mut buffer = serde_json::to_vec_pretty(metas)?;
     writeln!(&mut buffer)?;
     directory.atomic_write(&META_FILEPATH, &buffer[..])?;
     debug!("Saved metas {:?}", serde_json::to_string_pretty(&metas));
@@ -119,7 +120,9 @@ fn perform_merge(
 
     let num_docs = merger.write(segment_serializer)?;
 
-    let segment_meta = SegmentMeta::new(merged_segment.id(), num_docs);
+    let segment_meta =
+        SegmentMeta::new_with_settings(merged_segment.id(), num_docs, merged_segment.meta())
+            .with_num_bytes(merged_segment.num_bytes_written());
 
     let after_merge_segment_entry = SegmentEntry::new(segment_meta.clone(), delete_cursor, None);
     Ok(after_merge_segment_entry)
@@ -130,8 +133,14 @@ struct InnerSegmentUpdater {
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Box<MergePolicy>>,
+    disk_space_limit: RwLock<Option<u64>>,
+    merge_write_throttle: Arc<WriteThrottle>,
+    indexing_monitor: IndexingMonitor,
     merging_thread_id: AtomicUsize,
-    merging_threads: RwLock<HashMap<usize, JoinHandle<Result<()>>>>,
+    merging_threads: Mutex<HashMap<usize, JoinHandle<Result<()>>>>,
+    // notified whenever a merging thread ends.
+    merging_thread_ended: Condvar,
+    merge_cancellations: RwLock<HashMap<usize, MergeCancellation>>,
     generation: AtomicUsize,
     killed: AtomicBool,
     stamper: Stamper,
@@ -154,8 +163,13 @@ impl SegmentUpdater {
             index,
             segment_manager,
             merge_policy: RwLock::new(Box::new(DefaultMergePolicy::default())),
+            disk_space_limit: RwLock::new(None),
+            merge_write_throttle: Arc::new(WriteThrottle::default()),
+            indexing_monitor: IndexingMonitor::default(),
             merging_thread_id: AtomicUsize::default(),
-            merging_threads: RwLock::new(HashMap::new()),
+            merging_threads: Mutex::new(HashMap::new()),
+            merging_thread_ended: Condvar::new(),
+            merge_cancellations: RwLock::new(HashMap::new()),
             generation: AtomicUsize::default(),
             killed: AtomicBool::new(false),
             stamper,
@@ -177,6 +191,95 @@ impl SegmentUpdater {
         *self.0.merge_policy.write().unwrap() = merge_policy;
     }
 
+    pub(crate) fn indexing_monitor(&self) -> &IndexingMonitor {
+        &self.0.indexing_monitor
+    }
+
+    pub fn segment_infos(&self) -> Vec<SegmentInfo> {
+        self.0.segment_manager.segment_infos()
+    }
+
+    /// Returns the segment entries, committed or not, once the
+    /// segment updates that are already scheduled are applied.
+    pub fn segment_entries(&self) -> Result<Vec<SegmentEntry>> {
+        self.run_async(|segment_updater| segment_updater.0.segment_manager.segment_entries())
+            .wait()
+    }
+
+    pub fn get_merge_io_limit(&self) -> Option<u64> {
+        self.0.merge_write_throttle.bytes_per_sec()
+    }
+
+    pub fn set_merge_io_limit(&self, bytes_per_sec: Option<u64>) {
+        self.0.merge_write_throttle.set_bytes_per_sec(bytes_per_sec);
+    }
+
+    pub fn get_disk_space_limit(&self) -> Option<u64> {
+        *self.0.disk_space_limit.read().unwrap()
+    }
+
+    pub fn set_disk_space_limit(&self, disk_space_limit: Option<u64>) {
+        *self.0.disk_space_limit.write().unwrap() = disk_space_limit;
+    }
+
+    /// Returns the number of bytes taken on disk by the
+    /// segments known to the segment updater.
+    ///
+    /// Segments that are currently being written (by an indexing
+    /// thread or a merge) are not taken in account.
+    ///
+    /// The sizes recorded in the segment metas are used, so that
+    /// the segment files do not need to be opened.
+    pub fn num_bytes_on_disk(&self) -> Result<u64> {
+        Ok(self
+            .measured_segment_metas()?
+            .iter()
+            .map(|segment_meta| segment_meta.num_bytes().unwrap_or(0u64))
+            .sum())
+    }
+
+    pub fn estimate_merge_num_bytes(&self, segment_ids: &[SegmentId]) -> Result<u64> {
+        let segment_metas: Vec<SegmentMeta> = self
+            .measured_segment_metas()?
+            .into_iter()
+            .filter(|segment_meta| segment_ids.contains(&segment_meta.id()))
+            .collect();
+        Ok(estimate_merge_num_bytes(&segment_metas[..]))
+    }
+
+    /// Returns the metas of the segments known to the segment updater,
+    /// all of which record their number of bytes.
+    ///
+    /// The segments written by older versions of tantivy do not record
+    /// it. They are measured once, and their metas are replaced by the
+    /// measured ones.
+    fn measured_segment_metas(&self) -> Result<Vec<SegmentMeta>> {
+        let mut segment_metas = self.0.segment_manager.segment_metas();
+        for segment_meta in &mut segment_metas {
+            if segment_meta.num_bytes().is_none() {
+                *segment_meta = with_measured_num_bytes(&self.0.index, segment_meta.clone())?;
+                self.0.segment_manager.record_num_bytes(segment_meta);
+            }
+        }
+        Ok(segment_metas)
+    }
+
+    /// Returns an error if writing `required_num_bytes` more bytes
+    /// would exceed the disk space limit.
+    pub fn check_disk_space_limit(&self, required_num_bytes: u64) -> Result<()> {
+        if let Some(limit) = self.get_disk_space_limit() {
+            let used = self.num_bytes_on_disk()?;
+            if used + required_num_bytes > limit {
+                return Err(TantivyError::DiskSpaceLimitExceeded {
+                    used,
+                    required: required_num_bytes,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn get_merging_thread_id(&self) -> usize {
         self.0.merging_thread_id.fetch_add(1, Ordering::SeqCst)
     }
@@ -211,6 +314,51 @@ impl SegmentUpdater {
         !self.0.killed.load(Ordering::Acquire)
     }
 
+    /// Blocks until no merge is running, or until `deadline`
+    /// is reached.
+    ///
+    /// The merges started upon the completion of other merges
+    /// are waited for as well.
+    ///
+    /// Returns `true` if no merge is running.
+    pub fn wait_merges_until(&self, deadline: Instant) -> bool {
+        let mut merging_threads = self.0.merging_threads.lock().unwrap();
+        while !merging_threads.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            merging_threads = self
+                .0
+                .merging_thread_ended
+                .wait_timeout(merging_threads, deadline - now)
+                .unwrap()
+                .0;
+        }
+        true
+    }
+
+    /// Aborts all of the merges currently running.
+    ///
+    /// The files written by the aborted merges are deleted, and the
+    /// segments that were being merged become available for future merges.
+    pub fn cancel_merges(&self) {
+        for cancellation in self.0.merge_cancellations.read().unwrap().values() {
+            cancellation.cancel();
+        }
+    }
+
+    #[cfg(test)]
+    pub fn merge_cancellations(&self) -> Vec<MergeCancellation> {
+        self.0
+            .merge_cancellations
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+
     /// Apply deletes up to the target opstamp to all segments.
     ///
     /// Tne method returns copies of the segment entries,
@@ -244,14 +392,18 @@ impl SegmentUpdater {
             //
             // Segment 1 from disk 1, Segment 1 from disk 2, etc.
             commited_segment_metas.sort_by_key(|segment_meta| -(segment_meta.max_doc() as i32));
-            save_metas(
-                commited_segment_metas,
-                index.schema(),
+            let previous_metas = index.load_metas().expect("Could not load metas.");
+            let metas = IndexMeta {
+                segments: commited_segment_metas,
+                schema: index.schema(),
                 opstamp,
-                commit_message,
-                directory.box_clone().borrow_mut(),
-            )
-            .expect("Could not save metas.");
+                payload: commit_message,
+                generation: previous_metas.generation + 1,
+                schema_version: previous_metas.schema_version,
+                shard: previous_metas.shard,
+                index_format_version: INDEX_FORMAT_VERSION,
+            };
+            save_metas(&metas, directory.box_clone().borrow_mut()).expect("Could not save metas.");
         }
     }
 
@@ -298,6 +450,17 @@ impl SegmentUpdater {
     fn start_merge_impl(&self, segment_ids: &[SegmentId]) -> Result<Receiver<SegmentMeta>> {
         assert!(!segment_ids.is_empty(), "Segment_ids cannot be empty.");
 
+        if !self.is_alive() {
+            return Err(TantivyError::ErrorInThread(
+                "The segment updater was killed.".into(),
+            ));
+        }
+
+        if self.get_disk_space_limit().is_some() {
+            let merge_num_bytes = self.estimate_merge_num_bytes(segment_ids)?;
+            self.check_disk_space_limit(merge_num_bytes)?;
+        }
+
         let segment_updater_clone = self.clone();
         let segment_entries: Vec<SegmentEntry> = self.0.segment_manager.start_merge(segment_ids)?;
 
@@ -312,12 +475,25 @@ impl SegmentUpdater {
 
         let target_opstamp = self.0.stamper.stamp();
 
+        let cancellation = MergeCancellation::default();
+        self.0
+            .merge_cancellations
+            .write()
+            .unwrap()
+            .insert(merging_thread_id, cancellation.clone());
+
+        // the merging thread removes itself from `merging_threads` when it
+        // ends, so it must not be able to do so before being inserted.
+        let mut merging_threads = self.0.merging_threads.lock().unwrap();
         // first we need to apply deletes to our segment.
         let merging_join_handle = thread::Builder::new()
             .name(format!("mergingthread-{}", merging_thread_id))
             .spawn(move || {
                 // first we need to apply deletes to our segment.
-                let merged_segment = segment_updater_clone.new_segment();
+                let merged_segment = segment_updater_clone
+                    .new_segment()
+                    .with_write_throttle(Arc::clone(&segment_updater_clone.0.merge_write_throttle))
+                    .with_cancellation(cancellation.clone());
                 let merged_segment_id = merged_segment.id();
                 let merge_result = perform_merge(
                     &segment_updater_clone.0.index,
@@ -340,6 +516,15 @@ impl SegmentUpdater {
                         // possible error.
                         let _merging_future_res = merging_future_send.send(merged_segment_meta);
                     }
+                    Err(_) if cancellation.is_cancelled() => {
+                        info!("Merge of {:?} was aborted.", segment_ids_vec);
+                        segment_updater_clone.cancel_merge(&segment_ids_vec, merged_segment_id);
+                        // The files of the merged segment are not referenced
+                        // anymore, and are removed by the garbage collection.
+                        if let Err(e) = segment_updater_clone.garbage_collect_files() {
+                            warn!("Failed to remove the files of the aborted merge: {:?}", e);
+                        }
+                    }
                     Err(e) => {
                         warn!("Merge of {:?} was cancelled: {:?}", segment_ids_vec, e);
                         // ... cancel merge
@@ -353,20 +538,62 @@ impl SegmentUpdater {
                 segment_updater_clone
                     .0
                     .merging_threads
+                    .lock()
+                    .unwrap()
+                    .remove(&merging_thread_id);
+                segment_updater_clone.0.merging_thread_ended.notify_all();
+                segment_updater_clone
+                    .0
+                    .merge_cancellations
                     .write()
                     .unwrap()
                     .remove(&merging_thread_id);
                 Ok(())
             })
             .expect("Failed to spawn a thread.");
-        self.0
-            .merging_threads
-            .write()
-            .unwrap()
-            .insert(merging_thread_id, merging_join_handle);
+        merging_threads.insert(merging_thread_id, merging_join_handle);
         Ok(merging_future_recv)
     }
 
+    /// Rebuilds the data of `field` in all of the committed segments,
+    /// one segment at a time.
+    ///
+    /// Reindexed segments replace the original ones
+    /// as they are rebuilt, like merged segments.
+    pub fn reindex_field(&self, field: Field) -> Result<()> {
+        check_reindexable(&self.0.index.schema(), field)?;
+        let segment_ids: Vec<SegmentId> = self
+            .0
+            .segment_manager
+            .committed_segment_metas()
+            .iter()
+            .map(|segment_meta| segment_meta.id())
+            .collect();
+        for segment_id in segment_ids {
+            let mut segment_entries = self.0.segment_manager.start_merge(&[segment_id])?;
+            let segment_entry = segment_entries.pop().expect("The segment was just found.");
+            let target_opstamp = self.0.stamper.stamp();
+            let reindexed_segment_id = self.new_segment().id();
+            info!("Reindexing field {:?} of segment {:?}", field, segment_id);
+            match reindex_segment_field(
+                &self.0.index,
+                segment_entry,
+                field,
+                reindexed_segment_id,
+                target_opstamp,
+            ) {
+                Ok(reindexed_segment_entry) => {
+                    self.end_merge(vec![segment_id], reindexed_segment_entry)?;
+                }
+                Err(e) => {
+                    self.cancel_merge(&[segment_id], reindexed_segment_id);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn consider_merge_options(&self) {
         let (committed_segments, uncommitted_segments) =
             get_mergeable_segments(&self.0.segment_manager);
@@ -440,6 +667,7 @@ impl SegmentUpdater {
                     }
                 }
             }
+            let merged_segment_meta = after_merge_segment_entry.meta().clone();
             segment_updater
                 .0
                 .segment_manager
@@ -449,6 +677,10 @@ impl SegmentUpdater {
             let previous_metas = segment_updater.0.index.load_metas().unwrap();
             segment_updater.save_metas(previous_metas.opstamp, previous_metas.payload);
             segment_updater.garbage_collect_files_exec();
+            segment_updater
+                .0
+                .indexing_monitor
+                .merge_ended(&before_merge_segment_ids, &merged_segment_meta);
         })
         .wait()
     }
@@ -475,7 +707,7 @@ impl SegmentUpdater {
 
             let mut new_merging_threads = HashMap::new();
             {
-                let mut merging_threads = self.0.merging_threads.write().unwrap();
+                let mut merging_threads = self.0.merging_threads.lock().unwrap();
                 mem::swap(&mut new_merging_threads, merging_threads.deref_mut());
             }
             debug!("wait merging thread {}", new_merging_threads.len());
@@ -519,29 +751,29 @@ mod tests {
 
         {
             for _ in 0..100 {
-                index_writer.add_document(doc!(text_field=>"a"));
-                index_writer.add_document(doc!(text_field=>"b"));
+                index_writer.add_document(doc!(text_field=>"a")).unwrap();
+                index_writer.add_document(doc!(text_field=>"b")).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
 
         {
             for _ in 0..100 {
-                index_writer.add_document(doc!(text_field=>"c"));
-                index_writer.add_document(doc!(text_field=>"d"));
+                index_writer.add_document(doc!(text_field=>"c")).unwrap();
+                index_writer.add_document(doc!(text_field=>"d")).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
 
         {
-            index_writer.add_document(doc!(text_field=>"e"));
-            index_writer.add_document(doc!(text_field=>"f"));
+            index_writer.add_document(doc!(text_field=>"e")).unwrap();
+            index_writer.add_document(doc!(text_field=>"f")).unwrap();
             assert!(index_writer.commit().is_ok());
         }
 
         {
             let term = Term::from_field_text(text_field, "a");
-            index_writer.delete_term(term);
+            index_writer.delete_term(term).unwrap();
             assert!(index_writer.commit().is_ok());
         }
 
@@ -3,17 +3,20 @@ use core::Segment;
 use core::SerializableSegment;
 use fastfield::FastFieldsWriter;
 use fieldnorm::FieldNormsWriter;
+use indexer::json_indexer::index_json_objects;
 use indexer::segment_serializer::SegmentSerializer;
+use offsets::{OffsetsRecordingStream, OffsetsWriter};
 use postings::MultiFieldPostingsWriter;
 use schema::FieldType;
 use schema::Schema;
 use schema::Term;
 use schema::Value;
 use std::io;
+use std::net::IpAddr;
 use std::str;
 use tokenizer::BoxedTokenizer;
 use tokenizer::FacetTokenizer;
-use tokenizer::{TokenStream, Tokenizer};
+use tokenizer::{chain_token_streams, PreTokenizedStream, TokenStream, Tokenizer};
 use DocId;
 use Result;
 
@@ -28,6 +31,7 @@ pub struct SegmentWriter {
     segment_serializer: SegmentSerializer,
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: FieldNormsWriter,
+    offsets_writer: OffsetsWriter,
     doc_opstamps: Vec<u64>,
     tokenizers: Vec<Option<Box<BoxedTokenizer>>>,
 }
@@ -61,6 +65,12 @@ impl SegmentWriter {
                             let tokenizer_name = &text_index_option.tokenizer();
                             segment.index().tokenizers().get(tokenizer_name)
                         }),
+                    FieldType::JsonObject(ref json_object_options) => json_object_options
+                        .get_text_indexing_options()
+                        .and_then(|text_index_option| {
+                            let tokenizer_name = &text_index_option.tokenizer();
+                            segment.index().tokenizers().get(tokenizer_name)
+                        }),
                     _ => None,
                 })
                 .collect();
@@ -68,6 +78,7 @@ impl SegmentWriter {
             max_doc: 0,
             multifield_postings,
             fieldnorms_writer: FieldNormsWriter::for_schema(schema),
+            offsets_writer: OffsetsWriter::for_schema(schema),
             segment_serializer,
             fast_field_writers: FastFieldsWriter::from_schema(schema),
             doc_opstamps: Vec::with_capacity(1_000),
@@ -85,6 +96,8 @@ impl SegmentWriter {
             &self.multifield_postings,
             &self.fast_field_writers,
             &self.fieldnorms_writer,
+            &self.offsets_writer,
+            self.max_doc,
             self.segment_serializer,
         )?;
         Ok(self.doc_opstamps)
@@ -101,6 +114,7 @@ impl SegmentWriter {
         let doc_id = self.max_doc;
         let mut doc = add_operation.document;
         self.doc_opstamps.push(add_operation.opstamp);
+        doc.truncate_dates(schema);
 
         self.fast_field_writers.add_document(&doc);
 
@@ -139,25 +153,49 @@ impl SegmentWriter {
                     }
                 }
                 FieldType::Str(_) => {
-                    let num_tokens = if let Some(ref mut tokenizer) =
-                        self.tokenizers[field.0 as usize]
-                    {
-                        let texts: Vec<&str> = field_values
-                            .iter()
-                            .flat_map(|field_value| match *field_value.value() {
-                                Value::Str(ref text) => Some(text.as_str()),
-                                _ => None,
-                            })
-                            .collect();
-                        if texts.is_empty() {
-                            0
-                        } else {
-                            let mut token_stream = tokenizer.token_stream_texts(&texts[..]);
-                            self.multifield_postings
-                                .index_text(doc_id, field, &mut token_stream)
+                    let mut texts: Vec<&str> = Vec::new();
+                    let mut token_streams = Vec::new();
+                    for field_value in field_values {
+                        match *field_value.value() {
+                            Value::Str(ref text) => {
+                                if let Some(ref tokenizer) = self.tokenizers[field.0 as usize] {
+                                    texts.push(text);
+                                    token_streams.push(tokenizer.token_stream(text));
+                                }
+                            }
+                            Value::PreTokStr(ref tokenized_text) => {
+                                texts.push(&tokenized_text.text);
+                                token_streams.push(Box::new(PreTokenizedStream::from(
+                                    tokenized_text.clone(),
+                                )));
+                            }
+                            _ => {}
                         }
-                    } else {
+                    }
+                    let num_tokens = if token_streams.is_empty() {
                         0
+                    } else if self.offsets_writer.has_offsets(field) {
+                        let mut token_stream = OffsetsRecordingStream::new(
+                            chain_token_streams(&texts[..], token_streams),
+                            &texts[..],
+                        );
+                        let num_tokens = self.multifield_postings.index_text(
+                            doc_id,
+                            field,
+                            &mut token_stream,
+                            self.fast_field_writers.get_multivalue_writer(field),
+                        );
+                        self.offsets_writer
+                            .record(doc_id, field, token_stream.offsets());
+                        num_tokens
+                    } else {
+                        let mut token_stream = chain_token_streams(&texts[..], token_streams);
+                        self.multifield_postings.index_text(
+                            doc_id,
+                            field,
+                            &mut token_stream,
+                            self.fast_field_writers.get_multivalue_writer(field),
+                        )
                     };
                     self.fieldnorms_writer.record(doc_id, field, num_tokens);
                 }
@@ -183,8 +221,62 @@ impl SegmentWriter {
                         }
                     }
                 }
-                FieldType::Bytes => {
-                    // Do nothing. Bytes only supports fast fields.
+                FieldType::F64(ref int_option) => {
+                    if int_option.is_indexed() {
+                        for field_value in field_values {
+                            let term = Term::from_field_f64(
+                                field_value.field(),
+                                field_value.value().f64_value(),
+                            );
+                            self.multifield_postings.subscribe(doc_id, &term);
+                        }
+                    }
+                }
+                FieldType::Date(ref date_options) => {
+                    if date_options.is_indexed() {
+                        for field_value in field_values {
+                            let term = Term::from_field_date(
+                                field_value.field(),
+                                field_value.value().date_value(),
+                            );
+                            self.multifield_postings.subscribe(doc_id, &term);
+                        }
+                    }
+                }
+                FieldType::IpAddr(ref ip_addr_options) => {
+                    if ip_addr_options.is_indexed() {
+                        for field_value in field_values {
+                            let ip_addr = field_value.value().ip_addr_value();
+                            let term =
+                                Term::from_field_ip_addr(field_value.field(), IpAddr::V6(ip_addr));
+                            self.multifield_postings.subscribe(doc_id, &term);
+                        }
+                    }
+                }
+                FieldType::Bytes(_) => {
+                    // Do nothing. Bytes fields are not indexed.
+                }
+                FieldType::JsonObject(_) => {
+                    let num_tokens = if let Some(ref tokenizer) = self.tokenizers[field.0 as usize]
+                    {
+                        let json_objects: Vec<_> = field_values
+                            .iter()
+                            .flat_map(|field_value| match *field_value.value() {
+                                Value::JsonObject(ref json_object) => Some(json_object),
+                                _ => None,
+                            })
+                            .collect();
+                        index_json_objects(
+                            doc_id,
+                            field,
+                            &json_objects[..],
+                            &**tokenizer,
+                            &mut self.multifield_postings,
+                        )
+                    } else {
+                        0
+                    };
+                    self.fieldnorms_writer.record(doc_id, field, num_tokens);
                 }
             }
         }
@@ -221,11 +313,14 @@ fn write(
     multifield_postings: &MultiFieldPostingsWriter,
     fast_field_writers: &FastFieldsWriter,
     fieldnorms_writer: &FieldNormsWriter,
+    offsets_writer: &OffsetsWriter,
+    max_doc: DocId,
     mut serializer: SegmentSerializer,
 ) -> Result<()> {
     let term_ord_map = multifield_postings.serialize(serializer.get_postings_serializer())?;
     fast_field_writers.serialize(serializer.get_fast_field_serializer(), &term_ord_map)?;
     fieldnorms_writer.serialize(serializer.get_fieldnorms_serializer())?;
+    offsets_writer.serialize(serializer.get_offsets_serializer(), max_doc)?;
     serializer.close()?;
     Ok(())
 }
@@ -237,6 +332,8 @@ impl SerializableSegment for SegmentWriter {
             &self.multifield_postings,
             &self.fast_field_writers,
             &self.fieldnorms_writer,
+            &self.offsets_writer,
+            max_doc,
             serializer,
         )?;
         Ok(max_doc)
@@ -0,0 +1,201 @@
+use directory::SeekableWrite;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Writes are allowed to get ahead of the throughput limit by
+/// this duration before being put to sleep.
+const MAX_BURST_MILLIS: u64 = 100;
+
+struct ThrottleState {
+    bytes_per_sec: Option<u64>,
+    // instant at which all of the bytes written so far
+    // would have been written at the target throughput.
+    next_available: Instant,
+}
+
+/// `WriteThrottle` limits the throughput of all of the
+/// writes sharing it.
+///
+/// It is used to prevent merges from saturating the disk.
+pub struct WriteThrottle {
+    state: Mutex<ThrottleState>,
+}
+
+impl Default for WriteThrottle {
+    fn default() -> WriteThrottle {
+        WriteThrottle {
+            state: Mutex::new(ThrottleState {
+                bytes_per_sec: None,
+                next_available: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl WriteThrottle {
+    /// Returns the throughput limit, in bytes per second.
+    pub fn bytes_per_sec(&self) -> Option<u64> {
+        self.state.lock().unwrap().bytes_per_sec
+    }
+
+    /// Sets the throughput limit, in bytes per second.
+    /// `None` removes the limit.
+    ///
+    /// The new limit also applies to the writes that are
+    /// already in progress.
+    pub fn set_bytes_per_sec(&self, bytes_per_sec: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        state.bytes_per_sec = bytes_per_sec.filter(|&bytes_per_sec| bytes_per_sec > 0);
+        state.next_available = Instant::now();
+    }
+
+    /// Accounts for `num_bytes` that were just written,
+    /// and sleeps if the writes got too far ahead of the limit.
+    fn consume(&self, num_bytes: usize) {
+        if let Some(sleep_duration) = self.reserve(num_bytes, Instant::now()) {
+            thread::sleep(sleep_duration);
+        }
+    }
+
+    /// Accounts for `num_bytes` written at `now`, and returns
+    /// how long the writer should sleep to remain within the limit.
+    fn reserve(&self, num_bytes: usize, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let bytes_per_sec = state.bytes_per_sec?;
+        if state.next_available < now {
+            state.next_available = now;
+        }
+        let write_nanos = num_bytes as u64 * 1_000_000_000 / bytes_per_sec;
+        state.next_available += Duration::from_nanos(write_nanos);
+        let ahead = state.next_available - now;
+        let max_burst = Duration::from_millis(MAX_BURST_MILLIS);
+        if ahead <= max_burst {
+            return None;
+        }
+        Some(ahead - max_burst)
+    }
+}
+
+/// Wraps a `Write` to limit its throughput using a `WriteThrottle`.
+pub struct ThrottledWrite<W> {
+    underlying: W,
+    throttle: Arc<WriteThrottle>,
+}
+
+impl<W> ThrottledWrite<W> {
+    pub fn new(underlying: W, throttle: Arc<WriteThrottle>) -> ThrottledWrite<W> {
+        ThrottledWrite {
+            underlying,
+            throttle,
+        }
+    }
+}
+
+impl<W: Write> Write for ThrottledWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        self.throttle.consume(num_bytes);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl<W: Seek> Seek for ThrottledWrite<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.underlying.seek(pos)
+    }
+}
+
+impl<W: SeekableWrite> SeekableWrite for ThrottledWrite<W> {
+    fn terminate(&mut self) -> io::Result<()> {
+        self.underlying.terminate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{ThrottledWrite, WriteThrottle};
+    use std::io::{Cursor, Write};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_write_throttle_reserve() {
+        let throttle = WriteThrottle::default();
+        throttle.set_bytes_per_sec(Some(1_000_000));
+        assert_eq!(throttle.bytes_per_sec(), Some(1_000_000));
+        let now = Instant::now();
+        // 100KB at 1MB/s fit in the allowed burst.
+        assert_eq!(throttle.reserve(100_000, now), None);
+        assert_eq!(
+            throttle.reserve(10_000, now),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            throttle.reserve(10_000, now),
+            Some(Duration::from_millis(20))
+        );
+        // the bytes written so far are paid for after 120ms.
+        let later = now + Duration::from_millis(120);
+        assert_eq!(throttle.reserve(100_000, later), None);
+        assert_eq!(
+            throttle.reserve(1_000, later),
+            Some(Duration::from_millis(1))
+        );
+        // the budget does not accumulate while nothing is written.
+        let much_later = now + Duration::from_secs(10);
+        assert_eq!(throttle.reserve(100_000, much_later), None);
+        assert_eq!(
+            throttle.reserve(1_000, much_later),
+            Some(Duration::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn test_write_throttle_no_limit() {
+        let throttle = WriteThrottle::default();
+        assert_eq!(throttle.bytes_per_sec(), None);
+        let now = Instant::now();
+        assert_eq!(throttle.reserve(1_000_000_000, now), None);
+        throttle.set_bytes_per_sec(Some(0));
+        assert_eq!(throttle.bytes_per_sec(), None);
+        assert_eq!(throttle.reserve(1_000_000_000, now), None);
+    }
+
+    #[test]
+    fn test_write_throttle_reset() {
+        let throttle = WriteThrottle::default();
+        throttle.set_bytes_per_sec(Some(1_000));
+        let now = Instant::now();
+        assert_eq!(
+            throttle.reserve(1_000, now),
+            Some(Duration::from_millis(900))
+        );
+        // a new limit discards the debt of the previous one.
+        throttle.set_bytes_per_sec(Some(1_000_000));
+        let now = Instant::now();
+        assert_eq!(throttle.reserve(100_000, now), None);
+    }
+
+    #[test]
+    fn test_throttled_write() {
+        let throttle = Arc::new(WriteThrottle::default());
+        throttle.set_bytes_per_sec(Some(1_000_000));
+        let mut write = ThrottledWrite::new(Cursor::new(Vec::new()), Arc::clone(&throttle));
+        let before = Instant::now();
+        write.write_all(&[1u8; 50_000]).unwrap();
+        let after = Instant::now();
+        write.flush().unwrap();
+        assert_eq!(write.underlying.get_ref().len(), 50_000);
+        // the bytes written through `ThrottledWrite` were accounted for.
+        let next_available = throttle.state.lock().unwrap().next_available;
+        assert!(next_available >= before + Duration::from_millis(50));
+        assert!(next_available <= after + Duration::from_millis(50));
+    }
+}
@@ -65,7 +65,7 @@
 //!     body => "He was an old man who fished alone in a skiff in \
 //!             the Gulf Stream and he had gone eighty-four days \
 //!             now without taking a fish."
-//! ));
+//! ))?;
 //!
 //! // We need to call .commit() explicitly to force the
 //! // index_writer to finish processing the documents in the queue,
@@ -129,10 +129,14 @@ extern crate base64;
 extern crate bit_set;
 extern crate bitpacking;
 extern crate byteorder;
+extern crate chrono;
+#[cfg(feature = "mmap")]
+extern crate notify;
 extern crate scoped_pool;
 
 extern crate combine;
 
+extern crate crc32fast;
 extern crate crossbeam;
 extern crate fnv;
 extern crate fst;
@@ -200,10 +204,13 @@ mod indexer;
 mod error;
 pub mod tokenizer;
 
+#[cfg(feature = "benchmark")]
+pub mod benchmark;
 pub mod collector;
 pub mod directory;
 pub mod fastfield;
 pub mod fieldnorm;
+pub mod offsets;
 pub(crate) mod positions;
 pub mod postings;
 pub mod query;
@@ -218,15 +225,23 @@ pub use self::snippet::{Snippet, SnippetGenerator};
 mod docset;
 pub use self::docset::{DocSet, SkipResult};
 
+pub use core::FieldStatistics;
 pub use core::SegmentComponent;
-pub use core::{Index, Searcher, Segment, SegmentId, SegmentMeta};
-pub use core::{InvertedIndexReader, SegmentReader};
+pub use core::INDEX_FORMAT_VERSION;
+pub use core::{Index, Searcher, Segment, SegmentId, SegmentMeta, ShardDescriptor, TenantIndex};
+pub use core::{IndexReader, InvertedIndexReader, ReloadPolicy, SegmentReader, Warmer};
+pub use core::{QueryProfile, SearchFuture, SearchIterator, SegmentProfile};
 pub use directory::Directory;
-pub use indexer::IndexWriter;
+pub use indexer::{IndexWriter, IndexingObserver, IndexingStats, SegmentInfo};
 pub use postings::Postings;
 pub use schema::{Document, Term};
 
-pub use common::{i64_to_u64, u64_to_i64};
+pub use common::ip_addr_to_ipv6;
+pub use common::{date_to_i64, i64_to_date};
+pub use common::{f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64};
+
+/// A UTC date and time, the value of the date fields.
+pub type DateTime = chrono::DateTime<chrono::Utc>;
 
 /// Expose the current version of tantivy, as well
 /// whether it was compiled with the simd compression.
@@ -351,15 +366,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -372,23 +387,25 @@ mod tests {
         let index = Index::create_in_ram(schema_builder.build());
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
         {
-            index_writer.add_document(doc!(text_field=>"a b c"));
+            index_writer
+                .add_document(doc!(text_field=>"a b c"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         {
             {
                 let doc = doc!(text_field=>"a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
         {
             let doc = doc!(text_field=>"c");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
             index_writer.commit().unwrap();
         }
         {
@@ -415,7 +432,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -443,15 +460,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!();
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -489,20 +506,24 @@ mod tests {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             // 0
-            index_writer.add_document(doc!(text_field=>"a b"));
+            index_writer.add_document(doc!(text_field=>"a b")).unwrap();
             // 1
-            index_writer.add_document(doc!(text_field=>" a c"));
+            index_writer.add_document(doc!(text_field=>" a c")).unwrap();
             // 2
-            index_writer.add_document(doc!(text_field=>" b c"));
+            index_writer.add_document(doc!(text_field=>" b c")).unwrap();
             // 3
-            index_writer.add_document(doc!(text_field=>" b d"));
+            index_writer.add_document(doc!(text_field=>" b d")).unwrap();
 
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
-            index_writer.delete_term(Term::from_field_text(text_field, "a"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "a"))
+                .unwrap();
             // 4
-            index_writer.add_document(doc!(text_field=>" b c"));
+            index_writer.add_document(doc!(text_field=>" b c")).unwrap();
             // 5
-            index_writer.add_document(doc!(text_field=>" a"));
+            index_writer.add_document(doc!(text_field=>" a")).unwrap();
             index_writer.commit().unwrap();
         }
         {
@@ -536,9 +557,11 @@ mod tests {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             // 0
-            index_writer.add_document(doc!(text_field=>"a b"));
+            index_writer.add_document(doc!(text_field=>"a b")).unwrap();
             // 1
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
             index_writer.rollback().unwrap();
         }
         {
@@ -572,10 +595,14 @@ mod tests {
         {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a b"));
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
+            index_writer.add_document(doc!(text_field=>"a b")).unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
             index_writer.rollback().unwrap();
-            index_writer.delete_term(Term::from_field_text(text_field, "a"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "a"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         {
@@ -621,7 +648,7 @@ mod tests {
 
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-        index_writer.add_document(doc!(field=>1u64));
+        index_writer.add_document(doc!(field=>1u64)).unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -645,7 +672,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
         let negative_val = -1i64;
-        index_writer.add_document(doc!(value_field => negative_val));
+        index_writer
+            .add_document(doc!(value_field => negative_val))
+            .unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -668,7 +697,7 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(2, 40_000_000).unwrap();
-        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.add_document(doc!(text_field=>"a")).unwrap();
         assert!(index_writer.commit().is_ok());
         assert!(index.load_searchers().is_ok());
         let searcher = index.searcher();
@@ -688,12 +717,12 @@ mod tests {
 
         let add_document = |index_writer: &mut IndexWriter, val: &'static str| {
             let doc = doc!(text_field=>val);
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         };
 
         let remove_document = |index_writer: &mut IndexWriter, val: &'static str| {
             let delterm = Term::from_field_text(text_field, val);
-            index_writer.delete_term(delterm);
+            index_writer.delete_term(delterm).unwrap();
         };
 
         add_document(&mut index_writer, "63");
@@ -723,7 +752,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af af af bc bc");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -757,9 +786,15 @@ mod tests {
         {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"af af af b"));
-            index_writer.add_document(doc!(text_field=>"a b c"));
-            index_writer.add_document(doc!(text_field=>"a b c d"));
+            index_writer
+                .add_document(doc!(text_field=>"af af af b"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"a b c"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"a b c d"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         {
@@ -812,15 +847,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -858,7 +893,7 @@ mod tests {
         let mut index_writer = index.writer_with_num_threads(1, 50_000_000).unwrap();
         {
             let document = doc!(fast_field_unsigned => 4u64, fast_field_signed=>4i64);
-            index_writer.add_document(document);
+            index_writer.add_document(document).unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -0,0 +1,36 @@
+//! Offsets are the byte ranges of the tokens of a text field,
+//! within the text they were extracted from.
+//!
+//! They are indexed for the text fields configured with
+//! [`TextFieldIndexing::set_offsets`](../schema/struct.TextFieldIndexing.html#method.set_offsets),
+//! and make it possible to highlight the terms of a document
+//! without tokenizing its stored text again.
+//!
+//! For each document, the offsets of a field are stored as a list
+//! of `(position, offset_from, offset_to)` triplets, one per token.
+//! They are laid out like a multivalued fast field, in the
+//! [`OFFSETS`](../enum.SegmentComponent.html#variant.OFFSETS) component of the segment.
+//!
+//! If a field has several values in a document, the offsets are relative
+//! to the concatenation of its values, separated by a single space.
+mod reader;
+mod writer;
+
+pub use self::reader::OffsetsReader;
+pub use self::writer::OffsetsWriter;
+
+pub(crate) use self::writer::OffsetsRecordingStream;
+
+/// Position and byte range of a token within the text of a field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenOffsets {
+    /// Position of the token, as recorded in the postings.
+    pub position: u32,
+    /// Offset of the first byte of the token.
+    pub offset_from: usize,
+    /// Offset after the last byte of the token.
+    pub offset_to: usize,
+}
+
+/// Number of values stored for each token.
+const NUM_VALS_PER_TOKEN: usize = 3;
@@ -0,0 +1,42 @@
+use super::{TokenOffsets, NUM_VALS_PER_TOKEN};
+use directory::ReadOnlySource;
+use fastfield::{FastFieldReader, MultiValueIntFastFieldReader};
+use DocId;
+
+/// Reads the offsets of the tokens of a field, for each document.
+#[derive(Clone)]
+pub struct OffsetsReader {
+    vals_reader: MultiValueIntFastFieldReader<u64>,
+}
+
+impl OffsetsReader {
+    /// Opens the offsets of a field given the sources of its
+    /// document index and of its values.
+    pub fn open(idx_source: ReadOnlySource, vals_source: ReadOnlySource) -> OffsetsReader {
+        OffsetsReader {
+            vals_reader: MultiValueIntFastFieldReader::open(
+                FastFieldReader::open(idx_source),
+                FastFieldReader::open(vals_source),
+            ),
+        }
+    }
+
+    /// Returns the offsets of the tokens of the document `doc`,
+    /// ordered by position.
+    pub fn token_offsets(&self, doc: DocId) -> Vec<TokenOffsets> {
+        let mut vals = Vec::new();
+        self.vals(doc, &mut vals);
+        vals.chunks(NUM_VALS_PER_TOKEN)
+            .map(|token_vals| TokenOffsets {
+                position: token_vals[0] as u32,
+                offset_from: token_vals[1] as usize,
+                offset_to: token_vals[2] as usize,
+            })
+            .collect()
+    }
+
+    /// Returns the serialized values of the offsets of `doc`.
+    pub(crate) fn vals(&self, doc: DocId, vals: &mut Vec<u64>) {
+        self.vals_reader.get_vals(doc, vals);
+    }
+}
@@ -0,0 +1,192 @@
+use super::{TokenOffsets, NUM_VALS_PER_TOKEN};
+use fastfield::FastFieldSerializer;
+use schema::{Field, FieldType, Schema};
+use std::io;
+use tokenizer::{Token, TokenStream};
+use DocId;
+
+/// The `OffsetsWriter` is in charge of recording the offsets of the
+/// tokens of each document, for each field with offsets.
+pub struct OffsetsWriter {
+    fields: Vec<Field>,
+    doc_index: Vec<Vec<u64>>,
+    vals: Vec<Vec<u64>>,
+}
+
+impl OffsetsWriter {
+    /// Returns the fields that should have their offsets recorded
+    /// according to the given schema.
+    pub(crate) fn fields_with_offsets(schema: &Schema) -> Vec<Field> {
+        schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|&(_, field_entry)| match *field_entry.field_type() {
+                FieldType::Str(ref text_options) => text_options
+                    .get_indexing_options()
+                    .map(|indexing_options| indexing_options.has_offsets())
+                    .unwrap_or(false),
+                _ => false,
+            })
+            .map(|(field, _)| Field(field as u32))
+            .collect()
+    }
+
+    /// Initialize with state for tracking the offsets of the fields
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> OffsetsWriter {
+        let num_fields = schema.fields().len();
+        OffsetsWriter {
+            fields: OffsetsWriter::fields_with_offsets(schema),
+            doc_index: (0..num_fields).map(|_| Vec::new()).collect(),
+            vals: (0..num_fields).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    /// Returns true iff the offsets of `field` are recorded.
+    pub fn has_offsets(&self, field: Field) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// Records the offsets of the tokens of `field` in the document `doc`.
+    pub fn record(&mut self, doc: DocId, field: Field, offsets: &[TokenOffsets]) {
+        let doc_index = &mut self.doc_index[field.0 as usize];
+        let vals = &mut self.vals[field.0 as usize];
+        assert!(
+            doc_index.len() <= doc as usize,
+            "Cannot register the offsets of a given document twice"
+        );
+        // the intermediary `DocId`s have no offsets.
+        doc_index.resize(doc as usize + 1, vals.len() as u64);
+        for token_offsets in offsets {
+            vals.push(u64::from(token_offsets.position));
+            vals.push(token_offsets.offset_from as u64);
+            vals.push(token_offsets.offset_to as u64);
+        }
+    }
+
+    /// Serialize the recorded offsets of all fields.
+    pub fn serialize(
+        &self,
+        serializer: &mut FastFieldSerializer,
+        max_doc: DocId,
+    ) -> io::Result<()> {
+        for &field in &self.fields {
+            let doc_index = &self.doc_index[field.0 as usize];
+            let vals = &self.vals[field.0 as usize];
+            let num_vals = vals.len() as u64;
+            debug_assert_eq!(num_vals % NUM_VALS_PER_TOKEN as u64, 0);
+            {
+                let mut idx_serializer =
+                    serializer.new_u64_fast_field_with_idx(field, 0, num_vals, 0)?;
+                for doc in 0..max_doc as usize {
+                    idx_serializer.add_val(doc_index.get(doc).cloned().unwrap_or(num_vals))?;
+                }
+                idx_serializer.add_val(num_vals)?;
+                idx_serializer.close_field()?;
+            }
+            {
+                let max_val = vals.iter().cloned().max().unwrap_or(0u64);
+                let mut vals_serializer =
+                    serializer.new_u64_fast_field_with_idx(field, 0, max_val, 1)?;
+                for &val in vals {
+                    vals_serializer.add_val(val)?;
+                }
+                vals_serializer.close_field()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wraps the token stream of the values of a field, and records
+/// the offsets of the tokens as they are emitted.
+pub(crate) struct OffsetsRecordingStream<TTokenStream: TokenStream> {
+    token_stream: TTokenStream,
+    value_starts: Vec<usize>,
+    offsets: Vec<TokenOffsets>,
+}
+
+impl<TTokenStream: TokenStream> OffsetsRecordingStream<TTokenStream> {
+    /// Wraps the token stream emitting the tokens of `texts`.
+    pub fn new(token_stream: TTokenStream, texts: &[&str]) -> OffsetsRecordingStream<TTokenStream> {
+        let mut value_starts = Vec::with_capacity(texts.len());
+        let mut total_offset = 0;
+        for text in texts {
+            value_starts.push(total_offset);
+            total_offset += text.len();
+        }
+        OffsetsRecordingStream {
+            token_stream,
+            value_starts,
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Returns the offsets of the tokens emitted so far.
+    pub fn offsets(&self) -> &[TokenOffsets] {
+        &self.offsets[..]
+    }
+}
+
+impl<TTokenStream: TokenStream> TokenStream for OffsetsRecordingStream<TTokenStream> {
+    fn advance(&mut self) -> bool {
+        if !self.token_stream.advance() {
+            return false;
+        }
+        let token = self.token_stream.token();
+        // The offsets of the chained token stream do not account for
+        // the space separating the values.
+        let value_ord = self
+            .value_starts
+            .iter()
+            .rposition(|&value_start| value_start <= token.offset_from)
+            .unwrap_or(0);
+        self.offsets.push(TokenOffsets {
+            position: token.position as u32,
+            offset_from: token.offset_from + value_ord,
+            offset_to: token.offset_to + value_ord,
+        });
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.token_stream.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token_stream.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::OffsetsRecordingStream;
+    use tokenizer::{box_tokenizer, SimpleTokenizer, TokenStream};
+
+    #[test]
+    fn test_offsets_recording_stream() {
+        let tokenizer = box_tokenizer(SimpleTokenizer);
+        let texts = ["hello happy", "tax payer"];
+        let mut token_stream =
+            OffsetsRecordingStream::new(tokenizer.token_stream_texts(&texts[..]), &texts[..]);
+        let mut num_tokens = 0;
+        while token_stream.advance() {
+            num_tokens += 1;
+        }
+        assert_eq!(num_tokens, 4);
+        let offsets = token_stream.offsets();
+        let joined = texts.join(" ");
+        let words: Vec<&str> = offsets
+            .iter()
+            .map(|token_offsets| &joined[token_offsets.offset_from..token_offsets.offset_to])
+            .collect();
+        assert_eq!(words, vec!["hello", "happy", "tax", "payer"]);
+        assert_eq!(offsets[0].position, 0);
+        assert_eq!(offsets[1].position, 1);
+        // positions are shifted between two values.
+        assert!(offsets[2].position > 2);
+        assert_eq!(offsets[3].position, offsets[2].position + 1);
+    }
+}
@@ -8,6 +8,7 @@ pub(crate) mod compression;
 /// Postings, also called inverted lists, is the key datastructure
 /// to full-text search.
 mod postings;
+mod postings_codec;
 mod postings_writer;
 mod recorder;
 mod segment_postings;
@@ -21,12 +22,14 @@ pub use self::serializer::{FieldSerializer, InvertedIndexSerializer};
 
 use self::compression::COMPRESSION_BLOCK_SIZE;
 pub use self::postings::Postings;
+pub use self::postings_codec::PostingsCodec;
 pub(crate) use self::skip::SkipReader;
 pub use self::term_info::TermInfo;
 
 pub use self::segment_postings::{BlockSegmentPostings, SegmentPostings};
 
 pub(crate) use self::stacker::compute_table_size;
+pub(crate) use self::stacker::murmurhash2;
 
 pub use common::HasLen;
 
@@ -94,12 +97,20 @@ pub mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 30_000_000).unwrap();
-        index_writer.add_document(doc!(title => r#"abc abc abc"#));
-        index_writer.add_document(doc!(title => r#"abc be be be be abc"#));
+        index_writer
+            .add_document(doc!(title => r#"abc abc abc"#))
+            .unwrap();
+        index_writer
+            .add_document(doc!(title => r#"abc be be be be abc"#))
+            .unwrap();
         for _ in 0..1_000 {
-            index_writer.add_document(doc!(title => r#"abc abc abc"#));
+            index_writer
+                .add_document(doc!(title => r#"abc abc abc"#))
+                .unwrap();
         }
-        index_writer.add_document(doc!(title => r#"abc be be be be abc"#));
+        index_writer
+            .add_document(doc!(title => r#"abc be be be be abc"#))
+            .unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
 
@@ -284,12 +295,12 @@ pub mod tests {
             {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "g b b d c g c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "g a b b a d c g c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -307,6 +318,58 @@ pub mod tests {
         assert_eq!(&positions[..], &[1u32, 4]);
     }
 
+    #[test]
+    fn test_vint_postings_codec() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let build_index = |postings_codec: PostingsCodec| {
+            let mut index = Index::create_in_ram(schema.clone());
+            index.set_postings_codec(postings_codec);
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1_000 {
+                let text = if i % 3 == 0 {
+                    iter::repeat("a").take(i % 5 + 1).collect::<Vec<_>>().join(" ")
+                } else {
+                    "b".to_string()
+                };
+                index_writer.add_document(doc!(text_field => text)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            index.load_searchers().unwrap();
+            index
+        };
+        let bitpacked_index = build_index(PostingsCodec::Bitpacked);
+        let vint_index = build_index(PostingsCodec::VInt);
+        let vint_searcher = vint_index.searcher();
+        assert_eq!(
+            vint_index.searchable_segment_metas().unwrap()[0].postings_codec(),
+            PostingsCodec::VInt
+        );
+        let bitpacked_searcher = bitpacked_index.searcher();
+        let term_a = Term::from_field_text(text_field, "a");
+        let read_postings = |segment_reader: &SegmentReader| {
+            segment_reader
+                .inverted_index(text_field)
+                .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap()
+        };
+        let mut expected = read_postings(bitpacked_searcher.segment_reader(0));
+        let mut postings = read_postings(vint_searcher.segment_reader(0));
+        let mut expected_positions = Vec::new();
+        let mut positions = Vec::new();
+        for &target in &[12u32, 13u32, 500u32, 501u32, 998u32] {
+            let expected_skip_result = expected.skip_next(target);
+            assert_eq!(postings.skip_next(target), expected_skip_result);
+            assert_eq!(postings.doc(), expected.doc());
+            assert_eq!(postings.term_freq(), expected.term_freq());
+            expected.positions(&mut expected_positions);
+            postings.positions(&mut positions);
+            assert_eq!(positions, expected_positions);
+        }
+        assert!(!postings.advance());
+    }
+
     #[test]
     fn test_skip_next() {
         let term_0 = Term::from_field_u64(Field(0), 0);
@@ -328,7 +391,7 @@ pub mod tests {
                     doc.add_u64(value_field, 2);
                     doc.add_u64(value_field, (i % 2) as u64);
 
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 assert!(index_writer.commit().is_ok());
             }
@@ -400,7 +463,7 @@ pub mod tests {
         // delete some of the documents
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.delete_term(term_0);
+            index_writer.delete_term(term_0).unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
@@ -450,7 +513,7 @@ pub mod tests {
         // delete everything else
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.delete_term(term_1);
+            index_writer.delete_term(term_1).unwrap();
 
             assert!(index_writer.commit().is_ok());
         }
@@ -519,7 +582,7 @@ pub mod tests {
                         doc.add_text(text_field, "c");
                     }
                     doc.add_text(text_field, "d");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 assert!(index_writer.commit().is_ok());
             }
@@ -0,0 +1,31 @@
+/// `PostingsCodec` describes how the blocks of doc ids and
+/// term frequencies of the postings lists are encoded.
+///
+/// The codec is chosen when a segment is created
+/// (see [`Index::set_postings_codec`](../struct.Index.html#method.set_postings_codec))
+/// and recorded in the segment metadata, so that segments
+/// written with different codecs can coexist in the same index.
+///
+/// In both cases, the last incomplete block of a posting list is
+/// encoded using variable length integers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PostingsCodec {
+    /// Full blocks of 128 docs are bitpacked, using SIMD
+    /// instructions when they are available.
+    ///
+    /// This is the default codec.
+    #[serde(rename = "bitpacked")]
+    Bitpacked,
+    /// All of the docs are encoded using variable length integers.
+    ///
+    /// This codec is slower and produces larger postings
+    /// but does not depend on the bitpacking kernels.
+    #[serde(rename = "vint")]
+    VInt,
+}
+
+impl Default for PostingsCodec {
+    fn default() -> PostingsCodec {
+        PostingsCodec::Bitpacked
+    }
+}
@@ -1,5 +1,7 @@
 use super::stacker::{Addr, MemoryArena, TermHashMap};
 
+use fastfield::MultiValueIntFastFieldWriter;
+
 use postings::recorder::{NothingRecorder, Recorder, TFAndPositionRecorder, TermFrequencyRecorder};
 use postings::UnorderedTermId;
 use postings::{FieldSerializer, InvertedIndexSerializer};
@@ -17,9 +19,10 @@ use Result;
 
 fn posting_from_field_entry(field_entry: &FieldEntry) -> Box<PostingsWriter> {
     match *field_entry.field_type() {
-        FieldType::Str(ref text_options) => text_options
-            .get_indexing_options()
-            .map(|indexing_options| match indexing_options.index_option() {
+        FieldType::Str(_) | FieldType::JsonObject(_) => field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(|index_record_option| match index_record_option {
                 IndexRecordOption::Basic => {
                     SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
                 }
@@ -31,10 +34,13 @@ fn posting_from_field_entry(field_entry: &FieldEntry) -> Box<PostingsWriter> {
                 }
             })
             .unwrap_or_else(|| SpecializedPostingsWriter::<NothingRecorder>::new_boxed()),
-        FieldType::U64(_) | FieldType::I64(_) | FieldType::HierarchicalFacet => {
-            SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
-        }
-        FieldType::Bytes => {
+        FieldType::U64(_)
+        | FieldType::I64(_)
+        | FieldType::F64(_)
+        | FieldType::Date(_)
+        | FieldType::IpAddr(_)
+        | FieldType::HierarchicalFacet => SpecializedPostingsWriter::<NothingRecorder>::new_boxed(),
+        FieldType::Bytes(_) => {
             // FieldType::Bytes cannot actually be indexed.
             // TODO fix during the indexer refactoring described in #276
             SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
@@ -71,7 +77,17 @@ impl MultiFieldPostingsWriter {
         self.term_index.mem_usage() + self.heap.mem_usage()
     }
 
-    pub fn index_text(&mut self, doc: DocId, field: Field, token_stream: &mut TokenStream) -> u32 {
+    /// Indexes the tokens of a text.
+    ///
+    /// If the field is a string fast field, the term ids of
+    /// the tokens are pushed to `term_id_fast_field_writer_opt`.
+    pub fn index_text(
+        &mut self,
+        doc: DocId,
+        field: Field,
+        token_stream: &mut TokenStream,
+        term_id_fast_field_writer_opt: Option<&mut MultiValueIntFastFieldWriter>,
+    ) -> u32 {
         let postings_writer = self.per_field_postings_writers[field.0 as usize].deref_mut();
         postings_writer.index_text(
             &mut self.term_index,
@@ -79,12 +95,18 @@ impl MultiFieldPostingsWriter {
             field,
             token_stream,
             &mut self.heap,
+            term_id_fast_field_writer_opt,
         )
     }
 
     pub fn subscribe(&mut self, doc: DocId, term: &Term) -> UnorderedTermId {
+        self.subscribe_at(doc, 0u32, term)
+    }
+
+    /// Records that a document contains a term at a given position.
+    pub fn subscribe_at(&mut self, doc: DocId, position: u32, term: &Term) -> UnorderedTermId {
         let postings_writer = self.per_field_postings_writers[term.field().0 as usize].deref_mut();
-        postings_writer.subscribe(&mut self.term_index, doc, 0u32, term, &mut self.heap)
+        postings_writer.subscribe(&mut self.term_index, doc, position, term, &mut self.heap)
     }
 
     /// Serialize the inverted index.
@@ -141,8 +163,13 @@ impl MultiFieldPostingsWriter {
                         .collect();
                     unordered_term_mappings.insert(field, mapping);
                 }
-                FieldType::U64(_) | FieldType::I64(_) => {}
-                FieldType::Bytes => {}
+                FieldType::U64(_)
+                | FieldType::I64(_)
+                | FieldType::F64(_)
+                | FieldType::Date(_)
+                | FieldType::IpAddr(_)
+                | FieldType::JsonObject(_) => {}
+                FieldType::Bytes(_) => {}
             }
 
             let postings_writer = &self.per_field_postings_writers[field.0 as usize];
@@ -192,6 +219,9 @@ pub trait PostingsWriter {
     ) -> io::Result<()>;
 
     /// Tokenize a text and subscribe all of its token.
+    ///
+    /// The term ids of the tokens are pushed to
+    /// `term_id_fast_field_writer_opt` if it is given.
     fn index_text(
         &mut self,
         term_index: &mut TermHashMap,
@@ -199,11 +229,16 @@ pub trait PostingsWriter {
         field: Field,
         token_stream: &mut TokenStream,
         heap: &mut MemoryArena,
+        mut term_id_fast_field_writer_opt: Option<&mut MultiValueIntFastFieldWriter>,
     ) -> u32 {
         let mut term = Term::for_field(field);
         let mut sink = |token: &Token| {
             term.set_text(token.text.as_str());
-            self.subscribe(term_index, doc_id, token.position as u32, &term, heap);
+            let unordered_term_id =
+                self.subscribe(term_index, doc_id, token.position as u32, &term, heap);
+            if let Some(ref mut term_id_fast_field_writer) = term_id_fast_field_writer_opt {
+                term_id_fast_field_writer.add_val(unordered_term_id);
+            }
         };
         token_stream.process(&mut sink)
     }
@@ -10,10 +10,12 @@ use postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
 use postings::serializer::PostingsSerializer;
 use postings::FreqReadingOption;
 use postings::Postings;
+use postings::PostingsCodec;
 use postings::SkipReader;
 use postings::USE_SKIP_INFO_LIMIT;
 use schema::IndexRecordOption;
 use std::cmp::Ordering;
+use std::mem;
 use DocId;
 
 const EMPTY_ARR: [u8; 0] = [];
@@ -86,7 +88,8 @@ impl SegmentPostings {
     pub fn create_from_docs(docs: &[u32]) -> SegmentPostings {
         let mut buffer = Vec::new();
         {
-            let mut postings_serializer = PostingsSerializer::new(&mut buffer, false, false);
+            let mut postings_serializer =
+                PostingsSerializer::new(&mut buffer, false, false, PostingsCodec::default());
             for &doc in docs {
                 postings_serializer.write_doc(doc, 1u32);
             }
@@ -99,6 +102,7 @@ impl SegmentPostings {
             OwnedRead::new(buffer),
             IndexRecordOption::Basic,
             IndexRecordOption::Basic,
+            PostingsCodec::default(),
         );
         SegmentPostings::from_block_postings(block_segment_postings, None)
     }
@@ -290,6 +294,16 @@ impl DocSet for SegmentPostings {
             }
         }
     }
+
+    fn memory_usage(&self) -> usize {
+        // the decoded blocks of docs and freqs are held inline,
+        // the block of positions is boxed.
+        if self.position_computer.is_some() {
+            COMPRESSION_BLOCK_SIZE * mem::size_of::<u32>()
+        } else {
+            0
+        }
+    }
 }
 
 impl HasLen for SegmentPostings {
@@ -327,6 +341,7 @@ pub struct BlockSegmentPostings {
     doc_decoder: BlockDecoder,
     freq_decoder: BlockDecoder,
     freq_reading_option: FreqReadingOption,
+    postings_codec: PostingsCodec,
 
     doc_freq: usize,
     doc_offset: DocId,
@@ -364,6 +379,7 @@ impl BlockSegmentPostings {
         data: OwnedRead,
         record_option: IndexRecordOption,
         requested_option: IndexRecordOption,
+        postings_codec: PostingsCodec,
     ) -> BlockSegmentPostings {
         let freq_reading_option = match (record_option, requested_option) {
             (IndexRecordOption::Basic, _) => FreqReadingOption::NoFreq,
@@ -383,6 +399,7 @@ impl BlockSegmentPostings {
             doc_decoder: BlockDecoder::new(),
             freq_decoder: BlockDecoder::with_val(1),
             freq_reading_option,
+            postings_codec,
             doc_offset: 0,
             doc_freq,
             remaining_data: postings_data,
@@ -459,24 +476,10 @@ impl BlockSegmentPostings {
         self.doc_decoder.output_len
     }
 
-    /// position on a block that may contains `doc_id`.
-    /// Always advance the current block.
-    ///
-    /// Returns true if a block that has an element greater or equal to the target is found.
-    /// Returning true does not guarantee that the smallest element of the block is smaller
-    /// than the target. It only guarantees that the last element is greater or equal.
-    ///
-    /// Returns false iff all of the document remaining are smaller than
-    /// `doc_id`. In that case, all of these document are consumed.
-    ///
-    pub fn skip_to(&mut self, target_doc: DocId) -> BlockSegmentPostingsSkipResult {
-        let mut skip_freqs = 0u32;
-        while self.skip_reader.advance() {
-            if self.skip_reader.doc() >= target_doc {
-                // the last document of the current block is larger
-                // than the target.
-                //
-                // We found our block!
+    /// Decodes the full block the skip reader is currently pointing to.
+    fn read_block(&mut self) {
+        match self.postings_codec {
+            PostingsCodec::Bitpacked => {
                 let num_bits = self.skip_reader.doc_num_bits();
                 let num_consumed_bytes = self.doc_decoder.uncompress_block_sorted(
                     self.remaining_data.as_ref(),
@@ -498,13 +501,64 @@ impl BlockSegmentPostings {
                         self.remaining_data.advance(num_consumed_bytes);
                     }
                 }
+            }
+            PostingsCodec::VInt => {
+                let num_consumed_bytes = self.doc_decoder.uncompress_vint_sorted(
+                    self.remaining_data.as_ref(),
+                    self.doc_offset,
+                    COMPRESSION_BLOCK_SIZE,
+                );
+                self.remaining_data.advance(num_consumed_bytes);
+                match self.freq_reading_option {
+                    FreqReadingOption::NoFreq => {}
+                    // term frequencies need to be decoded to be skipped.
+                    FreqReadingOption::SkipFreq | FreqReadingOption::ReadFreq => {
+                        let num_consumed_bytes = self.freq_decoder.uncompress_vint_unsorted(
+                            self.remaining_data.as_ref(),
+                            COMPRESSION_BLOCK_SIZE,
+                        );
+                        self.remaining_data.advance(num_consumed_bytes);
+                    }
+                }
+            }
+        }
+    }
+
+    /// position on a block that may contains `doc_id`.
+    /// Always advance the current block.
+    ///
+    /// Returns true if a block that has an element greater or equal to the target is found.
+    /// Returning true does not guarantee that the smallest element of the block is smaller
+    /// than the target. It only guarantees that the last element is greater or equal.
+    ///
+    /// Returns false iff all of the document remaining are smaller than
+    /// `doc_id`. In that case, all of these document are consumed.
+    ///
+    pub fn skip_to(&mut self, target_doc: DocId) -> BlockSegmentPostingsSkipResult {
+        let mut skip_freqs = 0u32;
+        while self.skip_reader.advance() {
+            if self.skip_reader.doc() >= target_doc {
+                // the last document of the current block is larger
+                // than the target.
+                //
+                // We found our block!
+                self.read_block();
                 self.doc_offset = self.skip_reader.doc();
                 return BlockSegmentPostingsSkipResult::Success(skip_freqs);
             } else {
                 skip_freqs += self.skip_reader.tf_sum();
-                let advance_len = self.skip_reader.total_block_len();
+                match self.postings_codec {
+                    PostingsCodec::Bitpacked => {
+                        let advance_len = self.skip_reader.total_block_len();
+                        self.remaining_data.advance(advance_len);
+                    }
+                    PostingsCodec::VInt => {
+                        // The length of a vint block is unknown
+                        // until the block is decoded.
+                        self.read_block();
+                    }
+                }
                 self.doc_offset = self.skip_reader.doc();
-                self.remaining_data.advance(advance_len);
             }
         }
 
@@ -544,27 +598,7 @@ impl BlockSegmentPostings {
     /// Returns false iff there was no remaining blocks.
     pub fn advance(&mut self) -> bool {
         if self.skip_reader.advance() {
-            let num_bits = self.skip_reader.doc_num_bits();
-            let num_consumed_bytes = self.doc_decoder.uncompress_block_sorted(
-                self.remaining_data.as_ref(),
-                self.doc_offset,
-                num_bits,
-            );
-            self.remaining_data.advance(num_consumed_bytes);
-            let tf_num_bits = self.skip_reader.tf_num_bits();
-            match self.freq_reading_option {
-                FreqReadingOption::NoFreq => {}
-                FreqReadingOption::SkipFreq => {
-                    let num_bytes_to_skip = compressed_block_size(tf_num_bits);
-                    self.remaining_data.advance(num_bytes_to_skip);
-                }
-                FreqReadingOption::ReadFreq => {
-                    let num_consumed_bytes = self
-                        .freq_decoder
-                        .uncompress_block_unsorted(self.remaining_data.as_ref(), tf_num_bits);
-                    self.remaining_data.advance(num_consumed_bytes);
-                }
-            }
+            self.read_block();
             // it will be used as the next offset.
             self.doc_offset = self.doc_decoder.output(COMPRESSION_BLOCK_SIZE - 1);
             true
@@ -597,6 +631,7 @@ impl BlockSegmentPostings {
             doc_decoder: BlockDecoder::new(),
             freq_decoder: BlockDecoder::with_val(1),
             freq_reading_option: FreqReadingOption::NoFreq,
+            postings_codec: PostingsCodec::default(),
 
             doc_offset: 0,
             doc_freq: 0,
@@ -716,9 +751,9 @@ mod tests {
         let mut last_doc = 0u32;
         for doc in docs {
             for _ in last_doc..doc {
-                index_writer.add_document(doc!(int_field=>1u64));
+                index_writer.add_document(doc!(int_field=>1u64)).unwrap();
             }
-            index_writer.add_document(doc!(int_field=>0u64));
+            index_writer.add_document(doc!(int_field=>0u64)).unwrap();
             last_doc = doc + 1;
         }
         index_writer.commit().unwrap();
@@ -788,7 +823,7 @@ mod tests {
         // the other containing odd numbers.
         for i in 0..6 {
             let doc = doc!(int_field=> (i % 2) as u64);
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
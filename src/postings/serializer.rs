@@ -6,11 +6,12 @@ use directory::WritePtr;
 use positions::PositionSerializer;
 use postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
 use postings::skip::SkipSerializer;
+use postings::PostingsCodec;
 use postings::USE_SKIP_INFO_LIMIT;
 use schema::Schema;
 use schema::{Field, FieldEntry, FieldType};
 use std::io::{self, Write};
-use termdict::{TermDictionaryBuilder, TermOrdinal};
+use termdict::{TermDictionaryBuilder, TermDictionaryCodec, TermOrdinal};
 use DocId;
 use Result;
 
@@ -51,6 +52,8 @@ pub struct InvertedIndexSerializer {
     positions_write: CompositeWrite<WritePtr>,
     positionsidx_write: CompositeWrite<WritePtr>,
     schema: Schema,
+    postings_codec: PostingsCodec,
+    term_dictionary_codec: TermDictionaryCodec,
 }
 
 impl InvertedIndexSerializer {
@@ -61,6 +64,8 @@ impl InvertedIndexSerializer {
         positions_write: CompositeWrite<WritePtr>,
         positionsidx_write: CompositeWrite<WritePtr>,
         schema: Schema,
+        postings_codec: PostingsCodec,
+        term_dictionary_codec: TermDictionaryCodec,
     ) -> Result<InvertedIndexSerializer> {
         Ok(InvertedIndexSerializer {
             terms_write,
@@ -68,6 +73,8 @@ impl InvertedIndexSerializer {
             positions_write,
             positionsidx_write,
             schema,
+            postings_codec,
+            term_dictionary_codec,
         })
     }
 
@@ -80,6 +87,8 @@ impl InvertedIndexSerializer {
             CompositeWrite::wrap(segment.open_write(POSITIONS)?),
             CompositeWrite::wrap(segment.open_write(POSITIONSSKIP)?),
             segment.schema(),
+            segment.meta().postings_codec(),
+            segment.meta().term_dictionary_codec(),
         )
     }
 
@@ -105,6 +114,8 @@ impl InvertedIndexSerializer {
             postings_write,
             positions_write,
             positionsidx_write,
+            self.postings_codec,
+            self.term_dictionary_codec,
         )
     }
 
@@ -136,6 +147,8 @@ impl<'a> FieldSerializer<'a> {
         postings_write: &'a mut CountingWriter<WritePtr>,
         positions_write: &'a mut CountingWriter<WritePtr>,
         positionsidx_write: &'a mut CountingWriter<WritePtr>,
+        postings_codec: PostingsCodec,
+        term_dictionary_codec: TermDictionaryCodec,
     ) -> io::Result<FieldSerializer<'a>> {
         let (term_freq_enabled, position_enabled): (bool, bool) = match field_type {
             FieldType::Str(ref text_options) => {
@@ -149,12 +162,28 @@ impl<'a> FieldSerializer<'a> {
                     (false, false)
                 }
             }
+            FieldType::JsonObject(_) => field_type
+                .get_index_record_option()
+                .map(|index_option| {
+                    (
+                        index_option.is_termfreq_enabled(),
+                        index_option.is_position_enabled(),
+                    )
+                })
+                .unwrap_or((false, false)),
             _ => (false, false),
         };
-        let term_dictionary_builder =
-            TermDictionaryBuilder::create(term_dictionary_write, &field_type)?;
-        let postings_serializer =
-            PostingsSerializer::new(postings_write, term_freq_enabled, position_enabled);
+        let term_dictionary_builder = TermDictionaryBuilder::create_with_codec(
+            term_dictionary_write,
+            &field_type,
+            term_dictionary_codec,
+        )?;
+        let postings_serializer = PostingsSerializer::new(
+            postings_write,
+            term_freq_enabled,
+            position_enabled,
+            postings_codec,
+        );
         let positions_serializer_opt = if position_enabled {
             Some(PositionSerializer::new(positions_write, positionsidx_write))
         } else {
@@ -312,6 +341,7 @@ pub struct PostingsSerializer<W: Write> {
 
     termfreq_enabled: bool,
     termfreq_sum_enabled: bool,
+    postings_codec: PostingsCodec,
 }
 
 impl<W: Write> PostingsSerializer<W> {
@@ -319,6 +349,7 @@ impl<W: Write> PostingsSerializer<W> {
         write: W,
         termfreq_enabled: bool,
         termfreq_sum_enabled: bool,
+        postings_codec: PostingsCodec,
     ) -> PostingsSerializer<W> {
         PostingsSerializer {
             output_write: CountingWriter::wrap(write),
@@ -332,15 +363,24 @@ impl<W: Write> PostingsSerializer<W> {
             last_doc_id_encoded: 0u32,
             termfreq_enabled,
             termfreq_sum_enabled,
+            postings_codec,
         }
     }
 
     fn write_block(&mut self) {
         {
             // encode the doc ids
-            let (num_bits, block_encoded): (u8, &[u8]) = self
-                .block_encoder
-                .compress_block_sorted(&self.block.doc_ids(), self.last_doc_id_encoded);
+            let (num_bits, block_encoded): (u8, &[u8]) = match self.postings_codec {
+                PostingsCodec::Bitpacked => self
+                    .block_encoder
+                    .compress_block_sorted(&self.block.doc_ids(), self.last_doc_id_encoded),
+                // vint blocks do not have a num bits. The skip data records 0 instead.
+                PostingsCodec::VInt => (
+                    0u8,
+                    self.block_encoder
+                        .compress_vint_sorted(&self.block.doc_ids(), self.last_doc_id_encoded),
+                ),
+            };
             self.last_doc_id_encoded = self.block.last_doc();
             self.skip_write
                 .write_doc(self.last_doc_id_encoded, num_bits);
@@ -349,9 +389,16 @@ impl<W: Write> PostingsSerializer<W> {
         }
         if self.termfreq_enabled {
             // encode the term_freqs
-            let (num_bits, block_encoded): (u8, &[u8]) = self
-                .block_encoder
-                .compress_block_unsorted(&self.block.term_freqs());
+            let (num_bits, block_encoded): (u8, &[u8]) = match self.postings_codec {
+                PostingsCodec::Bitpacked => self
+                    .block_encoder
+                    .compress_block_unsorted(&self.block.term_freqs()),
+                PostingsCodec::VInt => (
+                    0u8,
+                    self.block_encoder
+                        .compress_vint_unsorted(&self.block.term_freqs()),
+                ),
+            };
             self.postings_write.extend(block_encoded);
             self.skip_write.write_term_freq(num_bits);
             if self.termfreq_sum_enabled {
@@ -96,10 +96,10 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
-        index_writer.add_document(doc!(field=>"aaa"));
-        index_writer.add_document(doc!(field=>"bbb"));
+        index_writer.add_document(doc!(field=>"aaa")).unwrap();
+        index_writer.add_document(doc!(field=>"bbb")).unwrap();
         index_writer.commit().unwrap();
-        index_writer.add_document(doc!(field=>"ccc"));
+        index_writer.add_document(doc!(field=>"ccc")).unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
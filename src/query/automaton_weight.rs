@@ -1,13 +1,47 @@
 use common::BitSet;
+use core::InvertedIndexReader;
 use core::SegmentReader;
+use error::TantivyError;
 use fst::Automaton;
+use postings::TermInfo;
 use query::BitSetDocSet;
 use query::ConstScorer;
 use query::{Scorer, Weight};
 use schema::{Field, IndexRecordOption};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use termdict::{TermDictionary, TermStreamer};
 use Result;
 
+/// Limits the number of terms a multi-term query,
+/// like a `RegexQuery` or a `FuzzyTermQuery`, expands to.
+///
+/// The limit applies to the terms of each segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpansionLimit {
+    /// The search fails with an `ExpansionLimitExceeded` error
+    /// if the query expands to more than `max_expansions` terms.
+    Error(usize),
+    /// The query is restricted to the `max_expansions` terms
+    /// with the highest document frequency.
+    TopDocFreq(usize),
+}
+
+/// Adds the documents of the postings of `term_info` to `doc_bitset`.
+fn insert_docs(
+    inverted_index: &InvertedIndexReader,
+    term_info: &TermInfo,
+    doc_bitset: &mut BitSet,
+) {
+    let mut block_segment_postings =
+        inverted_index.read_block_postings_from_terminfo(term_info, IndexRecordOption::Basic);
+    while block_segment_postings.advance() {
+        for &doc in block_segment_postings.docs() {
+            doc_bitset.insert(doc);
+        }
+    }
+}
+
 /// A weight struct for Fuzzy Term and Regex Queries
 pub struct AutomatonWeight<A>
 where
@@ -15,6 +49,7 @@ where
 {
     field: Field,
     automaton: A,
+    expansion_limit: Option<ExpansionLimit>,
 }
 
 impl<A> AutomatonWeight<A>
@@ -23,7 +58,17 @@ where
 {
     /// Create a new AutomationWeight
     pub fn new(field: Field, automaton: A) -> AutomatonWeight<A> {
-        AutomatonWeight { field, automaton }
+        AutomatonWeight {
+            field,
+            automaton,
+            expansion_limit: None,
+        }
+    }
+
+    /// Limits the number of terms the automaton expands to.
+    pub fn with_expansion_limit(mut self, expansion_limit: Option<ExpansionLimit>) -> Self {
+        self.expansion_limit = expansion_limit;
+        self
     }
 
     fn automaton_stream<'a>(&'a self, term_dict: &'a TermDictionary) -> TermStreamer<'a, &'a A> {
@@ -43,13 +88,30 @@ where
         let inverted_index = reader.inverted_index(self.field);
         let term_dict = inverted_index.terms();
         let mut term_stream = self.automaton_stream(term_dict);
-        while term_stream.advance() {
-            let term_info = term_stream.value();
-            let mut block_segment_postings = inverted_index
-                .read_block_postings_from_terminfo(term_info, IndexRecordOption::Basic);
-            while block_segment_postings.advance() {
-                for &doc in block_segment_postings.docs() {
-                    doc_bitset.insert(doc);
+        match self.expansion_limit {
+            Some(ExpansionLimit::TopDocFreq(max_expansions)) => {
+                // min-heap of the term infos with the highest doc freqs.
+                let mut top_term_infos = BinaryHeap::with_capacity(max_expansions + 1);
+                while term_stream.advance() {
+                    top_term_infos.push(Reverse(term_stream.value().clone()));
+                    if top_term_infos.len() > max_expansions {
+                        top_term_infos.pop();
+                    }
+                }
+                for Reverse(term_info) in top_term_infos {
+                    insert_docs(&inverted_index, &term_info, &mut doc_bitset);
+                }
+            }
+            expansion_limit_opt => {
+                let mut num_expansions = 0;
+                while term_stream.advance() {
+                    num_expansions += 1;
+                    if let Some(ExpansionLimit::Error(max_expansions)) = expansion_limit_opt {
+                        if num_expansions > max_expansions {
+                            return Err(TantivyError::ExpansionLimitExceeded { max_expansions });
+                        }
+                    }
+                    insert_docs(&inverted_index, term_stream.value(), &mut doc_bitset);
                 }
             }
         }
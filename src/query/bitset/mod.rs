@@ -116,6 +116,10 @@ impl DocSet for BitSetDocSet {
     fn size_hint(&self) -> u32 {
         self.docs.len() as u32
     }
+
+    fn memory_usage(&self) -> usize {
+        self.docs.num_bytes()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,269 @@
+/*!
+Block join queries, relating parent documents to their child documents.
+
+A block is a group of documents indexed contiguously via
+[`IndexWriter::add_document_block`](../indexer/struct.IndexWriter.html#method.add_document_block),
+made of child documents followed by their parent document.
+Parent documents are identified by a term, that none of the
+child documents contain.
+
+All of the child documents are expected to be indexed within a block:
+a document that is not a parent simply belongs to the block
+of the first parent following it.
+
+- [`ToParentQuery`](./struct.ToParentQuery.html) matches the parents
+of the children matched by a query.
+- [`ToChildQuery`](./struct.ToChildQuery.html) matches the children
+of the parents matched by a query.
+*/
+
+mod to_child_query;
+mod to_parent_query;
+
+pub use self::to_child_query::ToChildQuery;
+pub use self::to_parent_query::ToParentQuery;
+
+use core::SegmentReader;
+use docset::DocSet;
+use schema::{IndexRecordOption, Term};
+use DocId;
+
+/// Returns the sorted `DocId`s of the parent documents of the segment.
+///
+/// Deleted parents are returned as well, since they still delimit
+/// the blocks of the segment.
+fn parent_docs(reader: &SegmentReader, parent_term: &Term) -> Vec<DocId> {
+    let mut parents = Vec::new();
+    if let Some(mut postings) = reader
+        .inverted_index(parent_term.field())
+        .read_postings_no_deletes(parent_term, IndexRecordOption::Basic)
+    {
+        while postings.advance() {
+            parents.push(postings.doc());
+        }
+    }
+    parents
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::TestCollector;
+    use core::Index;
+    use futures::Future;
+    use query::{Query, TermQuery};
+    use schema::{Document, Field, IndexRecordOption, Schema, Term, STORED, STRING};
+    use tests::assert_nearly_equals;
+    use DocAddress;
+
+    fn product_block(
+        doc_type: Field,
+        name: Field,
+        color: Field,
+        product: &str,
+        colors: &[&str],
+    ) -> Vec<Document> {
+        let mut block: Vec<Document> = colors
+            .iter()
+            .map(|variant_color| doc!(doc_type => "variant", color => *variant_color))
+            .collect();
+        block.push(doc!(doc_type => "product", name => product));
+        block
+    }
+
+    #[test]
+    fn test_block_join_queries() {
+        let mut schema_builder = Schema::builder();
+        let doc_type = schema_builder.add_text_field("type", STRING);
+        let name = schema_builder.add_text_field("name", STRING | STORED);
+        let color = schema_builder.add_text_field("color", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document_block(product_block(
+                    doc_type,
+                    name,
+                    color,
+                    "shirt",
+                    &["red", "blue", "red"],
+                ))
+                .unwrap();
+            index_writer
+                .add_document_block(product_block(doc_type, name, color, "hat", &[]))
+                .unwrap();
+            index_writer
+                .add_document_block(product_block(doc_type, name, color, "shoes", &["blue"]))
+                .unwrap();
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let parent_term = Term::from_field_text(doc_type, "product");
+        let term_query = |field, text| -> Box<Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, text),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let docs = |query: &Query| {
+            searcher
+                .search(query, &TestCollector)
+                .unwrap()
+                .docs()
+                .iter()
+                .map(|doc_address| doc_address.1)
+                .collect::<Vec<_>>()
+        };
+
+        let red_products = ToParentQuery::new(term_query(color, "red"), parent_term.clone());
+        assert_eq!(docs(&red_products), vec![3]);
+        let blue_products = ToParentQuery::new(term_query(color, "blue"), parent_term.clone());
+        assert_eq!(docs(&blue_products), vec![3, 6]);
+        assert_eq!(blue_products.count(&searcher).unwrap(), 2);
+        // parents matched by the child query are ignored.
+        let hats = ToParentQuery::new(term_query(name, "hat"), parent_term.clone());
+        assert!(docs(&hats).is_empty());
+
+        let shirt_variants = ToChildQuery::new(term_query(name, "shirt"), parent_term.clone());
+        assert_eq!(docs(&shirt_variants), vec![0, 1, 2]);
+        let hat_variants = ToChildQuery::new(term_query(name, "hat"), parent_term.clone());
+        assert!(docs(&hat_variants).is_empty());
+        let variants = ToChildQuery::new(term_query(doc_type, "product"), parent_term.clone());
+        assert_eq!(docs(&variants), vec![0, 1, 2, 5]);
+        // children matched by the parent query are ignored.
+        let red_variants = ToChildQuery::new(term_query(color, "red"), parent_term.clone());
+        assert!(docs(&red_variants).is_empty());
+
+        let shirt = searcher.doc(DocAddress(0, 3)).unwrap();
+        assert_eq!(shirt.get_first(name).unwrap().text(), Some("shirt"));
+    }
+
+    #[test]
+    fn test_to_parent_query_scores() {
+        let mut schema_builder = Schema::builder();
+        let doc_type = schema_builder.add_text_field("type", STRING);
+        let name = schema_builder.add_text_field("name", STRING);
+        let color = schema_builder.add_text_field("color", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document_block(product_block(
+                    doc_type,
+                    name,
+                    color,
+                    "shirt",
+                    &["red", "red"],
+                ))
+                .unwrap();
+            index_writer
+                .add_document_block(product_block(doc_type, name, color, "hat", &["red"]))
+                .unwrap();
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let parent_term = Term::from_field_text(doc_type, "product");
+        let red = TermQuery::new(
+            Term::from_field_text(color, "red"),
+            IndexRecordOption::Basic,
+        );
+        let red_scores = searcher.search(&red, &TestCollector).unwrap();
+        let red_products = ToParentQuery::new(Box::new(red), parent_term.clone());
+        let product_scores = searcher.search(&red_products, &TestCollector).unwrap();
+        assert_eq!(
+            product_scores
+                .docs()
+                .iter()
+                .map(|doc_address| doc_address.1)
+                .collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+        // the score of a parent is the sum of the scores of its matching children.
+        assert_nearly_equals(
+            product_scores.scores()[0],
+            red_scores.scores()[0] + red_scores.scores()[1],
+        );
+        assert_nearly_equals(product_scores.scores()[1], red_scores.scores()[2]);
+
+        // the shirt is a parent, and has no parent.
+        let shirt_parents = ToParentQuery::new(
+            Box::new(TermQuery::new(
+                Term::from_field_text(name, "shirt"),
+                IndexRecordOption::Basic,
+            )),
+            parent_term.clone(),
+        );
+        let shirt_variants = ToChildQuery::new(Box::new(shirt_parents), parent_term);
+        assert!(searcher
+            .search(&shirt_variants, &TestCollector)
+            .unwrap()
+            .docs()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_block_join_with_deletes_and_merge() {
+        let mut schema_builder = Schema::builder();
+        let doc_type = schema_builder.add_text_field("type", STRING);
+        let name = schema_builder.add_text_field("name", STRING);
+        let color = schema_builder.add_text_field("color", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        let mut block = product_block(doc_type, name, color, "shirt", &["red", "blue"]);
+        for doc in &mut block {
+            doc.add_text(name, "shirt");
+        }
+        index_writer.add_document_block(block).unwrap();
+        assert!(index_writer.commit().is_ok());
+        index_writer
+            .add_document_block(product_block(doc_type, name, color, "hat", &["red"]))
+            .unwrap();
+        index_writer
+            .add_document_block(product_block(doc_type, name, color, "shoes", &["red"]))
+            .unwrap();
+        assert!(index_writer.commit().is_ok());
+
+        let parent_term = Term::from_field_text(doc_type, "product");
+        let red_products = ToParentQuery::new(
+            Box::new(TermQuery::new(
+                Term::from_field_text(color, "red"),
+                IndexRecordOption::Basic,
+            )),
+            parent_term.clone(),
+        );
+        let count = |query: &Query| {
+            index.load_searchers().unwrap();
+            query.count(&index.searcher()).unwrap()
+        };
+        assert_eq!(count(&red_products), 3);
+
+        // deleting the whole block of the shirt.
+        index_writer
+            .delete_term(Term::from_field_text(name, "shirt"))
+            .unwrap();
+        assert!(index_writer.commit().is_ok());
+        assert_eq!(count(&red_products), 2);
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merge failed");
+        index_writer.wait_merging_threads().unwrap();
+        index.load_searchers().unwrap();
+        assert_eq!(index.searcher().segment_readers().len(), 1);
+        assert_eq!(count(&red_products), 2);
+        let shoes_variants = ToChildQuery::new(
+            Box::new(TermQuery::new(
+                Term::from_field_text(name, "shoes"),
+                IndexRecordOption::Basic,
+            )),
+            parent_term,
+        );
+        assert_eq!(count(&shoes_variants), 1);
+    }
+}
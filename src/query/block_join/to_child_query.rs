@@ -0,0 +1,160 @@
+use super::parent_docs;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use docset::DocSet;
+use fastfield::DeleteBitSet;
+use query::{Query, Scorer, Weight};
+use schema::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::mem;
+use DocId;
+use Result;
+use Score;
+
+/// `ToChildQuery` matches the child documents of the
+/// parent documents matched by a query.
+///
+/// For instance, given blocks made of the variants of a product
+/// followed by the product itself, wrapping the query `name:shirt`
+/// matches all of the variants of the shirt.
+///
+/// The children get the score of their parent.
+/// The child documents matched by the wrapped query are ignored.
+pub struct ToChildQuery {
+    parent_query: Box<Query>,
+    parent_term: Term,
+}
+
+impl ToChildQuery {
+    /// Creates a new `ToChildQuery` given the query matching the
+    /// parents, and the term identifying the parent documents.
+    pub fn new(parent_query: Box<Query>, parent_term: Term) -> ToChildQuery {
+        ToChildQuery {
+            parent_query,
+            parent_term,
+        }
+    }
+}
+
+impl Clone for ToChildQuery {
+    fn clone(&self) -> Self {
+        ToChildQuery {
+            parent_query: self.parent_query.box_clone(),
+            parent_term: self.parent_term.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ToChildQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ToChild(parent_query={:?}, parent_term={:?})",
+            self.parent_query, self.parent_term
+        )
+    }
+}
+
+impl Query for ToChildQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        let parent_weight = self.parent_query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(ToChildWeight {
+            parent_weight,
+            parent_term: self.parent_term.clone(),
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.parent_query.query_terms(term_set);
+    }
+}
+
+/// Weight associated to the `ToChildQuery` query.
+pub struct ToChildWeight {
+    parent_weight: Box<Weight>,
+    parent_term: Term,
+}
+
+impl Weight for ToChildWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let parent_scorer = self.parent_weight.scorer(reader)?;
+        Ok(Box::new(ToChildScorer {
+            parent_scorer,
+            parents: parent_docs(reader, &self.parent_term),
+            delete_bitset_opt: reader.delete_bitset().cloned(),
+            next_child: 0u32,
+            parent: 0u32,
+            doc: 0u32,
+            score: 0f32,
+        }))
+    }
+}
+
+/// Scorer associated to the `ToChildQuery` query.
+pub struct ToChildScorer {
+    parent_scorer: Box<Scorer>,
+    parents: Vec<DocId>,
+    delete_bitset_opt: Option<DeleteBitSet>,
+    // the children of the current block that remain to be
+    // emitted range from `next_child` to `parent` excluded.
+    next_child: DocId,
+    parent: DocId,
+    doc: DocId,
+    score: Score,
+}
+
+impl ToChildScorer {
+    fn is_deleted(&self, doc: DocId) -> bool {
+        self.delete_bitset_opt
+            .as_ref()
+            .map(|delete_bitset| delete_bitset.is_deleted(doc))
+            .unwrap_or(false)
+    }
+}
+
+impl DocSet for ToChildScorer {
+    fn advance(&mut self) -> bool {
+        loop {
+            while self.next_child < self.parent {
+                let child = self.next_child;
+                self.next_child += 1;
+                if !self.is_deleted(child) {
+                    self.doc = child;
+                    return true;
+                }
+            }
+            if !self.parent_scorer.advance() {
+                return false;
+            }
+            let parent = self.parent_scorer.doc();
+            if let Ok(parent_ord) = self.parents.binary_search(&parent) {
+                self.next_child = if parent_ord == 0 {
+                    0u32
+                } else {
+                    self.parents[parent_ord - 1] + 1
+                };
+                self.parent = parent;
+                self.score = self.parent_scorer.score();
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.parent_scorer.size_hint()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.parent_scorer.memory_usage() + self.parents.capacity() * mem::size_of::<DocId>()
+    }
+}
+
+impl Scorer for ToChildScorer {
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
@@ -0,0 +1,171 @@
+use super::parent_docs;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use docset::DocSet;
+use fastfield::DeleteBitSet;
+use query::{Query, Scorer, Weight};
+use schema::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::mem;
+use DocId;
+use Result;
+use Score;
+
+/// `ToParentQuery` matches the parent documents of the
+/// child documents matched by a query.
+///
+/// For instance, given blocks made of the variants of a product
+/// followed by the product itself, wrapping the query `color:red`
+/// matches the products having at least one red variant.
+///
+/// The score of a parent is the sum of the scores of its
+/// matching children.
+/// The parent documents matched by the wrapped query are ignored.
+pub struct ToParentQuery {
+    child_query: Box<Query>,
+    parent_term: Term,
+}
+
+impl ToParentQuery {
+    /// Creates a new `ToParentQuery` given the query matching the
+    /// children, and the term identifying the parent documents.
+    pub fn new(child_query: Box<Query>, parent_term: Term) -> ToParentQuery {
+        ToParentQuery {
+            child_query,
+            parent_term,
+        }
+    }
+}
+
+impl Clone for ToParentQuery {
+    fn clone(&self) -> Self {
+        ToParentQuery {
+            child_query: self.child_query.box_clone(),
+            parent_term: self.parent_term.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ToParentQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ToParent(child_query={:?}, parent_term={:?})",
+            self.child_query, self.parent_term
+        )
+    }
+}
+
+impl Query for ToParentQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        let child_weight = self.child_query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(ToParentWeight {
+            child_weight,
+            parent_term: self.parent_term.clone(),
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.child_query.query_terms(term_set);
+    }
+}
+
+/// Weight associated to the `ToParentQuery` query.
+pub struct ToParentWeight {
+    child_weight: Box<Weight>,
+    parent_term: Term,
+}
+
+impl Weight for ToParentWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let child_scorer = self.child_weight.scorer(reader)?;
+        Ok(Box::new(ToParentScorer {
+            child_scorer,
+            parents: parent_docs(reader, &self.parent_term),
+            delete_bitset_opt: reader.delete_bitset().cloned(),
+            started: false,
+            has_child: false,
+            doc: 0u32,
+            score: 0f32,
+        }))
+    }
+}
+
+/// Scorer associated to the `ToParentQuery` query.
+pub struct ToParentScorer {
+    child_scorer: Box<Scorer>,
+    parents: Vec<DocId>,
+    delete_bitset_opt: Option<DeleteBitSet>,
+    started: bool,
+    // true iff the child scorer is positioned on a
+    // child that was not consumed yet.
+    has_child: bool,
+    doc: DocId,
+    score: Score,
+}
+
+impl ToParentScorer {
+    fn is_deleted(&self, doc: DocId) -> bool {
+        self.delete_bitset_opt
+            .as_ref()
+            .map(|delete_bitset| delete_bitset.is_deleted(doc))
+            .unwrap_or(false)
+    }
+}
+
+impl DocSet for ToParentScorer {
+    fn advance(&mut self) -> bool {
+        if !self.started {
+            self.started = true;
+            self.has_child = self.child_scorer.advance();
+        }
+        while self.has_child {
+            let child = self.child_scorer.doc();
+            let parent_ord = match self.parents.binary_search(&child) {
+                Ok(_) => {
+                    // the document is a parent.
+                    self.has_child = self.child_scorer.advance();
+                    continue;
+                }
+                Err(parent_ord) => parent_ord,
+            };
+            if parent_ord == self.parents.len() {
+                // the remaining children do not belong to any block.
+                self.has_child = false;
+                break;
+            }
+            let parent = self.parents[parent_ord];
+            let mut score = 0f32;
+            while self.has_child && self.child_scorer.doc() < parent {
+                score += self.child_scorer.score();
+                self.has_child = self.child_scorer.advance();
+            }
+            if self.is_deleted(parent) {
+                continue;
+            }
+            self.doc = parent;
+            self.score = score;
+            return true;
+        }
+        false
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.child_scorer.size_hint()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.child_scorer.memory_usage() + self.parents.capacity() * mem::size_of::<DocId>()
+    }
+}
+
+impl Scorer for ToParentScorer {
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
@@ -24,6 +24,11 @@ fn compute_tf_cache(average_fieldnorm: f32) -> [f32; 256] {
     cache
 }
 
+/// BM25 similarity of a set of terms, with the statistics
+/// of the terms over the whole index.
+///
+/// Custom scorers can rely on it to score the term frequencies
+/// they read from postings.
 #[derive(Clone)]
 pub struct BM25Weight {
     weight: f32,
@@ -31,6 +36,7 @@ pub struct BM25Weight {
 }
 
 impl BM25Weight {
+    /// Returns a weight scoring all of the documents with 0.
     pub fn null() -> BM25Weight {
         BM25Weight {
             weight: 0f32,
@@ -38,6 +44,15 @@ impl BM25Weight {
         }
     }
 
+    /// Computes the BM25 weight of `terms`, considered as a single
+    /// term whose idf is the sum of the terms' idfs.
+    ///
+    /// The weight is multiplied by the boost of the field
+    /// declared in the schema (see `TextFieldIndexing::set_boost`).
+    ///
+    /// # Panics
+    /// Panics if `terms` is empty or if the terms do not all
+    /// belong to the same field.
     pub fn for_terms(searcher: &Searcher, terms: &[Term]) -> BM25Weight {
         assert!(!terms.is_empty(), "BM25 requires at least one term");
         let field = terms[0].field();
@@ -49,14 +64,9 @@ impl BM25Weight {
             );
         }
 
-        let mut total_num_tokens = 0u64;
-        let mut total_num_docs = 0u64;
-        for segment_reader in searcher.segment_readers() {
-            let inverted_index = segment_reader.inverted_index(field);
-            total_num_tokens += inverted_index.total_num_tokens();
-            total_num_docs += u64::from(segment_reader.max_doc());
-        }
-        let average_fieldnorm = total_num_tokens as f32 / total_num_docs as f32;
+        let field_statistics = searcher.field_statistics(field);
+        let total_num_docs = field_statistics.num_docs;
+        let average_fieldnorm = field_statistics.average_fieldnorm();
 
         let idf = terms
             .iter()
@@ -65,16 +75,23 @@ impl BM25Weight {
                 idf(term_doc_freq, total_num_docs)
             })
             .sum::<f32>();
-        BM25Weight::new(idf, average_fieldnorm)
+        let boost = searcher
+            .schema()
+            .get_field_entry(field)
+            .field_type()
+            .get_boost();
+        BM25Weight::new(idf, average_fieldnorm, boost)
     }
 
-    fn new(idf: f32, average_fieldnorm: f32) -> BM25Weight {
+    fn new(idf: f32, average_fieldnorm: f32, boost: f32) -> BM25Weight {
         BM25Weight {
-            weight: idf * (1f32 + K1),
+            weight: boost * idf * (1f32 + K1),
             cache: compute_tf_cache(average_fieldnorm),
         }
     }
 
+    /// Returns the score of a document given the fieldnorm id
+    /// of its field and the frequency of the term in it.
     #[inline(always)]
     pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
         let norm = self.cache[fieldnorm_id as usize];
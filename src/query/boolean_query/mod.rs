@@ -32,23 +32,23 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field => "a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "a c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -114,6 +114,10 @@ where
     fn size_hint(&self) -> u32 {
         self.underlying_docset.size_hint()
     }
+
+    fn memory_usage(&self) -> usize {
+        self.underlying_docset.memory_usage() + self.excluding_docset.memory_usage()
+    }
 }
 
 impl<TScorer, TDocSetExclude> Scorer for Exclude<TScorer, TDocSetExclude>
@@ -1,5 +1,5 @@
 use levenshtein_automata::{LevenshteinAutomatonBuilder, DFA};
-use query::{AutomatonWeight, Query, Weight};
+use query::{AutomatonWeight, ExpansionLimit, Query, Weight};
 use schema::Term;
 use std::collections::HashMap;
 use Result;
@@ -40,16 +40,16 @@ lazy_static! {
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -78,6 +78,8 @@ pub struct FuzzyTermQuery {
     transposition_cost_one: bool,
     ///
     prefix: bool,
+    /// Maximum number of terms the query expands to
+    expansion_limit: Option<ExpansionLimit>,
 }
 
 impl FuzzyTermQuery {
@@ -88,6 +90,7 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: false,
+            expansion_limit: None,
         }
     }
 
@@ -98,14 +101,22 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: true,
+            expansion_limit: None,
         }
     }
 
+    /// Limits the number of terms the query expands to.
+    /// By default, the query is not limited.
+    pub fn set_expansion_limit(&mut self, expansion_limit: Option<ExpansionLimit>) {
+        self.expansion_limit = expansion_limit;
+    }
+
     fn specialized_weight(&self) -> Result<AutomatonWeight<DFA>> {
         let automaton = LEV_BUILDER.get(&(self.distance, false))
             .unwrap() // TODO return an error
             .build_dfa(self.term.text());
-        Ok(AutomatonWeight::new(self.term.field(), automaton))
+        Ok(AutomatonWeight::new(self.term.field(), automaton)
+            .with_expansion_limit(self.expansion_limit))
     }
 }
 
@@ -133,12 +144,16 @@ mod test {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
-            index_writer.add_document(doc!(
-                country_field => "japan",
-            ));
-            index_writer.add_document(doc!(
-                country_field => "korea",
-            ));
+            index_writer
+                .add_document(doc!(
+                    country_field => "japan",
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    country_field => "korea",
+                ))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -4,6 +4,7 @@ use query::term_query::TermScorer;
 use query::EmptyScorer;
 use query::Scorer;
 use std::borrow::Borrow;
+use std::mem;
 use DocId;
 use Score;
 
@@ -221,6 +222,15 @@ impl<TDocSet: DocSet, TOtherDocSet: DocSet> DocSet for Intersection<TDocSet, TOt
     fn size_hint(&self) -> u32 {
         self.left.size_hint()
     }
+
+    fn memory_usage(&self) -> usize {
+        let others_num_bytes: usize = self
+            .others
+            .iter()
+            .map(|docset| mem::size_of_val(docset) + docset.memory_usage())
+            .sum();
+        self.left.memory_usage() + self.right.memory_usage() + others_num_bytes
+    }
 }
 
 impl<TScorer, TOtherScorer> Scorer for Intersection<TScorer, TOtherScorer>
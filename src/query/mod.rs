@@ -5,6 +5,7 @@ Query
 mod all_query;
 mod automaton_weight;
 mod bitset;
+mod block_join;
 mod bm25;
 mod boolean_query;
 mod empty_query;
@@ -13,12 +14,14 @@ mod fuzzy_query;
 mod intersection;
 mod occur;
 mod phrase_query;
+mod proximity_query;
 mod query;
 mod query_parser;
 mod range_query;
 mod regex_query;
 mod reqopt_scorer;
 mod scorer;
+mod tenant_query;
 mod term_query;
 mod union;
 mod weight;
@@ -34,8 +37,10 @@ pub use self::union::Union;
 pub use self::vec_docset::VecDocSet;
 
 pub use self::all_query::{AllQuery, AllScorer, AllWeight};
-pub use self::automaton_weight::AutomatonWeight;
+pub use self::automaton_weight::{AutomatonWeight, ExpansionLimit};
 pub use self::bitset::BitSetDocSet;
+pub use self::block_join::{ToChildQuery, ToParentQuery};
+pub use self::bm25::BM25Weight;
 pub use self::boolean_query::BooleanQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
@@ -43,16 +48,20 @@ pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::intersect_scorers;
 pub use self::occur::Occur;
 pub use self::phrase_query::PhraseQuery;
+pub use self::proximity_query::ProximityQuery;
 pub use self::query::Query;
 pub use self::query_parser::QueryParser;
 pub use self::query_parser::QueryParserError;
+pub use self::query_parser::SynonymMap;
 pub use self::range_query::RangeQuery;
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
 pub use self::scorer::ConstScorer;
 pub use self::scorer::Scorer;
-pub use self::term_query::TermQuery;
+pub use self::tenant_query::TenantQuery;
+pub use self::term_query::{TermQuery, TermScorer};
 pub use self::weight::Weight;
+pub use common::BitSet;
 
 #[cfg(test)]
 mod tests {
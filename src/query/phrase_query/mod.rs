@@ -13,8 +13,10 @@ mod tests {
     use collector::tests::TestCollector;
     use core::Index;
     use error::TantivyError;
-    use schema::{Schema, Term, TEXT};
+    use query::QueryParser;
+    use schema::{IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, TEXT};
     use tests::assert_nearly_equals;
+    use tokenizer::{LowerCaser, SimpleTokenizer, StopWordFilter, Tokenizer};
     use DocAddress;
     use DocId;
 
@@ -27,7 +29,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             for &text in texts {
                 let doc = doc!(text_field=>text);
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -87,7 +89,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a b c"));
+            index_writer
+                .add_document(doc!(text_field=>"a b c"))
+                .unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
@@ -142,9 +146,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"b"));
-            index_writer.add_document(doc!(text_field=>"a b"));
-            index_writer.add_document(doc!(text_field=>"b a"));
+            index_writer.add_document(doc!(text_field=>"b")).unwrap();
+            index_writer.add_document(doc!(text_field=>"a b")).unwrap();
+            index_writer.add_document(doc!(text_field=>"b a")).unwrap();
             assert!(index_writer.commit().is_ok());
         }
 
@@ -174,7 +178,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a b c d e f g h"));
+            index_writer
+                .add_document(doc!(text_field=>"a b c d e f g h"))
+                .unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
@@ -203,4 +209,59 @@ mod tests {
         assert!(test_query(vec![(0, "a"), (2, "d")]).is_empty());
         assert_eq!(test_query(vec![(1, "a"), (3, "c")]), vec![0]);
     }
+
+    #[test]
+    pub fn test_phrase_query_with_stop_words() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("en_stop_words")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "en_stop_words",
+            SimpleTokenizer
+                .filter(LowerCaser)
+                .filter(StopWordFilter::default()),
+        );
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"The state of the art"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"state art"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field=>"Whether to be or not to be happy"))
+                .unwrap();
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let test_query = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher
+                .search(&*query, &TestCollector)
+                .expect("search should succeed")
+                .docs()
+                .iter()
+                .map(|doc_address| doc_address.1)
+                .collect::<Vec<DocId>>()
+        };
+        // the stop words removed by the filter leave gaps in the positions,
+        // that the phrase query requires to be there.
+        assert_eq!(test_query("\"state of the art\""), vec![0]);
+        assert_eq!(test_query("\"state in an art\""), vec![0]);
+        assert_eq!(test_query("\"state art\""), vec![1]);
+        assert_eq!(test_query("\"whether to be or not to be happy\""), vec![2]);
+        assert!(test_query("\"whether happy\"").is_empty());
+        // the phrase is reduced to a single term.
+        assert_eq!(test_query("\"the art\""), vec![0, 1]);
+        // all of the words are stop words.
+        assert!(test_query("\"to be or not to be\"").is_empty());
+    }
 }
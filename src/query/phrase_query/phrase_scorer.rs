@@ -3,6 +3,7 @@ use fieldnorm::FieldNormReader;
 use postings::Postings;
 use query::bm25::BM25Weight;
 use query::{Intersection, Scorer};
+use std::mem;
 use DocId;
 
 struct PostingsWithOffset<TPostings> {
@@ -39,6 +40,10 @@ impl<TPostings: Postings> DocSet for PostingsWithOffset<TPostings> {
     fn size_hint(&self) -> u32 {
         self.postings.size_hint()
     }
+
+    fn memory_usage(&self) -> usize {
+        self.postings.memory_usage()
+    }
 }
 
 pub struct PhraseScorer<TPostings: Postings> {
@@ -244,6 +249,11 @@ impl<TPostings: Postings> DocSet for PhraseScorer<TPostings> {
     fn size_hint(&self) -> u32 {
         self.intersection_docset.size_hint()
     }
+
+    fn memory_usage(&self) -> usize {
+        self.intersection_docset.memory_usage()
+            + (self.left.capacity() + self.right.capacity()) * mem::size_of::<u32>()
+    }
 }
 
 impl<TPostings: Postings> Scorer for PhraseScorer<TPostings> {
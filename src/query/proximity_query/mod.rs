@@ -0,0 +1,107 @@
+mod proximity_query;
+mod proximity_scorer;
+mod proximity_weight;
+
+pub use self::proximity_query::ProximityQuery;
+pub use self::proximity_scorer::ProximityScorer;
+pub use self::proximity_weight::ProximityWeight;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::TestCollector;
+    use core::Index;
+    use error::TantivyError;
+    use query::{BooleanQuery, Occur, Query, TermQuery};
+    use schema::{IndexRecordOption, Schema, Term, STRING, TEXT};
+    use tests::assert_nearly_equals;
+
+    #[test]
+    pub fn test_proximity_query() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c d e f b"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "c d a b e f"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c d b e f"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c d e f g"))
+                .unwrap();
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_a = Term::from_field_text(text_field, "a");
+        let term_b = Term::from_field_text(text_field, "b");
+        let boolean_query = BooleanQuery::from(
+            vec![term_a.clone(), term_b.clone()]
+                .into_iter()
+                .map(|term| {
+                    let term_query: Box<Query> =
+                        Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                    (Occur::Should, term_query)
+                })
+                .collect::<Vec<_>>(),
+        );
+        let scores = |query: &Query| {
+            let test_fruits = searcher.search(query, &TestCollector).unwrap();
+            assert_eq!(
+                test_fruits
+                    .docs()
+                    .iter()
+                    .map(|doc_address| doc_address.1)
+                    .collect::<Vec<_>>(),
+                vec![0, 1, 2, 3]
+            );
+            test_fruits.scores().to_vec()
+        };
+        let base_scores = scores(&boolean_query);
+        assert_nearly_equals(base_scores[0], base_scores[1]);
+        assert_nearly_equals(base_scores[0], base_scores[2]);
+
+        let proximity_query =
+            ProximityQuery::new(Box::new(boolean_query.clone()), vec![term_a, term_b]);
+        let proximity_scores = scores(&proximity_query);
+        assert_nearly_equals(proximity_scores[0], base_scores[0] * (1f32 + 1f32 / 5f32));
+        assert_nearly_equals(proximity_scores[1], base_scores[1] * 2f32);
+        assert_nearly_equals(proximity_scores[2], base_scores[2] * (1f32 + 1f32 / 3f32));
+        assert_nearly_equals(proximity_scores[3], base_scores[3]);
+        assert_eq!(proximity_query.count(&searcher).unwrap(), 4);
+    }
+
+    #[test]
+    pub fn test_proximity_query_no_positions() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term = Term::from_field_text(text_field, "a");
+        let proximity_query = ProximityQuery::new(
+            Box::new(TermQuery::new(term.clone(), IndexRecordOption::Basic)),
+            vec![term],
+        );
+        match searcher
+            .search(&proximity_query, &TestCollector)
+            .map(|_| ())
+            .unwrap_err()
+        {
+            TantivyError::SchemaError(_) => {}
+            _ => panic!("Should have returned a schema error"),
+        }
+    }
+}
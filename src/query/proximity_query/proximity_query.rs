@@ -0,0 +1,128 @@
+use super::ProximityWeight;
+use core::searcher::Searcher;
+use error::TantivyError;
+use query::Query;
+use query::Weight;
+use schema::{Field, Term};
+use std::collections::BTreeSet;
+use std::fmt;
+use Result;
+
+/// `ProximityQuery` wraps a query, and boosts the score of its
+/// documents when they contain some of the given terms close
+/// to each other.
+///
+/// For instance, wrapping the query `part time` with the terms
+/// `part` and `time` ranks the sentence
+///
+/// **Alan just got a part time job.**
+///
+/// before the sentence
+///
+/// **This is my favorite part of the job, most of the time.**
+///
+/// The set of matching documents is the set of documents
+/// matched by the underlying query.
+///
+/// Using a `ProximityQuery` on a field requires positions
+/// to be indexed for this field.
+pub struct ProximityQuery {
+    query: Box<Query>,
+    field: Field,
+    terms: Vec<Term>,
+    boost: f32,
+}
+
+impl ProximityQuery {
+    /// Creates a new `ProximityQuery` given the query it wraps,
+    /// and the terms whose proximity boosts the scores.
+    ///
+    /// All terms must belong to the same field.
+    /// The boost is `1` by default: the score of the documents
+    /// containing the terms next to each other is doubled.
+    pub fn new(query: Box<Query>, mut terms: Vec<Term>) -> ProximityQuery {
+        assert!(
+            !terms.is_empty(),
+            "A proximity query is required to have at least one term."
+        );
+        let field = terms[0].field();
+        assert!(
+            terms[1..].iter().all(|term| term.field() == field),
+            "All terms from a proximity query must belong to the same field"
+        );
+        terms.sort();
+        terms.dedup();
+        ProximityQuery {
+            query,
+            field,
+            terms,
+            boost: 1f32,
+        }
+    }
+
+    /// Sets the boost applied to the documents
+    /// containing the terms next to each other.
+    pub fn set_boost(&mut self, boost: f32) {
+        self.boost = boost;
+    }
+
+    /// The `Field` of the terms of this `ProximityQuery`.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+impl Clone for ProximityQuery {
+    fn clone(&self) -> Self {
+        ProximityQuery {
+            query: self.query.box_clone(),
+            field: self.field,
+            terms: self.terms.clone(),
+            boost: self.boost,
+        }
+    }
+}
+
+impl fmt::Debug for ProximityQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Proximity(query={:?}, terms={:?}, boost={})",
+            self.query, self.terms, self.boost
+        )
+    }
+}
+
+impl Query for ProximityQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        let schema = searcher.schema();
+        let field_entry = schema.get_field_entry(self.field);
+        let has_positions = field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(|index_record_option| index_record_option.has_positions())
+            .unwrap_or(false);
+        if !has_positions {
+            let field_name = field_entry.name();
+            return Err(TantivyError::SchemaError(format!(
+                "Applied proximity query on field {:?}, which does not have positions indexed",
+                field_name
+            )));
+        }
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        if scoring_enabled {
+            Ok(Box::new(ProximityWeight::new(
+                weight,
+                self.terms.clone(),
+                self.boost,
+            )))
+        } else {
+            // The proximity only affects the scores.
+            Ok(weight)
+        }
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+}
@@ -0,0 +1,190 @@
+use docset::{DocSet, SkipResult};
+use postings::Postings;
+use postings::SegmentPostings;
+use query::Scorer;
+use std::cmp;
+use std::mem;
+use DocId;
+use Score;
+
+/// Postings of one of the terms of a `ProximityScorer`,
+/// moved lazily to the documents being scored.
+struct TermPositions {
+    postings: SegmentPostings,
+    current_doc: Option<DocId>,
+    ended: bool,
+}
+
+impl TermPositions {
+    fn new(postings: SegmentPostings) -> TermPositions {
+        TermPositions {
+            postings,
+            current_doc: None,
+            ended: false,
+        }
+    }
+
+    /// Moves the postings to `target`, and returns true iff
+    /// `target` contains the term.
+    ///
+    /// The targets are expected to be increasing.
+    fn seek(&mut self, target: DocId) -> bool {
+        if let Some(doc) = self.current_doc {
+            if doc >= target {
+                return doc == target;
+            }
+        }
+        if self.ended {
+            return false;
+        }
+        match self.postings.skip_next(target) {
+            SkipResult::Reached => {
+                self.current_doc = Some(target);
+                true
+            }
+            SkipResult::OverStep => {
+                self.current_doc = Some(self.postings.doc());
+                false
+            }
+            SkipResult::End => {
+                self.ended = true;
+                false
+            }
+        }
+    }
+}
+
+/// Returns the width of the smallest window of positions
+/// containing at least one position of each of the lists.
+///
+/// Each of the lists is sorted, and none of them is empty.
+pub(crate) fn min_span_width(positions: &[&[u32]]) -> u32 {
+    let mut cursors = vec![0usize; positions.len()];
+    let mut min_width = u32::max_value();
+    loop {
+        let mut min_ord = 0;
+        let mut min_position = u32::max_value();
+        let mut max_position = 0u32;
+        for (ord, (term_positions, &cursor)) in positions.iter().zip(cursors.iter()).enumerate() {
+            let position = term_positions[cursor];
+            if position < min_position {
+                min_position = position;
+                min_ord = ord;
+            }
+            max_position = cmp::max(max_position, position);
+        }
+        min_width = cmp::min(min_width, max_position - min_position + 1);
+        cursors[min_ord] += 1;
+        if cursors[min_ord] == positions[min_ord].len() {
+            return min_width;
+        }
+    }
+}
+
+/// Scorer boosting the score of an underlying scorer when
+/// its documents contain the terms close to each other.
+///
+/// If `n` of the terms appear in the document within a span of
+/// `width` positions, the score is multiplied by
+/// `1 + boost * (n - 1) / (width - 1)`.
+/// The documents containing fewer than two of the terms
+/// are left unchanged.
+pub struct ProximityScorer {
+    scorer: Box<Scorer>,
+    term_positions: Vec<TermPositions>,
+    positions: Vec<Vec<u32>>,
+    boost: f32,
+}
+
+impl ProximityScorer {
+    pub fn new(scorer: Box<Scorer>, postings: Vec<SegmentPostings>, boost: f32) -> ProximityScorer {
+        let positions = vec![Vec::new(); postings.len()];
+        let term_positions = postings.into_iter().map(TermPositions::new).collect();
+        ProximityScorer {
+            scorer,
+            term_positions,
+            positions,
+            boost,
+        }
+    }
+
+    fn proximity(&mut self) -> f32 {
+        let doc = self.scorer.doc();
+        let mut doc_positions: Vec<&[u32]> = Vec::with_capacity(self.positions.len());
+        for (term_positions, positions) in self
+            .term_positions
+            .iter_mut()
+            .zip(self.positions.iter_mut())
+        {
+            positions.clear();
+            if term_positions.seek(doc) {
+                term_positions.postings.positions(positions);
+                if !positions.is_empty() {
+                    doc_positions.push(&positions[..]);
+                }
+            }
+        }
+        if doc_positions.len() < 2 {
+            return 0f32;
+        }
+        let num_terms = doc_positions.len() as u32;
+        let width = min_span_width(&doc_positions[..]);
+        (num_terms - 1) as f32 / cmp::max(width - 1, num_terms - 1) as f32
+    }
+}
+
+impl DocSet for ProximityScorer {
+    fn advance(&mut self) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn memory_usage(&self) -> usize {
+        let term_positions_num_bytes: usize = self
+            .term_positions
+            .iter()
+            .map(|term_positions| {
+                mem::size_of_val(term_positions) + term_positions.postings.memory_usage()
+            })
+            .sum();
+        let positions_num_bytes: usize = self
+            .positions
+            .iter()
+            .map(|positions| positions.capacity() * mem::size_of::<u32>())
+            .sum();
+        self.scorer.memory_usage() + term_positions_num_bytes + positions_num_bytes
+    }
+}
+
+impl Scorer for ProximityScorer {
+    fn score(&mut self) -> Score {
+        let score = self.scorer.score();
+        score * (1f32 + self.boost * self.proximity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::min_span_width;
+
+    #[test]
+    fn test_min_span_width() {
+        assert_eq!(min_span_width(&[&[3], &[4]]), 2);
+        assert_eq!(min_span_width(&[&[4], &[3]]), 2);
+        assert_eq!(min_span_width(&[&[0, 10], &[5, 12], &[11]]), 3);
+        assert_eq!(min_span_width(&[&[1, 2, 3], &[2]]), 1);
+        assert_eq!(min_span_width(&[&[0], &[7, 100]]), 8);
+    }
+}
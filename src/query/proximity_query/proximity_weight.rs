@@ -0,0 +1,48 @@
+use super::ProximityScorer;
+use core::SegmentReader;
+use query::Scorer;
+use query::Weight;
+use schema::IndexRecordOption;
+use schema::Term;
+use Result;
+
+pub struct ProximityWeight {
+    weight: Box<Weight>,
+    terms: Vec<Term>,
+    boost: f32,
+}
+
+impl ProximityWeight {
+    /// Creates a new proximity weight.
+    pub fn new(weight: Box<Weight>, terms: Vec<Term>, boost: f32) -> ProximityWeight {
+        ProximityWeight {
+            weight,
+            terms,
+            boost,
+        }
+    }
+}
+
+impl Weight for ProximityWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let scorer = self.weight.scorer(reader)?;
+        let postings: Vec<_> = self
+            .terms
+            .iter()
+            .filter_map(|term| {
+                reader
+                    .inverted_index(term.field())
+                    .read_postings_no_deletes(term, IndexRecordOption::WithFreqsAndPositions)
+            })
+            .collect();
+        if postings.len() < 2 {
+            // no document of the segment can be boosted.
+            return Ok(scorer);
+        }
+        Ok(Box::new(ProximityScorer::new(scorer, postings, self.boost)))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        self.weight.count(reader)
+    }
+}
@@ -39,6 +39,10 @@ use Term;
 ///
 /// When implementing a new type of `Query`, it is normal to implement a
 /// dedicated `Query`, `Weight` and `Scorer`.
+/// The building blocks of the built-in queries, such as the
+/// [`BM25Weight`](./struct.BM25Weight.html) similarity or the
+/// [`TermScorer`](./struct.TermScorer.html), are available to do so.
+/// See the `custom_query` example.
 pub trait Query: QueryClone + downcast::Any + fmt::Debug {
     /// Create the weight associated to a query.
     ///
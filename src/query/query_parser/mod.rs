@@ -1,7 +1,9 @@
 mod query_grammar;
 mod query_parser;
+mod synonyms;
 mod user_input_ast;
 
 pub mod logical_ast;
 pub use self::query_parser::QueryParser;
 pub use self::query_parser::QueryParserError;
+pub use self::synonyms::SynonymMap;
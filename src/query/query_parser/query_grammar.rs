@@ -14,7 +14,7 @@ parser! {
     where [I: Stream<Item = char>] {
         (
             letter(),
-            many(satisfy(|c: char| c.is_alphanumeric() || c == '_')),
+            many(satisfy(|c: char| c.is_alphanumeric() || c == '_' || c == '.')),
         ).map(|(s1, s2): (char, String)| format!("{}{}", s1, s2))
     }
 }
@@ -42,7 +42,9 @@ parser! {
             let phrase = (char('"'), many1(satisfy(|c| c != '"')), char('"')).map(|(_, s, _)| s);
             phrase.or(word())
         };
-        let term_val_with_field = negative_number().or(term_val());
+        let term_val_with_field = try(positive_decimal())
+            .or(negative_number())
+            .or(term_val());
         let term_query =
             (field(), char(':'), term_val_with_field).map(|(field_name, _, phrase)| UserInputLiteral {
                 field_name: Some(field_name),
@@ -62,7 +64,27 @@ parser! {
     fn negative_number[I]()(I) -> String
     where [I: Stream<Item = char>]
     {
-            (char('-'), many1(satisfy(|c: char| c.is_numeric())))
+            (char('-'), many1(satisfy(|c: char| c.is_numeric())), optional(try(fraction())))
+                .map(|(s1, s2, s3): (char, String, Option<String>)| {
+                    format!("{}{}{}", s1, s2, s3.unwrap_or_default())
+                })
+    }
+}
+
+parser! {
+    fn positive_decimal[I]()(I) -> String
+    where [I: Stream<Item = char>]
+    {
+            (many1(satisfy(|c: char| c.is_numeric())), fraction())
+                .map(|(s1, s2): (String, String)| format!("{}{}", s1, s2))
+    }
+}
+
+parser! {
+    fn fraction[I]()(I) -> String
+    where [I: Stream<Item = char>]
+    {
+            (char('.'), many1(satisfy(|c: char| c.is_numeric())))
                 .map(|(s1, s2): (char, String)| format!("{}{}", s1, s2))
     }
 }
@@ -78,7 +100,10 @@ parser! {
     fn range[I]()(I) -> UserInputLeaf
     where [I: Stream<Item = char>] {
         let term_val = || {
-            word().or(negative_number()).or(char('*').map(|_| "*".to_string()))
+            try(positive_decimal())
+                .or(word())
+                .or(negative_number())
+                .or(char('*').map(|_| "*".to_string()))
         };
         let lower_bound = {
             let excl = (char('{'), term_val()).map(|(_, w)| UserInputBound::Exclusive(w));
@@ -279,6 +304,10 @@ mod test {
         test_parse_query_to_ast_helper("foo:[1 TO toto}", "foo:[\"1\" TO \"toto\"}");
         test_parse_query_to_ast_helper("foo:[* TO toto}", "foo:[\"*\" TO \"toto\"}");
         test_parse_query_to_ast_helper("foo:[1 TO *}", "foo:[\"1\" TO \"*\"}");
+        test_parse_query_to_ast_helper("foo:1.5", "foo:\"1.5\"");
+        test_parse_query_to_ast_helper("foo:-1.5", "foo:\"-1.5\"");
+        test_parse_query_to_ast_helper("foo:[-1.5 TO 2.25]", "foo:[\"-1.5\" TO \"2.25\"]");
+        test_parse_query_to_ast_helper("attrs.color:red", "attrs.color:\"red\"");
         test_is_parse_err("abc +    ");
     }
 }
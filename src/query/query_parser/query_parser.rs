@@ -1,7 +1,10 @@
 use super::logical_ast::*;
 use super::query_grammar::parse_to_ast;
+use super::synonyms::SynonymMap;
 use super::user_input_ast::*;
+use chrono::{self, Utc};
 use combine::Parser;
+use common::ip_cidr_range;
 use core::Index;
 use query::occur::compose_occur;
 use query::query_parser::logical_ast::LogicalAST;
@@ -15,9 +18,10 @@ use query::RangeQuery;
 use query::TermQuery;
 use schema::IndexRecordOption;
 use schema::{Field, Schema};
-use schema::{FieldType, Term};
+use schema::{FieldType, Term, Type};
 use std::borrow::Cow;
-use std::num::ParseIntError;
+use std::net::{AddrParseError, IpAddr};
+use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
 use std::str::FromStr;
 use tokenizer::TokenizerManager;
@@ -33,6 +37,18 @@ pub enum QueryParserError {
     /// The query contains a term for a `u64`-field, but the value
     /// is not a u64.
     ExpectedInt(ParseIntError),
+    /// The query contains a term for a `f64`-field, but the value
+    /// is not a f64.
+    ExpectedFloat(ParseFloatError),
+    /// The query contains a term for a date field, but the value
+    /// is not a RFC 3339 date, e.g. `"2019-01-01T12:00:00Z"`.
+    ExpectedDate(chrono::ParseError),
+    /// The query contains a term for an IP address field, but the value
+    /// is not an IP address, or a CIDR block such as `"10.0.0.0/8"`.
+    ExpectedIpAddr(AddrParseError),
+    /// The query contains a CIDR block whose prefix is longer
+    /// than its address, e.g. `"10.0.0.0/33"`.
+    InvalidCidrPrefix(String),
     /// It is forbidden queries that are only "excluding". (e.g. -title:pop)
     AllButQueryForbidden,
     /// If no default field is declared, running a query without any
@@ -50,6 +66,9 @@ pub enum QueryParserError {
     /// The query contains a range query with a phrase as one of the bounds.
     /// Only terms can be used as bounds.
     RangeMustNotHavePhrase,
+    /// The query contains a range query on a JSON object field.
+    /// Only terms and phrases can be searched within JSON objects.
+    RangeOnJsonObject(String),
 }
 
 impl From<ParseIntError> for QueryParserError {
@@ -58,6 +77,24 @@ impl From<ParseIntError> for QueryParserError {
     }
 }
 
+impl From<ParseFloatError> for QueryParserError {
+    fn from(err: ParseFloatError) -> QueryParserError {
+        QueryParserError::ExpectedFloat(err)
+    }
+}
+
+impl From<chrono::ParseError> for QueryParserError {
+    fn from(err: chrono::ParseError) -> QueryParserError {
+        QueryParserError::ExpectedDate(err)
+    }
+}
+
+impl From<AddrParseError> for QueryParserError {
+    fn from(err: AddrParseError) -> QueryParserError {
+        QueryParserError::ExpectedIpAddr(err)
+    }
+}
+
 /// Recursively remove empty clause from the AST
 ///
 /// Returns `None` iff the `logical_ast` ended up being empty.
@@ -129,12 +166,23 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///
 /// *  all docs query: A plain `*` will match all documents in the index.
 ///
+/// * synonyms: If a [`SynonymMap`](./struct.SynonymMap.html) is set, a term of a text
+///   field matches the documents containing any of its synonyms.
+///   e.g. with `car` and `automobile` declared as synonyms, `car` is equivalent to
+///   `(car OR automobile)`. Synonyms are not expanded within phrases.
+///
+/// * JSON object fields: the values of a JSON object field are searched under
+///   their path, e.g. `attrs.size.width:30` for the object `{"size": {"width": 30}}`
+///   of the field `attrs`. A number matches the documents containing it either
+///   as a number or as a text.
+///
 #[derive(Clone)]
 pub struct QueryParser {
     schema: Schema,
     default_fields: Vec<Field>,
     conjunction_by_default: bool,
     tokenizer_manager: TokenizerManager,
+    synonyms: SynonymMap,
 }
 
 impl QueryParser {
@@ -152,6 +200,7 @@ impl QueryParser {
             default_fields,
             tokenizer_manager,
             conjunction_by_default: false,
+            synonyms: SynonymMap::default(),
         }
     }
 
@@ -172,6 +221,14 @@ impl QueryParser {
         self.conjunction_by_default = true;
     }
 
+    /// Sets the synonyms expanded at query time.
+    ///
+    /// Each term is replaced by a disjunction of the term and its synonyms,
+    /// all of them having the same weight in the query.
+    pub fn set_synonyms(&mut self, synonyms: SynonymMap) {
+        self.synonyms = synonyms;
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
@@ -201,6 +258,27 @@ impl QueryParser {
             .ok_or_else(|| QueryParserError::FieldDoesNotExist(String::from(field_name)))
     }
 
+    /// Resolves a field name, possibly followed by the path of
+    /// a value within a JSON object field, e.g. `attrs.size.width`.
+    ///
+    /// Returns the field and the path, which is empty
+    /// if the field is not a JSON object field.
+    fn resolve_field_path<'a>(
+        &self,
+        field_path: &'a str,
+    ) -> Result<(Field, &'a str), QueryParserError> {
+        if let Some(dot_pos) = field_path.find('.') {
+            let (field_name, json_path) = (&field_path[..dot_pos], &field_path[dot_pos + 1..]);
+            if let Some(field) = self.schema.get_field(field_name) {
+                if let FieldType::JsonObject(_) = *self.schema.get_field_entry(field).field_type() {
+                    return Ok((field, json_path));
+                }
+            }
+            return Err(QueryParserError::FieldDoesNotExist(field_path.to_string()));
+        }
+        Ok((self.resolve_field_name(field_path)?, ""))
+    }
+
     fn compute_logical_ast(
         &self,
         user_input_ast: UserInputAST,
@@ -234,6 +312,22 @@ impl QueryParser {
                 let term = Term::from_field_u64(field, val);
                 Ok(vec![(0, term)])
             }
+            FieldType::F64(_) => {
+                let val: f64 = f64::from_str(phrase)?;
+                let term = Term::from_field_f64(field, val);
+                Ok(vec![(0, term)])
+            }
+            FieldType::Date(ref date_options) => {
+                let date = chrono::DateTime::parse_from_rfc3339(phrase)?.with_timezone(&Utc);
+                let date = date_options.get_precision().truncate(&date);
+                let term = Term::from_field_date(field, &date);
+                Ok(vec![(0, term)])
+            }
+            FieldType::IpAddr(_) => {
+                let val: IpAddr = IpAddr::from_str(phrase)?;
+                let term = Term::from_field_ip_addr(field, val);
+                Ok(vec![(0, term)])
+            }
             FieldType::Str(ref str_options) => {
                 if let Some(option) = str_options.get_indexing_options() {
                     let mut tokenizer =
@@ -280,26 +374,161 @@ impl QueryParser {
                 }
             }
             FieldType::HierarchicalFacet => Ok(vec![(0, Term::from_field_text(field, phrase))]),
-            FieldType::Bytes => {
+            FieldType::Bytes(_) => {
                 let field_name = self.schema.get_field_name(field).to_string();
                 Err(QueryParserError::FieldNotIndexed(field_name))
             }
+            FieldType::JsonObject(_) => {
+                let field_name = self.schema.get_field_name(field).to_string();
+                Err(QueryParserError::RangeOnJsonObject(field_name))
+            }
         }
     }
 
     fn compute_logical_ast_for_leaf(
         &self,
         field: Field,
+        json_path: &str,
         phrase: &str,
-    ) -> Result<Option<LogicalLiteral>, QueryParserError> {
+    ) -> Result<Option<LogicalAST>, QueryParserError> {
+        match *self.schema.get_field_entry(field).field_type() {
+            FieldType::IpAddr(_) => {
+                if let Some(slash_pos) = phrase.find('/') {
+                    let (ip_addr, prefix_len) = (&phrase[..slash_pos], &phrase[slash_pos + 1..]);
+                    return self
+                        .compute_logical_ast_for_cidr(field, ip_addr, prefix_len)
+                        .map(Some);
+                }
+            }
+            FieldType::JsonObject(_) => {
+                return self.compute_logical_ast_for_json(field, json_path, phrase);
+            }
+            _ => {}
+        }
         let terms = self.compute_terms_for_string(field, phrase)?;
         match &terms[..] {
             [] => Ok(None),
-            [(_, term)] => Ok(Some(LogicalLiteral::Term(term.clone()))),
-            _ => Ok(Some(LogicalLiteral::Phrase(terms.clone()))),
+            [(_, term)] => Ok(Some(self.expand_synonyms(term.clone()))),
+            _ => Ok(Some(LogicalLiteral::Phrase(terms.clone()).into())),
         }
     }
 
+    /// Returns the range of the addresses of the CIDR block `ip_addr/prefix_len`.
+    fn compute_logical_ast_for_cidr(
+        &self,
+        field: Field,
+        ip_addr: &str,
+        prefix_len: &str,
+    ) -> Result<LogicalAST, QueryParserError> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.is_indexed() {
+            let field_name = field_entry.name().to_string();
+            return Err(QueryParserError::FieldNotIndexed(field_name));
+        }
+        let ip_addr = IpAddr::from_str(ip_addr)?;
+        let prefix_len = u8::from_str(prefix_len)?;
+        let (first_ip_addr, last_ip_addr) =
+            ip_cidr_range(ip_addr, prefix_len).ok_or_else(|| {
+                QueryParserError::InvalidCidrPrefix(format!("{}/{}", ip_addr, prefix_len))
+            })?;
+        Ok(LogicalLiteral::Range {
+            field,
+            value_type: Type::IpAddr,
+            lower: Bound::Included(Term::from_field_ip_addr(field, IpAddr::V6(first_ip_addr))),
+            upper: Bound::Included(Term::from_field_ip_addr(field, IpAddr::V6(last_ip_addr))),
+        }
+        .into())
+    }
+
+    /// Returns the query matching the documents containing `phrase`
+    /// at the path `json_path` of a JSON object field.
+    ///
+    /// If `phrase` is a number, the documents containing it either
+    /// as a number or as a text are matched.
+    fn compute_logical_ast_for_json(
+        &self,
+        field: Field,
+        json_path: &str,
+        phrase: &str,
+    ) -> Result<Option<LogicalAST>, QueryParserError> {
+        let field_entry = self.schema.get_field_entry(field);
+        let indexing_options = match *field_entry.field_type() {
+            FieldType::JsonObject(ref json_object_options) => {
+                json_object_options.get_text_indexing_options()
+            }
+            _ => None,
+        }
+        .ok_or_else(|| QueryParserError::FieldNotIndexed(field_entry.name().to_string()))?;
+        let mut asts: Vec<LogicalAST> = Vec::new();
+        let number_term_opt = if let Ok(val) = i64::from_str(phrase) {
+            Some(Term::from_field_json_i64(field, json_path, val))
+        } else if let Ok(val) = u64::from_str(phrase) {
+            Some(Term::from_field_json_u64(field, json_path, val))
+        } else if let Ok(val) = f64::from_str(phrase) {
+            Some(Term::from_field_json_f64(field, json_path, val))
+        } else {
+            None
+        };
+        if let Some(number_term) = number_term_opt {
+            asts.push(LogicalLiteral::Term(number_term).into());
+        }
+        let tokenizer = self
+            .tokenizer_manager
+            .get(indexing_options.tokenizer())
+            .ok_or_else(|| {
+                QueryParserError::UnknownTokenizer(
+                    field_entry.name().to_string(),
+                    indexing_options.tokenizer().to_string(),
+                )
+            })?;
+        let mut terms: Vec<(usize, Term)> = Vec::new();
+        tokenizer.token_stream(phrase).process(&mut |token| {
+            let term = Term::from_field_json_text(field, json_path, &token.text);
+            terms.push((token.position, term));
+        });
+        if terms.len() == 1 {
+            let (_, term) = terms.into_iter().next().unwrap();
+            asts.push(LogicalLiteral::Term(term).into());
+        } else if terms.len() > 1 {
+            if indexing_options.index_option().has_positions() {
+                asts.push(LogicalLiteral::Phrase(terms).into());
+            } else if asts.is_empty() {
+                let field_name = field_entry.name().to_string();
+                return Err(QueryParserError::FieldDoesNotHavePositionsIndexed(
+                    field_name,
+                ));
+            }
+        }
+        if asts.len() <= 1 {
+            Ok(asts.into_iter().next())
+        } else {
+            Ok(Some(LogicalAST::Clause(
+                asts.into_iter().map(|ast| (Occur::Should, ast)).collect(),
+            )))
+        }
+    }
+
+    /// Returns the disjunction of `term` and of its synonyms, if it has any.
+    fn expand_synonyms(&self, term: Term) -> LogicalAST {
+        if let FieldType::Str(_) = *self.schema.get_field_entry(term.field()).field_type() {
+            let synonym_terms: Vec<Term> = self
+                .synonyms
+                .synonyms(term.text())
+                .map(|synonym| Term::from_field_text(term.field(), synonym))
+                .collect();
+            if !synonym_terms.is_empty() {
+                return LogicalAST::Clause(
+                    Some(term)
+                        .into_iter()
+                        .chain(synonym_terms)
+                        .map(|term| (Occur::Should, LogicalLiteral::Term(term).into()))
+                        .collect(),
+                );
+            }
+        }
+        LogicalLiteral::Term(term).into()
+    }
+
     fn default_occur(&self) -> Occur {
         if self.conjunction_by_default {
             Occur::Must
@@ -339,7 +568,10 @@ impl QueryParser {
                     Ok(Cow::from(&self.default_fields[..]))
                 }
             }
-            Some(ref field) => Ok(Cow::from(vec![self.resolve_field_name(&*field)?])),
+            Some(ref field_path) => {
+                let (field, _) = self.resolve_field_path(field_path)?;
+                Ok(Cow::from(vec![field]))
+            }
         }
     }
 
@@ -376,10 +608,10 @@ impl QueryParser {
     ) -> Result<LogicalAST, QueryParserError> {
         match leaf {
             UserInputLeaf::Literal(literal) => {
-                let term_phrases: Vec<(Field, String)> = match literal.field_name {
-                    Some(ref field_name) => {
-                        let field = self.resolve_field_name(field_name)?;
-                        vec![(field, literal.phrase.clone())]
+                let term_phrases: Vec<(Field, &str, String)> = match literal.field_name {
+                    Some(ref field_path) => {
+                        let (field, json_path) = self.resolve_field_path(field_path)?;
+                        vec![(field, json_path, literal.phrase.clone())]
                     }
                     None => {
                         if self.default_fields.is_empty() {
@@ -387,15 +619,17 @@ impl QueryParser {
                         } else {
                             self.default_fields
                                 .iter()
-                                .map(|default_field| (*default_field, literal.phrase.clone()))
-                                .collect::<Vec<(Field, String)>>()
+                                .map(|default_field| (*default_field, "", literal.phrase.clone()))
+                                .collect::<Vec<(Field, &str, String)>>()
                         }
                     }
                 };
                 let mut asts: Vec<LogicalAST> = Vec::new();
-                for (field, phrase) in term_phrases {
-                    if let Some(ast) = self.compute_logical_ast_for_leaf(field, &phrase)? {
-                        asts.push(LogicalAST::Leaf(Box::new(ast)));
+                for (field, json_path, phrase) in term_phrases {
+                    if let Some(ast) =
+                        self.compute_logical_ast_for_leaf(field, json_path, &phrase)?
+                    {
+                        asts.push(ast);
                     }
                 }
                 let result_ast: LogicalAST = if asts.len() == 1 {
@@ -417,6 +651,10 @@ impl QueryParser {
                     .map(|&field| {
                         let field_entry = self.schema.get_field_entry(field);
                         let value_type = field_entry.field_type().value_type();
+                        if value_type == Type::Json {
+                            let field_name = field_entry.name().to_string();
+                            return Err(QueryParserError::RangeOnJsonObject(field_name));
+                        }
                         Ok(LogicalAST::Leaf(Box::new(LogicalLiteral::Range {
                             field,
                             value_type,
@@ -484,8 +722,11 @@ mod test {
     use super::super::logical_ast::*;
     use super::QueryParser;
     use super::QueryParserError;
+    use super::SynonymMap;
+    use chrono::{TimeZone, Utc};
+    use collector::Count;
     use query::Query;
-    use schema::Field;
+    use schema::{DateOptions, Field, IpAddrOptions, JsonObjectOptions};
     use schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
     use schema::{Schema, Term, INT_INDEXED, STORED, STRING, TEXT};
     use tokenizer::{LowerCaser, SimpleTokenizer, StopWordFilter, Tokenizer, TokenizerManager};
@@ -508,6 +749,10 @@ mod test {
         schema_builder.add_text_field("notindexed_i64", STORED);
         schema_builder.add_text_field("nottokenized", STRING);
         schema_builder.add_text_field("with_stop_words", text_options);
+        schema_builder.add_f64_field("float", INT_INDEXED);
+        schema_builder.add_date_field("date", DateOptions::from(INT_INDEXED));
+        schema_builder.add_ip_addr_field("ip", IpAddrOptions::default().set_indexed());
+        schema_builder.add_json_object_field("attrs", JsonObjectOptions::from(TEXT));
         let schema = schema_builder.build();
         let default_fields = vec![title, text];
         let tokenizer_manager = TokenizerManager::default();
@@ -626,6 +871,155 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_floats() {
+        let query_parser = make_query_parser();
+        assert!(query_parser.parse_query("float:2324").is_ok());
+        assert!(query_parser.parse_query("float:\"-1e10\"").is_ok());
+        assert!(query_parser.parse_query("float:[-1.5 TO 2.25}").is_ok());
+        assert_matches!(
+            query_parser.parse_query("float:\"1.5a\""),
+            Err(QueryParserError::ExpectedFloat(_))
+        );
+        test_parse_query_to_logical_ast_helper(
+            "float:-1.5",
+            &format!("{:?}", Term::from_field_f64(Field(9u32), -1.5)),
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "float:0.25",
+            &format!("{:?}", Term::from_field_f64(Field(9u32), 0.25)),
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_dates() {
+        let query_parser = make_query_parser();
+        assert_matches!(
+            query_parser.parse_query("date:\"2019-01-01\""),
+            Err(QueryParserError::ExpectedDate(_))
+        );
+        // the dates are truncated to the precision of the field, the second.
+        let date = Utc.ymd(2019, 1, 1).and_hms(11, 0, 0);
+        test_parse_query_to_logical_ast_helper(
+            "date:\"2019-01-01T12:00:00.250+01:00\"",
+            &format!("{:?}", Term::from_field_date(Field(10u32), &date)),
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_ip_addrs() {
+        let query_parser = make_query_parser();
+        let ip_term =
+            |ip_str: &str| Term::from_field_ip_addr(Field(11u32), ip_str.parse().unwrap());
+        assert_matches!(
+            query_parser.parse_query("ip:\"10.0.0\""),
+            Err(QueryParserError::ExpectedIpAddr(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("ip:\"10.0.0.0/33\""),
+            Err(QueryParserError::InvalidCidrPrefix(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("ip:\"10.0.0.0/a\""),
+            Err(QueryParserError::ExpectedInt(_))
+        );
+        test_parse_query_to_logical_ast_helper(
+            "ip:\"10.0.0.1\"",
+            &format!("{:?}", ip_term("10.0.0.1")),
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "ip:\"10.1.2.3/16\"",
+            &format!(
+                "(Included({:?}) TO Included({:?}))",
+                ip_term("10.1.0.0"),
+                ip_term("10.1.255.255")
+            ),
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "ip:\"2001:db8::/32\"",
+            &format!(
+                "(Included({:?}) TO Included({:?}))",
+                ip_term("2001:db8::"),
+                ip_term("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff")
+            ),
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_json_object() {
+        let query_parser = make_query_parser();
+        let attrs = Field(12u32);
+        test_parse_query_to_logical_ast_helper(
+            "attrs.color:red",
+            &format!("{:?}", Term::from_field_json_text(attrs, "color", "red")),
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "attrs.color:\"dark red\"",
+            &format!(
+                "\"[(0, {:?}), (1, {:?})]\"",
+                Term::from_field_json_text(attrs, "color", "dark"),
+                Term::from_field_json_text(attrs, "color", "red")
+            ),
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "attrs.size.width:30",
+            &format!(
+                "({:?} {:?})",
+                Term::from_field_json_i64(attrs, "size.width", 30),
+                Term::from_field_json_text(attrs, "size.width", "30")
+            ),
+            false,
+        );
+        assert_matches!(
+            query_parser.parse_query("attrs.size.width:[10 TO 20]"),
+            Err(QueryParserError::RangeOnJsonObject(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("title.color:red"),
+            Err(QueryParserError::FieldDoesNotExist(_))
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_json_object_search() {
+        let mut schema_builder = Schema::builder();
+        let attrs = schema_builder.add_json_object_field("attrs", JsonObjectOptions::from(TEXT));
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for doc_json in &[
+            r#"{"attrs": {"color": "dark red", "size": {"width": 30}}}"#,
+            r#"{"attrs": {"color": "red", "size": {"width": "30"}}}"#,
+            r#"{"attrs": {"color": "blue", "size": {"width": 40.5}}}"#,
+        ] {
+            index_writer
+                .add_document(schema.parse_document(doc_json).unwrap())
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![attrs]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&*query, &Count).unwrap()
+        };
+        assert_eq!(count("attrs.color:red"), 2);
+        assert_eq!(count("attrs.color:\"dark red\""), 1);
+        assert_eq!(count("attrs.size.width:30"), 2);
+        assert_eq!(count("attrs.size.width:40.5"), 1);
+        assert_eq!(count("attrs.color:red AND attrs.size.width:30"), 2);
+        assert_eq!(count("attrs.width:30"), 0);
+    }
+
     #[test]
     pub fn test_parse_query_to_ast_disjunction() {
         test_parse_query_to_logical_ast_helper(
@@ -813,4 +1207,63 @@ mod test {
             true,
         );
     }
+
+    #[test]
+    pub fn test_parse_query_with_synonyms() {
+        let mut query_parser = make_query_parser();
+        let mut synonyms = SynonymMap::new();
+        synonyms.add_synonyms(&["a", "c"]);
+        query_parser.set_synonyms(synonyms);
+        query_parser.set_conjunction_by_default();
+        let query = query_parser
+            .parse_query_to_logical_ast("title:a b title:\"a b\"")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            "(+(Term([0, 0, 0, 0, 97]) Term([0, 0, 0, 0, 99])) \
+             +(Term([0, 0, 0, 0, 98]) Term([0, 0, 0, 1, 98])) \
+             +\"[(0, Term([0, 0, 0, 0, 97])), (1, Term([0, 0, 0, 0, 98]))]\")"
+        );
+        // synonyms are only expanded for text fields.
+        let mut synonyms = SynonymMap::new();
+        synonyms.add_synonyms(&["1", "2"]);
+        query_parser.set_synonyms(synonyms);
+        let query = query_parser
+            .parse_query_to_logical_ast("unsigned:1")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            "Term([0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 1])"
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_synonyms_search() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer
+            .add_document(doc!(title => "a red car"))
+            .unwrap();
+        index_writer
+            .add_document(doc!(title => "an old automobile"))
+            .unwrap();
+        index_writer.add_document(doc!(title => "a bike")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut query_parser = QueryParser::for_index(&index, vec![title]);
+        let count = |query_parser: &QueryParser, query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&*query, &Count).unwrap()
+        };
+        assert_eq!(count(&query_parser, "car"), 1);
+        let mut synonyms = SynonymMap::new();
+        synonyms.add_synonyms(&["car", "automobile"]);
+        query_parser.set_synonyms(synonyms);
+        assert_eq!(count(&query_parser, "car"), 2);
+        assert_eq!(count(&query_parser, "automobile"), 2);
+        assert_eq!(count(&query_parser, "+car +old"), 1);
+    }
 }
@@ -0,0 +1,74 @@
+use std::collections::{BTreeSet, HashMap};
+
+/// Synonyms expanded by the [`QueryParser`](./struct.QueryParser.html)
+/// at query time.
+///
+/// The synonyms are matched against the tokens emitted by the tokenizer
+/// of the field, and should therefore be given in their analyzed form
+/// (e.g. lowercased if the tokenizer lowercases its tokens).
+///
+/// Since the expansion happens at query time, the synonyms can be
+/// changed without reindexing any document.
+#[derive(Clone, Debug, Default)]
+pub struct SynonymMap {
+    synonyms: HashMap<String, BTreeSet<String>>,
+}
+
+impl SynonymMap {
+    /// Creates an empty `SynonymMap`.
+    pub fn new() -> SynonymMap {
+        SynonymMap::default()
+    }
+
+    /// Declares that all of the `words` are synonyms of each other.
+    pub fn add_synonyms(&mut self, words: &[&str]) {
+        for &word in words {
+            let synonyms = self
+                .synonyms
+                .entry(word.to_string())
+                .or_insert_with(BTreeSet::new);
+            for &synonym in words {
+                if synonym != word {
+                    synonyms.insert(synonym.to_string());
+                }
+            }
+        }
+    }
+
+    /// Returns the synonyms of `word`, not including `word` itself.
+    pub fn synonyms<'a>(&'a self, word: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.synonyms
+            .get(word)
+            .into_iter()
+            .flat_map(|synonyms| synonyms.iter().map(|synonym| synonym.as_str()))
+    }
+
+    /// Returns true iff no synonym was declared.
+    pub fn is_empty(&self) -> bool {
+        self.synonyms.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SynonymMap;
+
+    #[test]
+    fn test_synonym_map() {
+        let mut synonym_map = SynonymMap::new();
+        assert!(synonym_map.is_empty());
+        synonym_map.add_synonyms(&["car", "automobile"]);
+        synonym_map.add_synonyms(&["car", "auto"]);
+        assert!(!synonym_map.is_empty());
+        assert_eq!(
+            synonym_map.synonyms("car").collect::<Vec<_>>(),
+            vec!["auto", "automobile"]
+        );
+        assert_eq!(
+            synonym_map.synonyms("auto").collect::<Vec<_>>(),
+            vec!["car"]
+        );
+        assert_eq!(synonym_map.synonyms("bike").count(), 0);
+    }
+}
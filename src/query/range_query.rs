@@ -1,3 +1,4 @@
+use common::ip_cidr_range;
 use common::BitSet;
 use core::Searcher;
 use core::SegmentReader;
@@ -8,8 +9,10 @@ use query::{Query, Scorer, Weight};
 use schema::Type;
 use schema::{Field, IndexRecordOption, Term};
 use std::collections::Bound;
+use std::net::IpAddr;
 use std::ops::Range;
 use termdict::{TermDictionary, TermStreamer};
+use DateTime;
 use Result;
 
 fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
@@ -56,7 +59,7 @@ fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
 /// #         for year in 1950u64..2017u64 {
 /// #             let num_docs_within_year = 10 + (year - 1950) * (year - 1950);
 /// #             for _ in 0..num_docs_within_year {
-/// #                 index_writer.add_document(doc!(year_field => year));
+/// #                 index_writer.add_document(doc!(year_field => year))?;
 /// #             }
 /// #         }
 /// #         index_writer.commit().unwrap();
@@ -140,6 +143,119 @@ impl RangeQuery {
         }
     }
 
+    /// Creates a new `RangeQuery` over a `f64` field.
+    ///
+    /// If the field is not of the type `f64`, tantivy
+    /// will panic when the `Weight` object is created.
+    pub fn new_f64(field: Field, range: Range<f64>) -> RangeQuery {
+        RangeQuery::new_f64_bounds(
+            field,
+            Bound::Included(range.start),
+            Bound::Excluded(range.end),
+        )
+    }
+
+    /// Create a new `RangeQuery` over a `f64` field.
+    ///
+    /// The two `Bound` arguments make it possible to create more complex
+    /// ranges than semi-inclusive range.
+    ///
+    /// If the field is not of the type `f64`, tantivy
+    /// will panic when the `Weight` object is created.
+    pub fn new_f64_bounds(
+        field: Field,
+        left_bound: Bound<f64>,
+        right_bound: Bound<f64>,
+    ) -> RangeQuery {
+        let make_term_val = |val: &f64| Term::from_field_f64(field, *val).value_bytes().to_owned();
+        RangeQuery {
+            field,
+            value_type: Type::F64,
+            left_bound: map_bound(&left_bound, &make_term_val),
+            right_bound: map_bound(&right_bound, &make_term_val),
+        }
+    }
+
+    /// Creates a new `RangeQuery` over a date field.
+    ///
+    /// If the field is not a date field, tantivy
+    /// will panic when the `Weight` object is created.
+    pub fn new_date(field: Field, range: Range<DateTime>) -> RangeQuery {
+        RangeQuery::new_date_bounds(
+            field,
+            Bound::Included(range.start),
+            Bound::Excluded(range.end),
+        )
+    }
+
+    /// Create a new `RangeQuery` over a date field.
+    ///
+    /// The two `Bound` arguments make it possible to create more complex
+    /// ranges than semi-inclusive range.
+    ///
+    /// If the field is not a date field, tantivy
+    /// will panic when the `Weight` object is created.
+    pub fn new_date_bounds(
+        field: Field,
+        left_bound: Bound<DateTime>,
+        right_bound: Bound<DateTime>,
+    ) -> RangeQuery {
+        let make_term_val =
+            |val: &DateTime| Term::from_field_date(field, val).value_bytes().to_owned();
+        RangeQuery {
+            field,
+            value_type: Type::Date,
+            left_bound: map_bound(&left_bound, &make_term_val),
+            right_bound: map_bound(&right_bound, &make_term_val),
+        }
+    }
+
+    /// Create a new `RangeQuery` over an IP address field.
+    ///
+    /// The two `Bound` arguments make it possible to create more complex
+    /// ranges than semi-inclusive range. IPv4 addresses are mapped into
+    /// IPv6, so that `::ffff:0.0.0.0` to `::ffff:255.255.255.255` is
+    /// the range of all of the IPv4 addresses.
+    ///
+    /// If the field is not an IP address field, tantivy
+    /// will panic when the `Weight` object is created.
+    pub fn new_ip_addr_bounds(
+        field: Field,
+        left_bound: Bound<IpAddr>,
+        right_bound: Bound<IpAddr>,
+    ) -> RangeQuery {
+        let make_term_val = |val: &IpAddr| {
+            Term::from_field_ip_addr(field, *val)
+                .value_bytes()
+                .to_owned()
+        };
+        RangeQuery {
+            field,
+            value_type: Type::IpAddr,
+            left_bound: map_bound(&left_bound, &make_term_val),
+            right_bound: map_bound(&right_bound, &make_term_val),
+        }
+    }
+
+    /// Creates a new `RangeQuery` over an IP address field,
+    /// matching the addresses of a CIDR block, e.g. `10.0.0.0/8`
+    /// with `ip_addr` being `10.0.0.0` and `prefix_len` being `8`.
+    ///
+    /// The prefix of an IPv4 block is relative to the IPv4 address.
+    ///
+    /// # Panics
+    /// If `prefix_len` is longer than the address, i.e. more than
+    /// 32 bits for an IPv4 address or 128 bits for an IPv6 address.
+    pub fn new_ip_addr_cidr(field: Field, ip_addr: IpAddr, prefix_len: u8) -> RangeQuery {
+        let (first_ip_addr, last_ip_addr) = ip_cidr_range(ip_addr, prefix_len)
+            .unwrap_or_else(|| panic!("Invalid CIDR prefix {}/{}", ip_addr, prefix_len));
+        RangeQuery::new_ip_addr_bounds(
+            field,
+            Bound::Included(IpAddr::V6(first_ip_addr)),
+            Bound::Included(IpAddr::V6(last_ip_addr)),
+        )
+    }
+
     /// Create a new `RangeQuery` over a `u64` field.
     ///
     /// The two `Bound` arguments make it possible to create more complex
@@ -292,9 +408,12 @@ impl Weight for RangeWeight {
 mod tests {
 
     use super::RangeQuery;
+    use chrono::{TimeZone, Utc};
     use collector::Count;
-    use schema::{Document, Field, Schema, INT_INDEXED};
+    use schema::IpAddrOptions;
+    use schema::{DateOptions, DatePrecision, Document, Field, Schema, INT_INDEXED};
     use std::collections::Bound;
+    use std::net::IpAddr;
     use Index;
     use Result;
 
@@ -311,7 +430,7 @@ mod tests {
                 for year in 1950u64..2017u64 {
                     let num_docs_within_year = 10 + (year - 1950) * (year - 1950);
                     for _ in 0..num_docs_within_year {
-                        index_writer.add_document(doc!(year_field => year));
+                        index_writer.add_document(doc!(year_field => year)).unwrap();
                     }
                 }
                 index_writer.commit().unwrap();
@@ -350,7 +469,7 @@ mod tests {
                         doc.add_i64(int_field, j as i64);
                     }
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
 
             index_writer.commit().unwrap();
@@ -387,4 +506,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_range_query_f64() {
+        let mut schema_builder = Schema::builder();
+        let float_field = schema_builder.add_f64_field("floatfield", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            for &val in &[-10.5f64, -1.0f64, -0.25f64, 0.0f64, 0.5f64, 3.0f64, 1e10f64] {
+                index_writer.add_document(doc!(float_field => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |range_query: RangeQuery| searcher.search(&range_query, &Count).unwrap();
+
+        assert_eq!(count(RangeQuery::new_f64(float_field, -1.0..0.5)), 3);
+        assert_eq!(count(RangeQuery::new_f64(float_field, -20.0..-0.5)), 2);
+        assert_eq!(
+            count(RangeQuery::new_f64_bounds(
+                float_field,
+                Bound::Excluded(0.0),
+                Bound::Unbounded
+            )),
+            3
+        );
+        assert_eq!(
+            count(RangeQuery::new_f64_bounds(
+                float_field,
+                Bound::Unbounded,
+                Bound::Included(-0.25)
+            )),
+            3
+        );
+    }
+
+    #[test]
+    fn test_range_query_date() {
+        let mut schema_builder = Schema::builder();
+        let date_options =
+            DateOptions::from(INT_INDEXED).set_precision(DatePrecision::Milliseconds);
+        let date_field = schema_builder.add_date_field("date", date_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            for day in 1..31 {
+                let date = Utc.ymd(2019, 1, day).and_hms_nano(12, 0, 0, 999_999);
+                index_writer.add_document(doc!(date_field => date)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |range_query: RangeQuery| searcher.search(&range_query, &Count).unwrap();
+
+        let jan_10 = Utc.ymd(2019, 1, 10).and_hms(0, 0, 0);
+        let jan_20 = Utc.ymd(2019, 1, 20).and_hms(0, 0, 0);
+        assert_eq!(count(RangeQuery::new_date(date_field, jan_10..jan_20)), 10);
+        // the dates are truncated to the millisecond.
+        let jan_10_noon = Utc.ymd(2019, 1, 10).and_hms(12, 0, 0);
+        assert_eq!(
+            count(RangeQuery::new_date_bounds(
+                date_field,
+                Bound::Excluded(jan_10_noon),
+                Bound::Unbounded
+            )),
+            20
+        );
+        assert_eq!(
+            count(RangeQuery::new_date_bounds(
+                date_field,
+                Bound::Unbounded,
+                Bound::Included(jan_10_noon)
+            )),
+            10
+        );
+    }
+
+    #[test]
+    fn test_range_query_ip_addr() {
+        let ip = |ip_str: &str| ip_str.parse::<IpAddr>().unwrap();
+        let mut schema_builder = Schema::builder();
+        let ip_field =
+            schema_builder.add_ip_addr_field("ip", IpAddrOptions::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            for ip_str in &[
+                "10.0.0.1",
+                "10.0.1.1",
+                "10.1.0.1",
+                "11.0.0.1",
+                "192.168.0.1",
+                "2001:db8::1",
+                "2001:db8:1::1",
+                "2001:db9::1",
+            ] {
+                index_writer
+                    .add_document(doc!(ip_field => ip(ip_str)))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |range_query: RangeQuery| searcher.search(&range_query, &Count).unwrap();
+
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_cidr(ip_field, ip("10.0.0.0"), 8)),
+            3
+        );
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_cidr(ip_field, ip("10.0.0.0"), 16)),
+            2
+        );
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_cidr(ip_field, ip("10.0.1.1"), 32)),
+            1
+        );
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_cidr(ip_field, ip("0.0.0.0"), 0)),
+            5
+        );
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_cidr(ip_field, ip("2001:db8::"), 32)),
+            2
+        );
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_cidr(ip_field, ip("::"), 0)),
+            8
+        );
+        assert_eq!(
+            count(RangeQuery::new_ip_addr_bounds(
+                ip_field,
+                Bound::Excluded(ip("10.0.1.1")),
+                Bound::Included(ip("192.168.0.1"))
+            )),
+            3
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use error::TantivyError;
 use fst_regex::Regex;
-use query::{AutomatonWeight, Query, Weight};
+use query::{AutomatonWeight, ExpansionLimit, Query, Weight};
 use schema::Field;
 use std::clone::Clone;
 use Result;
@@ -31,16 +31,16 @@ use Searcher;
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -58,6 +58,7 @@ use Searcher;
 pub struct RegexQuery {
     regex_pattern: String,
     field: Field,
+    expansion_limit: Option<ExpansionLimit>,
 }
 
 impl RegexQuery {
@@ -66,14 +67,21 @@ impl RegexQuery {
         RegexQuery {
             regex_pattern,
             field,
+            expansion_limit: None,
         }
     }
 
+    /// Limits the number of terms the regex expands to.
+    /// By default, the query is not limited.
+    pub fn set_expansion_limit(&mut self, expansion_limit: Option<ExpansionLimit>) {
+        self.expansion_limit = expansion_limit;
+    }
+
     fn specialized_weight(&self) -> Result<AutomatonWeight<Regex>> {
         let automaton = Regex::new(&self.regex_pattern)
             .map_err(|_| TantivyError::InvalidArgument(self.regex_pattern.clone()))?;
 
-        Ok(AutomatonWeight::new(self.field, automaton))
+        Ok(AutomatonWeight::new(self.field, automaton).with_expansion_limit(self.expansion_limit))
     }
 }
 
@@ -86,7 +94,8 @@ impl Query for RegexQuery {
 #[cfg(test)]
 mod test {
     use super::RegexQuery;
-    use collector::TopDocs;
+    use collector::{Count, TopDocs};
+    use query::ExpansionLimit;
     use schema::Schema;
     use schema::TEXT;
     use tests::assert_nearly_equals;
@@ -100,12 +109,16 @@ mod test {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
-            index_writer.add_document(doc!(
-                country_field => "japan",
-            ));
-            index_writer.add_document(doc!(
-                country_field => "korea",
-            ));
+            index_writer
+                .add_document(doc!(
+                    country_field => "japan",
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    country_field => "korea",
+                ))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -125,4 +138,42 @@ mod test {
             .unwrap();
         assert!(top_docs.is_empty(), "Expected ZERO document");
     }
+
+    #[test]
+    pub fn test_regex_query_expansion_limit() {
+        let mut schema_builder = Schema::builder();
+        let country_field = schema_builder.add_text_field("country", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+            index_writer
+                .add_document(doc!(country_field => "japan"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(country_field => "jamaica"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(country_field => "jamaica"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(country_field => "jordan"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(country_field => "korea"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut regex_query = RegexQuery::new("j.*".to_string(), country_field);
+        assert_eq!(searcher.search(&regex_query, &Count).unwrap(), 4);
+        regex_query.set_expansion_limit(Some(ExpansionLimit::Error(3)));
+        assert_eq!(searcher.search(&regex_query, &Count).unwrap(), 4);
+        regex_query.set_expansion_limit(Some(ExpansionLimit::Error(2)));
+        assert!(searcher.search(&regex_query, &Count).is_err());
+        regex_query.set_expansion_limit(Some(ExpansionLimit::TopDocFreq(1)));
+        assert_eq!(searcher.search(&regex_query, &Count).unwrap(), 2);
+        regex_query.set_expansion_limit(Some(ExpansionLimit::TopDocFreq(2)));
+        assert_eq!(searcher.search(&regex_query, &Count).unwrap(), 3);
+    }
 }
@@ -60,6 +60,10 @@ where
     fn size_hint(&self) -> u32 {
         self.req_scorer.size_hint()
     }
+
+    fn memory_usage(&self) -> usize {
+        self.req_scorer.memory_usage() + self.opt_scorer.memory_usage()
+    }
 }
 
 impl<TReqScorer, TOptScorer, TScoreCombiner> Scorer
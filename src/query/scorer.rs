@@ -86,6 +86,10 @@ impl<TDocSet: DocSet> DocSet for ConstScorer<TDocSet> {
         self.docset.size_hint()
     }
 
+    fn memory_usage(&self) -> usize {
+        self.docset.memory_usage()
+    }
+
     fn append_to_bitset(&mut self, bitset: &mut BitSet) {
         self.docset.append_to_bitset(bitset);
     }
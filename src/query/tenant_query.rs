@@ -0,0 +1,140 @@
+use core::searcher::Searcher;
+use core::SegmentReader;
+use docset::{DocSet, SkipResult};
+use postings::SegmentPostings;
+use query::{EmptyScorer, Query, Scorer, Weight};
+use schema::{IndexRecordOption, Term};
+use std::collections::BTreeSet;
+use std::fmt;
+use DocId;
+use Result;
+use Score;
+
+/// `TenantQuery` restricts the documents matched by a query to the
+/// documents of a tenant, identified by a term.
+///
+/// The tenant term has no impact on scoring: the documents
+/// are scored by the wrapped query only.
+///
+/// See [`TenantIndex`](../struct.TenantIndex.html).
+pub struct TenantQuery {
+    query: Box<Query>,
+    tenant_term: Term,
+}
+
+impl TenantQuery {
+    /// Creates a new `TenantQuery` given the query to restrict,
+    /// and the term identifying the documents of the tenant.
+    pub fn new(query: Box<Query>, tenant_term: Term) -> TenantQuery {
+        TenantQuery { query, tenant_term }
+    }
+
+    /// Returns the term identifying the documents of the tenant.
+    pub fn tenant_term(&self) -> &Term {
+        &self.tenant_term
+    }
+}
+
+impl Clone for TenantQuery {
+    fn clone(&self) -> Self {
+        TenantQuery {
+            query: self.query.box_clone(),
+            tenant_term: self.tenant_term.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for TenantQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Tenant(query={:?}, tenant_term={:?})",
+            self.query, self.tenant_term
+        )
+    }
+}
+
+impl Query for TenantQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(TenantWeight {
+            weight,
+            tenant_term: self.tenant_term.clone(),
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+}
+
+/// Weight associated to the `TenantQuery` query.
+pub struct TenantWeight {
+    weight: Box<Weight>,
+    tenant_term: Term,
+}
+
+impl Weight for TenantWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let tenant_postings_opt = reader
+            .inverted_index(self.tenant_term.field())
+            .read_postings(&self.tenant_term, IndexRecordOption::Basic);
+        match tenant_postings_opt {
+            Some(tenant_postings) => {
+                let scorer = self.weight.scorer(reader)?;
+                Ok(Box::new(TenantScorer {
+                    scorer,
+                    tenant_postings,
+                }))
+            }
+            None => Ok(Box::new(EmptyScorer)),
+        }
+    }
+}
+
+/// Scorer associated to the `TenantQuery` query.
+pub struct TenantScorer {
+    scorer: Box<Scorer>,
+    tenant_postings: SegmentPostings,
+}
+
+impl DocSet for TenantScorer {
+    fn advance(&mut self) -> bool {
+        if !self.scorer.advance() {
+            return false;
+        }
+        loop {
+            // the tenant postings are always positioned
+            // before the document of the scorer.
+            match self.tenant_postings.skip_next(self.scorer.doc()) {
+                SkipResult::Reached => return true,
+                SkipResult::End => return false,
+                SkipResult::OverStep => match self.scorer.skip_next(self.tenant_postings.doc()) {
+                    SkipResult::Reached => return true,
+                    SkipResult::End => return false,
+                    SkipResult::OverStep => {}
+                },
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer
+            .size_hint()
+            .min(self.tenant_postings.size_hint())
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.scorer.memory_usage() + self.tenant_postings.memory_usage()
+    }
+}
+
+impl Scorer for TenantScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
@@ -12,7 +12,7 @@ mod tests {
     use collector::TopDocs;
     use docset::DocSet;
     use query::{Query, QueryParser, Scorer, TermQuery};
-    use schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STRING, TEXT};
     use tests::assert_nearly_equals;
     use Index;
     use Term;
@@ -28,7 +28,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field => "a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -61,8 +61,10 @@ mod tests {
                 left_field => "left1 left2 left2 left2f2 left2f2 left3 abcde abcde abcde abcde abcde abcde abcde abcde abcde abcewde abcde abcde",
                 right_field => "right1 right2",
                 large_field => "large0 large1 large2 large3 large4 large5 large6 large7 large8 large9 large10 large11 large12 large13 large14 large15 large16 large17 large18 large19 large20 large21 large22 large23 large24 large25 large26 large27 large28 large29 large30 large31 large32 large33 large34 large35 large36 large37 large38 large39 large40 large41 large42 large43 large44 large45 large46 large47 large48 large49 large50 large51 large52 large53 large54 large55 large56 large57 large58 large59 large60 large61 large62 large63 large64 large65 large66 large67 large68 large69 large70 large71 large72 large73 large74 large75 large76 large77 large78 large79 large80 large81 large82 large83 large84 large85 large86 large87 large88 large89 large90 large91 large92 large93 large94 large95 large96 large97 large98 large99 large100 large101 large102 large103 large104 large105 large106 large107 large108 large109 large110 large111 large112 large113 large114 large115 large116 large117 large118 large119 large120 large121 large122 large123 large124 large125 large126 large127 large128 large129 large130 large131 large132 large133 large134 large135 large136 large137 large138 large139 large140 large141 large142 large143 large144 large145 large146 large147 large148 large149 large150 large151 large152 large153 large154 large155 large156 large157 large158 large159 large160 large161 large162 large163 large164 large165 large166 large167 large168 large169 large170 large171 large172 large173 large174 large175 large176 large177 large178 large179 large180 large181 large182 large183 large184 large185 large186 large187 large188 large189 large190 large191 large192 large193 large194 large195 large196 large197 large198 large199 large200 large201 large202 large203 large204 large205 large206 large207 large208 large209 large210 large211 large212 large213 large214 large215 large216 large217 large218 large219 large220 large221 large222 large223 large224 large225 large226 large227 large228 large229 large230 large231 large232 large233 large234 large235 large236 large237 large238 large239 large240 large241 large242 large243 large244 large245 large246 large247 large248 large249 large250 large251 large252 large253 large254 large255 large256 large257 large258 large259 large260 large261 large262 large263 large264 large265 large266 large267 large268 large269 large270 large271 large272 large273 large274 large275 large276 large277 large278 large279 large280 large281 large282 large283 large284 large285 large286"
-            ));
-            index_writer.add_document(doc!(left_field => "left4 left1"));
+            )).unwrap();
+            index_writer
+                .add_document(doc!(left_field => "left4 left1"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -101,4 +103,38 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_term_weight_boost() {
+        let mut schema_builder = Schema::builder();
+        let title_indexing = TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .set_boost(2f32);
+        let title_field = schema_builder.add_text_field(
+            "title",
+            TextOptions::default().set_indexing_options(title_indexing),
+        );
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+            index_writer
+                .add_document(doc!(body_field => "hello world"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title_field => "hello world"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title_field, body_field]);
+        let query = query_parser.parse_query("hello").unwrap();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(2)).unwrap();
+        assert_eq!(top_docs.len(), 2);
+        let (title_score, title_doc) = top_docs[0];
+        let (body_score, _) = top_docs[1];
+        assert_eq!(title_doc.doc(), 1);
+        assert_nearly_equals(2f32 * body_score, title_score);
+    }
+
 }
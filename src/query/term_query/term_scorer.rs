@@ -8,6 +8,8 @@ use postings::Postings;
 use postings::SegmentPostings;
 use query::bm25::BM25Weight;
 
+/// Scorer of the documents of a `SegmentPostings`,
+/// with the BM25 similarity.
 pub struct TermScorer {
     postings: SegmentPostings,
     fieldnorm_reader: FieldNormReader,
@@ -15,6 +17,8 @@ pub struct TermScorer {
 }
 
 impl TermScorer {
+    /// Creates a new `TermScorer`, given the postings of the term, the
+    /// fieldnorm reader of its field and its BM25 weight.
     pub fn new(
         postings: SegmentPostings,
         fieldnorm_reader: FieldNormReader,
@@ -41,6 +45,10 @@ impl DocSet for TermScorer {
         self.postings.size_hint()
     }
 
+    fn memory_usage(&self) -> usize {
+        self.postings.memory_usage()
+    }
+
     fn skip_next(&mut self, target: DocId) -> SkipResult {
         self.postings.skip_next(target)
     }
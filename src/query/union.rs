@@ -3,6 +3,7 @@ use docset::{DocSet, SkipResult};
 use query::score_combiner::{DoNothingCombiner, ScoreCombiner};
 use query::Scorer;
 use std::cmp::Ordering;
+use std::mem;
 use DocId;
 use Score;
 
@@ -248,6 +249,15 @@ where
     fn size_hint(&self) -> u32 {
         0u32
     }
+
+    fn memory_usage(&self) -> usize {
+        let docsets_num_bytes: usize = self
+            .docsets
+            .iter()
+            .map(|docset| mem::size_of_val(docset) + docset.memory_usage())
+            .sum();
+        docsets_num_bytes + mem::size_of_val(&*self.bitsets) + mem::size_of_val(&*self.scores)
+    }
 }
 
 impl<TScorer, TScoreCombiner> Scorer for Union<TScorer, TScoreCombiner>
@@ -2,6 +2,7 @@
 
 use common::HasLen;
 use docset::DocSet;
+use std::mem;
 use std::num::Wrapping;
 use DocId;
 
@@ -39,6 +40,10 @@ impl DocSet for VecDocSet {
     fn size_hint(&self) -> u32 {
         self.len() as u32
     }
+
+    fn memory_usage(&self) -> usize {
+        self.doc_ids.capacity() * mem::size_of::<DocId>()
+    }
 }
 
 impl HasLen for VecDocSet {
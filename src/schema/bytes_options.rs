@@ -0,0 +1,91 @@
+use schema::StoreCompressionHint;
+
+/// Define how a bytes field should be handled by tantivy.
+///
+/// Bytes fields are not indexed: their values can be stored,
+/// and/or kept in a fast field for random access.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytesOptions {
+    fast: bool,
+    stored: bool,
+    #[serde(default, skip_serializing_if = "StoreCompressionHint::is_default")]
+    store_compression_hint: StoreCompressionHint,
+}
+
+impl BytesOptions {
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+
+    /// Set the bytes options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self) -> BytesOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Returns the compression hint for the stored values of this field.
+    pub fn store_compression_hint(&self) -> StoreCompressionHint {
+        self.store_compression_hint
+    }
+
+    /// Sets how the stored values of this field should be compressed.
+    ///
+    /// This is only relevant if the field is also stored.
+    pub fn set_store_compression_hint(
+        mut self,
+        store_compression_hint: StoreCompressionHint,
+    ) -> BytesOptions {
+        self.store_compression_hint = store_compression_hint;
+        self
+    }
+
+    /// Set the bytes options as a fast field.
+    ///
+    /// The values are read with `SegmentReader::bytes_fast_field_reader`.
+    /// If more than one value is associated to the field in a document,
+    /// the values are concatenated.
+    pub fn set_fast(mut self) -> BytesOptions {
+        self.fast = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::BytesOptions;
+    use schema::StoreCompressionHint;
+    use serde_json;
+
+    #[test]
+    fn test_bytes_options() {
+        let bytes_options = BytesOptions::default().set_stored();
+        assert!(bytes_options.is_stored());
+        assert!(!bytes_options.is_fast());
+        let json = serde_json::to_string(&bytes_options.set_fast()).unwrap();
+        assert_eq!(json, r#"{"fast":true,"stored":true}"#);
+    }
+
+    #[test]
+    fn test_bytes_options_store_compression_hint() {
+        let bytes_options = BytesOptions::default()
+            .set_stored()
+            .set_store_compression_hint(StoreCompressionHint::Uncompressed);
+        let json = serde_json::to_string(&bytes_options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"fast":false,"stored":true,"store_compression_hint":"uncompressed"}"#
+        );
+        let bytes_options_deser: BytesOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(bytes_options_deser, bytes_options);
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::Timelike;
+use schema::{Cardinality, IntOptions};
+use std::ops::BitOr;
+use DateTime;
+
+/// Precision of the values of a date field.
+///
+/// The dates are truncated to this precision when they are indexed,
+/// so that the values stored, indexed and in the fast field are the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DatePrecision {
+    /// The dates are truncated to the second.
+    #[serde(rename = "seconds")]
+    Seconds,
+    /// The dates are truncated to the millisecond.
+    #[serde(rename = "milliseconds")]
+    Milliseconds,
+    /// The dates are kept as is, with a precision of one nanosecond.
+    #[serde(rename = "nanoseconds")]
+    Nanoseconds,
+}
+
+impl Default for DatePrecision {
+    fn default() -> DatePrecision {
+        DatePrecision::Seconds
+    }
+}
+
+impl DatePrecision {
+    /// Truncates `date` to the precision.
+    ///
+    /// The terms of the queries on a date field
+    /// should be truncated to the precision of the field as well.
+    pub fn truncate(self, date: &DateTime) -> DateTime {
+        let nanos = date.nanosecond();
+        let truncated_nanos = match self {
+            DatePrecision::Seconds => nanos - nanos % 1_000_000_000,
+            DatePrecision::Milliseconds => nanos - nanos % 1_000_000,
+            DatePrecision::Nanoseconds => nanos,
+        };
+        date.with_nanosecond(truncated_nanos)
+            .expect("Truncating the nanoseconds keeps them valid.")
+    }
+}
+
+/// Define how a date field should be handled by tantivy.
+///
+/// Besides the precision of the dates, the options are
+/// the same as the ones of the integer fields, and can be
+/// built from them, e.g. `DateOptions::from(FAST | INT_STORED)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateOptions {
+    indexed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fast: Option<Cardinality>,
+    stored: bool,
+    #[serde(default)]
+    precision: DatePrecision,
+}
+
+impl DateOptions {
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast.is_some()
+    }
+
+    /// Returns the precision of the dates.
+    pub fn get_precision(&self) -> DatePrecision {
+        self.precision
+    }
+
+    /// Set the date options as stored.
+    pub fn set_stored(mut self) -> DateOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Set the date options as indexed, making
+    /// it possible to run term and range queries.
+    pub fn set_indexed(mut self) -> DateOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the date options as a fast field.
+    ///
+    /// The fast field values are the number of nanoseconds
+    /// since the UNIX epoch, read as `i64` (see `i64_to_date`).
+    pub fn set_fast(mut self, cardinality: Cardinality) -> DateOptions {
+        self.fast = Some(cardinality);
+        self
+    }
+
+    /// Sets the precision of the dates. Defaults to the second.
+    pub fn set_precision(mut self, precision: DatePrecision) -> DateOptions {
+        self.precision = precision;
+        self
+    }
+
+    /// Returns the cardinality of the fastfield.
+    ///
+    /// If the field has not been declared as a fastfield, then
+    /// the method returns None.
+    pub fn get_fastfield_cardinality(&self) -> Option<Cardinality> {
+        self.fast
+    }
+}
+
+impl From<IntOptions> for DateOptions {
+    fn from(int_options: IntOptions) -> DateOptions {
+        DateOptions {
+            indexed: int_options.is_indexed(),
+            fast: int_options.get_fastfield_cardinality(),
+            stored: int_options.is_stored(),
+            precision: DatePrecision::default(),
+        }
+    }
+}
+
+impl BitOr<IntOptions> for DateOptions {
+    type Output = DateOptions;
+
+    fn bitor(self, other: IntOptions) -> DateOptions {
+        let mut res = self;
+        res.indexed |= other.is_indexed();
+        res.stored |= other.is_stored();
+        res.fast = res.fast.or_else(|| other.get_fastfield_cardinality());
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{DateOptions, DatePrecision};
+    use chrono::{TimeZone, Utc};
+    use schema::{Cardinality, FAST, INT_INDEXED, INT_STORED};
+    use serde_json;
+
+    #[test]
+    fn test_date_precision_truncate() {
+        let date = Utc.ymd(2019, 3, 14).and_hms_nano(15, 9, 26, 535_897_932);
+        assert_eq!(
+            DatePrecision::Seconds.truncate(&date),
+            Utc.ymd(2019, 3, 14).and_hms(15, 9, 26)
+        );
+        assert_eq!(
+            DatePrecision::Milliseconds.truncate(&date),
+            Utc.ymd(2019, 3, 14).and_hms_milli(15, 9, 26, 535)
+        );
+        assert_eq!(DatePrecision::Nanoseconds.truncate(&date), date);
+    }
+
+    #[test]
+    fn test_date_options() {
+        let date_options = DateOptions::from(FAST | INT_STORED) | INT_INDEXED;
+        assert!(date_options.is_indexed());
+        assert!(date_options.is_stored());
+        assert_eq!(
+            date_options.get_fastfield_cardinality(),
+            Some(Cardinality::SingleValue)
+        );
+        assert_eq!(date_options.get_precision(), DatePrecision::Seconds);
+        let date_options = date_options.set_precision(DatePrecision::Milliseconds);
+        let json = serde_json::to_string(&date_options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"indexed":true,"fast":"single","stored":true,"precision":"milliseconds"}"#
+        );
+        let date_options_deser: DateOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(date_options_deser, date_options);
+    }
+}
@@ -2,7 +2,11 @@ use super::*;
 use common::BinarySerializable;
 use common::VInt;
 use itertools::Itertools;
+use serde_json::{Map as JsonObject, Value as JsonValue};
 use std::io::{self, Read, Write};
+use std::net::IpAddr;
+use tokenizer::PreTokenizedString;
+use DateTime;
 
 /// Tantivy's Document is the object that can
 /// be indexed and then searched for.
@@ -77,6 +81,14 @@ impl Document {
         self.add(FieldValue::new(field, value));
     }
 
+    /// Add a pre-tokenized text field.
+    ///
+    /// The tokens are indexed as they are, bypassing the tokenizer
+    /// of the field, while the text is the value stored.
+    pub fn add_pre_tokenized_text(&mut self, field: Field, tokenized_text: PreTokenizedString) {
+        self.add(FieldValue::new(field, Value::PreTokStr(tokenized_text)));
+    }
+
     /// Add a u64 field
     pub fn add_u64(&mut self, field: Field, value: u64) {
         self.add(FieldValue::new(field, Value::U64(value)));
@@ -87,6 +99,28 @@ impl Document {
         self.add(FieldValue::new(field, Value::I64(value)));
     }
 
+    /// Add a f64 field
+    pub fn add_f64(&mut self, field: Field, value: f64) {
+        self.add(FieldValue::new(field, Value::F64(value)));
+    }
+
+    /// Add a date field
+    pub fn add_date(&mut self, field: Field, value: &DateTime) {
+        self.add(FieldValue::new(field, Value::Date(*value)));
+    }
+
+    /// Add an IP address field
+    ///
+    /// IPv4 addresses are mapped into IPv6.
+    pub fn add_ip_addr(&mut self, field: Field, value: IpAddr) {
+        self.add(FieldValue::new(field, Value::from(value)));
+    }
+
+    /// Add a JSON object field
+    pub fn add_json_object(&mut self, field: Field, value: JsonObject<String, JsonValue>) {
+        self.add(FieldValue::new(field, Value::JsonObject(value)));
+    }
+
     /// Add a bytes field
     pub fn add_bytes(&mut self, field: Field, value: Vec<u8>) {
         self.add(FieldValue::new(field, Value::Bytes(value)))
@@ -102,6 +136,19 @@ impl Document {
         &self.field_values
     }
 
+    /// Truncates the dates of the document to
+    /// the precision of their field.
+    pub(crate) fn truncate_dates(&mut self, schema: &Schema) {
+        for field_value in &mut self.field_values {
+            let field_entry = schema.get_field_entry(field_value.field());
+            if let FieldType::Date(ref date_options) = *field_entry.field_type() {
+                if let Value::Date(ref mut date) = *field_value.value_mut() {
+                    *date = date_options.get_precision().truncate(date);
+                }
+            }
+        }
+    }
+
     /// Sort and groups the field_values by field.
     ///
     /// The result of this method is not cached and is
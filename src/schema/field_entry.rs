@@ -1,4 +1,8 @@
+use schema::BytesOptions;
+use schema::DateOptions;
 use schema::IntOptions;
+use schema::IpAddrOptions;
+use schema::JsonObjectOptions;
 use schema::TextOptions;
 
 use schema::FieldType;
@@ -48,6 +52,42 @@ impl FieldEntry {
         }
     }
 
+    /// Creates a new f64 field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_f64(field_name: String, field_type: IntOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::F64(field_type),
+        }
+    }
+
+    /// Creates a new date field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_date(field_name: String, field_type: DateOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::Date(field_type),
+        }
+    }
+
+    /// Creates a new IP address field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_ip_addr(field_name: String, field_type: IpAddrOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::IpAddr(field_type),
+        }
+    }
+
+    /// Creates a new JSON object field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_json_object(field_name: String, field_type: JsonObjectOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::JsonObject(field_type),
+        }
+    }
+
     /// Creates a field entry for a facet.
     pub fn new_facet(field_name: String) -> FieldEntry {
         FieldEntry {
@@ -57,10 +97,10 @@ impl FieldEntry {
     }
 
     /// Creates a field entry for a bytes field
-    pub fn new_bytes(field_name: String) -> FieldEntry {
+    pub fn new_bytes(field_name: String, field_type: BytesOptions) -> FieldEntry {
         FieldEntry {
             name: field_name,
-            field_type: FieldType::Bytes,
+            field_type: FieldType::Bytes(field_type),
         }
     }
 
@@ -78,16 +118,24 @@ impl FieldEntry {
     pub fn is_indexed(&self) -> bool {
         match self.field_type {
             FieldType::Str(ref options) => options.get_indexing_options().is_some(),
-            FieldType::U64(ref options) | FieldType::I64(ref options) => options.is_indexed(),
+            FieldType::U64(ref options)
+            | FieldType::I64(ref options)
+            | FieldType::F64(ref options) => options.is_indexed(),
+            FieldType::Date(ref options) => options.is_indexed(),
+            FieldType::IpAddr(ref options) => options.is_indexed(),
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(_) => false,
+            FieldType::JsonObject(ref options) => options.get_text_indexing_options().is_some(),
         }
     }
 
-    /// Returns true iff the field is a int (signed or unsigned) fast field
+    /// Returns true iff the field is a numerical (u64, i64, f64 or date) fast field
     pub fn is_int_fast(&self) -> bool {
         match self.field_type {
-            FieldType::U64(ref options) | FieldType::I64(ref options) => options.is_fast(),
+            FieldType::U64(ref options)
+            | FieldType::I64(ref options)
+            | FieldType::F64(ref options) => options.is_fast(),
+            FieldType::Date(ref options) => options.is_fast(),
             _ => false,
         }
     }
@@ -95,11 +143,16 @@ impl FieldEntry {
     /// Returns true iff the field is stored
     pub fn is_stored(&self) -> bool {
         match self.field_type {
-            FieldType::U64(ref options) | FieldType::I64(ref options) => options.is_stored(),
+            FieldType::U64(ref options)
+            | FieldType::I64(ref options)
+            | FieldType::F64(ref options) => options.is_stored(),
+            FieldType::Date(ref options) => options.is_stored(),
+            FieldType::IpAddr(ref options) => options.is_stored(),
             FieldType::Str(ref options) => options.is_stored(),
             // TODO make stored hierarchical facet optional
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(ref options) => options.is_stored(),
+            FieldType::JsonObject(ref options) => options.is_stored(),
         }
     }
 }
@@ -125,11 +178,28 @@ impl Serialize for FieldEntry {
                 s.serialize_field("type", "i64")?;
                 s.serialize_field("options", options)?;
             }
+            FieldType::F64(ref options) => {
+                s.serialize_field("type", "f64")?;
+                s.serialize_field("options", options)?;
+            }
+            FieldType::Date(ref options) => {
+                s.serialize_field("type", "date")?;
+                s.serialize_field("options", options)?;
+            }
+            FieldType::IpAddr(ref options) => {
+                s.serialize_field("type", "ip_addr")?;
+                s.serialize_field("options", options)?;
+            }
             FieldType::HierarchicalFacet => {
                 s.serialize_field("type", "hierarchical_facet")?;
             }
-            FieldType::Bytes => {
+            FieldType::Bytes(ref options) => {
                 s.serialize_field("type", "bytes")?;
+                s.serialize_field("options", options)?;
+            }
+            FieldType::JsonObject(ref options) => {
+                s.serialize_field("type", "json_object")?;
+                s.serialize_field("options", options)?;
             }
         }
 
@@ -186,9 +256,13 @@ impl<'de> Deserialize<'de> for FieldEntry {
                                     field_type = Some(FieldType::HierarchicalFacet);
                                 }
                                 "bytes" => {
-                                    field_type = Some(FieldType::Bytes);
+                                    // the schemas written before the bytes options
+                                    // existed define fast bytes fields, without options.
+                                    let options = BytesOptions::default().set_fast();
+                                    field_type = Some(FieldType::Bytes(options));
                                 }
-                                "text" | "u64" | "i64" => {
+                                "text" | "u64" | "i64" | "f64" | "date" | "ip_addr"
+                                | "json_object" => {
                                     // These types require additional options to create a field_type
                                 }
                                 _ => panic!("unhandled type"),
@@ -205,6 +279,15 @@ impl<'de> Deserialize<'de> for FieldEntry {
                                 "text" => field_type = Some(FieldType::Str(map.next_value()?)),
                                 "u64" => field_type = Some(FieldType::U64(map.next_value()?)),
                                 "i64" => field_type = Some(FieldType::I64(map.next_value()?)),
+                                "f64" => field_type = Some(FieldType::F64(map.next_value()?)),
+                                "date" => field_type = Some(FieldType::Date(map.next_value()?)),
+                                "ip_addr" => {
+                                    field_type = Some(FieldType::IpAddr(map.next_value()?))
+                                }
+                                "bytes" => field_type = Some(FieldType::Bytes(map.next_value()?)),
+                                "json_object" => {
+                                    field_type = Some(FieldType::JsonObject(map.next_value()?))
+                                }
                                 _ => {
                                     let msg = format!("Unrecognised type {}", ty);
                                     return Err(de::Error::custom(msg));
@@ -229,7 +312,7 @@ impl<'de> Deserialize<'de> for FieldEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use schema::TEXT;
+    use schema::{STORED, TEXT};
     use serde_json;
 
     #[test]
@@ -260,4 +343,37 @@ mod tests {
             _ => panic!("expected FieldType::Str"),
         }
     }
+
+    #[test]
+    fn test_bytes_json_serialization() {
+        let field_entry =
+            FieldEntry::new_bytes(String::from("hash"), BytesOptions::default().set_stored());
+        let field_entry_json = serde_json::to_string(&field_entry).unwrap();
+        assert_eq!(
+            field_entry_json,
+            r#"{"name":"hash","type":"bytes","options":{"fast":false,"stored":true}}"#
+        );
+        let field_entry_deser: FieldEntry = serde_json::from_str(&field_entry_json).unwrap();
+        assert_eq!(field_entry_deser, field_entry);
+        // bytes fields without options are fast fields.
+        let legacy_field_entry: FieldEntry =
+            serde_json::from_str(r#"{"name":"hash","type":"bytes"}"#).unwrap();
+        assert_eq!(
+            legacy_field_entry.field_type(),
+            &FieldType::Bytes(BytesOptions::default().set_fast())
+        );
+    }
+
+    #[test]
+    fn test_json_object_json_serialization() {
+        let field_entry =
+            FieldEntry::new_json_object(String::from("attrs"), JsonObjectOptions::from(STORED));
+        let field_entry_json = serde_json::to_string(&field_entry).unwrap();
+        assert_eq!(
+            field_entry_json,
+            r#"{"name":"attrs","type":"json_object","options":{"stored":true,"indexing":null}}"#
+        );
+        let field_entry_deser: FieldEntry = serde_json::from_str(&field_entry_json).unwrap();
+        assert_eq!(field_entry_deser, field_entry);
+    }
 }
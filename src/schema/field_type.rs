@@ -1,11 +1,16 @@
 use base64::decode;
+use chrono::{DateTime, Utc};
+use common::ip_addr_to_ipv6;
+use std::net::IpAddr;
 
-use schema::{IntOptions, TextOptions};
+use schema::{
+    BytesOptions, DateOptions, IntOptions, IpAddrOptions, JsonObjectOptions, TextOptions,
+};
 
 use schema::Facet;
 use schema::IndexRecordOption;
 use schema::Value;
-use serde_json::Value as JsonValue;
+use serde_json::{self, Value as JsonValue};
 
 /// Possible error that may occur while parsing a field value
 /// At this point the JSON is known to be valid.
@@ -20,6 +25,10 @@ pub enum ValueParsingError {
     /// The json node is a string but contains json that is
     /// not valid base64.
     InvalidBase64(String),
+    /// The json node is a string but is not a valid RFC 3339 date.
+    InvalidDate(String),
+    /// The json node is a string but is not a valid IP address.
+    InvalidIpAddr(String),
 }
 
 /// Type of the value that a field can take.
@@ -34,10 +43,18 @@ pub enum Type {
     U64,
     /// `i64`
     I64,
+    /// `f64`
+    F64,
+    /// `tantivy::DateTime`. Passed as a RFC 3339 string in JSON.
+    Date,
     /// `tantivy::schema::Facet`. Passed as a string in JSON.
     HierarchicalFacet,
     /// `Vec<u8>`
     Bytes,
+    /// `std::net::Ipv6Addr`. Passed as a string in JSON.
+    IpAddr,
+    /// `serde_json::Map<String, serde_json::Value>`, a JSON object.
+    Json,
 }
 
 /// A `FieldType` describes the type (text, u64) of a field as well as
@@ -50,10 +67,18 @@ pub enum FieldType {
     U64(IntOptions),
     /// Signed 64-bits integers 64 field type configuration
     I64(IntOptions),
+    /// 64-bits float 64 field type configuration
+    F64(IntOptions),
+    /// UTC date field type configuration
+    Date(DateOptions),
     /// Hierachical Facet
     HierarchicalFacet,
-    /// Bytes (one per document)
-    Bytes,
+    /// Bytes field type configuration
+    Bytes(BytesOptions),
+    /// IP address field type configuration
+    IpAddr(IpAddrOptions),
+    /// JSON object field type configuration
+    JsonObject(JsonObjectOptions),
 }
 
 impl FieldType {
@@ -63,8 +88,12 @@ impl FieldType {
             FieldType::Str(_) => Type::Str,
             FieldType::U64(_) => Type::U64,
             FieldType::I64(_) => Type::I64,
+            FieldType::F64(_) => Type::F64,
+            FieldType::Date(_) => Type::Date,
             FieldType::HierarchicalFacet => Type::HierarchicalFacet,
-            FieldType::Bytes => Type::Bytes,
+            FieldType::Bytes(_) => Type::Bytes,
+            FieldType::IpAddr(_) => Type::IpAddr,
+            FieldType::JsonObject(_) => Type::Json,
         }
     }
 
@@ -72,11 +101,16 @@ impl FieldType {
     pub fn is_indexed(&self) -> bool {
         match *self {
             FieldType::Str(ref text_options) => text_options.get_indexing_options().is_some(),
-            FieldType::U64(ref int_options) | FieldType::I64(ref int_options) => {
-                int_options.is_indexed()
-            }
+            FieldType::U64(ref int_options)
+            | FieldType::I64(ref int_options)
+            | FieldType::F64(ref int_options) => int_options.is_indexed(),
+            FieldType::Date(ref date_options) => date_options.is_indexed(),
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(_) => false,
+            FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_indexed(),
+            FieldType::JsonObject(ref json_object_options) => {
+                json_object_options.get_text_indexing_options().is_some()
+            }
         }
     }
 
@@ -89,18 +123,55 @@ impl FieldType {
             FieldType::Str(ref text_options) => text_options
                 .get_indexing_options()
                 .map(|indexing_options| indexing_options.index_option()),
-            FieldType::U64(ref int_options) | FieldType::I64(ref int_options) => {
+            FieldType::U64(ref int_options)
+            | FieldType::I64(ref int_options)
+            | FieldType::F64(ref int_options) => {
                 if int_options.is_indexed() {
                     Some(IndexRecordOption::Basic)
                 } else {
                     None
                 }
             }
+            FieldType::Date(ref date_options) => {
+                if date_options.is_indexed() {
+                    Some(IndexRecordOption::Basic)
+                } else {
+                    None
+                }
+            }
+            FieldType::IpAddr(ref ip_addr_options) => {
+                if ip_addr_options.is_indexed() {
+                    Some(IndexRecordOption::Basic)
+                } else {
+                    None
+                }
+            }
             FieldType::HierarchicalFacet => Some(IndexRecordOption::Basic),
-            FieldType::Bytes => None,
+            FieldType::Bytes(_) => None,
+            FieldType::JsonObject(ref json_object_options) => json_object_options
+                .get_text_indexing_options()
+                .map(|indexing_options| indexing_options.index_option()),
         }
     }
 
+    /// Returns the boost applied to the scores of the field.
+    ///
+    /// Only the text and JSON object fields can be boosted
+    /// (see `TextFieldIndexing::set_boost`), the boost of
+    /// the other fields is 1.
+    pub fn get_boost(&self) -> f32 {
+        let text_indexing_options = match *self {
+            FieldType::Str(ref text_options) => text_options.get_indexing_options(),
+            FieldType::JsonObject(ref json_object_options) => {
+                json_object_options.get_text_indexing_options()
+            }
+            _ => None,
+        };
+        text_indexing_options
+            .map(|indexing_options| indexing_options.boost())
+            .unwrap_or(1f32)
+    }
+
     /// Parses a field value from json, given the target FieldType.
     ///
     /// Tantivy will not try to cast values.
@@ -110,16 +181,37 @@ impl FieldType {
         match *json {
             JsonValue::String(ref field_text) => match *self {
                 FieldType::Str(_) => Ok(Value::Str(field_text.clone())),
-                FieldType::U64(_) | FieldType::I64(_) => Err(ValueParsingError::TypeError(
-                    format!("Expected an integer, got {:?}", json),
-                )),
+                FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) => Err(
+                    ValueParsingError::TypeError(format!("Expected a number, got {:?}", json)),
+                ),
+                FieldType::Date(_) => DateTime::parse_from_rfc3339(field_text)
+                    .map(|date| Value::Date(date.with_timezone(&Utc)))
+                    .map_err(|_| {
+                        ValueParsingError::InvalidDate(format!(
+                            "Expected a RFC 3339 date, got {:?}",
+                            field_text
+                        ))
+                    }),
                 FieldType::HierarchicalFacet => Ok(Value::Facet(Facet::from(field_text))),
-                FieldType::Bytes => decode(field_text).map(Value::Bytes).map_err(|_| {
+                FieldType::Bytes(_) => decode(field_text).map(Value::Bytes).map_err(|_| {
                     ValueParsingError::InvalidBase64(format!(
                         "Expected base64 string, got {:?}",
                         field_text
                     ))
                 }),
+                FieldType::IpAddr(_) => field_text
+                    .parse::<IpAddr>()
+                    .map(|ip_addr| Value::IpAddr(ip_addr_to_ipv6(ip_addr)))
+                    .map_err(|_| {
+                        ValueParsingError::InvalidIpAddr(format!(
+                            "Expected an IP address, got {:?}",
+                            field_text
+                        ))
+                    }),
+                FieldType::JsonObject(_) => Err(ValueParsingError::TypeError(format!(
+                    "Expected a JSON object, got {:?}",
+                    json
+                ))),
             },
             JsonValue::Number(ref field_val_num) => match *self {
                 FieldType::I64(_) => {
@@ -138,11 +230,40 @@ impl FieldType {
                         Err(ValueParsingError::OverflowError(msg))
                     }
                 }
-                FieldType::Str(_) | FieldType::HierarchicalFacet | FieldType::Bytes => {
+                FieldType::F64(_) => {
+                    if let Some(field_val_f64) = field_val_num.as_f64() {
+                        Ok(Value::F64(field_val_f64))
+                    } else {
+                        let msg = format!("Expected a f64, got {:?}", json);
+                        Err(ValueParsingError::OverflowError(msg))
+                    }
+                }
+                FieldType::Str(_)
+                | FieldType::Date(_)
+                | FieldType::HierarchicalFacet
+                | FieldType::Bytes(_)
+                | FieldType::IpAddr(_) => {
                     let msg = format!("Expected a string, got {:?}", json);
                     Err(ValueParsingError::TypeError(msg))
                 }
+                FieldType::JsonObject(_) => {
+                    let msg = format!("Expected a JSON object, got {:?}", json);
+                    Err(ValueParsingError::TypeError(msg))
+                }
             },
+            JsonValue::Object(ref json_object) if self.value_type() == Type::Json => {
+                Ok(Value::JsonObject(json_object.clone()))
+            }
+            JsonValue::Object(_) if self.value_type() == Type::Str => {
+                serde_json::from_value(json.clone())
+                    .map(Value::PreTokStr)
+                    .map_err(|_| {
+                        ValueParsingError::TypeError(format!(
+                            "Expected a string or a pre-tokenized string, got {:?}",
+                            json
+                        ))
+                    })
+            }
             _ => {
                 let msg = format!(
                     "Json value not supported error {:?}. Expected {:?}",
@@ -157,26 +278,127 @@ impl FieldType {
 #[cfg(test)]
 mod tests {
     use super::FieldType;
+    use chrono::{TimeZone, Utc};
     use schema::field_type::ValueParsingError;
-    use schema::Value;
+    use schema::{BytesOptions, DateOptions, IpAddrOptions, JsonObjectOptions, Value, FAST, TEXT};
+    use std::net::Ipv4Addr;
+    use tokenizer::{PreTokenizedString, Token};
 
     #[test]
     fn test_bytes_value_from_json() {
-        let result = FieldType::Bytes
+        let field_type = FieldType::Bytes(BytesOptions::default());
+        let result = field_type
             .value_from_json(&json!("dGhpcyBpcyBhIHRlc3Q="))
             .unwrap();
         assert_eq!(result, Value::Bytes("this is a test".as_bytes().to_vec()));
 
-        let result = FieldType::Bytes.value_from_json(&json!(521));
+        let result = field_type.value_from_json(&json!(521));
         match result {
             Err(ValueParsingError::TypeError(_)) => {}
             _ => panic!("Expected parse failure for wrong type"),
         }
 
-        let result = FieldType::Bytes.value_from_json(&json!("-"));
+        let result = field_type.value_from_json(&json!("-"));
         match result {
             Err(ValueParsingError::InvalidBase64(_)) => {}
             _ => panic!("Expected parse failure for invalid base64"),
         }
     }
+
+    #[test]
+    fn test_f64_value_from_json() {
+        let field_type = FieldType::F64(FAST);
+        assert_eq!(
+            field_type.value_from_json(&json!(-1.5)).unwrap(),
+            Value::F64(-1.5)
+        );
+        assert_eq!(
+            field_type.value_from_json(&json!(3)).unwrap(),
+            Value::F64(3.0)
+        );
+        match field_type.value_from_json(&json!("1.5")) {
+            Err(ValueParsingError::TypeError(_)) => {}
+            _ => panic!("Expected parse failure for wrong type"),
+        }
+    }
+
+    #[test]
+    fn test_date_value_from_json() {
+        let field_type = FieldType::Date(DateOptions::default());
+        assert_eq!(
+            field_type
+                .value_from_json(&json!("2019-03-14T16:09:26.535+01:00"))
+                .unwrap(),
+            Value::Date(Utc.ymd(2019, 3, 14).and_hms_milli(15, 9, 26, 535))
+        );
+        match field_type.value_from_json(&json!("2019-03-14")) {
+            Err(ValueParsingError::InvalidDate(_)) => {}
+            _ => panic!("Expected parse failure for invalid date"),
+        }
+        match field_type.value_from_json(&json!(1552576166)) {
+            Err(ValueParsingError::TypeError(_)) => {}
+            _ => panic!("Expected parse failure for wrong type"),
+        }
+    }
+
+    #[test]
+    fn test_ip_addr_value_from_json() {
+        let field_type = FieldType::IpAddr(IpAddrOptions::default());
+        assert_eq!(
+            field_type.value_from_json(&json!("192.168.0.1")).unwrap(),
+            Value::from(Ipv4Addr::new(192, 168, 0, 1))
+        );
+        assert_eq!(
+            field_type.value_from_json(&json!("2001:db8::1")).unwrap(),
+            Value::IpAddr("2001:db8::1".parse().unwrap())
+        );
+        match field_type.value_from_json(&json!("192.168.0.256")) {
+            Err(ValueParsingError::InvalidIpAddr(_)) => {}
+            _ => panic!("Expected parse failure for invalid IP address"),
+        }
+    }
+    #[test]
+    fn test_json_object_value_from_json() {
+        let field_type = FieldType::JsonObject(JsonObjectOptions::from(TEXT));
+        let json_object = json!({"color": "red", "size": {"width": 30}});
+        assert_eq!(
+            field_type.value_from_json(&json_object).unwrap(),
+            Value::JsonObject(json_object.as_object().unwrap().clone())
+        );
+        match field_type.value_from_json(&json!("red")) {
+            Err(ValueParsingError::TypeError(_)) => {}
+            _ => panic!("Expected parse failure for wrong type"),
+        }
+        match FieldType::Str(TEXT).value_from_json(&json_object) {
+            Err(ValueParsingError::TypeError(_)) => {}
+            _ => panic!("Expected parse failure for wrong type"),
+        }
+    }
+
+    #[test]
+    fn test_pre_tokenized_text_value_from_json() {
+        let field_type = FieldType::Str(TEXT);
+        let json = json!({
+            "text": "New York",
+            "tokens": [{
+                "offset_from": 0,
+                "offset_to": 8,
+                "position": 0,
+                "text": "new_york",
+                "position_length": 1
+            }]
+        });
+        let tokenized_text = PreTokenizedString {
+            text: "New York".to_string(),
+            tokens: vec![Token {
+                offset_from: 0,
+                offset_to: 8,
+                position: 0,
+                text: "new_york".to_string(),
+                position_length: 1,
+            }],
+        };
+        let value = field_type.value_from_json(&json).unwrap();
+        assert_eq!(value.tokenized_text(), Some(&tokenized_text));
+    }
 }
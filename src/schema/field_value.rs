@@ -27,6 +27,10 @@ impl FieldValue {
     pub fn value(&self) -> &Value {
         &self.value
     }
+
+    pub(crate) fn value_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
 }
 
 impl BinarySerializable for FieldValue {
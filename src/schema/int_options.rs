@@ -1,3 +1,4 @@
+use schema::StoreCompressionHint;
 use std::ops::BitOr;
 
 /// Express whether a field is single-value or multi-valued.
@@ -19,6 +20,8 @@ pub struct IntOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     fast: Option<Cardinality>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "StoreCompressionHint::is_default")]
+    store_compression_hint: StoreCompressionHint,
 }
 
 impl IntOptions {
@@ -46,6 +49,22 @@ impl IntOptions {
         self
     }
 
+    /// Returns the compression hint for the stored values of this field.
+    pub fn store_compression_hint(&self) -> StoreCompressionHint {
+        self.store_compression_hint
+    }
+
+    /// Sets how the stored values of this field should be compressed.
+    ///
+    /// This is only relevant if the field is also stored.
+    pub fn set_store_compression_hint(
+        mut self,
+        store_compression_hint: StoreCompressionHint,
+    ) -> IntOptions {
+        self.store_compression_hint = store_compression_hint;
+        self
+    }
+
     /// Set the u64 options as indexed.
     ///
     /// Setting an integer as indexed will generate
@@ -81,6 +100,7 @@ impl Default for IntOptions {
             indexed: false,
             stored: false,
             fast: None,
+            store_compression_hint: StoreCompressionHint::Default,
         }
     }
 }
@@ -92,6 +112,7 @@ pub const FAST: IntOptions = IntOptions {
     indexed: false,
     stored: false,
     fast: Some(Cardinality::SingleValue),
+    store_compression_hint: StoreCompressionHint::Default,
 };
 
 /// Shortcut for a u64 indexed field.
@@ -101,6 +122,7 @@ pub const INT_INDEXED: IntOptions = IntOptions {
     indexed: true,
     stored: false,
     fast: None,
+    store_compression_hint: StoreCompressionHint::Default,
 };
 
 /// Shortcut for a u64 stored field.
@@ -110,6 +132,7 @@ pub const INT_STORED: IntOptions = IntOptions {
     indexed: false,
     stored: true,
     fast: None,
+    store_compression_hint: StoreCompressionHint::Default,
 };
 
 impl BitOr for IntOptions {
@@ -120,6 +143,11 @@ impl BitOr for IntOptions {
         res.indexed = self.indexed | other.indexed;
         res.stored = self.stored | other.stored;
         res.fast = self.fast.or(other.fast);
+        res.store_compression_hint = if self.store_compression_hint.is_default() {
+            other.store_compression_hint
+        } else {
+            self.store_compression_hint
+        };
         res
     }
 }
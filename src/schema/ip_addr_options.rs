@@ -0,0 +1,73 @@
+/// Define how an IP address field should be handled by tantivy.
+///
+/// IPv4 addresses are mapped into IPv6 (see `ip_addr_to_ipv6`),
+/// so that a field can hold both kinds of addresses.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpAddrOptions {
+    indexed: bool,
+    fast: bool,
+    stored: bool,
+}
+
+impl IpAddrOptions {
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+
+    /// Set the IP address options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self) -> IpAddrOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Set the IP address options as indexed, making it possible
+    /// to search for an address, or for a range of addresses
+    /// (e.g. `RangeQuery::new_ip_addr_cidr`).
+    pub fn set_indexed(mut self) -> IpAddrOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the IP address options as a single-valued fast field.
+    ///
+    /// The addresses are read with `SegmentReader::ip_addr_fast_field_reader`.
+    /// If more than one address is associated to the field in a document,
+    /// only the first one is kept.
+    pub fn set_fast(mut self) -> IpAddrOptions {
+        self.fast = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::IpAddrOptions;
+    use serde_json;
+
+    #[test]
+    fn test_ip_addr_options() {
+        let ip_addr_options = IpAddrOptions::default().set_indexed().set_fast();
+        assert!(ip_addr_options.is_indexed());
+        assert!(ip_addr_options.is_fast());
+        assert!(!ip_addr_options.is_stored());
+        let json = serde_json::to_string(&ip_addr_options).unwrap();
+        assert_eq!(json, r#"{"indexed":true,"fast":true,"stored":false}"#);
+        let ip_addr_options_deser: IpAddrOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(ip_addr_options_deser, ip_addr_options);
+    }
+}
@@ -0,0 +1,86 @@
+use schema::{TextFieldIndexing, TextOptions};
+
+/// Define how a JSON object field should be handled by tantivy.
+///
+/// The leaves of the objects are indexed under their path, the keys
+/// of the nested objects being joined with dots: the object
+/// `{"color": "red", "size": {"width": 30}}` of the field `attrs`
+/// matches the queries `attrs.color:red` and `attrs.size.width:30`.
+///
+/// The text leaves are tokenized according to the `TextFieldIndexing`
+/// options, while the numbers are indexed as `i64`, `u64` or `f64`.
+/// The options can be built from the ones of the text fields,
+/// e.g. `JsonObjectOptions::from(TEXT | STORED)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonObjectOptions {
+    stored: bool,
+    indexing: Option<TextFieldIndexing>,
+}
+
+impl JsonObjectOptions {
+    /// Returns true iff the objects are stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns the indexing options of the text leaves of the objects,
+    /// or `None` if the field is not indexed.
+    pub fn get_text_indexing_options(&self) -> Option<&TextFieldIndexing> {
+        self.indexing.as_ref()
+    }
+
+    /// Sets the field as stored.
+    pub fn set_stored(mut self) -> JsonObjectOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Sets the field as indexed, with the indexing options
+    /// of its text leaves.
+    ///
+    /// The offsets of the tokens are never recorded for
+    /// the JSON object fields.
+    pub fn set_indexing_options(mut self, indexing: TextFieldIndexing) -> JsonObjectOptions {
+        self.indexing = Some(indexing);
+        self
+    }
+}
+
+impl From<TextOptions> for JsonObjectOptions {
+    fn from(text_options: TextOptions) -> JsonObjectOptions {
+        JsonObjectOptions {
+            stored: text_options.is_stored(),
+            indexing: text_options.get_indexing_options().cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::JsonObjectOptions;
+    use schema::{IndexRecordOption, STORED, TEXT};
+    use serde_json;
+
+    #[test]
+    fn test_json_object_options() {
+        let json_object_options = JsonObjectOptions::from(TEXT | STORED);
+        assert!(json_object_options.is_stored());
+        assert_eq!(
+            json_object_options
+                .get_text_indexing_options()
+                .map(|indexing| indexing.index_option()),
+            Some(IndexRecordOption::WithFreqsAndPositions)
+        );
+        assert!(JsonObjectOptions::default()
+            .get_text_indexing_options()
+            .is_none());
+        let json = serde_json::to_string(&json_object_options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"stored":true,"indexing":{"record":"position","tokenizer":"default"}}"#
+        );
+        let json_object_options_deser: JsonObjectOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(json_object_options_deser, json_object_options);
+    }
+}
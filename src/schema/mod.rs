@@ -98,10 +98,43 @@ u64 that are indexed as fast will be stored in a special data structure that wil
 make it possible to access the u64 value given the doc id rapidly. This is useful if the value of
 the field is required during scoring or collection for instance.
 
+
+## Setting a date field
+
+Date fields hold UTC dates, parsed from RFC 3339 strings in JSON documents.
+Their options are the ones of the integer fields, plus the precision
+the dates are truncated to when they are indexed.
+
+```
+use tantivy::schema::*;
+let mut schema_builder = Schema::builder();
+let date_options = DateOptions::from(FAST | INT_STORED)
+    .set_precision(DatePrecision::Milliseconds);
+schema_builder.add_date_field("timestamp", date_options);
+let schema = schema_builder.build();
+```
+
+## Setting a JSON object field
+
+JSON object fields hold objects whose shape does not need to be declared
+in the schema. Their leaves are indexed under their path, so that the
+object `{"color": "red"}` of the field `attrs` matches `attrs.color:red`.
+
+```
+use tantivy::schema::*;
+let mut schema_builder = Schema::builder();
+schema_builder.add_json_object_field("attrs", JsonObjectOptions::from(TEXT | STORED));
+let schema = schema_builder.build();
+```
+
 */
 
+mod bytes_options;
+mod date_options;
 mod document;
 mod facet;
+mod ip_addr_options;
+mod json_object_options;
 mod schema;
 mod term;
 
@@ -133,12 +166,18 @@ pub use self::field_type::{FieldType, Type};
 pub use self::field_value::FieldValue;
 
 pub use self::index_record_option::IndexRecordOption;
+pub use self::text_options::StoreCompressionHint;
 pub use self::text_options::TextFieldIndexing;
 pub use self::text_options::TextOptions;
 pub use self::text_options::STORED;
 pub use self::text_options::STRING;
 pub use self::text_options::TEXT;
 
+pub use self::bytes_options::BytesOptions;
+pub use self::date_options::{DateOptions, DatePrecision};
+pub use self::ip_addr_options::IpAddrOptions;
+pub use self::json_object_options::JsonObjectOptions;
+
 pub use self::int_options::Cardinality;
 pub use self::int_options::IntOptions;
 pub use self::int_options::FAST;
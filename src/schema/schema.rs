@@ -74,6 +74,38 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new f64 field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name
+    /// will result in the shadowing of the first
+    /// by the second one.
+    /// The first field will get a field id
+    /// but only the second one will be indexed
+    pub fn add_f64_field(&mut self, field_name_str: &str, field_options: IntOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_f64(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new date field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name
+    /// will result in the shadowing of the first
+    /// by the second one.
+    /// The first field will get a field id
+    /// but only the second one will be indexed
+    pub fn add_date_field(&mut self, field_name_str: &str, field_options: DateOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_date(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
     /// Adds a new text field.
     /// Returns the associated field handle
     ///
@@ -90,15 +122,65 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new IP address field.
+    /// Returns the associated field handle
+    ///
+    /// IPv4 addresses are mapped into IPv6, so that
+    /// the field can hold both kinds of addresses.
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name
+    /// will result in the shadowing of the first
+    /// by the second one.
+    /// The first field will get a field id
+    /// but only the second one will be indexed
+    pub fn add_ip_addr_field(
+        &mut self,
+        field_name_str: &str,
+        field_options: IpAddrOptions,
+    ) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_ip_addr(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new JSON object field.
+    /// Returns the associated field handle
+    ///
+    /// The leaves of the objects are searched under their path,
+    /// e.g. `attrs.color:red` for the object `{"color": "red"}`
+    /// of the field `attrs`.
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name
+    /// will result in the shadowing of the first
+    /// by the second one.
+    /// The first field will get a field id
+    /// but only the second one will be indexed
+    pub fn add_json_object_field(
+        &mut self,
+        field_name_str: &str,
+        field_options: JsonObjectOptions,
+    ) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_json_object(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
     /// Adds a facet field to the schema.
     pub fn add_facet_field(&mut self, field_name: &str) -> Field {
         let field_entry = FieldEntry::new_facet(field_name.to_string());
         self.add_field(field_entry)
     }
 
-    /// Adds a fast bytes field to the schema
-    pub fn add_bytes_field(&mut self, field_name: &str) -> Field {
-        let field_entry = FieldEntry::new_bytes(field_name.to_string());
+    /// Adds a bytes field to the schema
+    ///
+    /// Bytes fields are not indexed, but they can be stored
+    /// and/or declared as fast fields through the `BytesOptions`.
+    pub fn add_bytes_field(&mut self, field_name: &str, field_options: BytesOptions) -> Field {
+        let field_entry = FieldEntry::new_bytes(field_name.to_string(), field_options);
         self.add_field(field_entry)
     }
 
@@ -178,6 +260,17 @@ impl Schema {
         SchemaBuilder::default()
     }
 
+    /// Creates a new `SchemaBuilder` containing the fields of the schema.
+    ///
+    /// The fields added to the builder are appended after the existing ones,
+    /// which keep their `Field` handles.
+    pub fn to_builder(&self) -> SchemaBuilder {
+        SchemaBuilder {
+            fields: self.0.fields.clone(),
+            fields_map: self.0.fields_map.clone(),
+        }
+    }
+
     /// Returns the field options associated with a given name.
     ///
     /// # Panics
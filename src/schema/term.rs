@@ -3,12 +3,29 @@ use std::fmt;
 use super::Field;
 use byteorder::{BigEndian, ByteOrder};
 use common;
-use schema::Facet;
+use schema::{Facet, Type};
+use std::net::{IpAddr, Ipv6Addr};
 use std::str;
+use DateTime;
 
 /// Size (in bytes) of the buffer of a int field.
 const INT_TERM_LEN: usize = 4 + 8;
 
+/// Byte ending the path of the terms of a JSON object field.
+/// It is followed by a byte encoding the type of the value.
+const JSON_END_OF_PATH: u8 = 0u8;
+
+// Code of the type of the values of a JSON object field.
+fn json_type_code(typ: Type) -> u8 {
+    match typ {
+        Type::Str => b's',
+        Type::U64 => b'u',
+        Type::I64 => b'i',
+        Type::F64 => b'f',
+        _ => panic!("{:?} values cannot be indexed in a JSON object field.", typ),
+    }
+}
+
 /// Term represents the value that the token can take.
 ///
 /// It actually wraps a `Vec<u8>`.
@@ -30,6 +47,83 @@ impl Term {
         Term::from_field_u64(field, val_u64)
     }
 
+    /// Builds a term given a field, and a f64-value
+    ///
+    /// The value is mapped to a `u64` with `common::f64_to_u64`,
+    /// which preserves the order of the values.
+    pub fn from_field_f64(field: Field, val: f64) -> Term {
+        let val_u64: u64 = common::f64_to_u64(val);
+        Term::from_field_u64(field, val_u64)
+    }
+
+    /// Builds a term given a field, and a date
+    ///
+    /// The date is encoded as the `i64` number of nanoseconds
+    /// since the UNIX epoch (see `date_to_i64`).
+    /// As dates are truncated to the precision of their field when
+    /// they are indexed, `val` should be truncated as well
+    /// (see `DatePrecision::truncate`).
+    pub fn from_field_date(field: Field, val: &DateTime) -> Term {
+        Term::from_field_i64(field, common::date_to_i64(val))
+    }
+
+    /// Builds a term given a field, and an IP address
+    ///
+    /// IPv4 addresses are mapped into IPv6 (see `ip_addr_to_ipv6`).
+    /// The address is encoded over 16 bytes in network order,
+    /// which preserves the natural order of the addresses.
+    pub fn from_field_ip_addr(field: Field, val: IpAddr) -> Term {
+        let mut term = Term::for_field(field);
+        term.set_ip_addr(val);
+        term
+    }
+
+    /// Builds the term of a text value of a JSON object field, given
+    /// the path of the value within the object, e.g. `"size.width"`.
+    ///
+    /// The term is made of the path, followed by a `0` byte, a byte
+    /// encoding the type of the value, and the value itself.
+    /// The text values are tokenized before they are indexed.
+    pub fn from_field_json_text(field: Field, json_path: &str, text: &str) -> Term {
+        let mut term = Term::for_json_path(field, json_path, Type::Str);
+        term.append_bytes(text.as_bytes());
+        term
+    }
+
+    /// Builds the term of a `u64` value of a JSON object field,
+    /// given the path of the value within the object.
+    pub fn from_field_json_u64(field: Field, json_path: &str, val: u64) -> Term {
+        let mut term = Term::for_json_path(field, json_path, Type::U64);
+        term.append_u64(val);
+        term
+    }
+
+    /// Builds the term of a `i64` value of a JSON object field,
+    /// given the path of the value within the object.
+    pub fn from_field_json_i64(field: Field, json_path: &str, val: i64) -> Term {
+        let mut term = Term::for_json_path(field, json_path, Type::I64);
+        term.append_u64(common::i64_to_u64(val));
+        term
+    }
+
+    /// Builds the term of a `f64` value of a JSON object field,
+    /// given the path of the value within the object.
+    pub fn from_field_json_f64(field: Field, json_path: &str, val: f64) -> Term {
+        let mut term = Term::for_json_path(field, json_path, Type::F64);
+        term.append_u64(common::f64_to_u64(val));
+        term
+    }
+
+    /// Creates the prefix of the terms of the values of type `typ`
+    /// of a JSON object field, at the path `json_path`.
+    pub(crate) fn for_json_path(field: Field, json_path: &str, typ: Type) -> Term {
+        let mut term = Term::for_field(field);
+        term.append_bytes(json_path.as_bytes());
+        term.0.push(JSON_END_OF_PATH);
+        term.0.push(json_type_code(typ));
+        term
+    }
+
     /// Creates a `Term` given a facet.
     pub fn from_facet(field: Field, facet: &Facet) -> Term {
         let bytes = facet.encoded_bytes();
@@ -104,11 +198,37 @@ impl Term {
         self.set_u64(common::i64_to_u64(val));
     }
 
+    /// Sets a `f64` value in the term.
+    pub fn set_f64(&mut self, val: f64) {
+        self.set_u64(common::f64_to_u64(val));
+    }
+
+    /// Sets an IP address value in the term.
+    pub fn set_ip_addr(&mut self, val: IpAddr) {
+        self.set_bytes(&common::ip_addr_to_ipv6(val).octets());
+    }
+
     fn set_bytes(&mut self, bytes: &[u8]) {
         self.0.resize(4, 0u8);
         self.0.extend(bytes);
     }
 
+    fn append_u64(&mut self, val: u64) {
+        let mut buffer = [0u8; 8];
+        BigEndian::write_u64(&mut buffer, val);
+        self.append_bytes(&buffer);
+    }
+
+    /// Appends bytes to the term, keeping its field and its current value.
+    pub(crate) fn append_bytes(&mut self, bytes: &[u8]) {
+        self.0.extend(bytes);
+    }
+
+    /// Truncates the term to its first `len` bytes, field included.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
     pub(crate) fn from_field_bytes(field: Field, bytes: &[u8]) -> Term {
         let mut term = Term::for_field(field);
         term.set_bytes(bytes);
@@ -153,6 +273,27 @@ where
         common::u64_to_i64(BigEndian::read_u64(&self.0.as_ref()[4..]))
     }
 
+    /// Returns the `f64` value stored in a term.
+    ///
+    /// # Panics
+    /// ... or returns an invalid value
+    /// if the term is not a `f64` field.
+    pub fn get_f64(&self) -> f64 {
+        common::u64_to_f64(BigEndian::read_u64(&self.0.as_ref()[4..]))
+    }
+
+    /// Returns the IP address stored in a term,
+    /// IPv4 addresses being mapped into IPv6.
+    ///
+    /// # Panics
+    /// ... or returns an invalid value
+    /// if the term is not an IP address field.
+    pub fn get_ip_addr(&self) -> Ipv6Addr {
+        let mut octets = [0u8; 16];
+        octets.copy_from_slice(&self.0.as_ref()[4..20]);
+        Ipv6Addr::from(octets)
+    }
+
     /// Returns the text associated with the term.
     ///
     /// # Panics
@@ -198,6 +339,7 @@ impl fmt::Debug for Term {
 mod tests {
 
     use schema::*;
+    use std::net::Ipv6Addr;
 
     #[test]
     pub fn test_term() {
@@ -226,4 +368,46 @@ mod tests {
             assert_eq!(term.as_slice()[11], (983u64 % 256u64) as u8);
         }
     }
+
+    #[test]
+    pub fn test_ip_addr_term() {
+        let field = Field(1);
+        let term = Term::from_field_ip_addr(field, "10.0.0.1".parse().unwrap());
+        assert_eq!(term.field(), field);
+        assert_eq!(term.as_slice().len(), 4 + 16);
+        assert_eq!(
+            &term.as_slice()[4..],
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 10, 0, 0, 1]
+        );
+        assert_eq!(
+            term.get_ip_addr(),
+            "::ffff:10.0.0.1".parse::<Ipv6Addr>().unwrap()
+        );
+        // the order of the terms is the order of the addresses.
+        let other_term = Term::from_field_ip_addr(field, "10.0.1.0".parse().unwrap());
+        assert!(term < other_term);
+    }
+
+    #[test]
+    pub fn test_json_terms() {
+        let field = Field(1);
+        let term = Term::from_field_json_text(field, "size.unit", "cm");
+        assert_eq!(term.field(), field);
+        assert_eq!(term.value_bytes(), b"size.unit\x00scm");
+        let term = Term::from_field_json_i64(field, "size.width", 30i64);
+        assert_eq!(&term.value_bytes()[..12], b"size.width\x00i");
+        assert_eq!(
+            term.value_bytes()[12..],
+            Term::from_field_i64(field, 30i64).value_bytes()[..]
+        );
+        // the values of a path and a type are ordered.
+        assert!(
+            Term::from_field_json_f64(field, "size.width", -1.5f64)
+                < Term::from_field_json_f64(field, "size.width", 0.5f64)
+        );
+        assert_ne!(
+            Term::from_field_json_u64(field, "size.width", 30u64),
+            Term::from_field_json_i64(field, "size.width", 30i64)
+        );
+    }
 }
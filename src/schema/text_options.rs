@@ -1,12 +1,53 @@
 use schema::IndexRecordOption;
+use serde::de::{self, Deserialize, Deserializer};
 use std::borrow::Cow;
 use std::ops::BitOr;
 
+/// Hint on how the stored values of a field should be
+/// compressed in the doc store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StoreCompressionHint {
+    /// The values are compressed together with the
+    /// other stored fields of the document.
+    #[serde(rename = "default")]
+    Default,
+    /// The values are not compressed at all.
+    ///
+    /// This is useful for values that are already compressed,
+    /// and would only waste CPU both when indexing and when
+    /// fetching documents.
+    #[serde(rename = "uncompressed")]
+    Uncompressed,
+    /// The values are compressed separately, spending more
+    /// CPU at indexing time to get a better compression ratio.
+    ///
+    /// Decompression is not slower. This is useful for large,
+    /// very redundant texts.
+    #[serde(rename = "aggressive")]
+    Aggressive,
+}
+
+impl StoreCompressionHint {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == StoreCompressionHint::Default
+    }
+}
+
+impl Default for StoreCompressionHint {
+    fn default() -> StoreCompressionHint {
+        StoreCompressionHint::Default
+    }
+}
+
 /// Define how a text field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TextOptions {
     indexing: Option<TextFieldIndexing>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "StoreCompressionHint::is_default")]
+    store_compression_hint: StoreCompressionHint,
+    #[serde(default, skip_serializing_if = "is_false")]
+    fast: bool,
 }
 
 impl TextOptions {
@@ -26,11 +67,48 @@ impl TextOptions {
         self
     }
 
+    /// Returns the compression hint for the stored values of this field.
+    pub fn store_compression_hint(&self) -> StoreCompressionHint {
+        self.store_compression_hint
+    }
+
+    /// Sets how the stored values of this field should be compressed.
+    ///
+    /// This is only relevant if the field is also stored.
+    pub fn set_store_compression_hint(
+        mut self,
+        store_compression_hint: StoreCompressionHint,
+    ) -> TextOptions {
+        self.store_compression_hint = store_compression_hint;
+        self
+    }
+
     /// Sets the field as indexed, with the specific indexing options.
     pub fn set_indexing_options(mut self, indexing: TextFieldIndexing) -> TextOptions {
         self.indexing = Some(indexing);
         self
     }
+
+    /// Returns true iff the field is indexed and set as a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast && self.indexing.is_some()
+    }
+
+    /// Sets the field as a fast field.
+    ///
+    /// The fast field holds, for each document, the ordinals of its terms
+    /// in the term dictionary of the field, which maps them back to the
+    /// terms. It makes it possible to sort or group the documents by the
+    /// value of the field without fetching their stored fields
+    /// (see `SegmentReader::str_fast_field_reader`).
+    ///
+    /// An ordinal is recorded for each token, so that the fast field is
+    /// mostly useful for the untokenized (`STRING`) fields.
+    /// It is ignored if the field is not indexed.
+    pub fn set_fast(mut self) -> TextOptions {
+        self.fast = true;
+        self
+    }
 }
 
 impl Default for TextOptions {
@@ -38,6 +116,8 @@ impl Default for TextOptions {
         TextOptions {
             indexing: None,
             stored: false,
+            store_compression_hint: StoreCompressionHint::Default,
+            fast: false,
         }
     }
 }
@@ -48,10 +128,56 @@ impl Default for TextOptions {
 /// - the amount of information that should be stored about the presence of a term in a document.
 /// Essentially, should we store the term frequency and/or the positions (See [`IndexRecordOption`](./enum.IndexRecordOption.html)).
 /// - the name of the `Tokenizer` that should be used to process the field.
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// - whether the offsets of the tokens should be recorded, for highlighting.
+/// - the boost applied to the scores of the field.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     record: IndexRecordOption,
     tokenizer: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    offsets: bool,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_boost"
+    )]
+    boost: Option<f32>,
+}
+
+// The boosts are compared bitwise, so that the equality
+// remains an equivalence relation.
+impl PartialEq for TextFieldIndexing {
+    fn eq(&self, other: &TextFieldIndexing) -> bool {
+        self.record == other.record
+            && self.tokenizer == other.tokenizer
+            && self.offsets == other.offsets
+            && self.boost.map(f32::to_bits) == other.boost.map(f32::to_bits)
+    }
+}
+
+impl Eq for TextFieldIndexing {}
+
+fn is_false(val: &bool) -> bool {
+    !*val
+}
+
+fn is_valid_boost(boost: f32) -> bool {
+    boost.is_finite() && boost >= 0f32
+}
+
+// Rejects the boosts that `TextFieldIndexing::set_boost` would not accept.
+fn deserialize_boost<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let boost: Option<f32> = Option::deserialize(deserializer)?;
+    match boost {
+        Some(boost) if !is_valid_boost(boost) => Err(de::Error::custom(format!(
+            "The boost of a field must be a finite, non-negative number, got {}",
+            boost
+        ))),
+        _ => Ok(boost),
+    }
 }
 
 impl Default for TextFieldIndexing {
@@ -59,6 +185,8 @@ impl Default for TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: Cow::Borrowed("default"),
             record: IndexRecordOption::Basic,
+            offsets: false,
+            boost: None,
         }
     }
 }
@@ -89,6 +217,46 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets whether the byte offsets of the tokens within the text
+    /// should be indexed.
+    ///
+    /// Offsets are only recorded if the positions are indexed as well.
+    /// They make it possible to highlight the terms of a document
+    /// without tokenizing its stored text again.
+    /// (See [`SnippetGenerator::snippet_from_doc_address`](../struct.SnippetGenerator.html#method.snippet_from_doc_address))
+    pub fn set_offsets(mut self, offsets: bool) -> TextFieldIndexing {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Returns true iff the offsets of the tokens are indexed.
+    pub fn has_offsets(&self) -> bool {
+        self.offsets && self.record.has_positions()
+    }
+
+    /// Sets the boost of the field, by which the BM25 scores of
+    /// its terms are multiplied. Defaults to 1.
+    ///
+    /// Boosting a short field such as a title makes its
+    /// matches rank above the ones of a long body.
+    ///
+    /// # Panics
+    /// Panics if the boost is negative, infinite or NaN.
+    pub fn set_boost(mut self, boost: f32) -> TextFieldIndexing {
+        assert!(
+            is_valid_boost(boost),
+            "The boost of a field must be a finite, non-negative number, got {}",
+            boost
+        );
+        self.boost = Some(boost);
+        self
+    }
+
+    /// Returns the boost of the field.
+    pub fn boost(&self) -> f32 {
+        self.boost.unwrap_or(1f32)
+    }
 }
 
 /// The field will be untokenized and indexed
@@ -96,8 +264,12 @@ pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("raw"),
         record: IndexRecordOption::Basic,
+        offsets: false,
+        boost: None,
     }),
     stored: false,
+    store_compression_hint: StoreCompressionHint::Default,
+    fast: false,
 };
 
 /// The field will be tokenized and indexed
@@ -105,8 +277,12 @@ pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("default"),
         record: IndexRecordOption::WithFreqsAndPositions,
+        offsets: false,
+        boost: None,
     }),
     stored: false,
+    store_compression_hint: StoreCompressionHint::Default,
+    fast: false,
 };
 
 /// A stored fields of a document can be retrieved given its `DocId`.
@@ -116,6 +292,8 @@ pub const TEXT: TextOptions = TextOptions {
 pub const STORED: TextOptions = TextOptions {
     indexing: None,
     stored: true,
+    store_compression_hint: StoreCompressionHint::Default,
+    fast: false,
 };
 
 impl BitOr for TextOptions {
@@ -125,6 +303,12 @@ impl BitOr for TextOptions {
         let mut res = TextOptions::default();
         res.indexing = self.indexing.or(other.indexing);
         res.stored = self.stored | other.stored;
+        res.store_compression_hint = if self.store_compression_hint.is_default() {
+            other.store_compression_hint
+        } else {
+            self.store_compression_hint
+        };
+        res.fast = self.fast | other.fast;
         res
     }
 }
@@ -132,6 +316,7 @@ impl BitOr for TextOptions {
 #[cfg(test)]
 mod tests {
     use schema::*;
+    use serde_json;
 
     #[test]
     fn test_field_options() {
@@ -161,6 +346,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_store_compression_hint() {
+        let field_options =
+            STORED.set_store_compression_hint(StoreCompressionHint::Uncompressed) | TEXT;
+        assert_eq!(
+            field_options.store_compression_hint(),
+            StoreCompressionHint::Uncompressed
+        );
+        let json = serde_json::to_string(&field_options).unwrap();
+        assert!(json.contains("\"store_compression_hint\":\"uncompressed\""));
+        let deser: TextOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser, field_options);
+        let default_json = serde_json::to_string(&TEXT).unwrap();
+        assert!(!default_json.contains("store_compression_hint"));
+    }
+
+    #[test]
+    fn test_fast_option() {
+        let field_options = STRING.set_fast() | STORED;
+        assert!(field_options.is_fast());
+        let json = serde_json::to_string(&field_options).unwrap();
+        assert!(json.contains("\"fast\":true"));
+        let deser: TextOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser, field_options);
+        assert!(!STORED.set_fast().is_fast());
+        let default_json = serde_json::to_string(&TEXT).unwrap();
+        assert!(!default_json.contains("fast"));
+    }
+
+    #[test]
+    fn test_offsets_option() {
+        let indexing = TextFieldIndexing::default().set_offsets(true);
+        assert!(!indexing.has_offsets());
+        let indexing = indexing.set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        assert!(indexing.has_offsets());
+        let json = serde_json::to_string(&indexing).unwrap();
+        assert!(json.contains("\"offsets\":true"));
+        let deser: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser, indexing);
+        let default_json = serde_json::to_string(&TEXT).unwrap();
+        assert!(!default_json.contains("offsets"));
+    }
+
+    #[test]
+    fn test_boost_option() {
+        let indexing = TextFieldIndexing::default();
+        assert_eq!(indexing.boost(), 1f32);
+        let indexing = indexing.set_boost(2.5f32);
+        assert_eq!(indexing.boost(), 2.5f32);
+        let json = serde_json::to_string(&indexing).unwrap();
+        assert!(json.contains("\"boost\":2.5"));
+        let deser: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser, indexing);
+        let default_json = serde_json::to_string(&TEXT).unwrap();
+        assert!(!default_json.contains("boost"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_boost_option_negative() {
+        TextFieldIndexing::default().set_boost(-1f32);
+    }
+
+    #[test]
+    fn test_boost_option_invalid_json() {
+        let json = r#"{"record":"basic","tokenizer":"default","boost":-1.0}"#;
+        assert!(serde_json::from_str::<TextFieldIndexing>(json).is_err());
+        let json = r#"{"record":"basic","tokenizer":"default","boost":null}"#;
+        let deser: TextFieldIndexing = serde_json::from_str(json).unwrap();
+        assert_eq!(deser.boost(), 1f32);
+    }
+
+    #[test]
+    fn test_boost_option_eq() {
+        let indexing = TextFieldIndexing::default();
+        assert_eq!(
+            indexing.clone().set_boost(2f32),
+            indexing.clone().set_boost(2f32)
+        );
+        assert_ne!(
+            indexing.clone().set_boost(2f32),
+            indexing.clone().set_boost(3f32)
+        );
+    }
+
     #[test]
     fn test_cmp_index_record_option() {
         assert!(IndexRecordOption::WithFreqsAndPositions > IndexRecordOption::WithFreqs);
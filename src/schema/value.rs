@@ -1,11 +1,25 @@
+use base64::encode;
+use common::{f64_to_u64, ip_addr_to_ipv6};
 use schema::Facet;
-use serde::de::Visitor;
+use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{self, Map as JsonObject, Value as JsonValue};
+use std::cmp::Ordering;
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tokenizer::PreTokenizedString;
+use DateTime;
 
 /// Value represents the value of a any field.
 /// It is an enum over all over all of the possible field type.
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+///
+/// The `f64` values are compared through their mapping
+/// to `u64` (see [`f64_to_u64`](../fn.f64_to_u64.html)), so that
+/// values are totally ordered: `-0.0` is lower than `0.0`, and
+/// a `NaN` value is equal to itself.
+/// The JSON objects are compared through their serialization,
+/// and the pre-tokenized texts through their text.
+#[derive(Debug, Clone)]
 pub enum Value {
     /// The str type is used for any text information.
     Str(String),
@@ -13,12 +27,78 @@ pub enum Value {
     U64(u64),
     /// Signed 64-bits Integer `i64`
     I64(i64),
+    /// 64-bits Float `f64`
+    F64(f64),
+    /// UTC date and time
+    Date(DateTime),
     /// Hierarchical Facet
     Facet(Facet),
     /// Arbitrarily sized byte array
     Bytes(Vec<u8>),
+    /// IP address, IPv4 addresses being mapped into IPv6
+    IpAddr(Ipv6Addr),
+    /// JSON object
+    JsonObject(JsonObject<String, JsonValue>),
+    /// Pre-tokenized text, indexed without going through the tokenizer
+    PreTokStr(PreTokenizedString),
 }
 
+impl Value {
+    // Rank of the variant, ordering the values of different types.
+    fn type_ord(&self) -> u8 {
+        match *self {
+            Value::Str(_) => 0,
+            Value::U64(_) => 1,
+            Value::I64(_) => 2,
+            Value::F64(_) => 3,
+            Value::Date(_) => 4,
+            Value::Facet(_) => 5,
+            Value::Bytes(_) => 6,
+            Value::IpAddr(_) => 7,
+            Value::JsonObject(_) => 8,
+            Value::PreTokStr(_) => 9,
+        }
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (&Value::Str(ref left), &Value::Str(ref right)) => left.cmp(right),
+            (&Value::U64(left), &Value::U64(right)) => left.cmp(&right),
+            (&Value::I64(left), &Value::I64(right)) => left.cmp(&right),
+            (&Value::F64(left), &Value::F64(right)) => f64_to_u64(left).cmp(&f64_to_u64(right)),
+            (&Value::Date(ref left), &Value::Date(ref right)) => left.cmp(right),
+            (&Value::Facet(ref left), &Value::Facet(ref right)) => left.cmp(right),
+            (&Value::Bytes(ref left), &Value::Bytes(ref right)) => left.cmp(right),
+            (&Value::IpAddr(ref left), &Value::IpAddr(ref right)) => left.cmp(right),
+            (&Value::JsonObject(ref left), &Value::JsonObject(ref right)) => {
+                serde_json::to_string(left)
+                    .ok()
+                    .cmp(&serde_json::to_string(right).ok())
+            }
+            (&Value::PreTokStr(ref left), &Value::PreTokStr(ref right)) => {
+                left.text.cmp(&right.text)
+            }
+            _ => self.type_ord().cmp(&other.type_ord()),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -28,8 +108,19 @@ impl Serialize for Value {
             Value::Str(ref v) => serializer.serialize_str(v),
             Value::U64(u) => serializer.serialize_u64(u),
             Value::I64(u) => serializer.serialize_i64(u),
+            Value::F64(u) => serializer.serialize_f64(u),
+            Value::Date(ref date) => serializer.serialize_str(&date.to_rfc3339()),
             Value::Facet(ref facet) => facet.serialize(serializer),
-            Value::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
+            Value::Bytes(ref bytes) => serializer.serialize_str(&encode(bytes)),
+            Value::IpAddr(ref ip_addr) => match ip_addr.to_ipv4() {
+                // the IPv4 addresses are displayed as such.
+                Some(ipv4_addr) if ipv4_addr.to_ipv6_mapped() == *ip_addr => {
+                    serializer.serialize_str(&ipv4_addr.to_string())
+                }
+                _ => serializer.serialize_str(&ip_addr.to_string()),
+            },
+            Value::JsonObject(ref json_object) => json_object.serialize(serializer),
+            Value::PreTokStr(ref tokenized_text) => tokenized_text.serialize(serializer),
         }
     }
 }
@@ -56,6 +147,10 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::I64(v))
             }
 
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(Value::F64(v))
+            }
+
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
                 Ok(Value::Str(v.to_owned()))
             }
@@ -63,6 +158,17 @@ impl<'de> Deserialize<'de> for Value {
             fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
                 Ok(Value::Str(v))
             }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut json_object = JsonObject::new();
+                while let Some((key, value)) = map.next_entry::<String, JsonValue>()? {
+                    json_object.insert(key, value);
+                }
+                Ok(Value::JsonObject(json_object))
+            }
         }
 
         deserializer.deserialize_any(ValueVisitor)
@@ -102,6 +208,58 @@ impl Value {
             _ => panic!("This is not a text field."),
         }
     }
+
+    /// Returns the f64-value, provided the value is of the `F64` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `F64`
+    pub fn f64_value(&self) -> f64 {
+        match *self {
+            Value::F64(ref value) => *value,
+            _ => panic!("This is not a f64 field."),
+        }
+    }
+
+    /// Returns the date, provided the value is of the `Date` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `Date`
+    pub fn date_value(&self) -> &DateTime {
+        match *self {
+            Value::Date(ref value) => value,
+            _ => panic!("This is not a date field."),
+        }
+    }
+
+    /// Returns the IP address, provided the value is of the `IpAddr` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `IpAddr`
+    pub fn ip_addr_value(&self) -> Ipv6Addr {
+        match *self {
+            Value::IpAddr(value) => value,
+            _ => panic!("This is not an IP address field."),
+        }
+    }
+
+    /// Returns the JSON object, provided the value is of the `JsonObject` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `JsonObject`
+    pub fn json_object_value(&self) -> &JsonObject<String, JsonValue> {
+        match *self {
+            Value::JsonObject(ref value) => value,
+            _ => panic!("This is not a JSON object field."),
+        }
+    }
+
+    /// Returns the pre-tokenized text, provided the value is of the `PreTokStr` type.
+    pub fn tokenized_text(&self) -> Option<&PreTokenizedString> {
+        match *self {
+            Value::PreTokStr(ref tokenized_text) => Some(tokenized_text),
+            _ => None,
+        }
+    }
 }
 
 impl From<String> for Value {
@@ -122,6 +280,18 @@ impl From<i64> for Value {
     }
 }
 
+impl From<f64> for Value {
+    fn from(v: f64) -> Value {
+        Value::F64(v)
+    }
+}
+
+impl From<DateTime> for Value {
+    fn from(date: DateTime) -> Value {
+        Value::Date(date)
+    }
+}
+
 impl<'a> From<&'a str> for Value {
     fn from(s: &'a str) -> Value {
         Value::Str(s.to_string())
@@ -140,17 +310,55 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<IpAddr> for Value {
+    fn from(ip_addr: IpAddr) -> Value {
+        Value::IpAddr(ip_addr_to_ipv6(ip_addr))
+    }
+}
+
+impl From<Ipv4Addr> for Value {
+    fn from(ipv4_addr: Ipv4Addr) -> Value {
+        Value::IpAddr(ipv4_addr.to_ipv6_mapped())
+    }
+}
+
+impl From<Ipv6Addr> for Value {
+    fn from(ipv6_addr: Ipv6Addr) -> Value {
+        Value::IpAddr(ipv6_addr)
+    }
+}
+
+impl From<JsonObject<String, JsonValue>> for Value {
+    fn from(json_object: JsonObject<String, JsonValue>) -> Value {
+        Value::JsonObject(json_object)
+    }
+}
+
+impl From<PreTokenizedString> for Value {
+    fn from(tokenized_text: PreTokenizedString) -> Value {
+        Value::PreTokStr(tokenized_text)
+    }
+}
+
 mod binary_serialize {
     use super::Value;
+    use chrono::{TimeZone, Utc};
     use common::BinarySerializable;
     use schema::Facet;
+    use serde_json;
     use std::io::{self, Read, Write};
+    use std::net::Ipv6Addr;
 
     const TEXT_CODE: u8 = 0;
     const U64_CODE: u8 = 1;
     const I64_CODE: u8 = 2;
     const HIERARCHICAL_FACET_CODE: u8 = 3;
     const BYTES_CODE: u8 = 4;
+    const F64_CODE: u8 = 5;
+    const DATE_CODE: u8 = 6;
+    const IP_ADDR_CODE: u8 = 7;
+    const JSON_OBJECT_CODE: u8 = 8;
+    const PRE_TOKENIZED_TEXT_CODE: u8 = 9;
 
     impl BinarySerializable for Value {
         fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
@@ -167,6 +375,15 @@ mod binary_serialize {
                     I64_CODE.serialize(writer)?;
                     val.serialize(writer)
                 }
+                Value::F64(ref val) => {
+                    F64_CODE.serialize(writer)?;
+                    val.to_bits().serialize(writer)
+                }
+                Value::Date(ref date) => {
+                    DATE_CODE.serialize(writer)?;
+                    date.timestamp().serialize(writer)?;
+                    date.timestamp_subsec_nanos().serialize(writer)
+                }
                 Value::Facet(ref facet) => {
                     HIERARCHICAL_FACET_CODE.serialize(writer)?;
                     facet.serialize(writer)
@@ -175,6 +392,18 @@ mod binary_serialize {
                     BYTES_CODE.serialize(writer)?;
                     bytes.serialize(writer)
                 }
+                Value::IpAddr(ref ip_addr) => {
+                    IP_ADDR_CODE.serialize(writer)?;
+                    writer.write_all(&ip_addr.octets())
+                }
+                Value::JsonObject(ref json_object) => {
+                    JSON_OBJECT_CODE.serialize(writer)?;
+                    serde_json::to_string(json_object)?.serialize(writer)
+                }
+                Value::PreTokStr(ref tokenized_text) => {
+                    PRE_TOKENIZED_TEXT_CODE.serialize(writer)?;
+                    serde_json::to_string(tokenized_text)?.serialize(writer)
+                }
             }
         }
         fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
@@ -192,8 +421,38 @@ mod binary_serialize {
                     let value = i64::deserialize(reader)?;
                     Ok(Value::I64(value))
                 }
+                F64_CODE => {
+                    let value = f64::from_bits(u64::deserialize(reader)?);
+                    Ok(Value::F64(value))
+                }
+                DATE_CODE => {
+                    let secs = i64::deserialize(reader)?;
+                    let nanos = u32::deserialize(reader)?;
+                    Utc.timestamp_opt(secs, nanos)
+                        .single()
+                        .map(Value::Date)
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("Invalid date {:?}s {:?}ns", secs, nanos),
+                            )
+                        })
+                }
                 HIERARCHICAL_FACET_CODE => Ok(Value::Facet(Facet::deserialize(reader)?)),
                 BYTES_CODE => Ok(Value::Bytes(Vec::<u8>::deserialize(reader)?)),
+                IP_ADDR_CODE => {
+                    let mut octets = [0u8; 16];
+                    reader.read_exact(&mut octets)?;
+                    Ok(Value::IpAddr(Ipv6Addr::from(octets)))
+                }
+                JSON_OBJECT_CODE => {
+                    let json = String::deserialize(reader)?;
+                    Ok(Value::JsonObject(serde_json::from_str(&json)?))
+                }
+                PRE_TOKENIZED_TEXT_CODE => {
+                    let json = String::deserialize(reader)?;
+                    Ok(Value::PreTokStr(serde_json::from_str(&json)?))
+                }
                 _ => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("No field type is associated with code {:?}", type_code),
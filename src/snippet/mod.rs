@@ -1,14 +1,22 @@
 use htmlescape::encode_minimal;
+use offsets::TokenOffsets;
+use postings::Postings;
 use query::Query;
-use schema::Field;
+use schema::{Field, IndexRecordOption, Term};
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::HashMap;
 use tokenizer::BoxedTokenizer;
 use tokenizer::{Token, TokenStream};
+use DocAddress;
+use DocId;
+use DocSet;
 use Document;
 use Result;
 use Searcher;
+use SegmentReader;
+use SkipResult;
 
 const DEFAULT_MAX_NUM_CHARS: usize = 150;
 
@@ -60,13 +68,58 @@ impl FragmentCandidate {
     /// if the token is one of the terms, the score
     /// and highlighted fields are updated in the fragment.
     fn try_add_token(&mut self, token: &Token, terms: &BTreeMap<String, f32>) {
-        self.stop_offset = token.offset_to;
+        let score_opt = terms.get(&token.text.to_lowercase()).cloned();
+        self.add_token(token.offset_from, token.offset_to, score_opt);
+    }
+
+    /// Adds the token spanning `offset_from..offset_to` to the fragment,
+    /// and highlights it if it has a score.
+    fn add_token(&mut self, offset_from: usize, offset_to: usize, score_opt: Option<f32>) {
+        self.stop_offset = offset_to;
 
-        if let Some(score) = terms.get(&token.text.to_lowercase()) {
+        if let Some(score) = score_opt {
             self.score += score;
             self.highlighted
-                .push(HighlightSection::new(token.offset_from, token.offset_to));
+                .push(HighlightSection::new(offset_from, offset_to));
+        }
+    }
+}
+
+/// Groups the consecutive tokens of a text into fragments
+/// of at most `max_num_chars`.
+struct FragmentSearch {
+    max_num_chars: usize,
+    fragment: FragmentCandidate,
+    fragments: Vec<FragmentCandidate>,
+}
+
+impl FragmentSearch {
+    fn new(max_num_chars: usize) -> FragmentSearch {
+        FragmentSearch {
+            max_num_chars,
+            fragment: FragmentCandidate::new(0),
+            fragments: vec![],
+        }
+    }
+
+    /// Starts a new fragment if the token does not fit in the current one.
+    fn next_token(&mut self, offset_from: usize, offset_to: usize) -> &mut FragmentCandidate {
+        if (offset_to - self.fragment.start_offset) > self.max_num_chars {
+            let fragment =
+                ::std::mem::replace(&mut self.fragment, FragmentCandidate::new(offset_from));
+            if fragment.score > 0.0 {
+                self.fragments.push(fragment);
+            }
+        }
+        &mut self.fragment
+    }
+
+    /// Returns the fragments containing at least one highlighted token.
+    fn fragments(mut self) -> Vec<FragmentCandidate> {
+        if self.fragment.score > 0.0 {
+            self.fragments.push(self.fragment)
         }
+        self.fragments
     }
 }
 
@@ -147,23 +200,33 @@ fn search_fragments<'a>(
     max_num_chars: usize,
 ) -> Vec<FragmentCandidate> {
     let mut token_stream = tokenizer.token_stream(text);
-    let mut fragment = FragmentCandidate::new(0);
-    let mut fragments: Vec<FragmentCandidate> = vec![];
-
+    let mut fragment_search = FragmentSearch::new(max_num_chars);
     while let Some(next) = token_stream.next() {
-        if (next.offset_to - fragment.start_offset) > max_num_chars {
-            if fragment.score > 0.0 {
-                fragments.push(fragment)
-            };
-            fragment = FragmentCandidate::new(next.offset_from);
-        }
-        fragment.try_add_token(next, &terms);
-    }
-    if fragment.score > 0.0 {
-        fragments.push(fragment)
+        fragment_search
+            .next_token(next.offset_from, next.offset_to)
+            .try_add_token(next, &terms);
     }
+    fragment_search.fragments()
+}
 
-    fragments
+/// Returns the "good" fragments, as `search_fragments`, given the offsets
+/// of the tokens of the text and the scores of the positions to highlight.
+fn search_fragments_from_offsets(
+    token_offsets: &[TokenOffsets],
+    position_scores: &HashMap<u32, f32>,
+    max_num_chars: usize,
+) -> Vec<FragmentCandidate> {
+    let mut fragment_search = FragmentSearch::new(max_num_chars);
+    for token in token_offsets {
+        fragment_search
+            .next_token(token.offset_from, token.offset_to)
+            .add_token(
+                token.offset_from,
+                token.offset_to,
+                position_scores.get(&token.position).cloned(),
+            );
+    }
+    fragment_search.fragments()
 }
 
 /// Returns a Snippet
@@ -236,7 +299,7 @@ fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str)
 /// #  Quand avec mes haleurs ont fini ces tapages,
 /// #  Les Fleuves m'ont laissé descendre où je voulais.
 /// #  "#);
-/// #    index_writer.add_document(doc.clone());
+/// #    index_writer.add_document(doc.clone())?;
 /// #    index_writer.commit()?;
 /// #    let query_parser = QueryParser::for_index(&index, vec![text_field]);
 /// // ...
@@ -252,6 +315,7 @@ fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str)
 /// # }
 /// ```
 pub struct SnippetGenerator {
+    terms: BTreeMap<Term, f32>,
     terms_text: BTreeMap<String, f32>,
     tokenizer: Box<BoxedTokenizer>,
     field: Field,
@@ -263,21 +327,26 @@ impl SnippetGenerator {
     pub fn create(searcher: &Searcher, query: &Query, field: Field) -> Result<SnippetGenerator> {
         let mut terms = BTreeSet::new();
         query.query_terms(&mut terms);
-        let terms_text: BTreeMap<String, f32> = terms
+        let terms: BTreeMap<Term, f32> = terms
             .into_iter()
             .filter(|term| term.field() == field)
             .flat_map(|term| {
                 let doc_freq = searcher.doc_freq(&term);
                 let score = 1f32 / (1f32 + doc_freq as f32);
                 if doc_freq > 0 {
-                    Some((term.text().to_string(), score))
+                    Some((term, score))
                 } else {
                     None
                 }
             })
             .collect();
+        let terms_text: BTreeMap<String, f32> = terms
+            .iter()
+            .map(|(term, &score)| (term.text().to_string(), score))
+            .collect();
         let tokenizer = searcher.index().tokenizer_for_field(field)?;
         Ok(SnippetGenerator {
+            terms,
             terms_text,
             tokenizer,
             field,
@@ -300,13 +369,80 @@ impl SnippetGenerator {
     /// This method extract the text associated to the `SnippetGenerator`'s field
     /// and computes a snippet.
     pub fn snippet_from_doc(&self, doc: &Document) -> Snippet {
-        let text: String = doc
-            .get_all(self.field)
+        self.snippet(&self.field_text(doc))
+    }
+
+    /// Generates a snippet for the document at the given `DocAddress`.
+    ///
+    /// If the field was indexed with offsets, the highlighted terms are
+    /// located using the positions of their postings and the offsets of
+    /// the tokens of the document. The stored text is not tokenized again,
+    /// so that the highlights match what was indexed even if the tokenizer
+    /// of the field has changed since.
+    ///
+    /// Otherwise, the stored text is tokenized as in `snippet_from_doc`.
+    pub fn snippet_from_doc_address(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+    ) -> Result<Snippet> {
+        let text = self.field_text(&searcher.doc(doc_address)?);
+        let DocAddress(segment_ord, doc) = doc_address;
+        let segment_reader = searcher.segment_reader(segment_ord);
+        if let Some(offsets_reader) = segment_reader.offsets_reader(self.field) {
+            let token_offsets = offsets_reader.token_offsets(doc);
+            // the offsets are checked against the text, in case the stored value
+            // does not match the indexed one.
+            let offsets_match_text = token_offsets.iter().all(|token| {
+                token.offset_from <= token.offset_to
+                    && token.offset_to <= text.len()
+                    && text.is_char_boundary(token.offset_from)
+                    && text.is_char_boundary(token.offset_to)
+            });
+            if !token_offsets.is_empty() && offsets_match_text {
+                let position_scores = self.position_scores(segment_reader, doc);
+                let fragment_candidates = search_fragments_from_offsets(
+                    &token_offsets[..],
+                    &position_scores,
+                    self.max_num_chars,
+                );
+                return Ok(select_best_fragment_combination(
+                    &fragment_candidates[..],
+                    &text,
+                ));
+            }
+        }
+        Ok(self.snippet(&text))
+    }
+
+    /// Returns the score of the terms of the snippet generator,
+    /// for each of their positions in `doc`.
+    fn position_scores(&self, segment_reader: &SegmentReader, doc: DocId) -> HashMap<u32, f32> {
+        let inverted_index = segment_reader.inverted_index(self.field);
+        let mut position_scores = HashMap::new();
+        let mut positions = Vec::new();
+        for (term, &score) in &self.terms {
+            if let Some(mut postings) = inverted_index
+                .read_postings_no_deletes(term, IndexRecordOption::WithFreqsAndPositions)
+            {
+                if postings.skip_next(doc) == SkipResult::Reached {
+                    postings.positions(&mut positions);
+                    for &position in &positions {
+                        position_scores.insert(position, score);
+                    }
+                }
+            }
+        }
+        position_scores
+    }
+
+    /// Returns the values of the field in `doc`, separated by a space.
+    fn field_text(&self, doc: &Document) -> String {
+        doc.get_all(self.field)
             .into_iter()
             .flat_map(|val| val.text())
             .collect::<Vec<&str>>()
-            .join(" ");
-        self.snippet(&text)
+            .join(" ")
     }
 
     /// Generates a snippet for the given text.
@@ -324,11 +460,13 @@ impl SnippetGenerator {
 #[cfg(test)]
 mod tests {
     use super::{search_fragments, select_best_fragment_combination};
+    use collector::TopDocs;
+    use futures::Future;
     use query::QueryParser;
     use schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
     use std::collections::BTreeMap;
     use std::iter::Iterator;
-    use tokenizer::{box_tokenizer, SimpleTokenizer};
+    use tokenizer::{box_tokenizer, LowerCaser, RawTokenizer, SimpleTokenizer, Tokenizer};
     use Index;
     use SnippetGenerator;
 
@@ -524,9 +662,11 @@ Survey in 2016, 2017, and 2018."#;
         {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field => "a"));
-            index_writer.add_document(doc!(text_field => "a"));
-            index_writer.add_document(doc!(text_field => "a b"));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
             index_writer.commit().unwrap();
             index.load_searchers().unwrap();
         }
@@ -583,7 +723,7 @@ Survey in 2016, 2017, and 2018."#;
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc ! (text_field => TEST_TEXT);
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -603,4 +743,79 @@ Survey in 2016, 2017, and 2018."#;
             assert_eq!(snippet.to_html(), "<b>Rust</b> is syntactically similar to C++[according to whom?],\nbut its <b>designers</b> intend it to");
         }
     }
+
+    #[test]
+    fn test_snippet_from_offsets() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("custom")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                    .set_offsets(true),
+            )
+            .set_stored();
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index
+            .tokenizers()
+            .register("custom", SimpleTokenizer.filter(LowerCaser));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "Rust is a language",
+                    text_field => "Its designers like Rust"
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "Nothing relevant here"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "The rust belt"))
+                .unwrap();
+            index_writer.commit().unwrap();
+            let segment_ids = index
+                .searchable_segment_ids()
+                .expect("Searchable segments failed.");
+            index_writer
+                .merge(&segment_ids)
+                .expect("Failed to initiate merge")
+                .wait()
+                .expect("Merging failed");
+            index_writer.wait_merging_threads().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust").unwrap();
+        let top_docs = searcher.search(&*query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(top_docs.len(), 2);
+
+        // the tokenizer of the field is changed after indexing.
+        index.tokenizers().register("custom", RawTokenizer);
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field).unwrap();
+        let mut snippets: Vec<String> = top_docs
+            .iter()
+            .map(|&(_, doc_address)| {
+                snippet_generator
+                    .snippet_from_doc_address(&searcher, doc_address)
+                    .unwrap()
+                    .to_html()
+            })
+            .collect();
+        snippets.sort();
+        assert_eq!(
+            snippets,
+            vec![
+                "<b>Rust</b> is a language Its designers like <b>Rust</b>".to_string(),
+                "The <b>rust</b> belt".to_string(),
+            ]
+        );
+        // tokenizing the stored text again does not find the term anymore.
+        let doc = searcher.doc(top_docs[0].1).unwrap();
+        assert_eq!(snippet_generator.snippet_from_doc(&doc).to_html(), "");
+    }
 }
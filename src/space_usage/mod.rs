@@ -71,11 +71,14 @@ pub struct SegmentSpaceUsage {
     positions_idx: PerFieldSpaceUsage,
     fast_fields: PerFieldSpaceUsage,
     fieldnorms: PerFieldSpaceUsage,
+    offsets: PerFieldSpaceUsage,
 
     store: StoreSpaceUsage,
 
     deletes: ByteCount,
 
+    fast_field_updates: ByteCount,
+
     total: ByteCount,
 }
 
@@ -89,16 +92,20 @@ impl SegmentSpaceUsage {
         positions_idx: PerFieldSpaceUsage,
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
+        offsets: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
         deletes: ByteCount,
+        fast_field_updates: ByteCount,
     ) -> SegmentSpaceUsage {
         let total = termdict.total()
             + postings.total()
             + positions.total()
             + fast_fields.total()
             + fieldnorms.total()
+            + offsets.total()
             + store.total()
-            + deletes;
+            + deletes
+            + fast_field_updates;
         SegmentSpaceUsage {
             num_docs,
             termdict,
@@ -107,8 +114,10 @@ impl SegmentSpaceUsage {
             positions_idx,
             fast_fields,
             fieldnorms,
+            offsets,
             store,
             deletes,
+            fast_field_updates,
             total,
         }
     }
@@ -126,9 +135,11 @@ impl SegmentSpaceUsage {
             POSITIONSSKIP => PerField(self.positions_skip_idx().clone()),
             FASTFIELDS => PerField(self.fast_fields().clone()),
             FIELDNORMS => PerField(self.fieldnorms().clone()),
+            OFFSETS => PerField(self.offsets().clone()),
             TERMS => PerField(self.termdict().clone()),
             STORE => Store(self.store().clone()),
             DELETE => Basic(self.deletes()),
+            FASTFIELDUPDATES => Basic(self.fast_field_updates()),
         }
     }
 
@@ -167,6 +178,11 @@ impl SegmentSpaceUsage {
         &self.fieldnorms
     }
 
+    /// Space usage for token offsets
+    pub fn offsets(&self) -> &PerFieldSpaceUsage {
+        &self.offsets
+    }
+
     /// Space usage for stored documents
     pub fn store(&self) -> &StoreSpaceUsage {
         &self.store
@@ -177,6 +193,11 @@ impl SegmentSpaceUsage {
         self.deletes
     }
 
+    /// Space usage for fast field updates
+    pub fn fast_field_updates(&self) -> ByteCount {
+        self.fast_field_updates
+    }
+
     /// Total space usage in bytes for this segment.
     pub fn total(&self) -> ByteCount {
         self.total
@@ -338,10 +359,10 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => 1u64));
-            index_writer.add_document(doc!(name => 2u64));
-            index_writer.add_document(doc!(name => 10u64));
-            index_writer.add_document(doc!(name => 20u64));
+            index_writer.add_document(doc!(name => 1u64)).unwrap();
+            index_writer.add_document(doc!(name => 2u64)).unwrap();
+            index_writer.add_document(doc!(name => 10u64)).unwrap();
+            index_writer.add_document(doc!(name => 20u64)).unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -376,12 +397,18 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => "hi"));
-            index_writer.add_document(doc!(name => "this is a test"));
-            index_writer.add_document(
-                doc!(name => "some more documents with some word overlap with the other test"),
-            );
-            index_writer.add_document(doc!(name => "hello hi goodbye"));
+            index_writer.add_document(doc!(name => "hi")).unwrap();
+            index_writer
+                .add_document(doc!(name => "this is a test"))
+                .unwrap();
+            index_writer
+                .add_document(
+                    doc!(name => "some more documents with some word overlap with the other test"),
+                )
+                .unwrap();
+            index_writer
+                .add_document(doc!(name => "hello hi goodbye"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -416,12 +443,18 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => "hi"));
-            index_writer.add_document(doc!(name => "this is a test"));
-            index_writer.add_document(
-                doc!(name => "some more documents with some word overlap with the other test"),
-            );
-            index_writer.add_document(doc!(name => "hello hi goodbye"));
+            index_writer.add_document(doc!(name => "hi")).unwrap();
+            index_writer
+                .add_document(doc!(name => "this is a test"))
+                .unwrap();
+            index_writer
+                .add_document(
+                    doc!(name => "some more documents with some word overlap with the other test"),
+                )
+                .unwrap();
+            index_writer
+                .add_document(doc!(name => "hello hi goodbye"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -456,17 +489,21 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => 1u64));
-            index_writer.add_document(doc!(name => 2u64));
-            index_writer.add_document(doc!(name => 3u64));
-            index_writer.add_document(doc!(name => 4u64));
+            index_writer.add_document(doc!(name => 1u64)).unwrap();
+            index_writer.add_document(doc!(name => 2u64)).unwrap();
+            index_writer.add_document(doc!(name => 3u64)).unwrap();
+            index_writer.add_document(doc!(name => 4u64)).unwrap();
             index_writer.commit().unwrap();
         }
 
         {
             let mut index_writer2 = index.writer(50_000_000).unwrap();
-            index_writer2.delete_term(Term::from_field_u64(name, 2u64));
-            index_writer2.delete_term(Term::from_field_u64(name, 3u64));
+            index_writer2
+                .delete_term(Term::from_field_u64(name, 2u64))
+                .unwrap();
+            index_writer2
+                .delete_term(Term::from_field_u64(name, 3u64))
+                .unwrap();
 
             // ok, now we should have a deleted doc
             index_writer2.commit().unwrap();
@@ -0,0 +1,459 @@
+//! Dictionary based compression of the store blocks.
+//!
+//! Small blocks compress poorly because the compressor has
+//! no context to find repetitions in. The idea here is to
+//! train a dictionary on a sample of documents, and to
+//! prime the compressor with it for every block.
+//!
+//! The codec is a simple LZ77 variant.
+//! A block is encoded as `VInt(decompressed_len)`, followed by
+//! a sequence of operations:
+//! - a literal run: `VInt(len << 1)` followed by `len` bytes.
+//! - a match: `VInt((len - MIN_MATCH_LEN) << 1 | 1)` followed by `VInt(distance)`.
+//!
+//! The distance of a match is expressed with respect to the
+//! concatenation of the dictionary and the decompressed data,
+//! so that a match may point into the dictionary.
+use byteorder::{ByteOrder, LittleEndian};
+use common::{BinarySerializable, VInt};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+const MIN_MATCH_LEN: usize = 4;
+const HASH_LOG: usize = 13;
+
+/// Length of the shingles used to detect content
+/// that is shared across documents during training.
+const SHINGLE_LEN: usize = 8;
+
+/// Maximum size of the dictionary.
+pub const MAX_DICTIONARY_LEN: usize = 16_384;
+
+fn hash(bytes: &[u8]) -> usize {
+    (LittleEndian::read_u32(bytes).wrapping_mul(2_654_435_761u32) >> (32 - HASH_LOG)) as usize
+}
+
+fn shingle(bytes: &[u8]) -> u64 {
+    LittleEndian::read_u64(bytes)
+}
+
+/// Compression dictionary, shared by all of the blocks of a store.
+pub struct Dictionary {
+    data: Vec<u8>,
+    // `position + 1` of the last occurence of a given hash
+    // in the dictionary. 0 means no occurence.
+    hash_table: Vec<u32>,
+    // hash table used while compressing a block, reset
+    // to `hash_table` for every block.
+    block_hash_table: Vec<u32>,
+}
+
+impl Dictionary {
+    /// Creates a dictionary from its raw content.
+    pub fn from_bytes(data: Vec<u8>) -> Dictionary {
+        let mut hash_table = vec![0u32; 1 << HASH_LOG];
+        if data.len() >= MIN_MATCH_LEN {
+            for pos in 0..=data.len() - MIN_MATCH_LEN {
+                hash_table[hash(&data[pos..])] = (pos + 1) as u32;
+            }
+        }
+        let block_hash_table = hash_table.clone();
+        Dictionary {
+            data,
+            hash_table,
+            block_hash_table,
+        }
+    }
+
+    /// Trains a dictionary over a sample of documents.
+    ///
+    /// The dictionary is built by picking the chunks of the documents
+    /// (of at least `SHINGLE_LEN` bytes) that appear in
+    /// the largest number of documents.
+    pub fn train(samples: &[&[u8]]) -> Dictionary {
+        // number of distinct samples in which each shingle appear.
+        let mut shingle_doc_freqs: HashMap<u64, (u32, usize)> = HashMap::new();
+        for (sample_id, sample) in samples.iter().enumerate() {
+            for window in sample.windows(SHINGLE_LEN) {
+                let entry = shingle_doc_freqs
+                    .entry(shingle(window))
+                    .or_insert((0, usize::max_value()));
+                if entry.1 != sample_id {
+                    entry.0 += 1;
+                    entry.1 = sample_id;
+                }
+            }
+        }
+        let doc_freq = |shingle_doc_freqs: &HashMap<u64, (u32, usize)>, window: &[u8]| {
+            shingle_doc_freqs
+                .get(&shingle(window))
+                .map(|&(doc_freq, _)| doc_freq)
+                .unwrap_or(0)
+        };
+
+        // candidate chunks are the maximal runs of shingles
+        // shared by more than one document.
+        let mut candidates: Vec<(u64, &[u8])> = Vec::new();
+        for sample in samples {
+            if sample.len() < SHINGLE_LEN {
+                continue;
+            }
+            let mut start: Option<usize> = None;
+            let mut score = 0u64;
+            for pos in 0..=sample.len() - SHINGLE_LEN {
+                let freq = doc_freq(&shingle_doc_freqs, &sample[pos..pos + SHINGLE_LEN]);
+                if freq > 1 {
+                    if start.is_none() {
+                        start = Some(pos);
+                        score = 0;
+                    }
+                    score += u64::from(freq);
+                } else if let Some(start_pos) = start.take() {
+                    candidates.push((score, &sample[start_pos..pos + SHINGLE_LEN - 1]));
+                }
+            }
+            if let Some(start_pos) = start {
+                candidates.push((score, &sample[start_pos..]));
+            }
+        }
+        candidates.sort_by(|left, right| right.0.cmp(&left.0));
+
+        let mut chunks: Vec<&[u8]> = Vec::new();
+        let mut dictionary_len = 0;
+        for (_, chunk) in candidates {
+            if dictionary_len + chunk.len() > MAX_DICTIONARY_LEN {
+                continue;
+            }
+            // chunks are very likely to be repeated across documents.
+            // we skip them if their content is already in the dictionary.
+            let mut num_new_shingles = 0;
+            let mut num_shingles = 0;
+            for window in chunk.windows(SHINGLE_LEN) {
+                num_shingles += 1;
+                if let Some(entry) = shingle_doc_freqs.get_mut(&shingle(window)) {
+                    if entry.0 > 0 {
+                        num_new_shingles += 1;
+                        entry.0 = 0;
+                    }
+                }
+            }
+            if num_new_shingles * 2 < num_shingles {
+                continue;
+            }
+            dictionary_len += chunk.len();
+            chunks.push(chunk);
+        }
+        // The most useful chunks are placed at the end of the
+        // dictionary, where distances are the shortest.
+        let mut data = Vec::with_capacity(dictionary_len);
+        for chunk in chunks.iter().rev() {
+            data.extend_from_slice(chunk);
+        }
+        Dictionary::from_bytes(data)
+    }
+
+    /// Returns the raw content of the dictionary.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..]
+    }
+
+    /// Compresses a block using the dictionary.
+    pub fn compress(&mut self, uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+        compressed.clear();
+        VInt(uncompressed.len() as u64).serialize(compressed)?;
+        let mut buffer = Vec::with_capacity(self.data.len() + uncompressed.len());
+        buffer.extend_from_slice(&self.data);
+        buffer.extend_from_slice(uncompressed);
+        self.block_hash_table.copy_from_slice(&self.hash_table);
+        let hash_table = &mut self.block_hash_table;
+
+        let mut literal_start = self.data.len();
+        let mut pos = self.data.len();
+        while pos + MIN_MATCH_LEN <= buffer.len() {
+            let bucket = hash(&buffer[pos..]);
+            let candidate = hash_table[bucket] as usize;
+            hash_table[bucket] = (pos + 1) as u32;
+            if candidate > 0 {
+                let candidate_pos = candidate - 1;
+                if buffer[candidate_pos..candidate_pos + MIN_MATCH_LEN]
+                    == buffer[pos..pos + MIN_MATCH_LEN]
+                {
+                    let mut len = MIN_MATCH_LEN;
+                    while pos + len < buffer.len() && buffer[candidate_pos + len] == buffer[pos + len]
+                    {
+                        len += 1;
+                    }
+                    write_literals(&buffer[literal_start..pos], compressed)?;
+                    VInt(((len - MIN_MATCH_LEN) << 1 | 1) as u64).serialize(compressed)?;
+                    VInt((pos - candidate_pos) as u64).serialize(compressed)?;
+                    let match_end = pos + len;
+                    for match_pos in pos + 1..match_end {
+                        if match_pos + MIN_MATCH_LEN > buffer.len() {
+                            break;
+                        }
+                        hash_table[hash(&buffer[match_pos..])] = (match_pos + 1) as u32;
+                    }
+                    pos = match_end;
+                    literal_start = pos;
+                    continue;
+                }
+            }
+            pos += 1;
+        }
+        write_literals(&buffer[literal_start..], compressed)?;
+        Ok(())
+    }
+}
+
+/// Compresses a block, spending more time looking for long matches
+/// than `Dictionary::compress` does.
+///
+/// Hash chains are used to consider several candidates for each position,
+/// and a match is emitted only if the next position does not offer
+/// a longer one.
+/// The output can be decompressed with `decompress_with_dictionary`.
+pub fn compress_aggressive(
+    dictionary: &[u8],
+    uncompressed: &[u8],
+    compressed: &mut Vec<u8>,
+) -> io::Result<()> {
+    compressed.clear();
+    VInt(uncompressed.len() as u64).serialize(compressed)?;
+    let mut buffer = Vec::with_capacity(dictionary.len() + uncompressed.len());
+    buffer.extend_from_slice(dictionary);
+    buffer.extend_from_slice(uncompressed);
+    let mut chains = HashChains::new(buffer.len());
+    for pos in 0..dictionary.len() {
+        chains.insert(&buffer, pos);
+    }
+    let mut literal_start = dictionary.len();
+    let mut pos = dictionary.len();
+    while pos + MIN_MATCH_LEN <= buffer.len() {
+        let (len, distance) = chains.longest_match(&buffer, pos);
+        chains.insert(&buffer, pos);
+        if len >= MIN_MATCH_LEN {
+            let (next_len, _) = chains.longest_match(&buffer, pos + 1);
+            if next_len > len + 1 {
+                pos += 1;
+                continue;
+            }
+            write_literals(&buffer[literal_start..pos], compressed)?;
+            VInt(((len - MIN_MATCH_LEN) << 1 | 1) as u64).serialize(compressed)?;
+            VInt(distance as u64).serialize(compressed)?;
+            for match_pos in pos + 1..pos + len {
+                chains.insert(&buffer, match_pos);
+            }
+            pos += len;
+            literal_start = pos;
+            continue;
+        }
+        pos += 1;
+    }
+    write_literals(&buffer[literal_start..], compressed)?;
+    Ok(())
+}
+
+/// Maximum number of candidates considered by `compress_aggressive`.
+const MAX_CHAIN_LEN: usize = 64;
+
+struct HashChains {
+    // `position + 1` of the last occurence of a given hash.
+    heads: Vec<u32>,
+    // `position + 1` of the previous occurence of the hash at a given position.
+    previous: Vec<u32>,
+}
+
+impl HashChains {
+    fn new(len: usize) -> HashChains {
+        HashChains {
+            heads: vec![0u32; 1 << HASH_LOG],
+            previous: vec![0u32; len],
+        }
+    }
+
+    fn insert(&mut self, buffer: &[u8], pos: usize) {
+        if pos + MIN_MATCH_LEN > buffer.len() {
+            return;
+        }
+        let bucket = hash(&buffer[pos..]);
+        self.previous[pos] = self.heads[bucket];
+        self.heads[bucket] = (pos + 1) as u32;
+    }
+
+    /// Returns the length and the distance of the longest match
+    /// for the given position, amongst the positions inserted so far.
+    fn longest_match(&self, buffer: &[u8], pos: usize) -> (usize, usize) {
+        if pos + MIN_MATCH_LEN > buffer.len() {
+            return (0, 0);
+        }
+        let mut best = (0, 0);
+        let mut candidate = self.heads[hash(&buffer[pos..])] as usize;
+        for _ in 0..MAX_CHAIN_LEN {
+            if candidate == 0 {
+                break;
+            }
+            let candidate_pos = candidate - 1;
+            let mut len = 0;
+            while pos + len < buffer.len() && buffer[candidate_pos + len] == buffer[pos + len] {
+                len += 1;
+            }
+            if len > best.0 {
+                best = (len, pos - candidate_pos);
+            }
+            candidate = self.previous[candidate_pos] as usize;
+        }
+        best
+    }
+}
+
+fn write_literals(literals: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+    if !literals.is_empty() {
+        VInt((literals.len() << 1) as u64).serialize(compressed)?;
+        compressed.write_all(literals)?;
+    }
+    Ok(())
+}
+
+fn corrupted_block() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Corrupted store block")
+}
+
+/// Decompresses a block that was compressed using the given dictionary.
+///
+/// Returns an error if the operations of the block do not add up
+/// to its declared length.
+pub fn decompress_with_dictionary(
+    dictionary: &[u8],
+    mut compressed: &[u8],
+    decompressed: &mut Vec<u8>,
+) -> io::Result<()> {
+    decompressed.clear();
+    let decompressed_len = VInt::deserialize(&mut compressed)?.val() as usize;
+    while !compressed.is_empty() {
+        let op = VInt::deserialize(&mut compressed)?.val() as usize;
+        let remaining_len = decompressed_len - decompressed.len();
+        if op & 1 == 0 {
+            let len = op >> 1;
+            if len > compressed.len() || len > remaining_len {
+                return Err(corrupted_block());
+            }
+            decompressed.extend_from_slice(&compressed[..len]);
+            compressed = &compressed[len..];
+        } else {
+            let len = (op >> 1).saturating_add(MIN_MATCH_LEN);
+            let distance = VInt::deserialize(&mut compressed)?.val() as usize;
+            let pos = dictionary.len() + decompressed.len();
+            if distance == 0 || distance > pos || len > remaining_len {
+                return Err(corrupted_block());
+            }
+            let mut src = pos - distance;
+            // matches may overlap with the bytes they produce,
+            // so we need to copy byte per byte.
+            for _ in 0..len {
+                let byte = if src < dictionary.len() {
+                    dictionary[src]
+                } else {
+                    decompressed[src - dictionary.len()]
+                };
+                decompressed.push(byte);
+                src += 1;
+            }
+        }
+    }
+    if decompressed.len() != decompressed_len {
+        return Err(corrupted_block());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{compress_aggressive, decompress_with_dictionary, Dictionary};
+
+    fn sample_docs() -> Vec<Vec<u8>> {
+        (0..200)
+            .map(|i| {
+                format!(
+                    "{{\"title\": \"Product number {}\", \"category\": \"kitchen utensils\", \"price\": {}}}",
+                    i,
+                    i * 3
+                ).into_bytes()
+            }).collect()
+    }
+
+    #[test]
+    fn test_dictionary_compression_roundtrip() {
+        let docs = sample_docs();
+        let samples: Vec<&[u8]> = docs.iter().map(|doc| &doc[..]).collect();
+        let mut dictionary = Dictionary::train(&samples[..]);
+        assert!(!dictionary.as_bytes().is_empty());
+        let mut compressed = Vec::new();
+        let mut decompressed = Vec::new();
+        for doc in &docs {
+            dictionary.compress(doc, &mut compressed).unwrap();
+            assert!(compressed.len() * 3 < doc.len());
+            decompress_with_dictionary(dictionary.as_bytes(), &compressed, &mut decompressed)
+                .unwrap();
+            assert_eq!(&decompressed, doc);
+        }
+    }
+
+    #[test]
+    fn test_dictionary_compression_empty_dictionary() {
+        let mut dictionary = Dictionary::from_bytes(Vec::new());
+        let block = b"abcabcabcabcabcabc-hello-hello-hello".to_vec();
+        let mut compressed = Vec::new();
+        dictionary.compress(&block, &mut compressed).unwrap();
+        assert!(compressed.len() < block.len());
+        let mut decompressed = Vec::new();
+        decompress_with_dictionary(&[], &compressed, &mut decompressed).unwrap();
+        assert_eq!(decompressed, block);
+    }
+
+    #[test]
+    fn test_compress_aggressive() {
+        let docs = sample_docs();
+        let samples: Vec<&[u8]> = docs.iter().map(|doc| &doc[..]).collect();
+        let dictionary = Dictionary::train(&samples[..]);
+        let block: Vec<u8> = docs.iter().flat_map(|doc| doc.iter().cloned()).collect();
+        let mut compressed = Vec::new();
+        let mut decompressed = Vec::new();
+        for dictionary_bytes in &[dictionary.as_bytes(), &[]] {
+            compress_aggressive(dictionary_bytes, &block, &mut compressed).unwrap();
+            let mut fast_compressed = Vec::new();
+            Dictionary::from_bytes(dictionary_bytes.to_vec())
+                .compress(&block, &mut fast_compressed)
+                .unwrap();
+            assert!(compressed.len() <= fast_compressed.len());
+            decompress_with_dictionary(dictionary_bytes, &compressed, &mut decompressed).unwrap();
+            assert_eq!(decompressed, block);
+        }
+    }
+
+    #[test]
+    fn test_decompress_corrupted_match_len() {
+        let mut dictionary = Dictionary::from_bytes(b"hello world".to_vec());
+        let mut compressed = Vec::new();
+        dictionary
+            .compress(b"hello world", &mut compressed)
+            .unwrap();
+        let mut decompressed = Vec::new();
+        decompress_with_dictionary(dictionary.as_bytes(), &compressed, &mut decompressed).unwrap();
+        assert_eq!(&decompressed[..], b"hello world");
+        // the block is a single match: VInt(11), VInt((11 - 4) << 1 | 1), VInt(11).
+        assert_eq!(compressed, vec![11 | 128, 15 | 128, 11 | 128]);
+        // a match of 100 bytes, going past the declared length of the block.
+        let corrupted = vec![11 | 128, 65, 1 | 128, 11 | 128];
+        assert!(
+            decompress_with_dictionary(dictionary.as_bytes(), &corrupted, &mut decompressed)
+                .is_err()
+        );
+        // a block shorter than its declared length.
+        let truncated = vec![12 | 128, 15 | 128, 11 | 128];
+        assert!(
+            decompress_with_dictionary(dictionary.as_bytes(), &truncated, &mut decompressed)
+                .is_err()
+        );
+    }
+}
@@ -8,6 +8,14 @@ Internally, documents (or rather their stored fields) are serialized to a buffer
 When the buffer exceeds 16K, the buffer is compressed using `LZ4`
 and the resulting block is written to disk.
 
+Alternatively, with `StoreCompression::SharedDictionary`, a compression
+dictionary is trained over the first documents of the segment and
+shared by all of the (smaller) blocks.
+
+The values of text fields can also be kept uncompressed or compressed
+more aggressively, depending on their
+[`StoreCompressionHint`](../schema/enum.StoreCompressionHint.html).
+
 One can then request for a specific `DocId`.
 A skip list helps navigating to the right block,
 decompresses it entirely and returns the document within it.
@@ -33,10 +41,23 @@ and should rely on either
 
 !*/
 
+// A block may be split in several sections, depending on the
+// `StoreCompressionHint` of the fields. In that case,
+// the block length is flagged with `SECTIONS_FLAG`
+// and the sections follow one another.
+const SECTIONS_FLAG: u32 = 1u32 << 31;
+const NUM_SECTIONS: usize = 3;
+const DEFAULT_SECTION: usize = 0;
+const AGGRESSIVE_SECTION: usize = 1;
+const UNCOMPRESSED_SECTION: usize = 2;
+
+mod dictionary;
 mod reader;
 mod skiplist;
+mod store_compression;
 mod writer;
 pub use self::reader::StoreReader;
+pub use self::store_compression::StoreCompression;
 pub use self::writer::StoreWriter;
 
 #[cfg(feature = "lz4")]
@@ -57,10 +78,20 @@ pub mod tests {
     use schema::Document;
     use schema::FieldValue;
     use schema::Schema;
-    use schema::TextOptions;
+    use schema::{BytesOptions, IntOptions, StoreCompressionHint, TextOptions};
     use std::path::Path;
+    use futures::Future;
+    use Index;
 
     pub fn write_lorem_ipsum_store(writer: WritePtr, num_docs: usize) -> Schema {
+        write_lorem_ipsum_store_with_compression(writer, num_docs, StoreCompression::Block)
+    }
+
+    pub fn write_lorem_ipsum_store_with_compression(
+        writer: WritePtr,
+        num_docs: usize,
+        compression: StoreCompression,
+    ) -> Schema {
         let mut schema_builder = Schema::builder();
         let field_body = schema_builder.add_text_field("body", TextOptions::default().set_stored());
         let field_title =
@@ -77,7 +108,7 @@ pub mod tests {
              laborum.",
         );
         {
-            let mut store_writer = StoreWriter::new(writer);
+            let mut store_writer = StoreWriter::with_compression(writer, compression);
             for i in 0..num_docs {
                 let mut fields: Vec<FieldValue> = Vec::new();
                 {
@@ -121,6 +152,200 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_store_shared_dictionary() {
+        let mut directory = RAMDirectory::create();
+        let block_path = Path::new("store_block");
+        let dictionary_path = Path::new("store_dictionary");
+        write_lorem_ipsum_store(directory.open_write(block_path).unwrap(), 1_000);
+        let schema = write_lorem_ipsum_store_with_compression(
+            directory.open_write(dictionary_path).unwrap(),
+            1_000,
+            StoreCompression::SharedDictionary,
+        );
+        let field_title = schema.get_field("title").unwrap();
+        let block_source = directory.open_read(block_path).unwrap();
+        let dictionary_source = directory.open_read(dictionary_path).unwrap();
+        let block_store = StoreReader::from_source(block_source);
+        let store = StoreReader::from_source_with_compression(
+            dictionary_source,
+            StoreCompression::SharedDictionary,
+        );
+        assert_eq!(store.compression(), StoreCompression::SharedDictionary);
+        assert_eq!(store.max_doc(), 1_000);
+        for i in (0..1_000).rev() {
+            assert_eq!(
+                *store
+                    .get(i)
+                    .unwrap()
+                    .get_first(field_title)
+                    .unwrap()
+                    .text()
+                    .unwrap(),
+                format!("Doc {}", i)
+            );
+        }
+        assert!(store.space_usage().total() < block_store.space_usage().total());
+    }
+
+    #[test]
+    fn test_stack_shared_dictionary() {
+        let mut directory = RAMDirectory::create();
+        let path = Path::new("store");
+        let stacked_path = Path::new("stacked_store");
+        let schema = write_lorem_ipsum_store_with_compression(
+            directory.open_write(path).unwrap(),
+            10,
+            StoreCompression::SharedDictionary,
+        );
+        let field_title = schema.get_field("title").unwrap();
+        let store = StoreReader::from_source_with_compression(
+            directory.open_read(path).unwrap(),
+            StoreCompression::SharedDictionary,
+        );
+        {
+            let mut store_writer = StoreWriter::new(directory.open_write(stacked_path).unwrap());
+            store_writer.stack(&store).unwrap();
+            store_writer.stack(&store).unwrap();
+            store_writer.close().unwrap();
+        }
+        let stacked_store = StoreReader::from_source(directory.open_read(stacked_path).unwrap());
+        assert_eq!(stacked_store.max_doc(), 20);
+        for i in 0..20 {
+            assert_eq!(
+                *stacked_store
+                    .get(i)
+                    .unwrap()
+                    .get_first(field_title)
+                    .unwrap()
+                    .text()
+                    .unwrap(),
+                format!("Doc {}", i % 10)
+            );
+        }
+    }
+
+    #[test]
+    fn test_store_compression_hints() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TextOptions::default().set_stored());
+        let body = schema_builder.add_text_field(
+            "body",
+            TextOptions::default()
+                .set_stored()
+                .set_store_compression_hint(StoreCompressionHint::Aggressive),
+        );
+        let payload = schema_builder.add_text_field(
+            "payload",
+            TextOptions::default()
+                .set_stored()
+                .set_store_compression_hint(StoreCompressionHint::Uncompressed),
+        );
+        let thumbnail = schema_builder.add_bytes_field(
+            "thumbnail",
+            BytesOptions::default()
+                .set_stored()
+                .set_store_compression_hint(StoreCompressionHint::Uncompressed),
+        );
+        let timestamp = schema_builder.add_u64_field(
+            "timestamp",
+            IntOptions::default()
+                .set_stored()
+                .set_store_compression_hint(StoreCompressionHint::Aggressive),
+        );
+        let schema = schema_builder.build();
+        let make_doc = |i: usize| {
+            let mut doc = Document::default();
+            doc.add_text(title, &format!("Doc {}", i));
+            doc.add_text(body, &"lorem ipsum dolor sit amet ".repeat(i % 7));
+            doc.add_text(payload, &format!("payload-{}", i));
+            doc.add_bytes(thumbnail, vec![(i % 256) as u8; i % 13]);
+            doc.add_u64(timestamp, 1_500_000_000u64 + i as u64);
+            doc
+        };
+        let mut directory = RAMDirectory::create();
+        for &compression in &[StoreCompression::Block, StoreCompression::SharedDictionary] {
+            let path = Path::new("store");
+            let stacked_path = Path::new("stacked_store");
+            {
+                let mut store_writer =
+                    StoreWriter::with_compression(directory.open_write(path).unwrap(), compression)
+                        .with_schema_hints(&schema);
+                for i in 0..1_000 {
+                    store_writer.store(&make_doc(i)).unwrap();
+                }
+                store_writer.close().unwrap();
+            }
+            let store = StoreReader::from_source_with_compression(
+                directory.open_read(path).unwrap(),
+                compression,
+            );
+            {
+                let mut store_writer = StoreWriter::new(directory.open_write(stacked_path).unwrap());
+                store_writer.stack(&store).unwrap();
+                store_writer.close().unwrap();
+            }
+            let stacked_store = StoreReader::from_source(directory.open_read(stacked_path).unwrap());
+            for i in (0..1_000).rev() {
+                assert_eq!(store.get(i as u32).unwrap(), make_doc(i));
+                assert_eq!(stacked_store.get(i as u32).unwrap(), make_doc(i));
+            }
+            directory.delete(path).unwrap();
+            directory.delete(stacked_path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_index_shared_dictionary() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TextOptions::default().set_stored());
+        let mut index = Index::create_in_ram(schema_builder.build());
+        index.set_store_compression(StoreCompression::SharedDictionary);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..30 {
+                index_writer
+                    .add_document(doc!(title=>format!("Doc {}", i)))
+                    .unwrap();
+                if i % 10 == 9 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            let segment_ids = index.searchable_segment_ids().unwrap();
+            index_writer
+                .merge(&segment_ids)
+                .expect("Failed to initiate merge")
+                .wait()
+                .expect("Merging failed");
+            index_writer.wait_merging_threads().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let segment_metas = index.searchable_segment_metas().unwrap();
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(
+            segment_metas[0].store_compression(),
+            StoreCompression::SharedDictionary
+        );
+        let searcher = index.searcher();
+        let store_reader = searcher.segment_reader(0).get_store_reader();
+        assert_eq!(store_reader.compression(), StoreCompression::SharedDictionary);
+        let mut titles: Vec<String> = (0..30)
+            .map(|doc_id| {
+                store_reader
+                    .get(doc_id)
+                    .unwrap()
+                    .get_first(title)
+                    .unwrap()
+                    .text()
+                    .unwrap()
+                    .to_string()
+            }).collect();
+        titles.sort();
+        let mut expected: Vec<String> = (0..30).map(|i| format!("Doc {}", i)).collect();
+        expected.sort();
+        assert_eq!(titles, expected);
+    }
+
 }
 
 #[cfg(all(test, feature = "unstable"))]
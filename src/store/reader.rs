@@ -1,7 +1,10 @@
 use Result;
 
 use super::decompress;
+use super::dictionary::decompress_with_dictionary;
 use super::skiplist::SkipList;
+use super::StoreCompression;
+use super::SECTIONS_FLAG;
 use common::BinarySerializable;
 use common::VInt;
 use directory::ReadOnlySource;
@@ -19,22 +22,58 @@ pub struct StoreReader {
     offset_index_source: ReadOnlySource,
     current_block_offset: RefCell<usize>,
     current_block: RefCell<Vec<u8>>,
+    current_aggressive_block: RefCell<Vec<u8>>,
     max_doc: DocId,
+    dictionary_opt: Option<ReadOnlySource>,
+}
+
+/// Compressed block, as found on disk.
+struct CompressedBlock<'a> {
+    default_section: &'a [u8],
+    // aggressively compressed and uncompressed sections,
+    // if the block has any.
+    extra_sections_opt: Option<(&'a [u8], &'a [u8])>,
 }
 
 impl StoreReader {
     /// Opens a store reader
     pub fn from_source(data: ReadOnlySource) -> StoreReader {
-        let (data_source, offset_index_source, max_doc) = split_source(data);
+        StoreReader::from_source_with_compression(data, StoreCompression::default())
+    }
+
+    /// Opens a store reader, for a store that was written
+    /// using the given compression.
+    pub fn from_source_with_compression(
+        data: ReadOnlySource,
+        compression: StoreCompression,
+    ) -> StoreReader {
+        let (data_source, offset_index_source, dictionary_opt, max_doc) =
+            split_source(data, compression);
         StoreReader {
             data: data_source,
             offset_index_source,
             current_block_offset: RefCell::new(usize::max_value()),
             current_block: RefCell::new(Vec::new()),
+            current_aggressive_block: RefCell::new(Vec::new()),
             max_doc,
+            dictionary_opt,
         }
     }
 
+    /// Returns the compression used by this store.
+    pub fn compression(&self) -> StoreCompression {
+        if self.dictionary_opt.is_some() {
+            StoreCompression::SharedDictionary
+        } else {
+            StoreCompression::Block
+        }
+    }
+
+    /// Returns the number of documents in the store.
+    pub fn max_doc(&self) -> DocId {
+        self.max_doc
+    }
+
     pub(crate) fn block_index(&self) -> SkipList<u64> {
         SkipList::from(self.offset_index_source.as_slice())
     }
@@ -50,11 +89,28 @@ impl StoreReader {
         self.data.as_slice()
     }
 
-    fn compressed_block(&self, addr: usize) -> &[u8] {
+    fn compressed_block(&self, addr: usize) -> CompressedBlock {
+        fn read_section<'a>(buffer: &mut &'a [u8]) -> &'a [u8] {
+            let section_len = (u32::deserialize(buffer).expect("") & !SECTIONS_FLAG) as usize;
+            let section = &buffer[..section_len];
+            *buffer = &buffer[section_len..];
+            section
+        }
         let total_buffer = self.data.as_slice();
         let mut buffer = &total_buffer[addr..];
-        let block_len = u32::deserialize(&mut buffer).expect("") as usize;
-        &buffer[..block_len]
+        let has_sections = u32::deserialize(&mut &buffer[..]).expect("") & SECTIONS_FLAG != 0;
+        let default_section = read_section(&mut buffer);
+        let extra_sections_opt = if has_sections {
+            let aggressive_section = read_section(&mut buffer);
+            let uncompressed_section = read_section(&mut buffer);
+            Some((aggressive_section, uncompressed_section))
+        } else {
+            None
+        };
+        CompressedBlock {
+            default_section,
+            extra_sections_opt,
+        }
     }
 
     fn read_block(&self, block_offset: usize) -> io::Result<()> {
@@ -62,7 +118,27 @@ impl StoreReader {
             let mut current_block_mut = self.current_block.borrow_mut();
             current_block_mut.clear();
             let compressed_block = self.compressed_block(block_offset);
-            decompress(compressed_block, &mut current_block_mut)?;
+            let dictionary: &[u8] = self
+                .dictionary_opt
+                .as_ref()
+                .map(|dictionary| dictionary.as_slice())
+                .unwrap_or(&[]);
+            if self.dictionary_opt.is_some() {
+                decompress_with_dictionary(
+                    dictionary,
+                    compressed_block.default_section,
+                    &mut current_block_mut,
+                )?;
+            } else {
+                decompress(compressed_block.default_section, &mut current_block_mut)?;
+            }
+            if let Some((aggressive_section, _)) = compressed_block.extra_sections_opt {
+                decompress_with_dictionary(
+                    dictionary,
+                    aggressive_section,
+                    &mut self.current_aggressive_block.borrow_mut(),
+                )?;
+            }
             *self.current_block_offset.borrow_mut() = block_offset;
         }
         Ok(())
@@ -75,28 +151,68 @@ impl StoreReader {
     ///
     /// It should not be called to score documents
     /// for instance.
+    ///
+    /// The values of the fields that are not compressed with the
+    /// default `StoreCompressionHint` are appended
+    /// after the other values of the document.
     pub fn get(&self, doc_id: DocId) -> Result<Document> {
         let (first_doc_id, block_offset) = self.block_offset(doc_id);
         self.read_block(block_offset as usize)?;
-        let current_block_mut = self.current_block.borrow_mut();
-        let mut cursor = &current_block_mut[..];
-        for _ in first_doc_id..doc_id {
-            let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
-            cursor = &cursor[doc_length..];
+        let doc_ord = doc_id - first_doc_id;
+        let current_block = self.current_block.borrow();
+        let mut doc = read_doc(&current_block[..], doc_ord)?;
+        if let Some((_, uncompressed_section)) = self
+            .compressed_block(block_offset as usize)
+            .extra_sections_opt
+        {
+            let current_aggressive_block = self.current_aggressive_block.borrow();
+            for section in &[&current_aggressive_block[..], uncompressed_section] {
+                for field_value in read_doc(section, doc_ord)?.field_values() {
+                    doc.add(field_value.clone());
+                }
+            }
         }
-        let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
-        cursor = &cursor[..doc_length];
-        Ok(Document::deserialize(&mut cursor)?)
+        Ok(doc)
     }
 
     /// Summarize total space usage of this store reader.
+    ///
+    /// The shared dictionary, if any, is accounted for in the offsets.
     pub fn space_usage(&self) -> StoreSpaceUsage {
-        StoreSpaceUsage::new(self.data.len(), self.offset_index_source.len())
+        let dictionary_len = self
+            .dictionary_opt
+            .as_ref()
+            .map(|dictionary| dictionary.len())
+            .unwrap_or(0);
+        StoreSpaceUsage::new(
+            self.data.len(),
+            self.offset_index_source.len() + dictionary_len,
+        )
     }
 }
 
+/// Reads the `doc_ord`-th document of a decompressed block.
+fn read_doc(block: &[u8], doc_ord: DocId) -> io::Result<Document> {
+    let mut cursor = block;
+    for _ in 0..doc_ord {
+        let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
+        cursor = &cursor[doc_length..];
+    }
+    let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
+    cursor = &cursor[..doc_length];
+    Document::deserialize(&mut cursor)
+}
+
 #[cfg_attr(feature = "cargo-clippy", allow(clippy::needless_pass_by_value))]
-fn split_source(data: ReadOnlySource) -> (ReadOnlySource, ReadOnlySource, DocId) {
+fn split_source(
+    data: ReadOnlySource,
+    compression: StoreCompression,
+) -> (
+    ReadOnlySource,
+    ReadOnlySource,
+    Option<ReadOnlySource>,
+    DocId,
+) {
     let data_len = data.len();
     let footer_offset = data_len - size_of::<u64>() - size_of::<u32>();
     let serialized_offset: ReadOnlySource = data.slice(footer_offset, data_len);
@@ -104,9 +220,24 @@ fn split_source(data: ReadOnlySource) -> (ReadOnlySource, ReadOnlySource, DocId)
     let offset = u64::deserialize(&mut serialized_offset_buf).unwrap();
     let offset = offset as usize;
     let max_doc = u32::deserialize(&mut serialized_offset_buf).unwrap();
-    (
-        data.slice(0, offset),
-        data.slice(offset, footer_offset),
-        max_doc,
-    )
+    match compression {
+        StoreCompression::Block => (
+            data.slice(0, offset),
+            data.slice(offset, footer_offset),
+            None,
+            max_doc,
+        ),
+        StoreCompression::SharedDictionary => {
+            let dictionary_footer_offset = footer_offset - size_of::<u64>();
+            let serialized_dictionary_offset = data.slice(dictionary_footer_offset, footer_offset);
+            let dictionary_offset =
+                u64::deserialize(&mut serialized_dictionary_offset.as_slice()).unwrap() as usize;
+            (
+                data.slice(0, offset),
+                data.slice(offset, dictionary_offset),
+                Some(data.slice(dictionary_offset, dictionary_footer_offset)),
+                max_doc,
+            )
+        }
+    }
 }
@@ -0,0 +1,30 @@
+/// `StoreCompression` describes how the blocks of the doc store
+/// are compressed.
+///
+/// The compression is chosen when a segment is created
+/// (see [`Index::set_store_compression`](../struct.Index.html#method.set_store_compression))
+/// and recorded in the segment metadata.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StoreCompression {
+    /// Each block of 16K is compressed independently, using
+    /// `snappy` or `LZ4` depending on the `lz4-compression` feature.
+    ///
+    /// This is the default.
+    #[serde(rename = "block")]
+    Block,
+    /// A compression dictionary is trained on the first documents
+    /// of the segment, and is shared by all of the blocks of the store.
+    ///
+    /// Blocks are smaller (4K), which makes fetching a document cheaper,
+    /// and the dictionary makes up for the loss of context.
+    /// This is useful for indexes of many small documents, that
+    /// are typically very redundant with each other.
+    #[serde(rename = "shared_dictionary")]
+    SharedDictionary,
+}
+
+impl Default for StoreCompression {
+    fn default() -> StoreCompression {
+        StoreCompression::Block
+    }
+}
@@ -1,15 +1,28 @@
 use super::compress;
+use super::dictionary::{compress_aggressive, Dictionary};
 use super::skiplist::SkipListBuilder;
+use super::StoreCompression;
 use super::StoreReader;
+use super::{
+    AGGRESSIVE_SECTION, DEFAULT_SECTION, NUM_SECTIONS, SECTIONS_FLAG, UNCOMPRESSED_SECTION,
+};
 use common::CountingWriter;
 use common::{BinarySerializable, VInt};
-use directory::WritePtr;
-use schema::Document;
+use directory::{SeekableWrite, WritePtr};
+use schema::{Document, FieldType, FieldValue, Schema, StoreCompressionHint};
 use std::io::{self, Write};
+use std::mem;
 use DocId;
 
 const BLOCK_SIZE: usize = 16_384;
 
+/// Block size used when the store is compressed with a shared dictionary.
+const DICTIONARY_BLOCK_SIZE: usize = 4_096;
+
+/// Amount of serialized documents that are buffered
+/// to train the shared dictionary.
+const DICTIONARY_TRAINING_LEN: usize = 131_072;
+
 /// Write tantivy's [`Store`](./index.html)
 ///
 /// Contrary to the other components of `tantivy`,
@@ -23,7 +36,15 @@ pub struct StoreWriter {
     offset_index_writer: SkipListBuilder<u64>,
     writer: CountingWriter<WritePtr>,
     intermediary_buffer: Vec<u8>,
-    current_block: Vec<u8>,
+    doc_buffers: [Vec<u8>; NUM_SECTIONS],
+    current_blocks: [Vec<u8>; NUM_SECTIONS],
+    compression: StoreCompression,
+    dictionary: Option<Dictionary>,
+    // Serialized documents, held back until the dictionary is trained.
+    training_buffer: Vec<u8>,
+    // Section of each field, indexed by field id.
+    // Empty if all of the fields use the default section.
+    field_sections: Vec<usize>,
 }
 
 impl StoreWriter {
@@ -32,12 +53,75 @@ impl StoreWriter {
     /// The store writer will writes blocks on disc as
     /// document are added.
     pub fn new(writer: WritePtr) -> StoreWriter {
+        StoreWriter::with_compression(writer, StoreCompression::default())
+    }
+
+    /// Create a store writer using the given compression.
+    ///
+    /// With `StoreCompression::SharedDictionary`, the first documents
+    /// are buffered in memory until enough data is available to train the
+    /// dictionary.
+    pub fn with_compression(writer: WritePtr, compression: StoreCompression) -> StoreWriter {
         StoreWriter {
             doc: 0,
             offset_index_writer: SkipListBuilder::new(4),
             writer: CountingWriter::wrap(writer),
             intermediary_buffer: Vec::new(),
-            current_block: Vec::new(),
+            doc_buffers: [Vec::new(), Vec::new(), Vec::new()],
+            current_blocks: [Vec::new(), Vec::new(), Vec::new()],
+            compression,
+            dictionary: None,
+            training_buffer: Vec::new(),
+            field_sections: Vec::new(),
+        }
+    }
+
+    /// Applies the [`StoreCompressionHint`](../schema/enum.StoreCompressionHint.html)
+    /// of the fields of the schema.
+    ///
+    /// The values of the fields with a non-default hint are
+    /// stored in separate sections of each block.
+    pub fn with_schema_hints(mut self, schema: &Schema) -> StoreWriter {
+        let field_sections: Vec<usize> = schema
+            .fields()
+            .iter()
+            .map(|field_entry| {
+                let store_compression_hint = match *field_entry.field_type() {
+                    FieldType::Str(ref text_options) => text_options.store_compression_hint(),
+                    FieldType::U64(ref int_options)
+                    | FieldType::I64(ref int_options)
+                    | FieldType::F64(ref int_options) => int_options.store_compression_hint(),
+                    FieldType::Bytes(ref bytes_options) => bytes_options.store_compression_hint(),
+                    _ => StoreCompressionHint::Default,
+                };
+                match store_compression_hint {
+                    StoreCompressionHint::Default => DEFAULT_SECTION,
+                    StoreCompressionHint::Aggressive => AGGRESSIVE_SECTION,
+                    StoreCompressionHint::Uncompressed => UNCOMPRESSED_SECTION,
+                }
+            })
+            .collect();
+        if field_sections
+            .iter()
+            .any(|&section| section != DEFAULT_SECTION)
+        {
+            self.field_sections = field_sections;
+        }
+        self
+    }
+
+    fn has_sections(&self) -> bool {
+        !self.field_sections.is_empty()
+    }
+
+    fn is_training(&self) -> bool {
+        self.compression == StoreCompression::SharedDictionary && self.dictionary.is_none()
+    }
+
+    fn block_size(&self) -> usize {
+        match self.compression {
+            StoreCompression::Block => BLOCK_SIZE,
+            StoreCompression::SharedDictionary => DICTIONARY_BLOCK_SIZE,
         }
     }
 
@@ -47,25 +131,117 @@ impl StoreWriter {
     /// this method has been called.
     ///
     pub fn store(&mut self, stored_document: &Document) -> io::Result<()> {
-        self.intermediary_buffer.clear();
-        stored_document.serialize(&mut self.intermediary_buffer)?;
-        let doc_num_bytes = self.intermediary_buffer.len();
-        VInt(doc_num_bytes as u64).serialize(&mut self.current_block)?;
-        self.current_block
-            .write_all(&self.intermediary_buffer[..])?;
+        self.serialize_sections(stored_document)?;
+        if self.is_training() {
+            for doc_buffer in &self.doc_buffers {
+                VInt(doc_buffer.len() as u64).serialize(&mut self.training_buffer)?;
+                self.training_buffer.write_all(&doc_buffer[..])?;
+            }
+            if self.training_buffer.len() > DICTIONARY_TRAINING_LEN {
+                self.train_dictionary()?;
+            }
+            return Ok(());
+        }
+        let doc_buffers = mem::replace(&mut self.doc_buffers, [Vec::new(), Vec::new(), Vec::new()]);
+        let res = self.append_doc([
+            &doc_buffers[0][..],
+            &doc_buffers[1][..],
+            &doc_buffers[2][..],
+        ]);
+        self.doc_buffers = doc_buffers;
+        res
+    }
+
+    /// Serializes the part of the document belonging to
+    /// each section in `doc_buffers`.
+    fn serialize_sections(&mut self, stored_document: &Document) -> io::Result<()> {
+        for doc_buffer in &mut self.doc_buffers {
+            doc_buffer.clear();
+        }
+        if !self.has_sections() {
+            return stored_document.serialize(&mut self.doc_buffers[DEFAULT_SECTION]);
+        }
+        let mut section_field_values: [Vec<FieldValue>; NUM_SECTIONS] =
+            [Vec::new(), Vec::new(), Vec::new()];
+        for field_value in stored_document.field_values() {
+            let section = self
+                .field_sections
+                .get(field_value.field().0 as usize)
+                .cloned()
+                .unwrap_or(DEFAULT_SECTION);
+            section_field_values[section].push(field_value.clone());
+        }
+        for (doc_buffer, field_values) in self
+            .doc_buffers
+            .iter_mut()
+            .zip(section_field_values.iter_mut())
+        {
+            Document::from(mem::replace(field_values, Vec::new())).serialize(doc_buffer)?;
+        }
+        Ok(())
+    }
+
+    fn append_doc(&mut self, doc_bytes: [&[u8]; NUM_SECTIONS]) -> io::Result<()> {
+        let num_sections = if self.has_sections() { NUM_SECTIONS } else { 1 };
+        for section in 0..num_sections {
+            let current_block = &mut self.current_blocks[section];
+            VInt(doc_bytes[section].len() as u64).serialize(current_block)?;
+            current_block.write_all(doc_bytes[section])?;
+        }
         self.doc += 1;
-        if self.current_block.len() > BLOCK_SIZE {
+        let block_len: usize = self.current_blocks.iter().map(|block| block.len()).sum();
+        if block_len > self.block_size() {
             self.write_and_compress_block()?;
         }
         Ok(())
     }
 
+    /// Trains the dictionary over the buffered documents,
+    /// and appends them to the store.
+    ///
+    /// Only the default section is used to train the dictionary.
+    fn train_dictionary(&mut self) -> io::Result<()> {
+        let training_buffer = mem::replace(&mut self.training_buffer, Vec::new());
+        let mut docs: Vec<[&[u8]; NUM_SECTIONS]> = Vec::new();
+        let mut cursor = &training_buffer[..];
+        while !cursor.is_empty() {
+            let mut doc: [&[u8]; NUM_SECTIONS] = [&[], &[], &[]];
+            for section_bytes in &mut doc {
+                let num_bytes = VInt::deserialize(&mut cursor)?.val() as usize;
+                *section_bytes = &cursor[..num_bytes];
+                cursor = &cursor[num_bytes..];
+            }
+            docs.push(doc);
+        }
+        let samples: Vec<&[u8]> = docs.iter().map(|doc| doc[DEFAULT_SECTION]).collect();
+        self.dictionary = Some(Dictionary::train(&samples[..]));
+        for doc in docs {
+            self.append_doc(doc)?;
+        }
+        Ok(())
+    }
+
     /// Stacks a store reader on top of the documents written so far.
     /// This method is an optimization compared to iterating over the documents
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
+    ///
+    /// Blocks compressed with a shared dictionary cannot be copied as is,
+    /// so if either store uses `StoreCompression::SharedDictionary`,
+    /// documents are simply added one by one.
     pub fn stack(&mut self, store_reader: &StoreReader) -> io::Result<()> {
-        if !self.current_block.is_empty() {
+        if self.compression != StoreCompression::Block
+            || store_reader.compression() != StoreCompression::Block
+        {
+            for doc_id in 0..store_reader.max_doc() {
+                let doc = store_reader
+                    .get(doc_id)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+                self.store(&doc)?;
+            }
+            return Ok(());
+        }
+        if !self.current_blocks[DEFAULT_SECTION].is_empty() {
             self.write_and_compress_block()?;
             self.offset_index_writer
                 .insert(u64::from(self.doc), &(self.writer.written_bytes() as u64))?;
@@ -88,12 +264,39 @@ impl StoreWriter {
 
     fn write_and_compress_block(&mut self) -> io::Result<()> {
         self.intermediary_buffer.clear();
-        compress(&self.current_block[..], &mut self.intermediary_buffer)?;
-        (self.intermediary_buffer.len() as u32).serialize(&mut self.writer)?;
-        self.writer.write_all(&self.intermediary_buffer)?;
+        let default_block = &self.current_blocks[DEFAULT_SECTION][..];
+        if let Some(ref mut dictionary) = self.dictionary {
+            dictionary.compress(default_block, &mut self.intermediary_buffer)?;
+        } else {
+            compress(default_block, &mut self.intermediary_buffer)?;
+        }
+        if self.has_sections() {
+            (self.intermediary_buffer.len() as u32 | SECTIONS_FLAG).serialize(&mut self.writer)?;
+            self.writer.write_all(&self.intermediary_buffer)?;
+            let dictionary_bytes = self
+                .dictionary
+                .as_ref()
+                .map(|dictionary| dictionary.as_bytes())
+                .unwrap_or(&[]);
+            compress_aggressive(
+                dictionary_bytes,
+                &self.current_blocks[AGGRESSIVE_SECTION][..],
+                &mut self.intermediary_buffer,
+            )?;
+            (self.intermediary_buffer.len() as u32).serialize(&mut self.writer)?;
+            self.writer.write_all(&self.intermediary_buffer)?;
+            let uncompressed_block = &self.current_blocks[UNCOMPRESSED_SECTION];
+            (uncompressed_block.len() as u32).serialize(&mut self.writer)?;
+            self.writer.write_all(uncompressed_block)?;
+        } else {
+            (self.intermediary_buffer.len() as u32).serialize(&mut self.writer)?;
+            self.writer.write_all(&self.intermediary_buffer)?;
+        }
         self.offset_index_writer
             .insert(u64::from(self.doc), &(self.writer.written_bytes() as u64))?;
-        self.current_block.clear();
+        for current_block in &mut self.current_blocks {
+            current_block.clear();
+        }
         Ok(())
     }
 
@@ -101,14 +304,26 @@ impl StoreWriter {
     ///
     /// Compress the last unfinished block if any,
     /// and serializes the skip list index on disc.
+    ///
+    /// With a shared dictionary, the dictionary is written
+    /// right after the skip list index.
     pub fn close(mut self) -> io::Result<()> {
-        if !self.current_block.is_empty() {
+        if self.is_training() {
+            self.train_dictionary()?;
+        }
+        if !self.current_blocks[DEFAULT_SECTION].is_empty() {
             self.write_and_compress_block()?;
         }
         let header_offset: u64 = self.writer.written_bytes() as u64;
         self.offset_index_writer.write(&mut self.writer)?;
+        if let Some(ref dictionary) = self.dictionary {
+            let dictionary_offset: u64 = self.writer.written_bytes() as u64;
+            self.writer.write_all(dictionary.as_bytes())?;
+            dictionary_offset.serialize(&mut self.writer)?;
+        }
         header_offset.serialize(&mut self.writer)?;
         self.doc.serialize(&mut self.writer)?;
-        self.writer.flush()
+        let (mut write, _) = self.writer.finish()?;
+        write.terminate()
     }
 }
@@ -0,0 +1,349 @@
+use common::{BinarySerializable, CountingWriter, FixedSize, VInt};
+use directory::ReadOnlySource;
+use fst::Automaton;
+use std::cmp::Ordering;
+use std::io::{self, Write};
+use std::ops::Bound;
+use termdict::TermOrdinal;
+
+/// Number of terms in a block.
+const BLOCK_LEN: u64 = 64;
+
+fn common_prefix_len(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right)
+        .take_while(|&(left_byte, right_byte)| left_byte == right_byte)
+        .count()
+}
+
+/// Builder of the block term index.
+///
+/// The terms are written in blocks of `BLOCK_LEN` terms. Each term is
+/// front-coded, as the length of the prefix it shares with the previous term,
+/// the length of its suffix, and the suffix itself. The first term of a
+/// block is written in full, so that a block can be decoded on its own.
+///
+/// The blocks are followed by the offset of each block, as `u64`,
+/// and by the number of terms, as a `u64`.
+pub(crate) struct BlockTermIndexBuilder<W: Write> {
+    write: CountingWriter<W>,
+    block_offsets: Vec<u64>,
+    last_key: Vec<u8>,
+    num_terms: u64,
+}
+
+impl<W: Write> BlockTermIndexBuilder<W> {
+    pub fn new(write: W) -> BlockTermIndexBuilder<W> {
+        BlockTermIndexBuilder {
+            write: CountingWriter::wrap(write),
+            block_offsets: Vec::new(),
+            last_key: Vec::with_capacity(100),
+            num_terms: 0u64,
+        }
+    }
+
+    /// Inserts a key, with the next `TermOrdinal`.
+    ///
+    /// Keys have to be inserted in a strictly increasing order.
+    pub fn insert(&mut self, key: &[u8]) -> io::Result<()> {
+        if self.num_terms > 0 && key <= &self.last_key[..] {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Keys must be inserted in a strictly increasing order.",
+            ));
+        }
+        let prefix_len = if self.num_terms % BLOCK_LEN == 0 {
+            self.block_offsets.push(self.write.written_bytes() as u64);
+            0
+        } else {
+            common_prefix_len(&self.last_key, key)
+        };
+        VInt(prefix_len as u64).serialize(&mut self.write)?;
+        VInt((key.len() - prefix_len) as u64).serialize(&mut self.write)?;
+        self.write.write_all(&key[prefix_len..])?;
+        self.last_key.truncate(prefix_len);
+        self.last_key.extend_from_slice(&key[prefix_len..]);
+        self.num_terms += 1;
+        Ok(())
+    }
+
+    /// Writes the block offsets, and returns the underlying `Write` object.
+    pub fn finish(mut self) -> io::Result<W> {
+        for block_offset in &self.block_offsets {
+            block_offset.serialize(&mut self.write)?;
+        }
+        self.num_terms.serialize(&mut self.write)?;
+        let (write, _) = self.write.finish()?;
+        Ok(write)
+    }
+}
+
+/// Associates the terms of a block term dictionary to their `TermOrdinal`.
+///
+/// See `BlockTermIndexBuilder` for the format.
+pub(crate) struct BlockTermIndex {
+    blocks: ReadOnlySource,
+    block_offsets: ReadOnlySource,
+    num_terms: u64,
+}
+
+impl BlockTermIndex {
+    pub fn open(source: ReadOnlySource) -> BlockTermIndex {
+        let source_len = source.len();
+        let (body, footer) = source.split(source_len - u64::SIZE_IN_BYTES);
+        let num_terms = u64::deserialize(&mut footer.as_slice()).expect("Index corrupted");
+        let num_blocks = ((num_terms + BLOCK_LEN - 1) / BLOCK_LEN) as usize;
+        let body_len = body.len();
+        let (blocks, block_offsets) = body.split(body_len - num_blocks * u64::SIZE_IN_BYTES);
+        BlockTermIndex {
+            blocks,
+            block_offsets,
+            num_terms,
+        }
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.block_offsets.len() / u64::SIZE_IN_BYTES
+    }
+
+    /// Returns the data of the terms, starting at the first term of `block`.
+    fn block_data(&self, block: usize) -> &[u8] {
+        if block >= self.num_blocks() {
+            return &[];
+        }
+        let mut block_offset_bytes = &self.block_offsets.as_slice()[block * u64::SIZE_IN_BYTES..];
+        let block_offset =
+            u64::deserialize(&mut block_offset_bytes).expect("Index corrupted") as usize;
+        &self.blocks.as_slice()[block_offset..]
+    }
+
+    /// Returns a cursor positioned before the first term of `block`.
+    fn cursor(&self, block: usize) -> TermCursor {
+        TermCursor::new(self.block_data(block), block as u64 * BLOCK_LEN)
+    }
+
+    fn block_first_key(&self, block: usize) -> &[u8] {
+        // the first term of a block is written in full.
+        let mut data = self.block_data(block);
+        VInt::deserialize_u64(&mut data).expect("Index corrupted");
+        let key_len = VInt::deserialize_u64(&mut data).expect("Index corrupted") as usize;
+        &data[..key_len]
+    }
+
+    /// Returns the last block whose first term is lower or equal to `key`.
+    fn find_block(&self, key: &[u8]) -> Option<usize> {
+        let mut start = 0;
+        let mut end = self.num_blocks();
+        while start < end {
+            let mid = (start + end) / 2;
+            if self.block_first_key(mid) <= key {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        if start == 0 {
+            None
+        } else {
+            Some(start - 1)
+        }
+    }
+
+    pub fn term_ord(&self, key: &[u8]) -> Option<TermOrdinal> {
+        let block = self.find_block(key)?;
+        let mut cursor = self.cursor(block);
+        while cursor.advance().is_some() {
+            match cursor.key().cmp(key) {
+                Ordering::Less => {}
+                Ordering::Equal => return Some(cursor.term_ord()),
+                Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+
+    pub fn ord_to_term(&self, ord: TermOrdinal, bytes: &mut Vec<u8>) -> bool {
+        bytes.clear();
+        if ord >= self.num_terms {
+            return false;
+        }
+        let mut cursor = self.cursor((ord / BLOCK_LEN) as usize);
+        for _ in 0..=ord % BLOCK_LEN {
+            cursor.advance();
+        }
+        bytes.extend_from_slice(cursor.key());
+        true
+    }
+}
+
+/// Decodes the terms of a block term index, one after the other.
+struct TermCursor<'a> {
+    data: &'a [u8],
+    next_term_ord: TermOrdinal,
+    key: Vec<u8>,
+}
+
+impl<'a> TermCursor<'a> {
+    fn new(data: &'a [u8], next_term_ord: TermOrdinal) -> TermCursor<'a> {
+        TermCursor {
+            data,
+            next_term_ord,
+            key: Vec::with_capacity(100),
+        }
+    }
+
+    /// Decodes the next term.
+    ///
+    /// Returns the length of the prefix it shares with the previous term,
+    /// or `None` if all of the terms have been decoded.
+    fn advance(&mut self) -> Option<usize> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let prefix_len = VInt::deserialize_u64(&mut self.data).expect("Index corrupted") as usize;
+        let suffix_len = VInt::deserialize_u64(&mut self.data).expect("Index corrupted") as usize;
+        self.key.truncate(prefix_len);
+        self.key.extend_from_slice(&self.data[..suffix_len]);
+        self.data = &self.data[suffix_len..];
+        self.next_term_ord += 1;
+        Some(prefix_len)
+    }
+
+    fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    fn term_ord(&self) -> TermOrdinal {
+        self.next_term_ord - 1
+    }
+
+    fn terminate(&mut self) {
+        self.data = &[];
+    }
+}
+
+/// Builder of a `BlockTermStreamer`, mirroring the `fst` stream builders.
+pub(crate) struct BlockTermStreamerBuilder<'a, A: Automaton> {
+    term_index: &'a BlockTermIndex,
+    automaton: A,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+}
+
+impl<'a, A: Automaton> BlockTermStreamerBuilder<'a, A> {
+    pub fn new(term_index: &'a BlockTermIndex, automaton: A) -> Self {
+        BlockTermStreamerBuilder {
+            term_index,
+            automaton,
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+        }
+    }
+
+    pub fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.lower = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.lower = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn le<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.upper = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.upper = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn into_stream(self) -> BlockTermStreamer<'a, A> {
+        let start_block = match self.lower {
+            Bound::Included(ref key) | Bound::Excluded(ref key) => {
+                self.term_index.find_block(key).unwrap_or(0)
+            }
+            Bound::Unbounded => 0,
+        };
+        let start_state = self.automaton.start();
+        BlockTermStreamer {
+            cursor: self.term_index.cursor(start_block),
+            automaton: self.automaton,
+            lower: self.lower,
+            upper: self.upper,
+            states: vec![start_state],
+        }
+    }
+}
+
+/// Streams the terms of a block term index within a range,
+/// and matching an automaton.
+///
+/// The automaton is run incrementally: `states[i]` is the state
+/// reached after the first `i` bytes of the current key, so that the
+/// prefix a term shares with the previous one is not run again.
+/// Once the automaton cannot match a prefix anymore, the terms
+/// sharing this prefix are skipped without running the automaton.
+pub(crate) struct BlockTermStreamer<'a, A: Automaton> {
+    cursor: TermCursor<'a>,
+    automaton: A,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    states: Vec<A::State>,
+}
+
+impl<'a, A: Automaton> BlockTermStreamer<'a, A> {
+    pub fn advance(&mut self) -> bool {
+        while let Some(prefix_len) = self.cursor.advance() {
+            self.states.truncate(prefix_len + 1);
+            let is_above_lower = match self.lower {
+                Bound::Included(ref lower) => self.cursor.key() >= &lower[..],
+                Bound::Excluded(ref lower) => self.cursor.key() > &lower[..],
+                Bound::Unbounded => true,
+            };
+            if !is_above_lower {
+                continue;
+            }
+            let is_below_upper = match self.upper {
+                Bound::Included(ref upper) => self.cursor.key() <= &upper[..],
+                Bound::Excluded(ref upper) => self.cursor.key() < &upper[..],
+                Bound::Unbounded => true,
+            };
+            if !is_below_upper {
+                self.cursor.terminate();
+                return false;
+            }
+            if self.run_automaton() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Runs the automaton over the bytes of the current key that were not
+    /// shared with the previous key, and returns true iff the key matches.
+    fn run_automaton(&mut self) -> bool {
+        let key = &self.cursor.key;
+        while self.states.len() <= key.len() {
+            let next_state = {
+                let state = &self.states[self.states.len() - 1];
+                if !self.automaton.can_match(state) {
+                    return false;
+                }
+                self.automaton.accept(state, key[self.states.len() - 1])
+            };
+            self.states.push(next_state);
+        }
+        self.automaton.is_match(&self.states[key.len()])
+    }
+
+    pub fn key(&self) -> &[u8] {
+        self.cursor.key()
+    }
+
+    pub fn term_ord(&self) -> TermOrdinal {
+        self.cursor.term_ord()
+    }
+}
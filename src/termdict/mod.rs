@@ -3,8 +3,10 @@ The term dictionary main role is to associate the sorted [`Term`s](../struct.Ter
 a [`TermInfo`](../postings/struct.TermInfo.html) struct that contains some meta-information
 about the term.
 
-Internally, the term dictionary relies on the `fst` crate to store
+Internally, the term dictionary relies by default on the `fst` crate to store
 a sorted mapping that associate each term to its rank in the lexicographical order.
+Alternatively, the terms can be stored in front-coded blocks,
+see [`TermDictionaryCodec`](./enum.TermDictionaryCodec.html).
 For instance, in a dictionary containing the sorted terms "abba", "bjork", "blur" and "donovan",
 the `TermOrdinal` are respectively `0`, `1`, `2`, and `3`.
 
@@ -20,20 +22,24 @@ A second datastructure makes it possible to access a [`TermInfo`](../postings/st
 /// Position of the term in the sorted list of terms.
 pub type TermOrdinal = u64;
 
+mod block_term_index;
 mod merger;
 mod streamer;
 mod term_info_store;
 mod termdict;
+mod termdict_codec;
 
 pub use self::merger::TermMerger;
 pub use self::streamer::{TermStreamer, TermStreamerBuilder};
 pub use self::termdict::{TermDictionary, TermDictionaryBuilder};
+pub use self::termdict_codec::TermDictionaryCodec;
 
 #[cfg(test)]
 mod tests {
-    use super::{TermDictionary, TermDictionaryBuilder, TermStreamer};
+    use super::{TermDictionary, TermDictionaryBuilder, TermDictionaryCodec, TermStreamer};
     use core::Index;
     use directory::{Directory, RAMDirectory, ReadOnlySource};
+    use fst::Automaton;
     use postings::TermInfo;
     use schema::{Document, FieldType, Schema, TEXT};
     use std::path::PathBuf;
@@ -138,7 +144,7 @@ mod tests {
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b d f");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().unwrap();
             }
@@ -146,7 +152,7 @@ mod tests {
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c d f");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().unwrap();
             }
@@ -154,7 +160,7 @@ mod tests {
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "e f");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().unwrap();
             }
@@ -430,4 +436,152 @@ mod tests {
         assert_eq!("Spain".as_bytes(), range.key());
         assert!(!range.advance());
     }
+
+    fn build_term_dictionary(keys: &[String], codec: TermDictionaryCodec) -> TermDictionary {
+        let field_type = FieldType::Str(TEXT);
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_codec(vec![], &field_type, codec).unwrap();
+        for (i, key) in keys.iter().enumerate() {
+            term_dictionary_builder
+                .insert(key.as_bytes(), &make_term_info(i as u64))
+                .unwrap();
+        }
+        let buffer = term_dictionary_builder.finish().unwrap();
+        TermDictionary::from_source_with_codec(&ReadOnlySource::from(buffer), codec)
+    }
+
+    fn collect_keys<A: Automaton>(mut streamer: TermStreamer<A>) -> Vec<Vec<u8>> {
+        let mut streamed_keys = vec![];
+        while streamer.advance() {
+            assert_eq!(streamer.value(), &make_term_info(streamer.term_ord()));
+            streamed_keys.push(streamer.key().to_vec());
+        }
+        streamed_keys
+    }
+
+    #[test]
+    fn test_block_term_dictionary() {
+        use levenshtein_automata::LevenshteinAutomatonBuilder;
+
+        let mut keys: Vec<String> = (0u32..3_000u32)
+            .map(|i| format!("doc{:0>6}", i * 7))
+            .collect();
+        keys.insert(0, String::new());
+        keys.push("dog".to_string());
+        keys.push("dot".to_string());
+        let fst_dict = build_term_dictionary(&keys, TermDictionaryCodec::Fst);
+        let block_dict = build_term_dictionary(&keys, TermDictionaryCodec::Block);
+        assert_eq!(block_dict.num_terms(), keys.len());
+        let mut bytes = vec![];
+        for (ord, key) in keys.iter().enumerate() {
+            assert_eq!(block_dict.term_ord(key), Some(ord as u64));
+            assert_eq!(block_dict.get(key), Some(make_term_info(ord as u64)));
+            assert!(block_dict.ord_to_term(ord as u64, &mut bytes));
+            assert_eq!(&bytes[..], key.as_bytes());
+        }
+        assert!(!block_dict.ord_to_term(keys.len() as u64, &mut bytes));
+        for missing_key in &["a", "doc", "doc000001", "doc020995", "doc999999", "e"] {
+            assert_eq!(block_dict.term_ord(missing_key), None);
+        }
+        assert_eq!(collect_keys(block_dict.stream()).len(), keys.len());
+        for &(lower, upper) in &[
+            ("doc000070", "doc000700"),
+            ("doc000071", "doc000701"),
+            ("", "doc"),
+            ("doc020993", "e"),
+        ] {
+            assert_eq!(
+                collect_keys(block_dict.range().ge(lower).lt(upper).into_stream()),
+                collect_keys(fst_dict.range().ge(lower).lt(upper).into_stream())
+            );
+            assert_eq!(
+                collect_keys(block_dict.range().gt(lower).le(upper).into_stream()),
+                collect_keys(fst_dict.range().gt(lower).le(upper).into_stream())
+            );
+        }
+        let lev_automaton_builder = LevenshteinAutomatonBuilder::new(1, true);
+        for query in &["doc000700", "dop", "doc02099"] {
+            let block_matches = collect_keys(
+                block_dict
+                    .search(lev_automaton_builder.build_dfa(query))
+                    .into_stream(),
+            );
+            let fst_matches = collect_keys(
+                fst_dict
+                    .search(lev_automaton_builder.build_dfa(query))
+                    .into_stream(),
+            );
+            assert!(!block_matches.is_empty());
+            assert_eq!(block_matches, fst_matches);
+        }
+    }
+
+    #[test]
+    fn test_block_term_dictionary_empty() {
+        let block_dict = build_term_dictionary(&[], TermDictionaryCodec::Block);
+        assert_eq!(block_dict.num_terms(), 0);
+        assert_eq!(block_dict.term_ord("a"), None);
+        assert!(!block_dict.stream().advance());
+        assert!(!block_dict.range().ge("a").into_stream().advance());
+    }
+
+    #[test]
+    fn test_block_term_dictionary_index() {
+        use collector::Count;
+        use futures::Future;
+        use query::{FuzzyTermQuery, TermQuery};
+        use schema::{IndexRecordOption, Term};
+
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let build_index = |term_dictionary_codec: TermDictionaryCodec| {
+            let mut index = Index::create_in_ram(schema.clone());
+            index.set_term_dictionary_codec(term_dictionary_codec);
+            {
+                let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+                for i in 0..200 {
+                    index_writer
+                        .add_document(doc!(text_field => format!("term{} shared", i)))
+                        .unwrap();
+                }
+                index_writer.commit().unwrap();
+                index_writer
+                    .add_document(doc!(text_field => "shared tern1"))
+                    .unwrap();
+                index_writer.commit().unwrap();
+                let segment_ids = index.searchable_segment_ids().unwrap();
+                index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+                index_writer.wait_merging_threads().unwrap();
+            }
+            index.load_searchers().unwrap();
+            index
+        };
+        let index = build_index(TermDictionaryCodec::Block);
+        let fst_index = build_index(TermDictionaryCodec::Fst);
+        let searcher = index.searcher();
+        let fst_searcher = fst_index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(
+            index.searchable_segment_metas().unwrap()[0].term_dictionary_codec(),
+            TermDictionaryCodec::Block
+        );
+        let term_query = |text: &str| {
+            TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            )
+        };
+        assert_eq!(searcher.search(&term_query("shared"), &Count).unwrap(), 201);
+        assert_eq!(searcher.search(&term_query("term150"), &Count).unwrap(), 1);
+        assert_eq!(searcher.search(&term_query("term200"), &Count).unwrap(), 0);
+        let fuzzy_query = FuzzyTermQuery::new(Term::from_field_text(text_field, "term1"), 1, true);
+        let fst_fuzzy_count = fst_searcher.search(&fuzzy_query, &Count).unwrap();
+        // term0..term19, term21..term91 and tern1
+        assert_eq!(fst_fuzzy_count, 29);
+        assert_eq!(
+            searcher.search(&fuzzy_query, &Count).unwrap(),
+            fst_fuzzy_count
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use super::block_term_index::{BlockTermStreamer, BlockTermStreamerBuilder};
 use super::TermDictionary;
 use fst::automaton::AlwaysMatch;
 use fst::map::{Stream, StreamBuilder};
@@ -6,6 +7,16 @@ use fst::{IntoStreamer, Streamer};
 use postings::TermInfo;
 use termdict::TermOrdinal;
 
+pub(crate) enum TermStreamerBuilderImpl<'a, A: Automaton> {
+    Fst(StreamBuilder<'a, A>),
+    Block(BlockTermStreamerBuilder<'a, A>),
+}
+
+enum TermStreamImpl<'a, A: Automaton> {
+    Fst(Stream<'a, A>),
+    Block(BlockTermStreamer<'a, A>),
+}
+
 /// `TermStreamerBuilder` is a helper object used to define
 /// a range of terms that should be streamed.
 pub struct TermStreamerBuilder<'a, A = AlwaysMatch>
@@ -13,14 +24,17 @@ where
     A: Automaton,
 {
     fst_map: &'a TermDictionary,
-    stream_builder: StreamBuilder<'a, A>,
+    stream_builder: TermStreamerBuilderImpl<'a, A>,
 }
 
 impl<'a, A> TermStreamerBuilder<'a, A>
 where
     A: Automaton,
 {
-    pub(crate) fn new(fst_map: &'a TermDictionary, stream_builder: StreamBuilder<'a, A>) -> Self {
+    pub(crate) fn new(
+        fst_map: &'a TermDictionary,
+        stream_builder: TermStreamerBuilderImpl<'a, A>,
+    ) -> Self {
         TermStreamerBuilder {
             fst_map,
             stream_builder,
@@ -29,34 +43,66 @@ where
 
     /// Limit the range to terms greater or equal to the bound
     pub fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.ge(bound);
+        self.stream_builder = match self.stream_builder {
+            TermStreamerBuilderImpl::Fst(builder) => {
+                TermStreamerBuilderImpl::Fst(builder.ge(bound))
+            }
+            TermStreamerBuilderImpl::Block(builder) => {
+                TermStreamerBuilderImpl::Block(builder.ge(bound))
+            }
+        };
         self
     }
 
     /// Limit the range to terms strictly greater than the bound
     pub fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.gt(bound);
+        self.stream_builder = match self.stream_builder {
+            TermStreamerBuilderImpl::Fst(builder) => {
+                TermStreamerBuilderImpl::Fst(builder.gt(bound))
+            }
+            TermStreamerBuilderImpl::Block(builder) => {
+                TermStreamerBuilderImpl::Block(builder.gt(bound))
+            }
+        };
         self
     }
 
     /// Limit the range to terms lesser or equal to the bound
     pub fn le<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.le(bound);
+        self.stream_builder = match self.stream_builder {
+            TermStreamerBuilderImpl::Fst(builder) => {
+                TermStreamerBuilderImpl::Fst(builder.le(bound))
+            }
+            TermStreamerBuilderImpl::Block(builder) => {
+                TermStreamerBuilderImpl::Block(builder.le(bound))
+            }
+        };
         self
     }
 
     /// Limit the range to terms lesser or equal to the bound
     pub fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.lt(bound);
+        self.stream_builder = match self.stream_builder {
+            TermStreamerBuilderImpl::Fst(builder) => {
+                TermStreamerBuilderImpl::Fst(builder.lt(bound))
+            }
+            TermStreamerBuilderImpl::Block(builder) => {
+                TermStreamerBuilderImpl::Block(builder.lt(bound))
+            }
+        };
         self
     }
 
     /// Creates the stream corresponding to the range
     /// of terms defined using the `TermStreamerBuilder`.
     pub fn into_stream(self) -> TermStreamer<'a, A> {
+        let stream = match self.stream_builder {
+            TermStreamerBuilderImpl::Fst(builder) => TermStreamImpl::Fst(builder.into_stream()),
+            TermStreamerBuilderImpl::Block(builder) => TermStreamImpl::Block(builder.into_stream()),
+        };
         TermStreamer {
             fst_map: self.fst_map,
-            stream: self.stream_builder.into_stream(),
+            stream,
             term_ord: 0u64,
             current_key: Vec::with_capacity(100),
             current_value: TermInfo::default(),
@@ -71,7 +117,7 @@ where
     A: Automaton,
 {
     fst_map: &'a TermDictionary,
-    stream: Stream<'a, A>,
+    stream: TermStreamImpl<'a, A>,
     term_ord: TermOrdinal,
     current_key: Vec<u8>,
     current_value: TermInfo,
@@ -85,15 +131,29 @@ where
     /// Before the first call to `.advance()`, the stream
     /// is an unitialized state.
     pub fn advance(&mut self) -> bool {
-        if let Some((term, term_ord)) = self.stream.next() {
-            self.current_key.clear();
-            self.current_key.extend_from_slice(term);
-            self.term_ord = term_ord;
-            self.current_value = self.fst_map.term_info_from_ord(term_ord);
-            true
-        } else {
-            false
-        }
+        let term_ord = match self.stream {
+            TermStreamImpl::Fst(ref mut stream) => {
+                if let Some((term, term_ord)) = stream.next() {
+                    self.current_key.clear();
+                    self.current_key.extend_from_slice(term);
+                    term_ord
+                } else {
+                    return false;
+                }
+            }
+            TermStreamImpl::Block(ref mut stream) => {
+                if stream.advance() {
+                    self.current_key.clear();
+                    self.current_key.extend_from_slice(stream.key());
+                    stream.term_ord()
+                } else {
+                    return false;
+                }
+            }
+        };
+        self.term_ord = term_ord;
+        self.current_value = self.fst_map.term_info_from_ord(term_ord);
+        true
     }
 
     /// Returns the `TermOrdinal` of the given term.
@@ -1,9 +1,12 @@
+use super::block_term_index::{BlockTermIndex, BlockTermIndexBuilder, BlockTermStreamerBuilder};
+use super::streamer::TermStreamerBuilderImpl;
 use super::term_info_store::{TermInfoStore, TermInfoStoreWriter};
-use super::{TermStreamer, TermStreamerBuilder};
+use super::{TermDictionaryCodec, TermStreamer, TermStreamerBuilder};
 use common::BinarySerializable;
 use common::CountingWriter;
 use directory::ReadOnlySource;
 use fst;
+use fst::automaton::AlwaysMatch;
 use fst::raw::Fst;
 use fst::Automaton;
 use postings::TermInfo;
@@ -15,11 +18,16 @@ fn convert_fst_error(e: fst::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, e)
 }
 
+enum TermIndexBuilder<W: Write> {
+    Fst(fst::MapBuilder<W>),
+    Block(BlockTermIndexBuilder<W>),
+}
+
 /// Builder for the new term dictionary.
 ///
 /// Inserting must be done in the order of the `keys`.
-pub struct TermDictionaryBuilder<W> {
-    fst_builder: fst::MapBuilder<W>,
+pub struct TermDictionaryBuilder<W: Write> {
+    term_index_builder: TermIndexBuilder<W>,
     term_info_store_writer: TermInfoStoreWriter,
     term_ord: u64,
 }
@@ -28,11 +36,25 @@ impl<W> TermDictionaryBuilder<W>
 where
     W: Write,
 {
-    /// Creates a new `TermDictionaryBuilder`
-    pub fn create(w: W, _field_type: &FieldType) -> io::Result<Self> {
-        let fst_builder = fst::MapBuilder::new(w).map_err(convert_fst_error)?;
+    /// Creates a new `TermDictionaryBuilder`, using the default codec.
+    pub fn create(w: W, field_type: &FieldType) -> io::Result<Self> {
+        TermDictionaryBuilder::create_with_codec(w, field_type, TermDictionaryCodec::default())
+    }
+
+    /// Creates a new `TermDictionaryBuilder`, using the given codec.
+    pub fn create_with_codec(
+        w: W,
+        _field_type: &FieldType,
+        codec: TermDictionaryCodec,
+    ) -> io::Result<Self> {
+        let term_index_builder = match codec {
+            TermDictionaryCodec::Fst => {
+                TermIndexBuilder::Fst(fst::MapBuilder::new(w).map_err(convert_fst_error)?)
+            }
+            TermDictionaryCodec::Block => TermIndexBuilder::Block(BlockTermIndexBuilder::new(w)),
+        };
         Ok(TermDictionaryBuilder {
-            fst_builder,
+            term_index_builder,
             term_info_store_writer: TermInfoStoreWriter::new(),
             term_ord: 0,
         })
@@ -56,9 +78,12 @@ where
     ///
     /// Prefer using `.insert(key, value)`
     pub(crate) fn insert_key(&mut self, key: &[u8]) -> io::Result<()> {
-        self.fst_builder
-            .insert(key, self.term_ord)
-            .map_err(convert_fst_error)?;
+        match self.term_index_builder {
+            TermIndexBuilder::Fst(ref mut fst_builder) => fst_builder
+                .insert(key, self.term_ord)
+                .map_err(convert_fst_error)?,
+            TermIndexBuilder::Block(ref mut block_builder) => block_builder.insert(key)?,
+        }
         self.term_ord += 1;
         Ok(())
     }
@@ -74,7 +99,12 @@ where
     /// Finalize writing the builder, and returns the underlying
     /// `Write` object.
     pub fn finish(mut self) -> io::Result<W> {
-        let mut file = self.fst_builder.into_inner().map_err(convert_fst_error)?;
+        let mut file = match self.term_index_builder {
+            TermIndexBuilder::Fst(fst_builder) => {
+                fst_builder.into_inner().map_err(convert_fst_error)?
+            }
+            TermIndexBuilder::Block(block_builder) => block_builder.finish()?,
+        };
         {
             let mut counting_writer = CountingWriter::wrap(&mut file);
             self.term_info_store_writer
@@ -100,31 +130,49 @@ fn open_fst_index(source: ReadOnlySource) -> fst::Map {
     fst::Map::from(fst)
 }
 
+/// Associates the terms to their respective `TermOrdinal`,
+/// depending on the `TermDictionaryCodec`.
+enum TermIndex {
+    Fst(fst::Map),
+    Block(BlockTermIndex),
+}
+
 /// The term dictionary contains all of the terms in
 /// `tantivy index` in a sorted manner.
 ///
-/// The `Fst` crate is used to associate terms to their
-/// respective `TermOrdinal`. The `TermInfoStore` then makes it
+/// Depending on its [`TermDictionaryCodec`](./enum.TermDictionaryCodec.html),
+/// the `Fst` crate or front-coded blocks of terms are used to associate
+/// terms to their respective `TermOrdinal`. The `TermInfoStore` then makes it
 /// possible to fetch the associated `TermInfo`.
 pub struct TermDictionary {
-    fst_index: fst::Map,
+    term_index: TermIndex,
     term_info_store: TermInfoStore,
 }
 
 impl TermDictionary {
-    /// Opens a `TermDictionary` given a data source.
+    /// Opens a `TermDictionary` given a data source,
+    /// written with the default codec.
     pub fn from_source(source: &ReadOnlySource) -> Self {
+        TermDictionary::from_source_with_codec(source, TermDictionaryCodec::default())
+    }
+
+    /// Opens a `TermDictionary` given a data source,
+    /// written with the given codec.
+    pub fn from_source_with_codec(source: &ReadOnlySource, codec: TermDictionaryCodec) -> Self {
         let total_len = source.len();
         let length_offset = total_len - 8;
         let mut split_len_buffer: &[u8] = &source.as_slice()[length_offset..];
         let footer_size = u64::deserialize(&mut split_len_buffer)
             .expect("Deserializing 8 bytes should always work") as usize;
         let split_len = length_offset - footer_size;
-        let fst_source = source.slice(0, split_len);
+        let term_index_source = source.slice(0, split_len);
         let values_source = source.slice(split_len, length_offset);
-        let fst_index = open_fst_index(fst_source);
+        let term_index = match codec {
+            TermDictionaryCodec::Fst => TermIndex::Fst(open_fst_index(term_index_source)),
+            TermDictionaryCodec::Block => TermIndex::Block(BlockTermIndex::open(term_index_source)),
+        };
         TermDictionary {
-            fst_index,
+            term_index,
             term_info_store: TermInfoStore::open(&values_source),
         }
     }
@@ -148,7 +196,10 @@ impl TermDictionary {
 
     /// Returns the ordinal associated to a given term.
     pub fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> Option<TermOrdinal> {
-        self.fst_index.get(key)
+        match self.term_index {
+            TermIndex::Fst(ref fst_index) => fst_index.get(key),
+            TermIndex::Block(ref block_index) => block_index.term_ord(key.as_ref()),
+        }
     }
 
     /// Returns the term associated to a given term ordinal.
@@ -162,7 +213,10 @@ impl TermDictionary {
     /// the buffer may be modified.
     pub fn ord_to_term(&self, mut ord: TermOrdinal, bytes: &mut Vec<u8>) -> bool {
         bytes.clear();
-        let fst = self.fst_index.as_fst();
+        let fst = match self.term_index {
+            TermIndex::Fst(ref fst_index) => fst_index.as_fst(),
+            TermIndex::Block(ref block_index) => return block_index.ord_to_term(ord, bytes),
+        };
         let mut node = fst.root();
         while ord != 0 || !node.is_final() {
             if let Some(transition) = node
@@ -195,7 +249,7 @@ impl TermDictionary {
     /// Returns a range builder, to stream all of the terms
     /// within an interval.
     pub fn range(&self) -> TermStreamerBuilder {
-        TermStreamerBuilder::new(self, self.fst_index.range())
+        self.search(AlwaysMatch)
     }
 
     /// A stream of all the sorted terms. [See also `.stream_field()`](#method.stream_field)
@@ -206,7 +260,14 @@ impl TermDictionary {
     /// Returns a search builder, to stream all of the terms
     /// within the Automaton
     pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A> {
-        let stream_builder = self.fst_index.search(automaton);
+        let stream_builder = match self.term_index {
+            TermIndex::Fst(ref fst_index) => {
+                TermStreamerBuilderImpl::Fst(fst_index.search(automaton))
+            }
+            TermIndex::Block(ref block_index) => TermStreamerBuilderImpl::Block(
+                BlockTermStreamerBuilder::new(block_index, automaton),
+            ),
+        };
         TermStreamerBuilder::<A>::new(self, stream_builder)
     }
 }
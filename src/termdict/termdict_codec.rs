@@ -0,0 +1,38 @@
+/// `TermDictionaryCodec` describes how the sorted terms of a
+/// field are associated to their `TermOrdinal`.
+///
+/// The codec is chosen when a segment is created
+/// (see [`Index::set_term_dictionary_codec`](../struct.Index.html#method.set_term_dictionary_codec))
+/// and recorded in the segment metadata, so that segments
+/// written with different codecs can coexist in the same index.
+///
+/// In both cases, the `TermInfo`s are stored in the same
+/// bitpacked `TermInfoStore`, in the order of the terms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TermDictionaryCodec {
+    /// The terms are stored in a finite state transducer.
+    ///
+    /// This is the most compact codec, and it intersects
+    /// efficiently with the automata of regex and fuzzy queries.
+    ///
+    /// This is the default codec.
+    #[serde(rename = "fst")]
+    Fst,
+    /// The terms are stored in blocks of 64 front-coded terms:
+    /// each term only records the suffix it does not share with the
+    /// previous one.
+    ///
+    /// A point lookup is a binary search over the first term of the
+    /// blocks, followed by the scan of a single block, which
+    /// is faster than walking the FST on large dictionaries.
+    /// The dictionary is larger than the FST, and automata are
+    /// intersected by scanning the terms.
+    #[serde(rename = "block")]
+    Block,
+}
+
+impl Default for TermDictionaryCodec {
+    fn default() -> TermDictionaryCodec {
+        TermDictionaryCodec::Fst
+    }
+}
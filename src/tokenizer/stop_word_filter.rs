@@ -1,3 +1,8 @@
+//! The positions of the remaining tokens are not modified,
+//! so that the removed stop words leave gaps in the positions.
+//! A phrase query requires these gaps to be there: `"state of the art"`
+//! matches `state in an art`, but not `state art`.
+//!
 //! # Example
 //! ```
 //! extern crate tantivy;
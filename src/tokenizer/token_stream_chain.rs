@@ -69,6 +69,27 @@ where
     }
 }
 
+/// Chains the token streams of the values `texts` of a field,
+/// the offsets of each stream being shifted by the length
+/// of the values preceding it.
+pub(crate) fn chain_token_streams<'a>(
+    texts: &[&str],
+    mut token_streams: Vec<Box<TokenStream + 'a>>,
+) -> Box<TokenStream + 'a> {
+    assert_eq!(texts.len(), token_streams.len());
+    if token_streams.len() == 1 {
+        token_streams.pop().unwrap()
+    } else {
+        let mut offsets = vec![];
+        let mut total_offset = 0;
+        for text in texts {
+            offsets.push(total_offset);
+            total_offset += text.len();
+        }
+        Box::new(TokenStreamChain::new(offsets, token_streams))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::{SimpleTokenizer, TokenStream, Tokenizer};
@@ -0,0 +1,165 @@
+use tokenizer::{Token, TokenStream};
+
+/// A text along with the tokens it was split into by the caller.
+///
+/// Indexing a `PreTokenizedString` in a text field bypasses the
+/// tokenizer of the field: its tokens are indexed as they are, at
+/// their position, so that an external pipeline (e.g. a NLP library
+/// tagging named entities) controls the tokenization entirely.
+///
+/// The offsets of the tokens are byte offsets within `text`, and their
+/// positions should be increasing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreTokenizedString {
+    /// Original text, which is the value stored in the doc store.
+    pub text: String,
+    /// Tokens of the text.
+    pub tokens: Vec<Token>,
+}
+
+/// `TokenStream` emitting the tokens of a `PreTokenizedString`.
+pub struct PreTokenizedStream {
+    tokenized_string: PreTokenizedString,
+    num_advances: usize,
+}
+
+impl From<PreTokenizedString> for PreTokenizedStream {
+    fn from(tokenized_string: PreTokenizedString) -> PreTokenizedStream {
+        PreTokenizedStream {
+            tokenized_string,
+            num_advances: 0,
+        }
+    }
+}
+
+impl TokenStream for PreTokenizedStream {
+    fn advance(&mut self) -> bool {
+        if self.num_advances < self.tokenized_string.tokens.len() {
+            self.num_advances += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        assert!(
+            self.num_advances > 0,
+            "You called .token(), before calling .advance()"
+        );
+        &self.tokenized_string.tokens[self.num_advances - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        assert!(
+            self.num_advances > 0,
+            "You called .token_mut(), before calling .advance()"
+        );
+        &mut self.tokenized_string.tokens[self.num_advances - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{PreTokenizedStream, PreTokenizedString};
+    use collector::Count;
+    use query::{PhraseQuery, TermQuery};
+    use schema::{IndexRecordOption, Schema, Term, STORED, TEXT};
+    use serde_json;
+    use tokenizer::{Token, TokenStream};
+    use Index;
+
+    fn token(offset_from: usize, offset_to: usize, position: usize, text: &str) -> Token {
+        Token {
+            offset_from,
+            offset_to,
+            position,
+            text: text.to_string(),
+            position_length: 1,
+        }
+    }
+
+    fn new_york_city() -> PreTokenizedString {
+        PreTokenizedString {
+            text: "New York City".to_string(),
+            tokens: vec![
+                token(0, 8, 0, "new_york"),
+                token(0, 3, 0, "new"),
+                token(4, 8, 1, "york"),
+                token(9, 13, 2, "city"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_pre_tokenized_stream() {
+        let tokenized_string = new_york_city();
+        let mut token_stream = PreTokenizedStream::from(tokenized_string.clone());
+        for expected_token in &tokenized_string.tokens {
+            assert!(token_stream.advance());
+            assert_eq!(token_stream.token(), expected_token);
+        }
+        assert!(!token_stream.advance());
+    }
+
+    #[test]
+    fn test_pre_tokenized_string_json() {
+        let tokenized_string = PreTokenizedString {
+            text: "Hello".to_string(),
+            tokens: vec![token(0, 5, 0, "hello")],
+        };
+        let json = serde_json::to_string(&tokenized_string).unwrap();
+        assert_eq!(
+            json,
+            r#"{"text":"Hello","tokens":[{"offset_from":0,"offset_to":5,"position":0,"text":"hello","position_length":1}]}"#
+        );
+        let tokenized_string_deser: PreTokenizedString = serde_json::from_str(&json).unwrap();
+        assert_eq!(tokenized_string_deser, tokenized_string);
+    }
+
+    #[test]
+    fn test_index_pre_tokenized_text() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            let mut doc = doc!(text_field => "in the");
+            doc.add_pre_tokenized_text(text_field, new_york_city());
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |text: &str| {
+            let term = Term::from_field_text(text_field, text);
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("new_york"), 1);
+        assert_eq!(count("york"), 1);
+        assert_eq!(count("the"), 1);
+        // the pre-tokenized text is not processed by the tokenizer of the field.
+        assert_eq!(count("New"), 0);
+        let phrase_count = |words: &[&str]| {
+            let terms = words
+                .iter()
+                .map(|word| Term::from_field_text(text_field, word))
+                .collect();
+            searcher.search(&PhraseQuery::new(terms), &Count).unwrap()
+        };
+        assert_eq!(phrase_count(&["new", "york", "city"]), 1);
+        assert_eq!(phrase_count(&["new_york", "city"]), 0);
+        // the values of the field are not contiguous.
+        assert_eq!(phrase_count(&["the", "new"]), 0);
+        let stored_doc = searcher
+            .segment_reader(0)
+            .get_store_reader()
+            .get(0)
+            .unwrap();
+        let stored_values = stored_doc.get_all(text_field);
+        assert_eq!(stored_values[0].text(), Some("in the"));
+        assert_eq!(stored_values[1].tokenized_text(), Some(&new_york_city()));
+    }
+}